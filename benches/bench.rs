@@ -3,83 +3,34 @@
 // where N is the number of threads you want to use (N = 1 for single-thread).
 
 use ark_bls12_381::{Bls12_381, Fr as BlsFr};
+use ark_bw6_761::{Fr as BW6Fr, BW6_761};
 use ark_crypto_primitives::snark::SNARK;
 use ark_ff::{PrimeField, UniformRand};
-use darklake_groth16::Groth16;
 use ark_mnt4_298::{Fr as MNT4Fr, MNT4_298};
 use ark_mnt4_753::{Fr as MNT4BigFr, MNT4_753};
 use ark_mnt6_298::{Fr as MNT6Fr, MNT6_298};
 use ark_mnt6_753::{Fr as MNT6BigFr, MNT6_753};
-use ark_relations::{
-    lc,
-    r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError},
-};
+use darklake_groth16::{testing::SyntheticCircuit, Groth16};
 
 const NUM_PROVE_REPETITIONS: usize = 1;
 const NUM_VERIFY_REPETITIONS: usize = 50;
 const NUM_CONSTRAINTS: usize = (1 << 20) - 100;
 const NUM_VARIABLES: usize = (1 << 20) - 100;
-
-#[derive(Copy)]
-struct DummyCircuit<F: PrimeField> {
-    pub a: Option<F>,
-    pub b: Option<F>,
-    pub num_variables: usize,
-    pub num_constraints: usize,
-}
-
-impl<F: PrimeField> Clone for DummyCircuit<F> {
-    fn clone(&self) -> Self {
-        DummyCircuit {
-            a: self.a.clone(),
-            b: self.b.clone(),
-            num_variables: self.num_variables.clone(),
-            num_constraints: self.num_constraints.clone(),
-        }
-    }
-}
-
-impl<F: PrimeField> ConstraintSynthesizer<F> for DummyCircuit<F> {
-    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
-        let a = cs.new_witness_variable(|| self.a.ok_or(SynthesisError::AssignmentMissing))?;
-        let b = cs.new_witness_variable(|| self.b.ok_or(SynthesisError::AssignmentMissing))?;
-        let c = cs.new_input_variable(|| {
-            let a = self.a.ok_or(SynthesisError::AssignmentMissing)?;
-            let b = self.b.ok_or(SynthesisError::AssignmentMissing)?;
-
-            Ok(a * b)
-        })?;
-
-        for _ in 0..(self.num_variables - 3) {
-            let _ = cs.new_witness_variable(|| self.a.ok_or(SynthesisError::AssignmentMissing))?;
-        }
-
-        for _ in 0..self.num_constraints - 1 {
-            cs.enforce_constraint(lc!() + a, lc!() + b, lc!() + c)?;
-        }
-
-        cs.enforce_constraint(lc!(), lc!(), lc!())?;
-
-        Ok(())
-    }
-}
+const NUM_INPUTS: usize = 300;
 
 macro_rules! groth16_prove_bench {
     ($bench_name:ident, $bench_field:ty, $bench_pairing_engine:ty) => {
         let rng = &mut ark_std::rand::rngs::StdRng::seed_from_u64(0u64);
-        let c = DummyCircuit::<$bench_field> {
-            a: Some(<$bench_field>::rand(rng)),
-            b: Some(<$bench_field>::rand(rng)),
-            num_variables: NUM_VARIABLES,
-            num_constraints: NUM_CONSTRAINTS,
-        };
+        let c = SyntheticCircuit::<$bench_field>::blank(NUM_CONSTRAINTS)
+            .with_variables(NUM_VARIABLES)
+            .with_assignment(0);
 
         let (pk, _) = Groth16::<$bench_pairing_engine>::circuit_specific_setup(c, rng).unwrap();
 
         let start = ark_std::time::Instant::now();
 
         for _ in 0..NUM_PROVE_REPETITIONS {
-            let _ = Groth16::<$bench_pairing_engine>::prove(&pk, c.clone(), rng).unwrap();
+            let _ = Groth16::<$bench_pairing_engine>::prove(&pk, c, rng).unwrap();
         }
 
         println!(
@@ -98,22 +49,17 @@ macro_rules! groth16_prove_bench {
 macro_rules! groth16_verify_bench {
     ($bench_name:ident, $bench_field:ty, $bench_pairing_engine:ty) => {
         let rng = &mut ark_std::rand::rngs::StdRng::seed_from_u64(0u64);
-        let c = DummyCircuit::<$bench_field> {
-            a: Some(<$bench_field>::rand(rng)),
-            b: Some(<$bench_field>::rand(rng)),
-            num_variables: 10,
-            num_constraints: NUM_CONSTRAINTS,
-        };
+        let c = SyntheticCircuit::<$bench_field>::blank(NUM_CONSTRAINTS).with_assignment(0);
 
         let (pk, vk) = Groth16::<$bench_pairing_engine>::circuit_specific_setup(c, rng).unwrap();
-        let proof = Groth16::<$bench_pairing_engine>::prove(&pk, c.clone(), rng).unwrap();
+        let proof = Groth16::<$bench_pairing_engine>::prove(&pk, c, rng).unwrap();
 
-        let v = c.a.unwrap() * c.b.unwrap();
+        let v = c.public_inputs().unwrap();
 
         let start = ark_std::time::Instant::now();
 
         for _ in 0..NUM_VERIFY_REPETITIONS {
-            let _ = Groth16::<$bench_pairing_engine>::verify(&vk, &vec![v], &proof).unwrap();
+            let _ = Groth16::<$bench_pairing_engine>::verify(&vk, &v, &proof).unwrap();
         }
 
         println!(
@@ -127,6 +73,7 @@ macro_rules! groth16_verify_bench {
 fn bench_prove() {
     use ark_std::rand::SeedableRng;
     groth16_prove_bench!(bls, BlsFr, Bls12_381);
+    groth16_prove_bench!(bw6, BW6Fr, BW6_761);
     groth16_prove_bench!(mnt4, MNT4Fr, MNT4_298);
     groth16_prove_bench!(mnt6, MNT6Fr, MNT6_298);
     groth16_prove_bench!(mnt4big, MNT4BigFr, MNT4_753);
@@ -136,13 +83,56 @@ fn bench_prove() {
 fn bench_verify() {
     use ark_std::rand::SeedableRng;
     groth16_verify_bench!(bls, BlsFr, Bls12_381);
+    groth16_verify_bench!(bw6, BW6Fr, BW6_761);
     groth16_verify_bench!(mnt4, MNT4Fr, MNT4_298);
     groth16_verify_bench!(mnt6, MNT6Fr, MNT6_298);
     groth16_verify_bench!(mnt4big, MNT4BigFr, MNT4_753);
     groth16_verify_bench!(mnt6big, MNT6BigFr, MNT6_753);
 }
 
+fn bench_prepare_inputs() {
+    use ark_ec::{AffineRepr, CurveGroup};
+    use ark_std::rand::SeedableRng;
+    use darklake_groth16::{prepare_verifying_key, VerifyingKey};
+
+    let rng = &mut ark_std::rand::rngs::StdRng::seed_from_u64(0u64);
+    let vk = VerifyingKey::<Bls12_381> {
+        gamma_abc_g1_static: (0..=NUM_INPUTS)
+            .map(|_| ark_bls12_381::G1Projective::rand(rng).into_affine())
+            .collect(),
+        ..Default::default()
+    };
+    let pvk = prepare_verifying_key(&vk);
+    let inputs = (0..NUM_INPUTS)
+        .map(|_| BlsFr::rand(rng))
+        .collect::<Vec<_>>();
+
+    let start = ark_std::time::Instant::now();
+    for _ in 0..NUM_VERIFY_REPETITIONS {
+        let _ = Groth16::<Bls12_381>::prepare_inputs_with_variables(&pvk, &inputs, &[]).unwrap();
+    }
+    println!(
+        "input preparation time for {} inputs: {} ns",
+        NUM_INPUTS,
+        start.elapsed().as_nanos() / NUM_VERIFY_REPETITIONS as u128
+    );
+
+    let start = ark_std::time::Instant::now();
+    for _ in 0..NUM_VERIFY_REPETITIONS {
+        let _ = vk.gamma_abc_g1_static[1..].iter().zip(&inputs).fold(
+            vk.gamma_abc_g1_static[0].into_group(),
+            |acc, (base, input)| acc + base.mul_bigint(input.into_bigint()),
+        );
+    }
+    println!(
+        "input preparation time for {} inputs with double-and-add: {} ns",
+        NUM_INPUTS,
+        start.elapsed().as_nanos() / NUM_VERIFY_REPETITIONS as u128
+    );
+}
+
 fn main() {
     bench_prove();
     bench_verify();
+    bench_prepare_inputs();
 }
@@ -0,0 +1,128 @@
+//! Derive macros for `darklake-groth16`, re-exported by its `derive`
+//! feature.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, spanned::Spanned, Data, DeriveInput, Error, Fields, Index};
+
+/// The public input group of a field of a statement.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Group {
+    Static,
+    Variable,
+    Skip,
+}
+
+/// Parses the `#[public_input(...)]` attributes of a field.
+fn group(field: &syn::Field) -> syn::Result<Group> {
+    let mut group = Group::Variable;
+    for attr in &field.attrs {
+        if !attr.path().is_ident("public_input") {
+            continue;
+        }
+        attr.parse_nested_meta(|meta| {
+            group = if meta.path.is_ident("static") {
+                Group::Static
+            } else if meta.path.is_ident("variable") {
+                Group::Variable
+            } else if meta.path.is_ident("skip") {
+                Group::Skip
+            } else {
+                return Err(meta.error("expected `static`, `variable` or `skip`"));
+            };
+            Ok(())
+        })?;
+    }
+    Ok(group)
+}
+
+/// Derives `ToPublicInputs` for a struct: see the documentation of the
+/// re-export in `darklake_groth16::statement`.
+#[proc_macro_derive(ToPublicInputs, attributes(public_input))]
+pub fn derive_to_public_inputs(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let Data::Struct(data) = &input.data else {
+        return Err(Error::new(
+            input.span(),
+            "ToPublicInputs can only be derived for structs",
+        ));
+    };
+
+    let krate = quote!(::darklake_groth16::statement);
+    let field = quote!(__F);
+
+    let mut static_types = Vec::new();
+    let mut static_appends = Vec::new();
+    let mut variable_types = Vec::new();
+    let mut variable_appends = Vec::new();
+    let fields: Vec<_> = match &data.fields {
+        Fields::Named(fields) => fields.named.iter().collect(),
+        Fields::Unnamed(fields) => fields.unnamed.iter().collect(),
+        Fields::Unit => Vec::new(),
+    };
+    for (i, f) in fields.into_iter().enumerate() {
+        let member = match &f.ident {
+            Some(ident) => quote!(#ident),
+            None => {
+                let index = Index::from(i);
+                quote!(#index)
+            },
+        };
+        let ty = &f.ty;
+        let append = quote! {
+            #krate::PublicInputValue::<#field>::append_to(&self.#member, inputs);
+        };
+        match group(f)? {
+            Group::Static => {
+                static_types.push(ty);
+                static_appends.push(append);
+            },
+            Group::Variable => {
+                variable_types.push(ty);
+                variable_appends.push(append);
+            },
+            Group::Skip => {},
+        }
+    }
+
+    let name = &input.ident;
+    let mut generics = input.generics.clone();
+    generics
+        .params
+        .push(syn::parse_quote!(#field: #krate::__private::PrimeField));
+    let (impl_generics, _, _) = generics.split_for_impl();
+    let (_, ty_generics, where_clause) = input.generics.split_for_impl();
+    let mut bounds: Vec<TokenStream2> = where_clause
+        .map(|clause| clause.predicates.iter().map(|p| quote!(#p)).collect())
+        .unwrap_or_default();
+    for ty in static_types.iter().chain(&variable_types) {
+        bounds.push(quote!(#ty: #krate::PublicInputValue<#field>));
+    }
+
+    Ok(quote! {
+        impl #impl_generics #krate::ToPublicInputs<#field> for #name #ty_generics
+        where
+            #(#bounds,)*
+        {
+            const NUM_STATIC_INPUTS: usize =
+                0 #(+ <#static_types as #krate::PublicInputValue<#field>>::NUM_ELEMENTS)*;
+            const NUM_VARIABLE_INPUTS: usize =
+                0 #(+ <#variable_types as #krate::PublicInputValue<#field>>::NUM_ELEMENTS)*;
+
+            fn append_static_inputs(&self, inputs: &mut #krate::__private::Vec<#field>) {
+                #(#static_appends)*
+            }
+
+            fn append_variable_inputs(&self, inputs: &mut #krate::__private::Vec<#field>) {
+                #(#variable_appends)*
+            }
+        }
+    })
+}
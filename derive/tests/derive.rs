@@ -0,0 +1,84 @@
+use ark_bn254::Fr;
+use darklake_groth16::statement::{PublicInputValue, ToPublicInputs};
+
+#[derive(ToPublicInputs)]
+struct SwapStatement {
+    #[public_input(static)]
+    pool_id: u64,
+    amount_in: u64,
+    min_out: u64,
+    #[public_input(variable)]
+    order_hash: [u8; 32],
+    #[public_input(static)]
+    fee: Fr,
+    #[public_input(skip)]
+    #[allow(dead_code)]
+    memo: &'static str,
+}
+
+#[derive(ToPublicInputs)]
+struct Pair(#[public_input(static)] u32, bool);
+
+#[derive(ToPublicInputs)]
+struct Wrapper<T> {
+    value: T,
+}
+
+#[test]
+fn test_derive_named_fields() {
+    let mut order_hash = [0u8; 32];
+    order_hash[15] = 1;
+    order_hash[31] = 2;
+    let statement = SwapStatement {
+        pool_id: 7,
+        amount_in: 1_000,
+        min_out: 990,
+        order_hash,
+        fee: Fr::from(3u64),
+        memo: "ignored",
+    };
+
+    assert_eq!(<SwapStatement as ToPublicInputs<Fr>>::NUM_STATIC_INPUTS, 2);
+    assert_eq!(
+        <SwapStatement as ToPublicInputs<Fr>>::NUM_VARIABLE_INPUTS,
+        4
+    );
+    assert_eq!(
+        statement.static_inputs(),
+        vec![Fr::from(7u64), Fr::from(3u64)]
+    );
+    assert_eq!(
+        statement.variable_inputs(),
+        vec![
+            Fr::from(1_000u64),
+            Fr::from(990u64),
+            Fr::from(1u64),
+            Fr::from(2u64)
+        ]
+    );
+    assert_eq!(
+        statement.public_inputs(),
+        [statement.static_inputs(), statement.variable_inputs()].concat()
+    );
+}
+
+#[test]
+fn test_derive_tuple_and_generic_fields() {
+    let pair = Pair(5, true);
+    assert_eq!(<Pair as ToPublicInputs<Fr>>::NUM_STATIC_INPUTS, 1);
+    assert_eq!(<Pair as ToPublicInputs<Fr>>::NUM_VARIABLE_INPUTS, 1);
+    assert_eq!(
+        ToPublicInputs::<Fr>::public_inputs(&pair),
+        vec![Fr::from(5u64), Fr::from(1u64)]
+    );
+
+    let wrapper = Wrapper {
+        value: [0xffu8; 32],
+    };
+    assert_eq!(
+        <Wrapper<[u8; 32]> as ToPublicInputs<Fr>>::NUM_VARIABLE_INPUTS,
+        <[u8; 32] as PublicInputValue<Fr>>::NUM_ELEMENTS
+    );
+    assert!(ToPublicInputs::<Fr>::static_inputs(&wrapper).is_empty());
+    assert_eq!(ToPublicInputs::<Fr>::variable_inputs(&wrapper).len(), 2);
+}
@@ -0,0 +1,42 @@
+//! Proves and verifies a swap in a constant product pool, passing the pool
+//! parameters as static inputs and the trade as variable inputs.
+use ark_bn254::Bn254;
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+use darklake_groth16::reference::{self, Pool, Trade};
+
+fn main() {
+    let rng = &mut StdRng::seed_from_u64(0u64);
+
+    let (pk, pvk) = reference::setup::<Bn254>(rng).unwrap();
+
+    let pool = Pool {
+        reserve_in: 1_000_000,
+        reserve_out: 2_000_000,
+        fee_bps: 30,
+    };
+    let trade = Trade {
+        amount_in: 10_000,
+        min_amount_out: 19_000,
+    };
+    println!(
+        "{} static and {} variable inputs, quote: {}",
+        pvk.num_static_inputs(),
+        pvk.num_variable_inputs(),
+        pool.amount_out(trade.amount_in).unwrap()
+    );
+
+    let proof = reference::prove(&pk, pool, trade, rng).unwrap();
+    println!(
+        "trade accepted: {}",
+        reference::verify(&pvk, pool, trade, &proof).unwrap()
+    );
+
+    let other_trade = Trade {
+        min_amount_out: 19_500,
+        ..trade
+    };
+    println!(
+        "other trade accepted: {}",
+        reference::verify(&pvk, pool, other_trade, &proof).unwrap()
+    );
+}
@@ -0,0 +1,290 @@
+use crate::{
+    inputs::{Endianness, PublicInputs},
+    VerifyingKey,
+};
+use ark_ec::pairing::Pairing;
+use ark_std::{
+    fmt::{self, Write},
+    format,
+    string::String,
+    vec::Vec,
+};
+
+/// How the verifier receives a public input.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum InputKind {
+    /// A static input, known at proving time.
+    Static,
+    /// A variable input, only known at verification time.
+    Variable,
+    /// An input only given to the verifier as a Pedersen commitment.
+    Committed,
+}
+
+impl InputKind {
+    fn name(self) -> &'static str {
+        match self {
+            InputKind::Static => "static",
+            InputKind::Variable => "variable",
+            InputKind::Committed => "committed",
+        }
+    }
+}
+
+/// A named public input of a [`VerifyingKeyWithSchema`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct InputField {
+    /// The name of the input.
+    pub name: String,
+    /// How the verifier receives the input.
+    pub kind: InputKind,
+}
+
+/// An error when describing the public inputs of a verifying key.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SchemaError {
+    /// The number of names differs from the number of public inputs of the
+    /// key.
+    NameCount {
+        /// The number of public inputs of the key.
+        expected: usize,
+        /// The number of names given.
+        actual: usize,
+    },
+    /// A name is not an identifier of JavaScript and Rust: ASCII letters,
+    /// digits and underscores, not starting with a digit.
+    InvalidName(String),
+    /// Two inputs have the same name.
+    DuplicateName(String),
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchemaError::NameCount { expected, actual } => {
+                write!(f, "expected {} input names, got {}", expected, actual)
+            },
+            SchemaError::InvalidName(name) => write!(f, "invalid input name {:?}", name),
+            SchemaError::DuplicateName(name) => write!(f, "duplicate input name {:?}", name),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SchemaError {}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// A [`VerifyingKey`] with the names and the encoding of its public inputs,
+/// from which the ABI of the verifier is generated for frontends, as a JSON
+/// schema with [`Self::json_schema`] or as a TypeScript type with
+/// [`Self::typescript`].
+///
+/// Each input is encoded as a `0x`-prefixed hexadecimal string of the
+/// [`PublicInputs::to_bytes`] encoding of the input with the endianness of
+/// the schema.
+#[derive(Clone, Debug, PartialEq)]
+pub struct VerifyingKeyWithSchema<E: Pairing> {
+    vk: VerifyingKey<E>,
+    name: String,
+    inputs: Vec<InputField>,
+    endianness: Endianness,
+}
+
+impl<E: Pairing> VerifyingKeyWithSchema<E> {
+    /// Names the public inputs of `vk`, in order: its static inputs, then
+    /// its variable inputs. `name` names the generated types. Fails if the
+    /// number of names differs from the number of inputs, or if a name is
+    /// not an identifier or is repeated.
+    pub fn new(
+        vk: VerifyingKey<E>,
+        name: &str,
+        input_names: &[&str],
+        endianness: Endianness,
+    ) -> Result<Self, SchemaError> {
+        Self::with_committed_inputs(vk, 0, name, input_names, endianness)
+    }
+
+    /// Names the public inputs of `vk` like [`Self::new`], followed by
+    /// `num_committed` inputs given to the verifier as a commitment over a
+    /// [`CommitmentKey`](crate::CommitmentKey).
+    pub fn with_committed_inputs(
+        vk: VerifyingKey<E>,
+        num_committed: usize,
+        name: &str,
+        input_names: &[&str],
+        endianness: Endianness,
+    ) -> Result<Self, SchemaError> {
+        let num_static = vk.gamma_abc_g1_static.len().saturating_sub(1);
+        let kinds = ark_std::iter::repeat_n(InputKind::Static, num_static)
+            .chain(ark_std::iter::repeat_n(
+                InputKind::Variable,
+                vk.gamma_abc_g1_variable.len(),
+            ))
+            .chain(ark_std::iter::repeat_n(InputKind::Committed, num_committed));
+        let expected = kinds.clone().count();
+        if input_names.len() != expected {
+            return Err(SchemaError::NameCount {
+                expected,
+                actual: input_names.len(),
+            });
+        }
+        if !is_identifier(name) {
+            return Err(SchemaError::InvalidName(name.into()));
+        }
+        let mut inputs: Vec<InputField> = Vec::with_capacity(expected);
+        for (input_name, kind) in input_names.iter().zip(kinds) {
+            if !is_identifier(input_name) {
+                return Err(SchemaError::InvalidName((*input_name).into()));
+            }
+            if inputs.iter().any(|input| input.name == *input_name) {
+                return Err(SchemaError::DuplicateName((*input_name).into()));
+            }
+            inputs.push(InputField {
+                name: (*input_name).into(),
+                kind,
+            });
+        }
+        Ok(Self {
+            vk,
+            name: name.into(),
+            inputs,
+            endianness,
+        })
+    }
+
+    /// Names the public inputs of `vk` `input_1`, `input_2`, and so on, as
+    /// the [`SymbolTable`](crate::export::SymbolTable) of a setup does.
+    pub fn with_default_names(
+        vk: VerifyingKey<E>,
+        name: &str,
+        endianness: Endianness,
+    ) -> Result<Self, SchemaError> {
+        let num_inputs =
+            vk.gamma_abc_g1_static.len().saturating_sub(1) + vk.gamma_abc_g1_variable.len();
+        let names: Vec<String> = (1..=num_inputs).map(|i| format!("input_{}", i)).collect();
+        let names: Vec<&str> = names.iter().map(String::as_str).collect();
+        Self::new(vk, name, &names, endianness)
+    }
+
+    /// Returns the verifying key.
+    pub fn vk(&self) -> &VerifyingKey<E> {
+        &self.vk
+    }
+
+    /// Returns the public inputs, in order.
+    pub fn inputs(&self) -> &[InputField] {
+        &self.inputs
+    }
+
+    /// Returns the byte order of the encoded inputs.
+    pub fn endianness(&self) -> Endianness {
+        self.endianness
+    }
+
+    fn endianness_name(&self) -> &'static str {
+        match self.endianness {
+            Endianness::Big => "big",
+            Endianness::Little => "little",
+        }
+    }
+
+    /// Returns a JSON schema, in the 2020-12 draft, of an object mapping the
+    /// name of each public input to its encoding. Each property records the
+    /// kind and the position of its input in the `x-kind` and `x-index`
+    /// keywords.
+    pub fn json_schema(&self) -> String {
+        let size = PublicInputs::<E>::element_size();
+        let mut schema = String::new();
+        let mut write = || -> fmt::Result {
+            writeln!(schema, "{{")?;
+            writeln!(
+                schema,
+                "  \"$schema\": \"https://json-schema.org/draft/2020-12/schema\","
+            )?;
+            writeln!(schema, "  \"title\": \"{}\",", self.name)?;
+            writeln!(schema, "  \"type\": \"object\",")?;
+            writeln!(schema, "  \"properties\": {{")?;
+            for (i, input) in self.inputs.iter().enumerate() {
+                let separator = if i + 1 == self.inputs.len() { "" } else { "," };
+                writeln!(schema, "    \"{}\": {{", input.name)?;
+                writeln!(schema, "      \"type\": \"string\",")?;
+                writeln!(
+                    schema,
+                    "      \"pattern\": \"^0x[0-9a-fA-F]{{{}}}$\",",
+                    2 * size
+                )?;
+                writeln!(
+                    schema,
+                    "      \"description\": \"{} input, a {}-byte {} endian field element\",",
+                    input.kind.name(),
+                    size,
+                    self.endianness_name()
+                )?;
+                writeln!(schema, "      \"x-kind\": \"{}\",", input.kind.name())?;
+                writeln!(schema, "      \"x-index\": {}", i)?;
+                writeln!(schema, "    }}{}", separator)?;
+            }
+            writeln!(schema, "  }},")?;
+            let names: Vec<String> = self
+                .inputs
+                .iter()
+                .map(|input| format!("\"{}\"", input.name))
+                .collect();
+            writeln!(schema, "  \"required\": [{}],", names.join(", "))?;
+            writeln!(schema, "  \"additionalProperties\": false")?;
+            writeln!(schema, "}}")
+        };
+        write().expect("writing to a string cannot fail");
+        schema
+    }
+
+    /// Returns a TypeScript module declaring an interface of the public
+    /// inputs named after the schema, and a constant of their layout.
+    pub fn typescript(&self) -> String {
+        let size = PublicInputs::<E>::element_size();
+        let mut source = String::new();
+        let mut write = || -> fmt::Result {
+            writeln!(
+                source,
+                "/** The public inputs of {}, each a 0x-prefixed hexadecimal {}-byte {} endian field element. */",
+                self.name,
+                size,
+                self.endianness_name()
+            )?;
+            writeln!(source, "export interface {} {{", self.name)?;
+            for (i, input) in self.inputs.iter().enumerate() {
+                writeln!(source, "  /** Input {}, {}. */", i, input.kind.name())?;
+                writeln!(source, "  {}: string;", input.name)?;
+            }
+            writeln!(source, "}}")?;
+            writeln!(source)?;
+            writeln!(
+                source,
+                "/** The layout of {} expected by the verifier. */",
+                self.name
+            )?;
+            writeln!(source, "export const {}Layout = {{", self.name)?;
+            for kind in [InputKind::Static, InputKind::Variable, InputKind::Committed] {
+                let names: Vec<String> = self
+                    .inputs
+                    .iter()
+                    .filter(|input| input.kind == kind)
+                    .map(|input| format!("\"{}\"", input.name))
+                    .collect();
+                writeln!(source, "  {}: [{}],", kind.name(), names.join(", "))?;
+            }
+            writeln!(source, "  elementSize: {},", size)?;
+            writeln!(source, "  endianness: \"{}\",", self.endianness_name())?;
+            writeln!(source, "}} as const;")
+        };
+        write().expect("writing to a string cannot fail");
+        source
+    }
+}
@@ -0,0 +1,195 @@
+use crate::{bundle::VkFingerprint, r1cs_to_qap::R1CSToQAP, Groth16, Proof, VerifyingKey};
+use ark_crypto_primitives::snark::SNARK;
+use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup, VariableBaseMSM};
+use ark_ff::{One, PrimeField, UniformRand, Zero};
+use ark_relations::r1cs::Result as R1CSResult;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::{fmt, rand::Rng, vec::Vec};
+
+/// An error when accumulating verifying keys.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AccumulatorError {
+    /// More keys were accumulated than the parameters support.
+    CapacityExceeded {
+        /// The number of keys the parameters support.
+        capacity: usize,
+        /// The number of keys accumulated.
+        len: usize,
+    },
+    /// The key is not in the accumulated set.
+    NotAMember,
+}
+
+impl fmt::Display for AccumulatorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AccumulatorError::CapacityExceeded { capacity, len } => write!(
+                f,
+                "{} keys exceed the capacity of {} of the accumulator",
+                len, capacity
+            ),
+            AccumulatorError::NotAMember => write!(f, "key is not in the accumulated set"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for AccumulatorError {}
+
+/// The witness that a verifying key is in the set of an accumulator: the
+/// accumulator of the other keys of the set.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct MembershipWitness<E: Pairing> {
+    /// The product of the `(x_j + s)` of the other keys, times the
+    /// generator of `G1`.
+    pub w: E::G1Affine,
+}
+
+/// The parameters of a bilinear accumulator of [`VkFingerprint`]s, for
+/// verifiers of proofs of many circuits storing a single `G1` element
+/// rather than every verifying key, e.g. on chain.
+///
+/// A set of keys with fingerprints mapped to the scalars `x_i` is
+/// accumulated as `A = prod_i (x_i + s) G1`, for a secret `s` only known
+/// through its powers. A key is in the set if the verifier is given a
+/// [`MembershipWitness`] `W` with `e(W, x H + s H) = e(A, H)`, which cannot
+/// be forged for other keys without `s`. Accumulating a set requires a
+/// power of `s` per key, which bounds the size of the sets of the
+/// parameters, not of their accumulators.
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct AccumulatorParams<E: Pairing> {
+    /// The `s^i G1`, from `i = 0` to the capacity.
+    pub powers_g1: Vec<E::G1Affine>,
+    /// The generator `H` of `G2`.
+    pub h: E::G2Affine,
+    /// The element `s H`.
+    pub s_h: E::G2Affine,
+}
+
+/// Returns the scalar `x` a fingerprint is accumulated as.
+pub fn fingerprint_scalar<F: PrimeField>(fingerprint: &VkFingerprint) -> F {
+    F::from_le_bytes_mod_order(fingerprint)
+}
+
+/// Returns the coefficients of `prod_i (X + x_i)`, lowest degree first.
+fn vanishing_coefficients<F: PrimeField>(roots: impl Iterator<Item = F>) -> Vec<F> {
+    let mut coefficients = ark_std::vec![F::one()];
+    for x in roots {
+        coefficients.push(F::zero());
+        for i in (0..coefficients.len()).rev() {
+            let lower = if i > 0 {
+                coefficients[i - 1]
+            } else {
+                F::zero()
+            };
+            coefficients[i] = coefficients[i] * x + lower;
+        }
+    }
+    coefficients
+}
+
+impl<E: Pairing> AccumulatorParams<E> {
+    /// Samples parameters accumulating up to `capacity` keys.
+    ///
+    /// Whoever knows `s` can forge membership witnesses, so the parameters
+    /// of production verifiers must come from a ceremony, e.g. the powers
+    /// of tau of a Groth16 setup, rather than from this function, which
+    /// drops `s` but cannot prove it did.
+    pub fn setup(capacity: usize, rng: &mut impl Rng) -> Self {
+        let s = E::ScalarField::rand(rng);
+        let g1 = E::G1Affine::generator().into_group();
+        let mut power = E::ScalarField::one();
+        let powers = (0..=capacity)
+            .map(|_| {
+                let element = g1 * power;
+                power *= s;
+                element
+            })
+            .collect::<Vec<_>>();
+        let h = E::G2Affine::generator();
+        Self {
+            powers_g1: E::G1::normalize_batch(&powers),
+            h,
+            s_h: (h * s).into_affine(),
+        }
+    }
+
+    /// Returns the maximum number of keys in an accumulated set.
+    pub fn capacity(&self) -> usize {
+        self.powers_g1.len().saturating_sub(1)
+    }
+
+    /// Evaluates `prod_i (x_i + s) G1` for the fingerprints of `members`.
+    fn evaluate<'a>(
+        &self,
+        members: impl Iterator<Item = &'a VkFingerprint> + Clone,
+    ) -> Result<E::G1Affine, AccumulatorError> {
+        let len = members.clone().count();
+        if len > self.capacity() {
+            return Err(AccumulatorError::CapacityExceeded {
+                capacity: self.capacity(),
+                len,
+            });
+        }
+        let coefficients = vanishing_coefficients(members.map(fingerprint_scalar));
+        Ok(E::G1::msm_unchecked(&self.powers_g1, &coefficients).into_affine())
+    }
+
+    /// Returns the accumulator of the set of keys with the fingerprints
+    /// `members`, which must be distinct.
+    pub fn accumulate(&self, members: &[VkFingerprint]) -> Result<E::G1Affine, AccumulatorError> {
+        self.evaluate(members.iter())
+    }
+
+    /// Returns the witness that `fingerprint` is in the set `members`.
+    pub fn membership_witness(
+        &self,
+        members: &[VkFingerprint],
+        fingerprint: &VkFingerprint,
+    ) -> Result<MembershipWitness<E>, AccumulatorError> {
+        let index = members
+            .iter()
+            .position(|member| member == fingerprint)
+            .ok_or(AccumulatorError::NotAMember)?;
+        let others = members[..index].iter().chain(&members[index + 1..]);
+        Ok(MembershipWitness {
+            w: self.evaluate(others)?,
+        })
+    }
+
+    /// Checks that `witness` proves that `fingerprint` is in the set of
+    /// `accumulator`.
+    pub fn verify_membership(
+        &self,
+        accumulator: &E::G1Affine,
+        fingerprint: &VkFingerprint,
+        witness: &MembershipWitness<E>,
+    ) -> bool {
+        let x = fingerprint_scalar::<E::ScalarField>(fingerprint);
+        let x_s_h = self.h * x + self.s_h;
+        E::multi_pairing(
+            [witness.w, (-accumulator.into_group()).into_affine()],
+            [x_s_h.into_affine(), self.h],
+        )
+        .is_zero()
+    }
+}
+
+impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
+    /// Verifies `proof` against `inputs` and `vk`, after checking with
+    /// `witness` that `vk` is in the set of `accumulator`. Returns `false`
+    /// for keys outside of the set.
+    pub fn verify_with_accumulator(
+        params: &AccumulatorParams<E>,
+        accumulator: &E::G1Affine,
+        vk: &VerifyingKey<E>,
+        witness: &MembershipWitness<E>,
+        inputs: &[E::ScalarField],
+        proof: &Proof<E>,
+    ) -> R1CSResult<bool> {
+        if !params.verify_membership(accumulator, &vk.fingerprint(), witness) {
+            return Ok(false);
+        }
+        <Self as SNARK<E::ScalarField>>::verify(vk, inputs, proof)
+    }
+}
@@ -0,0 +1,278 @@
+use crate::{
+    inputs::{Endianness, PublicInputs},
+    loader::{load_proof, load_proving_key, load_verifying_key, KeyLoadError},
+    prepare_verifying_key, Groth16, InputCountMismatch, PreparedVerifyingKey, ProvingKey,
+    VerificationError,
+};
+use ark_bls12_377::Bls12_377;
+use ark_bls12_381::Bls12_381;
+use ark_bn254::Bn254;
+use ark_ec::pairing::Pairing;
+use ark_relations::r1cs::{ConstraintSynthesizer, SynthesisError};
+use ark_serialize::{CanonicalSerialize, SerializationError};
+use ark_std::{fmt, rand::Rng, vec::Vec};
+
+/// A curve supported by [`AnyGroth16`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Curve {
+    /// BN254, also known as alt_bn128 or BN128.
+    Bn254,
+    /// BLS12-381.
+    Bls12_381,
+    /// BLS12-377.
+    Bls12_377,
+}
+
+impl Curve {
+    /// The supported curves, in the order in which serialized keys are
+    /// tried against them.
+    pub const ALL: [Curve; 3] = [Curve::Bn254, Curve::Bls12_381, Curve::Bls12_377];
+}
+
+impl fmt::Display for Curve {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Curve::Bn254 => write!(f, "BN254"),
+            Curve::Bls12_381 => write!(f, "BLS12-381"),
+            Curve::Bls12_377 => write!(f, "BLS12-377"),
+        }
+    }
+}
+
+/// An error of [`AnyGroth16`].
+#[derive(Debug)]
+pub enum AnyGroth16Error {
+    /// A key or proof could not be loaded.
+    Load(KeyLoadError),
+    /// The inputs do not match the key, or the circuit is not satisfied.
+    Synthesis(SynthesisError),
+    /// The numbers of static or variable inputs differ from the ones
+    /// expected by the key.
+    InputCount(InputCountMismatch),
+    /// A proof was requested from keys loaded without a proving key.
+    MissingProvingKey,
+}
+
+impl fmt::Display for AnyGroth16Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AnyGroth16Error::Load(e) => write!(f, "{}", e),
+            AnyGroth16Error::Synthesis(e) => write!(f, "{}", e),
+            AnyGroth16Error::InputCount(e) => write!(f, "{}", e),
+            AnyGroth16Error::MissingProvingKey => write!(f, "no proving key loaded"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for AnyGroth16Error {}
+
+impl From<KeyLoadError> for AnyGroth16Error {
+    fn from(e: KeyLoadError) -> Self {
+        AnyGroth16Error::Load(e)
+    }
+}
+
+impl From<SerializationError> for AnyGroth16Error {
+    fn from(e: SerializationError) -> Self {
+        AnyGroth16Error::Load(KeyLoadError::Serialization(e))
+    }
+}
+
+impl From<SynthesisError> for AnyGroth16Error {
+    fn from(e: SynthesisError) -> Self {
+        AnyGroth16Error::Synthesis(e)
+    }
+}
+
+impl From<VerificationError> for AnyGroth16Error {
+    fn from(e: VerificationError) -> Self {
+        match e {
+            VerificationError::Synthesis(e) => AnyGroth16Error::Synthesis(e),
+            VerificationError::InputCount(e) => AnyGroth16Error::InputCount(e),
+        }
+    }
+}
+
+/// The keys of a circuit over one curve.
+#[derive(Clone, Debug)]
+pub struct CurveKeys<E: Pairing> {
+    /// The prepared verifying key.
+    pub pvk: PreparedVerifyingKey<E>,
+    /// The proving key, if loaded.
+    pub pk: Option<ProvingKey<E>>,
+}
+
+impl<E: Pairing> CurveKeys<E> {
+    fn input_size(&self) -> usize {
+        PublicInputs::<E>::element_size()
+    }
+
+    fn verify_bytes(
+        &self,
+        proof: &[u8],
+        static_inputs: &[u8],
+        variable_inputs: &[u8],
+        endianness: Endianness,
+    ) -> Result<bool, AnyGroth16Error> {
+        let proof = load_proof::<E>(proof)?;
+        Ok(Groth16::<E>::verify_with_input_bytes(
+            &self.pvk,
+            &proof,
+            static_inputs,
+            variable_inputs,
+            endianness,
+        )?)
+    }
+
+    fn prove_bytes<C: ConstraintSynthesizer<E::ScalarField>>(
+        &self,
+        circuit: C,
+        rng: &mut impl Rng,
+    ) -> Result<Vec<u8>, AnyGroth16Error> {
+        let pk = self.pk.as_ref().ok_or(AnyGroth16Error::MissingProvingKey)?;
+        let proof = Groth16::<E>::create_random_proof_with_reduction(circuit, pk, rng)?;
+        let mut bytes = Vec::new();
+        proof.serialize_compressed(&mut bytes)?;
+        Ok(bytes)
+    }
+}
+
+/// The keys of a circuit over any supported curve, for services handling
+/// proofs over several curves without threading the pairing through every
+/// layer as a type parameter.
+///
+/// Keys, proofs and public inputs cross the API as bytes: keys and proofs
+/// in the compressed or uncompressed serializations of arkworks, and inputs
+/// as fixed-width encodings, see [`PublicInputs`].
+#[derive(Clone, Debug)]
+pub enum AnyGroth16 {
+    /// Keys over BN254.
+    Bn254(CurveKeys<Bn254>),
+    /// Keys over BLS12-381.
+    Bls12_381(CurveKeys<Bls12_381>),
+    /// Keys over BLS12-377.
+    Bls12_377(CurveKeys<Bls12_377>),
+}
+
+macro_rules! dispatch {
+    ($any:expr, $keys:ident => $body:expr) => {
+        match $any {
+            AnyGroth16::Bn254($keys) => $body,
+            AnyGroth16::Bls12_381($keys) => $body,
+            AnyGroth16::Bls12_377($keys) => $body,
+        }
+    };
+}
+
+/// Loads an object over the first supported curve accepting `bytes`. If
+/// none does, returns the validation error of a curve with the layout of
+/// the bytes, or else the first layout error.
+fn load_any<T>(
+    bytes: &[u8],
+    mut load: impl FnMut(Curve, &[u8]) -> Result<T, KeyLoadError>,
+) -> Result<T, KeyLoadError> {
+    let mut error = KeyLoadError::UnknownLayout;
+    for curve in Curve::ALL {
+        match load(curve, bytes) {
+            Ok(value) => return Ok(value),
+            Err(e @ KeyLoadError::Serialization(_)) => error = e,
+            Err(e) if matches!(error, KeyLoadError::UnknownLayout) => error = e,
+            Err(_) => {},
+        }
+    }
+    Err(error)
+}
+
+impl AnyGroth16 {
+    /// Loads a serialized verifying key over any supported curve, detected
+    /// from its layout and from the validation of its points.
+    pub fn from_verifying_key_bytes(bytes: &[u8]) -> Result<Self, KeyLoadError> {
+        fn keys<E: Pairing>(bytes: &[u8]) -> Result<CurveKeys<E>, KeyLoadError> {
+            Ok(CurveKeys {
+                pvk: prepare_verifying_key(&load_verifying_key::<E>(bytes)?),
+                pk: None,
+            })
+        }
+        load_any(bytes, |curve, bytes| {
+            Ok(match curve {
+                Curve::Bn254 => AnyGroth16::Bn254(keys(bytes)?),
+                Curve::Bls12_381 => AnyGroth16::Bls12_381(keys(bytes)?),
+                Curve::Bls12_377 => AnyGroth16::Bls12_377(keys(bytes)?),
+            })
+        })
+    }
+
+    /// Loads a serialized proving key over any supported curve, detected
+    /// from its layout and from the validation of its points, along with
+    /// its verifying key.
+    pub fn from_proving_key_bytes(bytes: &[u8]) -> Result<Self, KeyLoadError> {
+        fn keys<E: Pairing>(bytes: &[u8]) -> Result<CurveKeys<E>, KeyLoadError> {
+            let pk = load_proving_key::<E>(bytes)?;
+            Ok(CurveKeys {
+                pvk: prepare_verifying_key(&pk.vk),
+                pk: Some(pk),
+            })
+        }
+        load_any(bytes, |curve, bytes| {
+            Ok(match curve {
+                Curve::Bn254 => AnyGroth16::Bn254(keys(bytes)?),
+                Curve::Bls12_381 => AnyGroth16::Bls12_381(keys(bytes)?),
+                Curve::Bls12_377 => AnyGroth16::Bls12_377(keys(bytes)?),
+            })
+        })
+    }
+
+    /// Returns the curve of the keys.
+    pub fn curve(&self) -> Curve {
+        match self {
+            AnyGroth16::Bn254(_) => Curve::Bn254,
+            AnyGroth16::Bls12_381(_) => Curve::Bls12_381,
+            AnyGroth16::Bls12_377(_) => Curve::Bls12_377,
+        }
+    }
+
+    /// Returns whether a proving key is loaded.
+    pub fn can_prove(&self) -> bool {
+        dispatch!(self, keys => keys.pk.is_some())
+    }
+
+    /// Returns the number of static inputs expected by the verifying key.
+    pub fn num_static_inputs(&self) -> usize {
+        dispatch!(self, keys => keys.pvk.num_static_inputs())
+    }
+
+    /// Returns the number of variable inputs expected by the verifying key.
+    pub fn num_variable_inputs(&self) -> usize {
+        dispatch!(self, keys => keys.pvk.num_variable_inputs())
+    }
+
+    /// Returns the size of the encoding of a public input.
+    pub fn input_size(&self) -> usize {
+        dispatch!(self, keys => keys.input_size())
+    }
+
+    /// Verifies a serialized proof against static and variable inputs given
+    /// as fixed-width encodings, see [`Groth16::verify_with_input_bytes`].
+    pub fn verify_bytes(
+        &self,
+        proof: &[u8],
+        static_inputs: &[u8],
+        variable_inputs: &[u8],
+        endianness: Endianness,
+    ) -> Result<bool, AnyGroth16Error> {
+        dispatch!(self, keys => keys.verify_bytes(proof, static_inputs, variable_inputs, endianness))
+    }
+
+    /// Proves `circuit`, which must be generic over the scalar fields of all
+    /// supported curves, returning the compressed serialization of the
+    /// proof.
+    pub fn prove_bytes<C>(&self, circuit: C, rng: &mut impl Rng) -> Result<Vec<u8>, AnyGroth16Error>
+    where
+        C: ConstraintSynthesizer<<Bn254 as Pairing>::ScalarField>
+            + ConstraintSynthesizer<<Bls12_381 as Pairing>::ScalarField>
+            + ConstraintSynthesizer<<Bls12_377 as Pairing>::ScalarField>,
+    {
+        dispatch!(self, keys => keys.prove_bytes(circuit, rng))
+    }
+}
@@ -0,0 +1,43 @@
+use ark_ff::Field;
+use ark_std::vec::Vec;
+
+/// Buffers for the largest temporaries of the prover, kept across proofs.
+///
+/// A long-lived prover otherwise allocates and frees several domain-sized
+/// vectors per proof, which fragments the heap and inflates the resident
+/// memory of the process. With an arena, each buffer is allocated once at
+/// the size of the largest proof and reused by
+/// [`Groth16::create_proof_with_arena`](crate::Groth16::create_proof_with_arena).
+#[derive(Clone, Debug, Default)]
+pub struct ProverArena<F: Field> {
+    pub(crate) full_assignment: Vec<F>,
+    pub(crate) h: Vec<F>,
+    pub(crate) scratch: Vec<F>,
+}
+
+impl<F: Field> ProverArena<F> {
+    /// Creates an arena without any allocated buffers.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an arena with buffers large enough for circuits with
+    /// `num_variables` variables and a QAP domain of size `domain_size`.
+    pub fn with_capacity(num_variables: usize, domain_size: usize) -> Self {
+        Self {
+            full_assignment: Vec::with_capacity(num_variables),
+            h: Vec::with_capacity(domain_size),
+            scratch: Vec::with_capacity(domain_size),
+        }
+    }
+
+    /// Returns the number of field elements allocated by the arena.
+    pub fn capacity(&self) -> usize {
+        self.full_assignment.capacity() + self.h.capacity() + self.scratch.capacity()
+    }
+
+    /// Frees the buffers of the arena.
+    pub fn release(&mut self) {
+        *self = Self::default();
+    }
+}
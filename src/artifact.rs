@@ -0,0 +1,190 @@
+use crate::{r1cs_to_qap::R1CSToQAP, Groth16, KeyBasis, ProvingKey, VerifyingKey};
+use ark_ec::{pairing::Pairing, CurveGroup, VariableBaseMSM};
+use ark_ff::UniformRand;
+use ark_poly::{EvaluationDomain, GeneralEvaluationDomain};
+use ark_relations::r1cs::{ConstraintSynthesizer, Matrix, Result as R1CSResult, SynthesisError};
+use ark_serialize::*;
+use ark_std::{cfg_into_iter, cfg_iter, rand::Rng, vec::Vec};
+
+use ark_std::ops::Mul;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// A compact form of a [`ProvingKey`], from which the `A` and `B` queries are
+/// recomputed by [`expand_proving_key`].
+///
+/// Instead of one `A`, `B` in `G1` and `B` in `G2` element per variable, the
+/// artifact stores the evaluations `L_j(t) * G` and `L_j(t) * H` of the
+/// Lagrange polynomials of the QAP domain at the secret point, along with
+/// the `A` and `B` constraint matrices. This is smaller when the circuit has
+/// many more variables than constraints and sparse matrices; the remaining
+/// queries depend on the other secrets of the setup and are stored as is.
+///
+/// Expansion follows the instance map of
+/// [`LibsnarkReduction`](crate::r1cs_to_qap::LibsnarkReduction).
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct CeremonyArtifact<E: Pairing> {
+    /// The verification key.
+    pub vk: VerifyingKey<E>,
+    /// The element `beta * G` in `E::G1`.
+    pub beta_g1: E::G1Affine,
+    /// The element `delta * G` in `E::G1`.
+    pub delta_g1: E::G1Affine,
+    /// The elements `L_j(t) * G` in `E::G1`, for the constraints followed by
+    /// the instance variables.
+    pub lagrange_g1: Vec<E::G1Affine>,
+    /// The elements `L_j(t) * H` in `E::G2`, for the constraints.
+    pub lagrange_g2: Vec<E::G2Affine>,
+    /// The number of instance variables, including the constant one.
+    pub num_instance_variables: usize,
+    /// The number of witness variables.
+    pub num_witness_variables: usize,
+    /// The `A` matrix of the constraint system.
+    pub a: Matrix<E::ScalarField>,
+    /// The `B` matrix of the constraint system.
+    pub b: Matrix<E::ScalarField>,
+    /// The basis in which `h_query` is expressed.
+    pub h_basis: KeyBasis,
+    /// The `h_query` of the proving key.
+    pub h_query: Vec<E::G1Affine>,
+    /// The `l_query` of the proving key.
+    pub l_query: Vec<E::G1Affine>,
+}
+
+/// Computes, for each variable, the linear combination of `lagrange`
+/// with the coefficients of the column of `matrix` for that variable.
+fn expand_query<G: VariableBaseMSM>(
+    matrix: &Matrix<G::ScalarField>,
+    lagrange: &[G::MulBase],
+    num_variables: usize,
+) -> R1CSResult<Vec<G>> {
+    let mut columns = vec![(Vec::new(), Vec::new()); num_variables];
+    for (row, base) in matrix.iter().zip(lagrange) {
+        for &(coeff, index) in row {
+            let (bases, scalars) = columns
+                .get_mut(index)
+                .ok_or(SynthesisError::MalformedVerifyingKey)?;
+            bases.push(*base);
+            scalars.push(coeff);
+        }
+    }
+
+    Ok(cfg_into_iter!(columns)
+        .map(|(bases, scalars)| G::msm_unchecked(&bases, &scalars))
+        .collect())
+}
+
+/// Recomputes the full proving key described by `artifact`. Fails with
+/// [`SynthesisError::MalformedVerifyingKey`] if the parts of the artifact
+/// are inconsistent.
+pub fn expand_proving_key<E: Pairing>(artifact: &CeremonyArtifact<E>) -> R1CSResult<ProvingKey<E>> {
+    let expand_time = start_timer!(|| "Expand proving key");
+    let num_constraints = artifact.a.len();
+    let num_instance_variables = artifact.num_instance_variables;
+    let num_variables = num_instance_variables + artifact.num_witness_variables;
+    if artifact.b.len() != num_constraints
+        || artifact.lagrange_g1.len() != num_constraints + num_instance_variables
+        || artifact.lagrange_g2.len() != num_constraints
+    {
+        return Err(SynthesisError::MalformedVerifyingKey);
+    }
+
+    let mut a_query = expand_query::<E::G1>(&artifact.a, &artifact.lagrange_g1, num_variables)?;
+    // The instance variables are also enforced by the extra constraints of
+    // the reduction.
+    for (a_i, base) in a_query
+        .iter_mut()
+        .zip(&artifact.lagrange_g1[num_constraints..])
+    {
+        *a_i += base;
+    }
+    let b_g1_query = expand_query::<E::G1>(&artifact.b, &artifact.lagrange_g1, num_variables)?;
+    let b_g2_query = expand_query::<E::G2>(&artifact.b, &artifact.lagrange_g2, num_variables)?;
+    end_timer!(expand_time);
+
+    Ok(ProvingKey {
+        vk: artifact.vk.clone(),
+        beta_g1: artifact.beta_g1,
+        delta_g1: artifact.delta_g1,
+        a_query: E::G1::normalize_batch(&a_query),
+        b_g1_query: E::G1::normalize_batch(&b_g1_query),
+        b_g2_query: E::G2::normalize_batch(&b_g2_query),
+        h_basis: artifact.h_basis,
+        h_query: artifact.h_query.clone(),
+        l_query: artifact.l_query.clone(),
+    })
+}
+
+impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
+    /// Generates a random common reference string for a circuit like
+    /// [`Self::generate_random_parameters_with_basis`], in the compact form
+    /// of a [`CeremonyArtifact`].
+    pub fn generate_ceremony_artifact<C>(
+        circuit: C,
+        rng: &mut impl Rng,
+        num_static_inputs: usize,
+        h_basis: KeyBasis,
+    ) -> R1CSResult<CeremonyArtifact<E>>
+    where
+        C: ConstraintSynthesizer<E::ScalarField>,
+    {
+        let alpha = E::ScalarField::rand(rng);
+        let beta = E::ScalarField::rand(rng);
+        let gamma = E::ScalarField::rand(rng);
+        let delta = E::ScalarField::rand(rng);
+
+        let g1_generator = E::G1::rand(rng);
+        let g2_generator = E::G2::rand(rng);
+
+        let cs = Self::synthesize_for_setup(circuit)?;
+        let matrices = cs.to_matrices().ok_or(SynthesisError::AssignmentMissing)?;
+        drop(cs);
+        let t = Self::sample_evaluation_point(&matrices, rng)?;
+        let num_constraints = matrices.num_constraints;
+        let num_instance_variables = matrices.num_instance_variables;
+        let num_witness_variables = matrices.num_witness_variables;
+
+        let domain = GeneralEvaluationDomain::<E::ScalarField>::new(
+            num_constraints + num_instance_variables,
+        )
+        .ok_or(SynthesisError::PolynomialDegreeTooLarge)?;
+        let mut lagrange = domain.evaluate_all_lagrange_coefficients(t);
+        lagrange.truncate(num_constraints + num_instance_variables);
+        let lagrange_g1 = cfg_iter!(lagrange)
+            .map(|l| g1_generator.mul(l))
+            .collect::<Vec<_>>();
+        let lagrange_g2 = cfg_iter!(lagrange[..num_constraints])
+            .map(|l| g2_generator.mul(l))
+            .collect::<Vec<_>>();
+
+        let pk = Self::generate_parameters_with_evaluation_point(
+            &matrices,
+            t,
+            alpha,
+            beta,
+            gamma,
+            delta,
+            num_static_inputs,
+            g1_generator,
+            g2_generator,
+            h_basis,
+            &mut (),
+        )?;
+
+        Ok(CeremonyArtifact {
+            vk: pk.vk,
+            beta_g1: pk.beta_g1,
+            delta_g1: pk.delta_g1,
+            lagrange_g1: E::G1::normalize_batch(&lagrange_g1),
+            lagrange_g2: E::G2::normalize_batch(&lagrange_g2),
+            num_instance_variables,
+            num_witness_variables,
+            a: matrices.a,
+            b: matrices.b,
+            h_basis: pk.h_basis,
+            h_query: pk.h_query,
+            l_query: pk.l_query,
+        })
+    }
+}
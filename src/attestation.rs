@@ -0,0 +1,493 @@
+use crate::{bundle::VkFingerprint, hash::hash_compressed, Groth16, ProvingKey, VerifyingKey};
+use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup};
+use ark_ff::{PrimeField, UniformRand, Zero};
+use ark_relations::r1cs::{Result as R1CSResult, SynthesisError};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::{fmt, rand::Rng, vec::Vec};
+use sha2::{Digest, Sha256};
+use zeroize::Zeroize;
+
+/// Domain separator of the signatures of participants and coordinators.
+pub const SIGNATURE_LABEL: &[u8] = b"darklake-groth16/setup-signature";
+
+/// Domain separator of the hashes of the contributions to a setup.
+pub const CONTRIBUTION_LABEL: &[u8] = b"darklake-groth16/setup-contribution";
+
+/// Domain separator of the factor derived from the beacon of a setup.
+pub const BEACON_LABEL: &[u8] = b"darklake-groth16/setup-beacon";
+
+/// The hash of the state of a setup after a contribution, chaining all the
+/// contributions before it.
+pub type ContributionHash = [u8; 32];
+
+/// An error when verifying a [`SetupAttestation`], or when recording a
+/// contribution to a [`Ceremony`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AttestationError {
+    /// The `delta` after a contribution is not the `delta` before it times
+    /// the factor of the contribution.
+    BrokenChain {
+        /// The position of the contribution.
+        index: usize,
+    },
+    /// The signature of a contribution does not verify under the key of
+    /// its participant.
+    InvalidContributionSignature {
+        /// The position of the contribution.
+        index: usize,
+    },
+    /// The `delta` after the beacon is not derived from the beacon value.
+    InvalidBeacon,
+    /// The keys are not the keys of the final state of the setup.
+    KeyMismatch,
+    /// The signature of the attestation does not verify under the key of
+    /// its coordinator.
+    InvalidSignature,
+}
+
+impl fmt::Display for AttestationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AttestationError::BrokenChain { index } => {
+                write!(
+                    f,
+                    "contribution {} does not follow the setup before it",
+                    index
+                )
+            },
+            AttestationError::InvalidContributionSignature { index } => {
+                write!(f, "invalid signature of contribution {}", index)
+            },
+            AttestationError::InvalidBeacon => write!(f, "beacon does not derive the final delta"),
+            AttestationError::KeyMismatch => {
+                write!(f, "keys are not the keys of the attested setup")
+            },
+            AttestationError::InvalidSignature => write!(f, "invalid signature of attestation"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for AttestationError {}
+
+/// A Schnorr signature in `G1`, with the generator of `G1` as base.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct Signature<E: Pairing> {
+    /// The commitment `k G1` to the nonce `k`.
+    pub r: E::G1Affine,
+    /// The response `k + c x`, for the challenge `c` and the secret key `x`.
+    pub s: E::ScalarField,
+}
+
+/// The secret key of a participant or of the coordinator of a setup, which
+/// signs their contributions and attestations. It is zeroized when
+/// dropped.
+pub struct SigningKey<E: Pairing>(E::ScalarField);
+
+impl<E: Pairing> SigningKey<E> {
+    /// Creates a key from its secret scalar.
+    pub fn new(secret: E::ScalarField) -> Self {
+        Self(secret)
+    }
+
+    /// Samples a key.
+    pub fn rand(rng: &mut impl Rng) -> Self {
+        Self(E::ScalarField::rand(rng))
+    }
+
+    /// Returns the public key `x G1` of the secret key `x`.
+    pub fn public_key(&self) -> E::G1Affine {
+        (E::G1Affine::generator() * self.0).into_affine()
+    }
+
+    /// Signs `message`, with a nonce sampled via `rng`.
+    pub fn sign(&self, message: &[u8], rng: &mut impl Rng) -> Signature<E> {
+        let mut nonce = E::ScalarField::rand(rng);
+        let r = (E::G1Affine::generator() * nonce).into_affine();
+        let c = challenge::<E>(&r, &self.public_key(), message);
+        let s = nonce + c * self.0;
+        nonce.zeroize();
+        Signature { r, s }
+    }
+}
+
+impl<E: Pairing> Drop for SigningKey<E> {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+/// Returns the challenge of a signature of `message` under `public_key`
+/// with the nonce commitment `r`.
+fn challenge<E: Pairing>(
+    r: &E::G1Affine,
+    public_key: &E::G1Affine,
+    message: &[u8],
+) -> E::ScalarField {
+    let mut bytes = SIGNATURE_LABEL.to_vec();
+    (*r, *public_key)
+        .serialize_compressed(&mut bytes)
+        .expect("serializing into a vector cannot fail");
+    bytes.extend_from_slice(message);
+    E::ScalarField::from_le_bytes_mod_order(&Sha256::digest(&bytes))
+}
+
+/// Returns whether `signature` is a signature of `message` under
+/// `public_key`.
+pub fn verify_signature<E: Pairing>(
+    public_key: &E::G1Affine,
+    message: &[u8],
+    signature: &Signature<E>,
+) -> bool {
+    if public_key.is_zero() {
+        return false;
+    }
+    let c = challenge::<E>(&signature.r, public_key, message);
+    E::G1Affine::generator() * signature.s == signature.r.into_group() + *public_key * c
+}
+
+/// Returns the hash of the state of a setup before any contribution.
+fn initial_hash<E: Pairing>(delta_g1: &E::G1Affine) -> ContributionHash {
+    chain_hash(&[0u8; 32], delta_g1)
+}
+
+/// Returns the hash chaining `value` to the `previous` hash.
+fn chain_hash(previous: &ContributionHash, value: &impl CanonicalSerialize) -> ContributionHash {
+    let mut bytes = CONTRIBUTION_LABEL.to_vec();
+    bytes.extend_from_slice(previous);
+    value
+        .serialize_compressed(&mut bytes)
+        .expect("serializing into a vector cannot fail");
+    Sha256::digest(&bytes).into()
+}
+
+/// A contribution of a participant to the second phase of a setup, which
+/// multiplies its `delta` by a secret factor.
+///
+/// The factor is only published as `factor H`, for the generator `H` of
+/// `G2`, which lets auditors check that the `delta` after the contribution
+/// is a multiple of the `delta` before it. The setup is sound as long as
+/// one participant forgot their factor.
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct Contribution<E: Pairing> {
+    /// The public key of the participant.
+    pub participant: E::G1Affine,
+    /// The element `delta G` after the contribution.
+    pub delta_g1: E::G1Affine,
+    /// The element `factor H`.
+    pub factor_g2: E::G2Affine,
+    /// The signature by the participant of the hash of the contribution.
+    pub signature: Signature<E>,
+}
+
+impl<E: Pairing> Contribution<E> {
+    /// Contributes to the setup of `pk`, after the contribution with hash
+    /// `previous`, with a factor sampled via `rng`. Returns the proving key
+    /// after the contribution, whose verifying key has the new `delta`.
+    pub fn create(
+        pk: &ProvingKey<E>,
+        previous: &ContributionHash,
+        participant: &SigningKey<E>,
+        rng: &mut impl Rng,
+    ) -> R1CSResult<(ProvingKey<E>, Self)> {
+        let mut factor = E::ScalarField::rand(rng);
+        let next = Groth16::<E>::rotate_delta_by(pk, factor);
+        let factor_g2 = (E::G2Affine::generator() * factor).into_affine();
+        factor.zeroize();
+        let next = next?;
+
+        let mut contribution = Self {
+            participant: participant.public_key(),
+            delta_g1: next.delta_g1,
+            factor_g2,
+            signature: Signature {
+                r: E::G1Affine::zero(),
+                s: E::ScalarField::zero(),
+            },
+        };
+        contribution.signature = participant.sign(&contribution.hash(previous), rng);
+        Ok((next, contribution))
+    }
+
+    /// Returns the hash of the contribution after the contribution with
+    /// hash `previous`, which is signed by the participant.
+    pub fn hash(&self, previous: &ContributionHash) -> ContributionHash {
+        chain_hash(previous, &(self.participant, self.delta_g1, self.factor_g2))
+    }
+
+    /// Checks that the contribution follows a setup with `delta_g1`,
+    /// after the contribution with hash `previous`.
+    fn check(
+        &self,
+        delta_g1: &E::G1Affine,
+        previous: &ContributionHash,
+        index: usize,
+    ) -> Result<(), AttestationError> {
+        if self.delta_g1.is_zero()
+            || self.factor_g2.is_zero()
+            || E::pairing(self.delta_g1, E::G2Affine::generator())
+                != E::pairing(*delta_g1, self.factor_g2)
+        {
+            return Err(AttestationError::BrokenChain { index });
+        }
+        if !verify_signature(&self.participant, &self.hash(previous), &self.signature) {
+            return Err(AttestationError::InvalidContributionSignature { index });
+        }
+        Ok(())
+    }
+}
+
+/// The last contribution to a setup, with a factor derived from a public
+/// beacon value unknown before the other contributions, e.g. the hash of a
+/// future block, so that no participant chose the final `delta`.
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct BeaconContribution<E: Pairing> {
+    /// The beacon value.
+    pub beacon: Vec<u8>,
+    /// The element `delta G` after the contribution.
+    pub delta_g1: E::G1Affine,
+}
+
+/// Returns the factor derived from `beacon` after the contribution with
+/// hash `previous`.
+fn beacon_factor<F: PrimeField>(previous: &ContributionHash, beacon: &[u8]) -> F {
+    let mut bytes = BEACON_LABEL.to_vec();
+    bytes.extend_from_slice(previous);
+    bytes.extend_from_slice(beacon);
+    F::from_le_bytes_mod_order(&Sha256::digest(&bytes))
+}
+
+/// An attestation of the second phase of a setup, for third parties to
+/// audit the keys it produced without the tooling of the ceremony.
+///
+/// It lists the contributions from the `delta` of the initial keys to the
+/// `delta` of the final keys, then the beacon, and the fingerprints of the
+/// final keys, all signed by the coordinator of the ceremony. Auditors
+/// compare the [`SetupAttestation::contribution_hashes`] with the hashes
+/// published by the participants, and the coordinator key with the one
+/// they expect, then check the attestation against the keys they were
+/// given with [`SetupAttestation::verify`].
+///
+/// Only the `delta` of the setup is attested: the other secrets come from
+/// the first phase of the setup, and the `h_query` and `l_query` of the
+/// keys are only checked through their fingerprint.
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct SetupAttestation<E: Pairing> {
+    /// The element `delta G` of the initial keys.
+    pub initial_delta_g1: E::G1Affine,
+    /// The contributions of the participants, in order.
+    pub contributions: Vec<Contribution<E>>,
+    /// The beacon contribution, if any.
+    pub beacon: Option<BeaconContribution<E>>,
+    /// The element `beta G` of the final proving key, which links the
+    /// final `delta G` to the `delta H` of the verifying key.
+    pub beta_g1: E::G1Affine,
+    /// The fingerprint of the final verifying key.
+    pub vk_fingerprint: VkFingerprint,
+    /// The SHA-256 hash of the compressed serialization of the final
+    /// proving key.
+    pub pk_fingerprint: [u8; 32],
+    /// The public key of the coordinator.
+    pub coordinator: E::G1Affine,
+    /// The signature by the coordinator of [`SetupAttestation::digest`].
+    pub signature: Signature<E>,
+}
+
+impl<E: Pairing> SetupAttestation<E> {
+    /// Returns the hashes of the contributions, in order, followed by the
+    /// hash of the beacon contribution, if any.
+    pub fn contribution_hashes(&self) -> Vec<ContributionHash> {
+        let mut hash = initial_hash::<E>(&self.initial_delta_g1);
+        let mut hashes = Vec::with_capacity(self.contributions.len() + 1);
+        for contribution in &self.contributions {
+            hash = contribution.hash(&hash);
+            hashes.push(hash);
+        }
+        if let Some(beacon) = &self.beacon {
+            hashes.push(chain_hash(&hash, beacon));
+        }
+        hashes
+    }
+
+    /// Returns the element `delta G` of the final keys.
+    pub fn final_delta_g1(&self) -> E::G1Affine {
+        match (&self.beacon, self.contributions.last()) {
+            (Some(beacon), _) => beacon.delta_g1,
+            (None, Some(contribution)) => contribution.delta_g1,
+            (None, None) => self.initial_delta_g1,
+        }
+    }
+
+    /// Returns the hash of everything but the signature, which is signed by
+    /// the coordinator.
+    pub fn digest(&self) -> [u8; 32] {
+        let mut bytes = Vec::new();
+        self.initial_delta_g1
+            .serialize_compressed(&mut bytes)
+            .and_then(|()| self.contributions.serialize_compressed(&mut bytes))
+            .and_then(|()| self.beacon.serialize_compressed(&mut bytes))
+            .and_then(|()| {
+                (
+                    self.beta_g1,
+                    self.vk_fingerprint,
+                    self.pk_fingerprint,
+                    self.coordinator,
+                )
+                    .serialize_compressed(&mut bytes)
+            })
+            .expect("serializing into a vector cannot fail");
+        Sha256::digest(&bytes).into()
+    }
+
+    /// Verifies the attestation of the setup of `vk`: the contributions
+    /// chain from the initial `delta` to the `delta` of `vk`, each signed by
+    /// its participant, the beacon derives the final `delta`, and the
+    /// coordinator signed the attestation.
+    pub fn verify(&self, vk: &VerifyingKey<E>) -> Result<(), AttestationError> {
+        let mut delta_g1 = self.initial_delta_g1;
+        let mut hash = initial_hash::<E>(&delta_g1);
+        for (index, contribution) in self.contributions.iter().enumerate() {
+            contribution.check(&delta_g1, &hash, index)?;
+            delta_g1 = contribution.delta_g1;
+            hash = contribution.hash(&hash);
+        }
+        if let Some(beacon) = &self.beacon {
+            let factor = beacon_factor::<E::ScalarField>(&hash, &beacon.beacon);
+            if (delta_g1 * factor).into_affine() != beacon.delta_g1 {
+                return Err(AttestationError::InvalidBeacon);
+            }
+        }
+
+        if vk.fingerprint() != self.vk_fingerprint
+            || E::pairing(self.final_delta_g1(), vk.beta_g2)
+                != E::pairing(self.beta_g1, vk.delta_g2)
+        {
+            return Err(AttestationError::KeyMismatch);
+        }
+        if !verify_signature(&self.coordinator, &self.digest(), &self.signature) {
+            return Err(AttestationError::InvalidSignature);
+        }
+        Ok(())
+    }
+
+    /// Verifies the attestation of the setup of `pk` and of its verifying
+    /// key, see [`Self::verify`].
+    pub fn verify_with_proving_key(&self, pk: &ProvingKey<E>) -> Result<(), AttestationError> {
+        if hash_compressed(pk) != self.pk_fingerprint
+            || pk.delta_g1 != self.final_delta_g1()
+            || pk.beta_g1 != self.beta_g1
+        {
+            return Err(AttestationError::KeyMismatch);
+        }
+        self.verify(&pk.vk)
+    }
+}
+
+/// The coordinator of the second phase of a setup, which records the
+/// contributions to the keys of a circuit and attests them once the setup
+/// is finalized.
+#[derive(Clone, Debug)]
+pub struct Ceremony<E: Pairing> {
+    initial_delta_g1: E::G1Affine,
+    delta_g1: E::G1Affine,
+    hash: ContributionHash,
+    contributions: Vec<Contribution<E>>,
+}
+
+impl<E: Pairing> Ceremony<E> {
+    /// Starts a ceremony contributing to the initial keys `pk`.
+    pub fn new(pk: &ProvingKey<E>) -> Self {
+        Self {
+            initial_delta_g1: pk.delta_g1,
+            delta_g1: pk.delta_g1,
+            hash: initial_hash::<E>(&pk.delta_g1),
+            contributions: Vec::new(),
+        }
+    }
+
+    /// Returns the hash of the last contribution, which the next
+    /// participant contributes after.
+    pub fn hash(&self) -> ContributionHash {
+        self.hash
+    }
+
+    /// Returns the number of recorded contributions.
+    pub fn num_contributions(&self) -> usize {
+        self.contributions.len()
+    }
+
+    /// Records a contribution created by a participant with
+    /// [`Contribution::create`] after the last recorded contribution.
+    pub fn record(&mut self, contribution: Contribution<E>) -> Result<(), AttestationError> {
+        contribution.check(&self.delta_g1, &self.hash, self.contributions.len())?;
+        self.delta_g1 = contribution.delta_g1;
+        self.hash = contribution.hash(&self.hash);
+        self.contributions.push(contribution);
+        Ok(())
+    }
+
+    /// Contributes to `pk`, the keys after the last recorded contribution,
+    /// as `participant`, and records the contribution. Returns the keys
+    /// after the contribution.
+    pub fn contribute(
+        &mut self,
+        pk: &ProvingKey<E>,
+        participant: &SigningKey<E>,
+        rng: &mut impl Rng,
+    ) -> R1CSResult<ProvingKey<E>> {
+        if pk.delta_g1 != self.delta_g1 {
+            return Err(SynthesisError::MalformedVerifyingKey);
+        }
+        let (next, contribution) = Contribution::create(pk, &self.hash, participant, rng)?;
+        self.record(contribution)
+            .map_err(|_| SynthesisError::MalformedVerifyingKey)?;
+        Ok(next)
+    }
+
+    /// Finalizes the setup of `pk`, the keys after the last recorded
+    /// contribution: applies the contribution of `beacon`, if any, and
+    /// returns the final proving key with the attestation of the setup,
+    /// signed by `coordinator`.
+    ///
+    /// Fails with [`SynthesisError::MalformedVerifyingKey`] if `pk` is not
+    /// the keys after the last recorded contribution.
+    pub fn finalize(
+        self,
+        pk: &ProvingKey<E>,
+        beacon: Option<&[u8]>,
+        coordinator: &SigningKey<E>,
+        rng: &mut impl Rng,
+    ) -> R1CSResult<(ProvingKey<E>, SetupAttestation<E>)> {
+        if pk.delta_g1 != self.delta_g1 {
+            return Err(SynthesisError::MalformedVerifyingKey);
+        }
+        let (pk, beacon) = match beacon {
+            Some(beacon) => {
+                let factor = beacon_factor(&self.hash, beacon);
+                let pk = Groth16::<E>::rotate_delta_by(pk, factor)?;
+                let contribution = BeaconContribution {
+                    beacon: beacon.to_vec(),
+                    delta_g1: pk.delta_g1,
+                };
+                (pk, Some(contribution))
+            },
+            None => (pk.clone(), None),
+        };
+
+        let mut attestation = SetupAttestation {
+            initial_delta_g1: self.initial_delta_g1,
+            contributions: self.contributions,
+            beacon,
+            beta_g1: pk.beta_g1,
+            vk_fingerprint: pk.vk.fingerprint(),
+            pk_fingerprint: hash_compressed(&pk),
+            coordinator: coordinator.public_key(),
+            signature: Signature {
+                r: E::G1Affine::zero(),
+                s: E::ScalarField::zero(),
+            },
+        };
+        attestation.signature = coordinator.sign(&attestation.digest(), rng);
+        Ok((pk, attestation))
+    }
+}
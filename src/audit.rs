@@ -0,0 +1,179 @@
+use ark_ff::Field;
+use ark_relations::r1cs::{
+    ConstraintMatrices, ConstraintSynthesizer, ConstraintSystem, OptimizationGoal,
+    Result as R1CSResult, SynthesisError, SynthesisMode,
+};
+use ark_std::{fmt, string::String, vec, vec::Vec};
+
+/// The witness variables linked by the constraints to a public input.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct InputInfluence {
+    /// The index of the public input among the instance variables, where
+    /// `0` is the constant one.
+    pub input: usize,
+    /// The indices of the constraints involving the input.
+    pub constraints: Vec<usize>,
+    /// The namespace paths of these constraints, if constraint traces were
+    /// recorded during synthesis.
+    pub labels: Vec<String>,
+    /// The indices of the witness variables sharing a constraint with the
+    /// input.
+    pub direct: Vec<usize>,
+    /// The indices of the witness variables linked to the input through a
+    /// chain of constraints over witness variables, including the direct
+    /// ones. Only these can influence the value of the input.
+    pub reachable: Vec<usize>,
+}
+
+/// A report of which witness variables influence each public input of a
+/// circuit, see [`audit_public_inputs`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct InputAudit {
+    /// The number of witness variables of the circuit.
+    pub num_witness_variables: usize,
+    /// The influences on each public input, in order, without the constant
+    /// one.
+    pub inputs: Vec<InputInfluence>,
+}
+
+impl InputAudit {
+    /// Returns the public inputs which no constraint involves. Their value
+    /// is not bound to the witness, and a prover can set them freely.
+    pub fn unconstrained_inputs(&self) -> impl Iterator<Item = usize> + '_ {
+        self.inputs
+            .iter()
+            .filter(|influence| influence.constraints.is_empty())
+            .map(|influence| influence.input)
+    }
+}
+
+impl fmt::Display for InputAudit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for influence in &self.inputs {
+            write!(f, "input {}: ", influence.input)?;
+            if influence.constraints.is_empty() {
+                writeln!(f, "unconstrained")?;
+                continue;
+            }
+            writeln!(
+                f,
+                "{} constraints, {} direct and {} of {} witness variables reachable",
+                influence.constraints.len(),
+                influence.direct.len(),
+                influence.reachable.len(),
+                self.num_witness_variables
+            )?;
+            for label in &influence.labels {
+                writeln!(f, "  {}", label)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns the representative of the set of `x`, halving paths on the way.
+fn find(parents: &mut [usize], mut x: usize) -> usize {
+    while parents[x] != x {
+        parents[x] = parents[parents[x]];
+        x = parents[x];
+    }
+    x
+}
+
+/// Reports which witness variables the constraints of `matrices` link to
+/// each public input.
+///
+/// Two witness variables are linked when they appear in a common
+/// constraint, and a witness variable is linked to an input when it
+/// appears in a constraint with the input. Links do not go through other
+/// public inputs, nor the constant one, which appears in most constraints.
+pub fn audit_matrices<F: Field>(matrices: &ConstraintMatrices<F>) -> InputAudit {
+    let num_instance = matrices.num_instance_variables;
+    let num_witness = matrices.num_witness_variables;
+    let mut parents: Vec<usize> = (0..num_witness).collect();
+    let mut inputs: Vec<InputInfluence> = (1..num_instance)
+        .map(|input| InputInfluence {
+            input,
+            ..Default::default()
+        })
+        .collect();
+
+    for constraint in 0..matrices.num_constraints {
+        let variables = || {
+            [&matrices.a, &matrices.b, &matrices.c]
+                .into_iter()
+                .flat_map(move |matrix| matrix[constraint].iter().map(|(_, variable)| *variable))
+        };
+        let mut witnesses: Vec<usize> = variables()
+            .filter(|variable| *variable >= num_instance)
+            .map(|variable| variable - num_instance)
+            .collect();
+        witnesses.sort_unstable();
+        witnesses.dedup();
+
+        if let Some((&first, rest)) = witnesses.split_first() {
+            for &witness in rest {
+                let (a, b) = (find(&mut parents, first), find(&mut parents, witness));
+                parents[a] = b;
+            }
+        }
+        for variable in variables().filter(|variable| (1..num_instance).contains(variable)) {
+            let influence = &mut inputs[variable - 1];
+            if influence.constraints.last() != Some(&constraint) {
+                influence.constraints.push(constraint);
+                influence.direct.extend_from_slice(&witnesses);
+            }
+        }
+    }
+
+    for influence in &mut inputs {
+        influence.direct.sort_unstable();
+        influence.direct.dedup();
+        let mut linked = vec![false; num_witness];
+        for &witness in &influence.direct {
+            let root = find(&mut parents, witness);
+            linked[root] = true;
+        }
+        influence.reachable = (0..num_witness)
+            .filter(|&witness| linked[find(&mut parents, witness)])
+            .collect();
+    }
+
+    InputAudit {
+        num_witness_variables: num_witness,
+        inputs,
+    }
+}
+
+/// Synthesizes `circuit` without a witness and reports which of its witness
+/// variables influence each of its public inputs, so that circuit authors
+/// can check that only intended values become public before running a
+/// setup ceremony.
+///
+/// Constraint labels are recorded if constraint traces are enabled, e.g.
+/// through a `ConstraintLayer` of `ark-relations` in a tracing subscriber.
+pub fn audit_public_inputs<F, C>(circuit: C) -> R1CSResult<InputAudit>
+where
+    F: Field,
+    C: ConstraintSynthesizer<F>,
+{
+    let cs = ConstraintSystem::new_ref();
+    cs.set_optimization_goal(OptimizationGoal::Constraints);
+    cs.set_mode(SynthesisMode::Setup);
+    circuit.generate_constraints(cs.clone())?;
+    cs.finalize();
+
+    let matrices = cs.to_matrices().ok_or(SynthesisError::AssignmentMissing)?;
+    let mut audit = audit_matrices(&matrices);
+    if let Some(names) = cs.constraint_names() {
+        for influence in &mut audit.inputs {
+            influence.labels = influence
+                .constraints
+                .iter()
+                .filter_map(|constraint| names.get(*constraint).cloned())
+                .collect();
+        }
+    }
+
+    Ok(audit)
+}
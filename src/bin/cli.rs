@@ -0,0 +1,621 @@
+//! A command-line interface to the library, for ad-hoc operations on keys,
+//! proofs and public inputs without writing Rust.
+//!
+//! Keys are stored in the compressed serialization of arkworks, and proofs
+//! and public inputs in the checksummed text encoding of the `text` module.
+//! The curve of a key is detected when it is loaded.
+use ark_bls12_377::Bls12_377;
+use ark_bls12_381::Bls12_381;
+use ark_bn254::Bn254;
+use ark_ec::pairing::Pairing;
+use ark_ff::PrimeField;
+use ark_serialize::CanonicalSerialize;
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+use darklake_groth16::{
+    any_curve::{AnyGroth16, Curve, CurveKeys},
+    inputs::{Endianness, PublicInputs},
+    loader::load_verifying_key,
+    onchain::{
+        borsh_encode_verifying_key, evm_verifying_key_commitment, solana_verifying_key_commitment,
+        ProofWireFormat,
+    },
+    reference::{self, Pool, Trade},
+    snarkjs,
+    testing::SyntheticCircuit,
+    text::{curve_tag, inputs_from_hex, inputs_to_hex},
+    Groth16, PreparedVerifyingKey, Proof, ProvingKey, VerifyingKey,
+};
+use std::{
+    collections::BTreeMap,
+    env,
+    fmt::Display,
+    fs::{self, File},
+    io::Read,
+    path::{Path, PathBuf},
+    process::ExitCode,
+    str::FromStr,
+};
+
+const USAGE: &str = "\
+usage: darklake-groth16-cli <command> [options]
+
+commands:
+  setup --curve <curve> --circuit <circuit> --pk <file> --vk <file> [--seed <n>]
+      generate the keys of a circuit, from a single-party setup for tests
+      and staging environments
+  prove --pk <file> --circuit <circuit> --proof <file> --inputs <file> [--seed <n>]
+      prove a statement of a circuit with its proving key
+  verify --vk <file> --proof <file> --inputs <file>
+      verify a proof, exiting with status 1 if it is invalid
+  convert --from <format> --to <format> --out-dir <dir>
+          [--vk <file>] [--proof <file>] [--inputs <file>] [--curve <curve>]
+      convert a verifying key, a proof and public inputs between formats
+  inspect vk <file>
+      print the shape and the fingerprints of a verifying key
+
+curves: bn254, bls12-381, bls12-377
+
+circuits:
+  synthetic --constraints <n> [--public <n>] [--density <n>] [--depth <n>]
+            [--circuit-seed <n>] [--assignment <n>]
+      a synthetic circuit of the given shape, proven for the leaf values
+      drawn from the assignment seed
+  swap [--reserve-in <n> --reserve-out <n> --fee-bps <n>
+        --amount-in <n> --min-amount-out <n>]
+      the reference swap in a constant product pool
+
+formats:
+  arkworks  vk.bin, proof.txt and inputs.txt, as used by the other commands
+  snarkjs   verification_key.json, proof.json and public.json
+  solana    verifying_key.bin, proof.bin and public_inputs.bin, for the
+            alt_bn128 syscalls, with A negated (output only, BN254 only)
+
+--seed makes the randomness reproducible, e.g. in tests; it must not be used
+for keys or proofs protecting anything.";
+
+/// The result of a command, with errors reported as messages.
+type CliResult<T> = Result<T, String>;
+
+/// Returns a function prefixing errors with `context`.
+fn context<E: Display>(context: impl Display) -> impl FnOnce(E) -> String {
+    move |e| format!("{}: {}", context, e)
+}
+
+/// The options and positional arguments of a command.
+struct Args {
+    positional: Vec<String>,
+    options: BTreeMap<String, String>,
+}
+
+impl Args {
+    /// Parses arguments, which are all `--name value` options but for the
+    /// positional ones.
+    fn parse(args: impl IntoIterator<Item = String>) -> CliResult<Self> {
+        let mut positional = Vec::new();
+        let mut options = BTreeMap::new();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.strip_prefix("--") {
+                Some(name) => {
+                    let value = args
+                        .next()
+                        .ok_or_else(|| format!("missing value of --{}", name))?;
+                    if options.insert(String::from(name), value).is_some() {
+                        return Err(format!("--{} given twice", name));
+                    }
+                },
+                None => positional.push(arg),
+            }
+        }
+        Ok(Self {
+            positional,
+            options,
+        })
+    }
+
+    fn take(&mut self, name: &str) -> Option<String> {
+        self.options.remove(name)
+    }
+
+    fn required(&mut self, name: &str) -> CliResult<String> {
+        self.take(name)
+            .ok_or_else(|| format!("missing option --{}", name))
+    }
+
+    fn number<T: FromStr>(&mut self, name: &str) -> CliResult<Option<T>>
+    where
+        T::Err: Display,
+    {
+        self.take(name)
+            .map(|value| value.parse().map_err(context(format!("--{}", name))))
+            .transpose()
+    }
+
+    fn required_number<T: FromStr>(&mut self, name: &str) -> CliResult<T>
+    where
+        T::Err: Display,
+    {
+        self.number(name)?
+            .ok_or_else(|| format!("missing option --{}", name))
+    }
+
+    /// Fails if arguments were not used by the command.
+    fn finish(self) -> CliResult<()> {
+        if let Some(name) = self.options.keys().next() {
+            return Err(format!("unexpected option --{}", name));
+        }
+        if let Some(arg) = self.positional.first() {
+            return Err(format!("unexpected argument {}", arg));
+        }
+        Ok(())
+    }
+}
+
+/// Calls a function generic over the pairing with the pairing of `curve`.
+macro_rules! with_curve {
+    ($curve:expr, $function:ident($($arg:expr),*)) => {
+        match $curve {
+            Curve::Bn254 => $function::<Bn254>($($arg),*),
+            Curve::Bls12_381 => $function::<Bls12_381>($($arg),*),
+            Curve::Bls12_377 => $function::<Bls12_377>($($arg),*),
+        }
+    };
+}
+
+/// Calls a function generic over the pairing with the keys of `any`.
+macro_rules! with_keys {
+    ($any:expr, $function:ident($keys:ident $(, $arg:expr)*)) => {
+        match $any {
+            AnyGroth16::Bn254($keys) => $function::<Bn254>($keys $(, $arg)*),
+            AnyGroth16::Bls12_381($keys) => $function::<Bls12_381>($keys $(, $arg)*),
+            AnyGroth16::Bls12_377($keys) => $function::<Bls12_377>($keys $(, $arg)*),
+        }
+    };
+}
+
+fn parse_curve(name: &str) -> CliResult<Curve> {
+    match name {
+        "bn254" | "bn128" => Ok(Curve::Bn254),
+        "bls12-381" | "bls12381" => Ok(Curve::Bls12_381),
+        "bls12-377" | "bls12377" => Ok(Curve::Bls12_377),
+        _ => Err(format!("unknown curve {}", name)),
+    }
+}
+
+fn read(path: &Path) -> CliResult<Vec<u8>> {
+    fs::read(path).map_err(context(path.display()))
+}
+
+fn read_text(path: &Path) -> CliResult<String> {
+    fs::read_to_string(path).map_err(context(path.display()))
+}
+
+fn write(path: &Path, contents: impl AsRef<[u8]>) -> CliResult<()> {
+    fs::write(path, contents).map_err(context(path.display()))?;
+    println!("wrote {}", path.display());
+    Ok(())
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Returns a generator seeded with `--seed`, or else from the entropy of
+/// the operating system.
+fn rng(args: &mut Args) -> CliResult<StdRng> {
+    if let Some(seed) = args.number("seed")? {
+        return Ok(StdRng::seed_from_u64(seed));
+    }
+    let mut seed = [0u8; 32];
+    File::open("/dev/urandom")
+        .and_then(|mut urandom| urandom.read_exact(&mut seed))
+        .map_err(context("cannot read /dev/urandom, pass --seed"))?;
+    Ok(StdRng::from_seed(seed))
+}
+
+/// The shape of a [`SyntheticCircuit`].
+#[derive(Clone, Copy)]
+struct SyntheticShape {
+    constraints: usize,
+    public: usize,
+    density: usize,
+    depth: usize,
+    seed: u64,
+}
+
+impl SyntheticShape {
+    fn circuit<F: PrimeField>(&self) -> SyntheticCircuit<F> {
+        SyntheticCircuit::blank(self.constraints)
+            .with_inputs(self.public)
+            .with_density(self.density)
+            .with_depth(self.depth)
+            .with_seed(self.seed)
+    }
+}
+
+/// The options of the witness of the swap circuit.
+const SWAP_OPTIONS: [&str; 5] = [
+    "reserve-in",
+    "reserve-out",
+    "fee-bps",
+    "amount-in",
+    "min-amount-out",
+];
+
+/// A circuit selected with `--circuit`, with its witness if given.
+enum CircuitSpec {
+    Synthetic(SyntheticShape, Option<u64>),
+    Swap(Option<(Pool, Trade)>),
+}
+
+impl CircuitSpec {
+    fn parse(args: &mut Args) -> CliResult<Self> {
+        match args.required("circuit")?.as_str() {
+            "synthetic" => {
+                let shape = SyntheticShape {
+                    constraints: args.required_number("constraints")?,
+                    public: args.number("public")?.unwrap_or(1),
+                    density: args.number("density")?.unwrap_or(1),
+                    depth: args.number("depth")?.unwrap_or(1),
+                    seed: args.number("circuit-seed")?.unwrap_or(0),
+                };
+                Ok(CircuitSpec::Synthetic(shape, args.number("assignment")?))
+            },
+            "swap" => {
+                if SWAP_OPTIONS
+                    .iter()
+                    .all(|name| !args.options.contains_key(*name))
+                {
+                    return Ok(CircuitSpec::Swap(None));
+                }
+                let pool = Pool {
+                    reserve_in: args.required_number("reserve-in")?,
+                    reserve_out: args.required_number("reserve-out")?,
+                    fee_bps: args.required_number("fee-bps")?,
+                };
+                let trade = Trade {
+                    amount_in: args.required_number("amount-in")?,
+                    min_amount_out: args.required_number("min-amount-out")?,
+                };
+                Ok(CircuitSpec::Swap(Some((pool, trade))))
+            },
+            circuit => Err(format!("unknown circuit {}", circuit)),
+        }
+    }
+}
+
+fn setup_keys<E: Pairing>(circuit: &CircuitSpec, rng: &mut StdRng) -> CliResult<ProvingKey<E>> {
+    let pk = match circuit {
+        CircuitSpec::Synthetic(shape, _) => {
+            Groth16::<E>::generate_random_parameters_with_reduction(
+                shape.circuit::<E::ScalarField>(),
+                rng,
+                1,
+                shape.public,
+            )
+        },
+        CircuitSpec::Swap(_) => reference::setup::<E>(rng).map(|(pk, _)| pk),
+    };
+    pk.map_err(context("setup failed"))
+}
+
+fn setup(mut args: Args) -> CliResult<ExitCode> {
+    let curve = parse_curve(&args.required("curve")?)?;
+    let circuit = CircuitSpec::parse(&mut args)?;
+    let pk_path = PathBuf::from(args.required("pk")?);
+    let vk_path = PathBuf::from(args.required("vk")?);
+    let mut rng = rng(&mut args)?;
+    args.finish()?;
+
+    fn keys<E: Pairing>(circuit: &CircuitSpec, rng: &mut StdRng) -> CliResult<(Vec<u8>, Vec<u8>)> {
+        let pk = setup_keys::<E>(circuit, rng)?;
+        let (mut pk_bytes, mut vk_bytes) = (Vec::new(), Vec::new());
+        pk.serialize_compressed(&mut pk_bytes)
+            .and_then(|_| pk.vk.serialize_compressed(&mut vk_bytes))
+            .map_err(context("cannot serialize the keys"))?;
+        Ok((pk_bytes, vk_bytes))
+    }
+    let (pk, vk) = with_curve!(curve, keys(&circuit, &mut rng))?;
+    write(&pk_path, pk)?;
+    write(&vk_path, vk)?;
+    Ok(ExitCode::SUCCESS)
+}
+
+fn prove(mut args: Args) -> CliResult<ExitCode> {
+    let pk_path = PathBuf::from(args.required("pk")?);
+    let circuit = CircuitSpec::parse(&mut args)?;
+    let proof_path = PathBuf::from(args.required("proof")?);
+    let inputs_path = PathBuf::from(args.required("inputs")?);
+    let mut rng = rng(&mut args)?;
+    args.finish()?;
+
+    fn prove_with<E: Pairing>(
+        keys: &CurveKeys<E>,
+        circuit: &CircuitSpec,
+        rng: &mut StdRng,
+    ) -> CliResult<(String, String)> {
+        let pk = keys.pk.as_ref().expect("proving keys are loaded");
+        let (proof, inputs) = match circuit {
+            CircuitSpec::Synthetic(shape, assignment) => {
+                let assignment = assignment.ok_or("missing option --assignment")?;
+                let circuit = shape.circuit().with_assignment(assignment);
+                let inputs = circuit
+                    .public_inputs()
+                    .expect("the circuit has an assignment");
+                let proof = Groth16::<E>::create_random_proof_with_reduction(circuit, pk, rng);
+                (proof, inputs)
+            },
+            CircuitSpec::Swap(witness) => {
+                let (pool, trade) = witness.ok_or("missing options of the swap")?;
+                let mut inputs = pool.static_inputs();
+                inputs.extend(trade.variable_inputs::<E::ScalarField>());
+                (reference::prove(pk, pool, trade, rng), inputs)
+            },
+        };
+        let proof = proof.map_err(context("proving failed"))?;
+        Ok((proof.to_hex(), inputs_to_hex::<E>(&inputs)))
+    }
+    let any =
+        AnyGroth16::from_proving_key_bytes(&read(&pk_path)?).map_err(context(pk_path.display()))?;
+    let (proof, inputs) = with_keys!(&any, prove_with(keys, &circuit, &mut rng))?;
+    write(&proof_path, proof + "\n")?;
+    write(&inputs_path, inputs + "\n")?;
+    Ok(ExitCode::SUCCESS)
+}
+
+fn verify(mut args: Args) -> CliResult<ExitCode> {
+    let vk_path = PathBuf::from(args.required("vk")?);
+    let proof_path = PathBuf::from(args.required("proof")?);
+    let inputs_path = PathBuf::from(args.required("inputs")?);
+    args.finish()?;
+
+    fn verify_with<E: Pairing>(keys: &CurveKeys<E>, proof: &str, inputs: &str) -> CliResult<bool> {
+        let proof = Proof::<E>::from_hex(proof).map_err(context("proof"))?;
+        let inputs = inputs_from_hex::<E>(inputs).map_err(context("inputs"))?;
+        let num_static = keys.pvk.num_static_inputs().min(inputs.len());
+        let (static_inputs, variable_inputs) = inputs.split_at(num_static);
+        Groth16::<E>::verify_strict(&keys.pvk, &proof, static_inputs, variable_inputs)
+            .map_err(context("verification failed"))
+    }
+    let any = AnyGroth16::from_verifying_key_bytes(&read(&vk_path)?)
+        .map_err(context(vk_path.display()))?;
+    let valid = with_keys!(
+        &any,
+        verify_with(keys, &read_text(&proof_path)?, &read_text(&inputs_path)?)
+    )?;
+    if valid {
+        println!("valid");
+        Ok(ExitCode::SUCCESS)
+    } else {
+        println!("invalid");
+        Ok(ExitCode::FAILURE)
+    }
+}
+
+/// A format of keys, proofs and public inputs, see [`USAGE`].
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Arkworks,
+    Snarkjs,
+    Solana,
+}
+
+impl Format {
+    fn parse(name: &str) -> CliResult<Self> {
+        match name {
+            "arkworks" => Ok(Format::Arkworks),
+            "snarkjs" => Ok(Format::Snarkjs),
+            "solana" => Ok(Format::Solana),
+            _ => Err(format!("unknown format {}", name)),
+        }
+    }
+
+    /// Returns the names of the files of the verifying key, the proof and
+    /// the public inputs.
+    fn file_names(self) -> [&'static str; 3] {
+        match self {
+            Format::Arkworks => ["vk.bin", "proof.txt", "inputs.txt"],
+            Format::Snarkjs => ["verification_key.json", "proof.json", "public.json"],
+            Format::Solana => ["verifying_key.bin", "proof.bin", "public_inputs.bin"],
+        }
+    }
+}
+
+/// The files given to `convert`, read.
+struct ConvertFiles {
+    vk: Option<Vec<u8>>,
+    proof: Option<String>,
+    inputs: Option<String>,
+}
+
+/// Returns whether the verifying key or else the proof of `files` is over
+/// `E`, to detect the curve of the files.
+fn is_over<E: Pairing>(from: Format, files: &ConvertFiles) -> bool {
+    let text = |bytes: &[u8]| String::from_utf8_lossy(bytes).into_owned();
+    match (from, &files.vk, &files.proof) {
+        (Format::Arkworks, Some(vk), _) => load_verifying_key::<E>(vk).is_ok(),
+        (Format::Arkworks, None, Some(proof)) => Proof::<E>::from_hex(proof).is_ok(),
+        (Format::Snarkjs, Some(vk), _) => snarkjs::verifying_key_from_json::<E>(&text(vk)).is_ok(),
+        (Format::Snarkjs, None, Some(proof)) => snarkjs::proof_from_json::<E>(proof).is_ok(),
+        _ => false,
+    }
+}
+
+fn convert_files<E: Pairing>(
+    from: Format,
+    to: Format,
+    files: &ConvertFiles,
+    out_dir: &Path,
+) -> CliResult<()> {
+    let vk: Option<VerifyingKey<E>> = match &files.vk {
+        None => None,
+        Some(vk) if from == Format::Arkworks => {
+            Some(load_verifying_key(vk).map_err(context("verifying key"))?)
+        },
+        Some(vk) => Some(
+            snarkjs::verifying_key_from_json(&String::from_utf8_lossy(vk))
+                .map_err(context("verifying key"))?,
+        ),
+    };
+    let proof: Option<Proof<E>> = match &files.proof {
+        None => None,
+        Some(proof) if from == Format::Arkworks => {
+            Some(Proof::from_hex(proof).map_err(context("proof"))?)
+        },
+        Some(proof) => Some(snarkjs::proof_from_json(proof).map_err(context("proof"))?),
+    };
+    let inputs: Option<Vec<E::ScalarField>> = match &files.inputs {
+        None => None,
+        Some(inputs) if from == Format::Arkworks => {
+            Some(inputs_from_hex::<E>(inputs).map_err(context("inputs"))?)
+        },
+        Some(inputs) => Some(snarkjs::public_inputs_from_json(inputs).map_err(context("inputs"))?),
+    };
+
+    if to == Format::Solana && curve_tag::<E>() != "bn254" {
+        return Err(String::from("solana verifiers only support BN254"));
+    }
+    let [vk_name, proof_name, inputs_name] = to.file_names();
+    if let Some(vk) = &vk {
+        let bytes = match to {
+            Format::Arkworks => {
+                let mut bytes = Vec::new();
+                vk.serialize_compressed(&mut bytes).map(|_| bytes)
+            },
+            Format::Snarkjs => snarkjs::verifying_key_to_json(vk).map(String::into_bytes),
+            Format::Solana => borsh_encode_verifying_key(vk),
+        };
+        write(
+            &out_dir.join(vk_name),
+            bytes.map_err(context("verifying key"))?,
+        )?;
+    }
+    if let Some(proof) = &proof {
+        let bytes = match to {
+            Format::Arkworks => Ok((proof.to_hex() + "\n").into_bytes()),
+            Format::Snarkjs => snarkjs::proof_to_json(proof).map(String::into_bytes),
+            Format::Solana => Ok(ProofWireFormat::EIP197_NEGATED_A.encode_proof(proof)),
+        };
+        write(&out_dir.join(proof_name), bytes.map_err(context("proof"))?)?;
+    }
+    if let Some(inputs) = inputs {
+        let bytes = match to {
+            Format::Arkworks => (inputs_to_hex::<E>(&inputs) + "\n").into_bytes(),
+            Format::Snarkjs => snarkjs::public_inputs_to_json(&inputs).into_bytes(),
+            Format::Solana => PublicInputs::<E>(inputs).to_bytes(Endianness::Big),
+        };
+        write(&out_dir.join(inputs_name), bytes)?;
+    }
+    Ok(())
+}
+
+fn convert(mut args: Args) -> CliResult<ExitCode> {
+    let from = Format::parse(&args.required("from")?)?;
+    let to = Format::parse(&args.required("to")?)?;
+    if from == Format::Solana {
+        return Err(String::from("the solana format is only an output format"));
+    }
+    let out_dir = PathBuf::from(args.required("out-dir")?);
+    let curve = args
+        .take("curve")
+        .map(|name| parse_curve(&name))
+        .transpose()?;
+    let files = ConvertFiles {
+        vk: args
+            .take("vk")
+            .map(|path| read(path.as_ref()))
+            .transpose()?,
+        proof: args
+            .take("proof")
+            .map(|path| read_text(path.as_ref()))
+            .transpose()?,
+        inputs: args
+            .take("inputs")
+            .map(|path| read_text(path.as_ref()))
+            .transpose()?,
+    };
+    args.finish()?;
+    if files.vk.is_none() && files.proof.is_none() && files.inputs.is_none() {
+        return Err(String::from(
+            "nothing to convert, pass --vk, --proof or --inputs",
+        ));
+    }
+
+    let curve = match curve {
+        Some(curve) => curve,
+        None if files.vk.is_none() && files.proof.is_none() => {
+            return Err(String::from("missing option --curve"))
+        },
+        None => Curve::ALL
+            .into_iter()
+            .find(|curve| with_curve!(curve, is_over(from, &files)))
+            .ok_or("the files are not over a supported curve, or are malformed")?,
+    };
+    fs::create_dir_all(&out_dir).map_err(context(out_dir.display()))?;
+    with_curve!(curve, convert_files(from, to, &files, &out_dir))?;
+    Ok(ExitCode::SUCCESS)
+}
+
+fn inspect(mut args: Args) -> CliResult<ExitCode> {
+    let (object, path) = match &args.positional[..] {
+        [object, path] => (object.clone(), PathBuf::from(path)),
+        _ => return Err(String::from("usage: inspect vk <file>")),
+    };
+    if object != "vk" {
+        return Err(format!("cannot inspect {}, only vk", object));
+    }
+    args.positional.clear();
+    args.finish()?;
+
+    fn inspect_key<E: Pairing>(keys: &CurveKeys<E>) {
+        let pvk: &PreparedVerifyingKey<E> = &keys.pvk;
+        let vk = &pvk.vk;
+        println!("static inputs: {}", pvk.num_static_inputs());
+        println!("variable inputs: {}", pvk.num_variable_inputs());
+        println!("fingerprint: {}", hex(&vk.fingerprint()));
+        if curve_tag::<E>() == "bn254" {
+            if let Ok(commitment) = solana_verifying_key_commitment(vk) {
+                println!("solana commitment: {}", hex(&commitment));
+            }
+            if let Ok(commitment) = evm_verifying_key_commitment(vk) {
+                println!("evm commitment: {}", hex(&commitment));
+            }
+        }
+    }
+    let any =
+        AnyGroth16::from_verifying_key_bytes(&read(&path)?).map_err(context(path.display()))?;
+    println!("curve: {}", any.curve());
+    with_keys!(&any, inspect_key(keys));
+    Ok(ExitCode::SUCCESS)
+}
+
+fn run(args: impl IntoIterator<Item = String>) -> CliResult<ExitCode> {
+    let mut args = Args::parse(args)?;
+    if args.positional.is_empty() {
+        println!("{}", USAGE);
+        return Ok(ExitCode::SUCCESS);
+    }
+    let command = args.positional.remove(0);
+    match command.as_str() {
+        "setup" => setup(args),
+        "prove" => prove(args),
+        "verify" => verify(args),
+        "convert" => convert(args),
+        "inspect" => inspect(args),
+        "help" => {
+            println!("{}", USAGE);
+            Ok(ExitCode::SUCCESS)
+        },
+        _ => Err(format!("unknown command {}, see help", command)),
+    }
+}
+
+fn main() -> ExitCode {
+    match run(env::args().skip(1)) {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::from(2)
+        },
+    }
+}
@@ -0,0 +1,104 @@
+use crate::{hash::hash_compressed, Groth16, PreparedVerifyingKey, Proof, VerifyingKey};
+use ark_crypto_primitives::snark::SNARK;
+use ark_ec::pairing::Pairing;
+use ark_relations::r1cs::{Result as R1CSResult, SynthesisError};
+use ark_serialize::*;
+use ark_std::{collections::BTreeMap, string::String, vec::Vec};
+
+/// The SHA-256 hash of the compressed serialization of a [`VerifyingKey`].
+pub type VkFingerprint = [u8; 32];
+
+impl<E: Pairing> VerifyingKey<E> {
+    /// Returns the fingerprint identifying `self` in a
+    /// [`VerifyingKeyRegistry`].
+    pub fn fingerprint(&self) -> VkFingerprint {
+        hash_compressed(self)
+    }
+}
+
+/// A set of prepared verifying keys, indexed by their fingerprints.
+#[derive(Clone, Debug, Default)]
+pub struct VerifyingKeyRegistry<E: Pairing> {
+    keys: BTreeMap<VkFingerprint, PreparedVerifyingKey<E>>,
+}
+
+impl<E: Pairing> VerifyingKeyRegistry<E> {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self {
+            keys: BTreeMap::new(),
+        }
+    }
+
+    /// Prepares and adds `vk` to the registry, returning its fingerprint.
+    pub fn insert(&mut self, vk: &VerifyingKey<E>) -> VkFingerprint {
+        let fingerprint = vk.fingerprint();
+        self.keys
+            .entry(fingerprint)
+            .or_insert_with(|| crate::prepare_verifying_key(vk));
+        fingerprint
+    }
+
+    /// Returns the prepared key with the given fingerprint, if registered.
+    pub fn get(&self, fingerprint: &VkFingerprint) -> Option<&PreparedVerifyingKey<E>> {
+        self.keys.get(fingerprint)
+    }
+
+    /// Returns the number of registered keys.
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Returns whether no keys are registered.
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+}
+
+/// A proof together with everything needed to verify it: its public inputs,
+/// the fingerprint of the key it was created for, and metadata identifying
+/// the circuit and the time of proving.
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct ProofBundle<E: Pairing> {
+    /// The proof.
+    pub proof: Proof<E>,
+    /// All public inputs of the proof, static inputs first.
+    pub public_inputs: Vec<E::ScalarField>,
+    /// The fingerprint of the verifying key of the circuit.
+    pub vk_fingerprint: VkFingerprint,
+    /// An application-defined identifier of the circuit.
+    pub circuit_id: String,
+    /// The time the proof was created, in seconds since the Unix epoch.
+    pub created_at: u64,
+}
+
+impl<E: Pairing> ProofBundle<E> {
+    /// Bundles `proof` and its `public_inputs` for verification with `vk`.
+    pub fn new(
+        proof: Proof<E>,
+        public_inputs: Vec<E::ScalarField>,
+        vk: &VerifyingKey<E>,
+        circuit_id: impl Into<String>,
+        created_at: u64,
+    ) -> Self {
+        Self {
+            proof,
+            public_inputs,
+            vk_fingerprint: vk.fingerprint(),
+            circuit_id: circuit_id.into(),
+            created_at,
+        }
+    }
+
+    /// Verifies the bundled proof against its public inputs, with the key
+    /// of `registry` matching [`Self::vk_fingerprint`]. Fails with
+    /// [`SynthesisError::MalformedVerifyingKey`] if no such key is
+    /// registered.
+    pub fn verify(&self, registry: &VerifyingKeyRegistry<E>) -> R1CSResult<bool> {
+        let pvk = registry
+            .get(&self.vk_fingerprint)
+            .ok_or(SynthesisError::MalformedVerifyingKey)?;
+
+        Groth16::<E>::verify_with_processed_vk(pvk, &self.public_inputs, &self.proof)
+    }
+}
@@ -0,0 +1,582 @@
+use crate::{hash::hash_compressed, Groth16, PreparedVerifyingKey, Proof};
+use ark_ec::pairing::Pairing;
+use ark_relations::r1cs::{Result as R1CSResult, SynthesisError};
+use ark_std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+    time::Duration,
+};
+#[cfg(feature = "std")]
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc, Mutex, PoisonError, RwLock,
+};
+
+/// A least-recently-used cache of prepared public inputs, keyed by the hash
+/// of the static and variable inputs.
+///
+/// The cache does not record which verifying key the inputs were prepared
+/// with, so a cache must only be used with a single key, as done by
+/// [`CachedVerifier`].
+#[derive(Clone, Debug)]
+pub struct PreparedInputCache<E: Pairing> {
+    entries: BTreeMap<[u8; 32], (E::G1, u64)>,
+    recency: BTreeMap<u64, [u8; 32]>,
+    tick: u64,
+    capacity: usize,
+}
+
+impl<E: Pairing> PreparedInputCache<E> {
+    /// Creates an empty cache holding at most `capacity` prepared inputs.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: BTreeMap::new(),
+            recency: BTreeMap::new(),
+            tick: 0,
+            capacity,
+        }
+    }
+
+    fn key(static_inputs: &[E::ScalarField], variable_inputs: &[E::ScalarField]) -> [u8; 32] {
+        hash_compressed(&(static_inputs, variable_inputs))
+    }
+
+    /// Returns the prepared form of the given inputs if it is cached, and
+    /// marks it as the most recently used.
+    pub fn get(
+        &mut self,
+        static_inputs: &[E::ScalarField],
+        variable_inputs: &[E::ScalarField],
+    ) -> Option<E::G1> {
+        let key = Self::key(static_inputs, variable_inputs);
+        let (prepared, last_used) = self.entries.get_mut(&key)?;
+        self.recency.remove(last_used);
+        self.tick += 1;
+        *last_used = self.tick;
+        self.recency.insert(self.tick, key);
+        Some(*prepared)
+    }
+
+    /// Caches the prepared form of the given inputs, evicting the least
+    /// recently used entry if the cache is full.
+    pub fn insert(
+        &mut self,
+        static_inputs: &[E::ScalarField],
+        variable_inputs: &[E::ScalarField],
+        prepared: E::G1,
+    ) {
+        if self.capacity == 0 {
+            return;
+        }
+        let key = Self::key(static_inputs, variable_inputs);
+        self.tick += 1;
+        if let Some((_, last_used)) = self.entries.insert(key, (prepared, self.tick)) {
+            self.recency.remove(&last_used);
+        } else if self.entries.len() > self.capacity {
+            if let Some((_, evicted)) = self.recency.pop_first() {
+                self.entries.remove(&evicted);
+            }
+        }
+        self.recency.insert(self.tick, key);
+    }
+
+    /// Returns the number of cached prepared inputs.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns whether no prepared inputs are cached.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Drops all cached prepared inputs.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.recency.clear();
+    }
+}
+
+/// The SHA-256 hash of the compressed serialization of a [`Proof`].
+pub type ProofFingerprint = [u8; 32];
+
+impl<E: Pairing> Proof<E> {
+    /// Returns the fingerprint identifying `self` in a [`ReplayGuard`].
+    pub fn fingerprint(&self) -> ProofFingerprint {
+        hash_compressed(self)
+    }
+}
+
+/// A bounded set of the fingerprints of the proofs accepted during a sliding
+/// window of time, to reject their re-submission.
+///
+/// Times are durations since an arbitrary epoch, e.g. the Unix epoch, and
+/// are expected not to decrease. Once the guard is full, the oldest
+/// fingerprints are forgotten before the end of the window, so its capacity
+/// should exceed the number of proofs accepted per window.
+///
+/// Only exact re-submissions are detected: Groth16 proofs are malleable, and
+/// anyone can derive a different valid proof of the same statement with
+/// [`Groth16::rerandomize_proof`].
+#[derive(Clone, Debug)]
+pub struct ReplayGuard {
+    seen: BTreeMap<ProofFingerprint, Duration>,
+    expiry: BTreeSet<(Duration, ProofFingerprint)>,
+    capacity: usize,
+    window: Duration,
+}
+
+impl ReplayGuard {
+    /// Creates an empty guard remembering at most `capacity` fingerprints,
+    /// each for `window`.
+    pub fn new(capacity: usize, window: Duration) -> Self {
+        Self {
+            seen: BTreeMap::new(),
+            expiry: BTreeSet::new(),
+            capacity,
+            window,
+        }
+    }
+
+    /// Returns whether `fingerprint` was inserted less than the window
+    /// before `now`.
+    pub fn contains(&self, fingerprint: &ProofFingerprint, now: Duration) -> bool {
+        self.seen
+            .get(fingerprint)
+            .is_some_and(|seen_at| now < seen_at.saturating_add(self.window))
+    }
+
+    /// Records `fingerprint` as seen at `now`, forgetting the fingerprints
+    /// seen a window or more before, and the oldest ones if the guard is
+    /// full.
+    pub fn insert(&mut self, fingerprint: ProofFingerprint, now: Duration) {
+        while let Some(&(seen_at, oldest)) = self.expiry.first() {
+            let expired = now >= seen_at.saturating_add(self.window);
+            if !expired && self.seen.len() < self.capacity {
+                break;
+            }
+            self.expiry.pop_first();
+            self.seen.remove(&oldest);
+        }
+        if self.capacity == 0 {
+            return;
+        }
+        if let Some(seen_at) = self.seen.insert(fingerprint, now) {
+            self.expiry.remove(&(seen_at, fingerprint));
+        }
+        self.expiry.insert((now, fingerprint));
+    }
+
+    /// Returns the number of remembered fingerprints, including expired
+    /// ones which were not forgotten yet.
+    pub fn len(&self) -> usize {
+        self.seen.len()
+    }
+
+    /// Returns whether no fingerprints are remembered.
+    pub fn is_empty(&self) -> bool {
+        self.seen.is_empty()
+    }
+
+    /// Forgets all fingerprints.
+    pub fn clear(&mut self) {
+        self.seen.clear();
+        self.expiry.clear();
+    }
+}
+
+/// An error of [`CachedVerifier::verify_once_at`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ReplayError {
+    /// The inputs do not match the verifying key.
+    Synthesis(SynthesisError),
+    /// The proof was already accepted during the window of the guard.
+    Replayed,
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayError::Synthesis(e) => write!(f, "{}", e),
+            ReplayError::Replayed => write!(f, "proof was already accepted"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ReplayError {}
+
+impl From<SynthesisError> for ReplayError {
+    fn from(e: SynthesisError) -> Self {
+        ReplayError::Synthesis(e)
+    }
+}
+
+/// A verifier for a single key which memoizes prepared public inputs, for
+/// workloads where many proofs share the same inputs, and optionally
+/// rejects the re-submission of accepted proofs with a [`ReplayGuard`].
+#[derive(Clone, Debug)]
+pub struct CachedVerifier<E: Pairing> {
+    pvk: PreparedVerifyingKey<E>,
+    inputs: PreparedInputCache<E>,
+    replay_guard: Option<ReplayGuard>,
+}
+
+impl<E: Pairing> CachedVerifier<E> {
+    /// Creates a verifier for `pvk` caching up to `cache_size` prepared
+    /// inputs.
+    pub fn new(pvk: PreparedVerifyingKey<E>, cache_size: usize) -> Self {
+        Self {
+            pvk,
+            inputs: PreparedInputCache::new(cache_size),
+            replay_guard: None,
+        }
+    }
+
+    /// Rejects the re-submission of proofs accepted by
+    /// [`Self::verify_once_at`] with `replay_guard`.
+    pub fn with_replay_guard(mut self, replay_guard: ReplayGuard) -> Self {
+        self.replay_guard = Some(replay_guard);
+        self
+    }
+
+    /// Returns the replay guard, if any.
+    pub fn replay_guard(&self) -> Option<&ReplayGuard> {
+        self.replay_guard.as_ref()
+    }
+
+    /// Returns the key proofs are verified against.
+    pub fn pvk(&self) -> &PreparedVerifyingKey<E> {
+        &self.pvk
+    }
+
+    /// Returns the cache of prepared inputs.
+    pub fn cache(&self) -> &PreparedInputCache<E> {
+        &self.inputs
+    }
+
+    /// Prepares the given inputs, reusing the cached result if they were
+    /// prepared before.
+    pub fn prepare_inputs_with_variables(
+        &mut self,
+        static_inputs: &[E::ScalarField],
+        variable_inputs: &[E::ScalarField],
+    ) -> R1CSResult<E::G1> {
+        if let Some(prepared) = self.inputs.get(static_inputs, variable_inputs) {
+            return Ok(prepared);
+        }
+        let prepared =
+            Groth16::<E>::prepare_inputs_with_variables(&self.pvk, static_inputs, variable_inputs)?;
+        self.inputs.insert(static_inputs, variable_inputs, prepared);
+        Ok(prepared)
+    }
+
+    /// Verify a proof with separate static and variable inputs, see
+    /// [`Groth16::verify_with_variables`].
+    pub fn verify_with_variables(
+        &mut self,
+        proof: &Proof<E>,
+        static_inputs: &[E::ScalarField],
+        variable_inputs: &[E::ScalarField],
+    ) -> R1CSResult<bool> {
+        let prepared_inputs = self.prepare_inputs_with_variables(static_inputs, variable_inputs)?;
+        Groth16::<E>::verify_proof_with_prepared_inputs(&self.pvk, proof, &prepared_inputs)
+    }
+
+    /// Verify a proof like [`Self::verify_with_variables`] at time `now`,
+    /// failing with [`ReplayError::Replayed`] if it was already accepted
+    /// during the window of the replay guard. Accepted proofs are recorded
+    /// in the guard. Without a guard, this is the same as
+    /// [`Self::verify_with_variables`].
+    pub fn verify_once_at(
+        &mut self,
+        proof: &Proof<E>,
+        static_inputs: &[E::ScalarField],
+        variable_inputs: &[E::ScalarField],
+        now: Duration,
+    ) -> Result<bool, ReplayError> {
+        let fingerprint = proof.fingerprint();
+        if let Some(guard) = &self.replay_guard {
+            if guard.contains(&fingerprint, now) {
+                return Err(ReplayError::Replayed);
+            }
+        }
+        let valid = self.verify_with_variables(proof, static_inputs, variable_inputs)?;
+        if let (true, Some(guard)) = (valid, &mut self.replay_guard) {
+            guard.insert(fingerprint, now);
+        }
+        Ok(valid)
+    }
+
+    /// Verify a proof like [`Self::verify_once_at`], at the current time
+    /// since the Unix epoch.
+    #[cfg(feature = "std")]
+    pub fn verify_once(
+        &mut self,
+        proof: &Proof<E>,
+        static_inputs: &[E::ScalarField],
+        variable_inputs: &[E::ScalarField],
+    ) -> Result<bool, ReplayError> {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default();
+        self.verify_once_at(proof, static_inputs, variable_inputs, now)
+    }
+}
+
+/// The prepared inputs of a [`SharedInputCache`] and their last use, which
+/// is shared by the snapshots holding the entry.
+#[cfg(feature = "std")]
+type SharedEntries<G> = BTreeMap<[u8; 32], (G, Arc<AtomicU64>)>;
+
+/// A least-recently-used cache of prepared public inputs, like
+/// [`PreparedInputCache`], shared between threads.
+///
+/// The entries are an immutable snapshot, which lookups read after only
+/// cloning its `Arc`, and record their use in an atomic counter. Lookups
+/// thus never wait for insertions, which copy the snapshot, update and
+/// evict entries in the copy, and then swap it in. The copy and the scan
+/// of the entries to evict the least recently used one are cheap next to
+/// the preparation of inputs for the capacities of typical services.
+///
+/// The locks are only held to clone or swap the snapshot, so a panicking
+/// thread cannot leave it inconsistent, and poisoned locks are ignored.
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct SharedInputCache<E: Pairing> {
+    snapshot: RwLock<Arc<SharedEntries<E::G1>>>,
+    /// Serializes the insertions, so that none is lost.
+    writer: Mutex<()>,
+    tick: AtomicU64,
+    capacity: usize,
+}
+
+#[cfg(feature = "std")]
+impl<E: Pairing> SharedInputCache<E> {
+    /// Creates an empty cache holding at most `capacity` prepared inputs.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            snapshot: RwLock::new(Arc::new(BTreeMap::new())),
+            writer: Mutex::new(()),
+            tick: AtomicU64::new(0),
+            capacity,
+        }
+    }
+
+    fn next_tick(&self) -> u64 {
+        self.tick.fetch_add(1, Ordering::Relaxed) + 1
+    }
+
+    fn snapshot(&self) -> Arc<SharedEntries<E::G1>> {
+        self.snapshot
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Replaces the snapshot with `entries`, while holding `writer`.
+    fn publish(&self, entries: SharedEntries<E::G1>) {
+        *self
+            .snapshot
+            .write()
+            .unwrap_or_else(PoisonError::into_inner) = Arc::new(entries);
+    }
+
+    /// Returns the prepared form of the given inputs if it is cached, and
+    /// marks it as the most recently used.
+    pub fn get(
+        &self,
+        static_inputs: &[E::ScalarField],
+        variable_inputs: &[E::ScalarField],
+    ) -> Option<E::G1> {
+        let key = PreparedInputCache::<E>::key(static_inputs, variable_inputs);
+        let entries = self.snapshot();
+        let (prepared, last_used) = entries.get(&key)?;
+        last_used.store(self.next_tick(), Ordering::Relaxed);
+        Some(*prepared)
+    }
+
+    /// Caches the prepared form of the given inputs, evicting the least
+    /// recently used entry if the cache is full.
+    pub fn insert(
+        &self,
+        static_inputs: &[E::ScalarField],
+        variable_inputs: &[E::ScalarField],
+        prepared: E::G1,
+    ) {
+        if self.capacity == 0 {
+            return;
+        }
+        let key = PreparedInputCache::<E>::key(static_inputs, variable_inputs);
+        let _writer = self.writer.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut entries = SharedEntries::clone(&self.snapshot());
+        let last_used = Arc::new(AtomicU64::new(self.next_tick()));
+        if entries.insert(key, (prepared, last_used)).is_none() && entries.len() > self.capacity {
+            let evicted = entries
+                .iter()
+                .min_by_key(|(_, (_, last_used))| last_used.load(Ordering::Relaxed))
+                .map(|(key, _)| *key);
+            if let Some(evicted) = evicted {
+                entries.remove(&evicted);
+            }
+        }
+        self.publish(entries);
+    }
+
+    /// Returns the number of cached prepared inputs.
+    pub fn len(&self) -> usize {
+        self.snapshot().len()
+    }
+
+    /// Returns whether no prepared inputs are cached.
+    pub fn is_empty(&self) -> bool {
+        self.snapshot().is_empty()
+    }
+
+    /// Drops all cached prepared inputs.
+    pub fn clear(&self) {
+        let _writer = self.writer.lock().unwrap_or_else(PoisonError::into_inner);
+        self.publish(BTreeMap::new());
+    }
+}
+
+/// A [`CachedVerifier`] whose methods take `&self`, to share a single
+/// verifier behind an `Arc` between the threads or tasks of a service.
+///
+/// The prepared verifying key is immutable and read without locking. The
+/// cache of prepared inputs is a [`SharedInputCache`], and the replay guard
+/// is behind a mutex held only to look up and record fingerprints, never
+/// while verifying. The verifier is `Send + Sync` for every [`Pairing`].
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct SharedVerifier<E: Pairing> {
+    pvk: PreparedVerifyingKey<E>,
+    inputs: SharedInputCache<E>,
+    replay_guard: Option<Mutex<ReplayGuard>>,
+}
+
+// Fails to compile if the shared verifier stops being `Send + Sync` for
+// some pairing.
+#[cfg(feature = "std")]
+const _: () = {
+    fn assert_send_sync<T: Send + Sync>() {}
+    #[allow(dead_code)]
+    fn assert_shared<E: Pairing>() {
+        assert_send_sync::<SharedVerifier<E>>();
+    }
+};
+
+#[cfg(feature = "std")]
+impl<E: Pairing> SharedVerifier<E> {
+    /// Creates a verifier for `pvk` caching up to `cache_size` prepared
+    /// inputs.
+    pub fn new(pvk: PreparedVerifyingKey<E>, cache_size: usize) -> Self {
+        Self {
+            pvk,
+            inputs: SharedInputCache::new(cache_size),
+            replay_guard: None,
+        }
+    }
+
+    /// Rejects the re-submission of proofs accepted by
+    /// [`Self::verify_once_at`] with `replay_guard`.
+    pub fn with_replay_guard(mut self, replay_guard: ReplayGuard) -> Self {
+        self.replay_guard = Some(Mutex::new(replay_guard));
+        self
+    }
+
+    /// Returns the number of fingerprints remembered by the replay guard, if
+    /// any.
+    pub fn replay_guard_len(&self) -> Option<usize> {
+        self.replay_guard
+            .as_ref()
+            .map(|guard| guard.lock().unwrap_or_else(PoisonError::into_inner).len())
+    }
+
+    /// Returns the key proofs are verified against.
+    pub fn pvk(&self) -> &PreparedVerifyingKey<E> {
+        &self.pvk
+    }
+
+    /// Returns the cache of prepared inputs.
+    pub fn cache(&self) -> &SharedInputCache<E> {
+        &self.inputs
+    }
+
+    /// Prepares the given inputs, reusing the cached result if they were
+    /// prepared before. Threads preparing the same uncached inputs at once
+    /// each compute them.
+    pub fn prepare_inputs_with_variables(
+        &self,
+        static_inputs: &[E::ScalarField],
+        variable_inputs: &[E::ScalarField],
+    ) -> R1CSResult<E::G1> {
+        if let Some(prepared) = self.inputs.get(static_inputs, variable_inputs) {
+            return Ok(prepared);
+        }
+        let prepared =
+            Groth16::<E>::prepare_inputs_with_variables(&self.pvk, static_inputs, variable_inputs)?;
+        self.inputs.insert(static_inputs, variable_inputs, prepared);
+        Ok(prepared)
+    }
+
+    /// Verify a proof with separate static and variable inputs, see
+    /// [`Groth16::verify_with_variables`].
+    pub fn verify_with_variables(
+        &self,
+        proof: &Proof<E>,
+        static_inputs: &[E::ScalarField],
+        variable_inputs: &[E::ScalarField],
+    ) -> R1CSResult<bool> {
+        let prepared_inputs = self.prepare_inputs_with_variables(static_inputs, variable_inputs)?;
+        Groth16::<E>::verify_proof_with_prepared_inputs(&self.pvk, proof, &prepared_inputs)
+    }
+
+    /// Verify a proof like [`CachedVerifier::verify_once_at`]. When the same
+    /// proof is submitted concurrently, it is verified by each submission
+    /// but accepted by only one of them, the others failing with
+    /// [`ReplayError::Replayed`].
+    pub fn verify_once_at(
+        &self,
+        proof: &Proof<E>,
+        static_inputs: &[E::ScalarField],
+        variable_inputs: &[E::ScalarField],
+        now: Duration,
+    ) -> Result<bool, ReplayError> {
+        let Some(guard) = &self.replay_guard else {
+            return Ok(self.verify_with_variables(proof, static_inputs, variable_inputs)?);
+        };
+        let fingerprint = proof.fingerprint();
+        if guard
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .contains(&fingerprint, now)
+        {
+            return Err(ReplayError::Replayed);
+        }
+        let valid = self.verify_with_variables(proof, static_inputs, variable_inputs)?;
+        if valid {
+            // Check again, as the proof may have been accepted meanwhile.
+            let mut guard = guard.lock().unwrap_or_else(PoisonError::into_inner);
+            if guard.contains(&fingerprint, now) {
+                return Err(ReplayError::Replayed);
+            }
+            guard.insert(fingerprint, now);
+        }
+        Ok(valid)
+    }
+
+    /// Verify a proof like [`Self::verify_once_at`], at the current time
+    /// since the Unix epoch.
+    pub fn verify_once(
+        &self,
+        proof: &Proof<E>,
+        static_inputs: &[E::ScalarField],
+        variable_inputs: &[E::ScalarField],
+    ) -> Result<bool, ReplayError> {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default();
+        self.verify_once_at(proof, static_inputs, variable_inputs, now)
+    }
+}
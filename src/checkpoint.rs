@@ -0,0 +1,200 @@
+use crate::{
+    bundle::VkFingerprint,
+    msm::{mul_secret, MsmBackend},
+    prover::ProverConfig,
+    r1cs_to_qap::R1CSToQAP,
+    Groth16, Proof, ProvingKey,
+};
+use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup};
+use ark_ff::{PrimeField, UniformRand};
+use ark_relations::r1cs::{ConstraintSynthesizer, Result as R1CSResult, SynthesisError};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::{fmt, rand::Rng, vec::Vec};
+use zeroize::Zeroize;
+
+/// The last completed stage of a [`ProverCheckpoint`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ProverStage {
+    /// The circuit is synthesized and reduced to its QAP witness.
+    Reduced,
+    /// The MSM of the QAP witness is computed.
+    HMsm,
+    /// The MSM of the `l_query` is computed.
+    LMsm,
+    /// The MSM of the `a_query` is computed.
+    AMsm,
+    /// The MSM of the `b_g1_query` is computed.
+    BG1Msm,
+    /// The MSM of the `b_g2_query` is computed, and the proof can be
+    /// assembled.
+    BG2Msm,
+}
+
+/// The state of a proof between the stages of the prover, serialized to
+/// resume proving after an interruption, e.g. the preemption of a spot
+/// instance, with [`Groth16::resume_proof`].
+///
+/// A checkpoint holds the witness and the blinding factors of the proof,
+/// and must be stored as securely as the witness itself. The blinding
+/// factors are zeroized when it is dropped.
+#[derive(Clone, CanonicalSerialize, CanonicalDeserialize)]
+pub struct ProverCheckpoint<E: Pairing> {
+    key: VkFingerprint,
+    r: E::ScalarField,
+    s: E::ScalarField,
+    input_assignment: Vec<E::ScalarField>,
+    aux_assignment: Vec<E::ScalarField>,
+    h: Vec<E::ScalarField>,
+    h_acc: Option<E::G1>,
+    l_acc: Option<E::G1>,
+    a: Option<E::G1>,
+    b_g1: Option<E::G1>,
+    b_g2: Option<E::G2>,
+}
+
+impl<E: Pairing> ProverCheckpoint<E> {
+    /// Returns the last completed stage.
+    pub fn stage(&self) -> ProverStage {
+        match (self.h_acc, self.l_acc, self.a, self.b_g1, self.b_g2) {
+            (_, _, _, _, Some(_)) => ProverStage::BG2Msm,
+            (_, _, _, Some(_), None) => ProverStage::BG1Msm,
+            (_, _, Some(_), None, None) => ProverStage::AMsm,
+            (_, Some(_), None, None, None) => ProverStage::LMsm,
+            (Some(_), None, None, None, None) => ProverStage::HMsm,
+            (None, None, None, None, None) => ProverStage::Reduced,
+        }
+    }
+
+    fn assignment(&self) -> Vec<<E::ScalarField as PrimeField>::BigInt> {
+        self.input_assignment
+            .iter()
+            .chain(&self.aux_assignment)
+            .map(|s| s.into_bigint())
+            .collect()
+    }
+}
+
+impl<E: Pairing> Drop for ProverCheckpoint<E> {
+    fn drop(&mut self) {
+        self.r.zeroize();
+        self.s.zeroize();
+    }
+}
+
+impl<E: Pairing> fmt::Debug for ProverCheckpoint<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProverCheckpoint")
+            .field("stage", &self.stage())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
+    /// Synthesizes `circuit` and reduces it to its QAP witness, returning
+    /// the first checkpoint of its proof. This method samples randomness for
+    /// zero knowledge via `rng`.
+    pub fn checkpoint_proof<C>(
+        pk: &ProvingKey<E>,
+        circuit: C,
+        rng: &mut impl Rng,
+    ) -> R1CSResult<ProverCheckpoint<E>>
+    where
+        C: ConstraintSynthesizer<E::ScalarField>,
+    {
+        let (cs, h) = Self::synthesize_and_witness_map(circuit, pk, &ProverConfig::default())?;
+        let prover = cs.borrow().unwrap();
+        Ok(ProverCheckpoint {
+            key: pk.vk.fingerprint(),
+            r: E::ScalarField::rand(rng),
+            s: E::ScalarField::rand(rng),
+            input_assignment: prover.instance_assignment[1..].to_vec(),
+            aux_assignment: prover.witness_assignment.clone(),
+            h,
+            h_acc: None,
+            l_acc: None,
+            a: None,
+            b_g1: None,
+            b_g2: None,
+        })
+    }
+
+    /// Computes the remaining MSMs of the proof of `checkpoint` with
+    /// `backend`, passing the checkpoint to `save` after each of them, and
+    /// assembles the proof.
+    ///
+    /// Fails with [`SynthesisError::MalformedVerifyingKey`] if `pk` is not
+    /// the key the proof was started with.
+    pub fn resume_proof<B>(
+        pk: &ProvingKey<E>,
+        checkpoint: &mut ProverCheckpoint<E>,
+        backend: &B,
+        mut save: impl FnMut(&ProverCheckpoint<E>),
+    ) -> R1CSResult<Proof<E>>
+    where
+        B: MsmBackend<E::G1> + MsmBackend<E::G2>,
+    {
+        if checkpoint.key != pk.vk.fingerprint() {
+            return Err(SynthesisError::MalformedVerifyingKey);
+        }
+
+        if checkpoint.h_acc.is_none() {
+            checkpoint.h_acc = Some(Self::h_accumulator(pk, &checkpoint.h, backend));
+            // The QAP witness is no longer needed, drop it from checkpoints.
+            checkpoint.h = Vec::new();
+            save(checkpoint);
+        }
+        if checkpoint.l_acc.is_none() {
+            let aux_assignment = checkpoint
+                .aux_assignment
+                .iter()
+                .map(|s| s.into_bigint())
+                .collect::<Vec<_>>();
+            checkpoint.l_acc = Some(backend.msm_bigint(&pk.l_query, &aux_assignment));
+            save(checkpoint);
+        }
+        if checkpoint.a.is_none() {
+            checkpoint.a = Some(Self::calculate_coeff(
+                mul_secret(pk.delta_g1.into_group(), checkpoint.r),
+                &pk.a_query,
+                pk.vk.alpha_g1,
+                &checkpoint.assignment(),
+                backend,
+            ));
+            save(checkpoint);
+        }
+        if checkpoint.b_g1.is_none() {
+            checkpoint.b_g1 = Some(Self::calculate_coeff(
+                mul_secret(pk.delta_g1.into_group(), checkpoint.s),
+                &pk.b_g1_query,
+                pk.beta_g1,
+                &checkpoint.assignment(),
+                backend,
+            ));
+            save(checkpoint);
+        }
+        if checkpoint.b_g2.is_none() {
+            checkpoint.b_g2 = Some(Self::calculate_coeff(
+                mul_secret(pk.vk.delta_g2.into_group(), checkpoint.s),
+                &pk.b_g2_query,
+                pk.vk.beta_g2,
+                &checkpoint.assignment(),
+                backend,
+            ));
+            save(checkpoint);
+        }
+
+        let (r, s) = (checkpoint.r, checkpoint.s);
+        let (g_a, g1_b) = (checkpoint.a.unwrap(), checkpoint.b_g1.unwrap());
+        let mut g_c = mul_secret(g_a, s);
+        g_c += mul_secret(g1_b, r);
+        g_c -= mul_secret(pk.delta_g1.into_group(), r * s);
+        g_c += checkpoint.l_acc.unwrap();
+        g_c += checkpoint.h_acc.unwrap();
+
+        Ok(Proof {
+            a: g_a.into_affine(),
+            b: checkpoint.b_g2.unwrap().into_affine(),
+            c: g_c.into_affine(),
+        })
+    }
+}
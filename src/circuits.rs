@@ -0,0 +1,597 @@
+use crate::{
+    inputs::{packing_capacity, InputPacker},
+    Groth16, PreparedVerifyingKey, Proof, ProvingKey, VerificationError,
+};
+use ark_ec::pairing::Pairing;
+use ark_ff::{BigInteger, PrimeField};
+use ark_relations::{
+    lc, ns,
+    r1cs::{
+        ConstraintSynthesizer, ConstraintSystemRef, LinearCombination, Result as R1CSResult,
+        SynthesisError, Variable,
+    },
+};
+use ark_serialize::SerializationError;
+use ark_std::{rand::Rng, vec::Vec};
+
+/// The number of rounds of [`MiMC`] used by upstream examples.
+pub const MIMC_ROUNDS: usize = 322;
+
+/// The MiMC `LongsightF` two-to-one compression function, as used in the
+/// circuits of this module. See <http://eprint.iacr.org/2016/492>.
+///
+/// The cube map is only a permutation of fields where `p - 1` is not a
+/// multiple of 3, so this is meant for tests and demos rather than as a
+/// production hash.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MiMC<F: PrimeField> {
+    /// The round constants.
+    pub constants: Vec<F>,
+}
+
+impl<F: PrimeField> MiMC<F> {
+    /// Creates an instance with the given round constants.
+    pub fn new(constants: Vec<F>) -> Self {
+        Self { constants }
+    }
+
+    /// Creates an instance with `rounds` random round constants.
+    pub fn random(rounds: usize, rng: &mut impl Rng) -> Self {
+        Self::new((0..rounds).map(|_| F::rand(rng)).collect())
+    }
+
+    /// Compresses `xl` and `xr` into a single element.
+    pub fn hash(&self, mut xl: F, mut xr: F) -> F {
+        for constant in &self.constants {
+            let tmp = xl + constant;
+            let new_xl = tmp.square() * tmp + xr;
+            xr = xl;
+            xl = new_xl;
+        }
+        xl
+    }
+
+    /// Enforces `output = self.hash(xl, xr)` in `cs`, returning a new witness
+    /// variable for `output`.
+    fn enforce(
+        &self,
+        cs: ConstraintSystemRef<F>,
+        (mut xl, mut xl_value): (Variable, Option<F>),
+        (mut xr, mut xr_value): (Variable, Option<F>),
+    ) -> R1CSResult<(Variable, Option<F>)> {
+        for constant in &self.constants {
+            let ns = ns!(cs, "round");
+            let cs = ns.cs();
+
+            // tmp = (xL + Ci)^2
+            let tmp_value = xl_value.map(|xl| (xl + constant).square());
+            let tmp =
+                cs.new_witness_variable(|| tmp_value.ok_or(SynthesisError::AssignmentMissing))?;
+            cs.enforce_constraint(
+                lc!() + xl + (*constant, Variable::One),
+                lc!() + xl + (*constant, Variable::One),
+                lc!() + tmp,
+            )?;
+
+            // new_xL = xR + tmp * (xL + Ci)
+            let new_xl_value = xl_value
+                .zip(tmp_value)
+                .zip(xr_value)
+                .map(|((xl, tmp), xr)| tmp * (xl + constant) + xr);
+            let new_xl =
+                cs.new_witness_variable(|| new_xl_value.ok_or(SynthesisError::AssignmentMissing))?;
+            cs.enforce_constraint(
+                lc!() + tmp,
+                lc!() + xl + (*constant, Variable::One),
+                lc!() + new_xl - xr,
+            )?;
+
+            xr = xl;
+            xr_value = xl_value;
+            xl = new_xl;
+            xl_value = new_xl_value;
+        }
+        Ok((xl, xl_value))
+    }
+}
+
+/// A circuit of this module, declaring how many of its public inputs are
+/// static: the first [`Self::NUM_STATIC_INPUTS`] inputs, which are fixed
+/// for a deployment, so that verifiers can precompute their terms.
+pub trait CircuitTemplate<F: PrimeField>: ConstraintSynthesizer<F> {
+    /// The number of static public inputs, allocated before the variable
+    /// ones.
+    const NUM_STATIC_INPUTS: usize;
+}
+
+/// Allocates a public input equal to the variable `value`.
+fn enforce_public<F: PrimeField>(
+    cs: &ConstraintSystemRef<F>,
+    (value, value_assignment): (Variable, Option<F>),
+) -> R1CSResult<()> {
+    let input =
+        cs.new_input_variable(|| value_assignment.ok_or(SynthesisError::AssignmentMissing))?;
+    cs.enforce_constraint(lc!() + value, lc!() + Variable::One, lc!() + input)
+}
+
+/// Allocates a witness variable constrained to be `0` or `1`.
+pub(crate) fn new_bit<F: PrimeField>(
+    cs: &ConstraintSystemRef<F>,
+    bit: Option<bool>,
+) -> R1CSResult<(Variable, Option<F>)> {
+    let value = bit.map(F::from);
+    let var = cs.new_witness_variable(|| value.ok_or(SynthesisError::AssignmentMissing))?;
+    cs.enforce_constraint(lc!() + var, lc!() + Variable::One - var, lc!())?;
+    Ok((var, value))
+}
+
+/// Allocates public inputs packing `values`, each given with its number of
+/// bits, in the layout of [`InputPacker`], and returns a witness variable
+/// equal to each value. Flags, on one bit, are returned as their bit.
+///
+/// Each bit is constrained to be `0` or `1`, so the verifier can read the
+/// values with [`crate::inputs::InputUnpacker`]. Fails with
+/// [`SynthesisError::Unsatisfiable`] if a value does not fit in its number
+/// of bits, see [`InputPacker::push`].
+pub fn enforce_packed_inputs<F: PrimeField>(
+    cs: &ConstraintSystemRef<F>,
+    values: &[(Option<u64>, usize)],
+) -> R1CSResult<Vec<Variable>> {
+    let capacity = packing_capacity::<F>();
+    let packed = values
+        .iter()
+        .map(|(value, num_bits)| value.map(|value| (value, *num_bits)))
+        .collect::<Option<Vec<_>>>()
+        .map(|values| {
+            let mut packer = InputPacker::<F>::new();
+            for (value, num_bits) in values {
+                packer.push(value, num_bits)?;
+            }
+            Ok(packer.finish())
+        })
+        .transpose()
+        .map_err(|_: SerializationError| SynthesisError::Unsatisfiable)?;
+    if values
+        .iter()
+        .any(|(_, num_bits)| *num_bits > 64 || *num_bits > capacity)
+    {
+        return Err(SynthesisError::Unsatisfiable);
+    }
+
+    let mut num_inputs = 0;
+    let mut enforce_input = |sum: LinearCombination<F>| {
+        let assignment = packed.as_ref().map(|packed| packed[num_inputs]);
+        num_inputs += 1;
+        let input =
+            cs.new_input_variable(|| assignment.ok_or(SynthesisError::AssignmentMissing))?;
+        cs.enforce_constraint(sum, lc!() + Variable::One, lc!() + input)
+    };
+
+    let mut variables = Vec::with_capacity(values.len());
+    let mut sum = lc!();
+    let mut position = 0;
+    let mut shift = F::one();
+    for (value, num_bits) in values {
+        if position + num_bits > capacity {
+            enforce_input(core::mem::take(&mut sum))?;
+            position = 0;
+            shift = F::one();
+        }
+        let mut bits = Vec::with_capacity(*num_bits);
+        for i in 0..*num_bits {
+            let (bit, _) = new_bit(cs, value.map(|value| (value >> i) & 1 == 1))?;
+            sum += (shift, bit);
+            shift.double_in_place();
+            bits.push(bit);
+        }
+        position += num_bits;
+        match bits[..] {
+            [] => variables.push(Variable::Zero),
+            [bit] => variables.push(bit),
+            _ => {
+                let assignment = value.map(F::from);
+                let variable = cs
+                    .new_witness_variable(|| assignment.ok_or(SynthesisError::AssignmentMissing))?;
+                let mut value_sum = lc!();
+                let mut coeff = F::one();
+                for bit in bits {
+                    value_sum += (coeff, bit);
+                    coeff.double_in_place();
+                }
+                cs.enforce_constraint(value_sum, lc!() + Variable::One, lc!() + variable)?;
+                variables.push(variable);
+            },
+        }
+    }
+    if position > 0 {
+        enforce_input(sum)?;
+    }
+    Ok(variables)
+}
+
+/// Proves knowledge of a preimage `(xl, xr)` of a public [`MiMC`] image.
+///
+/// Public inputs: the image.
+#[derive(Clone, Debug)]
+pub struct PreimageCircuit<F: PrimeField> {
+    /// The hash function.
+    pub mimc: MiMC<F>,
+    /// The preimage, or `None` during setup.
+    pub preimage: Option<(F, F)>,
+}
+
+impl<F: PrimeField> PreimageCircuit<F> {
+    /// Creates a circuit without a witness, for setup.
+    pub fn blank(mimc: MiMC<F>) -> Self {
+        Self {
+            mimc,
+            preimage: None,
+        }
+    }
+
+    /// Creates a circuit proving knowledge of `preimage`.
+    pub fn new(mimc: MiMC<F>, preimage: (F, F)) -> Self {
+        Self {
+            mimc,
+            preimage: Some(preimage),
+        }
+    }
+}
+
+impl<F: PrimeField> ConstraintSynthesizer<F> for PreimageCircuit<F> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> R1CSResult<()> {
+        let xl_value = self.preimage.map(|(xl, _)| xl);
+        let xr_value = self.preimage.map(|(_, xr)| xr);
+        let xl = cs.new_witness_variable(|| xl_value.ok_or(SynthesisError::AssignmentMissing))?;
+        let xr = cs.new_witness_variable(|| xr_value.ok_or(SynthesisError::AssignmentMissing))?;
+
+        let image = self
+            .mimc
+            .enforce(cs.clone(), (xl, xl_value), (xr, xr_value))?;
+        enforce_public(&cs, image)
+    }
+}
+
+impl<F: PrimeField> CircuitTemplate<F> for PreimageCircuit<F> {
+    const NUM_STATIC_INPUTS: usize = 0;
+}
+
+/// Proves that a leaf is in a [`MiMC`] Merkle tree with a public root.
+///
+/// Public inputs: the root.
+#[derive(Clone, Debug)]
+pub struct MerkleMembershipCircuit<F: PrimeField> {
+    /// The hash function.
+    pub mimc: MiMC<F>,
+    /// The depth of the tree.
+    pub depth: usize,
+    /// The leaf, or `None` during setup.
+    pub leaf: Option<F>,
+    /// For each level from the leaf up, the sibling of the current node and
+    /// whether the current node is the right child, or `None` during setup.
+    pub path: Option<Vec<(F, bool)>>,
+}
+
+impl<F: PrimeField> MerkleMembershipCircuit<F> {
+    /// Creates a circuit for trees of the given depth without a witness, for
+    /// setup.
+    pub fn blank(mimc: MiMC<F>, depth: usize) -> Self {
+        Self {
+            mimc,
+            depth,
+            leaf: None,
+            path: None,
+        }
+    }
+
+    /// Creates a circuit proving that `leaf` is at the position given by
+    /// `path` in a tree of depth `path.len()`.
+    pub fn new(mimc: MiMC<F>, leaf: F, path: Vec<(F, bool)>) -> Self {
+        Self {
+            mimc,
+            depth: path.len(),
+            leaf: Some(leaf),
+            path: Some(path),
+        }
+    }
+
+    /// Returns the root of the tree containing `leaf` at the position given
+    /// by `path`.
+    pub fn root(mimc: &MiMC<F>, leaf: F, path: &[(F, bool)]) -> F {
+        path.iter().fold(leaf, |node, &(sibling, is_right)| {
+            if is_right {
+                mimc.hash(sibling, node)
+            } else {
+                mimc.hash(node, sibling)
+            }
+        })
+    }
+}
+
+impl<F: PrimeField> MerkleMembershipCircuit<F> {
+    /// Enforces the path of `self` from the variable `leaf`, with the value
+    /// `self.leaf`, returning a new witness variable for the root.
+    fn enforce_path(
+        &self,
+        cs: &ConstraintSystemRef<F>,
+        leaf: Variable,
+    ) -> R1CSResult<(Variable, Option<F>)> {
+        let (mut node, mut node_value) = (leaf, self.leaf);
+        for level in 0..self.depth {
+            let ns = ns!(cs, "level");
+            let cs = ns.cs();
+            let step = self.path.as_ref().map(|path| path[level]);
+
+            let sibling_value = step.map(|(sibling, _)| sibling);
+            let sibling =
+                cs.new_witness_variable(|| sibling_value.ok_or(SynthesisError::AssignmentMissing))?;
+            let (is_right, is_right_value) = new_bit(&cs, step.map(|(_, is_right)| is_right))?;
+
+            // swap = is_right * (sibling - node), so that the children are
+            // left = node + swap and right = sibling - swap.
+            let swap_value = is_right_value
+                .zip(sibling_value)
+                .zip(node_value)
+                .map(|((is_right, sibling), node)| is_right * (sibling - node));
+            let swap =
+                cs.new_witness_variable(|| swap_value.ok_or(SynthesisError::AssignmentMissing))?;
+            cs.enforce_constraint(lc!() + is_right, lc!() + sibling - node, lc!() + swap)?;
+
+            let left_value = node_value.zip(swap_value).map(|(node, swap)| node + swap);
+            let left =
+                cs.new_witness_variable(|| left_value.ok_or(SynthesisError::AssignmentMissing))?;
+            cs.enforce_constraint(lc!() + node + swap, lc!() + Variable::One, lc!() + left)?;
+
+            let right_value = sibling_value
+                .zip(swap_value)
+                .map(|(sibling, swap)| sibling - swap);
+            let right =
+                cs.new_witness_variable(|| right_value.ok_or(SynthesisError::AssignmentMissing))?;
+            cs.enforce_constraint(lc!() + sibling - swap, lc!() + Variable::One, lc!() + right)?;
+
+            (node, node_value) = self
+                .mimc
+                .enforce(cs, (left, left_value), (right, right_value))?;
+        }
+        Ok((node, node_value))
+    }
+}
+
+impl<F: PrimeField> ConstraintSynthesizer<F> for MerkleMembershipCircuit<F> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> R1CSResult<()> {
+        if matches!(&self.path, Some(path) if path.len() != self.depth) {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+
+        let leaf =
+            cs.new_witness_variable(|| self.leaf.ok_or(SynthesisError::AssignmentMissing))?;
+        let root = self.enforce_path(&cs, leaf)?;
+        enforce_public(&cs, root)
+    }
+}
+
+impl<F: PrimeField> CircuitTemplate<F> for MerkleMembershipCircuit<F> {
+    const NUM_STATIC_INPUTS: usize = 0;
+}
+
+/// Proves that the leaf `hash(secret, 1)` is in a [`MiMC`] Merkle tree, and
+/// publishes the nullifier `hash(secret, topic)`, so that each member can
+/// e.g. vote once per topic without revealing their leaf.
+///
+/// Public inputs: the root, which is static since the tree of members is
+/// fixed for a deployment, then the topic and the nullifier.
+#[derive(Clone, Debug)]
+pub struct NullifierCircuit<F: PrimeField> {
+    /// The hash function.
+    pub mimc: MiMC<F>,
+    /// The depth of the tree.
+    pub depth: usize,
+    /// The topic, or `None` during setup. It must not be `1`, whose
+    /// nullifier is the leaf.
+    pub topic: Option<F>,
+    /// The secret of the member, or `None` during setup.
+    pub secret: Option<F>,
+    /// The path from the leaf of the member to the root, see
+    /// [`MerkleMembershipCircuit::path`], or `None` during setup.
+    pub path: Option<Vec<(F, bool)>>,
+}
+
+impl<F: PrimeField> NullifierCircuit<F> {
+    /// Creates a circuit for trees of the given depth without a witness, for
+    /// setup.
+    pub fn blank(mimc: MiMC<F>, depth: usize) -> Self {
+        Self {
+            mimc,
+            depth,
+            topic: None,
+            secret: None,
+            path: None,
+        }
+    }
+
+    /// Creates a circuit proving that the leaf of `secret` is at the
+    /// position given by `path`, with the nullifier of `secret` for `topic`.
+    pub fn new(mimc: MiMC<F>, topic: F, secret: F, path: Vec<(F, bool)>) -> Self {
+        Self {
+            mimc,
+            depth: path.len(),
+            topic: Some(topic),
+            secret: Some(secret),
+            path: Some(path),
+        }
+    }
+
+    /// Returns the leaf of the member with `secret`.
+    pub fn leaf(mimc: &MiMC<F>, secret: F) -> F {
+        mimc.hash(secret, F::one())
+    }
+
+    /// Returns the nullifier of `secret` for `topic`.
+    pub fn nullifier(mimc: &MiMC<F>, topic: F, secret: F) -> F {
+        mimc.hash(secret, topic)
+    }
+}
+
+impl<F: PrimeField> ConstraintSynthesizer<F> for NullifierCircuit<F> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> R1CSResult<()> {
+        if matches!(&self.path, Some(path) if path.len() != self.depth) {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+
+        let membership = MerkleMembershipCircuit {
+            leaf: self.secret.map(|secret| Self::leaf(&self.mimc, secret)),
+            mimc: self.mimc,
+            depth: self.depth,
+            path: self.path,
+        };
+        let mimc = &membership.mimc;
+
+        // The static root is allocated first, and constrained once computed.
+        let root_value = membership
+            .leaf
+            .zip(membership.path.as_ref())
+            .map(|(leaf, path)| MerkleMembershipCircuit::root(mimc, leaf, path));
+        let root = cs.new_input_variable(|| root_value.ok_or(SynthesisError::AssignmentMissing))?;
+        let topic =
+            cs.new_input_variable(|| self.topic.ok_or(SynthesisError::AssignmentMissing))?;
+
+        let secret =
+            cs.new_witness_variable(|| self.secret.ok_or(SynthesisError::AssignmentMissing))?;
+        let nullifier = mimc.enforce(cs.clone(), (secret, self.secret), (topic, self.topic))?;
+        enforce_public(&cs, nullifier)?;
+
+        let (leaf, _) = mimc.enforce(
+            cs.clone(),
+            (secret, self.secret),
+            (Variable::One, Some(F::one())),
+        )?;
+        let (node, _) = membership.enforce_path(&cs, leaf)?;
+        cs.enforce_constraint(lc!() + node, lc!() + Variable::One, lc!() + root)
+    }
+}
+
+impl<F: PrimeField> CircuitTemplate<F> for NullifierCircuit<F> {
+    const NUM_STATIC_INPUTS: usize = 1;
+}
+
+/// Proves that the value committed to by a public [`MiMC`] commitment
+/// `hash(value, blinding)` is less than `2^num_bits`.
+///
+/// Public inputs: the commitment.
+#[derive(Clone, Debug)]
+pub struct RangeCircuit<F: PrimeField> {
+    /// The hash function.
+    pub mimc: MiMC<F>,
+    /// The number of bits of the value.
+    pub num_bits: usize,
+    /// The value and the blinding factor of the commitment, or `None` during
+    /// setup.
+    pub opening: Option<(F, F)>,
+}
+
+impl<F: PrimeField> RangeCircuit<F> {
+    /// Creates a circuit for values of `num_bits` bits without a witness, for
+    /// setup.
+    pub fn blank(mimc: MiMC<F>, num_bits: usize) -> Self {
+        Self {
+            mimc,
+            num_bits,
+            opening: None,
+        }
+    }
+
+    /// Creates a circuit proving that `value`, committed to with `blinding`,
+    /// has at most `num_bits` bits.
+    pub fn new(mimc: MiMC<F>, num_bits: usize, value: F, blinding: F) -> Self {
+        Self {
+            mimc,
+            num_bits,
+            opening: Some((value, blinding)),
+        }
+    }
+
+    /// Returns the commitment to `value` with `blinding`.
+    pub fn commit(mimc: &MiMC<F>, value: F, blinding: F) -> F {
+        mimc.hash(value, blinding)
+    }
+}
+
+impl<F: PrimeField> ConstraintSynthesizer<F> for RangeCircuit<F> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> R1CSResult<()> {
+        if self.num_bits >= F::MODULUS_BIT_SIZE as usize {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+
+        let value_value = self.opening.map(|(value, _)| value);
+        let blinding_value = self.opening.map(|(_, blinding)| blinding);
+        let value =
+            cs.new_witness_variable(|| value_value.ok_or(SynthesisError::AssignmentMissing))?;
+        let blinding =
+            cs.new_witness_variable(|| blinding_value.ok_or(SynthesisError::AssignmentMissing))?;
+
+        let bits = value_value.map(|value| value.into_bigint().to_bits_le());
+        let mut sum = lc!();
+        let mut coeff = F::one();
+        for i in 0..self.num_bits {
+            let (bit, _) = new_bit(&cs, bits.as_ref().map(|bits| bits[i]))?;
+            sum += (coeff, bit);
+            coeff.double_in_place();
+        }
+        cs.enforce_constraint(sum, lc!() + Variable::One, lc!() + value)?;
+
+        let commitment =
+            self.mimc
+                .enforce(cs.clone(), (value, value_value), (blinding, blinding_value))?;
+        enforce_public(&cs, commitment)
+    }
+}
+
+impl<F: PrimeField> CircuitTemplate<F> for RangeCircuit<F> {
+    const NUM_STATIC_INPUTS: usize = 0;
+}
+
+/// Generates a proving key and a prepared verifying key for `circuit`, given
+/// without a witness, with its [`CircuitTemplate::NUM_STATIC_INPUTS`]
+/// static inputs.
+pub fn setup<E, C>(
+    circuit: C,
+    rng: &mut impl Rng,
+) -> R1CSResult<(ProvingKey<E>, PreparedVerifyingKey<E>)>
+where
+    E: Pairing,
+    C: CircuitTemplate<E::ScalarField>,
+{
+    let pk = Groth16::<E>::generate_random_parameters_with_reduction(
+        circuit,
+        rng,
+        C::NUM_STATIC_INPUTS + 1,
+        0,
+    )?;
+    let pvk = crate::prepare_verifying_key(&pk.vk);
+    Ok((pk, pvk))
+}
+
+/// Proves `circuit`, returning the proof together with its public inputs.
+pub fn prove<E, C>(
+    pk: &ProvingKey<E>,
+    circuit: C,
+    rng: &mut impl Rng,
+) -> R1CSResult<(Proof<E>, Vec<E::ScalarField>)>
+where
+    E: Pairing,
+    C: ConstraintSynthesizer<E::ScalarField>,
+{
+    Groth16::<E>::prove_and_output_inputs(pk, circuit, rng)
+}
+
+/// Verifies a proof of one of the circuits of this module against its
+/// public inputs, static inputs first as returned by [`prove`].
+pub fn verify<E: Pairing>(
+    pvk: &PreparedVerifyingKey<E>,
+    inputs: &[E::ScalarField],
+    proof: &Proof<E>,
+) -> Result<bool, VerificationError> {
+    let num_static = pvk.vk.gamma_abc_g1_static.len().saturating_sub(1);
+    let (static_inputs, variable_inputs) = inputs.split_at(num_static.min(inputs.len()));
+    Groth16::<E>::verify_with_variables(pvk, proof, static_inputs, variable_inputs)
+}
@@ -0,0 +1,162 @@
+use crate::VerifyingKey;
+use ark_ec::pairing::Pairing;
+use ark_relations::r1cs::{Result as R1CSResult, SynthesisError};
+use ark_serialize::CanonicalSerialize;
+use ark_std::{
+    fmt::{self, Write},
+    string::String,
+    vec::Vec,
+};
+
+/// The number of bytes per line of the generated constants.
+const BYTES_PER_LINE: usize = 12;
+
+/// Returns the compressed serialization of `point`.
+fn compressed(point: &impl CanonicalSerialize) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(point.compressed_size());
+    point
+        .serialize_compressed(&mut bytes)
+        .expect("serializing to a vector cannot fail");
+    bytes
+}
+
+/// Writes `bytes` as an array literal, indenting its lines by `indent`
+/// spaces.
+fn write_bytes(source: &mut String, bytes: &[u8], indent: usize) -> fmt::Result {
+    writeln!(source, "[")?;
+    for line in bytes.chunks(BYTES_PER_LINE) {
+        write!(source, "{:indent$}", "", indent = indent + 4)?;
+        for (i, byte) in line.iter().enumerate() {
+            let separator = if i + 1 == line.len() { "," } else { ", " };
+            write!(source, "0x{:02x}{}", byte, separator)?;
+        }
+        writeln!(source)?;
+    }
+    write!(source, "{:indent$}]", "", indent = indent)
+}
+
+/// Generates a standalone `no_std` Rust source file verifying proofs against
+/// `vk`, for the `lib.rs` of a crate depending on `ark-ec`, `ark-ff`,
+/// `ark-serialize` and the crate of the curve, whose pairing is at the path
+/// `pairing`, e.g. `ark_bn254::Bn254`. This is meant to be called from a
+/// build script.
+///
+/// The file exposes a single `verify(proof_bytes, inputs)` function taking
+/// the compressed serialization of a proof and the static inputs followed by
+/// the variable inputs. The key is embedded as the compressed serializations
+/// of its points, in static data rather than in code, and is deserialized
+/// without validation on each verification, trading time for flash usage on
+/// embedded devices.
+///
+/// Fails with [`SynthesisError::MalformedVerifyingKey`] if `vk` lacks the
+/// constant term.
+pub fn generate_verifier<E: Pairing>(vk: &VerifyingKey<E>, pairing: &str) -> R1CSResult<String> {
+    if vk.gamma_abc_g1_static.is_empty() {
+        return Err(SynthesisError::MalformedVerifyingKey);
+    }
+    let gamma_abc_g1 = vk
+        .gamma_abc_g1_static
+        .iter()
+        .chain(&vk.gamma_abc_g1_variable)
+        .collect::<Vec<_>>();
+
+    let mut source = String::new();
+    write_verifier(&mut source, vk, pairing, &gamma_abc_g1)
+        .expect("writing to a string cannot fail");
+    Ok(source)
+}
+
+/// Writes the source of [`generate_verifier`] to `source`.
+fn write_verifier<E: Pairing>(
+    source: &mut String,
+    vk: &VerifyingKey<E>,
+    pairing: &str,
+    gamma_abc_g1: &[&E::G1Affine],
+) -> fmt::Result {
+    writeln!(
+        source,
+        "//! A Groth16 verifier for a fixed verifying key, generated by
+//! darklake-groth16. Do not edit.
+
+#![no_std]
+
+use ark_ec::{{pairing::Pairing, AffineRepr, CurveGroup}};
+use ark_ff::Zero;
+use ark_serialize::{{CanonicalDeserialize, Compress, Validate}};
+
+type Curve = {};
+type G1 = <Curve as Pairing>::G1Affine;
+type G2 = <Curve as Pairing>::G2Affine;
+
+/// The scalar field of the public inputs.
+pub type Fr = <Curve as Pairing>::ScalarField;
+
+/// The number of public inputs.
+pub const NUM_INPUTS: usize = {};
+",
+        pairing,
+        gamma_abc_g1.len() - 1
+    )?;
+
+    for (name, bytes) in [
+        ("ALPHA_G1", compressed(&vk.alpha_g1)),
+        ("BETA_G2", compressed(&vk.beta_g2)),
+        ("GAMMA_G2", compressed(&vk.gamma_g2)),
+        ("DELTA_G2", compressed(&vk.delta_g2)),
+    ] {
+        write!(source, "static {}: [u8; {}] = ", name, bytes.len())?;
+        write_bytes(source, &bytes, 0)?;
+        writeln!(source, ";")?;
+    }
+    writeln!(
+        source,
+        "static GAMMA_ABC_G1: [[u8; {}]; {}] = [",
+        vk.alpha_g1.compressed_size(),
+        gamma_abc_g1.len()
+    )?;
+    for point in gamma_abc_g1 {
+        write!(source, "    ")?;
+        write_bytes(source, &compressed(*point), 4)?;
+        writeln!(source, ",")?;
+    }
+    writeln!(source, "];")?;
+
+    write!(
+        source,
+        "
+/// Deserializes a point of the verifying key.
+fn point<P: CanonicalDeserialize>(bytes: &[u8]) -> P {{
+    P::deserialize_with_mode(bytes, Compress::Yes, Validate::No).unwrap()
+}}
+
+/// Verifies a proof, in the compressed serialization of arkworks, against
+/// the static inputs followed by the variable inputs. Returns `false` if the
+/// proof is malformed or if there are not [`NUM_INPUTS`] inputs.
+pub fn verify(proof_bytes: &[u8], inputs: &[Fr]) -> bool {{
+    if inputs.len() != NUM_INPUTS {{
+        return false;
+    }}
+    let mut reader = proof_bytes;
+    let (a, b, c) = match (
+        G1::deserialize_compressed(&mut reader),
+        G2::deserialize_compressed(&mut reader),
+        G1::deserialize_compressed(&mut reader),
+    ) {{
+        (Ok(a), Ok(b), Ok(c)) if reader.is_empty() => (a, b, c),
+        _ => return false,
+    }};
+
+    let mut prepared_inputs = point::<G1>(&GAMMA_ABC_G1[0]).into_group();
+    for (base, input) in GAMMA_ABC_G1[1..].iter().zip(inputs) {{
+        prepared_inputs += point::<G1>(base) * input;
+    }}
+
+    Curve::multi_pairing(
+        [a, -point::<G1>(&ALPHA_G1), -prepared_inputs.into_affine(), -c],
+        [b, point::<G2>(&BETA_G2), point::<G2>(&GAMMA_G2), point::<G2>(&DELTA_G2)],
+    )
+    .is_zero()
+}}
+"
+    )
+}
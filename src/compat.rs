@@ -0,0 +1,342 @@
+use crate::{r1cs_to_qap::LibsnarkReduction, KeyBasis};
+use ark_crypto_primitives::snark::{CircuitSpecificSetupSNARK, SNARK};
+use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup, VariableBaseMSM};
+use ark_relations::r1cs::{ConstraintSynthesizer, Result as R1CSResult, SynthesisError};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::{
+    marker::PhantomData,
+    rand::{Rng, RngCore},
+    vec::Vec,
+};
+
+pub use crate::{r1cs_to_qap, r1cs_to_qap::R1CSToQAP, LegacyVerifyingKey as VerifyingKey, Proof};
+
+/// The prepared verifying key of upstream Groth16, over a
+/// [`VerifyingKey`] in the legacy layout.
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct PreparedVerifyingKey<E: Pairing> {
+    /// The unprepared verification key.
+    pub vk: VerifyingKey<E>,
+    /// The element `e(alpha * G, beta * H)` in `E::GT`.
+    pub alpha_g1_beta_g2: E::TargetField,
+    /// The element `- gamma * H` in `E::G2`, prepared for use in pairings.
+    pub gamma_g2_neg_pc: E::G2Prepared,
+    /// The element `- delta * H` in `E::G2`, prepared for use in pairings.
+    pub delta_g2_neg_pc: E::G2Prepared,
+}
+
+impl<E: Pairing> Default for PreparedVerifyingKey<E> {
+    fn default() -> Self {
+        Self {
+            vk: VerifyingKey::default(),
+            alpha_g1_beta_g2: E::TargetField::default(),
+            gamma_g2_neg_pc: E::G2Prepared::default(),
+            delta_g2_neg_pc: E::G2Prepared::default(),
+        }
+    }
+}
+
+impl<E: Pairing> From<PreparedVerifyingKey<E>> for VerifyingKey<E> {
+    fn from(other: PreparedVerifyingKey<E>) -> Self {
+        other.vk
+    }
+}
+
+impl<E: Pairing> From<VerifyingKey<E>> for PreparedVerifyingKey<E> {
+    fn from(other: VerifyingKey<E>) -> Self {
+        prepare_verifying_key(&other)
+    }
+}
+
+/// The proving key of upstream Groth16, in its layout: without the basis of
+/// the `h_query`, which is always the monomial basis.
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct ProvingKey<E: Pairing> {
+    /// The underlying verification key.
+    pub vk: VerifyingKey<E>,
+    /// The element `beta * G` in `E::G1`.
+    pub beta_g1: E::G1Affine,
+    /// The element `delta * G` in `E::G1`.
+    pub delta_g1: E::G1Affine,
+    /// The elements `a_i * G` in `E::G1`.
+    pub a_query: Vec<E::G1Affine>,
+    /// The elements `b_i * G` in `E::G1`.
+    pub b_g1_query: Vec<E::G1Affine>,
+    /// The elements `b_i * H` in `E::G2`.
+    pub b_g2_query: Vec<E::G2Affine>,
+    /// The elements `h_i * G` in `E::G1`.
+    pub h_query: Vec<E::G1Affine>,
+    /// The elements `l_i * G` in `E::G1`.
+    pub l_query: Vec<E::G1Affine>,
+}
+
+/// Converts a proving key of upstream Groth16, whose inputs all become
+/// variable inputs.
+impl<E: Pairing> From<ProvingKey<E>> for crate::ProvingKey<E> {
+    fn from(pk: ProvingKey<E>) -> Self {
+        // The constant term is static and the inputs variable. Unlike
+        // `LegacyVerifyingKey::into_split`, keys without the constant term
+        // are kept, as upstream only fails to verify with them.
+        let mut gamma_abc_g1_static = pk.vk.gamma_abc_g1;
+        let num_static = gamma_abc_g1_static.len().min(1);
+        let gamma_abc_g1_variable = gamma_abc_g1_static.split_off(num_static);
+        let vk = crate::VerifyingKey {
+            alpha_g1: pk.vk.alpha_g1,
+            beta_g2: pk.vk.beta_g2,
+            gamma_g2: pk.vk.gamma_g2,
+            delta_g2: pk.vk.delta_g2,
+            gamma_abc_g1_static,
+            gamma_abc_g1_variable,
+        };
+        Self {
+            vk,
+            beta_g1: pk.beta_g1,
+            delta_g1: pk.delta_g1,
+            a_query: pk.a_query,
+            b_g1_query: pk.b_g1_query,
+            b_g2_query: pk.b_g2_query,
+            h_basis: KeyBasis::Monomial,
+            h_query: pk.h_query,
+            l_query: pk.l_query,
+        }
+    }
+}
+
+/// Converts a proving key to the layout of upstream Groth16, failing with
+/// [`SynthesisError::MalformedVerifyingKey`] if its `h_query` is not in the
+/// monomial basis.
+impl<E: Pairing> TryFrom<crate::ProvingKey<E>> for ProvingKey<E> {
+    type Error = SynthesisError;
+
+    fn try_from(pk: crate::ProvingKey<E>) -> R1CSResult<Self> {
+        if pk.h_basis != KeyBasis::Monomial {
+            return Err(SynthesisError::MalformedVerifyingKey);
+        }
+        Ok(Self {
+            vk: pk.vk.into(),
+            beta_g1: pk.beta_g1,
+            delta_g1: pk.delta_g1,
+            a_query: pk.a_query,
+            b_g1_query: pk.b_g1_query,
+            b_g2_query: pk.b_g2_query,
+            h_query: pk.h_query,
+            l_query: pk.l_query,
+        })
+    }
+}
+
+/// Prepare the verifying key `vk` for use in proof verification.
+pub fn prepare_verifying_key<E: Pairing>(vk: &VerifyingKey<E>) -> PreparedVerifyingKey<E> {
+    PreparedVerifyingKey {
+        vk: vk.clone(),
+        alpha_g1_beta_g2: E::pairing(vk.alpha_g1, vk.beta_g2).0,
+        gamma_g2_neg_pc: (-vk.gamma_g2.into_group()).into_affine().into(),
+        delta_g2_neg_pc: (-vk.delta_g2.into_group()).into_affine().into(),
+    }
+}
+
+/// The SNARK of [[Groth16]](https://eprint.iacr.org/2016/260.pdf), with the
+/// public API of the upstream `ark-groth16` crate, so that code written
+/// against it compiles unchanged once its imports point to this module.
+///
+/// Keys are in the layouts of upstream Groth16, and serialize as upstream
+/// keys do. Proofs are created by the prover of this crate, with all inputs
+/// variable, from a [`crate::ProvingKey`] converted from the key on each
+/// call: services proving at volume should move to [`crate::Groth16`],
+/// which proves from its keys without copying them.
+pub struct Groth16<E: Pairing, QAP: R1CSToQAP = LibsnarkReduction> {
+    _p: PhantomData<(E, QAP)>,
+}
+
+impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
+    /// Generates a random common reference string for
+    /// a circuit using the provided R1CS-to-QAP reduction.
+    pub fn generate_random_parameters_with_reduction<C>(
+        circuit: C,
+        rng: &mut impl Rng,
+    ) -> R1CSResult<ProvingKey<E>>
+    where
+        C: ConstraintSynthesizer<E::ScalarField>,
+    {
+        crate::Groth16::<E, QAP>::generate_random_parameters_with_reduction(circuit, rng, 1, 0)?
+            .try_into()
+    }
+
+    /// Create parameters for a circuit, given some toxic waste, R1CS to QAP
+    /// calculator and group generators.
+    #[allow(clippy::too_many_arguments)]
+    pub fn generate_parameters_with_qap<C>(
+        circuit: C,
+        alpha: E::ScalarField,
+        beta: E::ScalarField,
+        gamma: E::ScalarField,
+        delta: E::ScalarField,
+        g1_generator: E::G1,
+        g2_generator: E::G2,
+        rng: &mut impl Rng,
+    ) -> R1CSResult<ProvingKey<E>>
+    where
+        C: ConstraintSynthesizer<E::ScalarField>,
+    {
+        crate::Groth16::<E, QAP>::generate_parameters_with_qap(
+            circuit,
+            alpha,
+            beta,
+            gamma,
+            delta,
+            1,
+            0,
+            g1_generator,
+            g2_generator,
+            rng,
+        )?
+        .try_into()
+    }
+
+    /// Create a Groth16 proof that is zero-knowledge using the provided
+    /// R1CS-to-QAP reduction. This method samples randomness for zero
+    /// knowledge via `rng`.
+    pub fn create_random_proof_with_reduction<C>(
+        circuit: C,
+        pk: &ProvingKey<E>,
+        rng: &mut impl Rng,
+    ) -> R1CSResult<Proof<E>>
+    where
+        C: ConstraintSynthesizer<E::ScalarField>,
+    {
+        crate::Groth16::<E, QAP>::create_random_proof_with_reduction(
+            circuit,
+            &pk.clone().into(),
+            rng,
+        )
+    }
+
+    /// Create a Groth16 proof that is *not* zero-knowledge with the provided
+    /// R1CS-to-QAP reduction.
+    pub fn create_proof_with_reduction_no_zk<C>(
+        circuit: C,
+        pk: &ProvingKey<E>,
+    ) -> R1CSResult<Proof<E>>
+    where
+        C: ConstraintSynthesizer<E::ScalarField>,
+    {
+        crate::Groth16::<E, QAP>::create_proof_with_reduction_no_zk(circuit, &pk.clone().into())
+    }
+
+    /// Create a Groth16 proof using randomness `r` and `s` and the provided
+    /// R1CS-to-QAP reduction.
+    pub fn create_proof_with_reduction<C>(
+        circuit: C,
+        pk: &ProvingKey<E>,
+        r: E::ScalarField,
+        s: E::ScalarField,
+    ) -> R1CSResult<Proof<E>>
+    where
+        C: ConstraintSynthesizer<E::ScalarField>,
+    {
+        crate::Groth16::<E, QAP>::create_proof_with_reduction(circuit, &pk.clone().into(), r, s)
+    }
+
+    /// Given a Groth16 proof, returns a fresh proof of the same statement,
+    /// see [`crate::Groth16::rerandomize_proof`].
+    pub fn rerandomize_proof(
+        vk: &VerifyingKey<E>,
+        proof: &Proof<E>,
+        rng: &mut impl Rng,
+    ) -> Proof<E> {
+        // Rerandomization only uses `delta`.
+        let vk = crate::VerifyingKey {
+            delta_g2: vk.delta_g2,
+            ..Default::default()
+        };
+        crate::Groth16::<E, QAP>::rerandomize_proof(&vk, proof, rng)
+    }
+
+    /// Prepare proof inputs for use with [`Self::verify_proof_with_prepared_inputs`],
+    /// wrt the prepared verification key `pvk` and instance public inputs.
+    pub fn prepare_inputs(
+        pvk: &PreparedVerifyingKey<E>,
+        public_inputs: &[E::ScalarField],
+    ) -> R1CSResult<E::G1> {
+        match pvk.vk.gamma_abc_g1.split_first() {
+            Some((constant, bases)) if bases.len() == public_inputs.len() => {
+                Ok(E::G1::msm_unchecked(bases, public_inputs) + constant)
+            },
+            _ => Err(SynthesisError::MalformedVerifyingKey),
+        }
+    }
+
+    /// Verify a Groth16 proof `proof` against the prepared verification key
+    /// `pvk` and prepared public inputs. This should be preferred over
+    /// [`Self::verify_proof`] if the instance's public inputs are known in
+    /// advance.
+    pub fn verify_proof_with_prepared_inputs(
+        pvk: &PreparedVerifyingKey<E>,
+        proof: &Proof<E>,
+        prepared_inputs: &E::G1,
+    ) -> R1CSResult<bool> {
+        let qap = E::multi_miller_loop(
+            [proof.a, prepared_inputs.into_affine(), proof.c],
+            [
+                proof.b.into(),
+                pvk.gamma_g2_neg_pc.clone(),
+                pvk.delta_g2_neg_pc.clone(),
+            ],
+        );
+        let test = E::final_exponentiation(qap).ok_or(SynthesisError::UnexpectedIdentity)?;
+        Ok(test.0 == pvk.alpha_g1_beta_g2)
+    }
+
+    /// Verify a Groth16 proof `proof` against the prepared verification key
+    /// `pvk`, with respect to the instance `public_inputs`.
+    pub fn verify_proof(
+        pvk: &PreparedVerifyingKey<E>,
+        proof: &Proof<E>,
+        public_inputs: &[E::ScalarField],
+    ) -> R1CSResult<bool> {
+        let prepared_inputs = Self::prepare_inputs(pvk, public_inputs)?;
+        Self::verify_proof_with_prepared_inputs(pvk, proof, &prepared_inputs)
+    }
+}
+
+impl<E: Pairing, QAP: R1CSToQAP> SNARK<E::ScalarField> for Groth16<E, QAP> {
+    type ProvingKey = ProvingKey<E>;
+    type VerifyingKey = VerifyingKey<E>;
+    type Proof = Proof<E>;
+    type ProcessedVerifyingKey = PreparedVerifyingKey<E>;
+    type Error = SynthesisError;
+
+    fn circuit_specific_setup<C: ConstraintSynthesizer<E::ScalarField>, R: RngCore>(
+        circuit: C,
+        rng: &mut R,
+    ) -> Result<(Self::ProvingKey, Self::VerifyingKey), Self::Error> {
+        let pk = Self::generate_random_parameters_with_reduction(circuit, rng)?;
+        let vk = pk.vk.clone();
+
+        Ok((pk, vk))
+    }
+
+    fn prove<C: ConstraintSynthesizer<E::ScalarField>, R: RngCore>(
+        pk: &Self::ProvingKey,
+        circuit: C,
+        rng: &mut R,
+    ) -> Result<Self::Proof, Self::Error> {
+        Self::create_random_proof_with_reduction(circuit, pk, rng)
+    }
+
+    fn process_vk(
+        circuit_vk: &Self::VerifyingKey,
+    ) -> Result<Self::ProcessedVerifyingKey, Self::Error> {
+        Ok(prepare_verifying_key(circuit_vk))
+    }
+
+    fn verify_with_processed_vk(
+        circuit_pvk: &Self::ProcessedVerifyingKey,
+        x: &[E::ScalarField],
+        proof: &Self::Proof,
+    ) -> Result<bool, Self::Error> {
+        Self::verify_proof(circuit_pvk, proof, x)
+    }
+}
+
+impl<E: Pairing, QAP: R1CSToQAP> CircuitSpecificSetupSNARK<E::ScalarField> for Groth16<E, QAP> {}
@@ -10,26 +10,26 @@ use ark_crypto_primitives::{
     sponge::constraints::AbsorbGadget,
 };
 use ark_ec::{pairing::Pairing, AffineRepr};
-use ark_ff::Field;
+use ark_ff::PrimeField;
 use ark_r1cs_std::{
     alloc::{AllocVar, AllocationMode},
     boolean::Boolean,
-    convert::{ToBitsGadget, ToBytesGadget},
     eq::EqGadget,
     fields::fp::FpVar,
     groups::CurveVar,
     pairing::PairingVar,
     uint8::UInt8,
+    ToBytesGadget,
 };
 use ark_relations::r1cs::{Namespace, SynthesisError};
 use ark_std::{borrow::Borrow, marker::PhantomData, vec::Vec};
 
-type BasePrimeField<E> = <<E as Pairing>::BaseField as Field>::BasePrimeField;
+type BasePrimeField<E> = <E as Pairing>::BaseField;
 
 /// The proof variable for the Groth16 construction
 #[derive(Derivative)]
 #[derivative(Clone(bound = "P::G1Var: Clone, P::G2Var: Clone"))]
-pub struct ProofVar<E: Pairing, P: PairingVar<E>> {
+pub struct ProofVar<E: Pairing, P: PairingVar<E, BasePrimeField<E>>> {
     /// The `A` element in `G1`.
     pub a: P::G1Var,
     /// The `B` element in `G2`.
@@ -43,7 +43,7 @@ pub struct ProofVar<E: Pairing, P: PairingVar<E>> {
 #[derivative(Clone(
     bound = "P::G1Var: Clone, P::GTVar: Clone, P::G1PreparedVar: Clone, P::G2PreparedVar: Clone"
 ))]
-pub struct VerifyingKeyVar<E: Pairing, P: PairingVar<E>> {
+pub struct VerifyingKeyVar<E: Pairing, P: PairingVar<E, BasePrimeField<E>>> {
     #[doc(hidden)]
     pub alpha_g1: P::G1Var,
     #[doc(hidden)]
@@ -53,10 +53,12 @@ pub struct VerifyingKeyVar<E: Pairing, P: PairingVar<E>> {
     #[doc(hidden)]
     pub delta_g2: P::G2Var,
     #[doc(hidden)]
-    pub gamma_abc_g1: Vec<P::G1Var>,
+    pub gamma_abc_g1_static: Vec<P::G1Var>,
+    #[doc(hidden)]
+    pub gamma_abc_g1_variable: Vec<P::G1Var>,
 }
 
-impl<E: Pairing, P: PairingVar<E>> VerifyingKeyVar<E, P> {
+impl<E: Pairing, P: PairingVar<E, BasePrimeField<E>>> VerifyingKeyVar<E, P> {
     /// Prepare `self` for use in proof verification.
     pub fn prepare(&self) -> Result<PreparedVerifyingKeyVar<E, P>, SynthesisError> {
         let alpha_g1_pc = P::prepare_g1(&self.alpha_g1)?;
@@ -70,7 +72,8 @@ impl<E: Pairing, P: PairingVar<E>> VerifyingKeyVar<E, P> {
             alpha_g1_beta_g2,
             gamma_g2_neg_pc,
             delta_g2_neg_pc,
-            gamma_abc_g1: self.gamma_abc_g1.clone(),
+            gamma_abc_g1_static: self.gamma_abc_g1_static.clone(),
+            gamma_abc_g1_variable: self.gamma_abc_g1_variable.clone(),
         })
     }
 }
@@ -78,7 +81,7 @@ impl<E: Pairing, P: PairingVar<E>> VerifyingKeyVar<E, P> {
 impl<E, P> AbsorbGadget<E::BaseField> for VerifyingKeyVar<E, P>
 where
     E: Pairing,
-    P: PairingVar<E>,
+    P: PairingVar<E, BasePrimeField<E>>,
     P::G1Var: AbsorbGadget<E::BaseField>,
     P::G2Var: AbsorbGadget<E::BaseField>,
 {
@@ -87,10 +90,13 @@ where
         bytes.extend(self.beta_g2.to_sponge_bytes()?);
         bytes.extend(self.gamma_g2.to_sponge_bytes()?);
         bytes.extend(self.delta_g2.to_sponge_bytes()?);
-        self.gamma_abc_g1.iter().try_for_each(|g| {
+        for g in self
+            .gamma_abc_g1_static
+            .iter()
+            .chain(&self.gamma_abc_g1_variable)
+        {
             bytes.extend(g.to_sponge_bytes()?);
-            Ok(())
-        })?;
+        }
         Ok(bytes)
     }
 
@@ -101,10 +107,13 @@ where
         field_elements.extend(self.beta_g2.to_sponge_field_elements()?);
         field_elements.extend(self.gamma_g2.to_sponge_field_elements()?);
         field_elements.extend(self.delta_g2.to_sponge_field_elements()?);
-        self.gamma_abc_g1.iter().try_for_each(|g| {
+        for g in self
+            .gamma_abc_g1_static
+            .iter()
+            .chain(&self.gamma_abc_g1_variable)
+        {
             field_elements.extend(g.to_sponge_field_elements()?);
-            Ok(())
-        })?;
+        }
         Ok(field_elements)
     }
 }
@@ -116,7 +125,7 @@ where
     Clone(bound = "P::G1Var: Clone, P::GTVar: Clone, P::G1PreparedVar: Clone, \
     P::G2PreparedVar: Clone, ")
 )]
-pub struct PreparedVerifyingKeyVar<E: Pairing, P: PairingVar<E>> {
+pub struct PreparedVerifyingKeyVar<E: Pairing, P: PairingVar<E, BasePrimeField<E>>> {
     #[doc(hidden)]
     pub alpha_g1_beta_g2: P::GTVar,
     #[doc(hidden)]
@@ -124,14 +133,127 @@ pub struct PreparedVerifyingKeyVar<E: Pairing, P: PairingVar<E>> {
     #[doc(hidden)]
     pub delta_g2_neg_pc: P::G2PreparedVar,
     #[doc(hidden)]
-    pub gamma_abc_g1: Vec<P::G1Var>,
+    pub gamma_abc_g1_static: Vec<P::G1Var>,
+    #[doc(hidden)]
+    pub gamma_abc_g1_variable: Vec<P::G1Var>,
+}
+
+impl<E: Pairing, P: PairingVar<E, BasePrimeField<E>>> PreparedVerifyingKeyVar<E, P> {
+    /// Returns the sum of the constant term and of the products of the
+    /// static and variable inputs with their bases, like
+    /// [`Groth16::prepare_inputs_with_variables`]. Fails with
+    /// [`SynthesisError::MalformedVerifyingKey`] if the numbers of inputs do
+    /// not match the key.
+    fn prepare_inputs(
+        &self,
+        static_inputs: Vec<Vec<Boolean<BasePrimeField<E>>>>,
+        variable_inputs: Vec<Vec<Boolean<BasePrimeField<E>>>>,
+    ) -> Result<P::G1Var, SynthesisError> {
+        let (constant, static_bases) = self
+            .gamma_abc_g1_static
+            .split_first()
+            .ok_or(SynthesisError::MalformedVerifyingKey)?;
+        if static_inputs.len() != static_bases.len()
+            || variable_inputs.len() != self.gamma_abc_g1_variable.len()
+        {
+            return Err(SynthesisError::MalformedVerifyingKey);
+        }
+
+        let mut g_ic = constant.clone();
+        for (input, base) in static_inputs
+            .iter()
+            .chain(&variable_inputs)
+            .zip(static_bases.iter().chain(&self.gamma_abc_g1_variable))
+        {
+            g_ic += base.scalar_mul_le(input.iter())?;
+        }
+        Ok(g_ic)
+    }
+
+    /// Checks the pairing equation of `proof` against the prepared inputs
+    /// `g_ic`.
+    fn verify_prepared_inputs(
+        &self,
+        g_ic: &P::G1Var,
+        proof: &ProofVar<E, P>,
+    ) -> Result<Boolean<BasePrimeField<E>>, SynthesisError> {
+        let test_exp = {
+            let proof_a_prep = P::prepare_g1(&proof.a)?;
+            let proof_b_prep = P::prepare_g2(&proof.b)?;
+            let proof_c_prep = P::prepare_g1(&proof.c)?;
+
+            let g_ic_prep = P::prepare_g1(g_ic)?;
+
+            P::miller_loop(
+                &[proof_a_prep, g_ic_prep, proof_c_prep],
+                &[
+                    proof_b_prep,
+                    self.gamma_g2_neg_pc.clone(),
+                    self.delta_g2_neg_pc.clone(),
+                ],
+            )?
+        };
+
+        let test = P::final_exponentiation(&test_exp)?;
+        test.is_eq(&self.alpha_g1_beta_g2)
+    }
+}
+
+/// The static and variable public inputs of a proof, as little endian bits
+/// in a constraint system.
+#[derive(Clone)]
+pub struct SplitInputVar<F: PrimeField, CF: PrimeField> {
+    /// The static inputs.
+    pub static_inputs: BooleanInputVar<F, CF>,
+    /// The variable inputs.
+    pub variable_inputs: BooleanInputVar<F, CF>,
+}
+
+impl<F: PrimeField, CF: PrimeField> SplitInputVar<F, CF> {
+    /// Groups allocated `static_inputs` and `variable_inputs`.
+    pub fn new(
+        static_inputs: BooleanInputVar<F, CF>,
+        variable_inputs: BooleanInputVar<F, CF>,
+    ) -> Self {
+        Self {
+            static_inputs,
+            variable_inputs,
+        }
+    }
+}
+
+/// Returns whether `proof` verifies against `vk` and the static and
+/// variable `inputs` in the constraint system of the namespace `cs`,
+/// mirroring [`Groth16::verify_with_variables`] for recursive proofs.
+///
+/// Fails with [`SynthesisError::MalformedVerifyingKey`] if the numbers of
+/// inputs do not match the key.
+#[cfg(feature = "r1cs-gadget")]
+pub fn verify_in_circuit<E, P>(
+    cs: impl Into<Namespace<BasePrimeField<E>>>,
+    vk: &VerifyingKeyVar<E, P>,
+    proof: &ProofVar<E, P>,
+    inputs: &SplitInputVar<E::ScalarField, BasePrimeField<E>>,
+) -> Result<Boolean<BasePrimeField<E>>, SynthesisError>
+where
+    E: Pairing,
+    P: PairingVar<E, BasePrimeField<E>>,
+{
+    // The constraints are labeled with the namespace until it is dropped.
+    let _ns = cs.into();
+    let pvk = vk.prepare()?;
+    let g_ic = pvk.prepare_inputs(
+        inputs.static_inputs.clone().into_iter().collect(),
+        inputs.variable_inputs.clone().into_iter().collect(),
+    )?;
+    pvk.verify_prepared_inputs(&g_ic, proof)
 }
 
 /// Constraints for the verifier of the SNARK of [[Groth16]](https://eprint.iacr.org/2016/260.pdf).
 pub struct Groth16VerifierGadget<E, P, QAP = LibsnarkReduction>
 where
     E: Pairing,
-    P: PairingVar<E>,
+    P: PairingVar<E, BasePrimeField<E>>,
     QAP: R1CSToQAP,
 {
     _pairing_engine: PhantomData<E>,
@@ -144,7 +266,7 @@ impl<E, QAP, P> SNARKGadget<E::ScalarField, BasePrimeField<E>, Groth16<E, QAP>>
 where
     E: Pairing,
     QAP: R1CSToQAP,
-    P: PairingVar<E>,
+    P: PairingVar<E, BasePrimeField<E>>,
 {
     type ProcessedVerifyingKeyVar = PreparedVerifyingKeyVar<E, P>;
     type VerifyingKeyVar = VerifyingKeyVar<E, P>;
@@ -156,7 +278,7 @@ where
     fn verifier_size(
         circuit_vk: &<Groth16<E> as SNARK<E::ScalarField>>::VerifyingKey,
     ) -> Self::VerifierSize {
-        circuit_vk.gamma_abc_g1.len()
+        circuit_vk.gamma_abc_g1_static.len() + circuit_vk.gamma_abc_g1_variable.len()
     }
 
     /// Allocates `N::Proof` in `cs` without performing
@@ -222,24 +344,28 @@ where
                 || Ok(vk.delta_g2.into_group()),
                 mode,
             )?;
-            let gamma_abc_g1 = vk
-                .gamma_abc_g1
-                .iter()
-                .map(|g| {
-                    P::G1Var::new_variable_omit_prime_order_check(
-                        ark_relations::ns!(cs, "gamma_abc_g1"),
-                        || Ok(g.into_group()),
-                        mode,
-                    )
-                })
-                .collect::<Result<Vec<_>, _>>()?;
+            let query = |query: &[E::G1Affine]| {
+                query
+                    .iter()
+                    .map(|g| {
+                        P::G1Var::new_variable_omit_prime_order_check(
+                            ark_relations::ns!(cs, "gamma_abc_g1"),
+                            || Ok(g.into_group()),
+                            mode,
+                        )
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+            };
+            let gamma_abc_g1_static = query(&vk.gamma_abc_g1_static)?;
+            let gamma_abc_g1_variable = query(&vk.gamma_abc_g1_variable)?;
 
             Ok(VerifyingKeyVar {
                 alpha_g1,
                 beta_g2,
                 gamma_g2,
                 delta_g2,
-                gamma_abc_g1,
+                gamma_abc_g1_static,
+                gamma_abc_g1_variable,
             })
         })
     }
@@ -250,45 +376,17 @@ where
         x: &Self::InputVar,
         proof: &Self::ProofVar,
     ) -> Result<Boolean<BasePrimeField<E>>, SynthesisError> {
-        let circuit_pvk = circuit_pvk.clone();
-
-        let g_ic = {
-            let mut g_ic: P::G1Var = circuit_pvk.gamma_abc_g1[0].clone();
-            let mut input_len = 1;
-            let mut public_inputs = x.clone().into_iter();
-            for (input, b) in public_inputs
-                .by_ref()
-                .zip(circuit_pvk.gamma_abc_g1.iter().skip(1))
-            {
-                let encoded_input_i: P::G1Var = b.scalar_mul_le(input.to_bits_le()?.iter())?;
-                g_ic += encoded_input_i;
-                input_len += 1;
-            }
-            // Check that the input and the query in the verification are of the
-            // same length.
-            assert!(input_len == circuit_pvk.gamma_abc_g1.len() && public_inputs.next().is_none());
-            g_ic
-        };
-
-        let test_exp = {
-            let proof_a_prep = P::prepare_g1(&proof.a)?;
-            let proof_b_prep = P::prepare_g2(&proof.b)?;
-            let proof_c_prep = P::prepare_g1(&proof.c)?;
-
-            let g_ic_prep = P::prepare_g1(&g_ic)?;
-
-            P::miller_loop(
-                &[proof_a_prep, g_ic_prep, proof_c_prep],
-                &[
-                    proof_b_prep,
-                    circuit_pvk.gamma_g2_neg_pc.clone(),
-                    circuit_pvk.delta_g2_neg_pc.clone(),
-                ],
-            )?
-        };
+        // Split inputs into static and variable parts, like the native
+        // verifier.
+        let mut inputs: Vec<_> = x.clone().into_iter().collect();
+        let num_static_inputs = circuit_pvk.gamma_abc_g1_static.len().saturating_sub(1);
+        if inputs.len() < num_static_inputs {
+            return Err(SynthesisError::MalformedVerifyingKey);
+        }
+        let variable_inputs = inputs.split_off(num_static_inputs);
 
-        let test = P::final_exponentiation(&test_exp)?;
-        test.is_eq(&circuit_pvk.alpha_g1_beta_g2)
+        let g_ic = circuit_pvk.prepare_inputs(inputs, variable_inputs)?;
+        circuit_pvk.verify_prepared_inputs(&g_ic, proof)
     }
 
     #[tracing::instrument(target = "r1cs", skip(circuit_vk, x, proof))]
@@ -307,7 +405,7 @@ impl<E, P, QAP: R1CSToQAP>
     for Groth16VerifierGadget<E, P, QAP>
 where
     E: Pairing,
-    P: PairingVar<E>,
+    P: PairingVar<E, BasePrimeField<E>>,
     QAP: R1CSToQAP,
 {
 }
@@ -315,7 +413,7 @@ where
 impl<E, P> AllocVar<PreparedVerifyingKey<E>, BasePrimeField<E>> for PreparedVerifyingKeyVar<E, P>
 where
     E: Pairing,
-    P: PairingVar<E>,
+    P: PairingVar<E, BasePrimeField<E>>,
 {
     #[tracing::instrument(target = "r1cs", skip(cs, f))]
     fn new_variable<T: Borrow<PreparedVerifyingKey<E>>>(
@@ -330,7 +428,7 @@ where
             let pvk = pvk.borrow();
             let alpha_g1_beta_g2 = P::GTVar::new_variable(
                 ark_relations::ns!(cs, "alpha_g1_beta_g2"),
-                || Ok(pvk.alpha_g1_beta_g2.clone()),
+                || Ok(pvk.alpha_g1_beta_g2),
                 mode,
             )?;
 
@@ -346,9 +444,14 @@ where
                 mode,
             )?;
 
-            let gamma_abc_g1 = Vec::new_variable(
-                ark_relations::ns!(cs, "gamma_abc_g1"),
-                || Ok(pvk.vk.gamma_abc_g1.clone()),
+            let gamma_abc_g1_static = Vec::new_variable(
+                ark_relations::ns!(cs, "gamma_abc_g1_static"),
+                || Ok(pvk.vk.gamma_abc_g1_static.clone()),
+                mode,
+            )?;
+            let gamma_abc_g1_variable = Vec::new_variable(
+                ark_relations::ns!(cs, "gamma_abc_g1_variable"),
+                || Ok(pvk.vk.gamma_abc_g1_variable.clone()),
                 mode,
             )?;
 
@@ -356,7 +459,8 @@ where
                 alpha_g1_beta_g2,
                 gamma_g2_neg_pc,
                 delta_g2_neg_pc,
-                gamma_abc_g1,
+                gamma_abc_g1_static,
+                gamma_abc_g1_variable,
             })
         })
     }
@@ -365,7 +469,7 @@ where
 impl<E, P> AllocVar<VerifyingKey<E>, BasePrimeField<E>> for VerifyingKeyVar<E, P>
 where
     E: Pairing,
-    P: PairingVar<E>,
+    P: PairingVar<E, BasePrimeField<E>>,
 {
     #[tracing::instrument(target = "r1cs", skip(cs, f))]
     fn new_variable<T: Borrow<VerifyingKey<E>>>(
@@ -382,7 +486,8 @@ where
                 beta_g2,
                 gamma_g2,
                 delta_g2,
-                gamma_abc_g1,
+                gamma_abc_g1_static,
+                gamma_abc_g1_variable,
             } = vk.borrow().clone();
             let alpha_g1 =
                 P::G1Var::new_variable(ark_relations::ns!(cs, "alpha_g1"), || Ok(alpha_g1), mode)?;
@@ -393,13 +498,17 @@ where
             let delta_g2 =
                 P::G2Var::new_variable(ark_relations::ns!(cs, "delta_g2"), || Ok(delta_g2), mode)?;
 
-            let gamma_abc_g1 = Vec::new_variable(cs.clone(), || Ok(gamma_abc_g1), mode)?;
+            let gamma_abc_g1_static =
+                Vec::new_variable(cs.clone(), || Ok(gamma_abc_g1_static), mode)?;
+            let gamma_abc_g1_variable =
+                Vec::new_variable(cs.clone(), || Ok(gamma_abc_g1_variable), mode)?;
             Ok(Self {
                 alpha_g1,
                 beta_g2,
                 gamma_g2,
                 delta_g2,
-                gamma_abc_g1,
+                gamma_abc_g1_static,
+                gamma_abc_g1_variable,
             })
         })
     }
@@ -408,7 +517,7 @@ where
 impl<E, P> AllocVar<Proof<E>, BasePrimeField<E>> for ProofVar<E, P>
 where
     E: Pairing,
-    P: PairingVar<E>,
+    P: PairingVar<E, BasePrimeField<E>>,
 {
     #[tracing::instrument(target = "r1cs", skip(cs, f))]
     fn new_variable<T: Borrow<Proof<E>>>(
@@ -432,18 +541,22 @@ where
 impl<E, P> ToBytesGadget<BasePrimeField<E>> for VerifyingKeyVar<E, P>
 where
     E: Pairing,
-    P: PairingVar<E>,
+    P: PairingVar<E, BasePrimeField<E>>,
 {
     #[inline]
     #[tracing::instrument(target = "r1cs", skip(self))]
-    fn to_bytes_le(&self) -> Result<Vec<UInt8<BasePrimeField<E>>>, SynthesisError> {
+    fn to_bytes(&self) -> Result<Vec<UInt8<BasePrimeField<E>>>, SynthesisError> {
         let mut bytes = Vec::new();
-        bytes.extend_from_slice(&self.alpha_g1.to_bytes_le()?);
-        bytes.extend_from_slice(&self.beta_g2.to_bytes_le()?);
-        bytes.extend_from_slice(&self.gamma_g2.to_bytes_le()?);
-        bytes.extend_from_slice(&self.delta_g2.to_bytes_le()?);
-        for g in &self.gamma_abc_g1 {
-            bytes.extend_from_slice(&g.to_bytes_le()?);
+        bytes.extend_from_slice(&self.alpha_g1.to_bytes()?);
+        bytes.extend_from_slice(&self.beta_g2.to_bytes()?);
+        bytes.extend_from_slice(&self.gamma_g2.to_bytes()?);
+        bytes.extend_from_slice(&self.delta_g2.to_bytes()?);
+        for g in self
+            .gamma_abc_g1_static
+            .iter()
+            .chain(&self.gamma_abc_g1_variable)
+        {
+            bytes.extend_from_slice(&g.to_bytes()?);
         }
         Ok(bytes)
     }
@@ -515,18 +628,18 @@ mod test {
         c.mul_assign(&b);
 
         let circ = Circuit {
-            a: Some(a.clone()),
-            b: Some(b.clone()),
+            a: Some(a),
+            b: Some(b),
             num_constraints: 100,
             num_variables: 25,
         };
 
         let (pk, vk) = TestSNARK::circuit_specific_setup(circ, &mut rng).unwrap();
 
-        let proof = TestSNARK::prove(&pk, circ.clone(), &mut rng).unwrap();
+        let proof = TestSNARK::prove(&pk, circ, &mut rng).unwrap();
 
         assert!(
-            TestSNARK::verify(&vk, &vec![c], &proof).unwrap(),
+            TestSNARK::verify(&vk, &[c], &proof).unwrap(),
             "The native verification check fails."
         );
 
@@ -586,4 +699,63 @@ mod test {
             cs.which_is_unsatisfied().unwrap().unwrap_or_default()
         );
     }
+
+    #[cfg(feature = "r1cs-gadget")]
+    #[test]
+    fn verify_in_circuit_split_inputs() {
+        use crate::{
+            constraints::{verify_in_circuit, ProofVar, SplitInputVar, VerifyingKeyVar},
+            reference::{self, Pool, Trade},
+        };
+        use ark_crypto_primitives::snark::BooleanInputVar;
+
+        let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+        let (pk, pvk) = reference::setup::<MNT4>(&mut rng).unwrap();
+        let pool = Pool {
+            reserve_in: 1_000_000,
+            reserve_out: 2_000_000,
+            fee_bps: 30,
+        };
+        let trade = Trade {
+            amount_in: 1_000,
+            min_amount_out: 1_900,
+        };
+        let proof = reference::prove(&pk, pool, trade, &mut rng).unwrap();
+        assert!(reference::verify(&pvk, pool, trade, &proof).unwrap());
+
+        type InputVar = BooleanInputVar<MNT4Fr, MNT6Fr>;
+        let verify = |static_inputs: Vec<MNT4Fr>, variable_inputs: Vec<MNT4Fr>| {
+            let cs = ConstraintSystem::<MNT6Fr>::new_ref();
+            let inputs = SplitInputVar::new(
+                InputVar::new_input(ns!(cs, "static_inputs"), || Ok(static_inputs)).unwrap(),
+                InputVar::new_input(ns!(cs, "variable_inputs"), || Ok(variable_inputs)).unwrap(),
+            );
+            let proof =
+                ProofVar::<MNT4, MNT4PairingVar>::new_witness(ns!(cs, "proof"), || Ok(&proof))
+                    .unwrap();
+            let vk =
+                VerifyingKeyVar::<MNT4, MNT4PairingVar>::new_constant(ns!(cs, "vk"), pk.vk.clone())
+                    .unwrap();
+            verify_in_circuit(ns!(cs, "verify"), &vk, &proof, &inputs)?
+                .enforce_equal(&Boolean::constant(true))?;
+            cs.is_satisfied()
+        };
+
+        let static_inputs = pool.static_inputs();
+        let variable_inputs = trade.variable_inputs();
+        assert!(verify(static_inputs.clone(), variable_inputs.clone()).unwrap());
+
+        let other_trade = Trade {
+            amount_in: 2_000,
+            ..trade
+        };
+        assert!(!verify(static_inputs.clone(), other_trade.variable_inputs()).unwrap());
+
+        // Inputs moved between the groups do not match the key.
+        let all_inputs = [static_inputs, variable_inputs].concat();
+        assert_eq!(
+            verify(all_inputs[..2].to_vec(), all_inputs[2..].to_vec()),
+            Err(SynthesisError::MalformedVerifyingKey)
+        );
+    }
 }
@@ -0,0 +1,249 @@
+use crate::{
+    msm::{BigInt, CpuMsm, MsmBackend},
+    prepare_verifying_key,
+    prover::ProverConfig,
+    testing::SyntheticCircuit,
+    Groth16, Proof, ProvingKey,
+};
+use ark_crypto_primitives::snark::SNARK;
+use ark_ec::{pairing::Pairing, AffineRepr};
+use ark_ff::{PrimeField, UniformRand};
+use ark_relations::r1cs::{ConstraintSynthesizer, Result as R1CSResult};
+use ark_std::{
+    rand::{rngs::StdRng, Rng, SeedableRng},
+    vec::Vec,
+};
+use std::{
+    hint::black_box,
+    sync::atomic::{AtomicBool, Ordering},
+    time::{Duration, Instant},
+};
+
+/// The number of bases of the MSMs run in each group by
+/// [`ProverContext::warm_up`].
+pub const WARM_UP_MSM_SIZE: usize = 1 << 10;
+
+/// The number of constraints of the dry proof of [`ProverContext::warm_up`].
+pub const WARM_UP_CONSTRAINTS: usize = 1 << 6;
+
+/// What a host offers to the prover, for routing proving jobs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Capabilities {
+    /// The number of threads the prover runs on.
+    pub threads: usize,
+    /// The SIMD and arithmetic extensions of the CPU detected at runtime
+    /// which speed up field arithmetic, e.g. `avx2` or `adx` on x86-64 and
+    /// `neon` on AArch64.
+    pub simd: Vec<&'static str>,
+    /// Whether the MSM backend computes MSMs on an accelerator which is
+    /// present, see [`MsmBackend::is_accelerated`].
+    pub accelerated: bool,
+    /// Whether inputs can be prepared with the GLV endomorphism of `G1` by
+    /// `Groth16::prepare_inputs_with_glv`, with the `glv` feature.
+    pub glv: bool,
+    /// Whether MSMs are computed in constant time, with the `hardened`
+    /// feature.
+    pub hardened: bool,
+}
+
+/// The time spent in each step of [`ProverContext::warm_up`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WarmUpReport {
+    /// The number of bytes of group elements of the proving key read.
+    pub key_bytes: usize,
+    /// The time spent reading the proving key.
+    pub key_time: Duration,
+    /// The time spent computing an MSM in each group with the backend.
+    pub msm_time: Duration,
+    /// The time spent generating a key for, and proving, a circuit of
+    /// [`WARM_UP_CONSTRAINTS`] constraints.
+    pub dry_proof_time: Duration,
+    /// Whether the dry proof verified. The context is only warm if it did.
+    pub dry_proof_verified: bool,
+}
+
+/// Returns the SIMD and arithmetic extensions detected on the CPU.
+fn detect_simd() -> Vec<&'static str> {
+    #[allow(unused_mut)]
+    let mut simd = Vec::new();
+    #[cfg(target_arch = "x86_64")]
+    {
+        macro_rules! detect {
+            ($($feature:tt),*) => {
+                $(if std::arch::is_x86_feature_detected!($feature) {
+                    simd.push($feature);
+                })*
+            };
+        }
+        detect!("sse4.2", "avx2", "avx512f", "avx512ifma", "bmi2", "adx");
+    }
+    #[cfg(target_arch = "aarch64")]
+    {
+        if std::arch::is_aarch64_feature_detected!("neon") {
+            simd.push("neon");
+        }
+    }
+    simd
+}
+
+/// Reads every element of `points`, so that the pages holding them are
+/// mapped and cached, returning the number of bytes read.
+fn touch<A: AffineRepr>(points: &[A]) -> usize {
+    black_box(points.iter().filter(|point| point.is_zero()).count());
+    ark_std::mem::size_of_val(points)
+}
+
+/// The MSM backend and configuration of a prover, with a warm-up step
+/// preparing the host for a proving key before the first job, and a report
+/// of the capabilities of the host, for orchestrators routing jobs to
+/// adequately warmed hosts.
+#[derive(Debug, Default)]
+pub struct ProverContext<B = CpuMsm> {
+    backend: B,
+    config: ProverConfig,
+    warm: AtomicBool,
+}
+
+impl ProverContext {
+    /// Creates a context computing MSMs with [`CpuMsm`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<B> ProverContext<B> {
+    /// Creates a context computing MSMs with `backend`.
+    pub fn with_backend(backend: B) -> Self {
+        Self {
+            backend,
+            config: ProverConfig::default(),
+            warm: AtomicBool::new(false),
+        }
+    }
+
+    /// Sets the configuration of the prover.
+    pub fn with_config(mut self, config: ProverConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Returns the MSM backend.
+    pub fn backend(&self) -> &B {
+        &self.backend
+    }
+
+    /// Returns the configuration of the prover.
+    pub fn config(&self) -> &ProverConfig {
+        &self.config
+    }
+
+    /// Returns whether [`Self::warm_up`] succeeded on this context.
+    pub fn is_warm(&self) -> bool {
+        self.warm.load(Ordering::Acquire)
+    }
+
+    /// Reports the capabilities of the host for proving over `E`.
+    pub fn capabilities<E: Pairing>(&self) -> Capabilities
+    where
+        B: MsmBackend<E::G1> + MsmBackend<E::G2>,
+    {
+        #[cfg(feature = "parallel")]
+        let threads = rayon::current_num_threads();
+        #[cfg(not(feature = "parallel"))]
+        let threads = 1;
+
+        Capabilities {
+            threads,
+            simd: detect_simd(),
+            accelerated: MsmBackend::<E::G1>::is_accelerated(&self.backend)
+                || MsmBackend::<E::G2>::is_accelerated(&self.backend),
+            glv: cfg!(feature = "glv"),
+            hardened: cfg!(feature = "hardened"),
+        }
+    }
+
+    /// Prepares the host for proving with `pk`: reads all the group
+    /// elements of `pk`, so that the first proof does not wait for its
+    /// pages to be loaded, runs an MSM on the bases of `pk` in each group
+    /// to initialize the backend, e.g. its device context and tables, and
+    /// generates and verifies a dry proof of a small circuit to start the
+    /// thread pool and the FFTs.
+    ///
+    /// The context is warm once the dry proof verified; a failure points to
+    /// a faulty backend.
+    pub fn warm_up<E: Pairing>(&self, pk: &ProvingKey<E>) -> R1CSResult<WarmUpReport>
+    where
+        B: MsmBackend<E::G1> + MsmBackend<E::G2>,
+    {
+        let start = Instant::now();
+        let key_bytes = touch(&pk.a_query)
+            + touch(&pk.b_g1_query)
+            + touch(&pk.b_g2_query)
+            + touch(&pk.h_query)
+            + touch(&pk.l_query)
+            + touch(&pk.vk.gamma_abc_g1_static)
+            + touch(&pk.vk.gamma_abc_g1_variable);
+        let key_time = start.elapsed();
+
+        let start = Instant::now();
+        let scalars: Vec<BigInt<E::G1>> = (1..=WARM_UP_MSM_SIZE as u64)
+            .map(|i| E::ScalarField::from(i).into_bigint())
+            .collect();
+        let g1_size = WARM_UP_MSM_SIZE.min(pk.a_query.len());
+        let g2_size = WARM_UP_MSM_SIZE.min(pk.b_g2_query.len());
+        black_box(MsmBackend::<E::G1>::msm_bigint(
+            &self.backend,
+            &pk.a_query[..g1_size],
+            &scalars[..g1_size],
+        ));
+        black_box(MsmBackend::<E::G2>::msm_bigint(
+            &self.backend,
+            &pk.b_g2_query[..g2_size],
+            &scalars[..g2_size],
+        ));
+        let msm_time = start.elapsed();
+
+        let start = Instant::now();
+        let mut rng = StdRng::seed_from_u64(0);
+        let circuit = SyntheticCircuit::<E::ScalarField>::blank(WARM_UP_CONSTRAINTS)
+            .with_density(2)
+            .with_depth(4)
+            .with_assignment(0);
+        let dry_pk =
+            Groth16::<E>::generate_random_parameters_with_reduction(circuit, &mut rng, 1, 0)?;
+        let proof = self.prove(&dry_pk, circuit, &mut rng)?;
+        let inputs = circuit.public_inputs().unwrap_or_default();
+        let dry_proof_verified = Groth16::<E>::verify_with_processed_vk(
+            &prepare_verifying_key(&dry_pk.vk),
+            &inputs,
+            &proof,
+        )?;
+        let dry_proof_time = start.elapsed();
+
+        self.warm.store(dry_proof_verified, Ordering::Release);
+        Ok(WarmUpReport {
+            key_bytes,
+            key_time,
+            msm_time,
+            dry_proof_time,
+            dry_proof_verified,
+        })
+    }
+
+    /// Create a Groth16 proof that is zero-knowledge with the backend and
+    /// configuration of the context, sampling randomness via `rng`.
+    pub fn prove<E: Pairing, C>(
+        &self,
+        pk: &ProvingKey<E>,
+        circuit: C,
+        rng: &mut impl Rng,
+    ) -> R1CSResult<Proof<E>>
+    where
+        B: MsmBackend<E::G1> + MsmBackend<E::G2>,
+        C: ConstraintSynthesizer<E::ScalarField>,
+    {
+        let r = E::ScalarField::rand(rng);
+        let s = E::ScalarField::rand(rng);
+        Groth16::<E>::create_proof_with_config(circuit, pk, r, s, &self.backend, &self.config)
+    }
+}
@@ -1,9 +1,22 @@
-use ark_crypto_primitives::sponge::Absorb;
+use ark_crypto_primitives::{snark::SNARK, sponge::Absorb};
 use ark_ec::pairing::Pairing;
 use ark_ff::PrimeField;
+use ark_relations::r1cs::{Result as R1CSResult, SynthesisError};
 use ark_serialize::*;
 use ark_std::vec::Vec;
 
+#[cfg(feature = "parallel")]
+use crate::layout::ComponentReader;
+#[cfg(feature = "parallel")]
+use ark_ec::AffineRepr;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::{
+    layout::{self, CanonicalReader},
+    InputCountMismatch,
+};
+
 /// A proof in the Groth16 SNARK.
 #[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
 pub struct Proof<E: Pairing> {
@@ -25,6 +38,48 @@ impl<E: Pairing> Default for Proof<E> {
     }
 }
 
+impl<E: Pairing> Proof<E> {
+    /// Returns the size of a proof serialized with `compress`. Unlike
+    /// [`CanonicalSerialize::serialized_size`], it does not depend on the
+    /// proof.
+    pub fn serialized_size_with(compress: Compress) -> usize {
+        let g1 = E::G1Affine::default().serialized_size(compress);
+        let g2 = E::G2Affine::default().serialized_size(compress);
+        2 * g1 + g2
+    }
+
+    /// Serializes the proof to `writer`, with compressed points for storage
+    /// or uncompressed points for verifiers which cannot afford point
+    /// decompression.
+    pub fn serialize_with(
+        &self,
+        writer: impl Write,
+        compress: Compress,
+    ) -> Result<(), SerializationError> {
+        self.serialize_with_mode(writer, compress)
+    }
+
+    /// Returns the serialization of the proof with `compress`, of
+    /// [`Self::serialized_size_with`] bytes.
+    pub fn to_bytes_with(&self, compress: Compress) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(Self::serialized_size_with(compress));
+        self.serialize_with(&mut bytes, compress)
+            .expect("serializing to a vector does not fail");
+        bytes
+    }
+
+    /// Deserializes a proof serialized with `compress`, checking that the
+    /// points are on the curve and in the right subgroup if `validate` is
+    /// set.
+    pub fn deserialize_with(
+        reader: impl Read,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        Self::deserialize_with_mode(reader, compress, validate)
+    }
+}
+
 ////////////////////////////////////////////////////////////////////////////////
 
 /// A verification key in the Groth16 SNARK.
@@ -39,10 +94,13 @@ pub struct VerifyingKey<E: Pairing> {
     /// The `delta * H`, where `H` is the generator of `E::G2`.
     pub delta_g2: E::G2Affine,
     /// The `gamma^{-1} * (beta * a_i + alpha * b_i + c_i) * H`, where `H` is
-    /// the generator of `E::G1`.
-    // pub gamma_abc_g1: Vec<E::G1Affine>,
-    pub gamma_abc_g1_static: Vec<E::G1Affine>,   // For inputs known at proving time
-    pub gamma_abc_g1_variable: Vec<E::G1Affine>, // For inputs only known at verification time
+    /// the generator of `E::G1`, for the constant term and the inputs known
+    /// at proving time.
+    pub gamma_abc_g1_static: Vec<E::G1Affine>,
+    /// The `gamma^{-1} * (beta * a_i + alpha * b_i + c_i) * H`, where `H` is
+    /// the generator of `E::G1`, for the inputs only known at verification
+    /// time.
+    pub gamma_abc_g1_variable: Vec<E::G1Affine>,
 }
 
 impl<E: Pairing> Default for VerifyingKey<E> {
@@ -58,6 +116,41 @@ impl<E: Pairing> Default for VerifyingKey<E> {
     }
 }
 
+impl<E: Pairing> VerifyingKey<E> {
+    /// Keeps the bases of the first `used_count` public inputs, static
+    /// inputs first, and drops the others, for circuits with spare input
+    /// slots at the end which are always zero. This shrinks the key and
+    /// the input preparation of the verifier, which then expects exactly
+    /// `used_count` inputs.
+    ///
+    /// A proof only verifies under the truncated key if the dropped inputs
+    /// are zero in its statement, which the key cannot check: this is only
+    /// sound if the circuit constrains the spare inputs to zero.
+    ///
+    /// Fails with [`SynthesisError::MalformedVerifyingKey`], leaving `self`
+    /// unchanged, if the key has fewer than `used_count` inputs or lacks the
+    /// constant term.
+    pub fn truncate_inputs(&mut self, used_count: usize) -> R1CSResult<()> {
+        let num_static = self
+            .gamma_abc_g1_static
+            .len()
+            .checked_sub(1)
+            .ok_or(SynthesisError::MalformedVerifyingKey)?;
+        if used_count > num_static + self.gamma_abc_g1_variable.len() {
+            return Err(SynthesisError::MalformedVerifyingKey);
+        }
+
+        match used_count.checked_sub(num_static) {
+            Some(num_variable) => self.gamma_abc_g1_variable.truncate(num_variable),
+            None => {
+                self.gamma_abc_g1_static.truncate(used_count + 1);
+                self.gamma_abc_g1_variable.clear();
+            },
+        }
+        Ok(())
+    }
+}
+
 impl<E> Absorb for VerifyingKey<E>
 where
     E: Pairing,
@@ -91,6 +184,104 @@ where
     }
 }
 
+/// A verification key in the layout of upstream Groth16, where the bases of
+/// all inputs are in a single vector.
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct LegacyVerifyingKey<E: Pairing> {
+    /// The `alpha * G`, where `G` is the generator of `E::G1`.
+    pub alpha_g1: E::G1Affine,
+    /// The `alpha * H`, where `H` is the generator of `E::G2`.
+    pub beta_g2: E::G2Affine,
+    /// The `gamma * H`, where `H` is the generator of `E::G2`.
+    pub gamma_g2: E::G2Affine,
+    /// The `delta * H`, where `H` is the generator of `E::G2`.
+    pub delta_g2: E::G2Affine,
+    /// The `gamma^{-1} * (beta * a_i + alpha * b_i + c_i) * H`, where `H` is
+    /// the generator of `E::G1`, for the constant term and all inputs.
+    pub gamma_abc_g1: Vec<E::G1Affine>,
+}
+
+impl<E: Pairing> Default for LegacyVerifyingKey<E> {
+    fn default() -> Self {
+        Self {
+            alpha_g1: E::G1Affine::default(),
+            beta_g2: E::G2Affine::default(),
+            gamma_g2: E::G2Affine::default(),
+            delta_g2: E::G2Affine::default(),
+            gamma_abc_g1: Vec::new(),
+        }
+    }
+}
+
+/// Merges the static and variable queries of a key.
+impl<E: Pairing> From<VerifyingKey<E>> for LegacyVerifyingKey<E> {
+    fn from(vk: VerifyingKey<E>) -> Self {
+        let mut gamma_abc_g1 = vk.gamma_abc_g1_static;
+        gamma_abc_g1.extend(vk.gamma_abc_g1_variable);
+        Self {
+            alpha_g1: vk.alpha_g1,
+            beta_g2: vk.beta_g2,
+            gamma_g2: vk.gamma_g2,
+            delta_g2: vk.delta_g2,
+            gamma_abc_g1,
+        }
+    }
+}
+
+impl<E: Pairing> LegacyVerifyingKey<E> {
+    /// Converts `self` to a [`VerifyingKey`] whose static query holds the
+    /// first `static_count` elements of `gamma_abc_g1`, including the
+    /// constant term. Fails if `static_count` is zero or larger than the
+    /// number of elements.
+    pub fn into_split(self, static_count: usize) -> R1CSResult<VerifyingKey<E>> {
+        if static_count == 0 || static_count > self.gamma_abc_g1.len() {
+            return Err(SynthesisError::MalformedVerifyingKey);
+        }
+        let mut gamma_abc_g1_static = self.gamma_abc_g1;
+        let gamma_abc_g1_variable = gamma_abc_g1_static.split_off(static_count);
+
+        Ok(VerifyingKey {
+            alpha_g1: self.alpha_g1,
+            beta_g2: self.beta_g2,
+            gamma_g2: self.gamma_g2,
+            delta_g2: self.delta_g2,
+            gamma_abc_g1_static,
+            gamma_abc_g1_variable,
+        })
+    }
+}
+
+/// A verification key in either the split layout of this crate or the
+/// legacy layout of upstream Groth16, see
+/// [`load_any_verifying_key`](crate::loader::load_any_verifying_key).
+#[derive(Clone, Debug, PartialEq)]
+pub enum AnyVerifyingKey<E: Pairing> {
+    /// A key with separate static and variable input queries.
+    Split(VerifyingKey<E>),
+    /// A key with a single input query.
+    Legacy(LegacyVerifyingKey<E>),
+}
+
+impl<E: Pairing> AnyVerifyingKey<E> {
+    /// Returns the key in the split layout. Legacy keys are split after the
+    /// constant term, so that all of their inputs are variable.
+    pub fn into_split(self) -> R1CSResult<VerifyingKey<E>> {
+        match self {
+            AnyVerifyingKey::Split(vk) => Ok(vk),
+            AnyVerifyingKey::Legacy(vk) => vk.into_split(1),
+        }
+    }
+
+    /// Verifies `proof` against all its public `inputs`, static inputs first,
+    /// whatever the layout of the key. This prepares the key on each call;
+    /// convert it with [`Self::into_split`] and prepare it once to verify
+    /// many proofs.
+    pub fn verify(&self, inputs: &[E::ScalarField], proof: &Proof<E>) -> R1CSResult<bool> {
+        let pvk = crate::prepare_verifying_key(&self.clone().into_split()?);
+        <crate::Groth16<E> as SNARK<E::ScalarField>>::verify_with_processed_vk(&pvk, inputs, proof)
+    }
+}
+
 /// Preprocessed verification key parameters that enable faster verification
 /// at the expense of larger size in memory.
 #[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
@@ -128,10 +319,157 @@ impl<E: Pairing> Default for PreparedVerifyingKey<E> {
     }
 }
 
+impl<E: Pairing> PreparedVerifyingKey<E> {
+    /// Returns the number of static inputs, known at proving time, expected
+    /// by the key. The constant term is not an input.
+    pub fn num_static_inputs(&self) -> usize {
+        self.vk.gamma_abc_g1_static.len().saturating_sub(1)
+    }
+
+    /// Returns the number of variable inputs, only known at verification
+    /// time, expected by the key.
+    pub fn num_variable_inputs(&self) -> usize {
+        self.vk.gamma_abc_g1_variable.len()
+    }
+
+    /// Returns the number of public inputs, static and variable, expected
+    /// by the key, so that callers can check the inputs they were given
+    /// before verifying a proof.
+    pub fn expected_total_inputs(&self) -> usize {
+        self.num_static_inputs() + self.num_variable_inputs()
+    }
+
+    /// Checks that `num_static` static and `num_variable` variable inputs
+    /// are the numbers expected by the key, returning both the expected and
+    /// actual counts otherwise.
+    pub fn check_input_counts(
+        &self,
+        num_static: usize,
+        num_variable: usize,
+    ) -> Result<(), InputCountMismatch> {
+        let mismatch = InputCountMismatch {
+            expected_static: self.num_static_inputs(),
+            actual_static: num_static,
+            expected_variable: self.num_variable_inputs(),
+            actual_variable: num_variable,
+        };
+        if num_static == mismatch.expected_static && num_variable == mismatch.expected_variable {
+            Ok(())
+        } else {
+            Err(mismatch)
+        }
+    }
+}
+
 ////////////////////////////////////////////////////////////////////////////////
 
-/// The prover key for for the Groth16 zkSNARK.
+/// The key of the Pedersen commitment to the inputs of a circuit which are
+/// given to the verifier as a commitment instead of in the clear, see
+/// [`Groth16::generate_random_parameters_with_commitment`](crate::Groth16::generate_random_parameters_with_commitment).
+///
+/// It is kept apart from the [`VerifyingKey`], whose serialization does not
+/// change whether or not the circuit has committed inputs.
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct CommitmentKey<E: Pairing> {
+    /// The part of the key needed by the verifier.
+    pub vk: CommitmentVerifyingKey<E>,
+    /// The `gamma^{-1} * (beta * a_i + alpha * b_i + c_i) * G`, where `G` is
+    /// the generator of `E::G1`, for the committed inputs.
+    pub bases: Vec<E::G1Affine>,
+    /// The `sigma * bases`, with which the committer proves knowledge of
+    /// the opening of a commitment.
+    pub sigma_bases: Vec<E::G1Affine>,
+}
+
+/// The part of a [`CommitmentKey`] with which the verifier checks the proof
+/// of knowledge of the opening of a commitment.
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct CommitmentVerifyingKey<E: Pairing> {
+    /// The generator `H` of `E::G2`.
+    pub g2: E::G2Affine,
+    /// The `-sigma * H`, where `H` is the generator of `E::G2`.
+    pub sigma_neg_g2: E::G2Affine,
+}
+
+/// A Pedersen commitment to the committed inputs of a circuit, with a proof
+/// of knowledge of its opening.
 #[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct InputCommitment<E: Pairing> {
+    /// The commitment `sum_i x_i * bases[i]`.
+    pub commitment: E::G1Affine,
+    /// The proof of knowledge `sum_i x_i * sigma_bases[i]`.
+    pub pok: E::G1Affine,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// The basis in which the `h_query` of a [`ProvingKey`] is expressed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum KeyBasis {
+    /// The elements `t^i * Z(t) / delta * G`, used with the coefficients of
+    /// `h(x)`.
+    #[default]
+    Monomial,
+    /// The elements `L_i(t) * Z(t) / delta * G`, where `L_i` are the Lagrange
+    /// polynomials of the coset used by the R1CS-to-QAP reduction. These are
+    /// used with the evaluations of `h(x)` over that coset, which lets the
+    /// prover skip the final inverse FFT.
+    Lagrange,
+}
+
+impl Valid for KeyBasis {
+    fn check(&self) -> Result<(), SerializationError> {
+        Ok(())
+    }
+}
+
+impl CanonicalSerialize for KeyBasis {
+    fn serialize_with_mode<W: Write>(
+        &self,
+        writer: W,
+        compress: Compress,
+    ) -> Result<(), SerializationError> {
+        let tag: u8 = match self {
+            KeyBasis::Monomial => 0,
+            KeyBasis::Lagrange => 1,
+        };
+        tag.serialize_with_mode(writer, compress)
+    }
+
+    fn serialized_size(&self, compress: Compress) -> usize {
+        0u8.serialized_size(compress)
+    }
+}
+
+impl KeyBasis {
+    /// Reads the tag ending a serialized [`ProvingKey`], or returns
+    /// [`KeyBasis::Monomial`] if `reader` ends before it, as keys serialized
+    /// before the tag was introduced do.
+    pub(crate) fn deserialize_trailing(mut reader: impl Read) -> Result<Self, SerializationError> {
+        let mut tag = [0u8; 1];
+        match reader.read(&mut tag)? {
+            0 => Ok(KeyBasis::Monomial),
+            _ => Self::deserialize_compressed(&tag[..]),
+        }
+    }
+}
+
+impl CanonicalDeserialize for KeyBasis {
+    fn deserialize_with_mode<R: Read>(
+        reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        match u8::deserialize_with_mode(reader, compress, validate)? {
+            0 => Ok(KeyBasis::Monomial),
+            1 => Ok(KeyBasis::Lagrange),
+            _ => Err(SerializationError::InvalidData),
+        }
+    }
+}
+
+/// The prover key for for the Groth16 zkSNARK.
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize)]
 pub struct ProvingKey<E: Pairing> {
     /// The underlying verification key.
     pub vk: VerifyingKey<E>,
@@ -148,5 +486,167 @@ pub struct ProvingKey<E: Pairing> {
     /// The elements `h_i * G` in `E::G1`.
     pub h_query: Vec<E::G1Affine>,
     /// The elements `l_i * G` in `E::G1`.
+    ///
+    /// These are `delta^{-1} * (beta * a_i + alpha * b_i + c_i) * G` for the
+    /// witness variables. Turning a witness variable into a public input
+    /// requires the same element scaled by `gamma^{-1}` instead, which
+    /// cannot be derived from this query without `gamma / delta`, so any
+    /// change to the input layout of a circuit requires a new setup.
     pub l_query: Vec<E::G1Affine>,
+    /// The basis in which `h_query` is expressed. It is serialized last, so
+    /// that keys serialized before it was introduced, which end without it
+    /// and are in the monomial basis, still deserialize.
+    pub h_basis: KeyBasis,
+}
+
+impl<E: Pairing> Valid for ProvingKey<E> {
+    fn check(&self) -> Result<(), SerializationError> {
+        self.vk.check()?;
+        self.beta_g1.check()?;
+        self.delta_g1.check()?;
+        self.a_query.check()?;
+        self.b_g1_query.check()?;
+        self.b_g2_query.check()?;
+        self.h_query.check()?;
+        self.l_query.check()?;
+        self.h_basis.check()
+    }
+}
+
+/// Keys end with their [`KeyBasis`] tag, and are read as in the monomial
+/// basis if the reader ends before it. A key must therefore be the last item
+/// of its reader: a key serialized without the tag, before it was introduced,
+/// would take the first byte of whatever follows it as the tag.
+impl<E: Pairing> CanonicalDeserialize for ProvingKey<E> {
+    fn deserialize_with_mode<R: Read>(
+        reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        layout::read_proving_key(&mut CanonicalReader {
+            reader,
+            compress,
+            validate,
+        })
+    }
+}
+
+/// The number of points of a query read and decoded together by
+/// [`ProvingKey::deserialize_parallel`].
+#[cfg(feature = "parallel")]
+const DESERIALIZE_CHUNK_SIZE: usize = 1 << 16;
+
+/// The number of points checked together by a thread when deserializing
+/// a query in parallel.
+#[cfg(feature = "parallel")]
+const CHECK_BATCH_SIZE: usize = 1 << 10;
+
+/// Deserializes a vector of points serialized with `compress`, reading
+/// `chunk_size` points at a time and decoding and checking them in
+/// parallel.
+#[cfg(feature = "parallel")]
+fn deserialize_points_parallel<G: AffineRepr>(
+    mut reader: impl Read,
+    compress: Compress,
+    validate: Validate,
+    chunk_size: usize,
+) -> Result<Vec<G>, SerializationError> {
+    let len = usize::try_from(u64::deserialize_with_mode(&mut reader, compress, validate)?)
+        .map_err(|_| SerializationError::InvalidData)?;
+    let point_size = G::zero().serialized_size(compress);
+    // The length is not trusted to preallocate more than a chunk.
+    let mut points = Vec::with_capacity(len.min(chunk_size));
+    let mut buffer = Vec::new();
+    while points.len() < len {
+        let count = (len - points.len()).min(chunk_size);
+        buffer.resize(count * point_size, 0);
+        reader.read_exact(&mut buffer)?;
+        let chunk = buffer
+            .par_chunks(point_size)
+            .map(|bytes| G::deserialize_with_mode(bytes, compress, Validate::No))
+            .collect::<Result<Vec<_>, _>>()?;
+        if validate == Validate::Yes {
+            chunk
+                .par_chunks(CHECK_BATCH_SIZE)
+                .try_for_each(|batch| G::batch_check(batch.iter()))?;
+        }
+        points.extend(chunk);
+    }
+    Ok(points)
+}
+
+#[cfg(feature = "parallel")]
+impl<E: Pairing> ProvingKey<E> {
+    /// Deserializes a key serialized with `compress`, as
+    /// [`CanonicalDeserialize::deserialize_with_mode`] does, decompressing
+    /// and checking the points of the queries in parallel. The queries are
+    /// read in chunks, so the reader is not buffered in full.
+    pub fn deserialize_parallel(
+        reader: impl Read,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        Self::deserialize_parallel_in_chunks(reader, compress, validate, DESERIALIZE_CHUNK_SIZE)
+    }
+
+    pub(crate) fn deserialize_parallel_in_chunks(
+        reader: impl Read,
+        compress: Compress,
+        validate: Validate,
+        chunk_size: usize,
+    ) -> Result<Self, SerializationError> {
+        layout::read_proving_key(&mut ParallelReader {
+            inner: CanonicalReader {
+                reader,
+                compress,
+                validate,
+            },
+            chunk_size,
+        })
+    }
+}
+
+/// Reads vectors of points with [`deserialize_points_parallel`], and the
+/// other components as [`CanonicalReader`] does.
+#[cfg(feature = "parallel")]
+struct ParallelReader<R> {
+    inner: CanonicalReader<R>,
+    chunk_size: usize,
+}
+
+#[cfg(feature = "parallel")]
+impl<E: Pairing, R: Read> ComponentReader<E> for ParallelReader<R> {
+    type Error = SerializationError;
+
+    fn g1(&mut self) -> Result<E::G1Affine, SerializationError> {
+        ComponentReader::<E>::g1(&mut self.inner)
+    }
+
+    fn g2(&mut self) -> Result<E::G2Affine, SerializationError> {
+        ComponentReader::<E>::g2(&mut self.inner)
+    }
+
+    fn g1_vec(&mut self, _inputs: bool) -> Result<Vec<E::G1Affine>, SerializationError> {
+        let inner = &mut self.inner;
+        deserialize_points_parallel(
+            &mut inner.reader,
+            inner.compress,
+            inner.validate,
+            self.chunk_size,
+        )
+    }
+
+    fn g2_vec(&mut self) -> Result<Vec<E::G2Affine>, SerializationError> {
+        let inner = &mut self.inner;
+        deserialize_points_parallel(
+            &mut inner.reader,
+            inner.compress,
+            inner.validate,
+            self.chunk_size,
+        )
+    }
+
+    fn trailing_basis(&mut self) -> Result<KeyBasis, SerializationError> {
+        ComponentReader::<E>::trailing_basis(&mut self.inner)
+    }
 }
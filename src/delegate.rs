@@ -0,0 +1,289 @@
+use crate::{
+    bundle::VkFingerprint,
+    msm::{mul_secret, MsmBackend},
+    prover::ProverConfig,
+    r1cs_to_qap::R1CSToQAP,
+    Groth16, Proof, ProvingKey,
+};
+use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup};
+use ark_ff::{PrimeField, UniformRand};
+use ark_relations::r1cs::{ConstraintSynthesizer, Result as R1CSResult, SynthesisError};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::{fmt, rand::Rng, vec::Vec};
+use zeroize::Zeroize;
+
+/// The MSMs of a proof over the queries of a proving key, computed by a
+/// compute provider from a [`DelegatedJob`], or over the mask of a
+/// [`DelegationMask`].
+///
+/// `a`, `b_g1` and `b_g2` exclude the first element of their queries,
+/// which belongs to the constant one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct DelegatedMsms<E: Pairing> {
+    /// The MSM of the assignment with the `a_query`.
+    pub a: E::G1Affine,
+    /// The MSM of the assignment with the `b_g1_query`.
+    pub b_g1: E::G1Affine,
+    /// The MSM of the assignment with the `b_g2_query`.
+    pub b_g2: E::G2Affine,
+    /// The MSM of the witness assignment with the `l_query`.
+    pub l: E::G1Affine,
+    /// The MSM of the QAP witness with the `h_query`.
+    pub h: E::G1Affine,
+}
+
+/// The scalar vectors of a proof sent by the witness holder to a compute
+/// provider, masked with a one-time [`DelegationMask`] so that they are
+/// uniformly random to the provider.
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct DelegatedJob<F: PrimeField> {
+    /// The fingerprint of the verifying key of the proving key.
+    pub key: VkFingerprint,
+    /// The masked assignment, without the constant one: the public inputs
+    /// followed by the witness.
+    pub assignment: Vec<F>,
+    /// The masked QAP witness.
+    pub h: Vec<F>,
+}
+
+/// A one-time mask of the scalar vectors of a proof, with its MSMs over the
+/// queries of the proving key.
+///
+/// Computing a mask costs as much as the MSMs of a proof, but it does not
+/// depend on the witness: the witness holder computes masks ahead of time,
+/// e.g. while idle, and later delegates each proof with one of them for
+/// only a few scalar multiplications. A mask must not be used twice, as the
+/// difference of two jobs masked with it is the difference of their
+/// witnesses; [`Groth16::delegate_proof`] consumes it. The mask is zeroized
+/// when dropped.
+pub struct DelegationMask<E: Pairing> {
+    key: VkFingerprint,
+    assignment: Vec<E::ScalarField>,
+    h: Vec<E::ScalarField>,
+    msms: DelegatedMsms<E>,
+}
+
+impl<E: Pairing> Drop for DelegationMask<E> {
+    fn drop(&mut self) {
+        self.assignment.iter_mut().for_each(Zeroize::zeroize);
+        self.h.iter_mut().for_each(Zeroize::zeroize);
+    }
+}
+
+impl<E: Pairing> fmt::Debug for DelegationMask<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DelegationMask")
+            .field("key", &self.key)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The state kept by the witness holder while a compute provider computes
+/// the MSMs of a [`DelegatedJob`], from which it assembles the proof. The
+/// blinding factors are zeroized when it is dropped.
+pub struct DelegatedProver<E: Pairing> {
+    key: VkFingerprint,
+    r: E::ScalarField,
+    s: E::ScalarField,
+    mask: DelegatedMsms<E>,
+}
+
+impl<E: Pairing> Drop for DelegatedProver<E> {
+    fn drop(&mut self) {
+        self.r.zeroize();
+        self.s.zeroize();
+    }
+}
+
+impl<E: Pairing> fmt::Debug for DelegatedProver<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DelegatedProver")
+            .field("key", &self.key)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<E: Pairing> DelegatedProver<E> {
+    /// Unmasks the MSMs computed by the compute provider and assembles the
+    /// blinded proof.
+    ///
+    /// Fails with [`SynthesisError::MalformedVerifyingKey`] if `pk` is not
+    /// the key the job was delegated with. A provider returning wrong MSMs
+    /// makes the proof invalid, so it must be verified before use.
+    pub fn finish(self, pk: &ProvingKey<E>, msms: &DelegatedMsms<E>) -> R1CSResult<Proof<E>> {
+        if self.key != pk.vk.fingerprint() {
+            return Err(SynthesisError::MalformedVerifyingKey);
+        }
+        let (r, s) = (self.r, self.s);
+
+        let g_a = pk.a_query[0] + pk.vk.alpha_g1 + msms.a - self.mask.a
+            + mul_secret(pk.delta_g1.into_group(), r);
+        let g1_b = pk.b_g1_query[0] + pk.beta_g1 + msms.b_g1 - self.mask.b_g1
+            + mul_secret(pk.delta_g1.into_group(), s);
+        let g2_b = pk.b_g2_query[0] + pk.vk.beta_g2 + msms.b_g2 - self.mask.b_g2
+            + mul_secret(pk.vk.delta_g2.into_group(), s);
+
+        let mut g_c = mul_secret(g_a, s);
+        g_c += mul_secret(g1_b, r);
+        g_c -= mul_secret(pk.delta_g1.into_group(), r * s);
+        g_c += msms.l.into_group() - self.mask.l;
+        g_c += msms.h.into_group() - self.mask.h;
+
+        Ok(Proof {
+            a: g_a.into_affine(),
+            b: g2_b.into_affine(),
+            c: g_c.into_affine(),
+        })
+    }
+}
+
+impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
+    /// Samples a one-time mask for delegating a proof with `pk` and
+    /// computes its MSMs with `backend`.
+    ///
+    /// Fails with [`SynthesisError::MalformedVerifyingKey`] if the queries
+    /// of `pk` do not have consistent lengths.
+    pub fn delegation_mask<B>(
+        pk: &ProvingKey<E>,
+        backend: &B,
+        rng: &mut impl Rng,
+    ) -> R1CSResult<DelegationMask<E>>
+    where
+        B: MsmBackend<E::G1> + MsmBackend<E::G2>,
+    {
+        let assignment = (1..pk.a_query.len())
+            .map(|_| E::ScalarField::rand(rng))
+            .collect::<Vec<_>>();
+        let h = (0..pk.h_query.len())
+            .map(|_| E::ScalarField::rand(rng))
+            .collect::<Vec<_>>();
+        let msms = Self::delegated_msms_unchecked(pk, &assignment, &h, backend)?;
+        Ok(DelegationMask {
+            key: pk.vk.fingerprint(),
+            assignment,
+            h,
+            msms,
+        })
+    }
+
+    /// Synthesizes `circuit` and reduces it to its QAP witness, returning
+    /// the job to send to a compute provider, masked with `mask`, and the
+    /// state from which the witness holder assembles the proof. This method
+    /// samples randomness for zero knowledge via `rng`.
+    ///
+    /// The witness holder computes no MSM: the provider computes them all
+    /// with [`Self::compute_delegated_msms`] without learning the witness,
+    /// and the holder only unmasks and blinds them with
+    /// [`DelegatedProver::finish`].
+    ///
+    /// Fails with [`SynthesisError::MalformedVerifyingKey`] if `mask` was
+    /// computed for another key.
+    pub fn delegate_proof<C>(
+        pk: &ProvingKey<E>,
+        circuit: C,
+        mask: DelegationMask<E>,
+        rng: &mut impl Rng,
+    ) -> R1CSResult<(DelegatedJob<E::ScalarField>, DelegatedProver<E>)>
+    where
+        C: ConstraintSynthesizer<E::ScalarField>,
+    {
+        let key = pk.vk.fingerprint();
+        if mask.key != key {
+            return Err(SynthesisError::MalformedVerifyingKey);
+        }
+        let (cs, mut h) = Self::synthesize_and_witness_map(circuit, pk, &ProverConfig::default())?;
+        let prover = cs.borrow().unwrap();
+        let mut assignment = prover.instance_assignment[1..]
+            .iter()
+            .chain(&prover.witness_assignment)
+            .copied()
+            .collect::<Vec<_>>();
+        if assignment.len() != mask.assignment.len() {
+            return Err(SynthesisError::MalformedVerifyingKey);
+        }
+
+        // Coefficients of `h` beyond the `h_query` do not contribute to the
+        // proof, and are not sent unmasked.
+        h.truncate(mask.h.len());
+        for (value, mask) in assignment.iter_mut().zip(&mask.assignment) {
+            *value += mask;
+        }
+        for (value, mask) in h.iter_mut().zip(&mask.h) {
+            *value += mask;
+        }
+
+        let job = DelegatedJob { key, assignment, h };
+        let prover = DelegatedProver {
+            key,
+            r: E::ScalarField::rand(rng),
+            s: E::ScalarField::rand(rng),
+            mask: mask.msms,
+        };
+        Ok((job, prover))
+    }
+
+    /// Computes the MSMs of a delegated `job` with `backend`, on behalf of
+    /// the witness holder.
+    ///
+    /// Fails with [`SynthesisError::MalformedVerifyingKey`] if `pk` is not
+    /// the key the job was delegated with, or if the lengths of the vectors
+    /// of the job do not match it.
+    pub fn compute_delegated_msms<B>(
+        pk: &ProvingKey<E>,
+        job: &DelegatedJob<E::ScalarField>,
+        backend: &B,
+    ) -> R1CSResult<DelegatedMsms<E>>
+    where
+        B: MsmBackend<E::G1> + MsmBackend<E::G2>,
+    {
+        if job.key != pk.vk.fingerprint()
+            || job.assignment.len() + 1 != pk.a_query.len()
+            || job.h.len() > pk.h_query.len()
+        {
+            return Err(SynthesisError::MalformedVerifyingKey);
+        }
+        Self::delegated_msms_unchecked(pk, &job.assignment, &job.h, backend)
+    }
+
+    /// Computes the MSMs of `assignment` and `h` over the queries of `pk`,
+    /// without checking that they were masked for it. Fails with
+    /// [`SynthesisError::MalformedVerifyingKey`] if `pk` has fewer bases
+    /// than `assignment` has variables, or more witness bases.
+    fn delegated_msms_unchecked<B>(
+        pk: &ProvingKey<E>,
+        assignment: &[E::ScalarField],
+        h: &[E::ScalarField],
+        backend: &B,
+    ) -> R1CSResult<DelegatedMsms<E>>
+    where
+        B: MsmBackend<E::G1> + MsmBackend<E::G2>,
+    {
+        let assignment = assignment
+            .iter()
+            .map(|s| s.into_bigint())
+            .collect::<Vec<_>>();
+        let num_inputs = assignment
+            .len()
+            .checked_sub(pk.l_query.len())
+            .ok_or(SynthesisError::MalformedVerifyingKey)?;
+        let num_variables = assignment.len() + 1;
+        if pk.a_query.len() < num_variables
+            || pk.b_g1_query.len() < num_variables
+            || pk.b_g2_query.len() < num_variables
+        {
+            return Err(SynthesisError::MalformedVerifyingKey);
+        }
+        let h_acc = Self::h_accumulator(pk, h, backend);
+        let l: E::G1 = backend.msm_bigint(&pk.l_query, &assignment[num_inputs..]);
+        let a: E::G1 = backend.msm_bigint(&pk.a_query[1..], &assignment);
+        let b_g1: E::G1 = backend.msm_bigint(&pk.b_g1_query[1..], &assignment);
+        let b_g2: E::G2 = backend.msm_bigint(&pk.b_g2_query[1..], &assignment);
+        Ok(DelegatedMsms {
+            a: a.into_affine(),
+            b_g1: b_g1.into_affine(),
+            b_g2: b_g2.into_affine(),
+            l: l.into_affine(),
+            h: h_acc.into_affine(),
+        })
+    }
+}
@@ -0,0 +1,210 @@
+use crate::{Groth16, InputCountMismatch, PreparedVerifyingKey, Proof};
+use ark_crypto_primitives::snark::SNARK;
+use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup};
+use ark_ff::{BigInteger, One, PrimeField};
+use ark_relations::r1cs::Result as R1CSResult;
+use ark_serialize::Valid;
+use ark_std::vec::Vec;
+
+/// A transformation of a list of public inputs, as produced by common
+/// integration mistakes between a prover and a verifier.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InputTransformation {
+    /// The inputs are used as given.
+    Identity,
+    /// Each input is reinterpreted with its bytes in the opposite order, as
+    /// when big-endian encodings are decoded as little-endian ones.
+    EndiannessFlipped,
+    /// The order of the inputs is reversed.
+    Reversed,
+    /// Every input is negated.
+    AllNegated,
+    /// The input at the given index is negated.
+    Negated(usize),
+    /// The inputs at the given indices are swapped.
+    Swapped(usize, usize),
+}
+
+impl InputTransformation {
+    /// Returns `inputs` transformed by `self`.
+    pub fn apply<F: PrimeField>(&self, inputs: &[F]) -> Vec<F> {
+        let mut inputs = inputs.to_vec();
+        match *self {
+            InputTransformation::Identity => {},
+            InputTransformation::EndiannessFlipped => {
+                for input in &mut inputs {
+                    let mut bytes = input.into_bigint().to_bytes_le();
+                    bytes.truncate((F::MODULUS_BIT_SIZE as usize).div_ceil(8));
+                    *input = F::from_be_bytes_mod_order(&bytes);
+                }
+            },
+            InputTransformation::Reversed => inputs.reverse(),
+            InputTransformation::AllNegated => {
+                for input in &mut inputs {
+                    *input = -*input;
+                }
+            },
+            InputTransformation::Negated(i) => {
+                if let Some(input) = inputs.get_mut(i) {
+                    *input = -*input;
+                }
+            },
+            InputTransformation::Swapped(i, j) => {
+                if i < inputs.len() && j < inputs.len() {
+                    inputs.swap(i, j);
+                }
+            },
+        }
+        inputs
+    }
+}
+
+/// Returns the transformations tried by [`diagnose_input_mismatch`] for
+/// `num_inputs` inputs, in order.
+fn candidate_transformations(num_inputs: usize) -> Vec<InputTransformation> {
+    let mut candidates = vec![
+        InputTransformation::Identity,
+        InputTransformation::EndiannessFlipped,
+    ];
+    if num_inputs > 1 {
+        candidates.push(InputTransformation::Reversed);
+        candidates.push(InputTransformation::AllNegated);
+    }
+    candidates.extend((0..num_inputs).map(InputTransformation::Negated));
+    for i in 0..num_inputs {
+        candidates.extend((i + 1..num_inputs).map(|j| InputTransformation::Swapped(i, j)));
+    }
+    candidates
+}
+
+/// Looks for a transformation of `inputs` under which `proof` verifies,
+/// to help debug integrations with external provers which disagree with
+/// the verifier on the encoding or order of the inputs.
+///
+/// Tries, in order: the inputs as given, flipping the byte order of every
+/// input, reversing their order, negating all of them, negating each one,
+/// and swapping each pair. Returns the first transformation which makes the
+/// proof verify, [`InputTransformation::Identity`] if the proof already
+/// verifies, or `None` if none does.
+///
+/// Every candidate costs a full verification, and there are quadratically
+/// many in the number of inputs: this is a debugging aid, not a verifier.
+pub fn diagnose_input_mismatch<E: Pairing>(
+    pvk: &PreparedVerifyingKey<E>,
+    proof: &Proof<E>,
+    inputs: &[E::ScalarField],
+) -> R1CSResult<Option<InputTransformation>> {
+    for transformation in candidate_transformations(inputs.len()) {
+        let candidate = transformation.apply(inputs);
+        if Groth16::<E>::verify_with_processed_vk(pvk, &candidate, proof)? {
+            return Ok(Some(transformation));
+        }
+    }
+    Ok(None)
+}
+
+/// An element of a proof.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ProofElement {
+    /// The element `A`, in `G1`.
+    A,
+    /// The element `B`, in `G2`.
+    B,
+    /// The element `C`, in `G1`.
+    C,
+}
+
+/// The most likely cause of the rejection of a proof, found by
+/// [`analyze_failure`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FailureDiagnosis {
+    /// The proof verifies.
+    Valid,
+    /// The number of inputs differs from the one expected by the key.
+    InputCount(InputCountMismatch),
+    /// The verifying key is degenerate: `e(alpha, beta)` is one, or `gamma`
+    /// or `delta` is the identity. It accepts arbitrary proofs and must not
+    /// be used.
+    DegenerateVerifyingKey,
+    /// The element is not on the curve or not in its prime-order subgroup,
+    /// as when a proof was decoded without validation from the encoding of
+    /// another curve, or with the coordinates of `G2` in the wrong order.
+    InvalidPoint(ProofElement),
+    /// The element is the identity, which honest provers only produce with
+    /// negligible probability.
+    Identity(ProofElement),
+    /// The proof verifies with the element negated: the prover and the
+    /// verifier disagree on the sign convention of the pairing equation, as
+    /// on-chain verifiers expecting `-A` do.
+    Negated(ProofElement),
+    /// The proof verifies with the inputs transformed, see
+    /// [`diagnose_input_mismatch`].
+    InputEncoding(InputTransformation),
+    /// The pairing equation fails for no known reason: the proof is for
+    /// other inputs, another verifying key or another circuit.
+    PairingMismatch,
+}
+
+/// Analyzes why `proof` is rejected for `inputs`, static inputs first, to
+/// help support integrations with external provers.
+///
+/// Checks, in order: the number of inputs, the verifying key, that the
+/// elements of the proof are valid points and not the identity, the
+/// pairing equation, the equation with `A` or `C` negated, and the
+/// equation with the inputs transformed as by [`diagnose_input_mismatch`].
+/// Returns the first failing check, or [`FailureDiagnosis::Valid`] if the
+/// proof verifies.
+///
+/// Like [`diagnose_input_mismatch`], this costs up to quadratically many
+/// verifications in the number of inputs.
+pub fn analyze_failure<E: Pairing>(
+    pvk: &PreparedVerifyingKey<E>,
+    proof: &Proof<E>,
+    inputs: &[E::ScalarField],
+) -> R1CSResult<FailureDiagnosis> {
+    let num_static = inputs.len().min(pvk.num_static_inputs());
+    if let Err(mismatch) = pvk.check_input_counts(num_static, inputs.len() - num_static) {
+        return Ok(FailureDiagnosis::InputCount(mismatch));
+    }
+    if pvk.alpha_g1_beta_g2.is_one() || pvk.vk.gamma_g2.is_zero() || pvk.vk.delta_g2.is_zero() {
+        return Ok(FailureDiagnosis::DegenerateVerifyingKey);
+    }
+
+    let elements = [
+        (ProofElement::A, proof.a.check().is_ok(), proof.a.is_zero()),
+        (ProofElement::B, proof.b.check().is_ok(), proof.b.is_zero()),
+        (ProofElement::C, proof.c.check().is_ok(), proof.c.is_zero()),
+    ];
+    if let Some((element, ..)) = elements.iter().find(|(_, valid, _)| !valid) {
+        return Ok(FailureDiagnosis::InvalidPoint(*element));
+    }
+    if let Some((element, ..)) = elements.iter().find(|(.., identity)| *identity) {
+        return Ok(FailureDiagnosis::Identity(*element));
+    }
+
+    if Groth16::<E>::verify_with_processed_vk(pvk, inputs, proof)? {
+        return Ok(FailureDiagnosis::Valid);
+    }
+
+    // Negating `B` is equivalent to negating `A`.
+    let neg = |p: E::G1Affine| (-p.into_group()).into_affine();
+    let negated_a = Proof {
+        a: neg(proof.a),
+        ..proof.clone()
+    };
+    if Groth16::<E>::verify_with_processed_vk(pvk, inputs, &negated_a)? {
+        return Ok(FailureDiagnosis::Negated(ProofElement::A));
+    }
+    let negated_c = Proof {
+        c: neg(proof.c),
+        ..proof.clone()
+    };
+    if Groth16::<E>::verify_with_processed_vk(pvk, inputs, &negated_c)? {
+        return Ok(FailureDiagnosis::Negated(ProofElement::C));
+    }
+
+    Ok(match diagnose_input_mismatch(pvk, proof, inputs)? {
+        Some(transformation) => FailureDiagnosis::InputEncoding(transformation),
+        None => FailureDiagnosis::PairingMismatch,
+    })
+}
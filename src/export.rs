@@ -0,0 +1,287 @@
+use crate::{r1cs_to_qap::R1CSToQAP, Groth16, KeyBasis, ProvingKey, VerifyingKey};
+use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup, VariableBaseMSM};
+use ark_ff::{Field, UniformRand, Zero};
+use ark_poly::{EvaluationDomain, GeneralEvaluationDomain};
+use ark_relations::r1cs::{
+    ConstraintMatrices, ConstraintSynthesizer, Matrix, Result as R1CSResult, SynthesisError,
+};
+use ark_serialize::*;
+use ark_std::{cfg_iter, format, rand::Rng, string::String, vec::Vec};
+
+use ark_std::ops::Mul;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// The names of the variables and constraints of a circuit.
+#[derive(Clone, Debug, Default, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct SymbolTable {
+    /// The names of the variables, in the order of the columns of the
+    /// matrices: `one` for the constant, `input_i` for the `i`-th instance
+    /// variable and `witness_i` for the `i`-th witness variable.
+    pub variables: Vec<String>,
+    /// The first constraint involving each variable, if any, locating the
+    /// variable in [`Self::constraints`].
+    pub first_constraints: Vec<Option<usize>>,
+    /// The namespace paths of the constraints, if constraint traces were
+    /// recorded during synthesis, and empty otherwise.
+    pub constraints: Vec<String>,
+}
+
+impl SymbolTable {
+    fn new<F: Field>(
+        matrices: &ConstraintMatrices<F>,
+        constraint_names: Option<Vec<String>>,
+    ) -> Self {
+        let num_instance = matrices.num_instance_variables;
+        let num_variables = num_instance + matrices.num_witness_variables;
+        let variables = (0..num_variables)
+            .map(|i| match i {
+                0 => String::from("one"),
+                i if i < num_instance => format!("input_{}", i),
+                i => format!("witness_{}", i - num_instance),
+            })
+            .collect();
+
+        let mut first_constraints = ark_std::vec![None; num_variables];
+        for constraint in 0..matrices.num_constraints {
+            for matrix in [&matrices.a, &matrices.b, &matrices.c] {
+                for (_, variable) in &matrix[constraint] {
+                    first_constraints[*variable].get_or_insert(constraint);
+                }
+            }
+        }
+
+        Self {
+            variables,
+            first_constraints,
+            constraints: constraint_names.unwrap_or_default(),
+        }
+    }
+}
+
+/// The constraint system of a setup, exported for auditors along with the
+/// [`VerifyingKey`] by
+/// [`Groth16::generate_random_parameters_with_export`].
+///
+/// Besides the matrices and the names of their variables, the export holds
+/// the evaluations `L_j(t) * G` and `L_j(t) * H` of the Lagrange
+/// polynomials of the QAP domain at the secret point, as a
+/// [`CeremonyArtifact`](crate::artifact::CeremonyArtifact) does. They let
+/// an auditor recompute the `gamma_abc_g1` of the key in the exponent from
+/// the matrices, see [`Self::matches_verifying_key`], and compare the
+/// matrices with those of the intended circuit, see
+/// [`Self::matches_circuit`].
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct ConstraintExport<E: Pairing> {
+    /// The number of instance variables, including the constant one.
+    pub num_instance_variables: usize,
+    /// The number of witness variables.
+    pub num_witness_variables: usize,
+    /// The `A` matrix of the constraint system.
+    pub a: Matrix<E::ScalarField>,
+    /// The `B` matrix of the constraint system.
+    pub b: Matrix<E::ScalarField>,
+    /// The `C` matrix of the constraint system.
+    pub c: Matrix<E::ScalarField>,
+    /// The names of the variables and constraints.
+    pub symbols: SymbolTable,
+    /// The generator `G` of `E::G1` of the setup.
+    pub g1_generator: E::G1Affine,
+    /// The generator `H` of `E::G2` of the setup.
+    pub g2_generator: E::G2Affine,
+    /// The elements `L_j(t) * G` in `E::G1`, for the constraints followed by
+    /// the instance variables.
+    pub lagrange_g1: Vec<E::G1Affine>,
+    /// The elements `L_j(t) * H` in `E::G2`, for the constraints.
+    pub lagrange_g2: Vec<E::G2Affine>,
+}
+
+/// Computes, for each instance variable, the linear combination of
+/// `lagrange` with the coefficients of the column of `matrix` for that
+/// variable.
+fn instance_column<G: CurveGroup>(
+    matrix: &Matrix<G::ScalarField>,
+    lagrange: &[G::Affine],
+    num_instance: usize,
+) -> Vec<G> {
+    let mut columns = ark_std::vec![G::zero(); num_instance];
+    for (row, point) in matrix.iter().zip(lagrange) {
+        for (coefficient, variable) in row {
+            if *variable < num_instance {
+                columns[*variable] += *point * coefficient;
+            }
+        }
+    }
+    columns
+}
+
+impl<E: Pairing> ConstraintExport<E> {
+    /// Returns the number of constraints.
+    pub fn num_constraints(&self) -> usize {
+        self.a.len()
+    }
+
+    /// Returns whether the matrices of `self` are those of `circuit`.
+    pub fn matches_circuit<C: ConstraintSynthesizer<E::ScalarField>>(
+        &self,
+        circuit: C,
+    ) -> R1CSResult<bool> {
+        let cs = Groth16::<E>::synthesize_for_setup(circuit)?;
+        let matrices = cs.to_matrices().ok_or(SynthesisError::AssignmentMissing)?;
+        Ok(
+            matrices.num_instance_variables == self.num_instance_variables
+                && matrices.num_witness_variables == self.num_witness_variables
+                && matrices.a == self.a
+                && matrices.b == self.b
+                && matrices.c == self.c,
+        )
+    }
+
+    /// Returns whether `vk` is the key of the constraint system of `self`
+    /// at its evaluation point: whether the `gamma_abc_g1` of `vk` are the
+    /// `gamma^{-1} * (beta * a_i(t) + alpha * b_i(t) + c_i(t)) * G` of the
+    /// instance variables, following the instance map of
+    /// [`LibsnarkReduction`](crate::r1cs_to_qap::LibsnarkReduction).
+    ///
+    /// The Lagrange evaluations in `E::G1` and `E::G2` are checked to agree,
+    /// and the equations for all variables are checked at once on a random
+    /// linear combination drawn from `rng`.
+    pub fn matches_verifying_key(&self, vk: &VerifyingKey<E>, rng: &mut impl Rng) -> bool {
+        let num_constraints = self.num_constraints();
+        let num_instance = self.num_instance_variables;
+        let gamma_abc_g1: Vec<_> = vk
+            .gamma_abc_g1_static
+            .iter()
+            .chain(&vk.gamma_abc_g1_variable)
+            .copied()
+            .collect();
+        if self.b.len() != num_constraints
+            || self.c.len() != num_constraints
+            || self.lagrange_g1.len() != num_constraints + num_instance
+            || self.lagrange_g2.len() != num_constraints
+            || gamma_abc_g1.len() != num_instance
+            || self.g1_generator.is_zero()
+            || self.g2_generator.is_zero()
+        {
+            return false;
+        }
+
+        let r: Vec<E::ScalarField> = (0..num_constraints)
+            .map(|_| E::ScalarField::rand(rng))
+            .collect();
+        let lagrange_g1 = E::G1::msm_unchecked(&self.lagrange_g1[..num_constraints], &r);
+        let lagrange_g2 = E::G2::msm_unchecked(&self.lagrange_g2, &r);
+        if E::multi_pairing(
+            [
+                lagrange_g1.into_affine(),
+                (-self.g1_generator.into_group()).into_affine(),
+            ],
+            [self.g2_generator, lagrange_g2.into_affine()],
+        ) != Zero::zero()
+        {
+            return false;
+        }
+
+        // The constraints `x_i * 0 = 0` of the reduction bind the inputs.
+        let mut a = instance_column::<E::G1>(&self.a, &self.lagrange_g1, num_instance);
+        for (column, point) in a.iter_mut().zip(&self.lagrange_g1[num_constraints..]) {
+            *column += point;
+        }
+        let b = instance_column::<E::G2>(&self.b, &self.lagrange_g2, num_instance);
+        let c = instance_column::<E::G1>(&self.c, &self.lagrange_g1, num_instance);
+
+        let r: Vec<E::ScalarField> = (0..num_instance)
+            .map(|_| E::ScalarField::rand(rng))
+            .collect();
+        let combine_g1 =
+            |points: &[E::G1]| E::G1::msm_unchecked(&E::G1::normalize_batch(points), &r);
+        let gamma_abc = E::G1::msm_unchecked(&gamma_abc_g1, &r);
+        let b = E::G2::msm_unchecked(&E::G2::normalize_batch(&b), &r);
+        E::multi_pairing(
+            [
+                gamma_abc.into_affine(),
+                (-combine_g1(&a)).into_affine(),
+                (-vk.alpha_g1.into_group()).into_affine(),
+                (-combine_g1(&c)).into_affine(),
+            ],
+            [vk.gamma_g2, vk.beta_g2, b.into_affine(), self.g2_generator],
+        ) == Zero::zero()
+    }
+}
+
+impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
+    /// Generates a random common reference string for a circuit, like
+    /// [`Self::generate_random_parameters_with_reduction`], along with a
+    /// [`ConstraintExport`] of its constraint system for auditors, to be
+    /// serialized separately from the keys.
+    ///
+    /// Constraint names are exported if constraint traces are enabled, e.g.
+    /// through a `ConstraintLayer` of `ark-relations` in a tracing
+    /// subscriber.
+    pub fn generate_random_parameters_with_export<C>(
+        circuit: C,
+        rng: &mut impl Rng,
+        num_static_inputs: usize,
+    ) -> R1CSResult<(ProvingKey<E>, ConstraintExport<E>)>
+    where
+        C: ConstraintSynthesizer<E::ScalarField>,
+    {
+        let alpha = E::ScalarField::rand(rng);
+        let beta = E::ScalarField::rand(rng);
+        let gamma = E::ScalarField::rand(rng);
+        let delta = E::ScalarField::rand(rng);
+
+        let g1_generator = E::G1::rand(rng);
+        let g2_generator = E::G2::rand(rng);
+
+        let cs = Self::synthesize_for_setup(circuit)?;
+        let matrices = cs.to_matrices().ok_or(SynthesisError::AssignmentMissing)?;
+        let symbols = SymbolTable::new(&matrices, cs.constraint_names());
+        drop(cs);
+        let t = Self::sample_evaluation_point(&matrices, rng)?;
+        let num_constraints = matrices.num_constraints;
+        let num_instance_variables = matrices.num_instance_variables;
+
+        let domain = GeneralEvaluationDomain::<E::ScalarField>::new(
+            num_constraints + num_instance_variables,
+        )
+        .ok_or(SynthesisError::PolynomialDegreeTooLarge)?;
+        let mut lagrange = domain.evaluate_all_lagrange_coefficients(t);
+        lagrange.truncate(num_constraints + num_instance_variables);
+        let lagrange_g1 = cfg_iter!(lagrange)
+            .map(|l| g1_generator.mul(l))
+            .collect::<Vec<_>>();
+        let lagrange_g2 = cfg_iter!(lagrange[..num_constraints])
+            .map(|l| g2_generator.mul(l))
+            .collect::<Vec<_>>();
+
+        let pk = Self::generate_parameters_with_evaluation_point(
+            &matrices,
+            t,
+            alpha,
+            beta,
+            gamma,
+            delta,
+            num_static_inputs,
+            g1_generator,
+            g2_generator,
+            KeyBasis::Monomial,
+            &mut (),
+        )?;
+
+        let export = ConstraintExport {
+            num_instance_variables,
+            num_witness_variables: matrices.num_witness_variables,
+            a: matrices.a,
+            b: matrices.b,
+            c: matrices.c,
+            symbols,
+            g1_generator: g1_generator.into_affine(),
+            g2_generator: g2_generator.into_affine(),
+            lagrange_g1: E::G1::normalize_batch(&lagrange_g1),
+            lagrange_g2: E::G2::normalize_batch(&lagrange_g2),
+        };
+        Ok((pk, export))
+    }
+}
@@ -0,0 +1,77 @@
+use crate::{Groth16, InputCountMismatch, PreparedVerifyingKey, Proof};
+use ark_ec::{pairing::Pairing, AffineRepr};
+use ark_ff::PrimeField;
+use ark_relations::r1cs::Result as R1CSResult;
+
+/// A verifier for a verifying key with `STATIC` static and `VARIABLE`
+/// variable inputs, fixed at compile time, for hot paths verifying proofs
+/// of a single circuit.
+///
+/// The shape of the key is checked once by [`Self::new`], and the inputs
+/// are arrays, so that their counts are checked by the compiler rather than
+/// on every verification. Inputs are aggregated by double-and-add, without
+/// allocating, which suits the few inputs of most circuits; circuits with
+/// many inputs are faster with [`Groth16::verify_with_variables`].
+#[derive(Clone, Debug)]
+pub struct FixedVerifier<E: Pairing, const STATIC: usize, const VARIABLE: usize> {
+    pvk: PreparedVerifyingKey<E>,
+}
+
+impl<E: Pairing, const STATIC: usize, const VARIABLE: usize> FixedVerifier<E, STATIC, VARIABLE> {
+    /// Creates a verifier for `pvk`, which must expect `STATIC` static and
+    /// `VARIABLE` variable inputs.
+    pub fn new(pvk: PreparedVerifyingKey<E>) -> Result<Self, InputCountMismatch> {
+        // A key without the constant term expects no valid number of static
+        // inputs, which `check_input_counts` does not catch.
+        if pvk.vk.gamma_abc_g1_static.is_empty() {
+            return Err(InputCountMismatch {
+                expected_static: 0,
+                actual_static: STATIC,
+                expected_variable: pvk.num_variable_inputs(),
+                actual_variable: VARIABLE,
+            });
+        }
+        pvk.check_input_counts(STATIC, VARIABLE)?;
+        Ok(Self { pvk })
+    }
+
+    /// Returns the prepared verifying key.
+    pub fn pvk(&self) -> &PreparedVerifyingKey<E> {
+        &self.pvk
+    }
+
+    /// Returns the prepared verifying key, consuming the verifier.
+    pub fn into_pvk(self) -> PreparedVerifyingKey<E> {
+        self.pvk
+    }
+
+    /// Aggregates the inputs with the `gamma_abc_g1` elements of the key,
+    /// like [`Groth16::prepare_inputs_with_variables`].
+    pub fn prepare_inputs(
+        &self,
+        static_inputs: &[E::ScalarField; STATIC],
+        variable_inputs: &[E::ScalarField; VARIABLE],
+    ) -> E::G1 {
+        let vk = &self.pvk.vk;
+        let mut g_ic = vk.gamma_abc_g1_static[0].into_group();
+        for (base, input) in vk.gamma_abc_g1_static[1..]
+            .iter()
+            .zip(static_inputs)
+            .chain(vk.gamma_abc_g1_variable.iter().zip(variable_inputs))
+        {
+            g_ic += base.mul_bigint(input.into_bigint());
+        }
+        g_ic
+    }
+
+    /// Verifies `proof` against the inputs.
+    pub fn verify(
+        &self,
+        proof: &Proof<E>,
+        static_inputs: &[E::ScalarField; STATIC],
+        variable_inputs: &[E::ScalarField; VARIABLE],
+    ) -> R1CSResult<bool> {
+        let prepared_inputs = self.prepare_inputs(static_inputs, variable_inputs);
+        Groth16::<E>::verify_proof_with_prepared_inputs(&self.pvk, proof, &prepared_inputs)
+    }
+}
@@ -0,0 +1,278 @@
+use crate::{
+    testing::SyntheticCircuit, Groth16, Proof, ProvingKey, VerificationError, VerifyingKey,
+};
+use ark_ec::{pairing::Pairing, CurveGroup};
+use ark_ff::{One, PrimeField};
+use ark_relations::r1cs::SynthesisError;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, SerializationError};
+use ark_std::{
+    fmt,
+    rand::{rngs::StdRng, Rng, SeedableRng},
+    vec::Vec,
+};
+
+#[cfg(feature = "any-curve")]
+use crate::any_curve::Curve;
+
+/// The largest number of elements of each input query of a generated
+/// [`VerifyingKey`].
+pub const MAX_FUZZ_INPUTS: usize = 16;
+
+/// The input of a fuzzer, from which values are consumed front to back.
+///
+/// Once the data is exhausted, zeros are read instead, so that any input,
+/// including an empty one, produces a value and the fuzzer never has to
+/// discard its input.
+#[derive(Clone, Copy, Debug)]
+pub struct FuzzInput<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> FuzzInput<'a> {
+    /// Wraps the `data` provided by a fuzzer.
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    /// Returns whether the data is exhausted.
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Consumes up to `len` bytes.
+    pub fn bytes(&mut self, len: usize) -> &'a [u8] {
+        let (head, tail) = self.data.split_at(len.min(self.data.len()));
+        self.data = tail;
+        head
+    }
+
+    /// Consumes a byte.
+    pub fn byte(&mut self) -> u8 {
+        self.bytes(1).first().copied().unwrap_or(0)
+    }
+
+    /// Consumes a length of at most `max`.
+    pub fn length(&mut self, max: usize) -> usize {
+        let mut bytes = [0u8; 8];
+        let head = self.bytes(8);
+        bytes[..head.len()].copy_from_slice(head);
+        (u64::from_le_bytes(bytes) % (max as u64 + 1)) as usize
+    }
+
+    /// Consumes a field element, reduced from as many bytes as the modulus.
+    pub fn field<F: PrimeField>(&mut self) -> F {
+        F::from_le_bytes_mod_order(self.bytes(F::MODULUS_BIT_SIZE.div_ceil(8) as usize))
+    }
+
+    /// Consumes a point of the prime-order subgroup of `G`, the generator
+    /// times a consumed scalar, which is the identity when the data is
+    /// exhausted.
+    pub fn point<G: CurveGroup>(&mut self) -> G::Affine {
+        (G::generator() * self.field::<G::ScalarField>()).into_affine()
+    }
+}
+
+/// A value which can be generated from the input of a fuzzer, for fuzz
+/// targets of the deserialization and verification paths.
+///
+/// This plays the role of `arbitrary::Arbitrary` without the dependency: an
+/// `Arbitrary` implementation of a wrapper can forward the bytes of its
+/// `Unstructured` input to [`FuzzInput::new`].
+pub trait FromFuzzInput: Sized {
+    /// Generates a value, consuming `input`.
+    fn from_fuzz_input(input: &mut FuzzInput<'_>) -> Self;
+}
+
+impl<E: Pairing> FromFuzzInput for Proof<E> {
+    fn from_fuzz_input(input: &mut FuzzInput<'_>) -> Self {
+        Self {
+            a: input.point::<E::G1>(),
+            b: input.point::<E::G2>(),
+            c: input.point::<E::G1>(),
+        }
+    }
+}
+
+impl<E: Pairing> FromFuzzInput for VerifyingKey<E> {
+    fn from_fuzz_input(input: &mut FuzzInput<'_>) -> Self {
+        let mut query = |min: usize| {
+            let len = min + input.length(MAX_FUZZ_INPUTS - min);
+            (0..len).map(|_| input.point::<E::G1>()).collect::<Vec<_>>()
+        };
+        let gamma_abc_g1_static = query(1);
+        let gamma_abc_g1_variable = query(0);
+        Self {
+            alpha_g1: input.point::<E::G1>(),
+            beta_g2: input.point::<E::G2>(),
+            gamma_g2: input.point::<E::G2>(),
+            delta_g2: input.point::<E::G2>(),
+            gamma_abc_g1_static,
+            gamma_abc_g1_variable,
+        }
+    }
+}
+
+/// Applies a mutation chosen by `input` to the serialization `bytes`:
+/// flipping a bit, overwriting, inserting or removing a byte, truncating,
+/// or appending bytes. Empty serializations are only ever extended.
+pub fn mutate_serialized(bytes: &mut Vec<u8>, input: &mut FuzzInput<'_>) {
+    let mutation = input.byte() % 6;
+    if bytes.is_empty() {
+        bytes.push(input.byte());
+        return;
+    }
+    let position = input.length(bytes.len() - 1);
+    match mutation {
+        0 => bytes[position] ^= 1 << (input.byte() % 8),
+        1 => bytes[position] = input.byte(),
+        2 => bytes.insert(position, input.byte()),
+        3 => {
+            bytes.remove(position);
+        },
+        4 => bytes.truncate(position),
+        _ => {
+            let len = input.length(32);
+            bytes.extend_from_slice(input.bytes(len));
+        },
+    }
+}
+
+/// The largest number of constraints of the circuits of
+/// [`roundtrip_check`].
+pub const MAX_ROUNDTRIP_CONSTRAINTS: usize = 32;
+
+/// The largest number of public inputs of the circuits of
+/// [`roundtrip_check`].
+pub const MAX_ROUNDTRIP_INPUTS: usize = 4;
+
+/// The step of [`roundtrip_check`] which failed.
+#[derive(Debug)]
+pub enum RoundtripError {
+    /// The keys could not be generated.
+    Setup(SynthesisError),
+    /// The proof could not be created.
+    Prove(SynthesisError),
+    /// A key or the proof could not be serialized or deserialized.
+    Serialization(SerializationError),
+    /// A deserialized key or proof differs from the serialized one.
+    Mismatch(&'static str),
+    /// The verifier failed on the proof.
+    Verify(VerificationError),
+    /// The verifier rejected the proof.
+    Rejected,
+    /// The verifier accepted the proof with altered public inputs.
+    AcceptedAlteredInputs,
+}
+
+impl fmt::Display for RoundtripError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RoundtripError::Setup(e) => write!(f, "setup failed: {}", e),
+            RoundtripError::Prove(e) => write!(f, "proving failed: {}", e),
+            RoundtripError::Serialization(e) => write!(f, "serialization failed: {}", e),
+            RoundtripError::Mismatch(what) => {
+                write!(f, "deserialized {} differs from the original", what)
+            },
+            RoundtripError::Verify(e) => write!(f, "verification failed: {}", e),
+            RoundtripError::Rejected => write!(f, "proof was rejected"),
+            RoundtripError::AcceptedAlteredInputs => {
+                write!(f, "proof was accepted with altered inputs")
+            },
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for RoundtripError {}
+
+/// Serializes `value`, compressed or not, and deserializes it back with
+/// validation, checking that the round trip preserves it.
+fn roundtrip<T>(value: &T, compress: bool, what: &'static str) -> Result<T, RoundtripError>
+where
+    T: CanonicalSerialize + CanonicalDeserialize + PartialEq,
+{
+    let mut bytes = Vec::new();
+    let deserialized = if compress {
+        value
+            .serialize_compressed(&mut bytes)
+            .and_then(|()| T::deserialize_compressed(&bytes[..]))
+    } else {
+        value
+            .serialize_uncompressed(&mut bytes)
+            .and_then(|()| T::deserialize_uncompressed(&bytes[..]))
+    }
+    .map_err(RoundtripError::Serialization)?;
+    if deserialized != *value {
+        return Err(RoundtripError::Mismatch(what));
+    }
+    Ok(deserialized)
+}
+
+/// Runs [`roundtrip_check`] over the curve of `E`.
+pub fn roundtrip_check_for<E: Pairing>(circuit_seed: u64) -> Result<(), RoundtripError> {
+    let mut rng = StdRng::seed_from_u64(circuit_seed);
+    let num_inputs = rng.gen_range(0..=MAX_ROUNDTRIP_INPUTS);
+    let num_static_inputs = rng.gen_range(0..=num_inputs);
+    let circuit =
+        SyntheticCircuit::<E::ScalarField>::blank(rng.gen_range(1..=MAX_ROUNDTRIP_CONSTRAINTS))
+            .with_inputs(num_inputs)
+            .with_density(rng.gen_range(1..=3))
+            .with_depth(rng.gen_range(1..=3))
+            .with_seed(rng.gen());
+    let circuit = circuit.with_variables(circuit.num_constraints + rng.gen_range(0..8));
+    let compress = rng.gen();
+
+    let pk = Groth16::<E>::generate_random_parameters_with_reduction(
+        circuit,
+        &mut rng,
+        1 + num_static_inputs,
+        0,
+    )
+    .map_err(RoundtripError::Setup)?;
+    let pk: ProvingKey<E> = roundtrip(&pk, compress, "proving key")?;
+
+    let circuit = circuit.with_assignment(rng.gen());
+    let proof = Groth16::<E>::create_random_proof_with_reduction(circuit, &pk, &mut rng)
+        .map_err(RoundtripError::Prove)?;
+    let proof: Proof<E> = roundtrip(&proof, compress, "proof")?;
+    let vk: VerifyingKey<E> = roundtrip(&pk.vk, compress, "verifying key")?;
+
+    let pvk = crate::prepare_verifying_key(&vk);
+    let mut inputs = circuit
+        .public_inputs()
+        .expect("the circuit has an assignment");
+    let verify = |inputs: &[E::ScalarField]| {
+        let (static_inputs, variable_inputs) = inputs.split_at(num_static_inputs);
+        Groth16::<E>::verify_with_variables(&pvk, &proof, static_inputs, variable_inputs)
+            .map_err(RoundtripError::Verify)
+    };
+    if !verify(&inputs)? {
+        return Err(RoundtripError::Rejected);
+    }
+    if let Some(input) = inputs.first_mut() {
+        *input += E::ScalarField::one();
+        if verify(&inputs)? {
+            return Err(RoundtripError::AcceptedAlteredInputs);
+        }
+    }
+    Ok(())
+}
+
+/// Sets up, proves, serializes, deserializes and verifies a small circuit
+/// over `curve`, with a shape and a witness derived from `circuit_seed`, for
+/// fuzz targets and CI jobs exercising the whole pipeline.
+///
+/// The circuit is a [`SyntheticCircuit`] of at most
+/// [`MAX_ROUNDTRIP_CONSTRAINTS`] constraints and [`MAX_ROUNDTRIP_INPUTS`]
+/// public inputs, split between static and variable inputs. The check fails
+/// if any step fails, if a key or the proof does not survive its round trip
+/// through serialization, if the proof is rejected, or if it is accepted
+/// with an altered input.
+#[cfg(feature = "any-curve")]
+pub fn roundtrip_check(circuit_seed: u64, curve: Curve) -> Result<(), RoundtripError> {
+    match curve {
+        Curve::Bn254 => roundtrip_check_for::<ark_bn254::Bn254>(circuit_seed),
+        Curve::Bls12_381 => roundtrip_check_for::<ark_bls12_381::Bls12_381>(circuit_seed),
+        Curve::Bls12_377 => roundtrip_check_for::<ark_bls12_377::Bls12_377>(circuit_seed),
+    }
+}
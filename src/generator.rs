@@ -1,19 +1,74 @@
-use crate::{r1cs_to_qap::R1CSToQAP, Groth16, ProvingKey, Vec, VerifyingKey};
-use ark_ec::{pairing::Pairing, CurveGroup, VariableBaseMSM};
-use ark_ff::{Field, UniformRand, Zero, PrimeField};
+use crate::{
+    msm::mul_secret, prover::PhaseProbe, r1cs_to_qap::R1CSToQAP, CommitmentKey,
+    CommitmentVerifyingKey, Groth16, KeyBasis, ProvingKey, Vec, VerifyingKey,
+};
+use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup};
+use ark_ff::{Field, UniformRand};
 use ark_poly::{EvaluationDomain, GeneralEvaluationDomain};
 use ark_relations::r1cs::{
-    ConstraintSynthesizer, ConstraintSystem, OptimizationGoal, Result as R1CSResult,
-    SynthesisError, SynthesisMode,
+    ConstraintMatrices, ConstraintSynthesizer, ConstraintSystem, ConstraintSystemRef,
+    OptimizationGoal, Result as R1CSResult, SynthesisError, SynthesisMode,
 };
+use ark_std::cfg_iter;
 use ark_std::rand::Rng;
-use ark_std::{cfg_into_iter, cfg_iter};
+use zeroize::Zeroize;
+
+use ark_std::ops::Mul;
 
-use std::ops::Mul;
+#[cfg(feature = "std")]
+use crate::progress::{ObserverProbe, ProgressObserver};
 
 #[cfg(feature = "parallel")]
 use rayon::prelude::*;
 
+type D<F> = GeneralEvaluationDomain<F>;
+
+/// The number of group elements of a query computed between two progress
+/// reports.
+const QUERY_CHUNK_SIZE: usize = 1 << 12;
+
+/// A phase of key generation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SetupPhase {
+    /// Synthesizing the circuit and building its constraint matrices.
+    Matrices,
+    /// Evaluating the QAP at the secret point, including its powers.
+    Evaluation,
+    /// Computing the `b_g2_query`.
+    BG2Query,
+    /// Computing the `a_query`.
+    AQuery,
+    /// Computing the `b_g1_query`.
+    BG1Query,
+    /// Computing the `h_query`.
+    HQuery,
+    /// Computing the `l_query`.
+    LQuery,
+    /// Computing the input bases of the verifying key.
+    VerifyingKey,
+}
+
+/// Returns the multiples of `generator` by `scalars`, reporting them to
+/// `probe` as `phase` in chunks of [`QUERY_CHUNK_SIZE`].
+fn fixed_base_query<G: CurveGroup>(
+    generator: G,
+    scalars: &[G::ScalarField],
+    phase: SetupPhase,
+    probe: &mut impl PhaseProbe<SetupPhase>,
+) -> Vec<G::Affine> {
+    let mut query = Vec::with_capacity(scalars.len());
+    probe.advance(phase, 0, scalars.len());
+    for chunk in scalars.chunks(QUERY_CHUNK_SIZE) {
+        query.extend(
+            cfg_iter!(chunk)
+                .map(|s| generator.mul(s).into_affine())
+                .collect::<Vec<_>>(),
+        );
+        probe.advance(phase, query.len(), scalars.len());
+    }
+    query
+}
+
 impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
     /// Generates a random common reference string for
     /// a circuit using the provided R1CS-to-QAP reduction.
@@ -24,6 +79,29 @@ impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
         num_static_inputs: usize,
         num_inputs: usize,
     ) -> R1CSResult<ProvingKey<E>>
+    where
+        C: ConstraintSynthesizer<E::ScalarField>,
+    {
+        Self::generate_random_parameters_with_basis(
+            circuit,
+            rng,
+            num_static_inputs,
+            num_inputs,
+            KeyBasis::Monomial,
+        )
+    }
+
+    /// Generates a random common reference string for a circuit using the
+    /// provided R1CS-to-QAP reduction, with the `h_query` expressed in
+    /// `h_basis`.
+    #[inline]
+    pub fn generate_random_parameters_with_basis<C>(
+        circuit: C,
+        rng: &mut impl Rng,
+        num_static_inputs: usize,
+        num_inputs: usize,
+        h_basis: KeyBasis,
+    ) -> R1CSResult<ProvingKey<E>>
     where
         C: ConstraintSynthesizer<E::ScalarField>,
     {
@@ -35,7 +113,7 @@ impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
         let g1_generator = E::G1::rand(rng);
         let g2_generator = E::G2::rand(rng);
 
-        Self::generate_parameters_with_qap(
+        Self::generate_parameters_with_qap_and_basis(
             circuit,
             alpha,
             beta,
@@ -45,11 +123,105 @@ impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
             num_inputs,
             g1_generator,
             g2_generator,
+            h_basis,
             rng,
         )
     }
 
+    /// Generates a random common reference string for a circuit using the
+    /// provided R1CS-to-QAP reduction, reporting the progress of its phases
+    /// to `observer`. The remaining time is estimated from the throughput of
+    /// the computation of the queries, weighting the elements of `G2` as
+    /// three times as costly.
+    ///
+    /// `circuit_specific_setup` is this with one static input, the
+    /// constant one.
+    #[cfg(feature = "std")]
+    pub fn generate_random_parameters_with_progress<C>(
+        circuit: C,
+        rng: &mut impl Rng,
+        num_static_inputs: usize,
+        observer: &mut impl ProgressObserver<SetupPhase>,
+    ) -> R1CSResult<ProvingKey<E>>
+    where
+        C: ConstraintSynthesizer<E::ScalarField>,
+    {
+        let alpha = E::ScalarField::rand(rng);
+        let beta = E::ScalarField::rand(rng);
+        let gamma = E::ScalarField::rand(rng);
+        let delta = E::ScalarField::rand(rng);
+
+        let g1_generator = E::G1::rand(rng);
+        let g2_generator = E::G2::rand(rng);
+
+        Self::generate_parameters_with_probe(
+            circuit,
+            [alpha, beta, gamma, delta],
+            num_static_inputs,
+            g1_generator,
+            g2_generator,
+            KeyBasis::Monomial,
+            rng,
+            &mut ObserverProbe::new(observer),
+        )
+    }
+
+    /// Generates a random common reference string for a circuit using the
+    /// provided R1CS-to-QAP reduction, where the last
+    /// `num_committed_inputs` inputs of the circuit are given to the
+    /// verifier as a Pedersen commitment over the returned
+    /// [`CommitmentKey`] instead of in the clear.
+    ///
+    /// The commitment is binding but only hides the committed inputs if one
+    /// of them is uniformly random, e.g. a blinding input of the circuit.
+    #[inline]
+    pub fn generate_random_parameters_with_commitment<C>(
+        circuit: C,
+        rng: &mut impl Rng,
+        num_static_inputs: usize,
+        num_inputs: usize,
+        num_committed_inputs: usize,
+    ) -> R1CSResult<(ProvingKey<E>, CommitmentKey<E>)>
+    where
+        C: ConstraintSynthesizer<E::ScalarField>,
+    {
+        let mut pk = Self::generate_random_parameters_with_reduction(
+            circuit,
+            rng,
+            num_static_inputs,
+            num_inputs,
+        )?;
+
+        let variable = &mut pk.vk.gamma_abc_g1_variable;
+        if num_committed_inputs > variable.len() {
+            return Err(SynthesisError::MalformedVerifyingKey);
+        }
+        let bases = variable.split_off(variable.len() - num_committed_inputs);
+
+        let mut sigma = E::ScalarField::rand(rng);
+        let g2 = E::G2::rand(rng);
+        let sigma_bases = E::G1::normalize_batch(
+            &bases
+                .iter()
+                .map(|base| mul_secret(base.into_group(), sigma))
+                .collect::<Vec<_>>(),
+        );
+        let sigma_neg_g2 = mul_secret(-g2, sigma).into_affine();
+        sigma.zeroize();
+
+        let ck = CommitmentKey {
+            vk: CommitmentVerifyingKey {
+                g2: g2.into_affine(),
+                sigma_neg_g2,
+            },
+            bases,
+            sigma_bases,
+        };
+        Ok((pk, ck))
+    }
+
     /// Create parameters for a circuit, given some toxic waste, R1CS to QAP calculator and group generators
+    #[allow(clippy::too_many_arguments)]
     pub fn generate_parameters_with_qap<C>(
         circuit: C,
         alpha: E::ScalarField,
@@ -65,9 +237,190 @@ impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
     where
         C: ConstraintSynthesizer<E::ScalarField>,
     {
-        type D<F> = GeneralEvaluationDomain<F>;
+        Self::generate_parameters_with_qap_and_basis(
+            circuit,
+            alpha,
+            beta,
+            gamma,
+            delta,
+            num_static_inputs,
+            num_inputs,
+            g1_generator,
+            g2_generator,
+            KeyBasis::Monomial,
+            rng,
+        )
+    }
+
+    /// Create parameters for a circuit, given some toxic waste, R1CS to QAP
+    /// calculator, group generators and the basis of the `h_query`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn generate_parameters_with_qap_and_basis<C>(
+        circuit: C,
+        alpha: E::ScalarField,
+        beta: E::ScalarField,
+        gamma: E::ScalarField,
+        delta: E::ScalarField,
+        num_static_inputs: usize,
+        _num_inputs: usize,
+        g1_generator: E::G1,
+        g2_generator: E::G2,
+        h_basis: KeyBasis,
+        rng: &mut impl Rng,
+    ) -> R1CSResult<ProvingKey<E>>
+    where
+        C: ConstraintSynthesizer<E::ScalarField>,
+    {
+        Self::generate_parameters_with_probe(
+            circuit,
+            [alpha, beta, gamma, delta],
+            num_static_inputs,
+            g1_generator,
+            g2_generator,
+            h_basis,
+            rng,
+            &mut (),
+        )
+    }
 
+    /// Create parameters for a circuit, given the toxic waste `alpha`,
+    /// `beta`, `gamma` and `delta`, reporting its phases to `probe`.
+    #[allow(clippy::too_many_arguments)]
+    fn generate_parameters_with_probe<C>(
+        circuit: C,
+        [alpha, beta, gamma, delta]: [E::ScalarField; 4],
+        num_static_inputs: usize,
+        g1_generator: E::G1,
+        g2_generator: E::G2,
+        h_basis: KeyBasis,
+        rng: &mut impl Rng,
+        probe: &mut impl PhaseProbe<SetupPhase>,
+    ) -> R1CSResult<ProvingKey<E>>
+    where
+        C: ConstraintSynthesizer<E::ScalarField>,
+    {
         let setup_time = start_timer!(|| "Groth16::Generator");
+        let matrices = probe.measure(SetupPhase::Matrices, || {
+            let cs = Self::synthesize_for_setup(circuit)?;
+            cs.to_matrices().ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        let t = Self::sample_evaluation_point(&matrices, rng)?;
+        let pk = Self::generate_parameters_with_evaluation_point(
+            &matrices,
+            t,
+            alpha,
+            beta,
+            gamma,
+            delta,
+            num_static_inputs,
+            g1_generator,
+            g2_generator,
+            h_basis,
+            probe,
+        )?;
+        end_timer!(setup_time);
+
+        Ok(pk)
+    }
+
+    /// Generates a random common reference string for the constraint
+    /// system `matrices` using the provided R1CS-to-QAP reduction, without
+    /// synthesizing a circuit. The first `num_static_inputs` instance
+    /// variables, including the constant one, are static inputs.
+    pub fn generate_parameters_with_matrices(
+        matrices: &ConstraintMatrices<E::ScalarField>,
+        num_static_inputs: usize,
+        rng: &mut impl Rng,
+    ) -> R1CSResult<ProvingKey<E>> {
+        let alpha = E::ScalarField::rand(rng);
+        let beta = E::ScalarField::rand(rng);
+        let gamma = E::ScalarField::rand(rng);
+        let delta = E::ScalarField::rand(rng);
+
+        let g1_generator = E::G1::rand(rng);
+        let g2_generator = E::G2::rand(rng);
+
+        let setup_time = start_timer!(|| "Groth16::Generator");
+        let t = Self::sample_evaluation_point(matrices, rng)?;
+        let pk = Self::generate_parameters_with_evaluation_point(
+            matrices,
+            t,
+            alpha,
+            beta,
+            gamma,
+            delta,
+            num_static_inputs,
+            g1_generator,
+            g2_generator,
+            KeyBasis::Monomial,
+            &mut (),
+        )?;
+        end_timer!(setup_time);
+
+        Ok(pk)
+    }
+
+    /// Re-randomizes the `delta` of the proving key `pk` and of its verifying
+    /// key `vk`, returning fresh keys for the same circuit, e.g. to retire
+    /// a `delta` which may have been exposed without a new ceremony. Proofs
+    /// made with the old keys do not verify under the new ones.
+    ///
+    /// `delta` is multiplied by a random factor sampled via `rng`, and the
+    /// `h_query` and `l_query` divided by it, as in a contribution to the
+    /// second phase of a ceremony. The new `delta` stays secret as long as
+    /// the factor does, which is zeroized after use. The other secrets of
+    /// the setup are unchanged, so this does not help if they are exposed.
+    ///
+    /// Fails with [`SynthesisError::MalformedVerifyingKey`] if `vk` is not
+    /// the verifying key of `pk`.
+    pub fn rotate_delta(
+        pk: &ProvingKey<E>,
+        vk: &VerifyingKey<E>,
+        rng: &mut impl Rng,
+    ) -> R1CSResult<(ProvingKey<E>, VerifyingKey<E>)> {
+        if pk.vk != *vk {
+            return Err(SynthesisError::MalformedVerifyingKey);
+        }
+        let mut factor = E::ScalarField::rand(rng);
+        let rotated = Self::rotate_delta_by(pk, factor);
+        factor.zeroize();
+
+        let rotated = rotated?;
+        let vk = rotated.vk.clone();
+        Ok((rotated, vk))
+    }
+
+    /// Multiplies the `delta` of `pk` by `factor`, dividing its `h_query`
+    /// and `l_query` by it, see [`Self::rotate_delta`].
+    pub(crate) fn rotate_delta_by(
+        pk: &ProvingKey<E>,
+        factor: E::ScalarField,
+    ) -> R1CSResult<ProvingKey<E>> {
+        let mut factor_inverse = factor.inverse().ok_or(SynthesisError::UnexpectedIdentity)?;
+        let divide = |query: &[E::G1Affine]| {
+            let query = cfg_iter!(query)
+                .map(|g| mul_secret(g.into_group(), factor_inverse))
+                .collect::<Vec<_>>();
+            E::G1::normalize_batch(&query)
+        };
+
+        let mut rotated = pk.clone();
+        rotated.delta_g1 = mul_secret(pk.delta_g1.into_group(), factor).into_affine();
+        rotated.vk.delta_g2 = mul_secret(pk.vk.delta_g2.into_group(), factor).into_affine();
+        rotated.h_query = divide(&pk.h_query);
+        rotated.l_query = divide(&pk.l_query);
+        factor_inverse.zeroize();
+        Ok(rotated)
+    }
+
+    /// Synthesizes `circuit` in setup mode and inlines its linear
+    /// combinations.
+    pub(crate) fn synthesize_for_setup<C>(
+        circuit: C,
+    ) -> R1CSResult<ConstraintSystemRef<E::ScalarField>>
+    where
+        C: ConstraintSynthesizer<E::ScalarField>,
+    {
         let cs = ConstraintSystem::new_ref();
         cs.set_optimization_goal(OptimizationGoal::Constraints);
         cs.set_mode(SynthesisMode::Setup);
@@ -81,6 +434,50 @@ impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
         cs.finalize();
         end_timer!(lc_time);
 
+        Ok(cs)
+    }
+
+    /// Samples the secret evaluation point `t` for the QAP of `matrices`.
+    pub(crate) fn sample_evaluation_point(
+        matrices: &ConstraintMatrices<E::ScalarField>,
+        rng: &mut impl Rng,
+    ) -> R1CSResult<E::ScalarField> {
+        let domain_time = start_timer!(|| "Constructing evaluation domain");
+
+        let domain_size = matrices.num_constraints + matrices.num_instance_variables;
+        let domain = D::new(domain_size).ok_or(SynthesisError::PolynomialDegreeTooLarge)?;
+        let t = domain.sample_element_outside_domain(rng);
+
+        end_timer!(domain_time);
+
+        Ok(t)
+    }
+
+    /// Create parameters for the constraint system `matrices`, given some
+    /// toxic waste including the evaluation point `t`, reporting the phases
+    /// after [`SetupPhase::Matrices`] to `probe`.
+    ///
+    /// Fails with [`SynthesisError::MalformedVerifyingKey`] unless
+    /// `num_static_inputs` counts the constant one and at most all the
+    /// instance variables.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn generate_parameters_with_evaluation_point(
+        matrices: &ConstraintMatrices<E::ScalarField>,
+        t: E::ScalarField,
+        alpha: E::ScalarField,
+        beta: E::ScalarField,
+        gamma: E::ScalarField,
+        delta: E::ScalarField,
+        num_static_inputs: usize,
+        g1_generator: E::G1,
+        g2_generator: E::G2,
+        h_basis: KeyBasis,
+        probe: &mut impl PhaseProbe<SetupPhase>,
+    ) -> R1CSResult<ProvingKey<E>> {
+        if num_static_inputs == 0 || num_static_inputs > matrices.num_instance_variables {
+            return Err(SynthesisError::MalformedVerifyingKey);
+        }
+
         // Following is the mapping of symbols from the Groth16 paper to this implementation
         // l -> num_instance_variables
         // m -> qap_num_variables
@@ -90,30 +487,25 @@ impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
         // v_i(x) -> b
         // w_i(x) -> c
 
-        ///////////////////////////////////////////////////////////////////////////
-        let domain_time = start_timer!(|| "Constructing evaluation domain");
-
-        let domain_size = cs.num_constraints() + cs.num_instance_variables();
-        let domain = D::new(domain_size).ok_or(SynthesisError::PolynomialDegreeTooLarge)?;
-        let t = domain.sample_element_outside_domain(rng);
-
-        end_timer!(domain_time);
-        ///////////////////////////////////////////////////////////////////////////
-
         let reduction_time = start_timer!(|| "R1CS to QAP Instance Map with Evaluation");
-        let num_instance_variables = cs.num_instance_variables();
-        let (a, b, c, zt, qap_num_variables, m_raw) =
-            QAP::instance_map_with_evaluation::<E::ScalarField, D<E::ScalarField>>(cs, &t)?;
+        let num_instance_variables = matrices.num_instance_variables;
+        let (a, b, c, zt, _qap_num_variables, m_raw) = probe.measure(SetupPhase::Evaluation, || {
+            QAP::instance_map_with_evaluation_from_matrices::<E::ScalarField, D<E::ScalarField>>(
+                matrices, &t,
+            )
+        })?;
         end_timer!(reduction_time);
-
-        // Compute query densities
-        let non_zero_a: usize = cfg_into_iter!(0..qap_num_variables)
-            .map(|i| usize::from(!a[i].is_zero()))
-            .sum();
-
-        let non_zero_b: usize = cfg_into_iter!(0..qap_num_variables)
-            .map(|i| usize::from(!b[i].is_zero()))
-            .sum();
+        probe.plan(&[
+            (SetupPhase::BG2Query, 3 * b.len() as u64),
+            (SetupPhase::AQuery, a.len() as u64),
+            (SetupPhase::BG1Query, b.len() as u64),
+            (SetupPhase::HQuery, m_raw as u64),
+            (
+                SetupPhase::LQuery,
+                (a.len() - num_instance_variables) as u64,
+            ),
+            (SetupPhase::VerifyingKey, num_instance_variables as u64),
+        ]);
 
         let gamma_inverse = gamma.inverse().ok_or(SynthesisError::UnexpectedIdentity)?;
         let delta_inverse = delta.inverse().ok_or(SynthesisError::UnexpectedIdentity)?;
@@ -139,16 +531,9 @@ impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
         // Compute the B-query in G2
         let b_g2_time = start_timer!(|| format!("Calculate B G2 of size {}", b.len()));
         // We need to handle a vector of points and convert to affine
-        let b_g2_query = cfg_iter!(b)
-            .map(|b_i| g2_generator.mul(b_i).into_affine())
-            .collect::<Vec<_>>();
+        let b_g2_query = fixed_base_query(g2_generator, &b, SetupPhase::BG2Query, probe);
         end_timer!(b_g2_time);
 
-        // Compute G window table
-        let g1_window_time = start_timer!(|| "Compute G1 window table");
-        let num_scalars = non_zero_a + non_zero_b + qap_num_variables + m_raw + 1;
-        end_timer!(g1_window_time);
-
         // Generate the R1CS proving key
         let proving_key_time = start_timer!(|| "Generate the R1CS proving key");
 
@@ -160,34 +545,32 @@ impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
 
         // Compute the A-query
         let a_time = start_timer!(|| "Calculate A");
-        let a_query = cfg_iter!(a)
-            .map(|a_i| g1_generator.mul(a_i).into_affine())
-            .collect::<Vec<_>>();
+        let a_query = fixed_base_query(g1_generator, &a, SetupPhase::AQuery, probe);
         drop(a);
         end_timer!(a_time);
 
         // Compute the B-query in G1
         let b_g1_time = start_timer!(|| "Calculate B G1");
-        let b_g1_query = cfg_iter!(b)
-            .map(|b_i| g1_generator.mul(b_i).into_affine())
-            .collect::<Vec<_>>();
+        let b_g1_query = fixed_base_query(g1_generator, &b, SetupPhase::BG1Query, probe);
         drop(b);
         end_timer!(b_g1_time);
 
         // Compute the H-query
         let h_time = start_timer!(|| "Calculate H");
-        let h_scalars =
-            QAP::h_query_scalars::<_, D<E::ScalarField>>(m_raw - 1, t, zt, delta_inverse)?;
-        let h_query = cfg_iter!(h_scalars)
-            .map(|h_i| g1_generator.mul(h_i).into_affine())
-            .collect::<Vec<_>>();
+        let h_scalars = match h_basis {
+            KeyBasis::Monomial => {
+                QAP::h_query_scalars::<_, D<E::ScalarField>>(m_raw - 1, t, zt, delta_inverse)?
+            },
+            KeyBasis::Lagrange => {
+                QAP::h_query_lagrange_scalars::<_, D<E::ScalarField>>(m_raw, t, zt, delta_inverse)?
+            },
+        };
+        let h_query = fixed_base_query(g1_generator, &h_scalars, SetupPhase::HQuery, probe);
         end_timer!(h_time);
 
         // Compute the L-query
         let l_time = start_timer!(|| "Calculate L");
-        let l_query = cfg_iter!(l)
-            .map(|l_i| g1_generator.mul(l_i).into_affine())
-            .collect::<Vec<_>>();
+        let l_query = fixed_base_query(g1_generator, &l, SetupPhase::LQuery, probe);
         drop(l);
         end_timer!(l_time);
 
@@ -198,13 +581,9 @@ impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
         let gamma_g2 = g2_generator.mul(&gamma).into_affine();
 
         // Calculate gamma_abc_g1
-        let gamma_abc_g1_static = cfg_iter!(gamma_abc[..num_static_inputs])
-            .map(|g_a| g1_generator.mul(g_a).into_affine())
-            .collect::<Vec<_>>();
-            
-        let gamma_abc_g1_variable = cfg_iter!(gamma_abc[num_static_inputs..])
-            .map(|g_a| g1_generator.mul(g_a).into_affine())
-            .collect::<Vec<_>>();
+        let mut gamma_abc_g1_static =
+            fixed_base_query(g1_generator, &gamma_abc, SetupPhase::VerifyingKey, probe);
+        let gamma_abc_g1_variable = gamma_abc_g1_static.split_off(num_static_inputs);
 
         end_timer!(verifying_key_time);
 
@@ -218,8 +597,6 @@ impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
             gamma_abc_g1_variable,
         };
 
-        end_timer!(setup_time);
-
         Ok(ProvingKey {
             vk,
             beta_g1,
@@ -227,6 +604,7 @@ impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
             a_query,
             b_g1_query,
             b_g2_query,
+            h_basis,
             h_query,
             l_query,
         })
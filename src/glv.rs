@@ -0,0 +1,40 @@
+use crate::msm::BigInt;
+use ark_ec::{scalar_mul::glv::GLVConfig, AffineRepr, CurveConfig, CurveGroup};
+use ark_ff::PrimeField;
+use ark_std::vec::Vec;
+
+/// The bases and scalars of products split by [`split_scalars`].
+pub(crate) type SplitProducts<G> = (Vec<<G as CurveGroup>::Affine>, Vec<BigInt<G>>);
+
+/// Splits the products `scalars[i] * bases[i]` into twice as many products
+/// with scalars of about half the size, decomposing each scalar `k` into
+/// `k1 + lambda * k2` with the GLV endomorphism `phi(base) = lambda * base`
+/// of `P`. Negative halves are negated along with their bases.
+///
+/// The scalars are split with data-dependent branches, so the split is not
+/// constant time.
+pub(crate) fn split_scalars<G, P>(
+    bases: &[G::Affine],
+    scalars: &[G::ScalarField],
+) -> SplitProducts<G>
+where
+    G: CurveGroup,
+    P: GLVConfig<Curve = G> + CurveConfig<ScalarField = G::ScalarField>,
+{
+    let mut split_bases = Vec::with_capacity(2 * bases.len());
+    let mut split_scalars = Vec::with_capacity(2 * scalars.len());
+    for (base, scalar) in bases.iter().zip(scalars) {
+        let (k1, k2) = P::scalar_decomposition(scalar);
+        for (image, k) in [(*base, k1), (P::endomorphism(base), k2)] {
+            if k.into_bigint() > G::ScalarField::MODULUS_MINUS_ONE_DIV_TWO {
+                split_bases.push((-image.into_group()).into_affine());
+                split_scalars.push((-k).into_bigint());
+            } else {
+                split_bases.push(image);
+                split_scalars.push(k.into_bigint());
+            }
+        }
+    }
+
+    (split_bases, split_scalars)
+}
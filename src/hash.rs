@@ -0,0 +1,131 @@
+use ark_serialize::CanonicalSerialize;
+use sha2::{Digest, Sha256};
+
+/// Returns the SHA-256 hash of the compressed canonical serialization of
+/// `value`.
+pub(crate) fn hash_compressed(value: &impl CanonicalSerialize) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    value
+        .serialize_compressed(HashWriter(&mut hasher))
+        .expect("hashing cannot fail");
+    hasher.finalize().into()
+}
+
+/// Returns the SHA-256 hash of `data`.
+pub(crate) fn sha256(data: &[u8]) -> [u8; 32] {
+    Sha256::digest(data).into()
+}
+
+/// Adapts a hasher to `ark_serialize::Write`, to hash values without
+/// serializing them into a buffer first.
+struct HashWriter<'a>(&'a mut Sha256);
+
+impl ark_serialize::Write for HashWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> ark_std::io::Result<usize> {
+        self.0.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> ark_std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// The round constants of Keccak-f[1600].
+const KECCAK_ROUND_CONSTANTS: [u64; 24] = [
+    0x0000000000000001,
+    0x0000000000008082,
+    0x800000000000808a,
+    0x8000000080008000,
+    0x000000000000808b,
+    0x0000000080000001,
+    0x8000000080008081,
+    0x8000000000008009,
+    0x000000000000008a,
+    0x0000000000000088,
+    0x0000000080008009,
+    0x000000008000000a,
+    0x000000008000808b,
+    0x800000000000008b,
+    0x8000000000008089,
+    0x8000000000008003,
+    0x8000000000008002,
+    0x8000000000000080,
+    0x000000000000800a,
+    0x800000008000000a,
+    0x8000000080008081,
+    0x8000000000008080,
+    0x0000000080000001,
+    0x8000000080008008,
+];
+
+/// The rotation offsets of Keccak-f[1600], indexed by `x + 5 * y`.
+const KECCAK_ROTATIONS: [u32; 25] = [
+    0, 1, 62, 28, 27, 36, 44, 6, 55, 20, 3, 10, 43, 25, 39, 41, 45, 15, 21, 8, 18, 2, 61, 56, 14,
+];
+
+/// The Keccak-f[1600] permutation, on lanes indexed by `x + 5 * y`.
+fn keccak_f(state: &mut [u64; 25]) {
+    for round_constant in KECCAK_ROUND_CONSTANTS {
+        // θ
+        let mut c = [0u64; 5];
+        for x in 0..5 {
+            c[x] = state[x] ^ state[x + 5] ^ state[x + 10] ^ state[x + 15] ^ state[x + 20];
+        }
+        for x in 0..5 {
+            let d = c[(x + 4) % 5] ^ c[(x + 1) % 5].rotate_left(1);
+            for y in 0..5 {
+                state[x + 5 * y] ^= d;
+            }
+        }
+        // ρ and π
+        let mut b = [0u64; 25];
+        for x in 0..5 {
+            for y in 0..5 {
+                b[y + 5 * ((2 * x + 3 * y) % 5)] =
+                    state[x + 5 * y].rotate_left(KECCAK_ROTATIONS[x + 5 * y]);
+            }
+        }
+        // χ
+        for x in 0..5 {
+            for y in 0..5 {
+                state[x + 5 * y] =
+                    b[x + 5 * y] ^ (!b[(x + 1) % 5 + 5 * y] & b[(x + 2) % 5 + 5 * y]);
+            }
+        }
+        // ι
+        state[0] ^= round_constant;
+    }
+}
+
+/// Returns the Keccak-256 hash of `data`, as computed by the `keccak256`
+/// function of the EVM, which differs from SHA3-256 by its padding.
+pub(crate) fn keccak256(data: &[u8]) -> [u8; 32] {
+    const RATE: usize = 136;
+    let mut state = [0u64; 25];
+    let mut absorb = |block: &[u8]| {
+        for (lane, chunk) in state.iter_mut().zip(block.chunks(8)) {
+            let mut bytes = [0u8; 8];
+            bytes.copy_from_slice(chunk);
+            *lane ^= u64::from_le_bytes(bytes);
+        }
+        keccak_f(&mut state);
+    };
+
+    let mut blocks = data.chunks_exact(RATE);
+    for block in &mut blocks {
+        absorb(block);
+    }
+    let remainder = blocks.remainder();
+    let mut last = [0u8; RATE];
+    last[..remainder.len()].copy_from_slice(remainder);
+    last[remainder.len()] ^= 0x01;
+    last[RATE - 1] ^= 0x80;
+    absorb(&last);
+
+    let mut hash = [0u8; 32];
+    for (chunk, lane) in hash.chunks_mut(8).zip(&state) {
+        chunk.copy_from_slice(&lane.to_le_bytes());
+    }
+    hash
+}
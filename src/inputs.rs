@@ -0,0 +1,504 @@
+use ark_ec::pairing::Pairing;
+use ark_ff::{BigInteger, PrimeField};
+use ark_serialize::SerializationError;
+use ark_std::{vec, vec::Vec};
+use sha2::digest::{core_api::BlockSizeUser, Digest};
+
+/// The byte order of an encoded field element.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Endianness {
+    /// Most significant byte first, as used by Solidity and most JSON
+    /// frontends.
+    Big,
+    /// Least significant byte first, as used by arkworks internally.
+    Little,
+}
+
+/// A list of public inputs, with explicit fixed-width byte encodings.
+///
+/// Every element is encoded on [`PublicInputs::element_size`] bytes, which
+/// is 32 for the scalar fields of BN254 and BLS12-381. Decoding rejects
+/// encodings of values that are not reduced modulo the field order, so that
+/// each list of inputs has exactly one encoding.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PublicInputs<E: Pairing>(pub Vec<E::ScalarField>);
+
+impl<E: Pairing> PublicInputs<E> {
+    /// The number of bytes used to encode each element.
+    pub fn element_size() -> usize {
+        (E::ScalarField::MODULUS_BIT_SIZE as usize).div_ceil(8)
+    }
+
+    /// Encodes the inputs, each on [`Self::element_size`] bytes.
+    pub fn to_bytes(&self, endianness: Endianness) -> Vec<u8> {
+        let size = Self::element_size();
+        let mut bytes = Vec::with_capacity(self.0.len() * size);
+        for input in &self.0 {
+            let bigint = input.into_bigint();
+            match endianness {
+                Endianness::Big => {
+                    let be = bigint.to_bytes_be();
+                    bytes.extend_from_slice(&be[be.len() - size..]);
+                },
+                Endianness::Little => bytes.extend_from_slice(&bigint.to_bytes_le()[..size]),
+            }
+        }
+        bytes
+    }
+
+    /// Decodes inputs encoded by [`Self::to_bytes`].
+    pub fn from_bytes(bytes: &[u8], endianness: Endianness) -> Result<Self, SerializationError> {
+        let size = Self::element_size();
+        if !bytes.len().is_multiple_of(size) {
+            return Err(SerializationError::InvalidData);
+        }
+        bytes
+            .chunks(size)
+            .map(|chunk| {
+                let value = match endianness {
+                    Endianness::Big => E::ScalarField::from_be_bytes_mod_order(chunk),
+                    Endianness::Little => E::ScalarField::from_le_bytes_mod_order(chunk),
+                };
+                // Reject non-canonical encodings, which were reduced above.
+                let canonical = Self(ark_std::vec![value]).to_bytes(endianness);
+                if canonical == chunk {
+                    Ok(value)
+                } else {
+                    Err(SerializationError::InvalidData)
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map(Self)
+    }
+
+    /// Encodes the inputs as big-endian bytes.
+    pub fn to_bytes_be(&self) -> Vec<u8> {
+        self.to_bytes(Endianness::Big)
+    }
+
+    /// Encodes the inputs as little-endian bytes.
+    pub fn to_bytes_le(&self) -> Vec<u8> {
+        self.to_bytes(Endianness::Little)
+    }
+
+    /// Decodes inputs encoded by [`Self::to_bytes_be`].
+    pub fn from_bytes_be(bytes: &[u8]) -> Result<Self, SerializationError> {
+        Self::from_bytes(bytes, Endianness::Big)
+    }
+
+    /// Decodes inputs encoded by [`Self::to_bytes_le`].
+    pub fn from_bytes_le(bytes: &[u8]) -> Result<Self, SerializationError> {
+        Self::from_bytes(bytes, Endianness::Little)
+    }
+
+    /// Encodes each input as a 32-byte word, e.g. for BN254 or BLS12-381.
+    /// Fails if the elements of the scalar field of `E` do not fit this
+    /// layout.
+    pub fn to_words32(&self, endianness: Endianness) -> Result<Vec<[u8; 32]>, SerializationError> {
+        if Self::element_size() != 32 {
+            return Err(SerializationError::InvalidData);
+        }
+        Ok(self
+            .to_bytes(endianness)
+            .chunks(32)
+            .map(|chunk| {
+                let mut word = [0u8; 32];
+                word.copy_from_slice(chunk);
+                word
+            })
+            .collect())
+    }
+
+    /// Decodes inputs encoded by [`Self::to_words32`].
+    pub fn from_words32(
+        words: &[[u8; 32]],
+        endianness: Endianness,
+    ) -> Result<Self, SerializationError> {
+        if Self::element_size() != 32 {
+            return Err(SerializationError::InvalidData);
+        }
+        Self::from_bytes(&words.concat(), endianness)
+    }
+}
+
+impl<E: Pairing> From<Vec<E::ScalarField>> for PublicInputs<E> {
+    fn from(inputs: Vec<E::ScalarField>) -> Self {
+        Self(inputs)
+    }
+}
+
+impl<E: Pairing> From<PublicInputs<E>> for Vec<E::ScalarField> {
+    fn from(inputs: PublicInputs<E>) -> Self {
+        inputs.0
+    }
+}
+
+impl<E: Pairing> AsRef<[E::ScalarField]> for PublicInputs<E> {
+    fn as_ref(&self) -> &[E::ScalarField] {
+        &self.0
+    }
+}
+
+/// The number of bits of each limb of a hash split by [`split_hash`].
+pub const HASH_LIMB_BITS: usize = 128;
+
+/// Embeds an unsigned integer, encoded on `bytes` with `endianness`, into a
+/// field element. Fails if the integer does not fit in the capacity of `F`,
+/// i.e. `MODULUS_BIT_SIZE - 1` bits, so that no two integers embed to the
+/// same element, whichever their encoding width.
+pub fn embed_bytes<F: PrimeField>(
+    bytes: &[u8],
+    endianness: Endianness,
+) -> Result<F, SerializationError> {
+    let mut be = bytes.to_vec();
+    if endianness == Endianness::Little {
+        be.reverse();
+    }
+    let leading_zeros = be.iter().take_while(|byte| **byte == 0).count();
+    let significant = &be[leading_zeros..];
+    let num_bits = significant.first().map_or(0, |byte| {
+        8 * significant.len() - byte.leading_zeros() as usize
+    });
+    if num_bits > F::MODULUS_BIT_SIZE as usize - 1 {
+        return Err(SerializationError::InvalidData);
+    }
+    Ok(F::from_be_bytes_mod_order(significant))
+}
+
+/// Extracts an unsigned integer embedded by [`embed_bytes`], encoded on
+/// `num_bytes` bytes with `endianness`. Fails if `value` does not fit in
+/// `num_bytes` bytes.
+pub fn extract_bytes<F: PrimeField>(
+    value: F,
+    num_bytes: usize,
+    endianness: Endianness,
+) -> Result<Vec<u8>, SerializationError> {
+    let be = value.into_bigint().to_bytes_be();
+    let leading_zeros = be.iter().take_while(|byte| **byte == 0).count();
+    if be.len() - leading_zeros > num_bytes {
+        return Err(SerializationError::InvalidData);
+    }
+    let mut bytes = ark_std::vec![0u8; num_bytes.saturating_sub(be.len())];
+    bytes.extend_from_slice(&be[be.len().saturating_sub(num_bytes)..]);
+    if endianness == Endianness::Little {
+        bytes.reverse();
+    }
+    Ok(bytes)
+}
+
+/// Embeds a 64-bit integer into a field element.
+pub fn embed_u64<F: PrimeField>(value: u64) -> F {
+    F::from(value)
+}
+
+/// Extracts a 64-bit integer embedded by [`embed_u64`]. Fails if `value` is
+/// at least `2^64`, e.g. if a prover wrapped an application value around
+/// the modulus.
+pub fn extract_u64<F: PrimeField>(value: F) -> Result<u64, SerializationError> {
+    let bytes = extract_bytes(value, 8, Endianness::Big)?;
+    let mut word = [0u8; 8];
+    word.copy_from_slice(&bytes);
+    Ok(u64::from_be_bytes(word))
+}
+
+/// Splits a 256-bit hash, such as a Keccak-256 or SHA-256 digest, into two
+/// public inputs of [`HASH_LIMB_BITS`] bits: the integer of its first 16
+/// bytes, then the integer of its last 16 bytes, both big-endian. This is
+/// the convention of Solidity verifiers, which recompose the hash as
+/// `high << 128 | low`.
+///
+/// # Panics
+///
+/// Panics if `F` cannot hold 128-bit integers, which does not happen for
+/// the scalar fields of pairing-friendly curves.
+pub fn split_hash<F: PrimeField>(hash: &[u8; 32]) -> [F; 2] {
+    let limb = |bytes: &[u8]| {
+        embed_bytes::<F>(bytes, Endianness::Big).expect("the field holds 128-bit integers")
+    };
+    [limb(&hash[..16]), limb(&hash[16..])]
+}
+
+/// Recomposes a hash split by [`split_hash`] from its two public inputs.
+/// Fails if there are not exactly two inputs or if an input does not fit
+/// in [`HASH_LIMB_BITS`] bits, in which case it would alias another hash.
+pub fn recompose_hash<F: PrimeField>(limbs: &[F]) -> Result<[u8; 32], SerializationError> {
+    let [high, low] = limbs else {
+        return Err(SerializationError::InvalidData);
+    };
+    let mut hash = [0u8; 32];
+    hash[..16].copy_from_slice(&extract_bytes(*high, 16, Endianness::Big)?);
+    hash[16..].copy_from_slice(&extract_bytes(*low, 16, Endianness::Big)?);
+    Ok(hash)
+}
+
+/// Returns whether `limbs` are the public inputs of `hash` split by
+/// [`split_hash`], to check on the verifier side that the inputs of a proof
+/// commit to an expected hash.
+pub fn check_split_hash<F: PrimeField>(limbs: &[F], hash: &[u8; 32]) -> bool {
+    recompose_hash(limbs).is_ok_and(|recomposed| recomposed == *hash)
+}
+
+/// The security level in bits of [`derive_input_from_bytes`]: the derived
+/// inputs are within statistical distance `2^-128` of uniform.
+pub const DERIVATION_SECURITY_BITS: usize = 128;
+
+/// Derives a public input from arbitrary `bytes`, e.g. a pool identifier or
+/// a name, with the `hash_to_field` of RFC 9380 over the hash function `H`,
+/// e.g. SHA-256, and `expand_message_xmd`. Provers and verifiers derive the
+/// same input from the same bytes, so circuits can take it as a public
+/// input and verifiers recompute it instead of trusting the prover.
+///
+/// `domain` is the domain separation tag of RFC 9380, which should be
+/// unique to each use, e.g. `b"darklake-v1-pool-id"`, so that an input
+/// derived for one purpose is unrelated to the ones derived for others.
+/// The input is reduced from [`DERIVATION_SECURITY_BITS`] more bits than
+/// the modulus has, so it has no practical bias, unlike a reduction of a
+/// single digest.
+///
+/// # Panics
+///
+/// Panics if `F` is too large for `expand_message_xmd` over `H`, which
+/// does not happen for the scalar fields of pairing-friendly curves and
+/// 256-bit hash functions.
+pub fn derive_input_from_bytes<H, F>(domain: &[u8], bytes: &[u8]) -> F
+where
+    H: Digest + BlockSizeUser,
+    F: PrimeField,
+{
+    let len = (F::MODULUS_BIT_SIZE as usize + DERIVATION_SECURITY_BITS).div_ceil(8);
+    F::from_be_bytes_mod_order(&expand_message_xmd::<H>(bytes, domain, len))
+}
+
+/// The `expand_message_xmd` of RFC 9380, returning `len` uniform bytes
+/// derived from `message` and the domain separation tag `domain`.
+fn expand_message_xmd<H: Digest + BlockSizeUser>(
+    message: &[u8],
+    domain: &[u8],
+    len: usize,
+) -> Vec<u8> {
+    let oversize;
+    let domain = if domain.len() > 255 {
+        oversize = H::new()
+            .chain_update(b"H2C-OVERSIZE-DST-")
+            .chain_update(domain)
+            .finalize();
+        &oversize[..]
+    } else {
+        domain
+    };
+    let num_blocks = len.div_ceil(<H as Digest>::output_size());
+    assert!(
+        num_blocks <= 255 && len <= 0xffff,
+        "too many bytes requested from expand_message_xmd"
+    );
+    let domain_len = [domain.len() as u8];
+
+    let b_0 = H::new()
+        .chain_update(vec![0u8; H::block_size()])
+        .chain_update(message)
+        .chain_update((len as u16).to_be_bytes())
+        .chain_update([0u8])
+        .chain_update(domain)
+        .chain_update(domain_len)
+        .finalize();
+    let mut b_i = H::new()
+        .chain_update(&b_0)
+        .chain_update([1u8])
+        .chain_update(domain)
+        .chain_update(domain_len)
+        .finalize();
+    let mut uniform_bytes = Vec::with_capacity(num_blocks * b_i.len());
+    uniform_bytes.extend_from_slice(&b_i);
+    for i in 2..=num_blocks {
+        let mixed = b_0.iter().zip(&b_i).map(|(x, y)| x ^ y).collect::<Vec<_>>();
+        b_i = H::new()
+            .chain_update(mixed)
+            .chain_update([i as u8])
+            .chain_update(domain)
+            .chain_update(domain_len)
+            .finalize();
+        uniform_bytes.extend_from_slice(&b_i);
+    }
+    uniform_bytes.truncate(len);
+    uniform_bytes
+}
+
+/// The number of bits packed into each public input by [`InputPacker`]: the
+/// capacity of `F`, so that packed inputs are never reduced modulo the
+/// field order.
+pub fn packing_capacity<F: PrimeField>() -> usize {
+    F::MODULUS_BIT_SIZE as usize - 1
+}
+
+/// Packs small unsigned values, such as flags and enum discriminants, into
+/// as few public inputs as possible, each verified with one scalar
+/// multiplication instead of one per value.
+///
+/// Values are packed in order from the least significant bit of each input.
+/// A value never spans two inputs: a value that does not fit in the
+/// [`packing_capacity`] left in the current input starts the next one.
+/// [`InputUnpacker`] reads inputs packed with the same sequence of widths,
+/// and circuits enforce the same layout with
+/// [`crate::circuits::enforce_packed_inputs`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InputPacker<F: PrimeField> {
+    inputs: Vec<F>,
+    bits: Vec<bool>,
+}
+
+impl<F: PrimeField> Default for InputPacker<F> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<F: PrimeField> InputPacker<F> {
+    /// Creates a packer without values.
+    pub fn new() -> Self {
+        Self {
+            inputs: Vec::new(),
+            bits: Vec::new(),
+        }
+    }
+
+    /// Packs `value` on `num_bits` bits. Fails if `value` does not fit in
+    /// `num_bits` bits, or if `num_bits` is more than 64 or than the
+    /// [`packing_capacity`] of `F`.
+    pub fn push(&mut self, value: u64, num_bits: usize) -> Result<(), SerializationError> {
+        if num_bits > 64 || num_bits > packing_capacity::<F>() {
+            return Err(SerializationError::InvalidData);
+        }
+        if num_bits < 64 && value >> num_bits != 0 {
+            return Err(SerializationError::InvalidData);
+        }
+        if self.bits.len() + num_bits > packing_capacity::<F>() {
+            self.flush();
+        }
+        self.bits
+            .extend((0..num_bits).map(|i| (value >> i) & 1 == 1));
+        Ok(())
+    }
+
+    /// Packs a flag on one bit.
+    pub fn push_bool(&mut self, flag: bool) {
+        self.push(flag.into(), 1)
+            .expect("fields have a capacity of at least one bit");
+    }
+
+    /// Returns the packed public inputs.
+    pub fn finish(mut self) -> Vec<F> {
+        if !self.bits.is_empty() {
+            self.flush();
+        }
+        self.inputs
+    }
+
+    fn flush(&mut self) {
+        let bigint = F::BigInt::from_bits_le(&self.bits);
+        self.inputs
+            .push(F::from_bigint(bigint).expect("packed bits fit in the capacity"));
+        self.bits.clear();
+    }
+}
+
+/// Reads values packed by [`InputPacker`], on the verifier side.
+///
+/// Unpacking validates the inputs: [`InputUnpacker::finish`] fails unless
+/// every input was read entirely and the bits left unused by the layout are
+/// zero, so that each sequence of values has exactly one packed encoding.
+#[derive(Clone, Debug)]
+pub struct InputUnpacker<'a, F: PrimeField> {
+    inputs: &'a [F],
+    bits: Vec<bool>,
+    position: usize,
+}
+
+impl<'a, F: PrimeField> InputUnpacker<'a, F> {
+    /// Creates an unpacker reading `inputs` from the first one.
+    pub fn new(inputs: &'a [F]) -> Self {
+        Self {
+            inputs,
+            bits: Vec::new(),
+            position: 0,
+        }
+    }
+
+    /// Reads a value packed on `num_bits` bits. Fails if `num_bits` is
+    /// invalid, see [`InputPacker::push`], if the inputs are exhausted, or
+    /// if the bits of the previous input left unused are not zero.
+    pub fn next(&mut self, num_bits: usize) -> Result<u64, SerializationError> {
+        if num_bits > 64 || num_bits > packing_capacity::<F>() {
+            return Err(SerializationError::InvalidData);
+        }
+        if num_bits == 0 {
+            return Ok(0);
+        }
+        if self.bits.is_empty() || self.position + num_bits > packing_capacity::<F>() {
+            self.advance()?;
+        }
+        let value = self.bits[self.position..self.position + num_bits]
+            .iter()
+            .rev()
+            .fold(0u64, |value, bit| (value << 1) | u64::from(*bit));
+        self.position += num_bits;
+        Ok(value)
+    }
+
+    /// Reads a flag packed on one bit.
+    pub fn next_bool(&mut self) -> Result<bool, SerializationError> {
+        self.next(1).map(|bit| bit == 1)
+    }
+
+    /// Checks that all the inputs were read and that the bits of the last
+    /// input left unused are zero.
+    pub fn finish(self) -> Result<(), SerializationError> {
+        if !self.inputs.is_empty() || !self.unused_bits_are_zero() {
+            return Err(SerializationError::InvalidData);
+        }
+        Ok(())
+    }
+
+    fn advance(&mut self) -> Result<(), SerializationError> {
+        if !self.unused_bits_are_zero() {
+            return Err(SerializationError::InvalidData);
+        }
+        let (input, rest) = self
+            .inputs
+            .split_first()
+            .ok_or(SerializationError::InvalidData)?;
+        self.bits = input.into_bigint().to_bits_le();
+        self.bits.truncate(F::MODULUS_BIT_SIZE as usize);
+        self.inputs = rest;
+        self.position = 0;
+        Ok(())
+    }
+
+    fn unused_bits_are_zero(&self) -> bool {
+        self.bits[self.position.min(self.bits.len())..]
+            .iter()
+            .all(|bit| !bit)
+    }
+}
+
+/// Packs flags into public inputs with [`InputPacker`].
+pub fn pack_flags<F: PrimeField>(flags: &[bool]) -> Vec<F> {
+    let mut packer = InputPacker::new();
+    for flag in flags {
+        packer.push_bool(*flag);
+    }
+    packer.finish()
+}
+
+/// Unpacks `num_flags` flags packed by [`pack_flags`]. Fails if `inputs` is
+/// not the packing of exactly `num_flags` flags.
+pub fn unpack_flags<F: PrimeField>(
+    inputs: &[F],
+    num_flags: usize,
+) -> Result<Vec<bool>, SerializationError> {
+    let mut unpacker = InputUnpacker::new(inputs);
+    let flags = (0..num_flags)
+        .map(|_| unpacker.next_bool())
+        .collect::<Result<Vec<_>, _>>()?;
+    unpacker.finish()?;
+    Ok(flags)
+}
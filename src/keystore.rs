@@ -0,0 +1,258 @@
+use crate::{bundle::VkFingerprint, ProvingKey, VerifyingKey};
+use ark_ec::pairing::Pairing;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, SerializationError};
+use ark_std::{
+    fmt::{self, Write},
+    future::Future,
+    io,
+    string::String,
+    vec::Vec,
+};
+use sha2::{Digest, Sha256};
+#[cfg(feature = "std")]
+use std::{
+    path::PathBuf,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+/// The size of the checksum prefixed to stored keys.
+const CHECKSUM_SIZE: usize = 32;
+
+/// An error when storing or fetching a key.
+#[derive(Debug)]
+pub enum KeyStoreError {
+    /// No key is stored under the requested fingerprint.
+    NotFound,
+    /// The stored object does not match its checksum, e.g. because it was
+    /// truncated.
+    ChecksumMismatch,
+    /// The stored key does not have the requested fingerprint.
+    FingerprintMismatch,
+    /// The key could not be serialized or deserialized.
+    Serialization(SerializationError),
+    /// The storage backend failed.
+    Io(io::Error),
+}
+
+impl fmt::Display for KeyStoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeyStoreError::NotFound => write!(f, "no key stored under the fingerprint"),
+            KeyStoreError::ChecksumMismatch => write!(f, "stored key does not match its checksum"),
+            KeyStoreError::FingerprintMismatch => {
+                write!(f, "stored key does not match the fingerprint")
+            },
+            KeyStoreError::Serialization(e) => write!(f, "{}", e),
+            KeyStoreError::Io(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for KeyStoreError {}
+
+impl From<SerializationError> for KeyStoreError {
+    fn from(e: SerializationError) -> Self {
+        KeyStoreError::Serialization(e)
+    }
+}
+
+impl From<io::Error> for KeyStoreError {
+    fn from(e: io::Error) -> Self {
+        KeyStoreError::Io(e)
+    }
+}
+
+/// Returns the name of the object holding the key of `kind` with the given
+/// fingerprint, e.g. `vk/<hex fingerprint>`.
+fn object_name(kind: &str, fingerprint: &VkFingerprint) -> String {
+    let mut name = String::with_capacity(kind.len() + 1 + 2 * fingerprint.len());
+    name.push_str(kind);
+    name.push('/');
+    for byte in fingerprint {
+        write!(name, "{:02x}", byte).expect("writing to a string cannot fail");
+    }
+    name
+}
+
+/// Returns the compressed serialization of `value`, prefixed with its
+/// SHA-256 checksum.
+fn seal(value: &impl CanonicalSerialize) -> Result<Vec<u8>, KeyStoreError> {
+    let mut object = ark_std::vec![0u8; CHECKSUM_SIZE];
+    value.serialize_compressed(&mut object)?;
+    let checksum = Sha256::digest(&object[CHECKSUM_SIZE..]);
+    object[..CHECKSUM_SIZE].copy_from_slice(&checksum);
+    Ok(object)
+}
+
+/// Checks the checksum of an object written by [`seal`] and deserializes
+/// the value it holds.
+fn open<T: CanonicalDeserialize>(object: &[u8]) -> Result<T, KeyStoreError> {
+    if object.len() < CHECKSUM_SIZE {
+        return Err(KeyStoreError::ChecksumMismatch);
+    }
+    let (checksum, payload) = object.split_at(CHECKSUM_SIZE);
+    if Sha256::digest(payload).as_slice() != checksum {
+        return Err(KeyStoreError::ChecksumMismatch);
+    }
+    Ok(T::deserialize_compressed(payload)?)
+}
+
+/// A storage backend for keys, such as a local directory or an object
+/// store bucket, through which a fleet of provers and verifiers shares its
+/// keys.
+///
+/// Backends only store opaque objects by name. Keys are stored under the
+/// [fingerprint](VerifyingKey::fingerprint) of their verifying key,
+/// prefixed with a checksum which is checked when they are fetched, along
+/// with their fingerprint.
+pub trait KeyStore {
+    /// Returns the object stored under `name`, or `None` if there is none.
+    fn get(
+        &self,
+        name: &str,
+    ) -> impl Future<Output = Result<Option<Vec<u8>>, KeyStoreError>> + Send;
+
+    /// Stores `object` under `name`, replacing any object stored under it.
+    fn put(
+        &self,
+        name: &str,
+        object: Vec<u8>,
+    ) -> impl Future<Output = Result<(), KeyStoreError>> + Send;
+
+    /// Stores `vk`, returning the fingerprint it is stored under.
+    fn put_verifying_key<E: Pairing>(
+        &self,
+        vk: &VerifyingKey<E>,
+    ) -> impl Future<Output = Result<VkFingerprint, KeyStoreError>> + Send
+    where
+        Self: Sync,
+    {
+        let fingerprint = vk.fingerprint();
+        let object = seal(vk);
+        async move {
+            self.put(&object_name("vk", &fingerprint), object?).await?;
+            Ok(fingerprint)
+        }
+    }
+
+    /// Fetches the verifying key with the given fingerprint.
+    fn get_verifying_key<E: Pairing>(
+        &self,
+        fingerprint: &VkFingerprint,
+    ) -> impl Future<Output = Result<VerifyingKey<E>, KeyStoreError>> + Send
+    where
+        Self: Sync,
+    {
+        let fingerprint = *fingerprint;
+        async move {
+            let object = self.get(&object_name("vk", &fingerprint)).await?;
+            let vk: VerifyingKey<E> = open(&object.ok_or(KeyStoreError::NotFound)?)?;
+            if vk.fingerprint() != fingerprint {
+                return Err(KeyStoreError::FingerprintMismatch);
+            }
+            Ok(vk)
+        }
+    }
+
+    /// Stores `pk`, returning the fingerprint of its verifying key, which it
+    /// is stored under.
+    fn put_proving_key<E: Pairing>(
+        &self,
+        pk: &ProvingKey<E>,
+    ) -> impl Future<Output = Result<VkFingerprint, KeyStoreError>> + Send
+    where
+        Self: Sync,
+    {
+        let fingerprint = pk.vk.fingerprint();
+        let object = seal(pk);
+        async move {
+            self.put(&object_name("pk", &fingerprint), object?).await?;
+            Ok(fingerprint)
+        }
+    }
+
+    /// Fetches the proving key whose verifying key has the given
+    /// fingerprint. The proving key is validated while deserialized, which
+    /// is slow for large circuits.
+    fn get_proving_key<E: Pairing>(
+        &self,
+        fingerprint: &VkFingerprint,
+    ) -> impl Future<Output = Result<ProvingKey<E>, KeyStoreError>> + Send
+    where
+        Self: Sync,
+    {
+        let fingerprint = *fingerprint;
+        async move {
+            let object = self.get(&object_name("pk", &fingerprint)).await?;
+            let pk: ProvingKey<E> = open(&object.ok_or(KeyStoreError::NotFound)?)?;
+            if pk.vk.fingerprint() != fingerprint {
+                return Err(KeyStoreError::FingerprintMismatch);
+            }
+            Ok(pk)
+        }
+    }
+}
+
+/// A [`KeyStore`] in a directory of the local file system.
+///
+/// Files are read and written synchronously when the futures are polled,
+/// blocking the executor. Objects are written to a temporary file first and
+/// then renamed, so that concurrent readers never see partial keys.
+#[cfg(feature = "std")]
+#[derive(Clone, Debug)]
+pub struct LocalKeyStore {
+    root: PathBuf,
+}
+
+/// The number of writes started by the [`LocalKeyStore`]s of the process,
+/// making the names of their temporary files unique.
+#[cfg(feature = "std")]
+static WRITES: AtomicU64 = AtomicU64::new(0);
+
+#[cfg(feature = "std")]
+impl LocalKeyStore {
+    /// Creates a store in the directory `root`, which is created on the
+    /// first write if missing.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+}
+
+#[cfg(feature = "std")]
+impl KeyStore for LocalKeyStore {
+    fn get(
+        &self,
+        name: &str,
+    ) -> impl Future<Output = Result<Option<Vec<u8>>, KeyStoreError>> + Send {
+        let path = self.root.join(name);
+        async move {
+            match std::fs::read(path) {
+                Ok(object) => Ok(Some(object)),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+                Err(e) => Err(e.into()),
+            }
+        }
+    }
+
+    fn put(
+        &self,
+        name: &str,
+        object: Vec<u8>,
+    ) -> impl Future<Output = Result<(), KeyStoreError>> + Send {
+        let path = self.root.join(name);
+        async move {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            let mut temporary = path.clone().into_os_string();
+            // Concurrent writes of the same name, from this process or
+            // another, each rename their own file.
+            let write = WRITES.fetch_add(1, Ordering::Relaxed);
+            temporary.push(format!(".{}.{}.tmp", std::process::id(), write));
+            std::fs::write(&temporary, object)?;
+            std::fs::rename(&temporary, &path)?;
+            Ok(())
+        }
+    }
+}
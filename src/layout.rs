@@ -0,0 +1,160 @@
+use crate::{KeyBasis, LegacyVerifyingKey, Proof, ProvingKey, VerifyingKey};
+use ark_ec::{pairing::Pairing, AffineRepr};
+use ark_serialize::{CanonicalDeserialize, Compress, Read, SerializationError, Validate};
+use ark_std::{convert::Infallible, vec::Vec};
+
+/// A component of a serialized key or proof.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Item {
+    G1,
+    G2,
+    G1Vec,
+    G2Vec,
+    /// The [`KeyBasis`] tag ending a proving key, which keys serialized
+    /// before it was introduced omit.
+    TrailingBasis,
+}
+
+/// A source of the components of serialized keys and proofs, from which
+/// [`read_proof`] and the other functions of this module assemble them in
+/// the order in which they are serialized. This order is only written down
+/// here, so that the loaders, the bounded and parallel deserializers and
+/// [`layout`] cannot disagree on it.
+pub(crate) trait ComponentReader<E: Pairing> {
+    type Error;
+
+    fn g1(&mut self) -> Result<E::G1Affine, Self::Error>;
+
+    fn g2(&mut self) -> Result<E::G2Affine, Self::Error>;
+
+    /// Reads a vector of points, which are the bases of the public inputs
+    /// if `inputs`.
+    fn g1_vec(&mut self, inputs: bool) -> Result<Vec<E::G1Affine>, Self::Error>;
+
+    fn g2_vec(&mut self) -> Result<Vec<E::G2Affine>, Self::Error>;
+
+    fn trailing_basis(&mut self) -> Result<KeyBasis, Self::Error>;
+}
+
+pub(crate) fn read_proof<E: Pairing, R: ComponentReader<E>>(
+    reader: &mut R,
+) -> Result<Proof<E>, R::Error> {
+    Ok(Proof {
+        a: reader.g1()?,
+        b: reader.g2()?,
+        c: reader.g1()?,
+    })
+}
+
+pub(crate) fn read_verifying_key<E: Pairing, R: ComponentReader<E>>(
+    reader: &mut R,
+) -> Result<VerifyingKey<E>, R::Error> {
+    Ok(VerifyingKey {
+        alpha_g1: reader.g1()?,
+        beta_g2: reader.g2()?,
+        gamma_g2: reader.g2()?,
+        delta_g2: reader.g2()?,
+        gamma_abc_g1_static: reader.g1_vec(true)?,
+        gamma_abc_g1_variable: reader.g1_vec(true)?,
+    })
+}
+
+pub(crate) fn read_legacy_verifying_key<E: Pairing, R: ComponentReader<E>>(
+    reader: &mut R,
+) -> Result<LegacyVerifyingKey<E>, R::Error> {
+    Ok(LegacyVerifyingKey {
+        alpha_g1: reader.g1()?,
+        beta_g2: reader.g2()?,
+        gamma_g2: reader.g2()?,
+        delta_g2: reader.g2()?,
+        gamma_abc_g1: reader.g1_vec(true)?,
+    })
+}
+
+pub(crate) fn read_proving_key<E: Pairing, R: ComponentReader<E>>(
+    reader: &mut R,
+) -> Result<ProvingKey<E>, R::Error> {
+    Ok(ProvingKey {
+        vk: read_verifying_key(reader)?,
+        beta_g1: reader.g1()?,
+        delta_g1: reader.g1()?,
+        a_query: reader.g1_vec(false)?,
+        b_g1_query: reader.g1_vec(false)?,
+        b_g2_query: reader.g2_vec()?,
+        h_query: reader.g1_vec(false)?,
+        l_query: reader.g1_vec(false)?,
+        h_basis: reader.trailing_basis()?,
+    })
+}
+
+/// Records the components read, returning placeholder values.
+pub(crate) struct LayoutRecorder(Vec<Item>);
+
+impl<E: Pairing> ComponentReader<E> for LayoutRecorder {
+    type Error = Infallible;
+
+    fn g1(&mut self) -> Result<E::G1Affine, Infallible> {
+        self.0.push(Item::G1);
+        Ok(E::G1Affine::zero())
+    }
+
+    fn g2(&mut self) -> Result<E::G2Affine, Infallible> {
+        self.0.push(Item::G2);
+        Ok(E::G2Affine::zero())
+    }
+
+    fn g1_vec(&mut self, _inputs: bool) -> Result<Vec<E::G1Affine>, Infallible> {
+        self.0.push(Item::G1Vec);
+        Ok(Vec::new())
+    }
+
+    fn g2_vec(&mut self) -> Result<Vec<E::G2Affine>, Infallible> {
+        self.0.push(Item::G2Vec);
+        Ok(Vec::new())
+    }
+
+    fn trailing_basis(&mut self) -> Result<KeyBasis, Infallible> {
+        self.0.push(Item::TrailingBasis);
+        Ok(KeyBasis::Monomial)
+    }
+}
+
+/// Returns the components read by `read`, e.g. `read_proof::<E, _>`.
+pub(crate) fn layout<T>(
+    read: impl FnOnce(&mut LayoutRecorder) -> Result<T, Infallible>,
+) -> Vec<Item> {
+    let mut recorder = LayoutRecorder(Vec::new());
+    let _ = read(&mut recorder);
+    recorder.0
+}
+
+/// Reads components with [`CanonicalDeserialize`].
+pub(crate) struct CanonicalReader<R> {
+    pub reader: R,
+    pub compress: Compress,
+    pub validate: Validate,
+}
+
+impl<E: Pairing, R: Read> ComponentReader<E> for CanonicalReader<R> {
+    type Error = SerializationError;
+
+    fn g1(&mut self) -> Result<E::G1Affine, SerializationError> {
+        E::G1Affine::deserialize_with_mode(&mut self.reader, self.compress, self.validate)
+    }
+
+    fn g2(&mut self) -> Result<E::G2Affine, SerializationError> {
+        E::G2Affine::deserialize_with_mode(&mut self.reader, self.compress, self.validate)
+    }
+
+    fn g1_vec(&mut self, _inputs: bool) -> Result<Vec<E::G1Affine>, SerializationError> {
+        Vec::deserialize_with_mode(&mut self.reader, self.compress, self.validate)
+    }
+
+    fn g2_vec(&mut self) -> Result<Vec<E::G2Affine>, SerializationError> {
+        Vec::deserialize_with_mode(&mut self.reader, self.compress, self.validate)
+    }
+
+    fn trailing_basis(&mut self) -> Result<KeyBasis, SerializationError> {
+        KeyBasis::deserialize_trailing(&mut self.reader)
+    }
+}
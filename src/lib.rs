@@ -34,6 +34,186 @@ pub mod prover;
 /// Verify proofs for the Groth16 zkSNARK construction.
 pub mod verifier;
 
+/// Multi-scalar multiplication backends used by the prover.
+pub mod msm;
+
+/// Check witnesses against the constraints of a circuit before proving.
+pub mod witness;
+
+/// Bind proofs, keys and public inputs into Fiat–Shamir transcripts.
+pub mod transcript;
+
+/// Fixed-width byte encodings of public inputs, embeddings of integers and
+/// hashes into them, packings of flags, and inputs derived from bytes by
+/// hashing to the field.
+pub mod inputs;
+
+/// Describe the public inputs of verifying keys to frontends.
+pub mod abi;
+
+/// Encode typed statements as public inputs.
+pub mod statement;
+
+/// Load serialized keys and proofs, detecting the curve they were
+/// serialized with.
+pub mod loader;
+
+/// Canonical text encodings of proofs and inputs for logs and debugging.
+pub mod text;
+
+/// Verify and create proofs over any supported curve, chosen at runtime.
+#[cfg(feature = "any-curve")]
+pub mod any_curve;
+
+/// Cache prepared proof components across verifications.
+pub mod preparer;
+
+/// Choose what prepared verifying keys precompute, trading memory for
+/// latency.
+pub mod profile;
+
+/// Run the stages of the prover on separately sized thread pools.
+#[cfg(feature = "parallel")]
+pub mod pipeline;
+
+/// Reusable buffers for the prover's temporaries.
+#[cfg(feature = "arena")]
+pub mod arena;
+
+/// Compact proving key artifacts and their expansion.
+pub mod artifact;
+
+/// Attest the contributions to the setup of keys, for third parties to
+/// audit it.
+pub mod attestation;
+
+/// Export the constraint system of a setup for audits.
+pub mod export;
+
+/// Ready-made circuits for common statements, for tests and demos.
+pub mod circuits;
+
+/// Synthetic circuits of configurable size for load tests and benchmarks.
+pub mod testing;
+
+/// A reference flow with static and variable inputs: a swap in a constant
+/// product pool.
+pub mod reference;
+
+/// Cache prepared public inputs across verifications, and share cached
+/// verifiers between threads.
+pub mod cache;
+
+/// Summaries of proving keys for capacity planning.
+pub mod report;
+
+/// Self-describing envelopes for proofs and their public inputs.
+pub mod bundle;
+
+/// Accumulate the verifying keys accepted by verifiers of many circuits.
+pub mod accumulator;
+
+/// Verify streams of serialized proofs.
+#[cfg(feature = "std")]
+pub mod stream;
+
+/// Verify proofs against keys with input counts fixed at compile time.
+pub mod fixed;
+
+/// Verify proofs of multiple tenants on a pool of worker threads.
+#[cfg(feature = "std")]
+pub mod verifier_pool;
+
+/// Report the progress of the prover and of key generation.
+#[cfg(feature = "std")]
+pub mod progress;
+
+/// Warm provers up and report the capabilities of their hosts.
+#[cfg(feature = "std")]
+pub mod context;
+
+/// Accept proofs under the verifying keys of consecutive epochs.
+pub mod rotation;
+
+/// Randomness sources for the blinding factors of proofs.
+pub mod rng;
+
+/// Diagnose proofs and public inputs rejected by the verifier.
+pub mod diagnose;
+
+/// Check the invariants of the arithmetic of the verifier and record its
+/// intermediate values, to certify new deployments.
+#[cfg(feature = "verifier-audit")]
+pub mod verifier_audit;
+
+/// Audit which witness variables influence the public inputs of a circuit.
+pub mod audit;
+
+/// Share proving and verifying keys through storage backends.
+pub mod keystore;
+
+/// Export and verify keys in the zkey format of snarkjs.
+pub mod zkey;
+
+/// Read and write the JSON files of keys, proofs and public inputs of
+/// snarkjs.
+pub mod snarkjs;
+
+/// Encode proofs and verifying keys, and prepare `G2` elements off-chain, for
+/// on-chain verifiers and registries.
+pub mod onchain;
+
+/// Bind proofs to the version of their circuit.
+pub mod versioned;
+
+/// Keys dividing the bases of static and variable inputs by distinct
+/// gammas, to rotate them independently.
+pub mod separated;
+
+/// The public API of the upstream `ark-groth16` crate, for migrating code
+/// written against it.
+#[cfg(feature = "compat")]
+pub mod compat;
+
+/// Share the blinding of proofs between several parties.
+pub mod threshold;
+
+/// Delegate the MSMs of proofs to compute providers without revealing the
+/// witness.
+pub mod delegate;
+
+/// Checkpoint long-running proofs to resume them after an interruption.
+pub mod checkpoint;
+
+/// Spool proving jobs and proofs durably for prover daemons.
+#[cfg(feature = "std")]
+pub mod spool;
+
+/// Generate standalone verifiers for a fixed verifying key.
+pub mod codegen;
+
+/// Prove that two proofs share the value of a hidden input.
+pub mod link;
+
+/// Generate proofs, keys and corrupted serializations from fuzzer input, and
+/// check the round trip of small circuits through the whole pipeline.
+#[cfg(feature = "fuzz")]
+pub mod fuzz;
+
+/// Verify proofs inside zkVM guests, with a hook for accelerated pairings.
+/// Guests should disable the default features, which enable threads.
+#[cfg(feature = "zkvm")]
+pub mod zkvm;
+
+mod hash;
+
+mod layout;
+
+/// Scalar multiplications with the GLV endomorphism of curves implementing
+/// `GLVConfig`.
+#[cfg(feature = "glv")]
+mod glv;
+
 /// Constraints for the Groth16 verifier.
 #[cfg(feature = "r1cs")]
 pub mod constraints;
@@ -46,6 +226,7 @@ pub use self::verifier::*;
 
 use ark_crypto_primitives::snark::*;
 use ark_ec::pairing::Pairing;
+use ark_ff::PrimeField;
 use ark_relations::r1cs::{ConstraintSynthesizer, SynthesisError};
 use ark_std::rand::RngCore;
 use ark_std::{marker::PhantomData, vec::Vec};
@@ -68,10 +249,8 @@ impl<E: Pairing, QAP: R1CSToQAP> SNARK<E::ScalarField> for Groth16<E, QAP> {
         rng: &mut R,
     ) -> Result<(Self::ProvingKey, Self::VerifyingKey), Self::Error> {
         let pk = Self::generate_random_parameters_with_reduction(
-            circuit, 
-            rng,
-            1,  // num_static_inputs - typically 1 for the "one" input
-            0,  // num_inputs - or determine dynamically if needed
+            circuit, rng, 1, // num_static_inputs - typically 1 for the "one" input
+            0, // num_inputs - or determine dynamically if needed
         )?;
         let vk = pk.vk.clone();
 
@@ -97,13 +276,106 @@ impl<E: Pairing, QAP: R1CSToQAP> SNARK<E::ScalarField> for Groth16<E, QAP> {
         x: &[E::ScalarField],
         proof: &Self::Proof,
     ) -> Result<bool, Self::Error> {
-        // Split inputs into static and variable parts
-        // Assuming the first element is static (like "one") and the rest are variable
-        let static_inputs = &x[..1];  // First element is static
-        let variable_inputs = &x[1..]; // Rest are variable
-        
-        Ok(Self::verify_with_variables(&circuit_pvk, proof, static_inputs, variable_inputs)?)
+        // Split inputs into static and variable parts. The first static
+        // query element is the constant term and has no matching input.
+        let num_static_inputs = circuit_pvk.num_static_inputs();
+        if x.len() < num_static_inputs {
+            return Err(SynthesisError::MalformedVerifyingKey);
+        }
+        let (static_inputs, variable_inputs) = x.split_at(num_static_inputs);
+
+        Ok(Self::verify_with_variables(
+            circuit_pvk,
+            proof,
+            static_inputs,
+            variable_inputs,
+        )?)
     }
 }
 
 impl<E: Pairing, QAP: R1CSToQAP> CircuitSpecificSetupSNARK<E::ScalarField> for Groth16<E, QAP> {}
+
+/// A [`SNARK`] whose public inputs are partitioned into static inputs,
+/// followed by variable inputs, for generic code such as aggregators and
+/// recursion frameworks verifying proofs with
+/// [`Groth16::verify_with_variables`] without knowing the concrete SNARK.
+pub trait SNARKWithPartitionedInputs<F: PrimeField>: SNARK<F> {
+    /// Generates keys for `circuit`, whose first `num_static_inputs` public
+    /// inputs are static inputs and the others variable inputs.
+    fn circuit_specific_setup_with_partition<C: ConstraintSynthesizer<F>, R: RngCore>(
+        circuit: C,
+        num_static_inputs: usize,
+        rng: &mut R,
+    ) -> Result<(Self::ProvingKey, Self::VerifyingKey), Self::Error>;
+
+    /// Returns the number of static inputs expected by `circuit_pvk`.
+    fn num_static_inputs(circuit_pvk: &Self::ProcessedVerifyingKey) -> usize;
+
+    /// Returns the number of variable inputs expected by `circuit_pvk`.
+    fn num_variable_inputs(circuit_pvk: &Self::ProcessedVerifyingKey) -> usize;
+
+    /// Checks that `proof` is valid for `static_inputs` and
+    /// `variable_inputs` under `circuit_pvk`.
+    fn verify_with_partitioned_inputs(
+        circuit_pvk: &Self::ProcessedVerifyingKey,
+        static_inputs: &[F],
+        variable_inputs: &[F],
+        proof: &Self::Proof,
+    ) -> Result<bool, Self::Error>;
+
+    /// Checks that `proof` is valid for `static_inputs` and
+    /// `variable_inputs` under `circuit_vk`, processing it first.
+    fn verify_partitioned(
+        circuit_vk: &Self::VerifyingKey,
+        static_inputs: &[F],
+        variable_inputs: &[F],
+        proof: &Self::Proof,
+    ) -> Result<bool, Self::Error> {
+        let circuit_pvk = Self::process_vk(circuit_vk)?;
+        Self::verify_with_partitioned_inputs(&circuit_pvk, static_inputs, variable_inputs, proof)
+    }
+}
+
+impl<E: Pairing, QAP: R1CSToQAP> SNARKWithPartitionedInputs<E::ScalarField> for Groth16<E, QAP> {
+    fn circuit_specific_setup_with_partition<
+        C: ConstraintSynthesizer<E::ScalarField>,
+        R: RngCore,
+    >(
+        circuit: C,
+        num_static_inputs: usize,
+        rng: &mut R,
+    ) -> Result<(Self::ProvingKey, Self::VerifyingKey), Self::Error> {
+        // The constant one precedes the static inputs.
+        let pk = Self::generate_random_parameters_with_reduction(
+            circuit,
+            rng,
+            num_static_inputs + 1,
+            0,
+        )?;
+        let vk = pk.vk.clone();
+
+        Ok((pk, vk))
+    }
+
+    fn num_static_inputs(circuit_pvk: &Self::ProcessedVerifyingKey) -> usize {
+        circuit_pvk.num_static_inputs()
+    }
+
+    fn num_variable_inputs(circuit_pvk: &Self::ProcessedVerifyingKey) -> usize {
+        circuit_pvk.num_variable_inputs()
+    }
+
+    fn verify_with_partitioned_inputs(
+        circuit_pvk: &Self::ProcessedVerifyingKey,
+        static_inputs: &[E::ScalarField],
+        variable_inputs: &[E::ScalarField],
+        proof: &Self::Proof,
+    ) -> Result<bool, Self::Error> {
+        Ok(Self::verify_with_variables(
+            circuit_pvk,
+            proof,
+            static_inputs,
+            variable_inputs,
+        )?)
+    }
+}
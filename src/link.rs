@@ -0,0 +1,217 @@
+use crate::{r1cs_to_qap::R1CSToQAP, Groth16, PreparedVerifyingKey, Proof};
+use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup};
+use ark_ff::{PrimeField, UniformRand, Zero};
+use ark_relations::r1cs::{Result as R1CSResult, SynthesisError};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::{rand::Rng, vec::Vec};
+use sha2::{Digest, Sha256};
+
+/// Domain separator of the challenge of an [`InputEqualityProof`].
+pub const INPUT_EQUALITY_LABEL: &[u8] = b"darklake-groth16/input-equality";
+
+/// The public inputs of a proof, one of which is hidden from the verifier
+/// in the prepared inputs of an [`InputEqualityProof`].
+#[derive(Clone, Copy, Debug)]
+pub struct PartiallyHiddenInputs<'a, F> {
+    /// The static inputs.
+    pub static_inputs: &'a [F],
+    /// The variable inputs.
+    pub variable_inputs: &'a [F],
+    /// The position of the hidden input among the static inputs followed by
+    /// the variable inputs. The verifier ignores its value.
+    pub position: usize,
+}
+
+impl<F: PrimeField> PartiallyHiddenInputs<'_, F> {
+    /// Returns the hidden input.
+    fn hidden(&self) -> R1CSResult<F> {
+        self.static_inputs
+            .iter()
+            .chain(self.variable_inputs)
+            .nth(self.position)
+            .copied()
+            .ok_or(SynthesisError::MalformedVerifyingKey)
+    }
+
+    /// Returns the static and variable inputs with the hidden input set to
+    /// zero.
+    fn without_hidden(&self) -> (Vec<F>, Vec<F>) {
+        let mut static_inputs = self.static_inputs.to_vec();
+        let mut variable_inputs = self.variable_inputs.to_vec();
+        match self.position.checked_sub(static_inputs.len()) {
+            None => static_inputs[self.position] = F::zero(),
+            Some(position) => variable_inputs[position] = F::zero(),
+        }
+        (static_inputs, variable_inputs)
+    }
+}
+
+/// A proof that two Groth16 proofs, possibly under different verifying
+/// keys, were made for the same value of a hidden input, e.g. the id of an
+/// order in an order proof and in its settlement proof.
+///
+/// The proofs are verified against prepared inputs computed by the prover,
+/// which hide their input as `x` times the base of its position in each
+/// key. This proves knowledge of `x` with the same discrete logarithm in
+/// both, with a Chaum-Pedersen proof made non-interactive with SHA-256.
+/// Since the prepared inputs determine `x`, it stays hidden only if it is
+/// hard to guess.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct InputEqualityProof<E: Pairing> {
+    /// The prepared inputs of the first proof.
+    pub prepared_inputs_a: E::G1Affine,
+    /// The prepared inputs of the second proof.
+    pub prepared_inputs_b: E::G1Affine,
+    /// The commitment to the nonce in the basis of the first key.
+    pub commitment_a: E::G1Affine,
+    /// The commitment to the nonce in the basis of the second key.
+    pub commitment_b: E::G1Affine,
+    /// The response to the challenge.
+    pub response: E::ScalarField,
+}
+
+/// Returns the base of the input at `position` in `pvk`.
+fn input_base<E: Pairing>(
+    pvk: &PreparedVerifyingKey<E>,
+    position: usize,
+) -> R1CSResult<E::G1Affine> {
+    pvk.vk
+        .gamma_abc_g1_static
+        .iter()
+        .skip(1)
+        .chain(&pvk.vk.gamma_abc_g1_variable)
+        .nth(position)
+        .copied()
+        .ok_or(SynthesisError::MalformedVerifyingKey)
+}
+
+/// Returns the challenge of the statement `hidden_a = x base_a` and
+/// `hidden_b = x base_b` with the commitments.
+fn challenge<E: Pairing>(points: [E::G1Affine; 6]) -> E::ScalarField {
+    let mut bytes = INPUT_EQUALITY_LABEL.to_vec();
+    points
+        .serialize_compressed(&mut bytes)
+        .expect("serializing into a vector cannot fail");
+    E::ScalarField::from_le_bytes_mod_order(&Sha256::digest(&bytes))
+}
+
+impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
+    /// Returns the part of the prepared inputs of `inputs` for `pvk` hiding
+    /// their hidden input, given the prepared inputs.
+    fn hidden_part(
+        pvk: &PreparedVerifyingKey<E>,
+        inputs: &PartiallyHiddenInputs<'_, E::ScalarField>,
+        prepared_inputs: E::G1,
+    ) -> R1CSResult<E::G1> {
+        inputs.hidden()?;
+        let (static_inputs, variable_inputs) = inputs.without_hidden();
+        let public = Self::prepare_inputs_with_variables(pvk, &static_inputs, &variable_inputs)?;
+        Ok(prepared_inputs - public)
+    }
+
+    /// Proves that the hidden inputs of `inputs_a` under `pvk_a` and of
+    /// `inputs_b` under `pvk_b` are equal. This method samples the nonce of
+    /// the proof via `rng`.
+    ///
+    /// Fails with [`SynthesisError::Unsatisfiable`] if they differ, and with
+    /// [`SynthesisError::MalformedVerifyingKey`] if the inputs do not match
+    /// their key.
+    pub fn prove_input_equality(
+        pvk_a: &PreparedVerifyingKey<E>,
+        inputs_a: &PartiallyHiddenInputs<'_, E::ScalarField>,
+        pvk_b: &PreparedVerifyingKey<E>,
+        inputs_b: &PartiallyHiddenInputs<'_, E::ScalarField>,
+        rng: &mut impl Rng,
+    ) -> R1CSResult<InputEqualityProof<E>> {
+        let x = inputs_a.hidden()?;
+        if inputs_b.hidden()? != x {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        let base_a = input_base(pvk_a, inputs_a.position)?;
+        let base_b = input_base(pvk_b, inputs_b.position)?;
+        let prepared_inputs_a = Self::prepare_inputs_with_variables(
+            pvk_a,
+            inputs_a.static_inputs,
+            inputs_a.variable_inputs,
+        )?;
+        let prepared_inputs_b = Self::prepare_inputs_with_variables(
+            pvk_b,
+            inputs_b.static_inputs,
+            inputs_b.variable_inputs,
+        )?;
+
+        let nonce = E::ScalarField::rand(rng);
+        let points = E::G1::normalize_batch(&[
+            base_a * nonce,
+            base_b * nonce,
+            base_a * x,
+            base_b * x,
+            prepared_inputs_a,
+            prepared_inputs_b,
+        ]);
+        let (commitment_a, commitment_b) = (points[0], points[1]);
+        let c = challenge::<E>([
+            base_a,
+            base_b,
+            points[2],
+            points[3],
+            commitment_a,
+            commitment_b,
+        ]);
+
+        Ok(InputEqualityProof {
+            prepared_inputs_a: points[4],
+            prepared_inputs_b: points[5],
+            commitment_a,
+            commitment_b,
+            response: nonce + c * x,
+        })
+    }
+
+    /// Verifies `proof_a` under `pvk_a` and `proof_b` under `pvk_b` against
+    /// the prepared inputs of `link`, and that these match `inputs_a` and
+    /// `inputs_b` with equal hidden inputs, whose values are ignored.
+    ///
+    /// Fails with [`SynthesisError::MalformedVerifyingKey`] if the inputs
+    /// do not match their key.
+    pub fn verify_input_equality(
+        pvk_a: &PreparedVerifyingKey<E>,
+        proof_a: &Proof<E>,
+        inputs_a: &PartiallyHiddenInputs<'_, E::ScalarField>,
+        pvk_b: &PreparedVerifyingKey<E>,
+        proof_b: &Proof<E>,
+        inputs_b: &PartiallyHiddenInputs<'_, E::ScalarField>,
+        link: &InputEqualityProof<E>,
+    ) -> R1CSResult<bool> {
+        let base_a = input_base(pvk_a, inputs_a.position)?;
+        let base_b = input_base(pvk_b, inputs_b.position)?;
+        let prepared_inputs_a = link.prepared_inputs_a.into_group();
+        let prepared_inputs_b = link.prepared_inputs_b.into_group();
+        let hidden = E::G1::normalize_batch(&[
+            Self::hidden_part(pvk_a, inputs_a, prepared_inputs_a)?,
+            Self::hidden_part(pvk_b, inputs_b, prepared_inputs_b)?,
+        ]);
+
+        let c = challenge::<E>([
+            base_a,
+            base_b,
+            hidden[0],
+            hidden[1],
+            link.commitment_a,
+            link.commitment_b,
+        ]);
+        // response base = commitment + c hidden in both bases
+        let consistent = [
+            (base_a, link.commitment_a, hidden[0]),
+            (base_b, link.commitment_b, hidden[1]),
+        ]
+        .into_iter()
+        .all(|(base, commitment, hidden)| {
+            (base * link.response - commitment - hidden * c).is_zero()
+        });
+
+        Ok(consistent
+            && Self::verify_proof_with_prepared_inputs(pvk_a, proof_a, &prepared_inputs_a)?
+            && Self::verify_proof_with_prepared_inputs(pvk_b, proof_b, &prepared_inputs_b)?)
+    }
+}
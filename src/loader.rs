@@ -0,0 +1,701 @@
+use crate::{
+    hash::hash_compressed,
+    layout::{
+        self, ComponentReader,
+        Item::{self, *},
+    },
+    AnyVerifyingKey, KeyBasis, LegacyVerifyingKey, Proof, ProvingKey, VerifyingKey,
+};
+use ark_ec::{pairing::Pairing, AffineRepr};
+use ark_serialize::{
+    CanonicalDeserialize, CanonicalSerialize, Compress, Read, SerializationError, Validate, Write,
+};
+use ark_std::{fmt, vec::Vec};
+
+/// The compressed sizes of the group elements of a pairing-friendly curve,
+/// which determine the layout of serialized keys and proofs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct CurveLayout {
+    /// The size of a compressed `G1` element, in bytes.
+    pub g1_size: usize,
+    /// The size of a compressed `G2` element, in bytes.
+    pub g2_size: usize,
+}
+
+/// The layouts of the curves supported by arkworks, with their names.
+/// BLS12-381 and BLS12-377 share a layout: serializations over them are
+/// told apart by their [`CurveTag`], or by point validation when untagged.
+pub const KNOWN_CURVES: &[(&str, CurveLayout)] = &[
+    ("BN254", CurveLayout::new(32, 64)),
+    ("BLS12-381/BLS12-377", CurveLayout::new(48, 96)),
+    ("BW6-761", CurveLayout::new(96, 96)),
+    ("MNT4-298", CurveLayout::new(38, 76)),
+    ("MNT6-298", CurveLayout::new(38, 114)),
+    ("MNT4-753", CurveLayout::new(95, 190)),
+    ("MNT6-753", CurveLayout::new(95, 285)),
+];
+
+impl CurveLayout {
+    /// Creates a layout from the compressed sizes of `G1` and `G2` elements.
+    pub const fn new(g1_size: usize, g2_size: usize) -> Self {
+        Self { g1_size, g2_size }
+    }
+
+    /// Returns the layout of the curves of `E`.
+    pub fn of<E: Pairing>() -> Self {
+        Self::new(
+            E::G1Affine::default().compressed_size(),
+            E::G2Affine::default().compressed_size(),
+        )
+    }
+
+    /// Returns the name of the curve with this layout, if it is known.
+    pub fn name(&self) -> Option<&'static str> {
+        KNOWN_CURVES
+            .iter()
+            .find(|(_, layout)| layout == self)
+            .map(|(name, _)| *name)
+    }
+
+    fn element_sizes(&self, compress: Compress) -> (usize, usize) {
+        match compress {
+            Compress::Yes => (self.g1_size, self.g2_size),
+            Compress::No => (2 * self.g1_size, 2 * self.g2_size),
+        }
+    }
+}
+
+impl fmt::Display for CurveLayout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.name() {
+            Some(name) => write!(f, "{}", name),
+            None => write!(
+                f,
+                "unknown curve (G1: {} bytes, G2: {} bytes)",
+                self.g1_size, self.g2_size
+            ),
+        }
+    }
+}
+
+/// The bytes starting a serialization tagged with its curves by
+/// [`serialize_tagged`], followed by their [`CurveTag`].
+pub const CURVE_TAG_MAGIC: [u8; 4] = *b"G16C";
+
+/// An identifier of the curves of a pairing: the first 8 bytes of the
+/// SHA-256 hash of the compressed generators of `G1` and `G2`. Unlike
+/// [`CurveLayout`], it tells apart curves whose points have the same sizes,
+/// such as BLS12-381 and BLS12-377.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct CurveTag(pub [u8; 8]);
+
+impl CurveTag {
+    /// Returns the tag of the curves of `E`.
+    pub fn of<E: Pairing>() -> Self {
+        let hash = hash_compressed(&(E::G1Affine::generator(), E::G2Affine::generator()));
+        Self(hash[..8].try_into().unwrap())
+    }
+}
+
+impl fmt::Display for CurveTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.iter().try_for_each(|byte| write!(f, "{:02x}", byte))
+    }
+}
+
+/// A key or proof over the curves of [`Self::Pairing`], which
+/// [`serialize_tagged`] tags with them.
+pub trait CurveObject: CanonicalSerialize {
+    /// The pairing whose curves the object is over.
+    type Pairing: Pairing;
+}
+
+impl<E: Pairing> CurveObject for Proof<E> {
+    type Pairing = E;
+}
+
+impl<E: Pairing> CurveObject for VerifyingKey<E> {
+    type Pairing = E;
+}
+
+impl<E: Pairing> CurveObject for LegacyVerifyingKey<E> {
+    type Pairing = E;
+}
+
+impl<E: Pairing> CurveObject for ProvingKey<E> {
+    type Pairing = E;
+}
+
+/// Serializes `value` with `compress` after [`CURVE_TAG_MAGIC`] and the
+/// [`CurveTag`] of its curves, so that the loaders of this module reject it
+/// with [`KeyLoadError::WrongCurveTag`] over any other curve, even one with
+/// the same [`CurveLayout`]. The loaders also accept untagged
+/// serializations.
+pub fn serialize_tagged<T: CurveObject>(
+    value: &T,
+    mut writer: impl Write,
+    compress: Compress,
+) -> Result<(), SerializationError> {
+    writer.write_all(&CURVE_TAG_MAGIC)?;
+    writer.write_all(&CurveTag::of::<T::Pairing>().0)?;
+    value.serialize_with_mode(writer, compress)
+}
+
+/// An error when loading a serialized key or proof.
+#[derive(Debug)]
+pub enum KeyLoadError {
+    /// The bytes encode an object over a different curve.
+    WrongCurve {
+        /// The layout of the curve the object was loaded for.
+        expected: CurveLayout,
+        /// The layout of the curve the bytes were serialized with.
+        found: CurveLayout,
+    },
+    /// The bytes are tagged with a different curve, see
+    /// [`serialize_tagged`].
+    WrongCurveTag {
+        /// The tag of the curve the object was loaded for.
+        expected: CurveTag,
+        /// The tag of the curve the bytes were serialized with.
+        found: CurveTag,
+    },
+    /// The bytes do not have the layout of the object over any known curve.
+    UnknownLayout,
+    /// The bytes have the expected layout, but do not encode valid points.
+    Serialization(SerializationError),
+    /// The object exceeds the [`DeserializeLimits`] it was loaded with.
+    LimitExceeded,
+}
+
+impl fmt::Display for KeyLoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeyLoadError::WrongCurve { expected, found } => {
+                write!(f, "expected an object over {}, found {}", expected, found)
+            },
+            KeyLoadError::WrongCurveTag { expected, found } => write!(
+                f,
+                "expected an object over the curve tagged {}, found {}",
+                expected, found
+            ),
+            KeyLoadError::UnknownLayout => write!(f, "bytes do not match any known curve"),
+            KeyLoadError::Serialization(e) => write!(f, "{}", e),
+            KeyLoadError::LimitExceeded => write!(f, "object exceeds the deserialization limits"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for KeyLoadError {}
+
+impl From<SerializationError> for KeyLoadError {
+    fn from(e: SerializationError) -> Self {
+        KeyLoadError::Serialization(e)
+    }
+}
+
+/// Returns whether `bytes` is exactly the serialization of an object with
+/// the components `items`, given the sizes of group elements.
+fn fits(bytes: &[u8], items: &[Item], (g1_size, g2_size): (usize, usize)) -> bool {
+    let mut offset = 0usize;
+    for item in items {
+        let size = match item {
+            G1 => Some(g1_size),
+            G2 => Some(g2_size),
+            TrailingBasis => Some(usize::from(offset < bytes.len())),
+            G1Vec | G2Vec => {
+                let element_size = if matches!(item, G1Vec) {
+                    g1_size
+                } else {
+                    g2_size
+                };
+                let len = match bytes.get(offset..offset + 8) {
+                    Some(len) => u64::from_le_bytes(len.try_into().unwrap()),
+                    None => return false,
+                };
+                usize::try_from(len)
+                    .ok()
+                    .and_then(|len| len.checked_mul(element_size))
+                    .and_then(|size| size.checked_add(8))
+            },
+        };
+        offset = match size.and_then(|size| offset.checked_add(size)) {
+            Some(offset) if offset <= bytes.len() => offset,
+            _ => return false,
+        };
+    }
+    offset == bytes.len()
+}
+
+/// Returns `bytes` without their curve tag if they start with one, see
+/// [`serialize_tagged`], failing with [`KeyLoadError::WrongCurveTag`] if it
+/// is not the tag of `E`.
+fn untag<'a, E: Pairing>(bytes: &'a [u8], items: &[Item]) -> Result<&'a [u8], KeyLoadError> {
+    let Some(tagged) = bytes.strip_prefix(&CURVE_TAG_MAGIC[..]) else {
+        return Ok(bytes);
+    };
+    let Some(found) = tagged.get(..8) else {
+        return Ok(bytes);
+    };
+    let found = CurveTag(found.try_into().unwrap());
+    let expected = CurveTag::of::<E>();
+    if found == expected {
+        return Ok(&tagged[8..]);
+    }
+
+    // An untagged serialization may start with the magic bytes by chance.
+    let layout = CurveLayout::of::<E>();
+    if [Compress::Yes, Compress::No]
+        .into_iter()
+        .any(|compress| fits(bytes, items, layout.element_sizes(compress)))
+    {
+        Ok(bytes)
+    } else {
+        Err(KeyLoadError::WrongCurveTag { expected, found })
+    }
+}
+
+/// Deserializes an object over `E` with the components `items` from
+/// `bytes`, tagged or not, in compressed or uncompressed form, after
+/// checking that the bytes were not serialized over another curve.
+fn load<E: Pairing, T: CanonicalDeserialize>(
+    bytes: &[u8],
+    items: &[Item],
+) -> Result<T, KeyLoadError> {
+    let bytes = untag::<E>(bytes, items)?;
+    let expected = CurveLayout::of::<E>();
+    for compress in [Compress::Yes, Compress::No] {
+        if fits(bytes, items, expected.element_sizes(compress)) {
+            let mut reader = bytes;
+            return Ok(T::deserialize_with_mode(
+                &mut reader,
+                compress,
+                Validate::Yes,
+            )?);
+        }
+    }
+
+    KNOWN_CURVES
+        .iter()
+        .map(|(_, layout)| *layout)
+        .filter(|layout| *layout != expected)
+        .find(|layout| {
+            [Compress::Yes, Compress::No]
+                .into_iter()
+                .any(|compress| fits(bytes, items, layout.element_sizes(compress)))
+        })
+        .map_or(Err(KeyLoadError::UnknownLayout), |found| {
+            Err(KeyLoadError::WrongCurve { expected, found })
+        })
+}
+
+/// Loads a compressed or uncompressed [`Proof`] over `E`, returning
+/// [`KeyLoadError::WrongCurve`] or [`KeyLoadError::WrongCurveTag`] if it
+/// was serialized over another curve. Points are checked to be on the
+/// curve and in the right subgroup.
+pub fn load_proof<E: Pairing>(bytes: &[u8]) -> Result<Proof<E>, KeyLoadError> {
+    load::<E, _>(bytes, &layout::layout(layout::read_proof::<E, _>))
+}
+
+/// Loads a compressed or uncompressed [`VerifyingKey`] over `E`, returning
+/// [`KeyLoadError::WrongCurve`] or [`KeyLoadError::WrongCurveTag`] if it
+/// was serialized over another curve. Points are checked to be on the
+/// curve and in the right subgroup.
+pub fn load_verifying_key<E: Pairing>(bytes: &[u8]) -> Result<VerifyingKey<E>, KeyLoadError> {
+    load::<E, _>(bytes, &KeyKind::Verifying.layout::<E>())
+}
+
+/// Loads a compressed or uncompressed [`ProvingKey`] over `E`, returning
+/// [`KeyLoadError::WrongCurve`] or [`KeyLoadError::WrongCurveTag`] if it
+/// was serialized over another curve. Points are checked to be on the
+/// curve and in the right subgroup.
+pub fn load_proving_key<E: Pairing>(bytes: &[u8]) -> Result<ProvingKey<E>, KeyLoadError> {
+    load::<E, _>(bytes, &KeyKind::Proving.layout::<E>())
+}
+
+/// Loads a compressed or uncompressed verifying key over `E` in either the
+/// split layout of [`VerifyingKey`] or the legacy layout of
+/// [`LegacyVerifyingKey`](crate::LegacyVerifyingKey), returning
+/// [`KeyLoadError::WrongCurve`] if it was serialized over another curve.
+/// Points are checked to be on the curve and in the right subgroup.
+pub fn load_any_verifying_key<E: Pairing>(
+    bytes: &[u8],
+) -> Result<AnyVerifyingKey<E>, KeyLoadError> {
+    let split_error = match load::<E, _>(bytes, &KeyKind::Verifying.layout::<E>()) {
+        Ok(vk) => return Ok(AnyVerifyingKey::Split(vk)),
+        Err(e) => e,
+    };
+    match load::<E, _>(bytes, &KeyKind::LegacyVerifying.layout::<E>()) {
+        Ok(vk) => Ok(AnyVerifyingKey::Legacy(vk)),
+        Err(legacy_error) => match split_error {
+            KeyLoadError::UnknownLayout => Err(legacy_error),
+            split_error => Err(split_error),
+        },
+    }
+}
+
+/// Caps on the size of keys deserialized from untrusted sources, checked
+/// against the length prefixes of their vectors before anything is
+/// allocated for them, so that a forged prefix cannot make an
+/// internet-facing verifier allocate gigabytes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct DeserializeLimits {
+    /// The maximum number of public inputs of a verifying key, static and
+    /// variable.
+    pub max_inputs: usize,
+    /// The maximum size of a serialized key, in bytes.
+    pub max_key_bytes: usize,
+}
+
+impl Default for DeserializeLimits {
+    /// Limits fitting verifying keys of up to `2^16` inputs: `2^16` inputs
+    /// and 16 MiB.
+    fn default() -> Self {
+        Self {
+            max_inputs: 1 << 16,
+            max_key_bytes: 1 << 24,
+        }
+    }
+}
+
+/// A reader deserializing the components of keys within the remaining
+/// budget of [`DeserializeLimits`].
+struct LimitedReader<R> {
+    reader: R,
+    compress: Compress,
+    remaining_inputs: usize,
+    remaining_bytes: usize,
+}
+
+impl<R: Read> LimitedReader<R> {
+    fn new(reader: R, compress: Compress, limits: &DeserializeLimits) -> Self {
+        Self {
+            reader,
+            compress,
+            remaining_inputs: limits.max_inputs,
+            remaining_bytes: limits.max_key_bytes,
+        }
+    }
+
+    /// Deducts `size` bytes from the budget.
+    fn spend(&mut self, size: usize) -> Result<(), KeyLoadError> {
+        self.remaining_bytes = self
+            .remaining_bytes
+            .checked_sub(size)
+            .ok_or(KeyLoadError::LimitExceeded)?;
+        Ok(())
+    }
+
+    /// Reads the [`KeyBasis`] tag ending a proving key, if any, see
+    /// [`Item::TrailingBasis`].
+    fn trailing_basis(&mut self) -> Result<KeyBasis, KeyLoadError> {
+        let mut tag = [0u8; 1];
+        if self
+            .reader
+            .read(&mut tag)
+            .map_err(SerializationError::from)?
+            == 0
+        {
+            return Ok(KeyBasis::Monomial);
+        }
+        self.spend(1)?;
+        Ok(KeyBasis::deserialize_compressed(&tag[..])?)
+    }
+
+    /// Reads a value of a fixed size, checked.
+    fn value<T: CanonicalDeserialize + CanonicalSerialize + Default>(
+        &mut self,
+    ) -> Result<T, KeyLoadError> {
+        self.spend(T::default().serialized_size(self.compress))?;
+        Ok(T::deserialize_with_mode(
+            &mut self.reader,
+            self.compress,
+            Validate::Yes,
+        )?)
+    }
+
+    /// Reads a vector of points, counting them as inputs if `inputs`, after
+    /// checking its length prefix against the budget.
+    fn points<G: AffineRepr>(&mut self, inputs: bool) -> Result<Vec<G>, KeyLoadError> {
+        let len = self.value::<u64>()?;
+        let len = usize::try_from(len).map_err(|_| KeyLoadError::LimitExceeded)?;
+        if inputs {
+            self.remaining_inputs = self
+                .remaining_inputs
+                .checked_sub(len)
+                .ok_or(KeyLoadError::LimitExceeded)?;
+        }
+        let size = len
+            .checked_mul(G::zero().serialized_size(self.compress))
+            .ok_or(KeyLoadError::LimitExceeded)?;
+        self.spend(size)?;
+
+        let mut points = Vec::with_capacity(len);
+        for _ in 0..len {
+            points.push(G::deserialize_with_mode(
+                &mut self.reader,
+                self.compress,
+                Validate::No,
+            )?);
+        }
+        G::batch_check(points.iter())?;
+        Ok(points)
+    }
+}
+
+impl<E: Pairing, R: Read> ComponentReader<E> for LimitedReader<R> {
+    type Error = KeyLoadError;
+
+    fn g1(&mut self) -> Result<E::G1Affine, KeyLoadError> {
+        self.value()
+    }
+
+    fn g2(&mut self) -> Result<E::G2Affine, KeyLoadError> {
+        self.value()
+    }
+
+    fn g1_vec(&mut self, inputs: bool) -> Result<Vec<E::G1Affine>, KeyLoadError> {
+        self.points(inputs)
+    }
+
+    fn g2_vec(&mut self) -> Result<Vec<E::G2Affine>, KeyLoadError> {
+        self.points(false)
+    }
+
+    fn trailing_basis(&mut self) -> Result<KeyBasis, KeyLoadError> {
+        LimitedReader::trailing_basis(self)
+    }
+}
+
+/// A reader returning the bytes of `prefix` from `start` before those of
+/// `reader`, to put back the bytes read to look for a curve tag.
+struct Prefixed<R> {
+    prefix: [u8; 4],
+    start: usize,
+    reader: R,
+}
+
+impl<R: Read> Read for Prefixed<R> {
+    fn read(&mut self, buf: &mut [u8]) -> ark_std::io::Result<usize> {
+        let prefix = &self.prefix[self.start..];
+        if prefix.is_empty() {
+            return self.reader.read(buf);
+        }
+        let len = prefix.len().min(buf.len());
+        buf[..len].copy_from_slice(&prefix[..len]);
+        self.start += len;
+        Ok(len)
+    }
+}
+
+/// Reads the curve tag starting `reader`, if any, see [`serialize_tagged`],
+/// failing with [`KeyLoadError::WrongCurveTag`] if it is not the tag of
+/// `E`. Returns whether there was a tag and a reader of the rest of the
+/// object.
+fn untag_reader<E: Pairing, R: Read>(mut reader: R) -> Result<(bool, Prefixed<R>), KeyLoadError> {
+    let mut magic = [0u8; 4];
+    reader
+        .read_exact(&mut magic)
+        .map_err(SerializationError::from)?;
+    if magic != CURVE_TAG_MAGIC {
+        let reader = Prefixed {
+            prefix: magic,
+            start: 0,
+            reader,
+        };
+        return Ok((false, reader));
+    }
+
+    let mut found = [0u8; 8];
+    reader
+        .read_exact(&mut found)
+        .map_err(SerializationError::from)?;
+    let (expected, found) = (CurveTag::of::<E>(), CurveTag(found));
+    if found != expected {
+        return Err(KeyLoadError::WrongCurveTag { expected, found });
+    }
+    let reader = Prefixed {
+        prefix: magic,
+        start: magic.len(),
+        reader,
+    };
+    Ok((true, reader))
+}
+
+/// Reads a [`VerifyingKey`] over `E` serialized with `compress` from
+/// `reader`, e.g. a network stream, failing with
+/// [`KeyLoadError::LimitExceeded`] as soon as it exceeds `limits`. Points
+/// are checked to be on the curve and in the right subgroup.
+///
+/// Tagged keys are accepted too, see [`serialize_tagged`]. Since the
+/// stream cannot be rewound, an untagged key starting with
+/// [`CURVE_TAG_MAGIC`] by chance is rejected, with probability `2^-32`.
+pub fn read_verifying_key<E: Pairing>(
+    reader: impl Read,
+    compress: Compress,
+    limits: &DeserializeLimits,
+) -> Result<VerifyingKey<E>, KeyLoadError> {
+    let (_, reader) = untag_reader::<E, _>(reader)?;
+    layout::read_verifying_key(&mut LimitedReader::new(reader, compress, limits))
+}
+
+/// Reads a [`ProvingKey`] over `E` serialized with `compress` from
+/// `reader`, tagged or not, failing with [`KeyLoadError::LimitExceeded`] as
+/// soon as it exceeds `limits`, see [`read_verifying_key`]. Points are
+/// checked to be on the curve and in the right subgroup.
+pub fn read_proving_key<E: Pairing>(
+    reader: impl Read,
+    compress: Compress,
+    limits: &DeserializeLimits,
+) -> Result<ProvingKey<E>, KeyLoadError> {
+    let (_, reader) = untag_reader::<E, _>(reader)?;
+    layout::read_proving_key(&mut LimitedReader::new(reader, compress, limits))
+}
+
+/// Loads a compressed or uncompressed [`VerifyingKey`] over `E` like
+/// [`load_verifying_key`], failing with [`KeyLoadError::LimitExceeded`] if
+/// it exceeds `limits`.
+pub fn load_verifying_key_with_limits<E: Pairing>(
+    bytes: &[u8],
+    limits: &DeserializeLimits,
+) -> Result<VerifyingKey<E>, KeyLoadError> {
+    if bytes.len() > limits.max_key_bytes {
+        return Err(KeyLoadError::LimitExceeded);
+    }
+    let vk: VerifyingKey<E> = load_verifying_key(bytes)?;
+    let num_inputs = vk.gamma_abc_g1_static.len() + vk.gamma_abc_g1_variable.len();
+    if num_inputs > limits.max_inputs {
+        return Err(KeyLoadError::LimitExceeded);
+    }
+    Ok(vk)
+}
+
+/// The number of points [`rewrite_key`] holds in memory at once.
+const REWRITE_CHUNK: usize = 1 << 12;
+
+/// The kind of a serialized key, which determines its layout.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum KeyKind {
+    /// A [`ProvingKey`].
+    Proving,
+    /// A [`VerifyingKey`].
+    Verifying,
+    /// A [`LegacyVerifyingKey`](crate::LegacyVerifyingKey).
+    LegacyVerifying,
+}
+
+impl KeyKind {
+    fn layout<E: Pairing>(self) -> Vec<Item> {
+        match self {
+            KeyKind::Proving => layout::layout(layout::read_proving_key::<E, _>),
+            KeyKind::Verifying => layout::layout(layout::read_verifying_key::<E, _>),
+            KeyKind::LegacyVerifying => layout::layout(layout::read_legacy_verifying_key::<E, _>),
+        }
+    }
+}
+
+/// How [`rewrite_key`] re-encodes a serialized key.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct RewriteOptions {
+    /// The kind of the key.
+    pub kind: KeyKind,
+    /// The compression mode the key is read with.
+    pub from: Compress,
+    /// The compression mode the key is written with.
+    pub to: Compress,
+    /// Whether points are checked to be on the curve and in the right
+    /// subgroup while rewriting. Keys rewritten without validation must
+    /// come from a trusted source, or be validated when they are loaded.
+    pub validate: Validate,
+}
+
+/// Streams the key over `E` serialized in `reader` to `writer`, converting
+/// it from `options.from` to `options.to` compression. At most a few
+/// thousand points are held in memory at once, so that keys of several
+/// gigabytes can be re-encoded without loading them.
+///
+/// A tagged key stays tagged, see [`serialize_tagged`].
+///
+/// Fails with [`KeyLoadError::Serialization`] if the bytes do not encode a
+/// key of `options.kind`, or if bytes are left after it. On failure,
+/// `writer` may have received a prefix of the rewritten key.
+pub fn rewrite_key<E: Pairing>(
+    reader: impl Read,
+    mut writer: impl Write,
+    options: &RewriteOptions,
+) -> Result<(), KeyLoadError> {
+    let (tagged, mut reader) = untag_reader::<E, _>(reader)?;
+    if tagged {
+        writer
+            .write_all(&CURVE_TAG_MAGIC)
+            .map_err(SerializationError::from)?;
+        writer
+            .write_all(&CurveTag::of::<E>().0)
+            .map_err(SerializationError::from)?;
+    }
+    for item in options.kind.layout::<E>() {
+        match item {
+            G1 => rewrite_points::<E::G1Affine>(&mut reader, &mut writer, 1, options)?,
+            G2 => rewrite_points::<E::G2Affine>(&mut reader, &mut writer, 1, options)?,
+            G1Vec => {
+                let len = rewrite_len(&mut reader, &mut writer)?;
+                rewrite_points::<E::G1Affine>(&mut reader, &mut writer, len, options)?
+            },
+            G2Vec => {
+                let len = rewrite_len(&mut reader, &mut writer)?;
+                rewrite_points::<E::G2Affine>(&mut reader, &mut writer, len, options)?
+            },
+            TrailingBasis => KeyBasis::deserialize_trailing(&mut reader)?
+                .serialize_with_mode(&mut writer, options.to)?,
+        }
+    }
+    let mut trailing = [0u8; 1];
+    if reader
+        .read(&mut trailing)
+        .map_err(SerializationError::from)?
+        != 0
+    {
+        return Err(SerializationError::InvalidData.into());
+    }
+    Ok(())
+}
+
+/// Copies the length prefix of a vector, which is the same in both
+/// compression modes.
+fn rewrite_len(reader: &mut impl Read, writer: &mut impl Write) -> Result<u64, KeyLoadError> {
+    let len = u64::deserialize_compressed(&mut *reader)?;
+    len.serialize_compressed(writer)?;
+    Ok(len)
+}
+
+/// Re-encodes `len` points, in chunks of at most [`REWRITE_CHUNK`] points
+/// checked in batches.
+fn rewrite_points<G: AffineRepr>(
+    reader: &mut impl Read,
+    writer: &mut impl Write,
+    len: u64,
+    options: &RewriteOptions,
+) -> Result<(), KeyLoadError> {
+    let mut remaining = len;
+    let mut chunk =
+        Vec::with_capacity(REWRITE_CHUNK.min(usize::try_from(len).unwrap_or(usize::MAX)));
+    while remaining > 0 {
+        let size = remaining.min(REWRITE_CHUNK as u64);
+        chunk.clear();
+        for _ in 0..size {
+            chunk.push(G::deserialize_with_mode(
+                &mut *reader,
+                options.from,
+                Validate::No,
+            )?);
+        }
+        if options.validate == Validate::Yes {
+            G::batch_check(chunk.iter())?;
+        }
+        for point in &chunk {
+            point.serialize_with_mode(&mut *writer, options.to)?;
+        }
+        remaining -= size;
+    }
+    Ok(())
+}
@@ -0,0 +1,623 @@
+use ark_ec::VariableBaseMSM;
+use ark_ff::{BigInteger, One, PrimeField, Zero};
+use ark_std::{any::TypeId, cfg_chunks, cfg_into_iter, fmt, vec::Vec};
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+#[cfg(feature = "std")]
+use ark_ec::pairing::Pairing;
+#[cfg(feature = "std")]
+use ark_ff::UniformRand;
+#[cfg(feature = "std")]
+use ark_std::rand::Rng;
+#[cfg(feature = "std")]
+use std::{hint::black_box, sync::Mutex, time::Instant};
+
+/// The bigint representation of the scalars of `G`.
+pub type BigInt<G> = <<G as ark_ec::Group>::ScalarField as PrimeField>::BigInt;
+
+/// A backend computing multi-scalar multiplications in the group `G`.
+///
+/// The prover routes all of its MSMs through a backend, so that hardware
+/// accelerated implementations can be plugged in without touching the
+/// proving logic.
+///
+/// GPU libraries such as ICICLE are not bundled with this crate. A backend
+/// wrapping one should convert the bases once per proving key rather than
+/// once per MSM, and fall back to [`CpuMsm`] when no device is present, or
+/// be combined with it in a [`HybridMsm`].
+pub trait MsmBackend<G: VariableBaseMSM> {
+    /// Computes `sum_i bases[i] * scalars[i]`. Extra bases or scalars are
+    /// ignored.
+    fn msm_bigint(&self, bases: &[G::MulBase], scalars: &[BigInt<G>]) -> G;
+
+    /// Returns whether the backend computes MSMs on an accelerator which is
+    /// present, for reporting the capabilities of a host. Backends wrapping
+    /// a GPU library should return `false` when no device was found.
+    fn is_accelerated(&self) -> bool {
+        false
+    }
+}
+
+/// The default backend, computing MSMs on the CPU with the arkworks
+/// implementation of Pippenger's algorithm.
+///
+/// With the `hardened` feature, MSMs are computed in constant time in the
+/// scalars instead, like [`ConstantTimeMsm`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CpuMsm;
+
+impl<G: VariableBaseMSM> MsmBackend<G> for CpuMsm {
+    #[inline]
+    fn msm_bigint(&self, bases: &[G::MulBase], scalars: &[BigInt<G>]) -> G {
+        #[cfg(feature = "hardened")]
+        return msm_constant_time(bases, scalars);
+
+        #[cfg(not(feature = "hardened"))]
+        G::msm_bigint(bases, scalars)
+    }
+}
+
+/// A backend computing MSMs on the CPU in constant time in the scalars, see
+/// [`msm_constant_time`], for provers running on hardware shared with
+/// untrusted tenants.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ConstantTimeMsm;
+
+impl<G: VariableBaseMSM> MsmBackend<G> for ConstantTimeMsm {
+    #[inline]
+    fn msm_bigint(&self, bases: &[G::MulBase], scalars: &[BigInt<G>]) -> G {
+        msm_constant_time(bases, scalars)
+    }
+}
+
+/// The number of bits of the windows of [`msm_constant_time`].
+const CT_WINDOW_BITS: usize = 4;
+
+/// The number of bases whose tables [`msm_constant_time`] keeps at once.
+const CT_CHUNK_SIZE: usize = 1 << 10;
+
+/// Computes `sum_i bases[i] * scalars[i]` without branching or accessing
+/// memory depending on the scalars. Extra bases or scalars are ignored.
+///
+/// Scalars are split in fixed windows of 4 bits, which are all processed,
+/// including leading zero ones. In each window, every base adds an entry of
+/// its table of multiples, found by scanning the whole table. The entries
+/// are the digit plus one times the base, so that no addition involves the
+/// identity, whose handling branches, and the extra multiple of the bases
+/// is subtracted at the end.
+///
+/// This is several times slower than [`CpuMsm`] on large MSMs. It only hides
+/// the scalars from timing and cache side channels as far as the field
+/// arithmetic of arkworks is itself constant-time.
+pub fn msm_constant_time<G: VariableBaseMSM>(bases: &[G::MulBase], scalars: &[BigInt<G>]) -> G {
+    let size = ark_std::cmp::min(bases.len(), scalars.len());
+    let (bases, scalars) = (&bases[..size], &scalars[..size]);
+    let table_size = 1 << CT_WINDOW_BITS;
+    let num_windows = (G::ScalarField::MODULUS_BIT_SIZE as usize).div_ceil(CT_WINDOW_BITS);
+
+    let chunk_sums = cfg_chunks!(bases, CT_CHUNK_SIZE)
+        .zip(cfg_chunks!(scalars, CT_CHUNK_SIZE))
+        .map(|(bases, scalars)| {
+            // tables[i * table_size + j] = (j + 1) * bases[i]
+            let mut multiples = Vec::with_capacity(bases.len() * table_size);
+            for base in bases {
+                let mut multiple = G::zero();
+                for _ in 0..table_size {
+                    multiple += base;
+                    multiples.push(multiple);
+                }
+            }
+            let tables = G::batch_convert_to_mul_base(&multiples);
+            drop(multiples);
+
+            let mut sum = G::zero();
+            for window in (0..num_windows).rev() {
+                for _ in 0..CT_WINDOW_BITS {
+                    sum.double_in_place();
+                }
+                let bit = window * CT_WINDOW_BITS;
+                for (table, scalar) in tables.chunks(table_size).zip(scalars) {
+                    let digit = (scalar.as_ref()[bit / 64] >> (bit % 64)) as usize % table_size;
+                    sum += select(table, digit);
+                }
+            }
+            sum
+        })
+        .collect::<Vec<_>>();
+
+    // Each window added each base once more than its digit.
+    let sixteen = G::ScalarField::from(table_size as u64);
+    let mut offset = G::ScalarField::zero();
+    for _ in 0..num_windows {
+        offset = offset * sixteen + G::ScalarField::one();
+    }
+    let mut base_sum = G::zero();
+    for base in bases {
+        base_sum += base;
+    }
+
+    chunk_sums.into_iter().sum::<G>() - base_sum * offset
+}
+
+/// Returns `table[index]`, reading every entry and selecting with indexing
+/// into a pair instead of branching.
+fn select<T: Copy>(table: &[T], index: usize) -> T {
+    let mut selected = table[0];
+    for (i, entry) in table.iter().enumerate().skip(1) {
+        selected = [selected, *entry][usize::from(i == index)];
+    }
+    selected
+}
+
+/// Computes `base * scalar` for a secret `scalar`, e.g. a blinding factor,
+/// in constant time with the `hardened` feature.
+#[inline]
+pub(crate) fn mul_secret<G: VariableBaseMSM>(base: G, scalar: G::ScalarField) -> G {
+    #[cfg(feature = "hardened")]
+    return msm_constant_time(
+        &G::batch_convert_to_mul_base(&[base]),
+        &[scalar.into_bigint()],
+    );
+
+    #[cfg(not(feature = "hardened"))]
+    {
+        base * scalar
+    }
+}
+
+/// Computes `sum_i bases[i] * scalars[i]` with Pippenger's bucket method,
+/// over as many windows as needed for the longest scalar rather than for
+/// the full size of the scalar field. Extra bases or scalars are ignored.
+///
+/// When all scalars are short, e.g. 64-bit amounts, this skips most of the
+/// windows the arkworks implementation processes. Otherwise, the MSM is left
+/// to the arkworks implementation, which uses signed digits and thus half as
+/// many buckets.
+pub fn msm_bounded_bits<G: VariableBaseMSM>(bases: &[G::MulBase], scalars: &[BigInt<G>]) -> G {
+    let size = ark_std::cmp::min(bases.len(), scalars.len());
+    let (bases, scalars) = (&bases[..size], &scalars[..size]);
+    let num_bits = scalars.iter().map(|s| s.num_bits()).max().unwrap_or(0) as usize;
+    if num_bits == 0 {
+        return G::zero();
+    }
+
+    let c = default_window(size);
+    if num_bits + c > G::ScalarField::MODULUS_BIT_SIZE as usize {
+        return G::msm_bigint(bases, scalars);
+    }
+    pippenger(bases, scalars, num_bits, c)
+}
+
+/// Returns the window size of [`msm_bounded_bits`] for an MSM of `size`.
+fn default_window(size: usize) -> usize {
+    if size < 32 {
+        3
+    } else {
+        (ark_std::log2(size) * 69 / 100) as usize + 2
+    }
+}
+
+/// Computes `sum_i bases[i] * scalars[i]` with Pippenger's bucket method,
+/// with unsigned digits of `c` bits, for scalars of at most `num_bits` bits.
+fn pippenger<G: VariableBaseMSM>(
+    bases: &[G::MulBase],
+    scalars: &[BigInt<G>],
+    num_bits: usize,
+    c: usize,
+) -> G {
+    let window_starts = (0..num_bits).step_by(c).collect::<Vec<_>>();
+    let window_sums = cfg_into_iter!(window_starts)
+        .map(|start| {
+            let mut buckets = vec![G::zero(); (1 << c) - 1];
+            for (base, scalar) in bases.iter().zip(scalars) {
+                let mut scalar = *scalar;
+                scalar.divn(start as u32);
+                let digit = (scalar.as_ref()[0] % (1 << c)) as usize;
+                if digit != 0 {
+                    buckets[digit - 1] += base;
+                }
+            }
+
+            // sum_j (j + 1) * buckets[j], with running sums.
+            let mut running_sum = G::zero();
+            let mut window_sum = G::zero();
+            for bucket in buckets.into_iter().rev() {
+                running_sum += bucket;
+                window_sum += running_sum;
+            }
+            window_sum
+        })
+        .collect::<Vec<_>>();
+
+    let mut result = G::zero();
+    for window_sum in window_sums.into_iter().rev() {
+        for _ in 0..c {
+            result.double_in_place();
+        }
+        result += window_sum;
+    }
+    result
+}
+
+/// The window sizes measured fastest for MSMs in a group, by MSM size.
+///
+/// Each entry gives the window of the MSMs from its size up to the size of
+/// the next entry, or `None` where the arkworks implementation, which uses
+/// signed digits, was faster than all windows tried. The first entry also
+/// applies to smaller MSMs.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct WindowTable {
+    entries: Vec<(usize, Option<usize>)>,
+}
+
+/// An error when creating a [`WindowTable`]: the window of an entry is not
+/// between 1 and [`MAX_TUNING_WINDOW`] bits.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InvalidWindow {
+    /// The MSM size of the entry.
+    pub size: usize,
+    /// The window of the entry.
+    pub window: usize,
+}
+
+impl fmt::Display for InvalidWindow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "window of {} bits for MSMs of size {} is not between 1 and {}",
+            self.window, self.size, MAX_TUNING_WINDOW
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for InvalidWindow {}
+
+impl WindowTable {
+    /// Creates a table from `(size, window)` entries, e.g. from a previous
+    /// calibration. Windows must be between 1 and [`MAX_TUNING_WINDOW`] bits,
+    /// as each MSM allocates `2^c - 1` buckets per thread.
+    pub fn from_entries(mut entries: Vec<(usize, Option<usize>)>) -> Result<Self, InvalidWindow> {
+        for &(size, window) in &entries {
+            match window {
+                Some(window) if !(1..=MAX_TUNING_WINDOW).contains(&window) => {
+                    return Err(InvalidWindow { size, window })
+                },
+                _ => {},
+            }
+        }
+        entries.sort_unstable();
+        Ok(Self { entries })
+    }
+
+    /// Returns the `(size, window)` entries, by increasing size.
+    pub fn entries(&self) -> &[(usize, Option<usize>)] {
+        &self.entries
+    }
+
+    /// Returns the window for an MSM of `size`, or `None` to use the
+    /// arkworks implementation.
+    pub fn window(&self, size: usize) -> Option<usize> {
+        self.entries
+            .iter()
+            .rev()
+            .find(|(min_size, _)| *min_size <= size)
+            .or(self.entries.first())
+            .and_then(|(_, window)| *window)
+    }
+}
+
+/// A backend computing MSMs on the CPU with the window sizes of a
+/// [`WindowTable`] per group, e.g. calibrated on the current machine by an
+/// [`MsmTuner`]. MSMs in groups without a table use the arkworks
+/// implementation. Unlike [`CpuMsm`] with the `hardened` feature, the MSMs
+/// are never computed in constant time.
+#[derive(Clone, Debug, Default)]
+pub struct TunedMsm {
+    tables: Vec<(TypeId, WindowTable)>,
+}
+
+impl TunedMsm {
+    /// Creates a backend without tables, equivalent to [`CpuMsm`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the window sizes of the MSMs in `G`.
+    pub fn with_table<G: VariableBaseMSM>(mut self, table: WindowTable) -> Self {
+        let group = TypeId::of::<G>();
+        self.tables.retain(|(g, _)| *g != group);
+        self.tables.push((group, table));
+        self
+    }
+
+    /// Returns the window sizes of the MSMs in `G`, if set.
+    pub fn table<G: VariableBaseMSM>(&self) -> Option<&WindowTable> {
+        let group = TypeId::of::<G>();
+        self.tables
+            .iter()
+            .find(|(g, _)| *g == group)
+            .map(|(_, table)| table)
+    }
+}
+
+impl<G: VariableBaseMSM> MsmBackend<G> for TunedMsm {
+    fn msm_bigint(&self, bases: &[G::MulBase], scalars: &[BigInt<G>]) -> G {
+        let size = ark_std::cmp::min(bases.len(), scalars.len());
+        match self.table::<G>().and_then(|table| table.window(size)) {
+            Some(c) => {
+                let scalars = &scalars[..size];
+                let num_bits = scalars.iter().map(|s| s.num_bits()).max().unwrap_or(0);
+                pippenger(bases, scalars, num_bits as usize, c)
+            },
+            None => G::msm_bigint(bases, scalars),
+        }
+    }
+}
+
+/// The largest window tried by an [`MsmTuner`] or accepted in a
+/// [`WindowTable`], as each thread allocates `2^c - 1` buckets.
+pub const MAX_TUNING_WINDOW: usize = 20;
+
+/// The MSM sizes benchmarked by default by an [`MsmTuner`].
+#[cfg(feature = "std")]
+pub const DEFAULT_TUNING_SIZES: [usize; 5] = [1 << 8, 1 << 10, 1 << 12, 1 << 14, 1 << 16];
+
+/// Benchmarks window sizes for MSMs on the current machine, since the best
+/// ones depend on its caches and number of cores.
+///
+/// For each size, the tuner times the arkworks implementation and a few
+/// windows around the usual heuristic, on random points, and keeps the
+/// fastest. Calibrating with the default sizes takes a few seconds in
+/// release builds; the resulting [`TunedMsm`] is meant to be computed once
+/// per process, or stored, and shared by all provers.
+#[cfg(feature = "std")]
+#[derive(Clone, Debug)]
+pub struct MsmTuner {
+    sizes: Vec<usize>,
+    window_spread: usize,
+    repetitions: usize,
+}
+
+#[cfg(feature = "std")]
+impl Default for MsmTuner {
+    fn default() -> Self {
+        Self {
+            sizes: DEFAULT_TUNING_SIZES.to_vec(),
+            window_spread: 2,
+            repetitions: 3,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl MsmTuner {
+    /// Creates a tuner benchmarking [`DEFAULT_TUNING_SIZES`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the MSM sizes to benchmark.
+    pub fn with_sizes(mut self, sizes: Vec<usize>) -> Self {
+        self.sizes = sizes;
+        self
+    }
+
+    /// Sets how many bits above and below the usual heuristic the windows
+    /// tried range.
+    pub fn with_window_spread(mut self, window_spread: usize) -> Self {
+        self.window_spread = window_spread;
+        self
+    }
+
+    /// Sets how many times each candidate is timed, keeping the fastest run.
+    pub fn with_repetitions(mut self, repetitions: usize) -> Self {
+        self.repetitions = repetitions.max(1);
+        self
+    }
+
+    /// Benchmarks the MSMs in both groups of `E`, with random points
+    /// sampled via `rng`.
+    pub fn calibrate<E: Pairing>(&self, rng: &mut impl Rng) -> TunedMsm {
+        TunedMsm::new()
+            .with_table::<E::G1>(self.calibrate_group::<E::G1>(rng))
+            .with_table::<E::G2>(self.calibrate_group::<E::G2>(rng))
+    }
+
+    /// Benchmarks the MSMs in `G`, with random points sampled via `rng`.
+    pub fn calibrate_group<G: VariableBaseMSM>(&self, rng: &mut impl Rng) -> WindowTable {
+        let max_size = self.sizes.iter().copied().max().unwrap_or(0);
+        let points = (0..max_size).map(|_| G::rand(rng)).collect::<Vec<_>>();
+        let bases = G::batch_convert_to_mul_base(&points);
+        let scalars = (0..max_size)
+            .map(|_| G::ScalarField::rand(rng).into_bigint())
+            .collect::<Vec<_>>();
+        let num_bits = G::ScalarField::MODULUS_BIT_SIZE as usize;
+
+        let fastest = |run: &dyn Fn() -> G| {
+            (0..self.repetitions)
+                .map(|_| {
+                    let start = Instant::now();
+                    black_box(run());
+                    start.elapsed()
+                })
+                .min()
+                .unwrap_or_default()
+        };
+        let entries = self
+            .sizes
+            .iter()
+            .map(|&size| {
+                let (bases, scalars) = (&bases[..size], &scalars[..size]);
+                let default = default_window(size);
+                let windows = default.saturating_sub(self.window_spread).max(1)
+                    ..=(default + self.window_spread).min(MAX_TUNING_WINDOW);
+                let mut best = (fastest(&|| G::msm_bigint(bases, scalars)), None);
+                for c in windows {
+                    let time = fastest(&|| pippenger(bases, scalars, num_bits, c));
+                    if time < best.0 {
+                        best = (time, Some(c));
+                    }
+                }
+                (size, best.1)
+            })
+            .collect();
+        WindowTable::from_entries(entries).expect("tuned windows are within the limit")
+    }
+}
+
+/// MSMs smaller than this are never split, as the fixed cost of dispatching
+/// to an accelerator dominates.
+#[cfg(feature = "std")]
+pub const DEFAULT_MIN_SPLIT_SIZE: usize = 1 << 12;
+
+/// If the accelerator would receive a smaller share of an MSM than this, the
+/// whole MSM is computed on the CPU instead.
+#[cfg(feature = "std")]
+pub const DEFAULT_MIN_ACCELERATOR_SHARE: f64 = 0.05;
+
+/// Throughput measurements, in points per second.
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Debug, Default)]
+struct Throughput {
+    cpu: f64,
+    accelerator: f64,
+}
+
+#[cfg(feature = "std")]
+impl Throughput {
+    /// Weight given to the newest measurement when updating a rate.
+    const SMOOTHING: f64 = 0.5;
+
+    fn update(rate: &mut f64, points: usize, seconds: f64) {
+        if points == 0 || seconds <= 0.0 {
+            return;
+        }
+        let measured = points as f64 / seconds;
+        *rate = if *rate == 0.0 {
+            measured
+        } else {
+            (1.0 - Self::SMOOTHING) * *rate + Self::SMOOTHING * measured
+        };
+    }
+
+    /// The fraction of an MSM to hand to the accelerator. Before anything
+    /// has been measured, work is split evenly to calibrate both sides.
+    fn accelerator_share(&self) -> f64 {
+        if self.cpu == 0.0 || self.accelerator == 0.0 {
+            0.5
+        } else {
+            self.accelerator / (self.cpu + self.accelerator)
+        }
+    }
+}
+
+/// A backend splitting each MSM between the CPU and an accelerator (e.g. a
+/// GPU), proportionally to the throughput measured for each of them on
+/// previous MSMs.
+///
+/// Both halves are computed concurrently. When the accelerator turns out to
+/// be much slower than the CPU, MSMs are computed on the CPU alone, so that
+/// weak accelerators do not slow proving down.
+#[cfg(feature = "std")]
+pub struct HybridMsm<C, A> {
+    cpu: C,
+    accelerator: A,
+    min_split_size: usize,
+    min_accelerator_share: f64,
+    throughput: Mutex<Throughput>,
+}
+
+#[cfg(feature = "std")]
+impl<C, A> HybridMsm<C, A> {
+    /// Creates a scheduler over the `cpu` and `accelerator` backends, with
+    /// the default thresholds.
+    pub fn new(cpu: C, accelerator: A) -> Self {
+        Self {
+            cpu,
+            accelerator,
+            min_split_size: DEFAULT_MIN_SPLIT_SIZE,
+            min_accelerator_share: DEFAULT_MIN_ACCELERATOR_SHARE,
+            throughput: Mutex::new(Throughput::default()),
+        }
+    }
+
+    /// Sets the size below which MSMs are computed on the CPU alone.
+    pub fn with_min_split_size(mut self, min_split_size: usize) -> Self {
+        self.min_split_size = min_split_size;
+        self
+    }
+
+    /// Sets the smallest share of an MSM worth handing to the accelerator.
+    pub fn with_min_accelerator_share(mut self, min_accelerator_share: f64) -> Self {
+        self.min_accelerator_share = min_accelerator_share;
+        self
+    }
+
+    /// Returns the fraction of the next large MSM that will be computed by
+    /// the accelerator.
+    pub fn accelerator_share(&self) -> f64 {
+        let share = self.throughput.lock().unwrap().accelerator_share();
+        if share < self.min_accelerator_share {
+            0.0
+        } else {
+            share
+        }
+    }
+
+    /// Forgets all throughput measurements, so that the next MSM is split
+    /// evenly again.
+    pub fn recalibrate(&self) {
+        *self.throughput.lock().unwrap() = Throughput::default();
+    }
+}
+
+#[cfg(feature = "std")]
+impl<G, C, A> MsmBackend<G> for HybridMsm<C, A>
+where
+    G: VariableBaseMSM,
+    C: MsmBackend<G> + Sync,
+    A: MsmBackend<G> + Sync,
+{
+    fn msm_bigint(&self, bases: &[G::MulBase], scalars: &[BigInt<G>]) -> G {
+        let size = bases.len().min(scalars.len());
+        let share = self.accelerator_share();
+        let split = size - (size as f64 * share) as usize;
+        if size < self.min_split_size || split == size {
+            return self.cpu.msm_bigint(bases, scalars);
+        }
+
+        let timed = |backend: &(dyn MsmBackend<G> + Sync), bases, scalars| {
+            let start = Instant::now();
+            let result = backend.msm_bigint(bases, scalars);
+            (result, start.elapsed().as_secs_f64())
+        };
+        let cpu_job = || timed(&self.cpu, &bases[..split], &scalars[..split]);
+        let accelerator_job = || {
+            timed(
+                &self.accelerator,
+                &bases[split..size],
+                &scalars[split..size],
+            )
+        };
+
+        #[cfg(feature = "parallel")]
+        let ((cpu_result, cpu_time), (accelerator_result, accelerator_time)) =
+            rayon::join(cpu_job, accelerator_job);
+        #[cfg(not(feature = "parallel"))]
+        let ((cpu_result, cpu_time), (accelerator_result, accelerator_time)) =
+            (cpu_job(), accelerator_job());
+
+        let mut throughput = self.throughput.lock().unwrap();
+        Throughput::update(&mut throughput.cpu, split, cpu_time);
+        Throughput::update(&mut throughput.accelerator, size - split, accelerator_time);
+
+        cpu_result + accelerator_result
+    }
+
+    fn is_accelerated(&self) -> bool {
+        self.accelerator.is_accelerated()
+    }
+}
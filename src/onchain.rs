@@ -0,0 +1,392 @@
+use crate::{
+    hash::{keccak256, sha256},
+    inputs::Endianness,
+    r1cs_to_qap::R1CSToQAP,
+    zkey::point_from_coordinates,
+    Groth16, PreparedVerifyingKey, Proof, VerifyingKey,
+};
+use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup};
+use ark_ff::{BigInteger, Field, PrimeField};
+use ark_relations::r1cs::Result as R1CSResult;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Compress, SerializationError};
+use ark_std::vec::Vec;
+
+/// The line coefficients of the negated `γ` and `δ` of a verifying key,
+/// prepared off-chain for the Miller loop of an on-chain verifier.
+///
+/// These depend on the key only, so they are computed once and deployed
+/// with the verifier rather than sent with each proof.
+#[derive(Clone, Debug, CanonicalSerialize, CanonicalDeserialize)]
+pub struct PreparedKeyLines<E: Pairing> {
+    /// The prepared `-γ` in `G2`.
+    pub gamma_g2_neg: E::G2Prepared,
+    /// The prepared `-δ` in `G2`.
+    pub delta_g2_neg: E::G2Prepared,
+}
+
+impl<E: Pairing> PreparedKeyLines<E> {
+    /// Returns the prepared lines of `pvk`.
+    pub fn new(pvk: &PreparedVerifyingKey<E>) -> Self {
+        Self {
+            gamma_g2_neg: pvk.gamma_g2_neg_pc.clone(),
+            delta_g2_neg: pvk.delta_g2_neg_pc.clone(),
+        }
+    }
+}
+
+/// A proof whose `B` is replaced by its line coefficients, prepared
+/// off-chain so that an on-chain verifier skips the doublings and additions
+/// in `G2` of the Miller loop, for chains which price pairings steeply.
+///
+/// The lines take a few kilobytes of calldata instead of the size of a
+/// point, see [`Self::calldata_size`]. They are only sound if the verifier
+/// trusts their origin, or checks that they are the lines of the `B` of the
+/// proof, see [`Self::is_prepared_from`]: arbitrary lines can make the
+/// pairing equation hold.
+///
+/// The serialization is the uncompressed one of arkworks. For BN and BLS12
+/// curves, a prepared `G2` element is the number of lines as a little
+/// endian `u64`, the three `Fp2` coefficients of each line, each as two
+/// little endian base field elements, and a byte set for the point at
+/// infinity.
+#[derive(Clone, Debug, CanonicalSerialize, CanonicalDeserialize)]
+pub struct OffloadedProof<E: Pairing> {
+    /// The `A` element of the proof.
+    pub a: E::G1Affine,
+    /// The prepared `B` element of the proof.
+    pub b: E::G2Prepared,
+    /// The `C` element of the proof.
+    pub c: E::G1Affine,
+}
+
+impl<E: Pairing> OffloadedProof<E> {
+    /// Prepares the `B` element of `proof`.
+    pub fn new(proof: &Proof<E>) -> Self {
+        Self {
+            a: proof.a,
+            b: proof.b.into(),
+            c: proof.c,
+        }
+    }
+
+    /// Returns whether the lines of `self` are those of `b`.
+    pub fn is_prepared_from(&self, b: &E::G2Affine) -> bool {
+        let prepared: E::G2Prepared = (*b).into();
+        match (to_bytes(&prepared), to_bytes(&self.b)) {
+            (Ok(expected), Ok(actual)) => expected == actual,
+            _ => false,
+        }
+    }
+
+    /// Returns the size of the uncompressed serialization of `self`, sent
+    /// as calldata to the verifier.
+    pub fn calldata_size(&self) -> usize {
+        self.serialized_size(Compress::No)
+    }
+
+    /// Returns the uncompressed serialization of `self`.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, SerializationError> {
+        to_bytes(self)
+    }
+}
+
+fn to_bytes(value: &impl CanonicalSerialize) -> Result<Vec<u8>, SerializationError> {
+    let mut bytes = Vec::with_capacity(value.serialized_size(Compress::No));
+    value.serialize_uncompressed(&mut bytes)?;
+    Ok(bytes)
+}
+
+impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
+    /// Verify an offloaded proof with separate static and variable inputs,
+    /// using its prepared `B` and the prepared lines of `pvk`. This is the
+    /// computation of an on-chain verifier consuming [`OffloadedProof`] and
+    /// [`PreparedKeyLines`], for testing it against the same inputs.
+    ///
+    /// The lines of `B` are used as given: lines which are not those of a
+    /// point of `G2` can make the pairing equation hold for any inputs. The
+    /// caller must check them against the `B` of the proof with
+    /// [`OffloadedProof::is_prepared_from`], or get them from a party it
+    /// trusts to prepare them.
+    pub fn verify_offloaded_unchecked(
+        pvk: &PreparedVerifyingKey<E>,
+        proof: &OffloadedProof<E>,
+        static_inputs: &[E::ScalarField],
+        variable_inputs: &[E::ScalarField],
+    ) -> R1CSResult<bool> {
+        let prepared_inputs =
+            Self::prepare_inputs_with_variables(pvk, static_inputs, variable_inputs)?;
+
+        Self::verify_prepared_proof(
+            pvk,
+            proof.a.into(),
+            proof.b.clone(),
+            proof.c.into(),
+            &prepared_inputs,
+        )
+    }
+}
+
+/// The order of the two base field elements `c0` and `c1` of each
+/// coordinate `c0 + c1 * u` of a point of `G2`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum G2CoordinateOrder {
+    /// `c0` first, as in arkworks and gnark.
+    C0First,
+    /// `c1` first, as in the EIP-197 pairing precompile of Ethereum.
+    C1First,
+}
+
+/// The encoding of a proof sent to an on-chain verifier: the affine
+/// coordinates of `A`, `B` and `C`, each base field element on
+/// [`Self::element_size`] bytes, with zeros for the point at infinity.
+///
+/// Verifiers checking `e(-A, B) * e(α, β) * e(L, γ) * e(C, δ) = 1` with a
+/// single pairing product either negate `A` themselves or expect it
+/// negated by the prover, see [`Self::negate_a`]. Verifiers negating `γ`
+/// and `δ` instead, as [`PreparedVerifyingKey`] does, take `A` as is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ProofWireFormat {
+    /// Whether `A` is sent negated.
+    pub negate_a: bool,
+    /// The order of the base field elements of the coordinates of `B`.
+    pub g2_order: G2CoordinateOrder,
+    /// The byte order of the base field elements.
+    pub endianness: Endianness,
+}
+
+/// The prime field of the coordinates of the points of `G`.
+type BasePrimeField<G> = <<G as AffineRepr>::BaseField as Field>::BasePrimeField;
+
+/// Returns the number of bytes used to encode each base field element of
+/// the coordinates of the points of `G`.
+fn element_size<G: AffineRepr>() -> usize {
+    (BasePrimeField::<G>::MODULUS_BIT_SIZE as usize).div_ceil(8)
+}
+
+/// Returns the number of bytes used to encode a point of `G`.
+fn point_size<G: AffineRepr>() -> usize {
+    2 * G::BaseField::extension_degree() as usize * element_size::<G>()
+}
+
+impl ProofWireFormat {
+    /// The format of the EIP-197 precompile, with `A` as is, as taken by
+    /// the Solidity verifiers of snarkjs, which negate it.
+    pub const EIP197: Self = Self {
+        negate_a: false,
+        g2_order: G2CoordinateOrder::C1First,
+        endianness: Endianness::Big,
+    };
+
+    /// The format of the EIP-197 precompile with `A` negated, for verifiers
+    /// passing it unchanged to the precompile.
+    pub const EIP197_NEGATED_A: Self = Self {
+        negate_a: true,
+        ..Self::EIP197
+    };
+
+    /// The number of bytes used to encode each base field element, which is
+    /// 32 for BN254 and 48 for BLS12-381.
+    pub fn element_size<E: Pairing>() -> usize {
+        element_size::<E::G1Affine>()
+    }
+
+    /// The number of bytes of an encoded proof.
+    pub fn proof_size<E: Pairing>() -> usize {
+        2 * point_size::<E::G1Affine>() + point_size::<E::G2Affine>()
+    }
+
+    fn encode_element<F: PrimeField>(&self, bytes: &mut Vec<u8>, element: F, size: usize) {
+        let bigint = element.into_bigint();
+        match self.endianness {
+            Endianness::Big => {
+                let be = bigint.to_bytes_be();
+                bytes.extend_from_slice(&be[be.len() - size..]);
+            },
+            Endianness::Little => bytes.extend_from_slice(&bigint.to_bytes_le()[..size]),
+        }
+    }
+
+    fn decode_element<F: PrimeField>(&self, chunk: &[u8]) -> Result<F, SerializationError> {
+        let element = match self.endianness {
+            Endianness::Big => F::from_be_bytes_mod_order(chunk),
+            Endianness::Little => F::from_le_bytes_mod_order(chunk),
+        };
+        // Reject non-canonical encodings, which were reduced above.
+        let mut canonical = Vec::with_capacity(chunk.len());
+        self.encode_element(&mut canonical, element, chunk.len());
+        if canonical == chunk {
+            Ok(element)
+        } else {
+            Err(SerializationError::InvalidData)
+        }
+    }
+
+    fn encode_point<G: AffineRepr>(&self, bytes: &mut Vec<u8>, point: &G) {
+        let Some((x, y)) = point.xy() else {
+            bytes.resize(bytes.len() + point_size::<G>(), 0);
+            return;
+        };
+        for coordinate in [x, y] {
+            let mut elements: Vec<_> = coordinate.to_base_prime_field_elements().collect();
+            if self.g2_order == G2CoordinateOrder::C1First {
+                elements.reverse();
+            }
+            for element in elements {
+                self.encode_element(bytes, element, element_size::<G>());
+            }
+        }
+    }
+
+    fn decode_point<G: AffineRepr>(&self, bytes: &mut &[u8]) -> Result<G, SerializationError> {
+        if bytes.len() < point_size::<G>() {
+            return Err(SerializationError::InvalidData);
+        }
+        let (point, rest) = bytes.split_at(point_size::<G>());
+        *bytes = rest;
+        if point.iter().all(|byte| *byte == 0) {
+            return Ok(G::zero());
+        }
+        let mut coordinates = point.chunks(point.len() / 2).map(|coordinate| {
+            let mut elements = coordinate
+                .chunks(element_size::<G>())
+                .map(|chunk| self.decode_element::<BasePrimeField<G>>(chunk))
+                .collect::<Result<Vec<_>, _>>()?;
+            if self.g2_order == G2CoordinateOrder::C1First {
+                elements.reverse();
+            }
+            G::BaseField::from_base_prime_field_elems(&elements)
+                .ok_or(SerializationError::InvalidData)
+        });
+        let (Some(x), Some(y)) = (coordinates.next(), coordinates.next()) else {
+            return Err(SerializationError::InvalidData);
+        };
+        point_from_coordinates(x?, y?)
+    }
+
+    /// Encodes `proof`, on [`Self::proof_size`] bytes.
+    pub fn encode_proof<E: Pairing>(&self, proof: &Proof<E>) -> Vec<u8> {
+        let a = if self.negate_a {
+            (-proof.a.into_group()).into_affine()
+        } else {
+            proof.a
+        };
+        let mut bytes = Vec::with_capacity(Self::proof_size::<E>());
+        self.encode_point(&mut bytes, &a);
+        self.encode_point(&mut bytes, &proof.b);
+        self.encode_point(&mut bytes, &proof.c);
+        bytes
+    }
+
+    /// Decodes a proof encoded by [`Self::encode_proof`], rejecting
+    /// non-canonical encodings and points which are not in the prime order
+    /// subgroup.
+    pub fn decode_proof<E: Pairing>(
+        &self,
+        mut bytes: &[u8],
+    ) -> Result<Proof<E>, SerializationError> {
+        let a: E::G1Affine = self.decode_point(&mut bytes)?;
+        let b = self.decode_point(&mut bytes)?;
+        let c = self.decode_point(&mut bytes)?;
+        if !bytes.is_empty() {
+            return Err(SerializationError::InvalidData);
+        }
+        let a = if self.negate_a {
+            (-a.into_group()).into_affine()
+        } else {
+            a
+        };
+        Ok(Proof { a, b, c })
+    }
+}
+
+/// Returns the `IC` points of `vk`, as taken by on-chain verifiers, which
+/// do not distinguish static from variable inputs.
+fn ic_points<E: Pairing>(vk: &VerifyingKey<E>) -> Vec<E::G1Affine> {
+    vk.gamma_abc_g1_static
+        .iter()
+        .chain(&vk.gamma_abc_g1_variable)
+        .copied()
+        .collect()
+}
+
+/// Encodes `vk` as `abi.encode(alpha, beta, gamma, delta, ic)` for an EVM
+/// verifier, with `alpha` a `uint256[2]`, `beta`, `gamma` and `delta`
+/// `uint256[2][2]` in the order of the EIP-197 precompile and `ic` a
+/// `uint256[2][]`.
+///
+/// Fails if the base field elements of `E` do not fit in a `uint256`.
+pub fn evm_encode_verifying_key<E: Pairing>(
+    vk: &VerifyingKey<E>,
+) -> Result<Vec<u8>, SerializationError> {
+    if ProofWireFormat::element_size::<E>() != 32 {
+        return Err(SerializationError::InvalidData);
+    }
+    let ic = ic_points(vk);
+    let format = ProofWireFormat::EIP197;
+    let word = |value: usize| {
+        let mut word = [0u8; 32];
+        word[24..].copy_from_slice(&(value as u64).to_be_bytes());
+        word
+    };
+
+    let mut bytes = Vec::with_capacity(32 * (16 + 2 * ic.len()));
+    format.encode_point(&mut bytes, &vk.alpha_g1);
+    for point in [vk.beta_g2, vk.gamma_g2, vk.delta_g2] {
+        format.encode_point(&mut bytes, &point);
+    }
+    // The offset of the dynamic array, after the 15 words of the head.
+    bytes.extend_from_slice(&word(15 * 32));
+    bytes.extend_from_slice(&word(ic.len()));
+    for point in &ic {
+        format.encode_point(&mut bytes, point);
+    }
+    Ok(bytes)
+}
+
+/// Returns the Keccak-256 hash of [`evm_encode_verifying_key`], identifying
+/// `vk` in EVM verifier registries.
+pub fn evm_verifying_key_commitment<E: Pairing>(
+    vk: &VerifyingKey<E>,
+) -> Result<[u8; 32], SerializationError> {
+    Ok(keccak256(&evm_encode_verifying_key(vk)?))
+}
+
+/// Encodes `vk` with Borsh as the Solana struct
+/// `{ alpha_g1: [u8; 64], beta_g2: [u8; 128], gamma_g2: [u8; 128],
+/// delta_g2: [u8; 128], ic: Vec<[u8; 64]> }`, with the array sizes of
+/// BN254. Each point is in the big-endian encoding of the EIP-197
+/// precompile taken by the `alt_bn128` syscalls, and the length of `ic` is
+/// a little-endian `u32`.
+///
+/// Fails if the base field elements of `E` do not fit in 32 bytes, as the
+/// syscalls only support BN254, or if `vk` has more `IC` points than a `u32`
+/// counts.
+pub fn borsh_encode_verifying_key<E: Pairing>(
+    vk: &VerifyingKey<E>,
+) -> Result<Vec<u8>, SerializationError> {
+    if ProofWireFormat::element_size::<E>() != 32 {
+        return Err(SerializationError::InvalidData);
+    }
+    let ic = ic_points(vk);
+    let num_ic = u32::try_from(ic.len()).map_err(|_| SerializationError::InvalidData)?;
+    let format = ProofWireFormat::EIP197;
+
+    let mut bytes = Vec::new();
+    format.encode_point(&mut bytes, &vk.alpha_g1);
+    for point in [vk.beta_g2, vk.gamma_g2, vk.delta_g2] {
+        format.encode_point(&mut bytes, &point);
+    }
+    bytes.extend_from_slice(&num_ic.to_le_bytes());
+    for point in &ic {
+        format.encode_point(&mut bytes, point);
+    }
+    Ok(bytes)
+}
+
+/// Returns the SHA-256 hash of [`borsh_encode_verifying_key`], identifying
+/// `vk` in Solana verifier registries.
+pub fn solana_verifying_key_commitment<E: Pairing>(
+    vk: &VerifyingKey<E>,
+) -> Result<[u8; 32], SerializationError> {
+    Ok(sha256(&borsh_encode_verifying_key(vk)?))
+}
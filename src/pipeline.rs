@@ -0,0 +1,154 @@
+use crate::{
+    msm::MsmBackend, prover::ProverConfig, r1cs_to_qap::R1CSToQAP, Groth16, Proof, ProvingKey,
+};
+use ark_ec::pairing::Pairing;
+use ark_ff::UniformRand;
+use ark_relations::r1cs::{
+    ConstraintMatrices, ConstraintSynthesizer, Result as R1CSResult, SynthesisError,
+};
+use ark_std::{rand::Rng, vec::Vec};
+use rayon::{ThreadPool, ThreadPoolBuildError, ThreadPoolBuilder};
+
+/// The number of threads of each stage of a [`ProverPipeline`]. Stages
+/// without a number of threads run on the global rayon pool.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StageThreads {
+    /// The threads synthesizing the circuit and building its matrices.
+    pub synthesis: Option<usize>,
+    /// The threads computing the QAP witness, mostly in FFTs.
+    pub qap: Option<usize>,
+    /// The threads computing the MSMs and assembling the proof.
+    pub msm: Option<usize>,
+}
+
+/// A prover running the stages of proving (synthesis, QAP witness map,
+/// MSMs and assembly) on thread pools sized independently.
+///
+/// FFTs and MSMs do not scale alike with the number of threads, so on large
+/// machines it can pay to cap the threads of one stage. Within the MSM
+/// stage, the MSM of the `h_query`, which only depends on the QAP witness,
+/// runs concurrently with the `A`, `B` and `C` MSMs over the assignment.
+pub struct ProverPipeline {
+    synthesis: Option<ThreadPool>,
+    qap: Option<ThreadPool>,
+    msm: Option<ThreadPool>,
+    config: ProverConfig,
+}
+
+impl ProverPipeline {
+    /// Creates the thread pools of the stages, with the prover options
+    /// `config`.
+    pub fn new(threads: StageThreads, config: ProverConfig) -> Result<Self, ThreadPoolBuildError> {
+        let pool = |threads: Option<usize>| {
+            threads
+                .map(|threads| ThreadPoolBuilder::new().num_threads(threads).build())
+                .transpose()
+        };
+
+        Ok(Self {
+            synthesis: pool(threads.synthesis)?,
+            qap: pool(threads.qap)?,
+            msm: pool(threads.msm)?,
+            config,
+        })
+    }
+
+    /// Returns the prover options of the pipeline.
+    pub fn config(&self) -> &ProverConfig {
+        &self.config
+    }
+
+    fn install<R: Send>(pool: &Option<ThreadPool>, stage: impl FnOnce() -> R + Send) -> R {
+        match pool {
+            Some(pool) => pool.install(stage),
+            None => stage(),
+        }
+    }
+}
+
+type Synthesized<F> = (ConstraintMatrices<F>, Vec<F>);
+
+impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
+    /// Create a Groth16 proof using randomness `r` and `s` and the provided
+    /// R1CS-to-QAP reduction, running each stage of the prover on the
+    /// thread pools of `pipeline` and computing all MSMs with `backend`.
+    pub fn create_proof_with_pipeline<C, B>(
+        circuit: C,
+        pk: &ProvingKey<E>,
+        r: E::ScalarField,
+        s: E::ScalarField,
+        backend: &B,
+        pipeline: &ProverPipeline,
+    ) -> R1CSResult<Proof<E>>
+    where
+        C: ConstraintSynthesizer<E::ScalarField> + Send,
+        B: MsmBackend<E::G1> + MsmBackend<E::G2> + Sync,
+    {
+        let prover_time = start_timer!(|| "Groth16::Prover pipeline");
+
+        let (matrices, full_assignment) =
+            ProverPipeline::install(&pipeline.synthesis, || -> R1CSResult<Synthesized<_>> {
+                let cs = Self::synthesize(circuit)?;
+                let matrices = cs.to_matrices().ok_or(SynthesisError::AssignmentMissing)?;
+                let prover = cs.borrow().ok_or(SynthesisError::AssignmentMissing)?;
+                let full_assignment = [
+                    prover.instance_assignment.as_slice(),
+                    prover.witness_assignment.as_slice(),
+                ]
+                .concat();
+                Ok((matrices, full_assignment))
+            })?;
+        let num_inputs = matrices.num_instance_variables;
+
+        let h = ProverPipeline::install(&pipeline.qap, || {
+            Self::witness_map_from_matrices_for_key(
+                matrices,
+                &full_assignment,
+                pk,
+                &pipeline.config,
+            )
+        })?;
+
+        let proof = ProverPipeline::install(&pipeline.msm, || {
+            let (h_acc, accumulators) = rayon::join(
+                || Self::h_accumulator(pk, &h, backend),
+                || {
+                    Self::blinded_accumulators(
+                        pk,
+                        r,
+                        s,
+                        &full_assignment[1..num_inputs],
+                        &full_assignment[num_inputs..],
+                        backend,
+                        &mut (),
+                    )
+                },
+            );
+            Self::assemble_proof(accumulators, h_acc)
+        });
+
+        end_timer!(prover_time);
+
+        Ok(proof)
+    }
+
+    /// Create a Groth16 proof that is zero-knowledge like
+    /// [`Self::create_proof_with_pipeline`], sampling randomness for zero
+    /// knowledge via `rng`.
+    pub fn create_random_proof_with_pipeline<C, B>(
+        circuit: C,
+        pk: &ProvingKey<E>,
+        backend: &B,
+        pipeline: &ProverPipeline,
+        rng: &mut impl Rng,
+    ) -> R1CSResult<Proof<E>>
+    where
+        C: ConstraintSynthesizer<E::ScalarField> + Send,
+        B: MsmBackend<E::G1> + MsmBackend<E::G2> + Sync,
+    {
+        let r = E::ScalarField::rand(rng);
+        let s = E::ScalarField::rand(rng);
+
+        Self::create_proof_with_pipeline(circuit, pk, r, s, backend, pipeline)
+    }
+}
@@ -0,0 +1,86 @@
+use crate::hash::hash_compressed;
+use ark_ec::pairing::Pairing;
+use ark_std::collections::BTreeMap;
+
+/// A cache of proof components prepared for use in pairings, keyed by the
+/// hash of the component.
+///
+/// Preparing a `G2` element is a significant part of the cost of a
+/// verification. When related proofs share components, such as a `proof.b`
+/// reused across a session, verifying them with the same preparer computes
+/// the prepared form of each distinct component only once.
+#[derive(Clone, Debug)]
+pub struct ProofPreparer<E: Pairing> {
+    g1: BTreeMap<[u8; 32], E::G1Prepared>,
+    g2: BTreeMap<[u8; 32], E::G2Prepared>,
+    max_entries: usize,
+}
+
+impl<E: Pairing> ProofPreparer<E> {
+    /// Creates an empty preparer holding at most `max_entries` prepared
+    /// elements of each group. When full, an arbitrary entry is evicted.
+    pub fn new(max_entries: usize) -> Self {
+        Self {
+            g1: BTreeMap::new(),
+            g2: BTreeMap::new(),
+            max_entries,
+        }
+    }
+
+    /// Returns the prepared form of the `G1` element `p`, computing and
+    /// caching it if needed.
+    pub fn prepare_g1(&mut self, p: &E::G1Affine) -> E::G1Prepared {
+        Self::prepare(&mut self.g1, self.max_entries, p, |p| (*p).into())
+    }
+
+    /// Returns the prepared form of the `G2` element `p`, computing and
+    /// caching it if needed.
+    pub fn prepare_g2(&mut self, p: &E::G2Affine) -> E::G2Prepared {
+        Self::prepare(&mut self.g2, self.max_entries, p, |p| (*p).into())
+    }
+
+    /// Returns whether the prepared form of the `G2` element `p` is cached.
+    pub fn contains_g2(&self, p: &E::G2Affine) -> bool {
+        self.g2.contains_key(&hash_compressed(p))
+    }
+
+    /// Returns the number of cached prepared elements, in `G1` and `G2`.
+    pub fn len(&self) -> (usize, usize) {
+        (self.g1.len(), self.g2.len())
+    }
+
+    /// Returns whether no prepared elements are cached.
+    pub fn is_empty(&self) -> bool {
+        self.g1.is_empty() && self.g2.is_empty()
+    }
+
+    /// Drops all cached prepared elements.
+    pub fn clear(&mut self) {
+        self.g1.clear();
+        self.g2.clear();
+    }
+
+    fn prepare<A, P>(
+        cache: &mut BTreeMap<[u8; 32], P>,
+        max_entries: usize,
+        p: &A,
+        prepare: impl FnOnce(&A) -> P,
+    ) -> P
+    where
+        A: ark_serialize::CanonicalSerialize,
+        P: Clone,
+    {
+        let key = hash_compressed(p);
+        if let Some(prepared) = cache.get(&key) {
+            return prepared.clone();
+        }
+        let prepared = prepare(p);
+        if max_entries > 0 {
+            if cache.len() >= max_entries {
+                cache.pop_first();
+            }
+            cache.insert(key, prepared.clone());
+        }
+        prepared
+    }
+}
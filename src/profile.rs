@@ -0,0 +1,255 @@
+use crate::{
+    hash::hash_compressed, Groth16, PreparedVerifyingKey, Proof, VerificationError, VerifyingKey,
+};
+use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup};
+use ark_ff::{BigInteger, PrimeField};
+use ark_relations::r1cs::{Result as R1CSResult, SynthesisError};
+use ark_std::{collections::BTreeMap, vec::Vec};
+
+/// The width in bits of the windows of the tables of
+/// [`PreparationProfile::Balanced`].
+pub const BALANCED_WINDOW: usize = 4;
+
+/// The width in bits of the windows of the tables of
+/// [`PreparationProfile::MaxSpeed`].
+pub const MAX_SPEED_WINDOW: usize = 8;
+
+/// What [`prepare_verifying_key_with_profile`] precomputes, trading the
+/// memory of a verifier for the latency of its verifications.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum PreparationProfile {
+    /// Only the pairing elements of [`crate::prepare_verifying_key`], for
+    /// memory-constrained verifiers.
+    #[default]
+    Minimal,
+    /// Also tables of the multiples of each input base, with windows of
+    /// [`BALANCED_WINDOW`] bits, so that aggregating an input takes one
+    /// addition per window instead of a scalar multiplication. The tables
+    /// hold `2^w - 1` elements of `G1` per `w` bits of each input.
+    Balanced,
+    /// Also tables with windows of [`MAX_SPEED_WINDOW`] bits, and caches of
+    /// the static part of the prepared inputs, for latency-critical
+    /// verifiers of proofs of few statements, see
+    /// [`ProfiledVerifyingKey::cache_static_inputs`].
+    MaxSpeed,
+}
+
+impl PreparationProfile {
+    /// Returns the width in bits of the windows of the tables, or `None`
+    /// if the profile precomputes no tables.
+    pub fn window(self) -> Option<usize> {
+        match self {
+            PreparationProfile::Minimal => None,
+            PreparationProfile::Balanced => Some(BALANCED_WINDOW),
+            PreparationProfile::MaxSpeed => Some(MAX_SPEED_WINDOW),
+        }
+    }
+}
+
+/// The multiples `j 2^(w i) base` of a base, for `j` from `1` to `2^w - 1`
+/// and each window `i` of the scalars.
+#[derive(Clone, Debug, PartialEq)]
+struct FixedBaseTable<G: CurveGroup> {
+    window: usize,
+    rows: Vec<Vec<G::Affine>>,
+}
+
+impl<G: CurveGroup> FixedBaseTable<G> {
+    fn new(base: G::Affine, window: usize) -> Self {
+        let num_bits = G::ScalarField::MODULUS_BIT_SIZE as usize;
+        let mut row_base = base.into_group();
+        let rows = (0..num_bits.div_ceil(window))
+            .map(|_| {
+                let mut multiples = Vec::with_capacity((1 << window) - 1);
+                let mut multiple = row_base;
+                for _ in 1..1 << window {
+                    multiples.push(multiple);
+                    multiple += row_base;
+                }
+                row_base = multiple;
+                G::normalize_batch(&multiples)
+            })
+            .collect();
+        Self { window, rows }
+    }
+
+    fn mul(&self, scalar: G::ScalarField) -> G {
+        let bits = scalar.into_bigint().to_bits_le();
+        let mut result = G::zero();
+        for (row, digit_bits) in self.rows.iter().zip(bits.chunks(self.window)) {
+            let digit = digit_bits
+                .iter()
+                .rev()
+                .fold(0usize, |digit, bit| (digit << 1) | usize::from(*bit));
+            if digit != 0 {
+                result += row[digit - 1];
+            }
+        }
+        result
+    }
+
+    fn len(&self) -> usize {
+        self.rows.iter().map(Vec::len).sum()
+    }
+}
+
+/// A prepared verifying key with the precomputations of a
+/// [`PreparationProfile`], created by
+/// [`prepare_verifying_key_with_profile`].
+///
+/// Verifications give the same results under every profile; only their
+/// latency and the memory of the key differ, see
+/// [`Self::precomputed_size`].
+#[derive(Clone, Debug)]
+pub struct ProfiledVerifyingKey<E: Pairing> {
+    pvk: PreparedVerifyingKey<E>,
+    profile: PreparationProfile,
+    tables: Vec<FixedBaseTable<E::G1>>,
+    static_cache: BTreeMap<[u8; 32], E::G1>,
+}
+
+/// Prepares `vk` for use in proof verification, with the precomputations
+/// of `profile`.
+pub fn prepare_verifying_key_with_profile<E: Pairing>(
+    vk: &VerifyingKey<E>,
+    profile: PreparationProfile,
+) -> ProfiledVerifyingKey<E> {
+    ProfiledVerifyingKey::new(crate::prepare_verifying_key(vk), profile)
+}
+
+impl<E: Pairing> ProfiledVerifyingKey<E> {
+    /// Adds the precomputations of `profile` to `pvk`.
+    pub fn new(pvk: PreparedVerifyingKey<E>, profile: PreparationProfile) -> Self {
+        let tables = match profile.window() {
+            Some(window) => pvk
+                .vk
+                .gamma_abc_g1_static
+                .iter()
+                .skip(1)
+                .chain(&pvk.vk.gamma_abc_g1_variable)
+                .map(|base| FixedBaseTable::new(*base, window))
+                .collect(),
+            None => Vec::new(),
+        };
+        Self {
+            pvk,
+            profile,
+            tables,
+            static_cache: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the prepared verifying key.
+    pub fn pvk(&self) -> &PreparedVerifyingKey<E> {
+        &self.pvk
+    }
+
+    /// Returns the prepared verifying key, dropping the precomputations.
+    pub fn into_pvk(self) -> PreparedVerifyingKey<E> {
+        self.pvk
+    }
+
+    /// Returns the profile of the precomputations.
+    pub fn profile(&self) -> PreparationProfile {
+        self.profile
+    }
+
+    /// Returns the number of bytes of the precomputations on top of the
+    /// prepared verifying key, i.e. of the tables and of the cached static
+    /// inputs.
+    pub fn precomputed_size(&self) -> usize {
+        let table_points = self.tables.iter().map(FixedBaseTable::len).sum::<usize>();
+        table_points * core::mem::size_of::<E::G1Affine>()
+            + self.static_cache.len() * (32 + core::mem::size_of::<E::G1>())
+    }
+
+    /// Precomputes the static part of the prepared inputs with
+    /// `static_inputs`, for verifiers of proofs of a few known statements,
+    /// e.g. of swaps in a few pools. Returns whether the profile caches
+    /// static inputs, which only [`PreparationProfile::MaxSpeed`] does.
+    ///
+    /// Fails with [`SynthesisError::MalformedVerifyingKey`] if the key does
+    /// not expect that many static inputs.
+    pub fn cache_static_inputs(&mut self, static_inputs: &[E::ScalarField]) -> R1CSResult<bool> {
+        if self.pvk.vk.gamma_abc_g1_static.is_empty()
+            || static_inputs.len() != self.pvk.num_static_inputs()
+        {
+            return Err(SynthesisError::MalformedVerifyingKey);
+        }
+        if self.profile != PreparationProfile::MaxSpeed {
+            return Ok(false);
+        }
+        let prepared = self.prepare_static_inputs(static_inputs);
+        self.static_cache
+            .insert(hash_compressed(&static_inputs), prepared);
+        Ok(true)
+    }
+
+    /// Returns the number of cached static inputs.
+    pub fn num_cached_static_inputs(&self) -> usize {
+        self.static_cache.len()
+    }
+
+    /// Drops the cached static inputs.
+    pub fn clear_static_cache(&mut self) {
+        self.static_cache.clear();
+    }
+
+    /// Returns the constant term plus the static inputs times their bases.
+    fn prepare_static_inputs(&self, static_inputs: &[E::ScalarField]) -> E::G1 {
+        let mut g_ic = self.pvk.vk.gamma_abc_g1_static[0].into_group();
+        for (table, input) in self.tables.iter().zip(static_inputs) {
+            g_ic += table.mul(*input);
+        }
+        g_ic
+    }
+
+    /// Aggregates the inputs with the `gamma_abc_g1` elements of the key,
+    /// like [`Groth16::prepare_inputs_with_variables`].
+    pub fn prepare_inputs(
+        &self,
+        static_inputs: &[E::ScalarField],
+        variable_inputs: &[E::ScalarField],
+    ) -> Result<E::G1, VerificationError> {
+        if self.tables.is_empty() {
+            return Groth16::<E>::prepare_inputs_with_variables(
+                &self.pvk,
+                static_inputs,
+                variable_inputs,
+            );
+        }
+        if self.pvk.vk.gamma_abc_g1_static.is_empty() {
+            return Err(SynthesisError::MalformedVerifyingKey.into());
+        }
+        self.pvk
+            .check_input_counts(static_inputs.len(), variable_inputs.len())?;
+
+        let cached = if self.static_cache.is_empty() {
+            None
+        } else {
+            self.static_cache.get(&hash_compressed(&static_inputs))
+        };
+        let mut g_ic = match cached {
+            Some(prepared) => *prepared,
+            None => self.prepare_static_inputs(static_inputs),
+        };
+        for (table, input) in self.tables[static_inputs.len()..]
+            .iter()
+            .zip(variable_inputs)
+        {
+            g_ic += table.mul(*input);
+        }
+        Ok(g_ic)
+    }
+
+    /// Verifies `proof` against its static and variable inputs.
+    pub fn verify(
+        &self,
+        proof: &Proof<E>,
+        static_inputs: &[E::ScalarField],
+        variable_inputs: &[E::ScalarField],
+    ) -> R1CSResult<bool> {
+        let prepared_inputs = self.prepare_inputs(static_inputs, variable_inputs)?;
+        Groth16::<E>::verify_proof_with_prepared_inputs(&self.pvk, proof, &prepared_inputs)
+    }
+}
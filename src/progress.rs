@@ -0,0 +1,121 @@
+use crate::prover::PhaseProbe;
+use ark_std::vec::Vec;
+use std::time::{Duration, Instant};
+
+/// The progress of a long computation, such as a proof or the generation
+/// of a key, passed to a [`ProgressObserver`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Progress<P> {
+    /// The current phase.
+    pub phase: P,
+    /// The number of items of the phase computed so far.
+    pub done: usize,
+    /// The number of items of the phase.
+    pub total: usize,
+    /// The time elapsed since the computation started.
+    pub elapsed: Duration,
+    /// The estimated time until the computation ends, extrapolated from the
+    /// throughput measured so far, once there is a measurement.
+    pub remaining: Option<Duration>,
+}
+
+/// Observes the progress of the prover, with phases of type
+/// [`ProverPhase`](crate::prover::ProverPhase), or of key generation, with
+/// phases of type [`SetupPhase`](crate::generator::SetupPhase).
+///
+/// The observer is called when a phase starts, when it ends, and, for the
+/// long phases of key generation, after each chunk of group elements.
+pub trait ProgressObserver<P> {
+    /// Called on progress.
+    fn on_progress(&mut self, progress: &Progress<P>);
+}
+
+impl<P, F: FnMut(&Progress<P>)> ProgressObserver<P> for F {
+    fn on_progress(&mut self, progress: &Progress<P>) {
+        self(progress)
+    }
+}
+
+/// Reports the phases of a computation to a [`ProgressObserver`], with an
+/// estimate of the remaining time from the planned weight of the phases.
+pub(crate) struct ObserverProbe<'a, P, O: ?Sized> {
+    observer: &'a mut O,
+    start: Instant,
+    weights: Vec<(P, u64)>,
+    total_weight: u64,
+    done_weight: u64,
+    weighted_time: Duration,
+    phase_start: Instant,
+}
+
+impl<'a, P: Copy + PartialEq, O: ProgressObserver<P> + ?Sized> ObserverProbe<'a, P, O> {
+    pub(crate) fn new(observer: &'a mut O) -> Self {
+        let start = Instant::now();
+        Self {
+            observer,
+            start,
+            weights: Vec::new(),
+            total_weight: 0,
+            done_weight: 0,
+            weighted_time: Duration::ZERO,
+            phase_start: start,
+        }
+    }
+
+    fn weight(&self, phase: P) -> u64 {
+        self.weights
+            .iter()
+            .find(|(p, _)| *p == phase)
+            .map_or(0, |(_, weight)| *weight)
+    }
+}
+
+impl<P: Copy + PartialEq, O: ProgressObserver<P> + ?Sized> PhaseProbe<P>
+    for ObserverProbe<'_, P, O>
+{
+    fn measure<T>(&mut self, phase: P, run: impl FnOnce() -> T) -> T {
+        self.advance(phase, 0, 1);
+        let result = run();
+        self.advance(phase, 1, 1);
+        result
+    }
+
+    fn plan(&mut self, weights: &[(P, u64)]) {
+        self.weights = weights.to_vec();
+        self.total_weight = weights.iter().map(|(_, weight)| weight).sum();
+    }
+
+    fn advance(&mut self, phase: P, done: usize, total: usize) {
+        if done == 0 {
+            self.phase_start = Instant::now();
+        }
+        let weight = self.weight(phase);
+        let phase_time = self.phase_start.elapsed();
+        // Only phases with a weight count toward the measured throughput.
+        let (weighted_time, done_weight) = if weight == 0 {
+            (self.weighted_time, self.done_weight)
+        } else {
+            let phase_done = (weight as u128 * done as u128 / total.max(1) as u128) as u64;
+            (
+                self.weighted_time + phase_time,
+                self.done_weight + phase_done,
+            )
+        };
+        let remaining = (done_weight > 0).then(|| {
+            let remaining_weight = self.total_weight.saturating_sub(done_weight);
+            weighted_time.mul_f64(remaining_weight as f64 / done_weight as f64)
+        });
+
+        self.observer.on_progress(&Progress {
+            phase,
+            done,
+            total,
+            elapsed: self.start.elapsed(),
+            remaining,
+        });
+        if done == total {
+            self.weighted_time = weighted_time;
+            self.done_weight = done_weight;
+        }
+    }
+}
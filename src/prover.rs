@@ -1,14 +1,21 @@
-use crate::{r1cs_to_qap::R1CSToQAP, Groth16, Proof, ProvingKey, VerifyingKey};
+use crate::{
+    msm::{mul_secret, CpuMsm, MsmBackend},
+    r1cs_to_qap::R1CSToQAP,
+    rng::{sample_scalar, BlindingFactors, BlindingSource, ProveError, ProverRng, ProverRngError},
+    Groth16, KeyBasis, Proof, ProvingKey, VerifyingKey,
+};
 use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup, VariableBaseMSM};
 use ark_ff::{Field, PrimeField, UniformRand, Zero};
+use ark_poly::EvaluationDomain;
 use ark_poly::GeneralEvaluationDomain;
 use ark_relations::r1cs::{
-    ConstraintMatrices, ConstraintSynthesizer, ConstraintSystem, OptimizationGoal,
-    Result as R1CSResult,
+    ConstraintMatrices, ConstraintSynthesizer, ConstraintSystem, ConstraintSystemRef,
+    OptimizationGoal, Result as R1CSResult, SynthesisError, SynthesisMode,
 };
 use ark_std::rand::Rng;
 use ark_std::{
     cfg_into_iter, cfg_iter,
+    future::Future,
     ops::{AddAssign, Mul},
     vec::Vec,
 };
@@ -16,13 +23,205 @@ use ark_std::{
 #[cfg(feature = "parallel")]
 use rayon::prelude::*;
 
+#[cfg(feature = "arena")]
+use crate::arena::ProverArena;
+
+#[cfg(feature = "std")]
+use crate::progress::{ObserverProbe, ProgressObserver};
+#[cfg(feature = "std")]
+use std::time::{Duration, Instant};
+
 type D<F> = GeneralEvaluationDomain<F>;
 
+/// Options controlling how the prover computes proofs.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ProverConfig {
+    /// Compute the QAP witness with two domain-sized vectors instead of
+    /// three, recycling buffers across the coset FFTs. This lowers the peak
+    /// memory of the witness map by about a third, at the cost of some
+    /// parallelism.
+    pub low_memory: bool,
+}
+
+impl ProverConfig {
+    /// Returns a configuration with [`Self::low_memory`] set.
+    pub fn low_memory() -> Self {
+        Self { low_memory: true }
+    }
+}
+
+/// Statistics of the global allocator, for reporting the memory used by
+/// the prover in a [`ProofReport`].
+///
+/// This crate does not install an allocator: implement this trait over the
+/// counters of a tracking allocator wrapping the system one, or over the
+/// statistics of e.g. jemalloc. The counters are process-wide, so
+/// allocations of other threads running concurrently with the prover are
+/// counted too.
+#[cfg(feature = "std")]
+pub trait AllocatorStats {
+    /// Returns the number of bytes currently allocated.
+    fn allocated(&self) -> usize;
+
+    /// Returns the largest number of bytes allocated at once since the last
+    /// call to [`Self::reset_peak`].
+    fn peak(&self) -> usize;
+
+    /// Resets the peak to the number of bytes currently allocated.
+    fn reset_peak(&self);
+}
+
+/// The time spent and the memory allocated in a phase of the prover.
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PhaseReport {
+    /// The time spent in the phase.
+    pub time: Duration,
+    /// The peak number of bytes allocated during the phase on top of those
+    /// allocated when it started, if [`AllocatorStats`] were provided.
+    pub peak_memory: Option<usize>,
+}
+
+/// The time spent and the memory allocated in each phase of a proof, see
+/// [`Groth16::prove_with_report`]. Phases which did not run, such as `B` in
+/// `G1` for a proof without zero knowledge, are left at their default.
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ProofReport {
+    /// Synthesizing the circuit and inlining its linear combinations.
+    pub synthesis: PhaseReport,
+    /// Computing the QAP witness.
+    pub witness_map: PhaseReport,
+    /// The MSM of the QAP witness with the `h_query`.
+    pub h_msm: PhaseReport,
+    /// The MSM of the witness with the `l_query`.
+    pub l_msm: PhaseReport,
+    /// The MSM of the assignment with the `a_query`.
+    pub a_msm: PhaseReport,
+    /// The MSM of the assignment with the `b_g1_query`.
+    pub b_g1_msm: PhaseReport,
+    /// The MSM of the assignment with the `b_g2_query`.
+    pub b_g2_msm: PhaseReport,
+    /// Combining the accumulators into the proof.
+    pub assembly: PhaseReport,
+}
+
+#[cfg(feature = "std")]
+impl ProofReport {
+    fn phases(&self) -> [&PhaseReport; 8] {
+        [
+            &self.synthesis,
+            &self.witness_map,
+            &self.h_msm,
+            &self.l_msm,
+            &self.a_msm,
+            &self.b_g1_msm,
+            &self.b_g2_msm,
+            &self.assembly,
+        ]
+    }
+
+    fn phase_mut(&mut self, phase: ProverPhase) -> &mut PhaseReport {
+        match phase {
+            ProverPhase::Synthesis => &mut self.synthesis,
+            ProverPhase::WitnessMap => &mut self.witness_map,
+            ProverPhase::HMsm => &mut self.h_msm,
+            ProverPhase::LMsm => &mut self.l_msm,
+            ProverPhase::AMsm => &mut self.a_msm,
+            ProverPhase::BG1Msm => &mut self.b_g1_msm,
+            ProverPhase::BG2Msm => &mut self.b_g2_msm,
+            ProverPhase::Assembly => &mut self.assembly,
+        }
+    }
+
+    /// Returns the total time spent in the phases.
+    pub fn total_time(&self) -> Duration {
+        self.phases().iter().map(|phase| phase.time).sum()
+    }
+
+    /// Returns the largest peak memory of the phases, if
+    /// [`AllocatorStats`] were provided.
+    pub fn peak_memory(&self) -> Option<usize> {
+        self.phases()
+            .iter()
+            .filter_map(|phase| phase.peak_memory)
+            .max()
+    }
+}
+
+/// A phase of the prover.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProverPhase {
+    /// Synthesizing the circuit and inlining its linear combinations.
+    Synthesis,
+    /// Computing the QAP witness.
+    WitnessMap,
+    /// The MSM of the QAP witness with the `h_query`.
+    HMsm,
+    /// The MSM of the witness with the `l_query`.
+    LMsm,
+    /// The MSM of the assignment with the `a_query`.
+    AMsm,
+    /// The MSM of the assignment with the `b_g1_query`.
+    BG1Msm,
+    /// The MSM of the assignment with the `b_g2_query`.
+    BG2Msm,
+    /// Combining the accumulators into the proof.
+    Assembly,
+}
+
+/// Observes the phases of the prover, or of key generation. The unit type
+/// ignores them.
+pub(crate) trait PhaseProbe<P = ProverPhase> {
+    /// Runs `phase`.
+    fn measure<T>(&mut self, phase: P, run: impl FnOnce() -> T) -> T;
+
+    /// Announces the relative cost of the phases still to run.
+    fn plan(&mut self, _weights: &[(P, u64)]) {}
+
+    /// Reports that `done` of the `total` items of `phase` are computed,
+    /// for phases which are not run by [`Self::measure`].
+    fn advance(&mut self, _phase: P, _done: usize, _total: usize) {}
+}
+
+impl<P> PhaseProbe<P> for () {
+    fn measure<T>(&mut self, _: P, run: impl FnOnce() -> T) -> T {
+        run()
+    }
+}
+
+/// Records the phases of the prover into a [`ProofReport`].
+#[cfg(feature = "std")]
+struct ReportProbe<'a> {
+    stats: Option<&'a dyn AllocatorStats>,
+    report: ProofReport,
+}
+
+#[cfg(feature = "std")]
+impl PhaseProbe for ReportProbe<'_> {
+    fn measure<T>(&mut self, phase: ProverPhase, run: impl FnOnce() -> T) -> T {
+        let baseline = self.stats.map(|stats| {
+            stats.reset_peak();
+            stats.allocated()
+        });
+        let start = Instant::now();
+        let result = run();
+        let time = start.elapsed();
+        let peak_memory = self
+            .stats
+            .zip(baseline)
+            .map(|(stats, baseline)| stats.peak().saturating_sub(baseline));
+        *self.report.phase_mut(phase) = PhaseReport { time, peak_memory };
+        result
+    }
+}
+
 impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
     /// Create a Groth16 proof using randomness `r` and `s` and
     /// the provided R1CS-to-QAP reduction, using the provided
     /// R1CS constraint matrices.
     #[inline]
+    #[allow(clippy::too_many_arguments)]
     pub fn create_proof_with_reduction_and_matrices(
         pk: &ProvingKey<E>,
         r: E::ScalarField,
@@ -35,46 +234,148 @@ impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
     ) -> R1CSResult<Proof<E>> {
         let prover_time = start_timer!(|| "Groth16::Prover");
         let witness_map_time = start_timer!(|| "R1CS to QAP witness map");
-        let h = QAP::witness_map_from_matrices::<E::ScalarField, D<E::ScalarField>>(
-            matrices,
-            num_inputs,
-            num_constraints,
-            full_assignment,
-        )?;
+        let h = match pk.h_basis {
+            KeyBasis::Monomial => QAP::witness_map_from_matrices::<
+                E::ScalarField,
+                D<E::ScalarField>,
+            >(
+                matrices, num_inputs, num_constraints, full_assignment
+            )?,
+            KeyBasis::Lagrange => QAP::witness_map_evaluations_from_matrices::<
+                E::ScalarField,
+                D<E::ScalarField>,
+            >(
+                matrices, num_inputs, num_constraints, full_assignment
+            )?,
+        };
         end_timer!(witness_map_time);
         let input_assignment = &full_assignment[1..num_static_inputs];
         let aux_assignment = &full_assignment[num_inputs..];
-        let proof =
-            Self::create_proof_with_assignment(pk, r, s, &h, input_assignment, aux_assignment)?;
+        let proof = Self::create_proof_with_assignment(
+            pk,
+            r,
+            s,
+            &h,
+            input_assignment,
+            aux_assignment,
+            &CpuMsm,
+        )?;
         end_timer!(prover_time);
 
         Ok(proof)
     }
 
+    /// Create a Groth16 proof that is zero-knowledge for the constraint
+    /// system `matrices`, without synthesizing a circuit. `full_assignment`
+    /// contains the instance assignment (starting with the constant `1`)
+    /// followed by the witness assignment. This method samples randomness
+    /// for zero knowledge via `rng`.
+    ///
+    /// Returns [`SynthesisError::AssignmentMissing`] if the assignment does
+    /// not have one value per variable, and
+    /// [`SynthesisError::MalformedVerifyingKey`] if `pk` was not generated
+    /// for a constraint system of this shape.
+    pub fn prove_with_matrices(
+        pk: &ProvingKey<E>,
+        matrices: &ConstraintMatrices<E::ScalarField>,
+        full_assignment: &[E::ScalarField],
+        rng: &mut impl Rng,
+    ) -> R1CSResult<Proof<E>> {
+        let num_inputs = matrices.num_instance_variables;
+        let num_variables = num_inputs + matrices.num_witness_variables;
+        if full_assignment.len() != num_variables {
+            return Err(SynthesisError::AssignmentMissing);
+        }
+        if pk.a_query.len() != num_variables || pk.l_query.len() != matrices.num_witness_variables {
+            return Err(SynthesisError::MalformedVerifyingKey);
+        }
+
+        let r = E::ScalarField::rand(rng);
+        let s = E::ScalarField::rand(rng);
+
+        // All instance variables are passed to the prover, whether the key
+        // treats them as static or variable inputs.
+        Self::create_proof_with_reduction_and_matrices(
+            pk,
+            r,
+            s,
+            matrices,
+            num_inputs,
+            num_inputs,
+            matrices.num_constraints,
+            full_assignment,
+        )
+    }
+
     #[inline]
-    fn create_proof_with_assignment(
+    fn create_proof_with_assignment<B>(
         pk: &ProvingKey<E>,
         r: E::ScalarField,
         s: E::ScalarField,
         h: &[E::ScalarField],
         input_assignment: &[E::ScalarField],
         aux_assignment: &[E::ScalarField],
-    ) -> R1CSResult<Proof<E>> {
-        let c_acc_time = start_timer!(|| "Compute C");
+        backend: &B,
+    ) -> R1CSResult<Proof<E>>
+    where
+        B: MsmBackend<E::G1> + MsmBackend<E::G2>,
+    {
+        let h_acc = Self::h_accumulator(pk, h, backend);
+        let accumulators = Self::blinded_accumulators(
+            pk,
+            r,
+            s,
+            input_assignment,
+            aux_assignment,
+            backend,
+            &mut (),
+        );
+
+        Ok(Self::assemble_proof(accumulators, h_acc))
+    }
+
+    /// Computes the MSM of the QAP witness `h` with the `h_query`.
+    pub(crate) fn h_accumulator<B>(pk: &ProvingKey<E>, h: &[E::ScalarField], backend: &B) -> E::G1
+    where
+        B: MsmBackend<E::G1>,
+    {
+        let h_acc_time = start_timer!(|| "Compute H");
         let h_assignment = cfg_into_iter!(h)
             .map(|s| s.into_bigint())
             .collect::<Vec<_>>();
-        let h_acc = E::G1::msm_bigint(&pk.h_query, &h_assignment);
-        drop(h_assignment);
+        let h_acc = backend.msm_bigint(&pk.h_query, &h_assignment);
+        end_timer!(h_acc_time);
+        h_acc
+    }
 
-        // Compute C
-        let aux_assignment = cfg_iter!(aux_assignment)
-            .map(|s| s.into_bigint())
-            .collect::<Vec<_>>();
+    /// Computes `A`, `B` and `C` without the contribution of the QAP
+    /// witness to `C`, which is added by [`Self::assemble_proof`], reporting
+    /// each MSM to `probe`.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn blinded_accumulators<B>(
+        pk: &ProvingKey<E>,
+        r: E::ScalarField,
+        s: E::ScalarField,
+        input_assignment: &[E::ScalarField],
+        aux_assignment: &[E::ScalarField],
+        backend: &B,
+        probe: &mut impl PhaseProbe,
+    ) -> (E::G1, E::G2, E::G1)
+    where
+        B: MsmBackend<E::G1> + MsmBackend<E::G2>,
+    {
+        let c_acc_time = start_timer!(|| "Compute C");
 
-        let l_aux_acc = E::G1::msm_bigint(&pk.l_query, &aux_assignment);
+        // Compute C
+        let (aux_assignment, l_aux_acc) = probe.measure(ProverPhase::LMsm, || {
+            let aux_assignment = cfg_iter!(aux_assignment)
+                .map(|s| s.into_bigint())
+                .collect::<Vec<_>>();
+            let l_aux_acc: E::G1 = backend.msm_bigint(&pk.l_query, &aux_assignment);
+            (aux_assignment, l_aux_acc)
+        });
 
-        let r_s_delta_g1 = pk.delta_g1 * (r * s);
+        let r_s_delta_g1 = mul_secret(pk.delta_g1.into_group(), r * s);
 
         end_timer!(c_acc_time);
 
@@ -88,18 +389,22 @@ impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
 
         // Compute A
         let a_acc_time = start_timer!(|| "Compute A");
-        let r_g1 = pk.delta_g1.mul(r);
+        let r_g1 = mul_secret(pk.delta_g1.into_group(), r);
 
-        let g_a = Self::calculate_coeff(r_g1, &pk.a_query, pk.vk.alpha_g1, &assignment);
+        let g_a = probe.measure(ProverPhase::AMsm, || {
+            Self::calculate_coeff(r_g1, &pk.a_query, pk.vk.alpha_g1, &assignment, backend)
+        });
 
-        let s_g_a = g_a * &s;
+        let s_g_a = mul_secret(g_a, s);
         end_timer!(a_acc_time);
 
         // Compute B in G1 if needed
         let g1_b = if !r.is_zero() {
             let b_g1_acc_time = start_timer!(|| "Compute B in G1");
-            let s_g1 = pk.delta_g1.mul(s);
-            let g1_b = Self::calculate_coeff(s_g1, &pk.b_g1_query, pk.beta_g1, &assignment);
+            let s_g1 = mul_secret(pk.delta_g1.into_group(), s);
+            let g1_b = probe.measure(ProverPhase::BG1Msm, || {
+                Self::calculate_coeff(s_g1, &pk.b_g1_query, pk.beta_g1, &assignment, backend)
+            });
 
             end_timer!(b_g1_acc_time);
 
@@ -110,26 +415,38 @@ impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
 
         // Compute B in G2
         let b_g2_acc_time = start_timer!(|| "Compute B in G2");
-        let s_g2 = pk.vk.delta_g2.mul(s);
-        let g2_b = Self::calculate_coeff(s_g2, &pk.b_g2_query, pk.vk.beta_g2, &assignment);
-        let r_g1_b = g1_b * &r;
+        let s_g2 = mul_secret(pk.vk.delta_g2.into_group(), s);
+        let g2_b = probe.measure(ProverPhase::BG2Msm, || {
+            Self::calculate_coeff(s_g2, &pk.b_g2_query, pk.vk.beta_g2, &assignment, backend)
+        });
+        let r_g1_b = mul_secret(g1_b, r);
         drop(assignment);
 
         end_timer!(b_g2_acc_time);
 
-        let c_time = start_timer!(|| "Finish C");
         let mut g_c = s_g_a;
         g_c += &r_g1_b;
         g_c -= &r_s_delta_g1;
         g_c += &l_aux_acc;
+
+        (g_a, g2_b, g_c)
+    }
+
+    /// Adds the contribution `h_acc` of the QAP witness to the accumulators
+    /// computed by [`Self::blinded_accumulators`].
+    pub(crate) fn assemble_proof(
+        (g_a, g2_b, mut g_c): (E::G1, E::G2, E::G1),
+        h_acc: E::G1,
+    ) -> Proof<E> {
+        let c_time = start_timer!(|| "Finish C");
         g_c += &h_acc;
         end_timer!(c_time);
 
-        Ok(Proof {
+        Proof {
             a: g_a.into_affine(),
             b: g2_b.into_affine(),
             c: g_c.into_affine(),
-        })
+        }
     }
 
     /// Create a Groth16 proof that is zero-knowledge using the provided
@@ -150,6 +467,26 @@ impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
         Self::create_proof_with_reduction(circuit, pk, r, s)
     }
 
+    /// Create a Groth16 proof that is zero-knowledge using the provided
+    /// R1CS-to-QAP reduction, sampling the randomness for zero knowledge from
+    /// `rng`. Fails without proving if `rng` cannot produce fresh
+    /// randomness, e.g. after a fork of a [`ForkDetectingRng`].
+    ///
+    /// [`ForkDetectingRng`]: crate::rng::ForkDetectingRng
+    pub fn prove_with_rng<C>(
+        pk: &ProvingKey<E>,
+        circuit: C,
+        rng: &mut impl ProverRng,
+    ) -> Result<Proof<E>, ProveError>
+    where
+        C: ConstraintSynthesizer<E::ScalarField>,
+    {
+        let r = sample_scalar(rng)?;
+        let s = sample_scalar(rng)?;
+
+        Ok(Self::create_proof_with_reduction(circuit, pk, r, s)?)
+    }
+
     /// Create a Groth16 proof that is *not* zero-knowledge with the provided
     /// R1CS-to-QAP reduction.
     #[inline]
@@ -181,8 +518,136 @@ impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
         E: Pairing,
         C: ConstraintSynthesizer<E::ScalarField>,
         QAP: R1CSToQAP,
+    {
+        Self::create_proof_with_reduction_and_backend(circuit, pk, r, s, &CpuMsm)
+    }
+
+    /// Create a Groth16 proof that is zero-knowledge using the provided
+    /// R1CS-to-QAP reduction, computing all MSMs with `backend`.
+    /// This method samples randomness for zero knowledges via `rng`.
+    #[inline]
+    pub fn create_random_proof_with_backend<C, B>(
+        circuit: C,
+        pk: &ProvingKey<E>,
+        backend: &B,
+        rng: &mut impl Rng,
+    ) -> R1CSResult<Proof<E>>
+    where
+        C: ConstraintSynthesizer<E::ScalarField>,
+        B: MsmBackend<E::G1> + MsmBackend<E::G2>,
+    {
+        let r = E::ScalarField::rand(rng);
+        let s = E::ScalarField::rand(rng);
+
+        Self::create_proof_with_reduction_and_backend(circuit, pk, r, s, backend)
+    }
+
+    /// Create a Groth16 proof using randomness `r` and `s` and the provided
+    /// R1CS-to-QAP reduction, computing all MSMs with `backend`.
+    pub fn create_proof_with_reduction_and_backend<C, B>(
+        circuit: C,
+        pk: &ProvingKey<E>,
+        r: E::ScalarField,
+        s: E::ScalarField,
+        backend: &B,
+    ) -> R1CSResult<Proof<E>>
+    where
+        C: ConstraintSynthesizer<E::ScalarField>,
+        B: MsmBackend<E::G1> + MsmBackend<E::G2>,
+    {
+        Self::create_proof_with_config(circuit, pk, r, s, backend, &ProverConfig::default())
+    }
+
+    /// Create a Groth16 proof using randomness `r` and `s` and the provided
+    /// R1CS-to-QAP reduction, computing all MSMs with `backend` and following
+    /// `config`.
+    pub fn create_proof_with_config<C, B>(
+        circuit: C,
+        pk: &ProvingKey<E>,
+        r: E::ScalarField,
+        s: E::ScalarField,
+        backend: &B,
+        config: &ProverConfig,
+    ) -> R1CSResult<Proof<E>>
+    where
+        C: ConstraintSynthesizer<E::ScalarField>,
+        B: MsmBackend<E::G1> + MsmBackend<E::G2>,
+    {
+        Self::create_proof_and_inputs(circuit, pk, r, s, backend, config).map(|(proof, _)| proof)
+    }
+
+    /// Create a Groth16 proof that is zero-knowledge, sampling randomness via
+    /// `rng`, and return it together with the public inputs assigned by the
+    /// synthesized circuit. Verifying against these inputs guarantees that
+    /// the verifier checks exactly the statement that was proven.
+    #[inline]
+    pub fn prove_and_output_inputs<C>(
+        pk: &ProvingKey<E>,
+        circuit: C,
+        rng: &mut impl Rng,
+    ) -> R1CSResult<(Proof<E>, Vec<E::ScalarField>)>
+    where
+        C: ConstraintSynthesizer<E::ScalarField>,
+    {
+        let r = E::ScalarField::rand(rng);
+        let s = E::ScalarField::rand(rng);
+
+        Self::create_proof_and_inputs(circuit, pk, r, s, &CpuMsm, &ProverConfig::default())
+    }
+
+    fn create_proof_and_inputs<C, B>(
+        circuit: C,
+        pk: &ProvingKey<E>,
+        r: E::ScalarField,
+        s: E::ScalarField,
+        backend: &B,
+        config: &ProverConfig,
+    ) -> R1CSResult<(Proof<E>, Vec<E::ScalarField>)>
+    where
+        C: ConstraintSynthesizer<E::ScalarField>,
+        B: MsmBackend<E::G1> + MsmBackend<E::G2>,
     {
         let prover_time = start_timer!(|| "Groth16::Prover");
+        let (cs, h) = Self::synthesize_and_witness_map(circuit, pk, config)?;
+
+        let prover = cs.borrow().unwrap();
+        let proof = Self::create_proof_with_assignment(
+            pk,
+            r,
+            s,
+            &h,
+            &prover.instance_assignment[1..],
+            &prover.witness_assignment,
+            backend,
+        )?;
+
+        end_timer!(prover_time);
+
+        Ok((proof, prover.instance_assignment[1..].to_vec()))
+    }
+
+    /// Synthesizes `circuit` and computes its QAP witness in the basis of
+    /// the `h_query` of `pk`.
+    #[allow(clippy::type_complexity)]
+    pub(crate) fn synthesize_and_witness_map<C>(
+        circuit: C,
+        pk: &ProvingKey<E>,
+        config: &ProverConfig,
+    ) -> R1CSResult<(ConstraintSystemRef<E::ScalarField>, Vec<E::ScalarField>)>
+    where
+        C: ConstraintSynthesizer<E::ScalarField>,
+    {
+        let cs = Self::synthesize(circuit)?;
+        let h = Self::witness_map_for_key(cs.clone(), pk, config)?;
+        Ok((cs, h))
+    }
+
+    /// Synthesizes `circuit` with its assignment and inlines its linear
+    /// combinations.
+    pub(crate) fn synthesize<C>(circuit: C) -> R1CSResult<ConstraintSystemRef<E::ScalarField>>
+    where
+        C: ConstraintSynthesizer<E::ScalarField>,
+    {
         let cs = ConstraintSystem::new_ref();
 
         // Set the optimization goal
@@ -198,18 +663,248 @@ impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
         cs.finalize();
         end_timer!(lc_time);
 
+        Ok(cs)
+    }
+
+    /// Computes the QAP witness of the synthesized circuit `cs` in the basis
+    /// of the `h_query` of `pk`.
+    pub(crate) fn witness_map_for_key(
+        cs: ConstraintSystemRef<E::ScalarField>,
+        pk: &ProvingKey<E>,
+        config: &ProverConfig,
+    ) -> R1CSResult<Vec<E::ScalarField>> {
         let witness_map_time = start_timer!(|| "R1CS to QAP witness map");
-        let h = QAP::witness_map::<E::ScalarField, D<E::ScalarField>>(cs.clone())?;
+        let h = match (config.low_memory, pk.h_basis) {
+            (true, h_basis) => Self::witness_map_low_memory(cs, h_basis)?,
+            (false, KeyBasis::Monomial) => {
+                QAP::witness_map::<E::ScalarField, D<E::ScalarField>>(cs)?
+            },
+            (false, KeyBasis::Lagrange) => {
+                QAP::witness_map_evaluations::<E::ScalarField, D<E::ScalarField>>(cs)?
+            },
+        };
         end_timer!(witness_map_time);
 
+        Ok(h)
+    }
+
+    /// Create a Groth16 proof that is zero-knowledge, sampling randomness via
+    /// `rng` and following `config`, and report the time spent and, if
+    /// `stats` are provided, the peak memory allocated in each phase of the
+    /// prover, for capacity planning.
+    ///
+    /// The phases run one after the other, so their peaks can be compared
+    /// with the memory available to the prover on top of the proving key.
+    #[cfg(feature = "std")]
+    pub fn prove_with_report<C>(
+        pk: &ProvingKey<E>,
+        circuit: C,
+        rng: &mut impl Rng,
+        config: &ProverConfig,
+        stats: Option<&dyn AllocatorStats>,
+    ) -> R1CSResult<(Proof<E>, ProofReport)>
+    where
+        C: ConstraintSynthesizer<E::ScalarField>,
+    {
+        let r = E::ScalarField::rand(rng);
+        let s = E::ScalarField::rand(rng);
+        let mut probe = ReportProbe {
+            stats,
+            report: ProofReport::default(),
+        };
+
+        let cs = probe.measure(ProverPhase::Synthesis, || Self::synthesize(circuit))?;
+        let h = probe.measure(ProverPhase::WitnessMap, || {
+            Self::witness_map_for_key(cs.clone(), pk, config)
+        })?;
+        let prover = cs.borrow().ok_or(SynthesisError::AssignmentMissing)?;
+        let h_acc = probe.measure(ProverPhase::HMsm, || Self::h_accumulator(pk, &h, &CpuMsm));
+        drop(h);
+        let accumulators = Self::blinded_accumulators(
+            pk,
+            r,
+            s,
+            &prover.instance_assignment[1..],
+            &prover.witness_assignment,
+            &CpuMsm,
+            &mut probe,
+        );
+        let proof = probe.measure(ProverPhase::Assembly, || {
+            Self::assemble_proof(accumulators, h_acc)
+        });
+
+        Ok((proof, probe.report))
+    }
+
+    /// Create a Groth16 proof that is zero-knowledge, sampling randomness via
+    /// `rng` and following `config`, and report the progress of its phases
+    /// to `observer`. The remaining time is estimated from the throughput of
+    /// the MSMs, weighting the MSMs in `G2` as three times as costly.
+    #[cfg(feature = "std")]
+    pub fn prove_with_progress<C>(
+        pk: &ProvingKey<E>,
+        circuit: C,
+        rng: &mut impl Rng,
+        config: &ProverConfig,
+        observer: &mut impl ProgressObserver<ProverPhase>,
+    ) -> R1CSResult<Proof<E>>
+    where
+        C: ConstraintSynthesizer<E::ScalarField>,
+    {
+        let r = E::ScalarField::rand(rng);
+        let s = E::ScalarField::rand(rng);
+        let mut probe = ObserverProbe::new(observer);
+
+        let cs = probe.measure(ProverPhase::Synthesis, || Self::synthesize(circuit))?;
+        probe.plan(&[
+            (ProverPhase::HMsm, pk.h_query.len() as u64),
+            (ProverPhase::LMsm, pk.l_query.len() as u64),
+            (ProverPhase::AMsm, pk.a_query.len() as u64),
+            (ProverPhase::BG1Msm, pk.b_g1_query.len() as u64),
+            (ProverPhase::BG2Msm, 3 * pk.b_g2_query.len() as u64),
+        ]);
+        let h = probe.measure(ProverPhase::WitnessMap, || {
+            Self::witness_map_for_key(cs.clone(), pk, config)
+        })?;
+        let prover = cs.borrow().ok_or(SynthesisError::AssignmentMissing)?;
+        let h_acc = probe.measure(ProverPhase::HMsm, || Self::h_accumulator(pk, &h, &CpuMsm));
+        drop(h);
+        let accumulators = Self::blinded_accumulators(
+            pk,
+            r,
+            s,
+            &prover.instance_assignment[1..],
+            &prover.witness_assignment,
+            &CpuMsm,
+            &mut probe,
+        );
+        Ok(probe.measure(ProverPhase::Assembly, || {
+            Self::assemble_proof(accumulators, h_acc)
+        }))
+    }
+
+    /// Create a Groth16 proof that is zero-knowledge, with the randomness
+    /// for zero knowledge produced by `source`, e.g. a callback to an HSM.
+    /// The blinding factors are only requested once the circuit is
+    /// synthesized, and are zeroized as soon as the proof is computed.
+    pub fn prove_with_blinding<C>(
+        pk: &ProvingKey<E>,
+        circuit: C,
+        source: &mut impl BlindingSource<E::ScalarField>,
+    ) -> Result<Proof<E>, ProveError>
+    where
+        C: ConstraintSynthesizer<E::ScalarField>,
+    {
+        let (cs, h) = Self::synthesize_and_witness_map(circuit, pk, &ProverConfig::default())?;
+        let factors = source.blinding_factors()?;
+        Ok(Self::create_proof_with_blinding(pk, &cs, &h, factors)?)
+    }
+
+    /// Create a Groth16 proof like [`Self::prove_with_blinding`], awaiting
+    /// the blinding factors from `factors`. As futures are lazy, a request
+    /// to an external signer made when `factors` is first polled only
+    /// happens after the circuit is synthesized.
+    ///
+    /// Proving itself is not asynchronous: the returned future blocks the
+    /// executor while synthesizing the circuit and computing the proof.
+    pub async fn prove_with_blinding_async<C, Fut>(
+        pk: &ProvingKey<E>,
+        circuit: C,
+        factors: Fut,
+    ) -> Result<Proof<E>, ProveError>
+    where
+        C: ConstraintSynthesizer<E::ScalarField>,
+        Fut: Future<Output = Result<BlindingFactors<E::ScalarField>, ProverRngError>>,
+    {
+        let (cs, h) = Self::synthesize_and_witness_map(circuit, pk, &ProverConfig::default())?;
+        let factors = factors.await?;
+        Ok(Self::create_proof_with_blinding(pk, &cs, &h, factors)?)
+    }
+
+    fn create_proof_with_blinding(
+        pk: &ProvingKey<E>,
+        cs: &ConstraintSystemRef<E::ScalarField>,
+        h: &[E::ScalarField],
+        factors: BlindingFactors<E::ScalarField>,
+    ) -> R1CSResult<Proof<E>> {
         let prover = cs.borrow().unwrap();
+        Self::create_proof_with_assignment(
+            pk,
+            factors.r,
+            factors.s,
+            h,
+            &prover.instance_assignment[1..],
+            &prover.witness_assignment,
+            &CpuMsm,
+        )
+    }
+
+    /// Create a Groth16 proof using randomness `r` and `s` like
+    /// [`Self::create_proof_with_config`] in low-memory mode, keeping the
+    /// assignment and the QAP witness in the buffers of `arena` so that they
+    /// are reused by subsequent proofs.
+    #[cfg(feature = "arena")]
+    pub fn create_proof_with_arena<C, B>(
+        circuit: C,
+        pk: &ProvingKey<E>,
+        r: E::ScalarField,
+        s: E::ScalarField,
+        backend: &B,
+        arena: &mut ProverArena<E::ScalarField>,
+    ) -> R1CSResult<Proof<E>>
+    where
+        C: ConstraintSynthesizer<E::ScalarField>,
+        B: MsmBackend<E::G1> + MsmBackend<E::G2>,
+    {
+        let prover_time = start_timer!(|| "Groth16::Prover");
+        let cs = ConstraintSystem::new_ref();
+        cs.set_optimization_goal(OptimizationGoal::Constraints);
+
+        let synthesis_time = start_timer!(|| "Constraint synthesis");
+        circuit.generate_constraints(cs.clone())?;
+        debug_assert!(cs.is_satisfied().unwrap());
+        end_timer!(synthesis_time);
+
+        let lc_time = start_timer!(|| "Inlining LCs");
+        cs.finalize();
+        end_timer!(lc_time);
+
+        let witness_map_time = start_timer!(|| "R1CS to QAP witness map");
+        let matrices = cs.to_matrices().ok_or(SynthesisError::AssignmentMissing)?;
+        let num_inputs = cs.num_instance_variables();
+        let num_constraints = cs.num_constraints();
+        let prover = cs.borrow().ok_or(SynthesisError::AssignmentMissing)?;
+
+        arena.full_assignment.clear();
+        arena
+            .full_assignment
+            .extend_from_slice(&prover.instance_assignment);
+        arena
+            .full_assignment
+            .extend_from_slice(&prover.witness_assignment);
+        QAP::witness_map_evaluations_into::<E::ScalarField, D<E::ScalarField>>(
+            &matrices,
+            num_inputs,
+            num_constraints,
+            &arena.full_assignment,
+            &mut arena.h,
+            &mut arena.scratch,
+        )?;
+        drop(matrices);
+        if pk.h_basis == KeyBasis::Monomial {
+            QAP::witness_coset::<E::ScalarField, D<E::ScalarField>>(num_constraints + num_inputs)?
+                .ifft_in_place(&mut arena.h);
+        }
+        end_timer!(witness_map_time);
+
         let proof = Self::create_proof_with_assignment(
             pk,
             r,
             s,
-            &h,
+            &arena.h,
             &prover.instance_assignment[1..],
             &prover.witness_assignment,
+            backend,
         )?;
 
         end_timer!(prover_time);
@@ -217,6 +912,123 @@ impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
         Ok(proof)
     }
 
+    /// Computes the QAP witness for a key with the given `h_basis` using
+    /// [`R1CSToQAP::witness_map_evaluations_from_matrices_low_memory`]. The
+    /// final inverse FFT, if any, is done in place.
+    fn witness_map_low_memory(
+        cs: ConstraintSystemRef<E::ScalarField>,
+        h_basis: KeyBasis,
+    ) -> R1CSResult<Vec<E::ScalarField>> {
+        let matrices = cs.to_matrices().ok_or(SynthesisError::AssignmentMissing)?;
+        let num_inputs = cs.num_instance_variables();
+        let num_constraints = cs.num_constraints();
+        let full_assignment = {
+            let prover = cs.borrow().ok_or(SynthesisError::AssignmentMissing)?;
+            [
+                prover.instance_assignment.as_slice(),
+                prover.witness_assignment.as_slice(),
+            ]
+            .concat()
+        };
+
+        let mut h = QAP::witness_map_evaluations_from_matrices_low_memory::<
+            E::ScalarField,
+            D<E::ScalarField>,
+        >(&matrices, num_inputs, num_constraints, &full_assignment)?;
+        drop(matrices);
+        drop(full_assignment);
+
+        if h_basis == KeyBasis::Monomial {
+            QAP::witness_coset::<E::ScalarField, D<E::ScalarField>>(num_constraints + num_inputs)?
+                .ifft_in_place(&mut h);
+        }
+
+        Ok(h)
+    }
+
+    #[cfg(feature = "parallel")]
+    fn witness_map_low_memory_from_matrices(
+        matrices: ConstraintMatrices<E::ScalarField>,
+        num_inputs: usize,
+        num_constraints: usize,
+        full_assignment: &[E::ScalarField],
+        h_basis: KeyBasis,
+    ) -> R1CSResult<Vec<E::ScalarField>> {
+        let mut h = QAP::witness_map_evaluations_from_matrices_low_memory::<
+            E::ScalarField,
+            D<E::ScalarField>,
+        >(&matrices, num_inputs, num_constraints, full_assignment)?;
+        drop(matrices);
+
+        if h_basis == KeyBasis::Monomial {
+            QAP::witness_coset::<E::ScalarField, D<E::ScalarField>>(num_constraints + num_inputs)?
+                .ifft_in_place(&mut h);
+        }
+
+        Ok(h)
+    }
+
+    /// Computes the QAP witness of the constraint system `matrices` for
+    /// `full_assignment`, in the basis of the `h_query` of `pk`. Consumes
+    /// the matrices, so that the low-memory mode can free them early.
+    #[cfg(feature = "parallel")]
+    pub(crate) fn witness_map_from_matrices_for_key(
+        matrices: ConstraintMatrices<E::ScalarField>,
+        full_assignment: &[E::ScalarField],
+        pk: &ProvingKey<E>,
+        config: &ProverConfig,
+    ) -> R1CSResult<Vec<E::ScalarField>> {
+        let num_inputs = matrices.num_instance_variables;
+        let num_constraints = matrices.num_constraints;
+        let witness_map_time = start_timer!(|| "R1CS to QAP witness map");
+        let h = match (config.low_memory, pk.h_basis) {
+            (true, h_basis) => Self::witness_map_low_memory_from_matrices(
+                matrices,
+                num_inputs,
+                num_constraints,
+                full_assignment,
+                h_basis,
+            )?,
+            (false, KeyBasis::Monomial) => {
+                QAP::witness_map_from_matrices::<E::ScalarField, D<E::ScalarField>>(
+                    &matrices,
+                    num_inputs,
+                    num_constraints,
+                    full_assignment,
+                )?
+            },
+            (false, KeyBasis::Lagrange) => {
+                QAP::witness_map_evaluations_from_matrices::<E::ScalarField, D<E::ScalarField>>(
+                    &matrices,
+                    num_inputs,
+                    num_constraints,
+                    full_assignment,
+                )?
+            },
+        };
+        end_timer!(witness_map_time);
+
+        Ok(h)
+    }
+
+    /// Synthesizes `circuit` without building the constraint matrices and
+    /// returns its public inputs, in the order expected by the verifier.
+    /// This lets callers verify against exactly the inputs assigned by the
+    /// circuit, rather than recomputing them by hand.
+    pub fn public_inputs<C>(circuit: C) -> R1CSResult<Vec<E::ScalarField>>
+    where
+        C: ConstraintSynthesizer<E::ScalarField>,
+    {
+        let cs = ConstraintSystem::new_ref();
+        cs.set_mode(SynthesisMode::Prove {
+            construct_matrices: false,
+        });
+        circuit.generate_constraints(cs.clone())?;
+
+        let prover = cs.borrow().ok_or(SynthesisError::AssignmentMissing)?;
+        Ok(prover.instance_assignment[1..].to_vec())
+    }
+
     /// Given a Groth16 proof, returns a fresh proof of the same statement. For a proof π of a
     /// statement S, the output of the non-deterministic procedure `rerandomize_proof(π)` is
     /// statistically indistinguishable from a fresh honest proof of S. For more info, see theorem 3 of
@@ -250,17 +1062,18 @@ impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
         }
     }
 
-    fn calculate_coeff<G: AffineRepr>(
+    pub(crate) fn calculate_coeff<G: AffineRepr, B: MsmBackend<G::Group>>(
         initial: G::Group,
         query: &[G],
         vk_param: G,
         assignment: &[<G::ScalarField as PrimeField>::BigInt],
+        backend: &B,
     ) -> G::Group
     where
         G::Group: VariableBaseMSM<MulBase = G>,
     {
         let el = query[0];
-        let acc = G::Group::msm_bigint(&query[1..], assignment);
+        let acc = backend.msm_bigint(&query[1..], assignment);
 
         let mut res = initial;
         res.add_assign(&el);
@@ -4,7 +4,8 @@ use ark_std::{cfg_iter, cfg_iter_mut, vec};
 
 use crate::Vec;
 use ark_relations::r1cs::{
-    ConstraintMatrices, ConstraintSystemRef, Result as R1CSResult, SynthesisError,
+    ConstraintMatrices, ConstraintSystem, ConstraintSystemRef, LinearCombination,
+    Result as R1CSResult, SynthesisError, SynthesisMode, Variable,
 };
 use core::ops::{AddAssign, Deref};
 
@@ -48,11 +49,55 @@ where
 /// Quadratic Arithmetic Programs (QAPs).
 pub trait R1CSToQAP {
     /// Computes a QAP instance corresponding to the R1CS instance defined by `cs`.
+    #[allow(clippy::type_complexity)]
     fn instance_map_with_evaluation<F: PrimeField, D: EvaluationDomain<F>>(
         cs: ConstraintSystemRef<F>,
         t: &F,
     ) -> Result<(Vec<F>, Vec<F>, Vec<F>, F, usize, usize), SynthesisError>;
 
+    /// Computes a QAP instance corresponding to the R1CS instance defined by
+    /// `matrices`.
+    ///
+    /// The default implementation rebuilds a constraint system enforcing the
+    /// rows of `matrices` and passes it to
+    /// [`Self::instance_map_with_evaluation`]; reductions which can work on
+    /// the matrices directly should override it.
+    #[allow(clippy::type_complexity)]
+    fn instance_map_with_evaluation_from_matrices<F: PrimeField, D: EvaluationDomain<F>>(
+        matrices: &ConstraintMatrices<F>,
+        t: &F,
+    ) -> Result<(Vec<F>, Vec<F>, Vec<F>, F, usize, usize), SynthesisError> {
+        let cs = ConstraintSystem::new_ref();
+        cs.set_mode(SynthesisMode::Setup);
+        for _ in 1..matrices.num_instance_variables {
+            cs.new_input_variable(|| Ok(F::zero()))?;
+        }
+        for _ in 0..matrices.num_witness_variables {
+            cs.new_witness_variable(|| Ok(F::zero()))?;
+        }
+
+        let num_instance_variables = matrices.num_instance_variables;
+        let lc = |row: &[(F, usize)]| {
+            LinearCombination(
+                row.iter()
+                    .map(|&(coeff, index)| {
+                        let variable = match index {
+                            0 => Variable::One,
+                            i if i < num_instance_variables => Variable::Instance(i),
+                            i => Variable::Witness(i - num_instance_variables),
+                        };
+                        (coeff, variable)
+                    })
+                    .collect(),
+            )
+        };
+        for ((a, b), c) in matrices.a.iter().zip(&matrices.b).zip(&matrices.c) {
+            cs.enforce_constraint(lc(a), lc(b), lc(c))?;
+        }
+
+        Self::instance_map_with_evaluation::<F, D>(cs, t)
+    }
+
     #[inline]
     /// Computes a QAP witness corresponding to the R1CS witness defined by `cs`.
     fn witness_map<F: PrimeField, D: EvaluationDomain<F>>(
@@ -79,6 +124,34 @@ pub trait R1CSToQAP {
         )
     }
 
+    #[inline]
+    /// Computes the evaluations of the QAP witness corresponding to the R1CS
+    /// witness defined by `cs` over the coset returned by
+    /// [`Self::witness_coset`].
+    fn witness_map_evaluations<F: PrimeField, D: EvaluationDomain<F>>(
+        prover: ConstraintSystemRef<F>,
+    ) -> Result<Vec<F>, SynthesisError> {
+        let matrices = prover.to_matrices().unwrap();
+        let num_inputs = prover.num_instance_variables();
+        let num_constraints = prover.num_constraints();
+
+        let cs = prover.borrow().unwrap();
+        let prover = cs.deref();
+
+        let full_assignment = [
+            prover.instance_assignment.as_slice(),
+            prover.witness_assignment.as_slice(),
+        ]
+        .concat();
+
+        Self::witness_map_evaluations_from_matrices::<F, D>(
+            &matrices,
+            num_inputs,
+            num_constraints,
+            &full_assignment,
+        )
+    }
+
     /// Computes a QAP witness corresponding to the R1CS witness defined by `cs`.
     fn witness_map_from_matrices<F: PrimeField, D: EvaluationDomain<F>>(
         matrices: &ConstraintMatrices<F>,
@@ -87,6 +160,86 @@ pub trait R1CSToQAP {
         full_assignment: &[F],
     ) -> R1CSResult<Vec<F>>;
 
+    /// Computes the evaluations of the QAP witness corresponding to the R1CS
+    /// witness defined by `full_assignment` over the coset returned by
+    /// [`Self::witness_coset`].
+    ///
+    /// The default implementation evaluates the output of
+    /// [`Self::witness_map_from_matrices`]; reductions which compute these
+    /// evaluations along the way should override it.
+    fn witness_map_evaluations_from_matrices<F: PrimeField, D: EvaluationDomain<F>>(
+        matrices: &ConstraintMatrices<F>,
+        num_inputs: usize,
+        num_constraints: usize,
+        full_assignment: &[F],
+    ) -> R1CSResult<Vec<F>> {
+        let mut h = Self::witness_map_from_matrices::<F, D>(
+            matrices,
+            num_inputs,
+            num_constraints,
+            full_assignment,
+        )?;
+        let coset_domain = Self::witness_coset::<F, D>(num_constraints + num_inputs)?;
+        h.resize(coset_domain.size(), F::zero());
+        coset_domain.fft_in_place(&mut h);
+        Ok(h)
+    }
+
+    /// Like [`Self::witness_map_evaluations_from_matrices`], but keeping
+    /// fewer domain-sized vectors alive at once, at the cost of some speed.
+    ///
+    /// The default implementation falls back to
+    /// [`Self::witness_map_evaluations_from_matrices`].
+    fn witness_map_evaluations_from_matrices_low_memory<F: PrimeField, D: EvaluationDomain<F>>(
+        matrices: &ConstraintMatrices<F>,
+        num_inputs: usize,
+        num_constraints: usize,
+        full_assignment: &[F],
+    ) -> R1CSResult<Vec<F>> {
+        Self::witness_map_evaluations_from_matrices::<F, D>(
+            matrices,
+            num_inputs,
+            num_constraints,
+            full_assignment,
+        )
+    }
+
+    /// Like [`Self::witness_map_evaluations_from_matrices_low_memory`], but
+    /// writing the result into `result` and using `scratch` as temporary
+    /// space, so that callers can reuse both buffers across proofs.
+    ///
+    /// The default implementation moves the result of
+    /// [`Self::witness_map_evaluations_from_matrices_low_memory`] into
+    /// `result`.
+    #[allow(clippy::ptr_arg)]
+    fn witness_map_evaluations_into<F: PrimeField, D: EvaluationDomain<F>>(
+        matrices: &ConstraintMatrices<F>,
+        num_inputs: usize,
+        num_constraints: usize,
+        full_assignment: &[F],
+        result: &mut Vec<F>,
+        _scratch: &mut Vec<F>,
+    ) -> R1CSResult<()> {
+        *result = Self::witness_map_evaluations_from_matrices_low_memory::<F, D>(
+            matrices,
+            num_inputs,
+            num_constraints,
+            full_assignment,
+        )?;
+        Ok(())
+    }
+
+    /// Returns the coset of the evaluation domain of size at least
+    /// `domain_size` over which [`Self::witness_map_evaluations_from_matrices`]
+    /// evaluates the QAP witness.
+    fn witness_coset<F: PrimeField, D: EvaluationDomain<F>>(
+        domain_size: usize,
+    ) -> Result<D, SynthesisError> {
+        D::new(domain_size)
+            .and_then(|domain| domain.get_coset(F::GENERATOR))
+            .ok_or(SynthesisError::PolynomialDegreeTooLarge)
+    }
+
     /// Computes the exponents that the generator uses to calculate base
     /// elements which the prover later uses to compute `h(x)t(x)/delta`.
     fn h_query_scalars<F: PrimeField, D: EvaluationDomain<F>>(
@@ -95,6 +248,22 @@ pub trait R1CSToQAP {
         zt: F,
         delta_inverse: F,
     ) -> Result<Vec<F>, SynthesisError>;
+
+    /// Computes the exponents that the generator uses to calculate base
+    /// elements which the prover later uses to compute `h(x)t(x)/delta` from
+    /// the evaluations of `h(x)` over [`Self::witness_coset`].
+    fn h_query_lagrange_scalars<F: PrimeField, D: EvaluationDomain<F>>(
+        domain_size: usize,
+        t: F,
+        zt: F,
+        delta_inverse: F,
+    ) -> Result<Vec<F>, SynthesisError> {
+        let coset_domain = Self::witness_coset::<F, D>(domain_size)?;
+        let scalar = zt * delta_inverse;
+        let mut scalars = coset_domain.evaluate_all_lagrange_coefficients(t);
+        cfg_iter_mut!(scalars).for_each(|l_i| *l_i *= &scalar);
+        Ok(scalars)
+    }
 }
 
 /// Computes the R1CS-to-QAP reduction defined in [`libsnark`](https://github.com/scipr-lab/libsnark/blob/2af440246fa2c3d0b1b0a425fb6abd8cc8b9c54d/libsnark/reductions/r1cs_to_qap/r1cs_to_qap.tcc).
@@ -107,8 +276,17 @@ impl R1CSToQAP for LibsnarkReduction {
         cs: ConstraintSystemRef<F>,
         t: &F,
     ) -> R1CSResult<(Vec<F>, Vec<F>, Vec<F>, F, usize, usize)> {
-        let matrices = cs.to_matrices().unwrap();
-        let domain_size = cs.num_constraints() + cs.num_instance_variables();
+        let matrices = cs.to_matrices().ok_or(SynthesisError::AssignmentMissing)?;
+        Self::instance_map_with_evaluation_from_matrices::<F, D>(&matrices, t)
+    }
+
+    #[inline]
+    #[allow(clippy::type_complexity)]
+    fn instance_map_with_evaluation_from_matrices<F: PrimeField, D: EvaluationDomain<F>>(
+        matrices: &ConstraintMatrices<F>,
+        t: &F,
+    ) -> R1CSResult<(Vec<F>, Vec<F>, Vec<F>, F, usize, usize)> {
+        let domain_size = matrices.num_constraints + matrices.num_instance_variables;
         let domain = D::new(domain_size).ok_or(SynthesisError::PolynomialDegreeTooLarge)?;
         let domain_size = domain.size();
 
@@ -119,7 +297,8 @@ impl R1CSToQAP for LibsnarkReduction {
         let u = domain.evaluate_all_lagrange_coefficients(*t);
         end_timer!(coefficients_time);
 
-        let qap_num_variables = (cs.num_instance_variables() - 1) + cs.num_witness_variables();
+        let qap_num_variables =
+            (matrices.num_instance_variables - 1) + matrices.num_witness_variables;
 
         let mut a = vec![F::zero(); qap_num_variables + 1];
         let mut b = vec![F::zero(); qap_num_variables + 1];
@@ -127,12 +306,12 @@ impl R1CSToQAP for LibsnarkReduction {
 
         {
             let start = 0;
-            let end = cs.num_instance_variables();
-            let num_constraints = cs.num_constraints();
+            let end = matrices.num_instance_variables;
+            let num_constraints = matrices.num_constraints;
             a[start..end].copy_from_slice(&u[(start + num_constraints)..(end + num_constraints)]);
         }
 
-        for (i, u_i) in u.iter().enumerate().take(cs.num_constraints()) {
+        for (i, u_i) in u.iter().enumerate().take(matrices.num_constraints) {
             for &(ref coeff, index) in &matrices.a[i] {
                 a[index] += &(*u_i * coeff);
             }
@@ -152,6 +331,25 @@ impl R1CSToQAP for LibsnarkReduction {
         num_inputs: usize,
         num_constraints: usize,
         full_assignment: &[F],
+    ) -> R1CSResult<Vec<F>> {
+        let mut h = Self::witness_map_evaluations_from_matrices::<F, D>(
+            matrices,
+            num_inputs,
+            num_constraints,
+            full_assignment,
+        )?;
+
+        let coset_domain = Self::witness_coset::<F, D>(num_constraints + num_inputs)?;
+        coset_domain.ifft_in_place(&mut h);
+
+        Ok(h)
+    }
+
+    fn witness_map_evaluations_from_matrices<F: PrimeField, D: EvaluationDomain<F>>(
+        matrices: &ConstraintMatrices<F>,
+        num_inputs: usize,
+        num_constraints: usize,
+        full_assignment: &[F],
     ) -> R1CSResult<Vec<F>> {
         let domain =
             D::new(num_constraints + num_inputs).ok_or(SynthesisError::PolynomialDegreeTooLarge)?;
@@ -166,8 +364,8 @@ impl R1CSToQAP for LibsnarkReduction {
             .zip(cfg_iter!(&matrices.a))
             .zip(cfg_iter!(&matrices.b))
             .for_each(|(((a, b), at_i), bt_i)| {
-                *a = evaluate_constraint(&at_i, &full_assignment);
-                *b = evaluate_constraint(&bt_i, &full_assignment);
+                *a = evaluate_constraint(at_i, full_assignment);
+                *b = evaluate_constraint(bt_i, full_assignment);
             });
 
         {
@@ -192,7 +390,7 @@ impl R1CSToQAP for LibsnarkReduction {
         cfg_iter_mut!(c[..num_constraints])
             .enumerate()
             .for_each(|(i, c)| {
-                *c = evaluate_constraint(&matrices.c[i], &full_assignment);
+                *c = evaluate_constraint(&matrices.c[i], full_assignment);
             });
 
         domain.ifft_in_place(&mut c);
@@ -207,11 +405,86 @@ impl R1CSToQAP for LibsnarkReduction {
             *ab_i *= &vanishing_polynomial_over_coset;
         });
 
-        coset_domain.ifft_in_place(&mut ab);
+        Ok(ab)
+    }
 
+    fn witness_map_evaluations_from_matrices_low_memory<F: PrimeField, D: EvaluationDomain<F>>(
+        matrices: &ConstraintMatrices<F>,
+        num_inputs: usize,
+        num_constraints: usize,
+        full_assignment: &[F],
+    ) -> R1CSResult<Vec<F>> {
+        let mut ab = Vec::new();
+        Self::witness_map_evaluations_into::<F, D>(
+            matrices,
+            num_inputs,
+            num_constraints,
+            full_assignment,
+            &mut ab,
+            &mut Vec::new(),
+        )?;
         Ok(ab)
     }
 
+    fn witness_map_evaluations_into<F: PrimeField, D: EvaluationDomain<F>>(
+        matrices: &ConstraintMatrices<F>,
+        num_inputs: usize,
+        num_constraints: usize,
+        full_assignment: &[F],
+        ab: &mut Vec<F>,
+        scratch: &mut Vec<F>,
+    ) -> R1CSResult<()> {
+        let domain =
+            D::new(num_constraints + num_inputs).ok_or(SynthesisError::PolynomialDegreeTooLarge)?;
+        let domain_size = domain.size();
+        let coset_domain = domain.get_coset(F::GENERATOR).unwrap();
+        let zero = F::zero();
+
+        // Only two domain-sized buffers are used: `ab` accumulates the
+        // product and then the result, while `scratch` holds the evaluations
+        // of `b` and then of `c`.
+        ab.clear();
+        ab.resize(domain_size, zero);
+        cfg_iter_mut!(ab[..num_constraints])
+            .zip(cfg_iter!(&matrices.a))
+            .for_each(|(a, at_i)| *a = evaluate_constraint(at_i, full_assignment));
+        ab[num_constraints..num_constraints + num_inputs]
+            .clone_from_slice(&full_assignment[..num_inputs]);
+        domain.ifft_in_place(ab);
+        coset_domain.fft_in_place(ab);
+
+        scratch.clear();
+        scratch.resize(domain_size, zero);
+        cfg_iter_mut!(scratch[..num_constraints])
+            .zip(cfg_iter!(&matrices.b))
+            .for_each(|(b, bt_i)| *b = evaluate_constraint(bt_i, full_assignment));
+        domain.ifft_in_place(scratch);
+        coset_domain.fft_in_place(scratch);
+        cfg_iter_mut!(ab)
+            .zip(cfg_iter!(scratch))
+            .for_each(|(ab_i, b_i)| *ab_i *= b_i);
+
+        cfg_iter_mut!(scratch).for_each(|c| *c = zero);
+        cfg_iter_mut!(scratch[..num_constraints])
+            .zip(cfg_iter!(&matrices.c))
+            .for_each(|(c, ct_i)| *c = evaluate_constraint(ct_i, full_assignment));
+        domain.ifft_in_place(scratch);
+        coset_domain.fft_in_place(scratch);
+
+        let vanishing_polynomial_over_coset = domain
+            .evaluate_vanishing_polynomial(F::GENERATOR)
+            .inverse()
+            .unwrap();
+        cfg_iter_mut!(ab)
+            .zip(cfg_iter!(scratch))
+            .for_each(|(ab_i, c_i)| {
+                *ab_i -= c_i;
+                *ab_i *= &vanishing_polynomial_over_coset;
+            });
+
+        Ok(())
+    }
+
     fn h_query_scalars<F: PrimeField, D: EvaluationDomain<F>>(
         max_power: usize,
         t: F,
@@ -0,0 +1,282 @@
+use crate::{
+    circuits::new_bit, Groth16, PreparedVerifyingKey, Proof, ProvingKey, VerificationError,
+};
+use ark_ec::pairing::Pairing;
+use ark_ff::{BigInteger, PrimeField};
+use ark_relations::{
+    lc,
+    r1cs::{
+        ConstraintSynthesizer, ConstraintSystemRef, LinearCombination, Result as R1CSResult,
+        SynthesisError, Variable,
+    },
+};
+use ark_std::{rand::Rng, vec, vec::Vec};
+
+/// The denominator of the fees of a [`Pool`], in basis points.
+pub const FEE_DENOMINATOR: u64 = 10_000;
+
+/// The number of static inputs of a [`SwapCircuit`]: the parameters of the
+/// pool.
+pub const NUM_STATIC_INPUTS: usize = 3;
+
+/// The number of variable inputs of a [`SwapCircuit`]: the amounts of the
+/// trade.
+pub const NUM_VARIABLE_INPUTS: usize = 2;
+
+/// The number of bits of the increase of the invariant of the pool in a
+/// swap, which is less than `(2^64 * FEE_DENOMINATOR * 2) * 2^64`.
+const SLACK_BITS: usize = 144;
+
+/// The parameters of a constant product pool, known when the proof is
+/// created and thus the static inputs of a [`SwapCircuit`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Pool {
+    /// The reserve of the token sold to the pool.
+    pub reserve_in: u64,
+    /// The reserve of the token bought from the pool.
+    pub reserve_out: u64,
+    /// The fee on the sold amount, in basis points.
+    pub fee_bps: u16,
+}
+
+impl Pool {
+    /// Returns the amount bought for `amount_in`, rounded down, or `None` if
+    /// the pool is empty or the fee exceeds [`FEE_DENOMINATOR`].
+    pub fn amount_out(&self, amount_in: u64) -> Option<u64> {
+        let fee_bps = self.fee_bps as u128;
+        if self.reserve_in == 0 || self.reserve_out == 0 || fee_bps > FEE_DENOMINATOR as u128 {
+            return None;
+        }
+        let amount_in_with_fee = amount_in as u128 * (FEE_DENOMINATOR as u128 - fee_bps);
+        let numerator = amount_in_with_fee * self.reserve_out as u128;
+        let denominator = self.reserve_in as u128 * FEE_DENOMINATOR as u128 + amount_in_with_fee;
+        Some((numerator / denominator) as u64)
+    }
+
+    /// Returns the static inputs of a [`SwapCircuit`] in this pool.
+    pub fn static_inputs<F: PrimeField>(&self) -> Vec<F> {
+        vec![
+            F::from(self.reserve_in),
+            F::from(self.reserve_out),
+            F::from(self.fee_bps as u64),
+        ]
+    }
+}
+
+/// A trade against a [`Pool`], only known to the verifier and thus the
+/// variable inputs of a [`SwapCircuit`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Trade {
+    /// The amount sold to the pool.
+    pub amount_in: u64,
+    /// The smallest amount the trader accepts to buy.
+    pub min_amount_out: u64,
+}
+
+impl Trade {
+    /// Returns the variable inputs of a [`SwapCircuit`] for this trade.
+    pub fn variable_inputs<F: PrimeField>(&self) -> Vec<F> {
+        vec![F::from(self.amount_in), F::from(self.min_amount_out)]
+    }
+}
+
+/// Proves that swapping in a constant product [`Pool`] an amount of one
+/// token for an amount of the other, kept private, satisfies a [`Trade`]
+/// without decreasing the invariant `reserve_in * reserve_out` of the pool.
+///
+/// Static inputs: the reserves and fee of the pool. Variable inputs: the
+/// sold amount and the smallest bought amount of the trade.
+#[derive(Clone, Copy, Debug)]
+pub struct SwapCircuit {
+    /// The pool, or `None` during setup.
+    pub pool: Option<Pool>,
+    /// The trade, or `None` during setup.
+    pub trade: Option<Trade>,
+    /// The amount bought, or `None` during setup.
+    pub amount_out: Option<u64>,
+}
+
+impl SwapCircuit {
+    /// Creates a circuit without a witness, for setup.
+    pub fn blank() -> Self {
+        Self {
+            pool: None,
+            trade: None,
+            amount_out: None,
+        }
+    }
+}
+
+/// Creates a circuit proving that buying `amount_out` in `pool` satisfies
+/// `trade`.
+pub fn swap_circuit(pool: Pool, trade: Trade, amount_out: u64) -> SwapCircuit {
+    SwapCircuit {
+        pool: Some(pool),
+        trade: Some(trade),
+        amount_out: Some(amount_out),
+    }
+}
+
+/// Enforces that `value`, whose assignment is `assignment`, is less than
+/// `2^num_bits`.
+fn enforce_bits<F: PrimeField>(
+    cs: &ConstraintSystemRef<F>,
+    value: LinearCombination<F>,
+    assignment: Option<F>,
+    num_bits: usize,
+) -> R1CSResult<()> {
+    let bits = assignment.map(|value| value.into_bigint().to_bits_le());
+    let mut sum = lc!();
+    let mut coeff = F::one();
+    for i in 0..num_bits {
+        let (bit, _) = new_bit(cs, bits.as_ref().map(|bits| bits[i]))?;
+        sum += (coeff, bit);
+        coeff.double_in_place();
+    }
+    cs.enforce_constraint(sum, lc!() + Variable::One, value)
+}
+
+impl<F: PrimeField> ConstraintSynthesizer<F> for SwapCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> R1CSResult<()> {
+        let input = |value: Option<u64>| {
+            let value = value.map(F::from);
+            cs.new_input_variable(|| value.ok_or(SynthesisError::AssignmentMissing))
+                .map(|var| (var, value))
+        };
+
+        // Static inputs come first
+        let (reserve_in, reserve_in_value) = input(self.pool.map(|pool| pool.reserve_in))?;
+        let (reserve_out, reserve_out_value) = input(self.pool.map(|pool| pool.reserve_out))?;
+        let (fee, fee_value) = input(self.pool.map(|pool| pool.fee_bps as u64))?;
+        let (amount_in, amount_in_value) = input(self.trade.map(|trade| trade.amount_in))?;
+        let (min_amount_out, min_amount_out_value) =
+            input(self.trade.map(|trade| trade.min_amount_out))?;
+
+        let amount_out_value = self.amount_out.map(F::from);
+        let amount_out =
+            cs.new_witness_variable(|| amount_out_value.ok_or(SynthesisError::AssignmentMissing))?;
+
+        // All amounts fit in 64 bits, and the fee is at most 100%, so that
+        // no product below wraps around the modulus.
+        for (var, value) in [
+            (reserve_in, reserve_in_value),
+            (reserve_out, reserve_out_value),
+            (amount_in, amount_in_value),
+            (min_amount_out, min_amount_out_value),
+        ] {
+            enforce_bits(&cs, lc!() + var, value, 64)?;
+        }
+        let denominator = F::from(FEE_DENOMINATOR);
+        let fee_complement_value = fee_value.map(|fee| denominator - fee);
+        enforce_bits(&cs, lc!() + fee, fee_value, 14)?;
+        enforce_bits(
+            &cs,
+            lc!() + (denominator, Variable::One) - fee,
+            fee_complement_value,
+            14,
+        )?;
+
+        // amount_in_with_fee = amount_in * (FEE_DENOMINATOR - fee)
+        let amount_in_with_fee_value = amount_in_value
+            .zip(fee_complement_value)
+            .map(|(amount_in, fee_complement)| amount_in * fee_complement);
+        let amount_in_with_fee = cs.new_witness_variable(|| {
+            amount_in_with_fee_value.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        cs.enforce_constraint(
+            lc!() + amount_in,
+            lc!() + (denominator, Variable::One) - fee,
+            lc!() + amount_in_with_fee,
+        )?;
+
+        // invariant = reserve_in * reserve_out
+        let invariant_value = reserve_in_value
+            .zip(reserve_out_value)
+            .map(|(reserve_in, reserve_out)| reserve_in * reserve_out);
+        let invariant =
+            cs.new_witness_variable(|| invariant_value.ok_or(SynthesisError::AssignmentMissing))?;
+        cs.enforce_constraint(lc!() + reserve_in, lc!() + reserve_out, lc!() + invariant)?;
+
+        // The pool keeps a 64-bit reserve of the bought token.
+        let remaining_value = reserve_out_value
+            .zip(amount_out_value)
+            .map(|(reserve_out, amount_out)| reserve_out - amount_out);
+        enforce_bits(&cs, lc!() + reserve_out - amount_out, remaining_value, 64)?;
+
+        // new_invariant = (reserve_in * FEE_DENOMINATOR + amount_in_with_fee)
+        //     * (reserve_out - amount_out)
+        let new_invariant_value = reserve_in_value
+            .zip(amount_in_with_fee_value)
+            .zip(remaining_value)
+            .map(|((reserve_in, amount_in_with_fee), remaining)| {
+                (reserve_in * denominator + amount_in_with_fee) * remaining
+            });
+        let new_invariant = cs.new_witness_variable(|| {
+            new_invariant_value.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        cs.enforce_constraint(
+            lc!() + (denominator, reserve_in) + amount_in_with_fee,
+            lc!() + reserve_out - amount_out,
+            lc!() + new_invariant,
+        )?;
+
+        // The invariant, scaled by the fee denominator, does not decrease.
+        let slack_value = new_invariant_value
+            .zip(invariant_value)
+            .map(|(new_invariant, invariant)| new_invariant - invariant * denominator);
+        enforce_bits(
+            &cs,
+            lc!() + new_invariant - (denominator, invariant),
+            slack_value,
+            SLACK_BITS,
+        )?;
+
+        // The trader buys at least the smallest amount they accept.
+        let surplus_value = amount_out_value
+            .zip(min_amount_out_value)
+            .map(|(amount_out, min_amount_out)| amount_out - min_amount_out);
+        enforce_bits(&cs, lc!() + amount_out - min_amount_out, surplus_value, 64)
+    }
+}
+
+/// Generates a proving key and a prepared verifying key for
+/// [`SwapCircuit`], whose pool parameters are static inputs.
+pub fn setup<E: Pairing>(
+    rng: &mut impl Rng,
+) -> R1CSResult<(ProvingKey<E>, PreparedVerifyingKey<E>)> {
+    let pk = Groth16::<E>::generate_random_parameters_with_reduction(
+        SwapCircuit::blank(),
+        rng,
+        NUM_STATIC_INPUTS + 1,
+        0,
+    )?;
+    let pvk = crate::prepare_verifying_key(&pk.vk);
+    Ok((pk, pvk))
+}
+
+/// Proves that `trade` can be executed in `pool`, buying the amount quoted
+/// by [`Pool::amount_out`]. Fails with [`SynthesisError::Unsatisfiable`] if
+/// the quote is below the smallest amount accepted by the trade.
+pub fn prove<E: Pairing>(
+    pk: &ProvingKey<E>,
+    pool: Pool,
+    trade: Trade,
+    rng: &mut impl Rng,
+) -> R1CSResult<Proof<E>> {
+    let amount_out = pool
+        .amount_out(trade.amount_in)
+        .filter(|amount_out| *amount_out >= trade.min_amount_out)
+        .ok_or(SynthesisError::Unsatisfiable)?;
+    Groth16::<E>::create_random_proof_with_reduction(swap_circuit(pool, trade, amount_out), pk, rng)
+}
+
+/// Verifies a proof that `trade` can be executed in `pool`, passing the
+/// pool parameters as static inputs and the trade as variable inputs.
+pub fn verify<E: Pairing>(
+    pvk: &PreparedVerifyingKey<E>,
+    pool: Pool,
+    trade: Trade,
+    proof: &Proof<E>,
+) -> Result<bool, VerificationError> {
+    Groth16::<E>::verify_with_variables(pvk, proof, &pool.static_inputs(), &trade.variable_inputs())
+}
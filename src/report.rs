@@ -0,0 +1,330 @@
+use crate::{Groth16, KeyBasis, Proof, ProvingKey};
+use ark_ec::{pairing::Pairing, AffineRepr};
+use ark_poly::{EvaluationDomain, GeneralEvaluationDomain};
+use ark_relations::r1cs::{ConstraintSynthesizer, Result as R1CSResult, SynthesisError};
+use ark_serialize::{CanonicalSerialize, Compress};
+use ark_std::{fmt, mem::size_of};
+
+/// A summary of the shape of a [`ProvingKey`], for capacity planning.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ProvingKeyReport {
+    /// The number of public inputs, excluding the constant one.
+    pub num_inputs: usize,
+    /// The number of witness variables.
+    pub num_witness_variables: usize,
+    /// The size of the QAP evaluation domain.
+    pub domain_size: usize,
+    /// The maximum number of constraints of a circuit with this key: the
+    /// domain size minus the number of instance variables, which the
+    /// reduction also enforces.
+    pub max_constraints: usize,
+    /// The length of `a_query`.
+    pub a_query_len: usize,
+    /// The length of `b_g1_query`.
+    pub b_g1_query_len: usize,
+    /// The length of `b_g2_query`.
+    pub b_g2_query_len: usize,
+    /// The length of `h_query`.
+    pub h_query_len: usize,
+    /// The length of `l_query`.
+    pub l_query_len: usize,
+    /// The number of non-zero elements of `a_query`.
+    pub a_query_non_zero: usize,
+    /// The number of non-zero elements of `b_g1_query`.
+    pub b_g1_query_non_zero: usize,
+    /// The number of non-zero elements of `b_g2_query`.
+    pub b_g2_query_non_zero: usize,
+    /// The size of the key in memory, in bytes.
+    pub key_memory: usize,
+    /// An estimate of the memory used by the prover on top of the key, in
+    /// bytes: the assignment, the QAP witness vectors and the scalars of the
+    /// multi-scalar multiplications.
+    pub prover_memory: usize,
+}
+
+fn non_zero<G: AffineRepr>(query: &[G]) -> usize {
+    query.iter().filter(|g| !g.is_zero()).count()
+}
+
+/// Returns the memory of a proving key with `num_g1` points in `G1` and
+/// `num_g2` points in `G2`, and an estimate of the memory used by the prover
+/// on top of it for `num_variables` variables and a domain of
+/// `domain_size`, in bytes.
+fn memory<E: Pairing>(
+    num_g1: usize,
+    num_g2: usize,
+    num_variables: usize,
+    domain_size: usize,
+) -> (usize, usize) {
+    let g1 = size_of::<E::G1Affine>();
+    let g2 = size_of::<E::G2Affine>();
+    let scalar = size_of::<E::ScalarField>();
+
+    // The full assignment and its conversion for the MSMs, plus three
+    // domain-sized vectors in the witness map.
+    let prover_memory = scalar * (2 * num_variables + 3 * domain_size);
+    (g1 * num_g1 + g2 * num_g2, prover_memory)
+}
+
+impl<E: Pairing> ProvingKey<E> {
+    /// Returns a summary of the sizes of `self` and of the circuit it was
+    /// generated for, or [`SynthesisError::MalformedVerifyingKey`] if `self`
+    /// has more witness than instance and witness variables.
+    pub fn report(&self) -> R1CSResult<ProvingKeyReport> {
+        let num_instance_variables = self
+            .a_query
+            .len()
+            .checked_sub(self.l_query.len())
+            .ok_or(SynthesisError::MalformedVerifyingKey)?;
+        let domain_size = match self.h_basis {
+            KeyBasis::Monomial => self.h_query.len() + 1,
+            KeyBasis::Lagrange => self.h_query.len(),
+        };
+        let num_g1 = self.a_query.len()
+            + self.b_g1_query.len()
+            + self.h_query.len()
+            + self.l_query.len()
+            + self.vk.gamma_abc_g1_static.len()
+            + self.vk.gamma_abc_g1_variable.len()
+            + 3;
+        let num_g2 = self.b_g2_query.len() + 4;
+        let (key_memory, prover_memory) =
+            memory::<E>(num_g1, num_g2, self.a_query.len(), domain_size);
+
+        Ok(ProvingKeyReport {
+            num_inputs: num_instance_variables.saturating_sub(1),
+            num_witness_variables: self.l_query.len(),
+            domain_size,
+            max_constraints: domain_size.saturating_sub(num_instance_variables),
+            a_query_len: self.a_query.len(),
+            b_g1_query_len: self.b_g1_query.len(),
+            b_g2_query_len: self.b_g2_query.len(),
+            h_query_len: self.h_query.len(),
+            l_query_len: self.l_query.len(),
+            a_query_non_zero: non_zero(&self.a_query),
+            b_g1_query_non_zero: non_zero(&self.b_g1_query),
+            b_g2_query_non_zero: non_zero(&self.b_g2_query),
+            key_memory,
+            prover_memory,
+        })
+    }
+}
+
+/// The numbers of variables and the domain size of a circuit, which must
+/// match those of its proving key.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct CircuitShape {
+    /// The number of public inputs, excluding the constant one.
+    pub num_inputs: usize,
+    /// The number of witness variables.
+    pub num_witness_variables: usize,
+    /// The size of the QAP evaluation domain.
+    pub domain_size: usize,
+}
+
+impl fmt::Display for CircuitShape {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} inputs, {} witness variables and a domain of size {}",
+            self.num_inputs, self.num_witness_variables, self.domain_size
+        )
+    }
+}
+
+/// An error of [`ProvingKey::matches_circuit`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CircuitMismatch {
+    /// The circuit could not be synthesized.
+    Synthesis(SynthesisError),
+    /// The circuit does not have the shape of the key.
+    Shape {
+        /// The shape of the circuit the key was generated for.
+        expected: CircuitShape,
+        /// The shape of the circuit.
+        actual: CircuitShape,
+    },
+}
+
+impl fmt::Display for CircuitMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CircuitMismatch::Synthesis(e) => write!(f, "{}", e),
+            CircuitMismatch::Shape { expected, actual } => write!(
+                f,
+                "the key expects a circuit with {}, got {}",
+                expected, actual
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for CircuitMismatch {}
+
+impl From<SynthesisError> for CircuitMismatch {
+    fn from(e: SynthesisError) -> Self {
+        CircuitMismatch::Synthesis(e)
+    }
+}
+
+impl From<CircuitMismatch> for SynthesisError {
+    fn from(e: CircuitMismatch) -> Self {
+        match e {
+            CircuitMismatch::Synthesis(e) => e,
+            CircuitMismatch::Shape { .. } => SynthesisError::MalformedVerifyingKey,
+        }
+    }
+}
+
+impl<E: Pairing> ProvingKey<E> {
+    /// Returns the shape of the circuit `self` was generated for, or an
+    /// error if `self` is malformed, see [`Self::report`].
+    pub fn circuit_shape(&self) -> R1CSResult<CircuitShape> {
+        let report = self.report()?;
+        Ok(CircuitShape {
+            num_inputs: report.num_inputs,
+            num_witness_variables: report.num_witness_variables,
+            domain_size: report.domain_size,
+        })
+    }
+
+    /// Synthesizes `circuit` in setup mode and checks that it has the
+    /// numbers of inputs and witness variables and the domain size of the
+    /// circuit `self` was generated for, to reject a mismatched key before
+    /// proving rather than fail in the middle of the MSMs or compute an
+    /// invalid proof.
+    ///
+    /// A circuit whose constraints differ from those of the key but which
+    /// has the same shape is not detected: its proofs do not verify.
+    pub fn matches_circuit<C>(&self, circuit: &C) -> Result<(), CircuitMismatch>
+    where
+        C: ConstraintSynthesizer<E::ScalarField> + Clone,
+    {
+        let cs = Groth16::<E>::synthesize_for_setup(circuit.clone())?;
+        let num_instance_variables = cs.num_instance_variables();
+        let domain_size = GeneralEvaluationDomain::<E::ScalarField>::new(
+            cs.num_constraints() + num_instance_variables,
+        )
+        .ok_or(SynthesisError::PolynomialDegreeTooLarge)?
+        .size();
+        let actual = CircuitShape {
+            num_inputs: num_instance_variables - 1,
+            num_witness_variables: cs.num_witness_variables(),
+            domain_size,
+        };
+
+        let expected = self.circuit_shape()?;
+        if actual == expected {
+            Ok(())
+        } else {
+            Err(CircuitMismatch::Shape { expected, actual })
+        }
+    }
+}
+
+impl fmt::Display for ProvingKeyReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "inputs: {}, witness variables: {}",
+            self.num_inputs, self.num_witness_variables
+        )?;
+        writeln!(
+            f,
+            "domain size: {} (at most {} constraints)",
+            self.domain_size, self.max_constraints
+        )?;
+        writeln!(
+            f,
+            "a_query: {} ({} non-zero), b_g1_query: {} ({} non-zero), b_g2_query: {} ({} non-zero)",
+            self.a_query_len,
+            self.a_query_non_zero,
+            self.b_g1_query_len,
+            self.b_g1_query_non_zero,
+            self.b_g2_query_len,
+            self.b_g2_query_non_zero
+        )?;
+        writeln!(
+            f,
+            "h_query: {}, l_query: {}",
+            self.h_query_len, self.l_query_len
+        )?;
+        write!(
+            f,
+            "key memory: {} bytes, estimated prover memory: {} bytes",
+            self.key_memory, self.prover_memory
+        )
+    }
+}
+
+/// The sizes of the keys and proofs of a circuit, estimated before running
+/// its setup, see [`estimate_sizes`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SizeEstimate {
+    /// The size of the QAP evaluation domain.
+    pub domain_size: usize,
+    /// The size of the compressed serialization of the proving key, in
+    /// bytes.
+    pub pk_bytes: usize,
+    /// The size of the compressed serialization of the verifying key, in
+    /// bytes.
+    pub vk_bytes: usize,
+    /// The size of a compressed proof, in bytes.
+    pub proof_bytes: usize,
+    /// The memory used to prove: the proving key in memory and the estimate
+    /// of [`ProvingKeyReport::prover_memory`], in bytes.
+    pub proving_mem: usize,
+}
+
+/// Estimates the sizes of the keys and proofs over `E` of a circuit with
+/// `num_constraints` constraints, `num_variables` witness variables and
+/// `num_inputs` public inputs besides the constant one, so that storage and
+/// memory can be budgeted before running an expensive setup.
+///
+/// The sizes are those of a key with an `h_query` in the monomial basis;
+/// how inputs are split between static and variable ones does not change
+/// them. Fails with
+/// [`SynthesisError::PolynomialDegreeTooLarge`] if `E` has no evaluation
+/// domain large enough for the circuit.
+pub fn estimate_sizes<E: Pairing>(
+    num_constraints: usize,
+    num_variables: usize,
+    num_inputs: usize,
+) -> R1CSResult<SizeEstimate> {
+    let num_instance_variables = num_inputs + 1;
+    let num_all_variables = num_instance_variables + num_variables;
+    let domain_size =
+        GeneralEvaluationDomain::<E::ScalarField>::new(num_constraints + num_instance_variables)
+            .ok_or(SynthesisError::PolynomialDegreeTooLarge)?
+            .size();
+
+    let g1 = E::G1Affine::default().compressed_size();
+    let g2 = E::G2Affine::default().compressed_size();
+    let len = 0u64.compressed_size();
+
+    // The a, b_g1, h and l queries of the proving key, and b_g2.
+    let num_query_g1 = 2 * num_all_variables + (domain_size - 1) + num_variables;
+    let num_query_g2 = num_all_variables;
+
+    // alpha, beta, gamma and delta, and the input queries split in two.
+    let vk_bytes = g1 + 3 * g2 + 2 * len + g1 * num_instance_variables;
+    // The verifying key, beta and delta, the queries and the basis tag.
+    let pk_bytes = vk_bytes
+        + 2 * g1
+        + 5 * len
+        + g1 * num_query_g1
+        + g2 * num_query_g2
+        + KeyBasis::Monomial.compressed_size();
+    let num_g1 = num_query_g1 + num_instance_variables + 3;
+    let num_g2 = num_query_g2 + 4;
+    let (key_memory, prover_memory) = memory::<E>(num_g1, num_g2, num_all_variables, domain_size);
+
+    Ok(SizeEstimate {
+        domain_size,
+        pk_bytes,
+        vk_bytes,
+        proof_bytes: Proof::<E>::serialized_size_with(Compress::Yes),
+        proving_mem: key_memory + prover_memory,
+    })
+}
@@ -0,0 +1,240 @@
+use ark_ff::PrimeField;
+use ark_relations::r1cs::SynthesisError;
+use ark_std::{fmt, rand::RngCore, vec};
+use zeroize::Zeroize;
+
+/// An error of a [`ProverRng`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProverRngError {
+    /// The process or virtual machine was cloned since the source was last
+    /// seeded, so its output may be shared with the clone. The source
+    /// refuses to produce randomness until it is reseeded.
+    ForkDetected,
+    /// The source cannot be reseeded.
+    ReseedUnsupported,
+    /// The source failed to produce randomness, e.g. an unreachable HSM.
+    Unavailable,
+}
+
+impl fmt::Display for ProverRngError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProverRngError::ForkDetected => write!(f, "fork detected since the last reseed"),
+            ProverRngError::ReseedUnsupported => write!(f, "randomness source cannot be reseeded"),
+            ProverRngError::Unavailable => write!(f, "randomness source unavailable"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ProverRngError {}
+
+/// An error when creating a proof with a [`ProverRng`] or a
+/// [`BlindingSource`].
+#[derive(Debug)]
+pub enum ProveError {
+    /// The blinding factors could not be sampled.
+    Rng(ProverRngError),
+    /// The circuit could not be proven.
+    Synthesis(SynthesisError),
+}
+
+impl fmt::Display for ProveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProveError::Rng(e) => write!(f, "{}", e),
+            ProveError::Synthesis(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ProveError {}
+
+impl From<ProverRngError> for ProveError {
+    fn from(e: ProverRngError) -> Self {
+        ProveError::Rng(e)
+    }
+}
+
+impl From<SynthesisError> for ProveError {
+    fn from(e: SynthesisError) -> Self {
+        ProveError::Synthesis(e)
+    }
+}
+
+/// A source of randomness for the blinding factors of proofs, such as a
+/// DRBG or an HSM.
+///
+/// Unlike [`RngCore`], sampling can fail, so that a source which cannot
+/// guarantee fresh output refuses to produce any instead of reusing
+/// blinding factors, which would leak the witness. Every [`RngCore`] is a
+/// `ProverRng` which cannot be reseeded.
+pub trait ProverRng {
+    /// Fills `dest` with random bytes.
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), ProverRngError>;
+
+    /// Reseeds the source from fresh entropy. By default, sources cannot be
+    /// reseeded and return [`ProverRngError::ReseedUnsupported`].
+    fn reseed(&mut self) -> Result<(), ProverRngError> {
+        Err(ProverRngError::ReseedUnsupported)
+    }
+}
+
+impl<R: RngCore> ProverRng for R {
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), ProverRngError> {
+        RngCore::try_fill_bytes(self, dest).map_err(|_| ProverRngError::Unavailable)
+    }
+}
+
+/// Samples a uniformly random field element from `rng`. The sampled bytes
+/// exceed the size of the modulus by 128 bits, so that the bias of the
+/// reduction is negligible.
+pub fn sample_scalar<F: PrimeField>(rng: &mut impl ProverRng) -> Result<F, ProverRngError> {
+    let mut bytes = vec![0u8; (F::MODULUS_BIT_SIZE as usize).div_ceil(8) + 16];
+    rng.try_fill_bytes(&mut bytes)?;
+    let scalar = F::from_le_bytes_mod_order(&bytes);
+    bytes.zeroize();
+    Ok(scalar)
+}
+
+/// The blinding factors `r` and `s` of a proof, zeroized when dropped.
+///
+/// Zeroization is best-effort: temporary copies made by the prover while
+/// computing the proof are not wiped.
+pub struct BlindingFactors<F: PrimeField> {
+    pub(crate) r: F,
+    pub(crate) s: F,
+}
+
+impl<F: PrimeField> BlindingFactors<F> {
+    /// Wraps the blinding factors `r` and `s`, e.g. as returned by an HSM.
+    pub fn new(r: F, s: F) -> Self {
+        Self { r, s }
+    }
+
+    /// Samples uniformly random blinding factors from `rng`.
+    pub fn sample(rng: &mut impl ProverRng) -> Result<Self, ProverRngError> {
+        Ok(Self::new(sample_scalar(rng)?, sample_scalar(rng)?))
+    }
+}
+
+impl<F: PrimeField> Drop for BlindingFactors<F> {
+    fn drop(&mut self) {
+        self.r.zeroize();
+        self.s.zeroize();
+    }
+}
+
+impl<F: PrimeField> fmt::Debug for BlindingFactors<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("BlindingFactors { .. }")
+    }
+}
+
+/// A source of the blinding factors of proofs, such as a certified
+/// hardware module. Implemented by closures returning
+/// [`BlindingFactors`].
+pub trait BlindingSource<F: PrimeField> {
+    /// Returns fresh blinding factors for one proof.
+    fn blinding_factors(&mut self) -> Result<BlindingFactors<F>, ProverRngError>;
+}
+
+impl<F, S> BlindingSource<F> for S
+where
+    F: PrimeField,
+    S: FnMut() -> Result<BlindingFactors<F>, ProverRngError>,
+{
+    fn blinding_factors(&mut self) -> Result<BlindingFactors<F>, ProverRngError> {
+        self()
+    }
+}
+
+/// A [`ProverRng`] wrapping another one, which refuses to produce
+/// randomness after the process forks or the virtual machine it runs in is
+/// cloned, until it is reseeded.
+///
+/// Forks are detected by a change of process id. Clones of virtual machine
+/// snapshots keep the process id, so they are only detected if a source of
+/// the VM generation counter (e.g. the Linux `vmgenid` device) is provided
+/// with [`Self::with_generation_source`].
+#[cfg(feature = "std")]
+pub struct ForkDetectingRng<R> {
+    rng: R,
+    generation_source: Option<fn() -> u64>,
+    seeded_at: (u32, u64),
+    fork_detected: bool,
+}
+
+#[cfg(feature = "std")]
+impl<R: ProverRng> ForkDetectingRng<R> {
+    /// Wraps `rng`, considering it freshly seeded.
+    pub fn new(rng: R) -> Self {
+        let mut rng = Self {
+            rng,
+            generation_source: None,
+            seeded_at: (0, 0),
+            fork_detected: false,
+        };
+        rng.seeded_at = rng.current_state();
+        rng
+    }
+
+    /// Also detects changes of the counter returned by `generation_source`,
+    /// which should change whenever the virtual machine is cloned.
+    pub fn with_generation_source(mut self, generation_source: fn() -> u64) -> Self {
+        self.generation_source = Some(generation_source);
+        self.seeded_at = self.current_state();
+        self
+    }
+
+    /// Reseeds the wrapped source with `seed`, which must come from fresh
+    /// entropy, and resumes producing randomness.
+    pub fn reseed_from_seed(&mut self, seed: R::Seed)
+    where
+        R: ark_std::rand::SeedableRng,
+    {
+        self.rng = R::from_seed(seed);
+        self.mark_seeded();
+    }
+
+    /// Returns whether a fork was detected since the last reseed.
+    pub fn fork_detected(&mut self) -> bool {
+        self.fork_detected |= self.current_state() != self.seeded_at;
+        self.fork_detected
+    }
+
+    /// Returns the wrapped source.
+    pub fn into_inner(self) -> R {
+        self.rng
+    }
+
+    fn current_state(&self) -> (u32, u64) {
+        (
+            std::process::id(),
+            self.generation_source.map_or(0, |generation| generation()),
+        )
+    }
+
+    fn mark_seeded(&mut self) {
+        self.seeded_at = self.current_state();
+        self.fork_detected = false;
+    }
+}
+
+#[cfg(feature = "std")]
+impl<R: ProverRng> ProverRng for ForkDetectingRng<R> {
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), ProverRngError> {
+        if self.fork_detected() {
+            return Err(ProverRngError::ForkDetected);
+        }
+        self.rng.try_fill_bytes(dest)
+    }
+
+    /// Reseeds the wrapped source and resumes producing randomness.
+    fn reseed(&mut self) -> Result<(), ProverRngError> {
+        self.rng.reseed()?;
+        self.mark_seeded();
+        Ok(())
+    }
+}
@@ -0,0 +1,95 @@
+use crate::{prepare_verifying_key, Groth16, PreparedVerifyingKey, Proof, VerifyingKey};
+use ark_crypto_primitives::snark::SNARK;
+use ark_ec::pairing::Pairing;
+use ark_relations::r1cs::Result as R1CSResult;
+use ark_serialize::*;
+use ark_std::vec::Vec;
+
+/// The key of a [`KeyRotation`] which accepted a proof.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum EpochUsed {
+    /// The key of the current epoch.
+    Current,
+    /// The key of the previous epoch, during its grace period.
+    Previous,
+    /// The proof was rejected by all keys.
+    Neither,
+}
+
+/// The verifying keys of a circuit across a key rotation.
+///
+/// After [`Self::rotate`], proofs are accepted under both the new key and
+/// the key of the previous epoch, so that proofs created before the
+/// rollout remain valid, until [`Self::end_grace_period`] is called.
+#[derive(Clone, Debug, CanonicalSerialize, CanonicalDeserialize)]
+pub struct KeyRotation<E: Pairing> {
+    epoch: u64,
+    current: PreparedVerifyingKey<E>,
+    previous: Option<PreparedVerifyingKey<E>>,
+}
+
+impl<E: Pairing> KeyRotation<E> {
+    /// Starts a rotation at `epoch`, with `vk` as the only key.
+    pub fn new(vk: &VerifyingKey<E>, epoch: u64) -> Self {
+        Self {
+            epoch,
+            current: prepare_verifying_key(vk),
+            previous: None,
+        }
+    }
+
+    /// Makes `vk` the key of the next epoch, and starts the grace period of
+    /// the current key. The key of the previous epoch, if still accepted,
+    /// is dropped.
+    pub fn rotate(&mut self, vk: &VerifyingKey<E>) {
+        let current = core::mem::replace(&mut self.current, prepare_verifying_key(vk));
+        self.previous = Some(current);
+        self.epoch += 1;
+    }
+
+    /// Stops accepting proofs under the key of the previous epoch.
+    pub fn end_grace_period(&mut self) {
+        self.previous = None;
+    }
+
+    /// Returns the current epoch.
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    /// Returns the key of the current epoch.
+    pub fn current(&self) -> &PreparedVerifyingKey<E> {
+        &self.current
+    }
+
+    /// Returns the key of the previous epoch, if it is in its grace period.
+    pub fn previous(&self) -> Option<&PreparedVerifyingKey<E>> {
+        self.previous.as_ref()
+    }
+
+    /// Verifies `proof` against all its public `inputs`, static inputs
+    /// first, under the current key and then under the previous key, and
+    /// returns whether it was accepted along with the key which accepted
+    /// it. As the circuits of both epochs may have different inputs, a key
+    /// which does not match the shape of `inputs` rejects the proof; the
+    /// verification fails only if no key matches it.
+    pub fn verify_any(
+        &self,
+        proof: &Proof<E>,
+        inputs: &[E::ScalarField],
+    ) -> R1CSResult<(bool, EpochUsed)> {
+        let current = Groth16::<E>::verify_with_processed_vk(&self.current, inputs, proof);
+        if let Ok(true) = current {
+            return Ok((true, EpochUsed::Current));
+        }
+        let previous = self
+            .previous
+            .as_ref()
+            .map(|previous| Groth16::<E>::verify_with_processed_vk(previous, inputs, proof));
+        match (current, previous) {
+            (_, Some(Ok(true))) => Ok((true, EpochUsed::Previous)),
+            (Err(e), None | Some(Err(_))) => Err(e),
+            _ => Ok((false, EpochUsed::Neither)),
+        }
+    }
+}
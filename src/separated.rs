@@ -0,0 +1,212 @@
+use crate::{
+    msm::mul_secret, r1cs_to_qap::R1CSToQAP, Groth16, PreparedVerifyingKey, Proof, ProvingKey,
+    VerificationError, VerifyingKey,
+};
+use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup, VariableBaseMSM};
+use ark_ff::{Field, UniformRand};
+use ark_relations::r1cs::{ConstraintSynthesizer, Result as R1CSResult, SynthesisError};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::{cfg_iter, ops::Neg, rand::Rng, vec::Vec};
+use zeroize::Zeroize;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// A verifying key whose bases of static and variable inputs are divided by
+/// distinct secrets, `gamma_s` and `gamma_v`.
+///
+/// The verifier checks the static and variable inputs in separate pairings,
+/// `e(S, gamma_s H)` and `e(V, gamma_v H)`, so that the bases of the
+/// variable inputs can be rotated with
+/// [`SeparatedVerifyingKey::rotate_variable_gamma`] without touching the
+/// bases of the static inputs, which verifiers may have committed to. The
+/// prover does not depend on either gamma, so proofs verify under rotated
+/// keys.
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct SeparatedVerifyingKey<E: Pairing> {
+    /// The key, with `gamma_g2 = gamma_s H`, the static bases divided by
+    /// `gamma_s` and the variable bases divided by `gamma_v`. It does not
+    /// verify proofs with non-zero variable inputs on its own unless
+    /// `gamma_s = gamma_v`.
+    pub vk: VerifyingKey<E>,
+    /// The element `gamma_v H`, where `H` is the generator of `E::G2`.
+    pub gamma_variable_g2: E::G2Affine,
+}
+
+impl<E: Pairing> From<VerifyingKey<E>> for SeparatedVerifyingKey<E> {
+    /// Wraps a key with a single gamma, which is both `gamma_s` and
+    /// `gamma_v` until the variable gamma is rotated.
+    fn from(vk: VerifyingKey<E>) -> Self {
+        Self {
+            gamma_variable_g2: vk.gamma_g2,
+            vk,
+        }
+    }
+}
+
+impl<E: Pairing> SeparatedVerifyingKey<E> {
+    /// Multiplies `gamma_v` by a random factor sampled via `rng`, dividing
+    /// the bases of the variable inputs by it. The static bases and
+    /// `gamma_s` are unchanged, and the factor is zeroized after use.
+    pub fn rotate_variable_gamma(&self, rng: &mut impl Rng) -> R1CSResult<Self> {
+        let mut factor = E::ScalarField::rand(rng);
+        let mut factor_inverse = factor.inverse().ok_or(SynthesisError::UnexpectedIdentity)?;
+        let mut rotated = self.clone();
+        rotated.gamma_variable_g2 =
+            mul_secret(self.gamma_variable_g2.into_group(), factor).into_affine();
+        rotated.vk.gamma_abc_g1_variable = scale(&self.vk.gamma_abc_g1_variable, factor_inverse);
+        factor.zeroize();
+        factor_inverse.zeroize();
+        Ok(rotated)
+    }
+}
+
+/// Returns the `bases` multiplied by the secret `scalar`.
+fn scale<G: AffineRepr>(bases: &[G], scalar: G::ScalarField) -> Vec<G> {
+    let scaled = cfg_iter!(bases)
+        .map(|base| mul_secret(base.into_group(), scalar))
+        .collect::<Vec<_>>();
+    G::Group::normalize_batch(&scaled)
+}
+
+/// A [`SeparatedVerifyingKey`] prepared for use in proof verification.
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct PreparedSeparatedVerifyingKey<E: Pairing> {
+    /// The prepared key, with `- gamma_s H` as prepared gamma.
+    pub pvk: PreparedVerifyingKey<E>,
+    /// The element `- gamma_v H`, prepared for use in pairings.
+    pub gamma_variable_g2_neg_pc: E::G2Prepared,
+}
+
+/// Prepare the separated verifying key `svk` for use in proof verification.
+pub fn prepare_separated_verifying_key<E: Pairing>(
+    svk: &SeparatedVerifyingKey<E>,
+) -> PreparedSeparatedVerifyingKey<E> {
+    PreparedSeparatedVerifyingKey {
+        pvk: crate::prepare_verifying_key(&svk.vk),
+        gamma_variable_g2_neg_pc: svk
+            .gamma_variable_g2
+            .into_group()
+            .neg()
+            .into_affine()
+            .into(),
+    }
+}
+
+impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
+    /// Generates a random common reference string for a circuit like
+    /// [`Self::generate_random_parameters_with_reduction`], with distinct
+    /// gammas for the static and variable inputs. The verifying key of the
+    /// proving key is the one of the separated verifying key.
+    pub fn generate_random_parameters_with_separated_gammas<C>(
+        circuit: C,
+        rng: &mut impl Rng,
+        num_static_inputs: usize,
+    ) -> R1CSResult<(ProvingKey<E>, SeparatedVerifyingKey<E>)>
+    where
+        C: ConstraintSynthesizer<E::ScalarField>,
+    {
+        let alpha = E::ScalarField::rand(rng);
+        let beta = E::ScalarField::rand(rng);
+        let mut gamma_static = E::ScalarField::rand(rng);
+        let mut gamma_variable = E::ScalarField::rand(rng);
+        let delta = E::ScalarField::rand(rng);
+
+        let g1_generator = E::G1::rand(rng);
+        let g2_generator = E::G2::rand(rng);
+
+        let mut pk = Self::generate_parameters_with_qap(
+            circuit,
+            alpha,
+            beta,
+            gamma_static,
+            delta,
+            num_static_inputs,
+            0,
+            g1_generator,
+            g2_generator,
+            rng,
+        )?;
+
+        // The variable bases are divided by `gamma_s`, and are divided by
+        // `gamma_v` instead by multiplying them with `gamma_s / gamma_v`.
+        let mut ratio = gamma_static
+            * gamma_variable
+                .inverse()
+                .ok_or(SynthesisError::UnexpectedIdentity)?;
+        pk.vk.gamma_abc_g1_variable = scale(&pk.vk.gamma_abc_g1_variable, ratio);
+        let gamma_variable_g2 = mul_secret(g2_generator, gamma_variable).into_affine();
+        ratio.zeroize();
+        gamma_static.zeroize();
+        gamma_variable.zeroize();
+
+        let svk = SeparatedVerifyingKey {
+            vk: pk.vk.clone(),
+            gamma_variable_g2,
+        };
+        Ok((pk, svk))
+    }
+
+    /// Aggregates the static inputs, with the constant term, and the
+    /// variable inputs with their bases in `psvk`, returning the prepared
+    /// static and variable inputs. Fails with
+    /// [`VerificationError::InputCount`] if the input counts do not match
+    /// the key.
+    pub fn prepare_separated_inputs(
+        psvk: &PreparedSeparatedVerifyingKey<E>,
+        static_inputs: &[E::ScalarField],
+        variable_inputs: &[E::ScalarField],
+    ) -> Result<(E::G1, E::G1), VerificationError> {
+        let vk = &psvk.pvk.vk;
+        if vk.gamma_abc_g1_static.is_empty() {
+            return Err(SynthesisError::MalformedVerifyingKey.into());
+        }
+        psvk.pvk
+            .check_input_counts(static_inputs.len(), variable_inputs.len())?;
+
+        let prepared_static = vk.gamma_abc_g1_static[0].into_group()
+            + E::G1::msm_unchecked(&vk.gamma_abc_g1_static[1..], static_inputs);
+        let prepared_variable = E::G1::msm_unchecked(&vk.gamma_abc_g1_variable, variable_inputs);
+        Ok((prepared_static, prepared_variable))
+    }
+
+    /// Verify a proof against a separated verifying key, with its static
+    /// and variable inputs, checking
+    /// `e(A, B) = e(alpha, beta) e(S, gamma_s) e(V, gamma_v) e(C, delta)`.
+    pub fn verify_separated(
+        psvk: &PreparedSeparatedVerifyingKey<E>,
+        proof: &Proof<E>,
+        static_inputs: &[E::ScalarField],
+        variable_inputs: &[E::ScalarField],
+    ) -> Result<bool, VerificationError> {
+        let (prepared_static, prepared_variable) =
+            Self::prepare_separated_inputs(psvk, static_inputs, variable_inputs)?;
+        #[cfg(feature = "verifier-audit")]
+        {
+            if !crate::verifier::audit_proof(proof) {
+                return Ok(false);
+            }
+            crate::verifier_audit::projective("prepared_inputs.static", &prepared_static);
+            crate::verifier_audit::projective("prepared_inputs.variable", &prepared_variable);
+        }
+        let pvk = &psvk.pvk;
+        let qap = E::multi_miller_loop(
+            [
+                proof.a,
+                prepared_static.into_affine(),
+                prepared_variable.into_affine(),
+                proof.c,
+            ],
+            [
+                proof.b.into(),
+                pvk.gamma_g2_neg_pc.clone(),
+                psvk.gamma_variable_g2_neg_pc.clone(),
+                pvk.delta_g2_neg_pc.clone(),
+            ],
+        );
+        let test = E::final_exponentiation(qap).ok_or(SynthesisError::UnexpectedIdentity)?;
+        #[cfg(feature = "verifier-audit")]
+        crate::verifier::audit_final_exponentiation(&qap, &test);
+        Ok(test.0 == pvk.alpha_g1_beta_g2)
+    }
+}
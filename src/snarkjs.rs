@@ -0,0 +1,518 @@
+use crate::{text::curve_tag, zkey::point_from_coordinates, Proof, VerifyingKey};
+use ark_ec::{pairing::Pairing, AffineRepr};
+use ark_ff::{Field, One, PrimeField, Zero};
+use ark_serialize::SerializationError;
+use ark_std::{
+    fmt::Write,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+/// The names of the curves of snarkjs, by [tag](curve_tag).
+const CURVE_NAMES: &[(&str, &str)] = &[("bn254", "bn128"), ("bls12381", "bls12381")];
+
+/// The protocol identifier of Groth16 in the JSON files of snarkjs.
+const PROTOCOL: &str = "groth16";
+
+/// A JSON value, with numbers kept as written.
+#[derive(Clone, Debug, PartialEq)]
+enum Json {
+    Null,
+    Bool(bool),
+    Number(String),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    fn get(&self, key: &str) -> Result<&Json, SerializationError> {
+        match self {
+            Json::Object(entries) => entries
+                .iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value)
+                .ok_or(SerializationError::InvalidData),
+            _ => Err(SerializationError::InvalidData),
+        }
+    }
+
+    fn as_str(&self) -> Result<&str, SerializationError> {
+        match self {
+            Json::String(value) => Ok(value),
+            _ => Err(SerializationError::InvalidData),
+        }
+    }
+
+    fn as_array(&self) -> Result<&[Json], SerializationError> {
+        match self {
+            Json::Array(values) => Ok(values),
+            _ => Err(SerializationError::InvalidData),
+        }
+    }
+
+    /// Writes the value as `JSON.stringify(value, null, 1)` does, as snarkjs
+    /// writes its files.
+    fn write(&self, out: &mut String, depth: usize) {
+        let indent = |out: &mut String, depth: usize| {
+            out.push('\n');
+            out.extend(ark_std::iter::repeat_n(' ', depth));
+        };
+        match self {
+            Json::Null => out.push_str("null"),
+            Json::Bool(value) => out.push_str(if *value { "true" } else { "false" }),
+            Json::Number(value) => out.push_str(value),
+            Json::String(value) => write_string(out, value),
+            Json::Array(values) if values.is_empty() => out.push_str("[]"),
+            Json::Array(values) => {
+                out.push('[');
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        out.push(',');
+                    }
+                    indent(out, depth + 1);
+                    value.write(out, depth + 1);
+                }
+                indent(out, depth);
+                out.push(']');
+            },
+            Json::Object(entries) if entries.is_empty() => out.push_str("{}"),
+            Json::Object(entries) => {
+                out.push('{');
+                for (i, (key, value)) in entries.iter().enumerate() {
+                    if i > 0 {
+                        out.push(',');
+                    }
+                    indent(out, depth + 1);
+                    write_string(out, key);
+                    out.push_str(": ");
+                    value.write(out, depth + 1);
+                }
+                indent(out, depth);
+                out.push('}');
+            },
+        }
+    }
+
+    fn to_text(&self) -> String {
+        let mut out = String::new();
+        self.write(&mut out, 0);
+        out
+    }
+}
+
+fn write_string(out: &mut String, value: &str) {
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            c if (c as u32) < 0x20 => {
+                write!(out, "\\u{:04x}", c as u32).expect("writing to a string cannot fail")
+            },
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// A parser of the JSON values of the files of snarkjs, which are small
+/// enough to be parsed recursively.
+struct Parser<'a> {
+    text: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    /// The maximum nesting of arrays and objects, far above the 4 levels of
+    /// the files of snarkjs.
+    const MAX_DEPTH: usize = 32;
+
+    fn parse(text: &'a str) -> Result<Json, SerializationError> {
+        let mut parser = Self {
+            text: text.as_bytes(),
+            position: 0,
+        };
+        let value = parser.value(0)?;
+        parser.skip_whitespace();
+        if parser.position != parser.text.len() {
+            return Err(SerializationError::InvalidData);
+        }
+        Ok(value)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self
+            .text
+            .get(self.position)
+            .is_some_and(|c| c.is_ascii_whitespace())
+        {
+            self.position += 1;
+        }
+    }
+
+    /// Skips whitespace and returns the next byte, without consuming it.
+    fn peek(&mut self) -> Result<u8, SerializationError> {
+        self.skip_whitespace();
+        self.text
+            .get(self.position)
+            .copied()
+            .ok_or(SerializationError::InvalidData)
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), SerializationError> {
+        if self.peek()? != byte {
+            return Err(SerializationError::InvalidData);
+        }
+        self.position += 1;
+        Ok(())
+    }
+
+    fn keyword(&mut self, keyword: &str, value: Json) -> Result<Json, SerializationError> {
+        if !self.text[self.position..].starts_with(keyword.as_bytes()) {
+            return Err(SerializationError::InvalidData);
+        }
+        self.position += keyword.len();
+        Ok(value)
+    }
+
+    fn value(&mut self, depth: usize) -> Result<Json, SerializationError> {
+        if depth > Self::MAX_DEPTH {
+            return Err(SerializationError::InvalidData);
+        }
+        match self.peek()? {
+            b'n' => self.keyword("null", Json::Null),
+            b't' => self.keyword("true", Json::Bool(true)),
+            b'f' => self.keyword("false", Json::Bool(false)),
+            b'"' => Ok(Json::String(self.string()?)),
+            b'[' => {
+                self.position += 1;
+                let mut values = Vec::new();
+                if self.peek()? == b']' {
+                    self.position += 1;
+                    return Ok(Json::Array(values));
+                }
+                loop {
+                    values.push(self.value(depth + 1)?);
+                    match self.peek()? {
+                        b',' => self.position += 1,
+                        b']' => break,
+                        _ => return Err(SerializationError::InvalidData),
+                    }
+                }
+                self.position += 1;
+                Ok(Json::Array(values))
+            },
+            b'{' => {
+                self.position += 1;
+                let mut entries = Vec::new();
+                if self.peek()? == b'}' {
+                    self.position += 1;
+                    return Ok(Json::Object(entries));
+                }
+                loop {
+                    self.peek()?;
+                    let key = self.string()?;
+                    self.expect(b':')?;
+                    entries.push((key, self.value(depth + 1)?));
+                    match self.peek()? {
+                        b',' => self.position += 1,
+                        b'}' => break,
+                        _ => return Err(SerializationError::InvalidData),
+                    }
+                }
+                self.position += 1;
+                Ok(Json::Object(entries))
+            },
+            b'-' | b'0'..=b'9' => {
+                let start = self.position;
+                while self
+                    .text
+                    .get(self.position)
+                    .is_some_and(|c| matches!(c, b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9'))
+                {
+                    self.position += 1;
+                }
+                let number = core::str::from_utf8(&self.text[start..self.position])
+                    .map_err(|_| SerializationError::InvalidData)?;
+                Ok(Json::Number(String::from(number)))
+            },
+            _ => Err(SerializationError::InvalidData),
+        }
+    }
+
+    /// Parses a string starting at the current byte.
+    fn string(&mut self) -> Result<String, SerializationError> {
+        if self.text.get(self.position) != Some(&b'"') {
+            return Err(SerializationError::InvalidData);
+        }
+        self.position += 1;
+        let mut value = Vec::new();
+        loop {
+            let byte = *self
+                .text
+                .get(self.position)
+                .ok_or(SerializationError::InvalidData)?;
+            self.position += 1;
+            match byte {
+                b'"' => break,
+                b'\\' => {
+                    let escaped = *self
+                        .text
+                        .get(self.position)
+                        .ok_or(SerializationError::InvalidData)?;
+                    self.position += 1;
+                    value.push(match escaped {
+                        b'"' | b'\\' | b'/' => escaped,
+                        b'n' => b'\n',
+                        b't' => b'\t',
+                        b'r' => b'\r',
+                        // The files of snarkjs hold no other escapes.
+                        _ => return Err(SerializationError::InvalidData),
+                    });
+                },
+                byte => value.push(byte),
+            }
+        }
+        String::from_utf8(value).map_err(|_| SerializationError::InvalidData)
+    }
+}
+
+/// Returns the name of the curve of `E` in snarkjs, or fails for the
+/// curves snarkjs does not support.
+fn curve_name<E: Pairing>() -> Result<&'static str, SerializationError> {
+    let tag = curve_tag::<E>();
+    CURVE_NAMES
+        .iter()
+        .find(|(known, _)| *known == tag)
+        .map(|(_, name)| *name)
+        .ok_or(SerializationError::InvalidData)
+}
+
+/// Checks that the `curve` and `protocol` fields of `json`, if present, are
+/// those of Groth16 proofs over `E`.
+fn check_header<E: Pairing>(json: &Json) -> Result<(), SerializationError> {
+    for (key, expected) in [("curve", curve_name::<E>()?), ("protocol", PROTOCOL)] {
+        if let Ok(value) = json.get(key) {
+            if value.as_str()? != expected {
+                return Err(SerializationError::InvalidData);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Parses a decimal string, rejecting values which are not reduced modulo
+/// the field order, so that each element has a single encoding.
+fn parse_element<F: PrimeField>(json: &Json) -> Result<F, SerializationError> {
+    let text = json.as_str()?;
+    let element = text
+        .parse::<F>()
+        .map_err(|_| SerializationError::InvalidData)?;
+    if element.into_bigint().to_string() != text {
+        return Err(SerializationError::InvalidData);
+    }
+    Ok(element)
+}
+
+fn element_json<F: PrimeField>(element: F) -> Json {
+    Json::String(element.into_bigint().to_string())
+}
+
+/// Parses a coordinate of a point of `G`: a decimal string over a prime
+/// field, or an array of them over an extension.
+fn parse_coordinate<G: AffineRepr>(json: &Json) -> Result<G::BaseField, SerializationError> {
+    let elements = if G::BaseField::extension_degree() == 1 {
+        ark_std::vec![parse_element(json)?]
+    } else {
+        json.as_array()?
+            .iter()
+            .map(parse_element)
+            .collect::<Result<Vec<_>, _>>()?
+    };
+    G::BaseField::from_base_prime_field_elems(&elements).ok_or(SerializationError::InvalidData)
+}
+
+fn coordinate_json<G: AffineRepr>(coordinate: G::BaseField) -> Json {
+    let mut elements = coordinate.to_base_prime_field_elements().map(element_json);
+    if G::BaseField::extension_degree() == 1 {
+        elements
+            .next()
+            .expect("a prime field element has a coordinate")
+    } else {
+        Json::Array(elements.collect())
+    }
+}
+
+/// Parses a point in the projective coordinates `[x, y, z]` of snarkjs,
+/// which are normalized to `z = 1`, or `z = 0` for the point at infinity.
+fn parse_point<G: AffineRepr>(json: &Json) -> Result<G, SerializationError> {
+    let [x, y, z] = json.as_array()? else {
+        return Err(SerializationError::InvalidData);
+    };
+    let z = parse_coordinate::<G>(z)?;
+    if z.is_zero() {
+        Ok(G::zero())
+    } else if z.is_one() {
+        point_from_coordinates(parse_coordinate::<G>(x)?, parse_coordinate::<G>(y)?)
+    } else {
+        Err(SerializationError::InvalidData)
+    }
+}
+
+fn point_json<G: AffineRepr>(point: &G) -> Json {
+    let (x, y, z) = match point.xy() {
+        Some((x, y)) => (*x, *y, G::BaseField::one()),
+        None => (
+            G::BaseField::zero(),
+            G::BaseField::one(),
+            G::BaseField::zero(),
+        ),
+    };
+    Json::Array(ark_std::vec![
+        coordinate_json::<G>(x),
+        coordinate_json::<G>(y),
+        coordinate_json::<G>(z),
+    ])
+}
+
+/// Parses a verifying key from the `verification_key.json` file exported
+/// by snarkjs, over BN254 (`bn128` in snarkjs) or BLS12-381.
+///
+/// snarkjs does not distinguish static from variable inputs: the key
+/// expects all its inputs as variable inputs. Fails with
+/// [`SerializationError::InvalidData`] if the file is malformed, is for
+/// another protocol or curve, or has a point which is not in the prime
+/// order subgroup.
+pub fn verifying_key_from_json<E: Pairing>(
+    json: &str,
+) -> Result<VerifyingKey<E>, SerializationError> {
+    let json = Parser::parse(json)?;
+    check_header::<E>(&json)?;
+    let ic = json
+        .get("IC")?
+        .as_array()?
+        .iter()
+        .map(parse_point)
+        .collect::<Result<Vec<E::G1Affine>, _>>()?;
+    let Some((constant, inputs)) = ic.split_first() else {
+        return Err(SerializationError::InvalidData);
+    };
+    if let Ok(Json::Number(num_public)) = json.get("nPublic") {
+        if num_public.parse::<usize>() != Ok(inputs.len()) {
+            return Err(SerializationError::InvalidData);
+        }
+    }
+    Ok(VerifyingKey {
+        alpha_g1: parse_point(json.get("vk_alpha_1")?)?,
+        beta_g2: parse_point(json.get("vk_beta_2")?)?,
+        gamma_g2: parse_point(json.get("vk_gamma_2")?)?,
+        delta_g2: parse_point(json.get("vk_delta_2")?)?,
+        gamma_abc_g1_static: ark_std::vec![*constant],
+        gamma_abc_g1_variable: inputs.to_vec(),
+    })
+}
+
+/// Writes `vk` as the `verification_key.json` file of snarkjs, with its
+/// static and variable inputs as the inputs of the key.
+///
+/// Fails with [`SerializationError::InvalidData`] if `E` is not a curve
+/// supported by snarkjs, or if `vk` lacks the constant term.
+pub fn verifying_key_to_json<E: Pairing>(
+    vk: &VerifyingKey<E>,
+) -> Result<String, SerializationError> {
+    let curve = curve_name::<E>()?;
+    if vk.gamma_abc_g1_static.is_empty() {
+        return Err(SerializationError::InvalidData);
+    }
+    let ic = vk
+        .gamma_abc_g1_static
+        .iter()
+        .chain(&vk.gamma_abc_g1_variable)
+        .map(point_json)
+        .collect::<Vec<_>>();
+    // `e(alpha, beta)`, nested as the `Fp12 = Fp6[w]` over `Fp6 = Fp2[v]`
+    // towers of snarkjs.
+    let alpha_beta = E::pairing(vk.alpha_g1, vk.beta_g2)
+        .0
+        .to_base_prime_field_elements()
+        .map(element_json)
+        .collect::<Vec<_>>();
+    let alpha_beta = alpha_beta
+        .chunks(alpha_beta.len() / 2)
+        .map(|half| {
+            Json::Array(
+                half.chunks(2)
+                    .map(|pair| Json::Array(pair.to_vec()))
+                    .collect(),
+            )
+        })
+        .collect();
+    Ok(Json::Object(ark_std::vec![
+        (
+            String::from("protocol"),
+            Json::String(String::from(PROTOCOL))
+        ),
+        (String::from("curve"), Json::String(String::from(curve))),
+        (
+            String::from("nPublic"),
+            Json::Number(format!("{}", ic.len() - 1)),
+        ),
+        (String::from("vk_alpha_1"), point_json(&vk.alpha_g1)),
+        (String::from("vk_beta_2"), point_json(&vk.beta_g2)),
+        (String::from("vk_gamma_2"), point_json(&vk.gamma_g2)),
+        (String::from("vk_delta_2"), point_json(&vk.delta_g2)),
+        (String::from("vk_alphabeta_12"), Json::Array(alpha_beta)),
+        (String::from("IC"), Json::Array(ic)),
+    ])
+    .to_text())
+}
+
+/// Parses a proof from the `proof.json` file written by snarkjs. Fails with
+/// [`SerializationError::InvalidData`] if the file is malformed, is for
+/// another protocol or curve, or has a point which is not in the prime
+/// order subgroup.
+pub fn proof_from_json<E: Pairing>(json: &str) -> Result<Proof<E>, SerializationError> {
+    let json = Parser::parse(json)?;
+    check_header::<E>(&json)?;
+    Ok(Proof {
+        a: parse_point(json.get("pi_a")?)?,
+        b: parse_point(json.get("pi_b")?)?,
+        c: parse_point(json.get("pi_c")?)?,
+    })
+}
+
+/// Writes `proof` as the `proof.json` file of snarkjs. Fails with
+/// [`SerializationError::InvalidData`] if `E` is not a curve supported by
+/// snarkjs.
+pub fn proof_to_json<E: Pairing>(proof: &Proof<E>) -> Result<String, SerializationError> {
+    let curve = curve_name::<E>()?;
+    Ok(Json::Object(ark_std::vec![
+        (String::from("pi_a"), point_json(&proof.a)),
+        (String::from("pi_b"), point_json(&proof.b)),
+        (String::from("pi_c"), point_json(&proof.c)),
+        (
+            String::from("protocol"),
+            Json::String(String::from(PROTOCOL))
+        ),
+        (String::from("curve"), Json::String(String::from(curve))),
+    ])
+    .to_text())
+}
+
+/// Parses public inputs from the `public.json` file written by snarkjs, an
+/// array of decimal strings.
+pub fn public_inputs_from_json<F: PrimeField>(json: &str) -> Result<Vec<F>, SerializationError> {
+    Parser::parse(json)?
+        .as_array()?
+        .iter()
+        .map(parse_element)
+        .collect()
+}
+
+/// Writes public inputs as the `public.json` file of snarkjs.
+pub fn public_inputs_to_json<F: PrimeField>(inputs: &[F]) -> String {
+    Json::Array(inputs.iter().copied().map(element_json).collect()).to_text()
+}
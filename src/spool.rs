@@ -0,0 +1,322 @@
+use crate::{bundle::VkFingerprint, hash::sha256, Proof};
+use ark_ec::pairing::Pairing;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, SerializationError};
+use ark_std::{fmt, io, marker::PhantomData, vec::Vec};
+use std::{
+    fs::{self, File},
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+/// The size of the checksum prefixed to spooled objects.
+const CHECKSUM_SIZE: usize = 32;
+
+/// The directory of pending jobs in a spool.
+const PENDING_DIR: &str = "pending";
+
+/// The directory of completed proofs in a spool.
+const COMPLETED_DIR: &str = "completed";
+
+/// The file holding the identifier of the next job in a spool, so that
+/// identifiers are not reused once all jobs have been taken.
+const NEXT_ID_FILE: &str = "next_id";
+
+/// The extension of the temporary files objects are written to before being
+/// renamed.
+const TEMPORARY_EXTENSION: &str = "tmp";
+
+/// The identifier of a job in a [`ProofSpool`], assigned in submission
+/// order.
+pub type JobId = u64;
+
+/// An error when spooling a job or a proof.
+#[derive(Debug)]
+pub enum SpoolError {
+    /// No pending job or completed proof has the requested identifier.
+    UnknownJob(JobId),
+    /// The spooled object of the job does not match its checksum, e.g.
+    /// because the disk corrupted it.
+    Corrupted(JobId),
+    /// All identifiers were assigned, so no job can be submitted.
+    IdsExhausted,
+    /// The job or proof could not be serialized or deserialized.
+    Serialization(SerializationError),
+    /// The file system failed.
+    Io(io::Error),
+}
+
+impl fmt::Display for SpoolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SpoolError::UnknownJob(id) => write!(f, "no job {} in the spool", id),
+            SpoolError::Corrupted(id) => write!(f, "job {} does not match its checksum", id),
+            SpoolError::IdsExhausted => write!(f, "no job identifiers left in the spool"),
+            SpoolError::Serialization(e) => write!(f, "{}", e),
+            SpoolError::Io(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for SpoolError {}
+
+impl From<SerializationError> for SpoolError {
+    fn from(e: SerializationError) -> Self {
+        SpoolError::Serialization(e)
+    }
+}
+
+impl From<io::Error> for SpoolError {
+    fn from(e: io::Error) -> Self {
+        SpoolError::Io(e)
+    }
+}
+
+/// A proving job: the serialization of a circuit or of its assignment,
+/// opaque to the spool, and the fingerprint of the verifying key of the
+/// proving key to prove it with.
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct ProvingJob {
+    /// The fingerprint of the verifying key of the proving key.
+    pub key: VkFingerprint,
+    /// The serialized circuit or assignment.
+    pub payload: Vec<u8>,
+}
+
+/// What [`ProofSpool::open`] recovered from a previous run.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Recovery {
+    /// The number of jobs still pending, to be proven again.
+    pub pending: usize,
+    /// The number of proofs completed but not yet taken.
+    pub completed: usize,
+    /// The number of pending jobs removed because their proof had been
+    /// written, after a crash between the two steps of
+    /// [`ProofSpool::complete`].
+    pub resolved: usize,
+    /// The number of partially written objects removed.
+    pub discarded: usize,
+}
+
+/// A durable queue of proving jobs and of their proofs in a directory, for
+/// prover daemons to pick up their work after a crash or a restart.
+///
+/// Jobs are spooled in `pending/` and proofs in `completed/`, each in a
+/// file named after its [`JobId`] and prefixed with a SHA-256 checksum.
+/// The next identifier is recorded in `next_id`, so that identifiers are
+/// never reused, even across restarts.
+/// Files are written to a temporary file, synced and renamed, so that a
+/// crash leaves either the previous or the next state of an object, never
+/// a partial one; a job is only removed once its proof is durable.
+///
+/// The spool is not locked: a single process should own a directory.
+#[derive(Debug)]
+pub struct ProofSpool<E: Pairing> {
+    root: PathBuf,
+    next_id: JobId,
+    _pairing: PhantomData<E>,
+}
+
+impl<E: Pairing> ProofSpool<E> {
+    /// Opens the spool in the directory `root`, creating it if missing, and
+    /// recovers from an interrupted previous run: partially written objects
+    /// are removed, as are the pending jobs whose proof was written.
+    ///
+    /// Fails with [`SpoolError::IdsExhausted`] if a spooled object has the
+    /// largest [`JobId`], as no identifier follows it.
+    pub fn open(root: impl Into<PathBuf>) -> Result<(Self, Recovery), SpoolError> {
+        let root = root.into();
+        let mut recovery = Recovery::default();
+        fs::create_dir_all(&root)?;
+        let next_id_path = root.join(NEXT_ID_FILE);
+        let temporary = next_id_path.with_extension(TEMPORARY_EXTENSION);
+        if temporary.exists() {
+            fs::remove_file(&temporary)?;
+            recovery.discarded += 1;
+        }
+        let mut next_id = match fs::read(&next_id_path) {
+            Ok(object) => {
+                let payload = unseal(&object).ok_or(SerializationError::InvalidData)?;
+                JobId::deserialize_compressed(payload)?
+            },
+            Err(e) if e.kind() == io::ErrorKind::NotFound => 0,
+            Err(e) => return Err(e.into()),
+        };
+        for dir in [PENDING_DIR, COMPLETED_DIR] {
+            fs::create_dir_all(root.join(dir))?;
+            for entry in fs::read_dir(root.join(dir))? {
+                let path = entry?.path();
+                match parse_job_id(&path) {
+                    Some(id) => {
+                        next_id = next_id.max(id.checked_add(1).ok_or(SpoolError::IdsExhausted)?)
+                    },
+                    None if path.extension().is_some_and(|e| e == TEMPORARY_EXTENSION) => {
+                        fs::remove_file(&path)?;
+                        recovery.discarded += 1;
+                    },
+                    None => {},
+                }
+            }
+        }
+
+        let spool = Self {
+            root,
+            next_id,
+            _pairing: PhantomData,
+        };
+        for id in spool.pending()? {
+            if spool.path(COMPLETED_DIR, id).exists() {
+                fs::remove_file(spool.path(PENDING_DIR, id))?;
+                recovery.resolved += 1;
+            } else {
+                recovery.pending += 1;
+            }
+        }
+        recovery.completed = spool.completed()?.len();
+        sync_dir(&spool.root.join(PENDING_DIR))?;
+        Ok((spool, recovery))
+    }
+
+    /// Returns the directory of the spool.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Spools `job`, returning its identifier once it is durable.
+    ///
+    /// The next identifier is recorded before the job, so a crash in
+    /// between skips an identifier rather than reusing it. Fails with
+    /// [`SpoolError::IdsExhausted`] once the largest [`JobId`] is reached.
+    pub fn submit(&mut self, job: &ProvingJob) -> Result<JobId, SpoolError> {
+        let id = self.next_id;
+        let next_id = id.checked_add(1).ok_or(SpoolError::IdsExhausted)?;
+        write_durably(&self.root.join(NEXT_ID_FILE), &seal(&next_id)?)?;
+        self.next_id = next_id;
+        write_durably(&self.path(PENDING_DIR, id), &seal(job)?)?;
+        Ok(id)
+    }
+
+    /// Returns the identifiers of the pending jobs, in submission order.
+    pub fn pending(&self) -> Result<Vec<JobId>, SpoolError> {
+        self.list(PENDING_DIR)
+    }
+
+    /// Returns the identifiers of the jobs whose proof is completed and not
+    /// yet taken, in submission order.
+    pub fn completed(&self) -> Result<Vec<JobId>, SpoolError> {
+        self.list(COMPLETED_DIR)
+    }
+
+    /// Reads the pending job `id`.
+    pub fn job(&self, id: JobId) -> Result<ProvingJob, SpoolError> {
+        self.read(PENDING_DIR, id)
+    }
+
+    /// Records `proof` as the proof of the pending job `id`, then removes
+    /// the job. A crash in between leaves both, which [`Self::open`]
+    /// resolves in favor of the proof.
+    pub fn complete(&mut self, id: JobId, proof: &Proof<E>) -> Result<(), SpoolError> {
+        let pending = self.path(PENDING_DIR, id);
+        if !pending.exists() {
+            return Err(SpoolError::UnknownJob(id));
+        }
+        write_durably(&self.path(COMPLETED_DIR, id), &seal(proof)?)?;
+        fs::remove_file(&pending)?;
+        sync_dir(&self.root.join(PENDING_DIR))?;
+        Ok(())
+    }
+
+    /// Reads the completed proof of job `id`.
+    pub fn proof(&self, id: JobId) -> Result<Proof<E>, SpoolError> {
+        self.read(COMPLETED_DIR, id)
+    }
+
+    /// Removes and returns the completed proof of job `id`, once it has
+    /// been delivered.
+    pub fn take_proof(&mut self, id: JobId) -> Result<Proof<E>, SpoolError> {
+        let proof = self.proof(id)?;
+        fs::remove_file(self.path(COMPLETED_DIR, id))?;
+        sync_dir(&self.root.join(COMPLETED_DIR))?;
+        Ok(proof)
+    }
+
+    /// Returns the path of the object of job `id` in `dir`.
+    fn path(&self, dir: &str, id: JobId) -> PathBuf {
+        self.root.join(dir).join(format!("{:020}", id))
+    }
+
+    /// Returns the sorted identifiers of the objects in `dir`.
+    fn list(&self, dir: &str) -> Result<Vec<JobId>, SpoolError> {
+        let mut ids = Vec::new();
+        for entry in fs::read_dir(self.root.join(dir))? {
+            ids.extend(parse_job_id(&entry?.path()));
+        }
+        ids.sort_unstable();
+        Ok(ids)
+    }
+
+    /// Reads and checks the object of job `id` in `dir`.
+    fn read<T: CanonicalDeserialize>(&self, dir: &str, id: JobId) -> Result<T, SpoolError> {
+        let object = match fs::read(self.path(dir, id)) {
+            Ok(object) => object,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                return Err(SpoolError::UnknownJob(id))
+            },
+            Err(e) => return Err(e.into()),
+        };
+        let payload = unseal(&object).ok_or(SpoolError::Corrupted(id))?;
+        Ok(T::deserialize_compressed(payload)?)
+    }
+}
+
+/// Returns the job identifier a spooled file is named after, if any.
+fn parse_job_id(path: &Path) -> Option<JobId> {
+    let name = path.file_name()?.to_str()?;
+    if name.len() != 20 || !name.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    name.parse().ok()
+}
+
+/// Returns the compressed serialization of `value`, prefixed with its
+/// SHA-256 checksum.
+fn seal(value: &impl CanonicalSerialize) -> Result<Vec<u8>, SpoolError> {
+    let mut object = ark_std::vec![0u8; CHECKSUM_SIZE];
+    value.serialize_compressed(&mut object)?;
+    let checksum = sha256(&object[CHECKSUM_SIZE..]);
+    object[..CHECKSUM_SIZE].copy_from_slice(&checksum);
+    Ok(object)
+}
+
+/// Returns the serialization sealed in `object` by [`seal`], or `None` if
+/// it does not match its checksum.
+fn unseal(object: &[u8]) -> Option<&[u8]> {
+    if object.len() < CHECKSUM_SIZE {
+        return None;
+    }
+    let (checksum, payload) = object.split_at(CHECKSUM_SIZE);
+    (sha256(payload) == checksum).then_some(payload)
+}
+
+/// Writes `object` to `path` through a synced temporary file, then syncs
+/// the directory so that the rename itself survives a crash.
+fn write_durably(path: &Path, object: &[u8]) -> Result<(), SpoolError> {
+    let temporary = path.with_extension(TEMPORARY_EXTENSION);
+    let mut file = File::create(&temporary)?;
+    file.write_all(object)?;
+    file.sync_all()?;
+    fs::rename(&temporary, path)?;
+    if let Some(parent) = path.parent() {
+        sync_dir(parent)?;
+    }
+    Ok(())
+}
+
+/// Syncs the entries of the directory `dir`, where the platform supports
+/// it.
+fn sync_dir(dir: &Path) -> Result<(), SpoolError> {
+    #[cfg(unix)]
+    File::open(dir)?.sync_all()?;
+    #[cfg(not(unix))]
+    let _ = dir;
+    Ok(())
+}
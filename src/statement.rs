@@ -0,0 +1,136 @@
+use crate::{
+    inputs::split_hash, r1cs_to_qap::R1CSToQAP, Groth16, PreparedVerifyingKey, Proof,
+    VerificationError,
+};
+use ark_ec::pairing::Pairing;
+use ark_ff::{Fp, FpConfig, PrimeField};
+use ark_std::vec::Vec;
+
+/// Derives [`ToPublicInputs`] for a struct, for every prime field in which
+/// the types of its fields implement [`PublicInputValue`].
+///
+/// Fields are encoded in declaration order, as variable inputs unless
+/// marked `#[public_input(static)]`. Fields marked `#[public_input(skip)]`
+/// are not inputs.
+///
+/// ```ignore
+/// #[derive(ToPublicInputs)]
+/// struct SwapStatement {
+///     #[public_input(static)]
+///     pool_id: u64,
+///     amount_in: u64,
+///     min_out: u64,
+/// }
+/// ```
+#[cfg(feature = "derive")]
+pub use darklake_groth16_derive::ToPublicInputs;
+
+/// A value encoded as a fixed number of public inputs in the field `F`.
+///
+/// Integers are embedded as field elements, which requires fields of more
+/// than 128 bits for `u128`, as are the scalar fields of pairing-friendly
+/// curves. 32-byte arrays are hashes, split in two by [`split_hash`].
+pub trait PublicInputValue<F: PrimeField> {
+    /// The number of field elements encoding a value.
+    const NUM_ELEMENTS: usize;
+
+    /// Appends the encoding of `self` to `inputs`.
+    fn append_to(&self, inputs: &mut Vec<F>);
+}
+
+macro_rules! impl_public_input_value_for_uint {
+    ($($uint:ty),*) => {
+        $(impl<F: PrimeField> PublicInputValue<F> for $uint {
+            const NUM_ELEMENTS: usize = 1;
+
+            fn append_to(&self, inputs: &mut Vec<F>) {
+                inputs.push(F::from(*self));
+            }
+        })*
+    };
+}
+
+impl_public_input_value_for_uint!(bool, u8, u16, u32, u64, u128);
+
+impl<F: PrimeField> PublicInputValue<F> for [u8; 32] {
+    const NUM_ELEMENTS: usize = 2;
+
+    fn append_to(&self, inputs: &mut Vec<F>) {
+        inputs.extend(split_hash::<F>(self));
+    }
+}
+
+impl<P: FpConfig<N>, const N: usize> PublicInputValue<Fp<P, N>> for Fp<P, N> {
+    const NUM_ELEMENTS: usize = 1;
+
+    fn append_to(&self, inputs: &mut Vec<Fp<P, N>>) {
+        inputs.push(*self);
+    }
+}
+
+/// A typed statement, such as the parameters of a swap, encoded as the
+/// static and variable public inputs of a circuit, so that the prover and
+/// the verifier derive the same inputs from the same statement.
+///
+/// Implement it with the `ToPublicInputs` derive macro of the `derive`
+/// feature.
+pub trait ToPublicInputs<F: PrimeField> {
+    /// The number of static inputs encoding a statement.
+    const NUM_STATIC_INPUTS: usize;
+
+    /// The number of variable inputs encoding a statement.
+    const NUM_VARIABLE_INPUTS: usize;
+
+    /// Appends the static inputs of `self` to `inputs`.
+    fn append_static_inputs(&self, inputs: &mut Vec<F>);
+
+    /// Appends the variable inputs of `self` to `inputs`.
+    fn append_variable_inputs(&self, inputs: &mut Vec<F>);
+
+    /// Returns the static inputs of `self`.
+    fn static_inputs(&self) -> Vec<F> {
+        let mut inputs = Vec::with_capacity(Self::NUM_STATIC_INPUTS);
+        self.append_static_inputs(&mut inputs);
+        inputs
+    }
+
+    /// Returns the variable inputs of `self`.
+    fn variable_inputs(&self) -> Vec<F> {
+        let mut inputs = Vec::with_capacity(Self::NUM_VARIABLE_INPUTS);
+        self.append_variable_inputs(&mut inputs);
+        inputs
+    }
+
+    /// Returns all the public inputs of `self`, static inputs first, as
+    /// assigned by a circuit proving the statement.
+    fn public_inputs(&self) -> Vec<F> {
+        let mut inputs = Vec::with_capacity(Self::NUM_STATIC_INPUTS + Self::NUM_VARIABLE_INPUTS);
+        self.append_static_inputs(&mut inputs);
+        self.append_variable_inputs(&mut inputs);
+        inputs
+    }
+}
+
+impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
+    /// Verify a proof of `statement`, with its static and variable inputs,
+    /// see [`Groth16::verify_with_variables`].
+    pub fn verify_statement<S: ToPublicInputs<E::ScalarField>>(
+        pvk: &PreparedVerifyingKey<E>,
+        proof: &Proof<E>,
+        statement: &S,
+    ) -> Result<bool, VerificationError> {
+        Self::verify_with_variables(
+            pvk,
+            proof,
+            &statement.static_inputs(),
+            &statement.variable_inputs(),
+        )
+    }
+}
+
+/// Items used by the code generated by the derive macro.
+#[doc(hidden)]
+pub mod __private {
+    pub use ark_ff::PrimeField;
+    pub use ark_std::vec::Vec;
+}
@@ -0,0 +1,184 @@
+use crate::{r1cs_to_qap::R1CSToQAP, Groth16, PreparedVerifyingKey, Proof};
+use ark_crypto_primitives::snark::SNARK;
+use ark_ec::pairing::Pairing;
+use ark_relations::r1cs::SynthesisError;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, SerializationError};
+use ark_std::{cfg_into_iter, collections::VecDeque, fmt, vec::Vec};
+use std::{
+    io::{self, Read, Write},
+    sync::mpsc::{sync_channel, Receiver},
+    thread,
+};
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// The number of records deserialized and verified together.
+const BATCH_SIZE: usize = 64;
+
+/// The number of deserialized batches buffered ahead of verification.
+const BUFFERED_BATCHES: usize = 2;
+
+/// An error on a record of a stream of proofs.
+#[derive(Debug)]
+pub enum StreamError {
+    /// The record could not be read or deserialized.
+    Serialization(SerializationError),
+    /// The record does not match the verifying key.
+    Synthesis(SynthesisError),
+}
+
+impl fmt::Display for StreamError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StreamError::Serialization(e) => write!(f, "{}", e),
+            StreamError::Synthesis(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for StreamError {}
+
+impl From<SerializationError> for StreamError {
+    fn from(e: SerializationError) -> Self {
+        StreamError::Serialization(e)
+    }
+}
+
+impl From<SynthesisError> for StreamError {
+    fn from(e: SynthesisError) -> Self {
+        StreamError::Synthesis(e)
+    }
+}
+
+type Record<E> = (Proof<E>, Vec<<E as Pairing>::ScalarField>);
+
+/// Writes a record of `proof` and its public `inputs` as read by
+/// [`Groth16::verify_stream`]: the length of the record as 8 little-endian
+/// bytes, followed by the compressed serializations of the proof and of the
+/// inputs.
+pub fn write_stream_record<E: Pairing>(
+    mut writer: impl Write,
+    proof: &Proof<E>,
+    inputs: &[E::ScalarField],
+) -> Result<(), SerializationError> {
+    let mut record = Vec::with_capacity(proof.compressed_size() + inputs.compressed_size());
+    proof.serialize_compressed(&mut record)?;
+    inputs.serialize_compressed(&mut record)?;
+    writer.write_all(&(record.len() as u64).to_le_bytes())?;
+    writer.write_all(&record)?;
+    Ok(())
+}
+
+/// Reads the next record from `reader`, or `None` at the end of the stream.
+/// An error other than a malformed record ends the stream.
+fn read_record<E: Pairing>(
+    reader: &mut impl Read,
+) -> Option<Result<Result<Record<E>, SerializationError>, SerializationError>> {
+    let mut len = [0u8; 8];
+    let mut filled = 0;
+    while filled < len.len() {
+        match reader.read(&mut len[filled..]) {
+            Ok(0) if filled == 0 => return None,
+            Ok(0) => return Some(Err(io::Error::from(io::ErrorKind::UnexpectedEof).into())),
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {},
+            Err(e) => return Some(Err(e.into())),
+        }
+    }
+    let len = u64::from_le_bytes(len);
+
+    // Read through `take` so that a corrupted length does not allocate
+    // more than the bytes actually available.
+    let mut record = Vec::new();
+    if let Err(e) = reader.by_ref().take(len).read_to_end(&mut record) {
+        return Some(Err(e.into()));
+    }
+    if (record.len() as u64) < len {
+        return Some(Err(io::Error::from(io::ErrorKind::UnexpectedEof).into()));
+    }
+
+    let mut bytes = &record[..];
+    let parsed = Proof::deserialize_compressed(&mut bytes).and_then(|proof| {
+        let inputs = Vec::deserialize_compressed(&mut bytes)?;
+        if bytes.is_empty() {
+            Ok((proof, inputs))
+        } else {
+            Err(SerializationError::InvalidData)
+        }
+    });
+    Some(Ok(parsed))
+}
+
+/// An iterator over the verification results of a stream of proofs, see
+/// [`Groth16::verify_stream`].
+pub struct VerifyStream<E: Pairing> {
+    pvk: PreparedVerifyingKey<E>,
+    batches: Receiver<Vec<Result<Record<E>, SerializationError>>>,
+    results: VecDeque<Result<bool, StreamError>>,
+}
+
+impl<E: Pairing> Iterator for VerifyStream<E> {
+    type Item = Result<bool, StreamError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.results.is_empty() {
+            let batch = self.batches.recv().ok()?;
+            let pvk = &self.pvk;
+            let results: Vec<_> = cfg_into_iter!(batch)
+                .map(|record| {
+                    let (proof, inputs) = record?;
+                    Ok(Groth16::<E>::verify_with_processed_vk(
+                        pvk, &inputs, &proof,
+                    )?)
+                })
+                .collect();
+            self.results.extend(results);
+        }
+        self.results.pop_front()
+    }
+}
+
+impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
+    /// Verifies the proofs of a stream of records written by
+    /// [`write_stream_record`], yielding one result per record, in order.
+    ///
+    /// Records are deserialized on a background thread while previous
+    /// records are verified, in parallel if the `parallel` feature is
+    /// enabled. A malformed record yields an error and verification
+    /// continues with the next record; a read error or a truncated record
+    /// yields an error and ends the stream.
+    pub fn verify_stream<R>(pvk: &PreparedVerifyingKey<E>, mut reader: R) -> VerifyStream<E>
+    where
+        R: Read + Send + 'static,
+    {
+        let (sender, batches) = sync_channel(BUFFERED_BATCHES);
+        thread::spawn(move || loop {
+            let mut batch = Vec::with_capacity(BATCH_SIZE);
+            let mut done = false;
+            while batch.len() < BATCH_SIZE {
+                match read_record::<E>(&mut reader) {
+                    Some(Ok(record)) => batch.push(record),
+                    Some(Err(e)) => {
+                        batch.push(Err(e));
+                        done = true;
+                        break;
+                    },
+                    None => {
+                        done = true;
+                        break;
+                    },
+                }
+            }
+            if (!batch.is_empty() && sender.send(batch).is_err()) || done {
+                break;
+            }
+        });
+
+        VerifyStream {
+            pvk: pvk.clone(),
+            batches,
+            results: VecDeque::new(),
+        }
+    }
+}
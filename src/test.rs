@@ -1,16 +1,76 @@
-use crate::{prepare_verifying_key, Groth16};
+use crate::{
+    abi::{InputKind, SchemaError, VerifyingKeyWithSchema},
+    aggregate_proof_points,
+    artifact::{expand_proving_key, CeremonyArtifact},
+    bundle::{ProofBundle, VerifyingKeyRegistry},
+    cache::{CachedVerifier, ReplayError, ReplayGuard},
+    checkpoint::{ProverCheckpoint, ProverStage},
+    circuits::{
+        self, MerkleMembershipCircuit, MiMC, NullifierCircuit, PreimageCircuit, RangeCircuit,
+    },
+    codegen::generate_verifier,
+    diagnose::{diagnose_input_mismatch, InputTransformation},
+    export::ConstraintExport,
+    generator::SetupPhase,
+    inputs::{
+        check_split_hash, embed_bytes, embed_u64, extract_bytes, extract_u64, recompose_hash,
+        split_hash, Endianness, PublicInputs, HASH_LIMB_BITS,
+    },
+    keystore::{KeyStore, KeyStoreError, LocalKeyStore},
+    link::{InputEqualityProof, PartiallyHiddenInputs},
+    loader::{
+        load_any_verifying_key, load_proof, load_proving_key, load_verifying_key, serialize_tagged,
+        CurveLayout, CurveTag, KeyLoadError,
+    },
+    msm::{
+        msm_bounded_bits, msm_constant_time, ConstantTimeMsm, CpuMsm, HybridMsm, InvalidWindow,
+        MsmBackend, MsmTuner, TunedMsm, WindowTable, MAX_TUNING_WINDOW,
+    },
+    onchain::{G2CoordinateOrder, OffloadedProof, PreparedKeyLines, ProofWireFormat},
+    prepare_verifying_key,
+    preparer::ProofPreparer,
+    progress::Progress,
+    prover::{AllocatorStats, ProverConfig, ProverPhase},
+    r1cs_to_qap::{LibsnarkReduction, R1CSToQAP},
+    report::{estimate_sizes, CircuitMismatch, CircuitShape},
+    rng::{BlindingFactors, ForkDetectingRng, ProveError, ProverRng, ProverRngError},
+    rotation::{EpochUsed, KeyRotation},
+    stream::{write_stream_record, StreamError},
+    testing::SyntheticCircuit,
+    transcript::{absorb_public_inputs, public_inputs_transcript_bytes},
+    versioned::{input_version, CircuitVersion, VersionedCircuit},
+    witness::check_witness_with_matrices,
+    AnyVerifyingKey, Groth16, InputCommitment, InputCountMismatch, KeyBasis, LegacyVerifyingKey,
+    PaddingPolicy, Proof, ProvingKey, SNARKWithPartitionedInputs, StrictVerificationError,
+    VerificationError, VerifyingKey,
+};
 use ark_crypto_primitives::snark::{CircuitSpecificSetupSNARK, SNARK};
-use ark_ec::pairing::Pairing;
-use ark_ff::Field;
+use ark_crypto_primitives::sponge::{
+    poseidon::{find_poseidon_ark_and_mds, PoseidonConfig, PoseidonSponge},
+    CryptographicSponge,
+};
+use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup, VariableBaseMSM};
+use ark_ff::{Field, One, PrimeField, Zero};
 use ark_relations::{
     lc,
-    r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError},
+    r1cs::{
+        ConstraintMatrices, ConstraintSynthesizer, ConstraintSystem, ConstraintSystemRef,
+        SynthesisError, SynthesisMode, Variable,
+    },
 };
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Compress, Valid, Validate};
 use ark_std::{
-    rand::{RngCore, SeedableRng},
-    test_rng, UniformRand,
+    rand::{rngs::StdRng, CryptoRng, RngCore, SeedableRng},
+    test_rng,
+    time::Duration,
+    UniformRand,
+};
+use std::{
+    future::Future,
+    sync::atomic::{AtomicU64, Ordering as AtomicOrdering},
 };
 
+#[derive(Clone)]
 struct MySillyCircuit<F: Field> {
     a: Option<F>,
     b: Option<F>,
@@ -42,6 +102,26 @@ impl<ConstraintF: Field> ConstraintSynthesizer<ConstraintF> for MySillyCircuit<C
     }
 }
 
+#[derive(Clone)]
+struct InputsCircuit<F: Field> {
+    inputs: Vec<Option<F>>,
+}
+
+impl<ConstraintF: Field> ConstraintSynthesizer<ConstraintF> for InputsCircuit<ConstraintF> {
+    fn generate_constraints(
+        self,
+        cs: ConstraintSystemRef<ConstraintF>,
+    ) -> Result<(), SynthesisError> {
+        for input in self.inputs {
+            let x = cs.new_input_variable(|| input.ok_or(SynthesisError::AssignmentMissing))?;
+            let y = cs.new_witness_variable(|| input.ok_or(SynthesisError::AssignmentMissing))?;
+            cs.enforce_constraint(lc!() + x, lc!() + Variable::One, lc!() + y)?;
+        }
+
+        Ok(())
+    }
+}
+
 fn test_prove_and_verify<E>(n_iters: usize)
 where
     E: Pairing,
@@ -117,43 +197,7270 @@ where
     }
 }
 
-mod bls12_377 {
-    use super::{test_prove_and_verify, test_rerandomize};
-    use ark_bls12_377::Bls12_377;
+fn test_hybrid_msm_backend<E>()
+where
+    E: Pairing,
+{
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
 
-    #[test]
-    fn prove_and_verify() {
-        test_prove_and_verify::<Bls12_377>(100);
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let pvk = prepare_verifying_key::<E>(&vk);
+    let backend = HybridMsm::new(CpuMsm, CpuMsm).with_min_split_size(0);
+
+    for _ in 0..3 {
+        let a = E::ScalarField::rand(&mut rng);
+        let b = E::ScalarField::rand(&mut rng);
+        let r = E::ScalarField::rand(&mut rng);
+        let s = E::ScalarField::rand(&mut rng);
+        let circuit = MySillyCircuit {
+            a: Some(a),
+            b: Some(b),
+        };
+
+        let expected = Groth16::<E>::create_proof_with_reduction(circuit, &pk, r, s).unwrap();
+        let proof = Groth16::<E>::create_proof_with_reduction_and_backend(
+            MySillyCircuit {
+                a: Some(a),
+                b: Some(b),
+            },
+            &pk,
+            r,
+            s,
+            &backend,
+        )
+        .unwrap();
+
+        assert_eq!(proof, expected);
+        assert!(Groth16::<E>::verify_with_processed_vk(&pvk, &[a * b], &proof).unwrap());
+        assert!((0.0..=1.0).contains(&backend.accelerator_share()));
     }
+}
 
-    #[test]
-    fn rerandomize() {
-        test_rerandomize::<Bls12_377>();
+fn test_lagrange_basis_key<E>()
+where
+    E: Pairing,
+{
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let pk = Groth16::<E>::generate_random_parameters_with_basis(
+        MySillyCircuit { a: None, b: None },
+        &mut rng,
+        1,
+        0,
+        KeyBasis::Lagrange,
+    )
+    .unwrap();
+    let pvk = prepare_verifying_key::<E>(&pk.vk);
+
+    for _ in 0..5 {
+        let a = E::ScalarField::rand(&mut rng);
+        let b = E::ScalarField::rand(&mut rng);
+
+        let proof = Groth16::<E>::prove(
+            &pk,
+            MySillyCircuit {
+                a: Some(a),
+                b: Some(b),
+            },
+            &mut rng,
+        )
+        .unwrap();
+
+        assert!(Groth16::<E>::verify_with_processed_vk(&pvk, &[a * b], &proof).unwrap());
+        assert!(!Groth16::<E>::verify_with_processed_vk(&pvk, &[a], &proof).unwrap());
     }
+
+    let mut bytes = Vec::new();
+    pk.serialize_compressed(&mut bytes).unwrap();
+    assert_eq!(
+        ProvingKey::<E>::deserialize_compressed(&*bytes).unwrap(),
+        pk
+    );
+
+    // Keys serialized without the basis tag, before it was introduced, are
+    // in the monomial basis.
+    let mut monomial_pk = pk.clone();
+    monomial_pk.h_basis = KeyBasis::Monomial;
+    let mut legacy_bytes = Vec::new();
+    monomial_pk.serialize_compressed(&mut legacy_bytes).unwrap();
+    assert_eq!(legacy_bytes.pop(), Some(0));
+    assert_eq!(
+        ProvingKey::<E>::deserialize_compressed(&*legacy_bytes).unwrap(),
+        monomial_pk
+    );
+    assert_eq!(load_proving_key::<E>(&legacy_bytes).unwrap(), monomial_pk);
+    legacy_bytes.push(2);
+    assert!(ProvingKey::<E>::deserialize_compressed(&*legacy_bytes).is_err());
+
+    // A key with its tag can be followed by more data, but a legacy key
+    // takes the first byte of what follows as its tag.
+    let mut reader = &bytes.iter().chain(&[1, 2]).copied().collect::<Vec<_>>()[..];
+    assert_eq!(
+        ProvingKey::<E>::deserialize_compressed(&mut reader).unwrap(),
+        pk
+    );
+    assert_eq!(reader, [1, 2]);
+    legacy_bytes.pop();
+    legacy_bytes.extend([1, 2]);
+    let mut reader = &legacy_bytes[..];
+    let read = ProvingKey::<E>::deserialize_compressed(&mut reader).unwrap();
+    assert_eq!(read.h_basis, KeyBasis::Lagrange);
+    assert_eq!(reader, [2]);
 }
 
-mod bw6_761 {
-    use super::{test_prove_and_verify, test_rerandomize};
+fn test_low_memory_prover<E>()
+where
+    E: Pairing,
+{
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
 
-    use ark_bw6_761::BW6_761;
+    for h_basis in [KeyBasis::Monomial, KeyBasis::Lagrange] {
+        let pk = Groth16::<E>::generate_random_parameters_with_basis(
+            MySillyCircuit { a: None, b: None },
+            &mut rng,
+            1,
+            0,
+            h_basis,
+        )
+        .unwrap();
+        let pvk = prepare_verifying_key::<E>(&pk.vk);
 
-    #[test]
-    fn prove_and_verify() {
-        test_prove_and_verify::<BW6_761>(1);
+        let a = E::ScalarField::rand(&mut rng);
+        let b = E::ScalarField::rand(&mut rng);
+        let r = E::ScalarField::rand(&mut rng);
+        let s = E::ScalarField::rand(&mut rng);
+        let circuit = || MySillyCircuit {
+            a: Some(a),
+            b: Some(b),
+        };
+
+        let proof = Groth16::<E>::create_proof_with_reduction(circuit(), &pk, r, s).unwrap();
+        let low_memory_proof = Groth16::<E>::create_proof_with_config(
+            circuit(),
+            &pk,
+            r,
+            s,
+            &CpuMsm,
+            &ProverConfig::low_memory(),
+        )
+        .unwrap();
+
+        assert_eq!(proof, low_memory_proof);
+        assert!(Groth16::<E>::verify_with_processed_vk(&pvk, &[a * b], &low_memory_proof).unwrap());
     }
+}
 
-    #[test]
-    fn rerandomize() {
-        test_rerandomize::<BW6_761>();
+fn test_verify_with_commitment<E>()
+where
+    E: Pairing,
+{
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, ck) = Groth16::<E>::generate_random_parameters_with_commitment(
+        MySillyCircuit { a: None, b: None },
+        &mut rng,
+        1,
+        0,
+        1,
+    )
+    .unwrap();
+    assert!(pk.vk.gamma_abc_g1_variable.is_empty());
+    assert_eq!(ck.bases.len(), 1);
+    let pvk = prepare_verifying_key::<E>(&pk.vk);
+
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let proof = Groth16::<E>::prove(
+        &pk,
+        MySillyCircuit {
+            a: Some(a),
+            b: Some(b),
+        },
+        &mut rng,
+    )
+    .unwrap();
+
+    let commitment = Groth16::<E>::commit_to_inputs(&ck, &[a * b]).unwrap();
+    assert!(Groth16::<E>::verify_with_commitment(&pvk, &ck.vk, &proof, &[], &commitment).unwrap());
+
+    let wrong_commitment = Groth16::<E>::commit_to_inputs(&ck, &[a]).unwrap();
+    assert!(
+        !Groth16::<E>::verify_with_commitment(&pvk, &ck.vk, &proof, &[], &wrong_commitment)
+            .unwrap()
+    );
+
+    // A commitment is only accepted with the proof of knowledge of its own
+    // opening.
+    let mut swapped = commitment.clone();
+    swapped.pok = wrong_commitment.pok;
+    assert!(!Groth16::<E>::verify_with_commitment(&pvk, &ck.vk, &proof, &[], &swapped).unwrap());
+
+    // A commitment cancelling the other inputs would satisfy any statement
+    // with a trivial proof, but its opening is unknown.
+    let trivial_proof = Proof::<E> {
+        a: pk.vk.alpha_g1,
+        b: pk.vk.beta_g2,
+        c: E::G1Affine::zero(),
+    };
+    let cancelling = (-pk.vk.gamma_abc_g1_static[0].into_group()).into_affine();
+    for pok in [E::G1Affine::zero(), cancelling, pk.vk.alpha_g1] {
+        let forged = InputCommitment::<E> {
+            commitment: cancelling,
+            pok,
+        };
+        assert!(
+            !Groth16::<E>::verify_with_commitment(&pvk, &ck.vk, &trivial_proof, &[], &forged)
+                .unwrap()
+        );
     }
+
+    // The verifying key of a circuit with committed inputs serializes like
+    // any other.
+    let mut bytes = Vec::new();
+    pk.vk.serialize_compressed(&mut bytes).unwrap();
+    assert_eq!(
+        VerifyingKey::<E>::deserialize_compressed(&*bytes).unwrap(),
+        pk.vk
+    );
+
+    assert!(Groth16::<E>::commit_to_inputs(&ck, &[a, b]).is_err());
+    assert!(Groth16::<E>::generate_random_parameters_with_commitment(
+        MySillyCircuit { a: None, b: None },
+        &mut rng,
+        1,
+        0,
+        2,
+    )
+    .is_err());
 }
 
-mod bn_254 {
-    use super::test_prove_and_verify;
-    use ark_bn254::Bn254;
+fn test_rotate_delta<E>()
+where
+    E: Pairing,
+{
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
 
-    #[test]
-    fn prove_and_verify() {
-        test_prove_and_verify::<Bn254>(100);
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let (rotated_pk, rotated_vk) = Groth16::<E>::rotate_delta(&pk, &vk, &mut rng).unwrap();
+    assert_ne!(rotated_vk.delta_g2, vk.delta_g2);
+    assert_eq!(rotated_vk.gamma_abc_g1_variable, vk.gamma_abc_g1_variable);
+    assert_eq!(rotated_pk.vk, rotated_vk);
+
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let circuit = || MySillyCircuit {
+        a: Some(a),
+        b: Some(b),
+    };
+    let proof = Groth16::<E>::prove(&rotated_pk, circuit(), &mut rng).unwrap();
+    let old_proof = Groth16::<E>::prove(&pk, circuit(), &mut rng).unwrap();
+    assert!(Groth16::<E>::verify(&rotated_vk, &[a * b], &proof).unwrap());
+    assert!(!Groth16::<E>::verify(&rotated_vk, &[a * b], &old_proof).unwrap());
+    assert!(!Groth16::<E>::verify(&vk, &[a * b], &proof).unwrap());
+
+    assert!(Groth16::<E>::rotate_delta(&pk, &rotated_vk, &mut rng).is_err());
+}
+
+fn test_truncate_inputs<E>()
+where
+    E: Pairing,
+{
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    // Two static and one variable inputs, the last two of which are spare.
+    let pk = Groth16::<E>::generate_random_parameters_with_reduction(
+        InputsCircuit {
+            inputs: vec![None; 3],
+        },
+        &mut rng,
+        3,
+        0,
+    )
+    .unwrap();
+    let x = E::ScalarField::rand(&mut rng);
+    let zero = E::ScalarField::zero();
+    let proof = Groth16::<E>::prove(
+        &pk,
+        InputsCircuit {
+            inputs: vec![Some(x), Some(zero), Some(zero)],
+        },
+        &mut rng,
+    )
+    .unwrap();
+
+    let mut vk = pk.vk.clone();
+    vk.truncate_inputs(2).unwrap();
+    assert_eq!(vk.gamma_abc_g1_static.len(), 3);
+    assert!(vk.gamma_abc_g1_variable.is_empty());
+    let pvk = prepare_verifying_key::<E>(&vk);
+    assert!(Groth16::<E>::verify_with_variables(&pvk, &proof, &[x, zero], &[]).unwrap());
+
+    vk.truncate_inputs(1).unwrap();
+    let pvk = prepare_verifying_key::<E>(&vk);
+    assert_eq!(pvk.expected_total_inputs(), 1);
+    assert!(Groth16::<E>::verify_with_variables(&pvk, &proof, &[x], &[]).unwrap());
+    assert!(!Groth16::<E>::verify_with_variables(&pvk, &proof, &[zero], &[]).unwrap());
+    assert!(Groth16::<E>::verify_with_variables(&pvk, &proof, &[x], &[zero]).is_err());
+
+    // Truncating to more inputs than the key has fails and keeps the key.
+    assert!(vk.truncate_inputs(2).is_err());
+    assert_eq!(vk.gamma_abc_g1_static.len(), 2);
+
+    assert!(VerifyingKey::<E>::default().truncate_inputs(0).is_err());
+}
+
+fn test_public_inputs<E>()
+where
+    E: Pairing,
+{
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let pvk = prepare_verifying_key::<E>(&vk);
+
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let circuit = || MySillyCircuit {
+        a: Some(a),
+        b: Some(b),
+    };
+
+    let inputs = Groth16::<E>::public_inputs(circuit()).unwrap();
+    assert_eq!(inputs, vec![a * b]);
+
+    let proof = Groth16::<E>::prove(&pk, circuit(), &mut rng).unwrap();
+    assert!(Groth16::<E>::verify_with_processed_vk(&pvk, &inputs, &proof).unwrap());
+
+    assert!(Groth16::<E>::public_inputs(MySillyCircuit {
+        a: Some(a),
+        b: None
+    })
+    .is_err());
+}
+
+fn test_cross_field_inputs<E>()
+where
+    E: Pairing,
+{
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let mut hash = [0u8; 32];
+    rng.fill_bytes(&mut hash);
+    let amount = rng.next_u64();
+    let limbs = split_hash::<E::ScalarField>(&hash);
+    let inputs = vec![limbs[0], limbs[1], embed_u64(amount)];
+
+    let circuit = |inputs: &[E::ScalarField]| InputsCircuit {
+        inputs: inputs.iter().copied().map(Some).collect(),
+    };
+    let (pk, vk) = Groth16::<E>::setup(circuit(&inputs), &mut rng).unwrap();
+    let proof = Groth16::<E>::prove(&pk, circuit(&inputs), &mut rng).unwrap();
+    assert!(Groth16::<E>::verify(&vk, &inputs, &proof).unwrap());
+
+    // The verifier recomposes the hash and the amount from the inputs.
+    assert_eq!(recompose_hash(&inputs[..2]).unwrap(), hash);
+    assert!(check_split_hash(&inputs[..2], &hash));
+    assert_eq!(extract_u64(inputs[2]).unwrap(), amount);
+
+    // A limb wrapped around the modulus aliases the same hash in the field
+    // and is rejected.
+    let shift = E::ScalarField::from(2u64).pow([HASH_LIMB_BITS as u64]);
+    let aliased = [limbs[0] - E::ScalarField::one(), limbs[1] + shift];
+    assert!(!check_split_hash(&aliased, &hash));
+    assert!(recompose_hash(&limbs[..1]).is_err());
+    assert!(extract_u64(-E::ScalarField::one()).is_err());
+
+    // Integers must fit in the capacity of the field, whatever their width.
+    let capacity = E::ScalarField::MODULUS_BIT_SIZE as usize - 1;
+    let mut max = vec![0u8; 64];
+    max[64 - capacity / 8..].fill(0xff);
+    max[63 - capacity / 8] = (1 << (capacity % 8)) - 1;
+    let embedded = embed_bytes::<E::ScalarField>(&max, Endianness::Big).unwrap();
+    assert_eq!(extract_bytes(embedded, 64, Endianness::Big).unwrap(), max);
+    max[63 - capacity / 8] += 1;
+    assert!(embed_bytes::<E::ScalarField>(&max, Endianness::Big).is_err());
+    max.reverse();
+    assert!(embed_bytes::<E::ScalarField>(&max, Endianness::Little).is_err());
+
+    let little = embed_bytes::<E::ScalarField>(&[1, 2], Endianness::Little).unwrap();
+    assert_eq!(little, E::ScalarField::from(0x0201u64));
+    assert_eq!(
+        extract_bytes(little, 4, Endianness::Little).unwrap(),
+        vec![1, 2, 0, 0]
+    );
+    assert!(extract_bytes(little, 1, Endianness::Little).is_err());
+}
+
+fn test_synthetic_circuit<E>()
+where
+    E: Pairing,
+{
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let blank = SyntheticCircuit::<E::ScalarField>::blank(20)
+        .with_variables(30)
+        .with_inputs(3)
+        .with_density(3)
+        .with_depth(4)
+        .with_seed(7);
+    let circuit = blank.with_assignment(11);
+
+    let cs = ConstraintSystem::new_ref();
+    circuit.generate_constraints(cs.clone()).unwrap();
+    assert!(cs.is_satisfied().unwrap());
+    assert_eq!(cs.num_constraints(), 20);
+    assert_eq!(cs.num_instance_variables(), 4);
+    assert_eq!(cs.num_witness_variables(), 30);
+    let matrices = cs.to_matrices().unwrap();
+    assert!(matrices.a.iter().all(|row| row.len() <= 3));
+    assert!(matrices.b.iter().all(|row| row.len() <= 3));
+    // Chains of four constraints: each constraint but the first of a chain
+    // uses the output of the previous one.
+    let output = |i: usize| 4 + 10 + i;
+    assert!(matrices.a[1].iter().any(|(_, v)| *v == output(0)));
+    assert!(!matrices.a[4].iter().any(|(_, v)| *v == output(3)));
+
+    // The matrices only depend on the seed.
+    let other = ConstraintSystem::new_ref();
+    blank
+        .with_assignment(12)
+        .generate_constraints(other.clone())
+        .unwrap();
+    assert!(other.is_satisfied().unwrap());
+    other.finalize();
+    cs.finalize();
+    assert_eq!(other.to_matrices().unwrap().a, cs.to_matrices().unwrap().a);
+    let reseeded = ConstraintSystem::new_ref();
+    blank
+        .with_seed(8)
+        .with_assignment(11)
+        .generate_constraints(reseeded.clone())
+        .unwrap();
+    reseeded.finalize();
+    assert_ne!(
+        reseeded.to_matrices().unwrap().a,
+        cs.to_matrices().unwrap().a
+    );
+
+    let inputs = circuit.public_inputs().unwrap();
+    assert_eq!(inputs.len(), 3);
+    assert!(blank.public_inputs().is_none());
+    let (pk, vk) = Groth16::<E>::setup(blank, &mut rng).unwrap();
+    let proof = Groth16::<E>::prove(&pk, circuit, &mut rng).unwrap();
+    assert!(Groth16::<E>::verify(&vk, &inputs, &proof).unwrap());
+    assert!(!Groth16::<E>::verify(&vk, &inputs[..2], &proof).unwrap_or(false));
+}
+
+fn test_prove_with_matrices<E>()
+where
+    E: Pairing,
+{
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let cs = ConstraintSystem::new_ref();
+    MySillyCircuit {
+        a: Some(a),
+        b: Some(b),
+    }
+    .generate_constraints(cs.clone())
+    .unwrap();
+    cs.finalize();
+    let matrices = cs.to_matrices().unwrap();
+    let full_assignment = [E::ScalarField::one(), a * b, a, b];
+
+    let pk = Groth16::<E>::generate_parameters_with_matrices(&matrices, 1, &mut rng).unwrap();
+    let pvk = prepare_verifying_key::<E>(&pk.vk);
+
+    let proof =
+        Groth16::<E>::prove_with_matrices(&pk, &matrices, &full_assignment, &mut rng).unwrap();
+    assert!(Groth16::<E>::verify_with_processed_vk(&pvk, &[a * b], &proof).unwrap());
+    assert!(!Groth16::<E>::verify_with_processed_vk(&pvk, &[a], &proof).unwrap());
+
+    // A key for the synthesized circuit works with the matrices too.
+    let (circuit_pk, circuit_vk) =
+        Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let proof =
+        Groth16::<E>::prove_with_matrices(&circuit_pk, &matrices, &full_assignment, &mut rng)
+            .unwrap();
+    assert!(Groth16::<E>::verify(&circuit_vk, &[a * b], &proof).unwrap());
+
+    assert!(matches!(
+        Groth16::<E>::prove_with_matrices(&pk, &matrices, &full_assignment[..3], &mut rng),
+        Err(SynthesisError::AssignmentMissing)
+    ));
+    assert!(matches!(
+        Groth16::<E>::generate_parameters_with_matrices(&matrices, 3, &mut rng),
+        Err(SynthesisError::MalformedVerifyingKey)
+    ));
+
+    // A reduction implementing only the methods required before the
+    // matrices-based setup still works with it.
+    use ark_poly::{EvaluationDomain, GeneralEvaluationDomain};
+    struct CircuitOnlyReduction;
+    impl R1CSToQAP for CircuitOnlyReduction {
+        #[allow(clippy::type_complexity)]
+        fn instance_map_with_evaluation<F: PrimeField, D: EvaluationDomain<F>>(
+            cs: ConstraintSystemRef<F>,
+            t: &F,
+        ) -> Result<(Vec<F>, Vec<F>, Vec<F>, F, usize, usize), SynthesisError> {
+            LibsnarkReduction::instance_map_with_evaluation::<F, D>(cs, t)
+        }
+
+        fn witness_map_from_matrices<F: PrimeField, D: EvaluationDomain<F>>(
+            matrices: &ConstraintMatrices<F>,
+            num_inputs: usize,
+            num_constraints: usize,
+            full_assignment: &[F],
+        ) -> Result<Vec<F>, SynthesisError> {
+            LibsnarkReduction::witness_map_from_matrices::<F, D>(
+                matrices,
+                num_inputs,
+                num_constraints,
+                full_assignment,
+            )
+        }
+
+        fn h_query_scalars<F: PrimeField, D: EvaluationDomain<F>>(
+            max_power: usize,
+            t: F,
+            zt: F,
+            delta_inverse: F,
+        ) -> Result<Vec<F>, SynthesisError> {
+            LibsnarkReduction::h_query_scalars::<F, D>(max_power, t, zt, delta_inverse)
+        }
+    }
+    type Domain<F> = GeneralEvaluationDomain<F>;
+    let t = E::ScalarField::rand(&mut rng);
+    assert_eq!(
+        CircuitOnlyReduction::instance_map_with_evaluation_from_matrices::<_, Domain<_>>(
+            &matrices, &t
+        )
+        .unwrap(),
+        LibsnarkReduction::instance_map_with_evaluation_from_matrices::<_, Domain<_>>(
+            &matrices, &t
+        )
+        .unwrap()
+    );
+    let pk = Groth16::<E, CircuitOnlyReduction>::generate_parameters_with_matrices(
+        &matrices, 1, &mut rng,
+    )
+    .unwrap();
+    let proof = Groth16::<E, CircuitOnlyReduction>::prove_with_matrices(
+        &pk,
+        &matrices,
+        &full_assignment,
+        &mut rng,
+    )
+    .unwrap();
+    assert!(Groth16::<E>::verify(&pk.vk, &[a * b], &proof).unwrap());
+}
+
+fn test_diagnose_input_mismatch<E>()
+where
+    E: Pairing,
+{
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let pvk = prepare_verifying_key::<E>(&vk);
+
+    // A small input, whose byte-reversed encoding is still reduced.
+    let c = E::ScalarField::from(5u64);
+    let proof = Groth16::<E>::prove(
+        &pk,
+        MySillyCircuit {
+            a: Some(c),
+            b: Some(E::ScalarField::one()),
+        },
+        &mut rng,
+    )
+    .unwrap();
+
+    let diagnose =
+        |inputs: &[E::ScalarField]| diagnose_input_mismatch(&pvk, &proof, inputs).unwrap();
+    assert_eq!(diagnose(&[c]), Some(InputTransformation::Identity));
+    let flipped = InputTransformation::EndiannessFlipped.apply(&[c]);
+    assert_ne!(flipped, vec![c]);
+    assert_eq!(
+        diagnose(&flipped),
+        Some(InputTransformation::EndiannessFlipped)
+    );
+    assert_eq!(diagnose(&[-c]), Some(InputTransformation::Negated(0)));
+    assert_eq!(diagnose(&[c + E::ScalarField::one()]), None);
+
+    let blank = InputsCircuit {
+        inputs: vec![None; 4],
+    };
+    let (pk, vk) = Groth16::<E>::setup(blank, &mut rng).unwrap();
+    let pvk = prepare_verifying_key::<E>(&vk);
+    let inputs = (0..4)
+        .map(|_| E::ScalarField::rand(&mut rng))
+        .collect::<Vec<_>>();
+    let circuit = InputsCircuit {
+        inputs: inputs.iter().copied().map(Some).collect(),
+    };
+    let proof = Groth16::<E>::prove(&pk, circuit, &mut rng).unwrap();
+
+    let diagnose =
+        |inputs: &[E::ScalarField]| diagnose_input_mismatch(&pvk, &proof, inputs).unwrap();
+    let mut reversed = inputs.clone();
+    reversed.reverse();
+    assert_eq!(diagnose(&reversed), Some(InputTransformation::Reversed));
+    let swapped = InputTransformation::Swapped(1, 3).apply(&inputs);
+    assert_eq!(diagnose(&swapped), Some(InputTransformation::Swapped(1, 3)));
+    let negated = InputTransformation::Negated(2).apply(&inputs);
+    assert_eq!(diagnose(&negated), Some(InputTransformation::Negated(2)));
+    assert!(diagnose_input_mismatch(&pvk, &proof, &inputs[..3]).is_err());
+}
+
+fn test_analyze_failure<E>()
+where
+    E: Pairing,
+{
+    use crate::diagnose::{analyze_failure, FailureDiagnosis, ProofElement};
+
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, vk) = Groth16::<E>::setup(
+        InputsCircuit {
+            inputs: vec![None; 3],
+        },
+        &mut rng,
+    )
+    .unwrap();
+    let pvk = prepare_verifying_key::<E>(&vk);
+    let inputs = (0..3)
+        .map(|_| E::ScalarField::rand(&mut rng))
+        .collect::<Vec<_>>();
+    let circuit = InputsCircuit {
+        inputs: inputs.iter().copied().map(Some).collect(),
+    };
+    let proof = Groth16::<E>::prove(&pk, circuit, &mut rng).unwrap();
+    let analyze =
+        |proof: &Proof<E>, inputs: &[E::ScalarField]| analyze_failure(&pvk, proof, inputs).unwrap();
+
+    assert_eq!(analyze(&proof, &inputs), FailureDiagnosis::Valid);
+    assert!(matches!(
+        analyze(&proof, &inputs[..2]),
+        FailureDiagnosis::InputCount(mismatch) if mismatch.actual_variable == 2
+    ));
+
+    // A point of `G2` outside of the prime-order subgroup.
+    let b = (0u8..)
+        .filter_map(|i| E::G2Affine::from_random_bytes(&[i; 256]))
+        .find(|b: &E::G2Affine| b.check().is_err())
+        .unwrap();
+    let invalid = Proof { b, ..proof.clone() };
+    assert_eq!(
+        analyze(&invalid, &inputs),
+        FailureDiagnosis::InvalidPoint(ProofElement::B)
+    );
+    let identity = Proof {
+        c: E::G1Affine::zero(),
+        ..proof.clone()
+    };
+    assert_eq!(
+        analyze(&identity, &inputs),
+        FailureDiagnosis::Identity(ProofElement::C)
+    );
+
+    let neg = |p: E::G1Affine| (-p.into_group()).into_affine();
+    let negated = Proof {
+        a: neg(proof.a),
+        ..proof.clone()
+    };
+    assert_eq!(
+        analyze(&negated, &inputs),
+        FailureDiagnosis::Negated(ProofElement::A)
+    );
+    let negated = Proof {
+        c: neg(proof.c),
+        ..proof.clone()
+    };
+    assert_eq!(
+        analyze(&negated, &inputs),
+        FailureDiagnosis::Negated(ProofElement::C)
+    );
+
+    let mut reversed = inputs.clone();
+    reversed.reverse();
+    assert_eq!(
+        analyze(&proof, &reversed),
+        FailureDiagnosis::InputEncoding(InputTransformation::Reversed)
+    );
+    let other = (0..3)
+        .map(|_| E::ScalarField::rand(&mut rng))
+        .collect::<Vec<_>>();
+    assert_eq!(analyze(&proof, &other), FailureDiagnosis::PairingMismatch);
+}
+
+fn test_prove_with_fork_detecting_rng<E>()
+where
+    E: Pairing,
+{
+    static GENERATION: AtomicU64 = AtomicU64::new(0);
+    fn generation() -> u64 {
+        GENERATION.load(AtomicOrdering::SeqCst)
+    }
+
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let circuit = || MySillyCircuit {
+        a: Some(a),
+        b: Some(b),
+    };
+
+    let proof = Groth16::<E>::prove_with_rng(&pk, circuit(), &mut rng).unwrap();
+    assert!(Groth16::<E>::verify(&vk, &[a * b], &proof).unwrap());
+
+    let mut fork_rng =
+        ForkDetectingRng::new(ark_std::rand::rngs::StdRng::seed_from_u64(rng.next_u64()))
+            .with_generation_source(generation);
+    let proof = Groth16::<E>::prove_with_rng(&pk, circuit(), &mut fork_rng).unwrap();
+    assert!(Groth16::<E>::verify(&vk, &[a * b], &proof).unwrap());
+
+    // Simulate restoring a cloned VM snapshot.
+    GENERATION.fetch_add(1, AtomicOrdering::SeqCst);
+    assert!(matches!(
+        Groth16::<E>::prove_with_rng(&pk, circuit(), &mut fork_rng),
+        Err(ProveError::Rng(ProverRngError::ForkDetected))
+    ));
+    // The wrapped source cannot reseed itself, so the fork is not cleared.
+    assert_eq!(
+        ProverRng::reseed(&mut fork_rng),
+        Err(ProverRngError::ReseedUnsupported)
+    );
+    assert!(fork_rng.fork_detected());
+
+    let mut seed = [0u8; 32];
+    rng.fill_bytes(&mut seed);
+    fork_rng.reseed_from_seed(seed);
+    assert!(!fork_rng.fork_detected());
+    let proof = Groth16::<E>::prove_with_rng(&pk, circuit(), &mut fork_rng).unwrap();
+    assert!(Groth16::<E>::verify(&vk, &[a * b], &proof).unwrap());
+}
+
+fn test_prove_with_blinding<E>()
+where
+    E: Pairing,
+{
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let circuit = || MySillyCircuit {
+        a: Some(a),
+        b: Some(b),
+    };
+
+    let r = E::ScalarField::rand(&mut rng);
+    let s = E::ScalarField::rand(&mut rng);
+    let mut requests = 0;
+    let mut hsm = || {
+        requests += 1;
+        Ok(BlindingFactors::new(r, s))
+    };
+    let proof = Groth16::<E>::prove_with_blinding(&pk, circuit(), &mut hsm).unwrap();
+    assert_eq!(requests, 1);
+    assert!(Groth16::<E>::verify(&vk, &[a * b], &proof).unwrap());
+    assert_eq!(
+        proof,
+        Groth16::<E>::create_proof_with_reduction(circuit(), &pk, r, s).unwrap()
+    );
+
+    // Blinding factors are not requested for circuits which fail to
+    // synthesize.
+    let mut unavailable =
+        || -> Result<BlindingFactors<E::ScalarField>, _> { panic!("requested blinding factors") };
+    assert!(matches!(
+        Groth16::<E>::prove_with_blinding(
+            &pk,
+            MySillyCircuit {
+                a: Some(a),
+                b: None
+            },
+            &mut unavailable
+        ),
+        Err(ProveError::Synthesis(SynthesisError::AssignmentMissing))
+    ));
+    let mut unavailable = || Err(ProverRngError::Unavailable);
+    assert!(matches!(
+        Groth16::<E>::prove_with_blinding(&pk, circuit(), &mut unavailable),
+        Err(ProveError::Rng(ProverRngError::Unavailable))
+    ));
+
+    let factors = async { BlindingFactors::sample(&mut rng) };
+    let proof = std::pin::pin!(Groth16::<E>::prove_with_blinding_async(
+        &pk,
+        circuit(),
+        factors
+    ))
+    .poll(&mut std::task::Context::from_waker(std::task::Waker::noop()));
+    match proof {
+        std::task::Poll::Ready(proof) => {
+            assert!(Groth16::<E>::verify(&vk, &[a * b], &proof.unwrap()).unwrap())
+        },
+        std::task::Poll::Pending => panic!("proof is pending"),
+    }
+}
+
+fn test_proof_serialize_with<E>()
+where
+    E: Pairing,
+{
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, _) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let a = E::ScalarField::rand(&mut rng);
+    let circuit = MySillyCircuit {
+        a: Some(a),
+        b: Some(a),
+    };
+    let proof = Groth16::<E>::prove(&pk, circuit, &mut rng).unwrap();
+
+    for compress in [Compress::Yes, Compress::No] {
+        let bytes = proof.to_bytes_with(compress);
+        assert_eq!(bytes.len(), Proof::<E>::serialized_size_with(compress));
+        assert_eq!(bytes.len(), proof.serialized_size(compress));
+        let mut writer = Vec::new();
+        proof.serialize_with(&mut writer, compress).unwrap();
+        assert_eq!(writer, bytes);
+
+        for validate in [Validate::Yes, Validate::No] {
+            assert_eq!(
+                Proof::<E>::deserialize_with(&bytes[..], compress, validate).unwrap(),
+                proof
+            );
+        }
+    }
+    assert_eq!(
+        Proof::<E>::serialized_size_with(Compress::No),
+        2 * Proof::<E>::serialized_size_with(Compress::Yes)
+    );
+    assert_eq!(
+        Proof::<E>::serialized_size_with(Compress::Yes),
+        Proof::<E>::default().to_bytes_with(Compress::Yes).len()
+    );
+    assert!(Proof::<E>::deserialize_with(
+        &proof.to_bytes_with(Compress::Yes)[..],
+        Compress::No,
+        Validate::Yes
+    )
+    .is_err());
+}
+
+fn test_verify_strict<E>()
+where
+    E: Pairing,
+{
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let pvk = prepare_verifying_key::<E>(&vk);
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let circuit = MySillyCircuit {
+        a: Some(a),
+        b: Some(b),
+    };
+    let proof = Groth16::<E>::prove(&pk, circuit, &mut rng).unwrap();
+
+    assert_eq!(
+        Groth16::<E>::verify_strict(&pvk, &proof, &[], &[a * b]),
+        Ok(true)
+    );
+    assert_eq!(
+        Groth16::<E>::verify_strict(&pvk, &proof, &[], &[a]),
+        Ok(false)
+    );
+    assert_eq!(
+        Groth16::<E>::verify_strict(&pvk, &proof, &[], &[]),
+        Err(StrictVerificationError::InputCount(InputCountMismatch {
+            expected_static: 0,
+            actual_static: 0,
+            expected_variable: 1,
+            actual_variable: 0,
+        }))
+    );
+
+    let mut degenerate = proof.clone();
+    degenerate.a = E::G1Affine::zero();
+    assert_eq!(
+        Groth16::<E>::verify_strict(&pvk, &degenerate, &[], &[a * b]),
+        Err(StrictVerificationError::IdentityA)
+    );
+    let mut degenerate = proof.clone();
+    degenerate.b = E::G2Affine::zero();
+    assert_eq!(
+        Groth16::<E>::verify_strict(&pvk, &degenerate, &[], &[a * b]),
+        Err(StrictVerificationError::IdentityB)
+    );
+    let mut degenerate = proof.clone();
+    degenerate.c = E::G1Affine::zero();
+    assert_eq!(
+        Groth16::<E>::verify_strict(&pvk, &degenerate, &[], &[a * b]),
+        Err(StrictVerificationError::IdentityC)
+    );
+
+    let mut zero_inputs_vk = vk.clone();
+    zero_inputs_vk.gamma_abc_g1_static[0] = E::G1Affine::zero();
+    let zero_inputs_pvk = prepare_verifying_key::<E>(&zero_inputs_vk);
+    assert_eq!(
+        Groth16::<E>::verify_strict(&zero_inputs_pvk, &proof, &[], &[E::ScalarField::zero()]),
+        Err(StrictVerificationError::IdentityPreparedInputs)
+    );
+
+    // A verifying key with `alpha = 0` accepts the identity proof for
+    // inputs aggregating to the identity.
+    let mut degenerate_vk = zero_inputs_vk;
+    degenerate_vk.alpha_g1 = E::G1Affine::zero();
+    let degenerate_pvk = prepare_verifying_key::<E>(&degenerate_vk);
+    assert!(Groth16::<E>::verify_with_variables(
+        &degenerate_pvk,
+        &Proof::default(),
+        &[],
+        &[E::ScalarField::zero()]
+    )
+    .unwrap());
+    assert_eq!(
+        Groth16::<E>::verify_strict(
+            &degenerate_pvk,
+            &Proof::default(),
+            &[],
+            &[E::ScalarField::zero()]
+        ),
+        Err(StrictVerificationError::DegenerateVerifyingKey)
+    );
+
+    // The precomputations of the prepared key are not trusted
+    let (_, other_vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let mismatched_pvk = crate::PreparedVerifyingKey {
+        vk: vk.clone(),
+        ..prepare_verifying_key::<E>(&other_vk)
+    };
+    assert!(!Groth16::<E>::verify_with_variables(&mismatched_pvk, &proof, &[], &[a * b]).unwrap());
+    assert_eq!(
+        Groth16::<E>::verify_strict(&mismatched_pvk, &proof, &[], &[a * b]),
+        Ok(true)
+    );
+}
+
+fn test_key_rotation<E>()
+where
+    E: Pairing,
+{
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (old_pk, old_vk) =
+        Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let (new_pk, new_vk) = Groth16::<E>::setup(
+        InputsCircuit {
+            inputs: vec![None; 2],
+        },
+        &mut rng,
+    )
+    .unwrap();
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let old_proof = Groth16::<E>::prove(
+        &old_pk,
+        MySillyCircuit {
+            a: Some(a),
+            b: Some(b),
+        },
+        &mut rng,
+    )
+    .unwrap();
+    let new_proof = Groth16::<E>::prove(
+        &new_pk,
+        InputsCircuit {
+            inputs: vec![Some(a), Some(b)],
+        },
+        &mut rng,
+    )
+    .unwrap();
+
+    let mut rotation = KeyRotation::new(&old_vk, 7);
+    assert_eq!(
+        rotation.verify_any(&old_proof, &[a * b]).unwrap(),
+        (true, EpochUsed::Current)
+    );
+    assert!(rotation.verify_any(&new_proof, &[a, b]).is_err());
+
+    rotation.rotate(&new_vk);
+    assert_eq!(rotation.epoch(), 8);
+    assert_eq!(rotation.current().vk, new_vk);
+    assert_eq!(rotation.previous().unwrap().vk, old_vk);
+    assert_eq!(
+        rotation.verify_any(&new_proof, &[a, b]).unwrap(),
+        (true, EpochUsed::Current)
+    );
+    assert_eq!(
+        rotation.verify_any(&old_proof, &[a * b]).unwrap(),
+        (true, EpochUsed::Previous)
+    );
+    assert_eq!(
+        rotation.verify_any(&old_proof, &[a]).unwrap(),
+        (false, EpochUsed::Neither)
+    );
+    assert!(rotation.verify_any(&old_proof, &[a, b, a]).is_err());
+
+    let mut bytes = Vec::new();
+    rotation.serialize_compressed(&mut bytes).unwrap();
+    let deserialized = KeyRotation::<E>::deserialize_compressed(&bytes[..]).unwrap();
+    assert_eq!(deserialized.epoch(), 8);
+    assert_eq!(
+        deserialized.verify_any(&old_proof, &[a * b]).unwrap(),
+        (true, EpochUsed::Previous)
+    );
+
+    rotation.end_grace_period();
+    assert!(rotation.previous().is_none());
+    assert_eq!(
+        rotation.verify_any(&new_proof, &[a, b]).unwrap(),
+        (true, EpochUsed::Current)
+    );
+    assert!(rotation.verify_any(&old_proof, &[a * b]).is_err());
+}
+
+fn test_expected_inputs<E>()
+where
+    E: Pairing,
+{
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (_, vk) = Groth16::<E>::setup(
+        InputsCircuit {
+            inputs: vec![None; 3],
+        },
+        &mut rng,
+    )
+    .unwrap();
+    let pvk = prepare_verifying_key(&vk);
+    assert_eq!(pvk.num_static_inputs(), 0);
+    assert_eq!(pvk.num_variable_inputs(), 3);
+    assert_eq!(pvk.expected_total_inputs(), 3);
+
+    let vk = LegacyVerifyingKey::<E> {
+        alpha_g1: vk.alpha_g1,
+        beta_g2: vk.beta_g2,
+        gamma_g2: vk.gamma_g2,
+        delta_g2: vk.delta_g2,
+        gamma_abc_g1: [vk.gamma_abc_g1_static, vk.gamma_abc_g1_variable].concat(),
+    }
+    .into_split(2)
+    .unwrap();
+    let pvk = prepare_verifying_key(&vk);
+    assert_eq!(pvk.num_static_inputs(), 1);
+    assert_eq!(pvk.num_variable_inputs(), 2);
+    assert_eq!(pvk.expected_total_inputs(), 3);
+
+    // Mismatched inputs report the expected and actual count of each group
+    assert_eq!(pvk.check_input_counts(1, 2), Ok(()));
+    let mismatch = InputCountMismatch {
+        expected_static: 1,
+        actual_static: 2,
+        expected_variable: 2,
+        actual_variable: 1,
+    };
+    assert_eq!(pvk.check_input_counts(2, 1), Err(mismatch));
+    assert_eq!(
+        mismatch.to_string(),
+        "expected 1 static and 2 variable inputs, got 2 static and 1 variable inputs"
+    );
+    let inputs = [E::ScalarField::one(); 3];
+    assert_eq!(
+        Groth16::<E>::verify_strict(&pvk, &Proof::default(), &inputs[..2], &inputs[2..]),
+        Err(StrictVerificationError::InputCount(mismatch))
+    );
+    assert_eq!(
+        Groth16::<E>::verify_with_variables(&pvk, &Proof::default(), &inputs[..2], &inputs[2..]),
+        Err(VerificationError::InputCount(mismatch))
+    );
+    // The `SNARK` verifiers can only report a malformed key.
+    assert_eq!(
+        SynthesisError::from(VerificationError::InputCount(mismatch)),
+        SynthesisError::MalformedVerifyingKey
+    );
+
+    let pvk = crate::PreparedVerifyingKey::<E>::default();
+    assert_eq!(pvk.num_static_inputs(), 0);
+    assert_eq!(pvk.expected_total_inputs(), 0);
+}
+
+fn test_swap_circuit<E>()
+where
+    E: Pairing,
+{
+    use crate::reference::{self, swap_circuit, Pool, Trade};
+
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, pvk) = reference::setup::<E>(&mut rng).unwrap();
+    assert_eq!(pvk.num_static_inputs(), reference::NUM_STATIC_INPUTS);
+    assert_eq!(pvk.num_variable_inputs(), reference::NUM_VARIABLE_INPUTS);
+
+    let pool = Pool {
+        reserve_in: 1_000_000,
+        reserve_out: 2_000_000,
+        fee_bps: 30,
+    };
+    let trade = Trade {
+        amount_in: 10_000,
+        min_amount_out: 19_000,
+    };
+    let proof = reference::prove(&pk, pool, trade, &mut rng).unwrap();
+    assert!(reference::verify(&pvk, pool, trade, &proof).unwrap());
+    let inputs = [
+        pool.static_inputs::<E::ScalarField>(),
+        trade.variable_inputs(),
+    ]
+    .concat();
+    assert!(Groth16::<E>::verify_with_processed_vk(&pvk, &inputs, &proof).unwrap());
+
+    let other_trade = Trade {
+        min_amount_out: 19_001,
+        ..trade
+    };
+    assert!(!reference::verify(&pvk, pool, other_trade, &proof).unwrap());
+    let other_pool = Pool { fee_bps: 0, ..pool };
+    assert!(!reference::verify(&pvk, other_pool, trade, &proof).unwrap());
+
+    let greedy_trade = Trade {
+        min_amount_out: pool.amount_out(trade.amount_in).unwrap() + 1,
+        ..trade
+    };
+    assert!(matches!(
+        reference::prove(&pk, pool, greedy_trade, &mut rng),
+        Err(SynthesisError::Unsatisfiable)
+    ));
+
+    // Buying more than the quote decreases the invariant of the pool.
+    let quote = pool.amount_out(trade.amount_in).unwrap();
+    for (amount_out, satisfied) in [(quote, true), (quote + 1, false), (u64::MAX, false)] {
+        let cs = ConstraintSystem::<E::ScalarField>::new_ref();
+        swap_circuit(pool, trade, amount_out)
+            .generate_constraints(cs.clone())
+            .unwrap();
+        assert_eq!(cs.is_satisfied().unwrap(), satisfied);
+    }
+}
+
+/// Polls `future` once, for futures which never wait.
+fn poll_ready<F: Future>(future: F) -> F::Output {
+    match std::pin::pin!(future).poll(&mut std::task::Context::from_waker(std::task::Waker::noop()))
+    {
+        std::task::Poll::Ready(output) => output,
+        std::task::Poll::Pending => panic!("future is pending"),
+    }
+}
+
+fn test_proof_spool<E>()
+where
+    E: Pairing,
+{
+    use crate::spool::{ProofSpool, ProvingJob, Recovery, SpoolError};
+
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    // The tests of both curves draw the same name from the same seed.
+    static SPOOLS: AtomicU64 = AtomicU64::new(0);
+    let root = std::env::temp_dir().join(format!(
+        "darklake-spool-{:016x}-{}",
+        rng.next_u64(),
+        SPOOLS.fetch_add(1, AtomicOrdering::SeqCst)
+    ));
+    let (mut spool, recovery) = ProofSpool::<E>::open(&root).unwrap();
+    assert_eq!(recovery, Recovery::default());
+
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let jobs = (0..3u8)
+        .map(|i| ProvingJob {
+            key: vk.fingerprint(),
+            payload: vec![i; 8],
+        })
+        .collect::<Vec<_>>();
+    let ids = jobs
+        .iter()
+        .map(|job| spool.submit(job).unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(ids, vec![0, 1, 2]);
+    assert_eq!(spool.pending().unwrap(), ids);
+    assert_eq!(spool.job(1).unwrap(), jobs[1]);
+
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let circuit = MySillyCircuit {
+        a: Some(a),
+        b: Some(b),
+    };
+    let proof = Groth16::<E>::prove(&pk, circuit, &mut rng).unwrap();
+    spool.complete(0, &proof).unwrap();
+    assert_eq!(spool.pending().unwrap(), vec![1, 2]);
+    assert_eq!(spool.completed().unwrap(), vec![0]);
+    assert!(matches!(
+        spool.complete(0, &proof),
+        Err(SpoolError::UnknownJob(0))
+    ));
+
+    // Simulate a crash after the proof of job 1 was written but before the
+    // job was removed, with a partially written job.
+    let pending = root.join("pending");
+    let job_1 = std::fs::read(pending.join(format!("{:020}", 1))).unwrap();
+    spool.complete(1, &proof).unwrap();
+    std::fs::write(pending.join(format!("{:020}", 1)), job_1).unwrap();
+    std::fs::write(pending.join(format!("{:020}.tmp", 3)), [0u8; 7]).unwrap();
+    drop(spool);
+
+    let (mut spool, recovery) = ProofSpool::<E>::open(&root).unwrap();
+    assert_eq!(
+        recovery,
+        Recovery {
+            pending: 1,
+            completed: 2,
+            resolved: 1,
+            discarded: 1,
+        }
+    );
+    assert_eq!(spool.pending().unwrap(), vec![2]);
+    assert_eq!(spool.submit(&jobs[0]).unwrap(), 3);
+    assert_eq!(spool.take_proof(0).unwrap(), proof);
+    assert_eq!(spool.completed().unwrap(), vec![1]);
+    assert!(matches!(spool.proof(0), Err(SpoolError::UnknownJob(0))));
+
+    let job_path = pending.join(format!("{:020}", 2));
+    let mut object = std::fs::read(&job_path).unwrap();
+    *object.last_mut().unwrap() ^= 1;
+    std::fs::write(&job_path, object).unwrap();
+    assert!(matches!(spool.job(2), Err(SpoolError::Corrupted(2))));
+
+    // Identifiers are not reused once the spool is drained and reopened.
+    for id in [2, 3] {
+        spool.complete(id, &proof).unwrap();
+    }
+    for id in [1, 2, 3] {
+        spool.take_proof(id).unwrap();
+    }
+    drop(spool);
+    let (mut spool, recovery) = ProofSpool::<E>::open(&root).unwrap();
+    assert_eq!(recovery, Recovery::default());
+    assert_eq!(spool.submit(&jobs[0]).unwrap(), 4);
+    drop(spool);
+
+    // The largest identifier is never assigned, as none would follow it.
+    let last = pending.join(format!("{:020}", u64::MAX));
+    std::fs::write(&last, [0u8; 8]).unwrap();
+    assert!(matches!(
+        ProofSpool::<E>::open(&root),
+        Err(SpoolError::IdsExhausted)
+    ));
+    std::fs::rename(&last, pending.join(format!("{:020}", u64::MAX - 1))).unwrap();
+    let (mut spool, _) = ProofSpool::<E>::open(&root).unwrap();
+    assert!(matches!(
+        spool.submit(&jobs[0]),
+        Err(SpoolError::IdsExhausted)
+    ));
+    assert!(!last.exists());
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+fn test_local_key_store<E>()
+where
+    E: Pairing,
+{
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let root = std::env::temp_dir().join(format!("darklake-keystore-{:016x}", rng.next_u64()));
+    let store = LocalKeyStore::new(&root);
+
+    let (pk, vk) = Groth16::<E>::setup(
+        InputsCircuit {
+            inputs: vec![None; 2],
+        },
+        &mut rng,
+    )
+    .unwrap();
+    let (_, other_vk) = Groth16::<E>::setup(
+        InputsCircuit {
+            inputs: vec![None; 2],
+        },
+        &mut rng,
+    )
+    .unwrap();
+
+    let fingerprint = poll_ready(store.put_verifying_key(&vk)).unwrap();
+    assert_eq!(fingerprint, vk.fingerprint());
+    assert_eq!(poll_ready(store.put_proving_key(&pk)).unwrap(), fingerprint);
+    assert_eq!(
+        poll_ready(store.get_verifying_key::<E>(&fingerprint)).unwrap(),
+        vk
+    );
+    assert_eq!(
+        poll_ready(store.get_proving_key::<E>(&fingerprint)).unwrap(),
+        pk
+    );
+    assert!(matches!(
+        poll_ready(store.get_verifying_key::<E>(&other_vk.fingerprint())),
+        Err(KeyStoreError::NotFound)
+    ));
+
+    // Objects are named after the hexadecimal fingerprint of the key
+    let hex = fingerprint
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect::<String>();
+    let vk_path = root.join("vk").join(&hex);
+    let mut object = std::fs::read(&vk_path).unwrap();
+    *object.last_mut().unwrap() ^= 1;
+    std::fs::write(&vk_path, &object).unwrap();
+    assert!(matches!(
+        poll_ready(store.get_verifying_key::<E>(&fingerprint)),
+        Err(KeyStoreError::ChecksumMismatch)
+    ));
+
+    let other_fingerprint = poll_ready(store.put_verifying_key(&other_vk)).unwrap();
+    let other_hex = other_fingerprint
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect::<String>();
+    std::fs::copy(root.join("vk").join(other_hex), &vk_path).unwrap();
+    assert!(matches!(
+        poll_ready(store.get_verifying_key::<E>(&fingerprint)),
+        Err(KeyStoreError::FingerprintMismatch)
+    ));
+
+    // Concurrent writes of the same key do not share a temporary file
+    std::thread::scope(|scope| {
+        for _ in 0..8 {
+            scope.spawn(|| poll_ready(store.put_verifying_key(&vk)).unwrap());
+        }
+    });
+    assert_eq!(
+        poll_ready(store.get_verifying_key::<E>(&fingerprint)).unwrap(),
+        vk
+    );
+    assert_eq!(std::fs::read_dir(root.join("vk")).unwrap().count(), 2);
+
+    std::fs::remove_dir_all(root).unwrap();
+}
+
+#[cfg(feature = "any-curve")]
+fn test_any_groth16<E>(curve: crate::any_curve::Curve)
+where
+    E: Pairing,
+{
+    use crate::any_curve::{AnyGroth16, AnyGroth16Error};
+    use crate::reference::{self, swap_circuit, Pool, Trade};
+
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, _) = reference::setup::<E>(&mut rng).unwrap();
+    let mut pk_bytes = Vec::new();
+    pk.serialize_compressed(&mut pk_bytes).unwrap();
+    let mut vk_bytes = Vec::new();
+    pk.vk.serialize_uncompressed(&mut vk_bytes).unwrap();
+
+    let prover = AnyGroth16::from_proving_key_bytes(&pk_bytes).unwrap();
+    let verifier = AnyGroth16::from_verifying_key_bytes(&vk_bytes).unwrap();
+    assert_eq!(prover.curve(), curve);
+    assert_eq!(verifier.curve(), curve);
+    assert!(prover.can_prove() && !verifier.can_prove());
+    assert_eq!(verifier.num_static_inputs(), reference::NUM_STATIC_INPUTS);
+    assert_eq!(
+        verifier.num_variable_inputs(),
+        reference::NUM_VARIABLE_INPUTS
+    );
+
+    let pool = Pool {
+        reserve_in: 1_000_000,
+        reserve_out: 2_000_000,
+        fee_bps: 30,
+    };
+    let trade = Trade {
+        amount_in: 1_000,
+        min_amount_out: 1_900,
+    };
+    let circuit = swap_circuit(pool, trade, pool.amount_out(trade.amount_in).unwrap());
+    let proof = prover.prove_bytes(circuit, &mut rng).unwrap();
+    assert!(matches!(
+        verifier.prove_bytes(circuit, &mut rng),
+        Err(AnyGroth16Error::MissingProvingKey)
+    ));
+
+    let encode = |inputs: Vec<E::ScalarField>| PublicInputs::<E>(inputs).to_bytes(Endianness::Big);
+    let static_inputs = encode(pool.static_inputs());
+    assert_eq!(static_inputs.len(), 3 * verifier.input_size());
+    assert!(verifier
+        .verify_bytes(
+            &proof,
+            &static_inputs,
+            &encode(trade.variable_inputs()),
+            Endianness::Big
+        )
+        .unwrap());
+    let other_trade = Trade {
+        min_amount_out: 1,
+        ..trade
+    };
+    assert!(!verifier
+        .verify_bytes(
+            &proof,
+            &static_inputs,
+            &encode(other_trade.variable_inputs()),
+            Endianness::Big
+        )
+        .unwrap());
+    assert!(matches!(
+        verifier.verify_bytes(&proof[1..], &static_inputs, &[], Endianness::Big),
+        Err(AnyGroth16Error::Load(_))
+    ));
+    assert!(matches!(
+        AnyGroth16::from_verifying_key_bytes(&vk_bytes[1..]),
+        Err(KeyLoadError::UnknownLayout)
+    ));
+}
+
+fn test_zero_inputs<E>()
+where
+    E: Pairing,
+{
+    use crate::cache::CachedVerifier;
+
+    /// Proves knowledge of a product, without public inputs.
+    struct NoInputsCircuit<F: Field> {
+        a: Option<F>,
+        b: Option<F>,
+    }
+
+    impl<F: Field> ConstraintSynthesizer<F> for NoInputsCircuit<F> {
+        fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+            let a = cs.new_witness_variable(|| self.a.ok_or(SynthesisError::AssignmentMissing))?;
+            let b = cs.new_witness_variable(|| self.b.ok_or(SynthesisError::AssignmentMissing))?;
+            let c = cs.new_witness_variable(|| {
+                Ok(self.a.ok_or(SynthesisError::AssignmentMissing)?
+                    * self.b.ok_or(SynthesisError::AssignmentMissing)?)
+            })?;
+            cs.enforce_constraint(lc!() + a, lc!() + b, lc!() + c)
+        }
+    }
+
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+    let (pk, vk) = Groth16::<E>::setup(NoInputsCircuit { a: None, b: None }, &mut rng).unwrap();
+    assert_eq!(vk.gamma_abc_g1_static.len(), 1);
+    assert!(vk.gamma_abc_g1_variable.is_empty());
+    let pvk = prepare_verifying_key::<E>(&vk);
+    assert_eq!(pvk.expected_total_inputs(), 0);
+
+    let circuit = || NoInputsCircuit {
+        a: Some(E::ScalarField::rand(&mut test_rng())),
+        b: Some(E::ScalarField::from(7u64)),
+    };
+    let proof = Groth16::<E>::prove(&pk, circuit(), &mut rng).unwrap();
+    assert!(Groth16::<E>::verify(&vk, &[], &proof).unwrap());
+    assert!(Groth16::<E>::verify_with_variables(&pvk, &proof, &[], &[]).unwrap());
+    assert_eq!(
+        Groth16::<E>::verify_strict(&pvk, &proof, &[], &[]),
+        Ok(true)
+    );
+    assert!(
+        Groth16::<E>::verify_with_input_bytes(&pvk, &proof, &[], &[], Endianness::Big).unwrap()
+    );
+    assert!(Groth16::<E>::verify_with_padding(
+        &pvk,
+        &[E::ScalarField::one()],
+        &proof,
+        PaddingPolicy::Truncate
+    )
+    .unwrap());
+    assert!(CachedVerifier::new(pvk.clone(), 4)
+        .verify_with_variables(&proof, &[], &[])
+        .unwrap());
+    assert_eq!(
+        Groth16::<E>::prepare_inputs_with_variables(&pvk, &[], &[]).unwrap(),
+        vk.gamma_abc_g1_static[0].into_group()
+    );
+    assert!(
+        Groth16::<E>::verify_with_variables(&pvk, &proof, &[], &[E::ScalarField::one()]).is_err()
+    );
+
+    // Inputs which are all committed to leave no input in the clear
+    let (pk, ck) = Groth16::<E>::generate_random_parameters_with_commitment(
+        InputsCircuit {
+            inputs: vec![None; 2],
+        },
+        &mut rng,
+        1,
+        0,
+        2,
+    )
+    .unwrap();
+    let pvk = prepare_verifying_key::<E>(&pk.vk);
+    assert_eq!(pvk.expected_total_inputs(), 0);
+    let inputs = vec![
+        E::ScalarField::rand(&mut rng),
+        E::ScalarField::rand(&mut rng),
+    ];
+    let proof = Groth16::<E>::prove(
+        &pk,
+        InputsCircuit {
+            inputs: inputs.iter().copied().map(Some).collect(),
+        },
+        &mut rng,
+    )
+    .unwrap();
+    let commitment = Groth16::<E>::commit_to_inputs(&ck, &inputs).unwrap();
+    assert!(Groth16::<E>::verify_with_commitment(&pvk, &ck.vk, &proof, &[], &commitment).unwrap());
+    let other_commitment = Groth16::<E>::commit_to_inputs(&ck, &[inputs[1], inputs[0]]).unwrap();
+    assert!(
+        !Groth16::<E>::verify_with_commitment(&pvk, &ck.vk, &proof, &[], &other_commitment)
+            .unwrap()
+    );
+}
+
+/// Proves and verifies `InputsCircuit` with two static inputs through the
+/// partitioned inputs of a generic SNARK.
+fn prove_and_verify_partitioned<F, S>(rng: &mut (impl RngCore + CryptoRng)) -> Result<(), S::Error>
+where
+    F: PrimeField,
+    S: SNARKWithPartitionedInputs<F>,
+{
+    let inputs = (0..5).map(|_| F::rand(rng)).collect::<Vec<_>>();
+    let (pk, vk) = S::circuit_specific_setup_with_partition(
+        InputsCircuit {
+            inputs: vec![None; 5],
+        },
+        2,
+        rng,
+    )?;
+    let pvk = S::process_vk(&vk)?;
+    assert_eq!(S::num_static_inputs(&pvk), 2);
+    assert_eq!(S::num_variable_inputs(&pvk), 3);
+
+    let proof = S::prove(
+        &pk,
+        InputsCircuit {
+            inputs: inputs.iter().copied().map(Some).collect(),
+        },
+        rng,
+    )?;
+    let (static_inputs, variable_inputs) = inputs.split_at(2);
+    assert!(S::verify_with_partitioned_inputs(
+        &pvk,
+        static_inputs,
+        variable_inputs,
+        &proof
+    )?);
+    assert!(S::verify_partitioned(
+        &vk,
+        static_inputs,
+        variable_inputs,
+        &proof
+    )?);
+    assert!(S::verify(&vk, &inputs, &proof)?);
+    assert!(!S::verify_with_partitioned_inputs(
+        &pvk,
+        static_inputs,
+        &[variable_inputs[1], variable_inputs[0], variable_inputs[2]],
+        &proof
+    )?);
+    Ok(())
+}
+
+fn test_snark_with_partitioned_inputs<E>()
+where
+    E: Pairing,
+{
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+    prove_and_verify_partitioned::<E::ScalarField, Groth16<E>>(&mut rng).unwrap();
+
+    let inputs = [E::ScalarField::one(); 2];
+    let (_, vk) = Groth16::<E>::circuit_specific_setup_with_partition(
+        InputsCircuit {
+            inputs: vec![None; 2],
+        },
+        1,
+        &mut rng,
+    )
+    .unwrap();
+    let proof = Proof::<E>::default();
+    assert_eq!(
+        Groth16::<E>::verify_partitioned(&vk, &inputs, &[], &proof),
+        Err(SynthesisError::MalformedVerifyingKey)
+    );
+
+    // More static inputs than the circuit has are rejected by every setup.
+    assert!(matches!(
+        Groth16::<E>::circuit_specific_setup_with_partition(
+            InputsCircuit {
+                inputs: vec![None; 2],
+            },
+            3,
+            &mut rng,
+        ),
+        Err(SynthesisError::MalformedVerifyingKey)
+    ));
+    assert!(matches!(
+        Groth16::<E>::generate_random_parameters_with_reduction(
+            InputsCircuit::<E::ScalarField> {
+                inputs: vec![None; 2],
+            },
+            &mut rng,
+            4,
+            0,
+        ),
+        Err(SynthesisError::MalformedVerifyingKey)
+    ));
+}
+
+fn test_verify_with_processed_vk<E>()
+where
+    E: Pairing,
+{
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    // The inputs are split at the number of static inputs of the key, so
+    // keys with several static inputs verify the same as with the split
+    // given explicitly.
+    let inputs = (0..4)
+        .map(|_| E::ScalarField::rand(&mut rng))
+        .collect::<Vec<_>>();
+    for num_static_inputs in 0..=inputs.len() {
+        let (pk, vk) = Groth16::<E>::circuit_specific_setup_with_partition(
+            InputsCircuit {
+                inputs: vec![None; inputs.len()],
+            },
+            num_static_inputs,
+            &mut rng,
+        )
+        .unwrap();
+        let pvk = prepare_verifying_key(&vk);
+        let proof = Groth16::<E>::prove(
+            &pk,
+            InputsCircuit {
+                inputs: inputs.iter().copied().map(Some).collect(),
+            },
+            &mut rng,
+        )
+        .unwrap();
+
+        assert!(Groth16::<E>::verify_with_processed_vk(&pvk, &inputs, &proof).unwrap());
+        let (static_inputs, variable_inputs) = inputs.split_at(num_static_inputs);
+        assert!(
+            Groth16::<E>::verify_with_variables(&pvk, &proof, static_inputs, variable_inputs)
+                .unwrap()
+        );
+
+        let mut tampered = inputs.clone();
+        tampered[0] += E::ScalarField::one();
+        assert!(!Groth16::<E>::verify_with_processed_vk(&pvk, &tampered, &proof).unwrap());
+
+        // Missing inputs are rejected, whether static or variable.
+        assert_eq!(
+            Groth16::<E>::verify_with_processed_vk(&pvk, &inputs[..inputs.len() - 1], &proof),
+            Err(SynthesisError::MalformedVerifyingKey)
+        );
+        assert_eq!(
+            Groth16::<E>::verify_with_processed_vk(&pvk, &[], &proof),
+            Err(SynthesisError::MalformedVerifyingKey)
+        );
+    }
+}
+
+fn test_input_equality<E>()
+where
+    E: Pairing,
+{
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+    let setup = |num_inputs: usize, num_static_inputs: usize, rng: &mut StdRng| {
+        let (pk, vk) = Groth16::<E>::circuit_specific_setup_with_partition(
+            InputsCircuit {
+                inputs: vec![None; num_inputs],
+            },
+            num_static_inputs,
+            rng,
+        )
+        .unwrap();
+        (pk, prepare_verifying_key(&vk))
+    };
+    let prove = |pk: &ProvingKey<E>, inputs: &[E::ScalarField], rng: &mut StdRng| {
+        Groth16::<E>::prove(
+            pk,
+            InputsCircuit {
+                inputs: inputs.iter().copied().map(Some).collect(),
+            },
+            rng,
+        )
+        .unwrap()
+    };
+
+    // An order with the id as its second input, and its settlement with the
+    // id as its first input.
+    let (order_pk, order_pvk) = setup(3, 1, &mut rng);
+    let (settlement_pk, settlement_pvk) = setup(2, 0, &mut rng);
+    let id = E::ScalarField::rand(&mut rng);
+    let order_inputs = [E::ScalarField::from(7u64), id, E::ScalarField::from(9u64)];
+    let settlement_inputs = [id, E::ScalarField::from(11u64)];
+    let order_proof = prove(&order_pk, &order_inputs, &mut rng);
+    let settlement_proof = prove(&settlement_pk, &settlement_inputs, &mut rng);
+
+    let order = PartiallyHiddenInputs {
+        static_inputs: &order_inputs[..1],
+        variable_inputs: &order_inputs[1..],
+        position: 1,
+    };
+    let settlement = PartiallyHiddenInputs {
+        static_inputs: &[],
+        variable_inputs: &settlement_inputs,
+        position: 0,
+    };
+    let link = Groth16::<E>::prove_input_equality(
+        &order_pvk,
+        &order,
+        &settlement_pvk,
+        &settlement,
+        &mut rng,
+    )
+    .unwrap();
+
+    // The verifier does not know the id.
+    let hidden_order_inputs = [order_inputs[0], E::ScalarField::zero(), order_inputs[2]];
+    let hidden_settlement_inputs = [E::ScalarField::zero(), settlement_inputs[1]];
+    let hidden_order = PartiallyHiddenInputs {
+        static_inputs: &hidden_order_inputs[..1],
+        variable_inputs: &hidden_order_inputs[1..],
+        position: 1,
+    };
+    let hidden_settlement = PartiallyHiddenInputs {
+        variable_inputs: &hidden_settlement_inputs,
+        ..settlement
+    };
+    let verify = |order: &PartiallyHiddenInputs<'_, E::ScalarField>,
+                  settlement: &PartiallyHiddenInputs<'_, E::ScalarField>,
+                  settlement_proof: &Proof<E>,
+                  link: &InputEqualityProof<E>| {
+        Groth16::<E>::verify_input_equality(
+            &order_pvk,
+            &order_proof,
+            order,
+            &settlement_pvk,
+            settlement_proof,
+            settlement,
+            link,
+        )
+    };
+    assert_eq!(
+        verify(&hidden_order, &hidden_settlement, &settlement_proof, &link),
+        Ok(true)
+    );
+
+    // Other public inputs must match.
+    let wrong_inputs = [E::ScalarField::zero(), E::ScalarField::from(12u64)];
+    let wrong_settlement = PartiallyHiddenInputs {
+        variable_inputs: &wrong_inputs,
+        ..settlement
+    };
+    assert_eq!(
+        verify(&hidden_order, &wrong_settlement, &settlement_proof, &link),
+        Ok(false)
+    );
+
+    // A settlement of another order cannot be linked.
+    let other_inputs = [E::ScalarField::rand(&mut rng), settlement_inputs[1]];
+    let other_proof = prove(&settlement_pk, &other_inputs, &mut rng);
+    let other = PartiallyHiddenInputs {
+        variable_inputs: &other_inputs,
+        ..settlement
+    };
+    assert_eq!(
+        Groth16::<E>::prove_input_equality(&order_pvk, &order, &settlement_pvk, &other, &mut rng),
+        Err(SynthesisError::Unsatisfiable)
+    );
+    let mut forged = link;
+    forged.prepared_inputs_b =
+        Groth16::<E>::prepare_inputs_with_variables(&settlement_pvk, &[], &other_inputs)
+            .unwrap()
+            .into_affine();
+    assert_eq!(
+        verify(&hidden_order, &hidden_settlement, &other_proof, &forged),
+        Ok(false)
+    );
+    assert_eq!(
+        verify(&hidden_order, &hidden_settlement, &other_proof, &link),
+        Ok(false)
+    );
+
+    let out_of_range = PartiallyHiddenInputs {
+        position: 2,
+        ..hidden_settlement
+    };
+    assert_eq!(
+        verify(&hidden_order, &out_of_range, &settlement_proof, &link),
+        Err(SynthesisError::MalformedVerifyingKey)
+    );
+}
+
+fn test_verify_same_inputs_batch<E>()
+where
+    E: Pairing,
+{
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let pvk = prepare_verifying_key::<E>(&vk);
+
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let c = a * b;
+    let proofs = (0..4)
+        .map(|_| {
+            Groth16::<E>::prove(
+                &pk,
+                MySillyCircuit {
+                    a: Some(a),
+                    b: Some(b),
+                },
+                &mut rng,
+            )
+            .unwrap()
+        })
+        .collect::<Vec<_>>();
+    let prepare = |inputs: &[E::ScalarField]| {
+        let (static_inputs, variable_inputs) = inputs.split_at(pvk.num_static_inputs());
+        Groth16::<E>::prepare_inputs_with_variables(&pvk, static_inputs, variable_inputs).unwrap()
+    };
+    let prepared_inputs = prepare(&[c]);
+
+    assert!(
+        Groth16::<E>::verify_same_inputs_batch(&pvk, &prepared_inputs, &proofs, &mut rng).unwrap()
+    );
+    assert!(
+        Groth16::<E>::verify_same_inputs_batch(&pvk, &prepared_inputs, &proofs[..1], &mut rng)
+            .unwrap()
+    );
+    assert!(Groth16::<E>::verify_same_inputs_batch(&pvk, &prepared_inputs, &[], &mut rng).unwrap());
+
+    // A proof of another statement invalidates the batch.
+    let other = Groth16::<E>::prove(
+        &pk,
+        MySillyCircuit {
+            a: Some(a),
+            b: Some(a),
+        },
+        &mut rng,
+    )
+    .unwrap();
+    let mut mixed = proofs.clone();
+    mixed[2] = other;
+    assert!(
+        !Groth16::<E>::verify_same_inputs_batch(&pvk, &prepared_inputs, &mixed, &mut rng).unwrap()
+    );
+
+    // So does a tampered proof.
+    let mut tampered = proofs.clone();
+    tampered[3].c = (tampered[3].c + E::G1Affine::generator()).into_affine();
+    assert!(
+        !Groth16::<E>::verify_same_inputs_batch(&pvk, &prepared_inputs, &tampered, &mut rng)
+            .unwrap()
+    );
+
+    let wrong_inputs = prepare(&[a]);
+    assert!(
+        !Groth16::<E>::verify_same_inputs_batch(&pvk, &wrong_inputs, &proofs, &mut rng).unwrap()
+    );
+
+    // Coefficients derived from a sponge give the same results, and the
+    // same coefficients for the same batch.
+    let (ark, mds) = find_poseidon_ark_and_mds::<E::ScalarField>(
+        E::ScalarField::MODULUS_BIT_SIZE as u64,
+        2,
+        8,
+        31,
+        0,
+    );
+    let config = PoseidonConfig::new(8, 31, 17, mds, ark, 2, 1);
+    let verify_with_sponge = |prepared_inputs: &E::G1, proofs: &[Proof<E>]| {
+        let mut sponge = PoseidonSponge::new(&config);
+        let valid = Groth16::<E>::verify_same_inputs_batch_with_sponge(
+            &pvk,
+            prepared_inputs,
+            proofs,
+            &mut sponge,
+        )
+        .unwrap();
+        (valid, sponge.squeeze_field_elements::<E::ScalarField>(1)[0])
+    };
+    let (valid, state) = verify_with_sponge(&prepared_inputs, &proofs);
+    assert!(valid);
+    assert_eq!(verify_with_sponge(&prepared_inputs, &proofs).1, state);
+    assert!(verify_with_sponge(&prepared_inputs, &[]).0);
+    assert!(!verify_with_sponge(&prepared_inputs, &mixed).0);
+    assert!(!verify_with_sponge(&prepared_inputs, &tampered).0);
+    assert!(!verify_with_sponge(&wrong_inputs, &proofs).0);
+}
+
+fn test_threshold_prover<E>()
+where
+    E: Pairing,
+{
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let circuit = || MySillyCircuit {
+        a: Some(a),
+        b: Some(b),
+    };
+    let (r_device, s_device) = (
+        E::ScalarField::rand(&mut rng),
+        E::ScalarField::rand(&mut rng),
+    );
+    let (r_server, s_server) = (
+        E::ScalarField::rand(&mut rng),
+        E::ScalarField::rand(&mut rng),
+    );
+    let device = BlindingFactors::new(r_device, s_device);
+    let server = BlindingFactors::new(r_server, s_server);
+
+    let mut prover = Groth16::<E>::threshold_prover(&pk, circuit()).unwrap();
+    for share in [&device, &server] {
+        prover
+            .add_commitment(&Groth16::<E>::commit_blinding_share(&pk, share))
+            .unwrap();
+    }
+    let challenge = prover.challenge();
+    let device_partial = Groth16::<E>::prove_blinding_share(&pk, &device, &challenge);
+    let server_partial = Groth16::<E>::prove_blinding_share(&pk, &server, &challenge);
+
+    let mut incomplete = prover.clone();
+    incomplete.add_partial_proof(&device_partial);
+    assert_eq!(incomplete.finish(), Err(SynthesisError::AssignmentMissing));
+    let mut late = prover.clone();
+    late.add_partial_proof(&device_partial);
+    assert_eq!(
+        late.add_commitment(&Groth16::<E>::commit_blinding_share(&pk, &device)),
+        Err(SynthesisError::Unsatisfiable)
+    );
+
+    // A share other than the committed one invalidates the proof.
+    let mut deviating = prover.clone();
+    deviating.add_partial_proof(&device_partial);
+    deviating.add_partial_proof(&Groth16::<E>::prove_blinding_share(
+        &pk,
+        &BlindingFactors::new(r_server, s_device),
+        &challenge,
+    ));
+    let proof = deviating.finish().unwrap();
+    assert!(!Groth16::<E>::verify(&vk, &[a * b], &proof).unwrap());
+
+    prover.add_partial_proof(&device_partial);
+    prover.add_partial_proof(&server_partial);
+    let proof = prover.finish().unwrap();
+    assert!(Groth16::<E>::verify(&vk, &[a * b], &proof).unwrap());
+    assert_eq!(
+        proof,
+        Groth16::<E>::create_proof_with_reduction(
+            circuit(),
+            &pk,
+            r_device + r_server,
+            s_device + s_server
+        )
+        .unwrap()
+    );
+
+    let unblinded = Groth16::<E>::threshold_prover(&pk, circuit()).unwrap();
+    assert_eq!(unblinded.finish(), Err(SynthesisError::AssignmentMissing));
+}
+
+fn test_delegated_proof<E>()
+where
+    E: Pairing,
+{
+    use crate::delegate::DelegatedMsms;
+
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let (other_pk, _) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let pvk = prepare_verifying_key::<E>(&vk);
+
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let circuit = MySillyCircuit {
+        a: Some(a),
+        b: Some(b),
+    };
+
+    // The witness holder masks the job, which hides the assignment from the
+    // compute provider.
+    let mask = Groth16::<E>::delegation_mask(&pk, &CpuMsm, &mut rng).unwrap();
+    let (job, prover) = Groth16::<E>::delegate_proof(&pk, circuit.clone(), mask, &mut rng).unwrap();
+    assert_eq!(job.assignment.len() + 1, pk.a_query.len());
+    assert!(!job.assignment.contains(&a) && !job.assignment.contains(&(a * b)));
+
+    // Jobs and MSMs travel serialized between the two parties.
+    let mut bytes = Vec::new();
+    job.serialize_compressed(&mut bytes).unwrap();
+    let job = CanonicalDeserialize::deserialize_compressed(&bytes[..]).unwrap();
+    let msms = Groth16::<E>::compute_delegated_msms(&pk, &job, &CpuMsm).unwrap();
+    assert_eq!(
+        Groth16::<E>::compute_delegated_msms(&other_pk, &job, &CpuMsm),
+        Err(SynthesisError::MalformedVerifyingKey)
+    );
+    let proof = prover.finish(&pk, &msms).unwrap();
+    assert!(Groth16::<E>::verify_with_processed_vk(&pvk, &[a * b], &proof).unwrap());
+
+    // A dishonest provider makes the proof invalid.
+    let mask = Groth16::<E>::delegation_mask(&pk, &CpuMsm, &mut rng).unwrap();
+    let (job, prover) = Groth16::<E>::delegate_proof(&pk, circuit.clone(), mask, &mut rng).unwrap();
+    let msms = Groth16::<E>::compute_delegated_msms(&pk, &job, &CpuMsm).unwrap();
+    let tampered = DelegatedMsms {
+        h: E::G1Affine::generator(),
+        ..msms
+    };
+    let proof = prover.finish(&pk, &tampered).unwrap();
+    assert!(!Groth16::<E>::verify_with_processed_vk(&pvk, &[a * b], &proof).unwrap());
+
+    // A proving key with more witness bases than variables is rejected
+    // rather than read out of bounds.
+    let mut malformed = pk.clone();
+    malformed.l_query = vec![E::G1Affine::generator(); pk.a_query.len()];
+    assert!(Groth16::<E>::delegation_mask(&malformed, &CpuMsm, &mut rng).is_err());
+    assert_eq!(
+        Groth16::<E>::compute_delegated_msms(&malformed, &job, &CpuMsm),
+        Err(SynthesisError::MalformedVerifyingKey)
+    );
+
+    let mask = Groth16::<E>::delegation_mask(&other_pk, &CpuMsm, &mut rng).unwrap();
+    assert!(Groth16::<E>::delegate_proof(&pk, circuit.clone(), mask, &mut rng).is_err());
+}
+
+fn test_checkpointed_proof<E>()
+where
+    E: Pairing,
+{
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let (a, b) = (
+        E::ScalarField::rand(&mut rng),
+        E::ScalarField::rand(&mut rng),
+    );
+
+    let mut checkpoint = Groth16::<E>::checkpoint_proof(
+        &pk,
+        MySillyCircuit {
+            a: Some(a),
+            b: Some(b),
+        },
+        &mut rng,
+    )
+    .unwrap();
+    assert_eq!(checkpoint.stage(), ProverStage::Reduced);
+
+    let mut saved = Vec::new();
+    checkpoint.serialize_compressed(&mut saved).unwrap();
+    let mut checkpoints = vec![saved];
+    let mut stages = Vec::new();
+    let proof = Groth16::<E>::resume_proof(&pk, &mut checkpoint, &CpuMsm, |checkpoint| {
+        stages.push(checkpoint.stage());
+        let mut saved = Vec::new();
+        checkpoint.serialize_compressed(&mut saved).unwrap();
+        checkpoints.push(saved);
+    })
+    .unwrap();
+    assert_eq!(
+        stages,
+        [
+            ProverStage::HMsm,
+            ProverStage::LMsm,
+            ProverStage::AMsm,
+            ProverStage::BG1Msm,
+            ProverStage::BG2Msm
+        ]
+    );
+    assert!(Groth16::<E>::verify(&vk, &[a * b], &proof).unwrap());
+
+    // Resuming from any checkpoint gives the same proof.
+    for saved in &checkpoints {
+        let mut checkpoint = ProverCheckpoint::<E>::deserialize_compressed(&saved[..]).unwrap();
+        let resumed = Groth16::<E>::resume_proof(&pk, &mut checkpoint, &CpuMsm, |_| {}).unwrap();
+        assert_eq!(resumed, proof);
+    }
+
+    let (other_pk, _) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let mut checkpoint =
+        ProverCheckpoint::<E>::deserialize_compressed(&checkpoints[0][..]).unwrap();
+    assert_eq!(
+        Groth16::<E>::resume_proof(&other_pk, &mut checkpoint, &CpuMsm, |_| {}),
+        Err(SynthesisError::MalformedVerifyingKey)
+    );
+}
+
+/// A verifier generated by [`generate_verifier`] for the key of
+/// `test_generated_verifier`.
+#[allow(unused_attributes)]
+#[rustfmt::skip]
+#[path = "test_data/verifier_bn254.rs"]
+mod generated_verifier;
+
+fn test_generated_verifier() {
+    use ark_bn254::Bn254;
+
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(0);
+    let (pk, vk) = Groth16::<Bn254>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let source = generate_verifier(&vk, "ark_bn254::Bn254").unwrap();
+    assert_eq!(source, include_str!("test_data/verifier_bn254.rs"));
+
+    let (a, b) = (ark_bn254::Fr::rand(&mut rng), ark_bn254::Fr::rand(&mut rng));
+    let proof = Groth16::<Bn254>::prove(
+        &pk,
+        MySillyCircuit {
+            a: Some(a),
+            b: Some(b),
+        },
+        &mut rng,
+    )
+    .unwrap();
+    let mut proof_bytes = Vec::new();
+    proof.serialize_compressed(&mut proof_bytes).unwrap();
+
+    assert!(generated_verifier::verify(&proof_bytes, &[a * b]));
+    assert!(!generated_verifier::verify(&proof_bytes, &[a]));
+    assert!(!generated_verifier::verify(&proof_bytes, &[a * b, a]));
+    assert!(!generated_verifier::verify(&proof_bytes[1..], &[a * b]));
+    proof_bytes.push(0);
+    assert!(!generated_verifier::verify(&proof_bytes, &[a * b]));
+
+    let mut vk_without_constant = vk.clone();
+    vk_without_constant.gamma_abc_g1_static.clear();
+    assert_eq!(
+        generate_verifier(&vk_without_constant, "ark_bn254::Bn254"),
+        Err(SynthesisError::MalformedVerifyingKey)
+    );
+}
+
+fn test_audit_public_inputs<E>()
+where
+    E: Pairing,
+{
+    use crate::audit::audit_public_inputs;
+
+    struct AuditCircuit;
+
+    impl<F: Field> ConstraintSynthesizer<F> for AuditCircuit {
+        fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+            let product = cs.new_input_variable(|| Ok(F::one()))?;
+            let _free = cs.new_input_variable(|| Ok(F::one()))?;
+            let copy = cs.new_input_variable(|| Ok(F::one()))?;
+            let w = (0..6)
+                .map(|_| cs.new_witness_variable(|| Ok(F::one())))
+                .collect::<Result<Vec<_>, _>>()?;
+            cs.enforce_constraint(lc!() + w[0], lc!() + w[1], lc!() + product)?;
+            cs.enforce_constraint(lc!() + w[2], lc!() + w[2], lc!() + w[1])?;
+            cs.enforce_constraint(lc!() + w[3], lc!() + w[3], lc!() + w[4])?;
+            cs.enforce_constraint(lc!() + copy, lc!() + Variable::One, lc!() + w[5])
+        }
+    }
+
+    let audit = audit_public_inputs::<E::ScalarField, _>(AuditCircuit).unwrap();
+    assert_eq!(audit.num_witness_variables, 6);
+    assert_eq!(audit.inputs.len(), 3);
+
+    // The product is computed from the first witnesses, one of them the
+    // square of another.
+    assert_eq!(audit.inputs[0].input, 1);
+    assert_eq!(audit.inputs[0].constraints, vec![0]);
+    assert_eq!(audit.inputs[0].direct, vec![0, 1]);
+    assert_eq!(audit.inputs[0].reachable, vec![0, 1, 2]);
+
+    // The copy only reaches its witness, not through the constant one.
+    assert_eq!(audit.inputs[2].direct, vec![5]);
+    assert_eq!(audit.inputs[2].reachable, vec![5]);
+
+    assert_eq!(audit.unconstrained_inputs().collect::<Vec<_>>(), vec![2]);
+    assert!(audit.to_string().contains("input 2: unconstrained"));
+}
+
+#[cfg(feature = "fuzz")]
+fn test_fuzz_input<E>()
+where
+    E: Pairing,
+{
+    use crate::fuzz::{mutate_serialized, FromFuzzInput, FuzzInput, MAX_FUZZ_INPUTS};
+    use crate::VerifyingKey;
+
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    // Any input produces a value, the identity once the input is exhausted.
+    let proof = Proof::<E>::from_fuzz_input(&mut FuzzInput::new(&[]));
+    assert!(proof.a.is_zero() && proof.b.is_zero() && proof.c.is_zero());
+
+    let (pk, _) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let pvk = prepare_verifying_key::<E>(&pk.vk);
+    for _ in 0..32 {
+        let mut data = vec![0u8; 1024];
+        rng.fill_bytes(&mut data);
+        let mut input = FuzzInput::new(&data);
+        let vk = VerifyingKey::<E>::from_fuzz_input(&mut input);
+        let proof = Proof::<E>::from_fuzz_input(&mut input);
+        assert!(!vk.gamma_abc_g1_static.is_empty());
+        assert!(vk.gamma_abc_g1_static.len() <= MAX_FUZZ_INPUTS);
+
+        let mut bytes = Vec::new();
+        vk.serialize_compressed(&mut bytes).unwrap();
+        assert_eq!(
+            VerifyingKey::<E>::deserialize_compressed(bytes.as_slice()).unwrap(),
+            vk
+        );
+
+        // Generated points pass the curve and subgroup checks, while
+        // deserializing and verifying corrupted proofs fails without
+        // panicking.
+        let mut bytes = Vec::new();
+        proof.serialize_compressed(&mut bytes).unwrap();
+        assert_eq!(
+            Proof::<E>::deserialize_compressed(bytes.as_slice()).unwrap(),
+            proof
+        );
+        while !input.is_empty() {
+            mutate_serialized(&mut bytes, &mut input);
+            if let Ok(proof) = Proof::<E>::deserialize_compressed(bytes.as_slice()) {
+                let input = E::ScalarField::rand(&mut rng);
+                let _ = Groth16::<E>::verify_with_processed_vk(&pvk, &[input], &proof);
+            }
+        }
+    }
+}
+
+fn test_verifier_pool<E>()
+where
+    E: Pairing,
+{
+    use crate::verifier_pool::{SubmitError, VerifierPool, VerifierPoolConfig};
+
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let (_, other_vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let proof = Groth16::<E>::prove(
+        &pk,
+        MySillyCircuit {
+            a: Some(a),
+            b: Some(b),
+        },
+        &mut rng,
+    )
+    .unwrap();
+
+    let pool = VerifierPool::<E>::new(VerifierPoolConfig {
+        num_workers: 2,
+        queue_capacity: 16,
+        max_pending_per_tenant: 2,
+    });
+    pool.register_tenant("alice", &vk);
+    pool.register_tenant("bob", &other_vk);
+
+    let valid = pool.submit("alice", proof.clone(), vec![a * b]).unwrap();
+    let invalid = pool.submit("alice", proof.clone(), vec![a]).unwrap();
+    let other_key = pool.submit("bob", proof.clone(), vec![a * b]).unwrap();
+    assert!(valid.recv().unwrap().unwrap());
+    assert!(!invalid.recv().unwrap().unwrap());
+    assert!(!other_key.recv().unwrap().unwrap());
+    assert!(matches!(
+        pool.submit("carol", proof.clone(), vec![a * b]),
+        Err(SubmitError::UnknownTenant)
+    ));
+
+    // A tenant submitting faster than it is verified hits its quota, while
+    // other tenants are still served.
+    let submissions: Vec<_> = (0..8)
+        .map(|_| pool.submit("alice", proof.clone(), vec![a * b]))
+        .collect();
+    assert!(submissions
+        .iter()
+        .any(|submission| matches!(submission, Err(SubmitError::TenantBusy))));
+    let other_tenant = pool.submit("bob", proof.clone(), vec![a * b]).unwrap();
+    for receiver in submissions.into_iter().flatten() {
+        assert!(receiver.recv().unwrap().unwrap());
+    }
+    assert!(!other_tenant.recv().unwrap().unwrap());
+    assert_eq!(pool.pending("alice"), Some(0));
+
+    assert!(pool.remove_tenant("bob"));
+    assert_eq!(pool.pending("bob"), None);
+
+    // Dropping the pool completes the queued verifications.
+    let queued = pool.submit("alice", proof, vec![a * b]).unwrap();
+    drop(pool);
+    assert!(queued.recv().unwrap().unwrap());
+}
+
+fn test_zkey_export<E>()
+where
+    E: Pairing,
+{
+    use crate::zkey::{domain, h_points, montgomery_factor, root_of_unity, write_zkey};
+    use ark_ff::BigInteger;
+    use ark_poly::EvaluationDomain;
+    use std::collections::BTreeMap;
+
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let cs = ConstraintSystem::new_ref();
+    MySillyCircuit {
+        a: Some(a),
+        b: Some(b),
+    }
+    .generate_constraints(cs.clone())
+    .unwrap();
+    cs.finalize();
+    let matrices = cs.to_matrices().unwrap();
+    let full_assignment = [E::ScalarField::one(), a * b, a, b];
+    let pk = Groth16::<E>::generate_parameters_with_matrices(&matrices, 1, &mut rng).unwrap();
+
+    let mut zkey = Vec::new();
+    write_zkey(&pk, &matrices, &mut zkey).unwrap();
+
+    let u32_at = |bytes: &[u8], offset: usize| {
+        u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap()) as usize
+    };
+    assert_eq!(&zkey[..4], b"zkey");
+    assert_eq!(u32_at(&zkey, 4), 1);
+    assert_eq!(u32_at(&zkey, 8), 10);
+    let mut sections = BTreeMap::new();
+    let mut offset = 12;
+    while offset < zkey.len() {
+        let id = u32_at(&zkey, offset);
+        let size = u64::from_le_bytes(zkey[offset + 4..offset + 12].try_into().unwrap()) as usize;
+        sections.insert(id, &zkey[offset + 12..offset + 12 + size]);
+        offset += 12 + size;
+    }
+    assert_eq!(
+        sections.keys().copied().collect::<Vec<_>>(),
+        (1..=10).collect::<Vec<_>>()
+    );
+    assert_eq!(u32_at(sections[&1], 0), 1);
+
+    // The header holds the moduli and the shape of the circuit, followed by
+    // points in Montgomery form.
+    let header = sections[&2];
+    let n8q = u32_at(header, 0);
+    let n8r = u32_at(header, 4 + n8q);
+    assert_eq!(
+        &header[8 + n8q..8 + n8q + n8r],
+        E::ScalarField::MODULUS.to_bytes_le().as_slice()
+    );
+    let shape = 8 + n8q + n8r;
+    let domain_size = u32_at(header, shape + 8);
+    assert_eq!(u32_at(header, shape), 4);
+    assert_eq!(u32_at(header, shape + 4), 1);
+    assert_eq!(
+        domain_size,
+        (matrices.num_constraints + 2).next_power_of_two()
+    );
+    type Fq<E> = <<<E as Pairing>::G1Affine as AffineRepr>::BaseField as Field>::BasePrimeField;
+    let decode_point = |bytes: &[u8]| -> Vec<Fq<E>> {
+        bytes
+            .chunks(n8q)
+            .map(|chunk| Fq::<E>::from_le_bytes_mod_order(chunk) / montgomery_factor::<Fq<E>>())
+            .collect()
+    };
+    let coordinates = |point: &E::G1Affine| -> Vec<_> {
+        let (x, y) = point.xy().unwrap();
+        x.to_base_prime_field_elements()
+            .chain(y.to_base_prime_field_elements())
+            .collect()
+    };
+    assert_eq!(
+        decode_point(&header[shape + 12..shape + 12 + 2 * n8q]),
+        coordinates(&pk.vk.alpha_g1)
+    );
+
+    // Evaluate A and B over the domain from the coefficients, stored times
+    // `R^2`, as snarkjs does.
+    let snarkjs_domain = domain::<E::ScalarField>(domain_size).unwrap();
+    let r_squared = montgomery_factor::<E::ScalarField>().square();
+    let coefficients = sections[&4];
+    let mut evaluations = [
+        vec![E::ScalarField::zero(); domain_size],
+        vec![E::ScalarField::zero(); domain_size],
+    ];
+    let entry_size = 12 + n8r;
+    assert_eq!(coefficients.len(), 4 + u32_at(coefficients, 0) * entry_size);
+    for entry in coefficients[4..].chunks(entry_size) {
+        let coefficient = E::ScalarField::from_le_bytes_mod_order(&entry[12..]) / r_squared;
+        evaluations[u32_at(entry, 0)][u32_at(entry, 4)] +=
+            coefficient * full_assignment[u32_at(entry, 8)];
+    }
+    let [a_evals, b_evals] = evaluations;
+    let c_evals: Vec<_> = a_evals.iter().zip(&b_evals).map(|(a, b)| *a * b).collect();
+
+    // Move them to the coset of the root of unity of twice the size
+    let shift = root_of_unity::<E::ScalarField>(2 * domain_size as u64).unwrap();
+    let to_coset = |mut evals: Vec<E::ScalarField>| {
+        snarkjs_domain.ifft_in_place(&mut evals);
+        let mut power = E::ScalarField::one();
+        for coefficient in evals.iter_mut() {
+            *coefficient *= power;
+            power *= shift;
+        }
+        snarkjs_domain.fft_in_place(&mut evals);
+        evals
+    };
+    let (a_coset, b_coset, c_coset) = (to_coset(a_evals), to_coset(b_evals), to_coset(c_evals));
+
+    let h = h_points::<E>(&pk.h_query, &snarkjs_domain).unwrap();
+    for (i, point) in h.iter().enumerate() {
+        let offset = i * 2 * n8q;
+        assert_eq!(
+            decode_point(&sections[&9][offset..offset + 2 * n8q]),
+            coordinates(point)
+        );
+    }
+
+    // A proof as created by snarkjs from the zkey, with `r = s = 0`
+    let mut c = E::G1::zero();
+    for i in 0..domain_size {
+        c += h[i] * (a_coset[i] * b_coset[i] - c_coset[i]);
+    }
+    for (point, w) in pk.l_query.iter().zip(&full_assignment[2..]) {
+        c += *point * w;
+    }
+    let mut proof_a = pk.vk.alpha_g1.into_group();
+    let mut proof_b = pk.vk.beta_g2.into_group();
+    for (i, w) in full_assignment.iter().enumerate() {
+        proof_a += pk.a_query[i] * w;
+        proof_b += pk.b_g2_query[i] * w;
+    }
+    let proof = Proof {
+        a: proof_a.into_affine(),
+        b: proof_b.into_affine(),
+        c: c.into_affine(),
+    };
+    assert!(Groth16::<E>::verify(&pk.vk, &[a * b], &proof).unwrap());
+    assert!(!Groth16::<E>::verify(&pk.vk, &[a], &proof).unwrap());
+
+    // Keys which snarkjs cannot use are rejected.
+    let mut lagrange_pk = pk.clone();
+    lagrange_pk.h_basis = KeyBasis::Lagrange;
+    assert!(write_zkey(&lagrange_pk, &matrices, &mut Vec::new()).is_err());
+    let mut short_pk = pk;
+    short_pk.l_query.pop();
+    assert!(write_zkey(&short_pk, &matrices, &mut Vec::new()).is_err());
+}
+
+fn test_zkey_verify<E>()
+where
+    E: Pairing,
+{
+    use crate::zkey::{domain, verify_zkey, write_points, write_section, write_zkey};
+    use ark_ff::BigInteger;
+    use ark_poly::EvaluationDomain;
+    use ark_relations::r1cs::ConstraintMatrices;
+
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let cs = ConstraintSystem::new_ref();
+    MySillyCircuit {
+        a: Some(E::ScalarField::rand(&mut rng)),
+        b: Some(E::ScalarField::rand(&mut rng)),
+    }
+    .generate_constraints(cs.clone())
+    .unwrap();
+    cs.finalize();
+    let matrices = cs.to_matrices().unwrap();
+    let num_instance = matrices.num_instance_variables;
+    let num_variables = num_instance + matrices.num_witness_variables;
+    let domain_size = (matrices.num_constraints + num_instance).next_power_of_two();
+    // The H query is read from the bases of the domain of twice the size.
+    let power = domain_size.trailing_zeros() + 1;
+
+    type Fq<E> = <<<E as Pairing>::G1Affine as AffineRepr>::BaseField as Field>::BasePrimeField;
+    let (g1, g2) = (
+        E::G1Affine::generator().into_group(),
+        E::G2Affine::generator().into_group(),
+    );
+    let lagrange = |tau: E::ScalarField, size: usize| {
+        domain::<E::ScalarField>(size)
+            .unwrap()
+            .evaluate_all_lagrange_coefficients(tau)
+    };
+    let powers = |tau: E::ScalarField, count: usize| {
+        ark_std::iter::successors(Some(E::ScalarField::one()), move |x| Some(*x * tau))
+            .take(count)
+            .collect::<Vec<_>>()
+    };
+    let g1_points = |scalars: &[E::ScalarField], factor: E::ScalarField| {
+        E::G1::normalize_batch(
+            &scalars
+                .iter()
+                .map(|s| g1 * (*s * factor))
+                .collect::<Vec<_>>(),
+        )
+    };
+    let g2_points = |scalars: &[E::ScalarField]| {
+        E::G2::normalize_batch(&scalars.iter().map(|s| g2 * s).collect::<Vec<_>>())
+    };
+    let file = |magic: &[u8], sections: Vec<(u32, Vec<u8>)>| {
+        let mut file = magic.to_vec();
+        file.extend_from_slice(&1u32.to_le_bytes());
+        file.extend_from_slice(&(sections.len() as u32).to_le_bytes());
+        for (id, section) in sections {
+            write_section(&mut file, id, &section).unwrap();
+        }
+        file
+    };
+    let points_section = |points: &[E::G1Affine]| {
+        let mut section = Vec::new();
+        write_points(&mut section, points);
+        section
+    };
+
+    // A prepared ptau of the ceremony of `tau`, `alpha` and `beta`.
+    let (tau, alpha, beta) = (
+        E::ScalarField::rand(&mut rng),
+        E::ScalarField::rand(&mut rng),
+        E::ScalarField::rand(&mut rng),
+    );
+    let ptau_for = |tau: E::ScalarField, power: u32| {
+        let size = 1usize << power;
+        let modulus = Fq::<E>::MODULUS.to_bytes_le();
+        let mut header = (modulus.len() as u32).to_le_bytes().to_vec();
+        header.extend_from_slice(&modulus);
+        header.extend_from_slice(&power.to_le_bytes());
+        header.extend_from_slice(&power.to_le_bytes());
+        let lagrange_g1 = |p_max: u32, factor| {
+            let scalars: Vec<_> = (0..=p_max).flat_map(|p| lagrange(tau, 1 << p)).collect();
+            points_section(&g1_points(&scalars, factor))
+        };
+        let lagrange_g2: Vec<_> = (0..=power).flat_map(|p| lagrange(tau, 1 << p)).collect();
+        let mut lagrange_g2_section = Vec::new();
+        write_points(&mut lagrange_g2_section, &g2_points(&lagrange_g2));
+        let mut tau_g2 = Vec::new();
+        write_points(&mut tau_g2, &g2_points(&powers(tau, size)));
+        let mut beta_g2 = Vec::new();
+        write_points(&mut beta_g2, &g2_points(&[beta]));
+        file(
+            b"ptau",
+            vec![
+                (1, header),
+                (
+                    2,
+                    points_section(&g1_points(
+                        &powers(tau, 2 * size - 1),
+                        E::ScalarField::one(),
+                    )),
+                ),
+                (3, tau_g2),
+                (4, points_section(&g1_points(&powers(tau, size), alpha))),
+                (5, points_section(&g1_points(&powers(tau, size), beta))),
+                (6, beta_g2),
+                (7, 0u32.to_le_bytes().to_vec()),
+                (12, lagrange_g1(power, E::ScalarField::one())),
+                (13, lagrange_g2_section),
+                (14, lagrange_g1(power, alpha)),
+                (15, lagrange_g1(power, beta)),
+            ],
+        )
+    };
+    let ptau = ptau_for(tau, power);
+
+    // The r1cs of the circuit, with the coefficients in standard form.
+    let r1cs_for = |matrices: &ConstraintMatrices<E::ScalarField>| {
+        let modulus = E::ScalarField::MODULUS.to_bytes_le();
+        let mut header = (modulus.len() as u32).to_le_bytes().to_vec();
+        header.extend_from_slice(&modulus);
+        for count in [
+            num_variables,
+            0,
+            num_instance - 1,
+            matrices.num_witness_variables,
+        ] {
+            header.extend_from_slice(&(count as u32).to_le_bytes());
+        }
+        header.extend_from_slice(&(num_variables as u64).to_le_bytes());
+        header.extend_from_slice(&(matrices.num_constraints as u32).to_le_bytes());
+        let mut constraints = Vec::new();
+        for i in 0..matrices.num_constraints {
+            for row in [&matrices.a[i], &matrices.b[i], &matrices.c[i]] {
+                constraints.extend_from_slice(&(row.len() as u32).to_le_bytes());
+                for (coefficient, variable) in row {
+                    constraints.extend_from_slice(&(*variable as u32).to_le_bytes());
+                    constraints.extend_from_slice(&coefficient.into_bigint().to_bytes_le());
+                }
+            }
+        }
+        file(b"r1cs", vec![(1, header), (2, constraints)])
+    };
+    let r1cs = r1cs_for(&matrices);
+
+    // The key snarkjs derives from the ptau, with `gamma = 1`, exported
+    // with the H section of snarkjs and the given contributions.
+    let l = lagrange(tau, domain_size);
+    let (mut u, mut v, mut w) = (
+        vec![E::ScalarField::zero(); num_variables],
+        vec![E::ScalarField::zero(); num_variables],
+        vec![E::ScalarField::zero(); num_variables],
+    );
+    for (c, evaluations) in [
+        (&matrices.a, &mut u),
+        (&matrices.b, &mut v),
+        (&matrices.c, &mut w),
+    ] {
+        for (constraint, row) in c.iter().enumerate() {
+            for (coefficient, variable) in row {
+                evaluations[*variable] += l[constraint] * coefficient;
+            }
+        }
+    }
+    for i in 0..num_instance {
+        u[i] += l[matrices.num_constraints + i];
+    }
+    let c: Vec<_> = (0..num_variables)
+        .map(|i| beta * u[i] + alpha * v[i] + w[i])
+        .collect();
+    let template = Groth16::<E>::generate_parameters_with_matrices(&matrices, 1, &mut rng).unwrap();
+    let zkey_for = |delta: E::ScalarField, contributions: &[E::G1Affine]| {
+        let delta_inv = delta.inverse().unwrap();
+        let ic = g1_points(&c[..num_instance], E::ScalarField::one());
+        let num_static = template.vk.gamma_abc_g1_static.len();
+        let mut pk = template.clone();
+        pk.vk.alpha_g1 = (g1 * alpha).into_affine();
+        pk.vk.beta_g2 = (g2 * beta).into_affine();
+        pk.vk.gamma_g2 = g2.into_affine();
+        pk.vk.delta_g2 = (g2 * delta).into_affine();
+        pk.vk.gamma_abc_g1_static = ic[..num_static].to_vec();
+        pk.vk.gamma_abc_g1_variable = ic[num_static..].to_vec();
+        pk.beta_g1 = (g1 * beta).into_affine();
+        pk.delta_g1 = (g1 * delta).into_affine();
+        pk.a_query = g1_points(&u, E::ScalarField::one());
+        pk.b_g1_query = g1_points(&v, E::ScalarField::one());
+        pk.b_g2_query = g2_points(&v);
+        pk.l_query = g1_points(&c[num_instance..], delta_inv);
+        let mut zkey = Vec::new();
+        write_zkey(&pk, &matrices, &mut zkey).unwrap();
+
+        let mut sections = Vec::new();
+        let mut offset = 12;
+        while offset < zkey.len() {
+            let id = u32::from_le_bytes(zkey[offset..offset + 4].try_into().unwrap());
+            let size =
+                u64::from_le_bytes(zkey[offset + 4..offset + 12].try_into().unwrap()) as usize;
+            sections.push((id, zkey[offset + 12..offset + 12 + size].to_vec()));
+            offset += 12 + size;
+        }
+        let h: Vec<_> = lagrange(tau, 2 * domain_size)
+            .into_iter()
+            .skip(1)
+            .step_by(2)
+            .collect();
+        sections[8].1 = points_section(&g1_points(&h, delta_inv));
+        let mut record = vec![0u8; 64];
+        record.extend_from_slice(&(contributions.len() as u32).to_le_bytes());
+        for delta_after in contributions {
+            let s = (g1 * tau).into_affine();
+            write_points(&mut record, [delta_after, &s, &s]);
+            write_points(&mut record, [&E::G2Affine::generator()]);
+            record.extend_from_slice(&[0u8; 64]);
+            record.extend_from_slice(&[0u8; 8]);
+        }
+        sections[9].1 = record;
+        file(b"zkey", sections)
+    };
+
+    let report = verify_zkey::<E>(
+        &zkey_for(E::ScalarField::one(), &[]),
+        &r1cs,
+        &ptau,
+        &mut rng,
+    )
+    .unwrap();
+    assert!(report.is_valid(), "{}", report);
+    assert_eq!(report.num_contributions, 0);
+
+    // A contribution must be recorded for delta.
+    let delta = E::ScalarField::rand(&mut rng);
+    let report = verify_zkey::<E>(&zkey_for(delta, &[]), &r1cs, &ptau, &mut rng).unwrap();
+    assert!(report.delta && report.c && report.h);
+    assert!(!report.contributions && !report.is_valid());
+    assert!(report.to_string().contains("contributions: failed"));
+    let contributions = [
+        (g1 * E::ScalarField::rand(&mut rng)).into_affine(),
+        (g1 * delta).into_affine(),
+    ];
+    let zkey = zkey_for(delta, &contributions);
+    let report = verify_zkey::<E>(&zkey, &r1cs, &ptau, &mut rng).unwrap();
+    assert!(report.is_valid(), "{}", report);
+    assert_eq!(report.num_contributions, 2);
+
+    // The key of another ceremony or another circuit is rejected.
+    let report = verify_zkey::<E>(
+        &zkey,
+        &r1cs,
+        &ptau_for(E::ScalarField::rand(&mut rng), power),
+        &mut rng,
+    )
+    .unwrap();
+    assert!(report.alpha_beta && report.coefficients);
+    assert!(!report.ic && !report.a && !report.b_g1 && !report.b_g2 && !report.c && !report.h);
+    let mut other_matrices = matrices.clone();
+    other_matrices.a[0][0].0 += E::ScalarField::one();
+    let report = verify_zkey::<E>(&zkey, &r1cs_for(&other_matrices), &ptau, &mut rng).unwrap();
+    assert!(report.shape && !report.coefficients && !report.a && !report.c);
+
+    // A ptau one power too small for the H query is reported, not an error.
+    let report = verify_zkey::<E>(&zkey, &r1cs, &ptau_for(tau, power - 1), &mut rng).unwrap();
+    assert!(report.shape && !report.ptau_power && !report.is_valid());
+
+    // Malformed files are errors.
+    assert!(verify_zkey::<E>(&zkey[..zkey.len() - 1], &r1cs, &ptau, &mut rng).is_err());
+    assert!(verify_zkey::<E>(&zkey, &ptau, &r1cs, &mut rng).is_err());
+    // The power follows the file header, the header of the first section
+    // and the modulus with its length.
+    let mut huge_power = ptau.clone();
+    let offset = 28 + Fq::<E>::MODULUS.to_bytes_le().len();
+    assert_eq!(huge_power[offset..offset + 4], power.to_le_bytes());
+    huge_power[offset..offset + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+    assert!(verify_zkey::<E>(&zkey, &r1cs, &huge_power, &mut rng).is_err());
+}
+
+fn test_msm_bounded_bits<E>()
+where
+    E: Pairing,
+{
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let bases = (0..40)
+        .map(|_| E::G1::rand(&mut rng).into_affine())
+        .collect::<Vec<_>>();
+    let small = (0..40)
+        .map(|_| E::ScalarField::from(rng.next_u64()))
+        .collect::<Vec<_>>();
+    let mut mixed = small.clone();
+    mixed[3] = E::ScalarField::rand(&mut rng);
+    mixed[5] = E::ScalarField::zero();
+    for scalars in [small, mixed, vec![E::ScalarField::zero(); 40]] {
+        for size in [1, 5, 40] {
+            let bigints = scalars[..size]
+                .iter()
+                .map(|s| s.into_bigint())
+                .collect::<Vec<_>>();
+            let expected = bases[..size]
+                .iter()
+                .zip(&scalars)
+                .map(|(base, scalar)| *base * scalar)
+                .sum::<E::G1>();
+            assert_eq!(msm_bounded_bits::<E::G1>(&bases, &bigints), expected);
+        }
+    }
+
+    let inputs = (0..8)
+        .map(|_| E::ScalarField::from(rng.next_u64()))
+        .collect::<Vec<_>>();
+    let (pk, vk) = Groth16::<E>::setup(
+        InputsCircuit {
+            inputs: vec![None; 8],
+        },
+        &mut rng,
+    )
+    .unwrap();
+    let proof = Groth16::<E>::prove(
+        &pk,
+        InputsCircuit {
+            inputs: inputs.iter().copied().map(Some).collect(),
+        },
+        &mut rng,
+    )
+    .unwrap();
+    assert!(Groth16::<E>::verify(&vk, &inputs, &proof).unwrap());
+    let mut wrong = inputs.clone();
+    wrong[7] += E::ScalarField::one();
+    assert!(!Groth16::<E>::verify(&vk, &wrong, &proof).unwrap());
+}
+
+fn test_msm_constant_time<E>()
+where
+    E: Pairing,
+{
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    // Spans two chunks of tables.
+    let size = 1100;
+    let bases = (0..size)
+        .map(|_| E::G1::rand(&mut rng).into_affine())
+        .collect::<Vec<_>>();
+    let mut scalars = (0..size)
+        .map(|_| E::ScalarField::rand(&mut rng))
+        .collect::<Vec<_>>();
+    scalars[0] = E::ScalarField::zero();
+    scalars[1] = E::ScalarField::one();
+    scalars[2] = -E::ScalarField::one();
+    scalars[3] = E::ScalarField::from(u64::MAX);
+    let bigints = scalars.iter().map(|s| s.into_bigint()).collect::<Vec<_>>();
+
+    for size in [0, 1, 4, size] {
+        let expected = E::G1::msm_bigint(&bases[..size], &bigints[..size]);
+        assert_eq!(
+            msm_constant_time::<E::G1>(&bases[..size], &bigints),
+            expected
+        );
+        assert_eq!(
+            MsmBackend::<E::G1>::msm_bigint(&ConstantTimeMsm, &bases[..size], &bigints),
+            expected
+        );
+    }
+    assert_eq!(
+        msm_constant_time::<E::G1>(&bases, &[E::ScalarField::zero().into_bigint(); 3]),
+        E::G1::zero()
+    );
+
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let (a, b) = (
+        E::ScalarField::rand(&mut rng),
+        E::ScalarField::rand(&mut rng),
+    );
+    let proof = Groth16::<E>::create_random_proof_with_backend(
+        MySillyCircuit {
+            a: Some(a),
+            b: Some(b),
+        },
+        &pk,
+        &ConstantTimeMsm,
+        &mut rng,
+    )
+    .unwrap();
+    assert!(Groth16::<E>::verify(&vk, &[a * b], &proof).unwrap());
+}
+
+fn test_verify_split<E>()
+where
+    E: Pairing,
+{
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let pvk = prepare_verifying_key::<E>(&vk);
+    let prepare =
+        |c: E::ScalarField| Groth16::<E>::prepare_inputs_with_variables(&pvk, &[], &[c]).unwrap();
+
+    let mut splits = Vec::new();
+    for _ in 0..3 {
+        let a = E::ScalarField::rand(&mut rng);
+        let b = E::ScalarField::rand(&mut rng);
+        let proof = Groth16::<E>::prove(
+            &pk,
+            MySillyCircuit {
+                a: Some(a),
+                b: Some(b),
+            },
+            &mut rng,
+        )
+        .unwrap();
+        let split = Groth16::<E>::verify_split(&pvk, &proof, &prepare(a * b));
+        assert!(Groth16::<E>::check_split(&pvk, &split).unwrap());
+        splits.push(split);
+
+        let wrong = Groth16::<E>::verify_split(&pvk, &proof, &prepare(a));
+        assert_eq!(wrong.ab, split.ab);
+        assert!(!Groth16::<E>::check_split(&pvk, &wrong).unwrap());
+        splits.push(wrong);
+    }
+
+    let valid = splits.iter().step_by(2).copied().collect::<Vec<_>>();
+    assert!(Groth16::<E>::check_split_batch(&pvk, &valid, &mut rng).unwrap());
+    assert!(Groth16::<E>::check_split_batch(&pvk, &[], &mut rng).unwrap());
+    assert!(!Groth16::<E>::check_split_batch(&pvk, &splits, &mut rng).unwrap());
+    assert!(!Groth16::<E>::check_split_batch(&pvk, &splits[..2], &mut rng).unwrap());
+}
+
+fn test_tuned_msm<E>()
+where
+    E: Pairing,
+{
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let size = 300;
+    let bases = (0..size)
+        .map(|_| E::G1::rand(&mut rng).into_affine())
+        .collect::<Vec<_>>();
+    let mut scalars = (0..size)
+        .map(|_| E::ScalarField::rand(&mut rng))
+        .collect::<Vec<_>>();
+    scalars[0] = E::ScalarField::zero();
+    scalars[1] = -E::ScalarField::one();
+    let bigints = scalars.iter().map(|s| s.into_bigint()).collect::<Vec<_>>();
+    let expected = E::G1::msm_bigint(&bases, &bigints);
+    for c in [1, 3, 8, 13] {
+        let backend = TunedMsm::new()
+            .with_table::<E::G1>(WindowTable::from_entries(vec![(0, Some(c))]).unwrap());
+        assert_eq!(
+            MsmBackend::<E::G1>::msm_bigint(&backend, &bases, &bigints),
+            expected
+        );
+        assert_eq!(
+            MsmBackend::<E::G1>::msm_bigint(&backend, &bases[..7], &bigints),
+            E::G1::msm_bigint(&bases[..7], &bigints[..7])
+        );
+    }
+
+    let table = WindowTable::from_entries(vec![(1024, None), (16, Some(5))]).unwrap();
+    assert_eq!(table.entries(), &[(16, Some(5)), (1024, None)]);
+    assert_eq!(table.window(1), Some(5));
+    assert_eq!(table.window(1023), Some(5));
+    assert_eq!(table.window(1 << 20), None);
+    assert_eq!(WindowTable::default().window(16), None);
+    for window in [0, MAX_TUNING_WINDOW + 1, 64] {
+        assert_eq!(
+            WindowTable::from_entries(vec![(16, Some(5)), (256, Some(window))]),
+            Err(InvalidWindow { size: 256, window })
+        );
+    }
+    assert!(WindowTable::from_entries(vec![(0, Some(MAX_TUNING_WINDOW))]).is_ok());
+
+    let tuned = MsmTuner::new()
+        .with_sizes(vec![16, 64])
+        .with_window_spread(1)
+        .with_repetitions(1)
+        .calibrate::<E>(&mut rng);
+    for table in [tuned.table::<E::G1>(), tuned.table::<E::G2>()] {
+        let sizes = table.unwrap().entries().iter().map(|(size, _)| *size);
+        assert_eq!(sizes.collect::<Vec<_>>(), [16, 64]);
+    }
+
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let (a, b) = (
+        E::ScalarField::rand(&mut rng),
+        E::ScalarField::rand(&mut rng),
+    );
+    let backend = TunedMsm::new()
+        .with_table::<E::G1>(WindowTable::from_entries(vec![(0, Some(2))]).unwrap())
+        .with_table::<E::G2>(WindowTable::from_entries(vec![(0, Some(3))]).unwrap());
+    let proof = Groth16::<E>::create_random_proof_with_backend(
+        MySillyCircuit {
+            a: Some(a),
+            b: Some(b),
+        },
+        &pk,
+        &backend,
+        &mut rng,
+    )
+    .unwrap();
+    assert!(Groth16::<E>::verify(&vk, &[a * b], &proof).unwrap());
+}
+
+fn test_vk_accumulator<E>()
+where
+    E: Pairing,
+{
+    use crate::accumulator::{AccumulatorError, AccumulatorParams};
+
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let params = AccumulatorParams::<E>::setup(3, &mut rng);
+    assert_eq!(params.capacity(), 3);
+    let keys = (0..4)
+        .map(|_| Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap())
+        .collect::<Vec<_>>();
+    let members = keys[..3]
+        .iter()
+        .map(|(_, vk)| vk.fingerprint())
+        .collect::<Vec<_>>();
+    let accumulator = params.accumulate(&members).unwrap();
+    assert_eq!(
+        params.accumulate(&[members.clone(), vec![keys[3].1.fingerprint()]].concat()),
+        Err(AccumulatorError::CapacityExceeded {
+            capacity: 3,
+            len: 4
+        })
+    );
+
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let prove = |pk| {
+        let circuit = MySillyCircuit {
+            a: Some(a),
+            b: Some(b),
+        };
+        Groth16::<E>::prove(
+            pk,
+            circuit,
+            &mut ark_std::rand::rngs::StdRng::seed_from_u64(0),
+        )
+        .unwrap()
+    };
+
+    let (pk, vk) = &keys[1];
+    let witness = params.membership_witness(&members, &members[1]).unwrap();
+    assert!(params.verify_membership(&accumulator, &members[1], &witness));
+    let proof = prove(pk);
+    assert!(Groth16::<E>::verify_with_accumulator(
+        &params,
+        &accumulator,
+        vk,
+        &witness,
+        &[a * b],
+        &proof
+    )
+    .unwrap());
+    assert!(!Groth16::<E>::verify_with_accumulator(
+        &params,
+        &accumulator,
+        vk,
+        &witness,
+        &[a],
+        &proof
+    )
+    .unwrap());
+
+    // A witness only proves the membership of its own key.
+    let (other_pk, other_vk) = &keys[3];
+    assert!(!params.verify_membership(&accumulator, &members[0], &witness));
+    assert!(!Groth16::<E>::verify_with_accumulator(
+        &params,
+        &accumulator,
+        other_vk,
+        &witness,
+        &[a * b],
+        &prove(other_pk)
+    )
+    .unwrap());
+    assert_eq!(
+        params.membership_witness(&members, &other_vk.fingerprint()),
+        Err(AccumulatorError::NotAMember)
+    );
+}
+
+fn test_proof_bundle<E>()
+where
+    E: Pairing,
+{
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let (_, other_vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let (proof, inputs) = Groth16::<E>::prove_and_output_inputs(
+        &pk,
+        MySillyCircuit {
+            a: Some(a),
+            b: Some(b),
+        },
+        &mut rng,
+    )
+    .unwrap();
+    let bundle = ProofBundle::new(proof, inputs, &vk, "silly", 1_700_000_000);
+
+    let mut bytes = Vec::new();
+    bundle.serialize_compressed(&mut bytes).unwrap();
+    let bundle = ProofBundle::<E>::deserialize_compressed(&bytes[..]).unwrap();
+    assert_eq!(bundle.circuit_id, "silly");
+
+    let mut registry = VerifyingKeyRegistry::new();
+    registry.insert(&other_vk);
+    assert!(bundle.verify(&registry).is_err());
+
+    assert_eq!(registry.insert(&vk), bundle.vk_fingerprint);
+    assert_eq!(registry.len(), 2);
+    assert!(bundle.verify(&registry).unwrap());
+
+    let mut tampered = bundle.clone();
+    tampered.public_inputs[0] = a;
+    assert!(!tampered.verify(&registry).unwrap());
+}
+
+fn test_verify_with_report<E>()
+where
+    E: Pairing,
+{
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let pvk = prepare_verifying_key::<E>(&vk);
+
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let proof = Groth16::<E>::prove(
+        &pk,
+        MySillyCircuit {
+            a: Some(a),
+            b: Some(b),
+        },
+        &mut rng,
+    )
+    .unwrap();
+
+    let report = Groth16::<E>::verify_with_report(&pvk, &proof, &[], &[a * b]).unwrap();
+    assert!(report.accepted);
+    assert_eq!(report.input_msm_size, 1);
+
+    let report = Groth16::<E>::verify_with_report(&pvk, &proof, &[], &[a]).unwrap();
+    assert!(!report.accepted);
+
+    assert!(Groth16::<E>::verify_with_report(&pvk, &proof, &[], &[]).is_err());
+}
+
+/// Allocator statistics growing by 64 bytes more in each phase.
+#[derive(Default)]
+struct GrowingAllocatorStats {
+    phases: std::cell::Cell<usize>,
+}
+
+impl AllocatorStats for GrowingAllocatorStats {
+    fn allocated(&self) -> usize {
+        1024
+    }
+
+    fn peak(&self) -> usize {
+        1024 + 64 * self.phases.get()
+    }
+
+    fn reset_peak(&self) {
+        self.phases.set(self.phases.get() + 1);
+    }
+}
+
+/// Returns the phases of `events` in order, and checks that each of them
+/// ends with all its items done and that the remaining time is estimated
+/// once the first weighted phase ends.
+fn progress_phases<P: Copy + PartialEq>(events: &[Progress<P>]) -> Vec<P> {
+    let mut phases = Vec::new();
+    for (i, event) in events.iter().enumerate() {
+        if phases.last() != Some(&event.phase) {
+            phases.push(event.phase);
+        }
+        if events.get(i + 1).map(|next| next.phase) != Some(event.phase) {
+            assert_eq!(event.done, event.total);
+        }
+        if i > 0 {
+            assert!(event.elapsed >= events[i - 1].elapsed);
+        }
+    }
+    assert_eq!(events[0].remaining, None);
+    assert_eq!(events.last().unwrap().remaining, Some(Duration::ZERO));
+    phases
+}
+
+fn test_setup_with_progress<E>()
+where
+    E: Pairing,
+{
+    let seed = test_rng().next_u64();
+    let circuit = || MySillyCircuit { a: None, b: None };
+
+    let mut events = Vec::new();
+    let pk = Groth16::<E>::generate_random_parameters_with_progress(
+        circuit(),
+        &mut StdRng::seed_from_u64(seed),
+        1,
+        &mut |progress: &Progress<SetupPhase>| events.push(*progress),
+    )
+    .unwrap();
+    // Observing the setup does not change the key.
+    let expected = Groth16::<E>::generate_random_parameters_with_reduction(
+        circuit(),
+        &mut StdRng::seed_from_u64(seed),
+        1,
+        0,
+    )
+    .unwrap();
+    assert_eq!(pk, expected);
+
+    assert_eq!(
+        progress_phases(&events),
+        [
+            SetupPhase::Matrices,
+            SetupPhase::Evaluation,
+            SetupPhase::BG2Query,
+            SetupPhase::AQuery,
+            SetupPhase::BG1Query,
+            SetupPhase::HQuery,
+            SetupPhase::LQuery,
+            SetupPhase::VerifyingKey,
+        ]
+    );
+    let h_query = events
+        .iter()
+        .rfind(|event| event.phase == SetupPhase::HQuery)
+        .unwrap();
+    assert_eq!(h_query.total, pk.h_query.len());
+}
+
+fn test_prove_with_progress<E>()
+where
+    E: Pairing,
+{
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let pvk = prepare_verifying_key::<E>(&vk);
+
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let mut events = Vec::new();
+    let proof = Groth16::<E>::prove_with_progress(
+        &pk,
+        MySillyCircuit {
+            a: Some(a),
+            b: Some(b),
+        },
+        &mut rng,
+        &ProverConfig::default(),
+        &mut |progress: &Progress<ProverPhase>| events.push(*progress),
+    )
+    .unwrap();
+    assert!(Groth16::<E>::verify_with_processed_vk(&pvk, &[a * b], &proof).unwrap());
+
+    assert_eq!(
+        progress_phases(&events),
+        [
+            ProverPhase::Synthesis,
+            ProverPhase::WitnessMap,
+            ProverPhase::HMsm,
+            ProverPhase::LMsm,
+            ProverPhase::AMsm,
+            ProverPhase::BG1Msm,
+            ProverPhase::BG2Msm,
+            ProverPhase::Assembly,
+        ]
+    );
+}
+
+fn test_prove_with_report<E>()
+where
+    E: Pairing,
+{
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let pvk = prepare_verifying_key::<E>(&vk);
+
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let circuit = || MySillyCircuit {
+        a: Some(a),
+        b: Some(b),
+    };
+
+    let (proof, report) =
+        Groth16::<E>::prove_with_report(&pk, circuit(), &mut rng, &ProverConfig::default(), None)
+            .unwrap();
+    assert!(Groth16::<E>::verify_with_processed_vk(&pvk, &[a * b], &proof).unwrap());
+    assert_eq!(report.peak_memory(), None);
+    assert!(report.total_time() >= report.synthesis.time);
+
+    let stats = GrowingAllocatorStats::default();
+    let (proof, report) = Groth16::<E>::prove_with_report(
+        &pk,
+        circuit(),
+        &mut rng,
+        &ProverConfig::low_memory(),
+        Some(&stats),
+    )
+    .unwrap();
+    assert!(Groth16::<E>::verify_with_processed_vk(&pvk, &[a * b], &proof).unwrap());
+    assert_eq!(stats.phases.get(), 8);
+    let peaks = [
+        report.synthesis,
+        report.witness_map,
+        report.h_msm,
+        report.l_msm,
+        report.a_msm,
+        report.b_g1_msm,
+        report.b_g2_msm,
+        report.assembly,
+    ]
+    .map(|phase| phase.peak_memory.unwrap());
+    assert_eq!(peaks, [64, 128, 192, 256, 320, 384, 448, 512]);
+    assert_eq!(report.peak_memory(), Some(512));
+}
+
+fn test_offloaded_proof<E>()
+where
+    E: Pairing,
+{
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let pvk = prepare_verifying_key::<E>(&vk);
+
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let proof = Groth16::<E>::prove(
+        &pk,
+        MySillyCircuit {
+            a: Some(a),
+            b: Some(b),
+        },
+        &mut rng,
+    )
+    .unwrap();
+
+    let offloaded = OffloadedProof::new(&proof);
+    assert!(Groth16::<E>::verify_offloaded_unchecked(&pvk, &offloaded, &[], &[a * b]).unwrap());
+    assert!(!Groth16::<E>::verify_offloaded_unchecked(&pvk, &offloaded, &[], &[a]).unwrap());
+    assert!(Groth16::<E>::verify_offloaded_unchecked(&pvk, &offloaded, &[], &[]).is_err());
+
+    assert!(offloaded.is_prepared_from(&proof.b));
+    assert!(!offloaded.is_prepared_from(&(-proof.b.into_group()).into_affine()));
+
+    let bytes = offloaded.to_bytes().unwrap();
+    assert_eq!(bytes.len(), offloaded.calldata_size());
+    assert!(bytes.len() > proof.uncompressed_size());
+    let decoded = OffloadedProof::<E>::deserialize_uncompressed(&bytes[..]).unwrap();
+    assert!(decoded.is_prepared_from(&proof.b));
+    assert!(Groth16::<E>::verify_offloaded_unchecked(&pvk, &decoded, &[], &[a * b]).unwrap());
+
+    let lines = PreparedKeyLines::new(&pvk);
+    let mut bytes = Vec::new();
+    lines.serialize_uncompressed(&mut bytes).unwrap();
+    let decoded = PreparedKeyLines::<E>::deserialize_uncompressed(&bytes[..]).unwrap();
+    let mut reencoded = Vec::new();
+    decoded.serialize_uncompressed(&mut reencoded).unwrap();
+    assert_eq!(bytes, reencoded);
+}
+
+fn test_verifying_key_commitments<E>()
+where
+    E: Pairing,
+{
+    use crate::hash::keccak256;
+    use crate::onchain::{
+        borsh_encode_verifying_key, evm_encode_verifying_key, evm_verifying_key_commitment,
+        solana_verifying_key_commitment,
+    };
+    use sha2::{Digest, Sha256};
+
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let hex = |hash: [u8; 32]| {
+        hash.iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect::<String>()
+    };
+    assert_eq!(
+        hex(keccak256(b"")),
+        "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+    );
+    assert_eq!(
+        hex(keccak256(b"abc")),
+        "4e03657aea45a94fc7d47ba826c8d667c0d1e6e33a64a036ec44f58fa12d6c45"
+    );
+    // Exactly the 136-byte rate, padded into a second block, and past it.
+    assert_eq!(
+        hex(keccak256(&[b'a'; 136])),
+        "a6c4d403279fe3e0af03729caada8374b5ca54d8065329a3ebcaeb4b60aa386e"
+    );
+    assert_eq!(
+        hex(keccak256(&(0..200).map(|i| i as u8).collect::<Vec<_>>())),
+        "bfb0aa97863e797943cf7c33bb7e880bb4543f3d2703c0923c6901c2af57b890"
+    );
+
+    let (_, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    if ProofWireFormat::element_size::<E>() != 32 {
+        assert!(borsh_encode_verifying_key(&vk).is_err());
+        assert!(solana_verifying_key_commitment(&vk).is_err());
+        assert!(evm_encode_verifying_key(&vk).is_err());
+        return;
+    }
+    let num_ic = vk.gamma_abc_g1_static.len() + vk.gamma_abc_g1_variable.len();
+    let g1_size = 2 * ProofWireFormat::element_size::<E>();
+    let g2_size = ProofWireFormat::proof_size::<E>() - 2 * g1_size;
+
+    let borsh = borsh_encode_verifying_key(&vk).unwrap();
+    assert_eq!(borsh.len(), g1_size + 3 * g2_size + 4 + num_ic * g1_size);
+    let ic_offset = g1_size + 3 * g2_size;
+    assert_eq!(
+        borsh[ic_offset..ic_offset + 4],
+        (num_ic as u32).to_le_bytes()
+    );
+    assert_eq!(
+        borsh[..g1_size],
+        ProofWireFormat::EIP197.encode_proof(&Proof::<E> {
+            a: vk.alpha_g1,
+            b: vk.beta_g2,
+            c: vk.alpha_g1,
+        })[..g1_size]
+    );
+    let commitment = solana_verifying_key_commitment(&vk).unwrap();
+    assert_eq!(commitment, <[u8; 32]>::from(Sha256::digest(&borsh)));
+
+    let abi = evm_encode_verifying_key(&vk).unwrap();
+    assert_eq!(abi.len(), 32 * (16 + 2 * num_ic));
+    // The head holds the points, then the offset of `ic`, and the tail
+    // its length and its points.
+    assert_eq!(abi[..ic_offset], borsh[..ic_offset]);
+    let word = |i: usize| {
+        assert!(abi[i..i + 24].iter().all(|byte| *byte == 0));
+        u64::from_be_bytes(abi[i + 24..i + 32].try_into().unwrap()) as usize
+    };
+    assert_eq!(word(ic_offset), 15 * 32);
+    assert_eq!(word(ic_offset + 32), num_ic);
+    assert_eq!(abi[ic_offset + 64..], borsh[ic_offset + 4..]);
+    assert_eq!(evm_verifying_key_commitment(&vk).unwrap(), keccak256(&abi));
+
+    // Keys with different `IC` points have different commitments, but the
+    // partition into static and variable inputs is not committed to.
+    let mut other = vk.clone();
+    other.gamma_abc_g1_variable[0] = vk.alpha_g1;
+    assert_ne!(solana_verifying_key_commitment(&other).unwrap(), commitment);
+    let mut repartitioned = vk.clone();
+    repartitioned
+        .gamma_abc_g1_static
+        .append(&mut repartitioned.gamma_abc_g1_variable);
+    assert_eq!(
+        solana_verifying_key_commitment(&repartitioned).unwrap(),
+        commitment
+    );
+}
+
+fn test_proof_wire_format<E>()
+where
+    E: Pairing,
+{
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let proof = Groth16::<E>::prove(
+        &pk,
+        MySillyCircuit {
+            a: Some(a),
+            b: Some(b),
+        },
+        &mut rng,
+    )
+    .unwrap();
+
+    let size = ProofWireFormat::element_size::<E>();
+    let little_endian = ProofWireFormat {
+        negate_a: false,
+        g2_order: G2CoordinateOrder::C0First,
+        endianness: Endianness::Little,
+    };
+    for format in [
+        ProofWireFormat::EIP197,
+        ProofWireFormat::EIP197_NEGATED_A,
+        little_endian,
+    ] {
+        let bytes = format.encode_proof(&proof);
+        assert_eq!(bytes.len(), ProofWireFormat::proof_size::<E>());
+        let decoded = format.decode_proof::<E>(&bytes).unwrap();
+        assert_eq!(decoded, proof);
+        assert!(Groth16::<E>::verify(&vk, &[a * b], &decoded).unwrap());
+
+        assert!(format.decode_proof::<E>(&bytes[1..]).is_err());
+        let mut non_canonical = bytes.clone();
+        non_canonical[..size].fill(0xff);
+        assert!(format.decode_proof::<E>(&non_canonical).is_err());
+        let mut off_curve = bytes.clone();
+        off_curve[size] ^= 1;
+        assert!(format.decode_proof::<E>(&off_curve).is_err());
+    }
+
+    // The EIP-197 layout: big endian coordinates, with `c1` first in `G2`.
+    let bytes = ProofWireFormat::EIP197.encode_proof(&proof);
+    fn big_endian<F: PrimeField>(element: F, size: usize) -> Vec<u8> {
+        use ark_ff::BigInteger;
+        let bytes = element.into_bigint().to_bytes_be();
+        bytes[bytes.len() - size..].to_vec()
+    }
+    let (x, y) = proof.a.xy().unwrap();
+    let coordinates: Vec<_> = x
+        .to_base_prime_field_elements()
+        .chain(y.to_base_prime_field_elements())
+        .collect();
+    assert_eq!(bytes[..size], big_endian(coordinates[0], size));
+    assert_eq!(bytes[size..2 * size], big_endian(coordinates[1], size));
+    let (bx, _) = proof.b.xy().unwrap();
+    let bx: Vec<_> = bx.to_base_prime_field_elements().collect();
+    assert_eq!(bytes[2 * size..3 * size], big_endian(bx[1], size));
+    assert_eq!(bytes[3 * size..4 * size], big_endian(bx[0], size));
+
+    // A negated `A` is only told apart by the verifier.
+    let negated = ProofWireFormat::EIP197_NEGATED_A.encode_proof(&proof);
+    assert_eq!(bytes[..size], negated[..size]);
+    assert_ne!(bytes, negated);
+    let misread = ProofWireFormat::EIP197.decode_proof::<E>(&negated).unwrap();
+    assert_eq!(misread.a, (-proof.a.into_group()).into_affine());
+    assert!(!Groth16::<E>::verify(&vk, &[a * b], &misread).unwrap());
+
+    // The point at infinity is encoded as zeros.
+    let zero_c = Proof {
+        c: E::G1Affine::zero(),
+        ..proof.clone()
+    };
+    let bytes = ProofWireFormat::EIP197.encode_proof(&zero_c);
+    assert!(bytes[bytes.len() - 2 * size..]
+        .iter()
+        .all(|byte| *byte == 0));
+    assert_eq!(
+        ProofWireFormat::EIP197.decode_proof::<E>(&bytes).unwrap(),
+        zero_c
+    );
+}
+
+#[cfg(feature = "parallel")]
+fn test_deserialize_parallel<E>()
+where
+    E: Pairing,
+{
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+    let (pk, _) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+
+    for compress in [Compress::Yes, Compress::No] {
+        let mut bytes = Vec::new();
+        pk.serialize_with_mode(&mut bytes, compress).unwrap();
+        let decoded =
+            ProvingKey::<E>::deserialize_parallel(&bytes[..], compress, Validate::Yes).unwrap();
+        assert_eq!(decoded, pk);
+        // Queries spanning several chunks.
+        for chunk_size in [1, 2, 3] {
+            let decoded = ProvingKey::<E>::deserialize_parallel_in_chunks(
+                &bytes[..],
+                compress,
+                Validate::Yes,
+                chunk_size,
+            )
+            .unwrap();
+            assert_eq!(decoded, pk);
+        }
+
+        // Dropping the basis tag leaves a legacy key, but not the last byte
+        // of `l_query`.
+        assert!(ProvingKey::<E>::deserialize_parallel(
+            &bytes[..bytes.len() - 2],
+            compress,
+            Validate::Yes
+        )
+        .is_err());
+        // A point of `l_query` with another `x`, which is rejected like the
+        // sequential deserialization does, and always when uncompressed.
+        let mut corrupted = bytes.clone();
+        let point_size = E::G1Affine::zero().serialized_size(compress);
+        corrupted[bytes.len() - point_size] ^= 1;
+        let parallel =
+            ProvingKey::<E>::deserialize_parallel(&corrupted[..], compress, Validate::Yes);
+        let sequential =
+            ProvingKey::<E>::deserialize_with_mode(&corrupted[..], compress, Validate::Yes);
+        assert_eq!(parallel.is_err(), sequential.is_err());
+        assert!(parallel.is_err() || compress == Compress::Yes);
+    }
+}
+
+fn test_verifying_key_schema<E>()
+where
+    E: Pairing,
+{
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+    let circuit = InputsCircuit::<E::ScalarField> {
+        inputs: vec![None; 3],
+    };
+    let (pk, ck) =
+        Groth16::<E>::generate_random_parameters_with_commitment(circuit, &mut rng, 2, 0, 1)
+            .unwrap();
+    let vk = pk.vk;
+
+    let schema = VerifyingKeyWithSchema::with_committed_inputs(
+        vk.clone(),
+        ck.bases.len(),
+        "OrderInputs",
+        &["pool", "amount", "order_id"],
+        Endianness::Big,
+    )
+    .unwrap();
+    assert_eq!(
+        schema
+            .inputs()
+            .iter()
+            .map(|input| input.kind)
+            .collect::<Vec<_>>(),
+        [InputKind::Static, InputKind::Variable, InputKind::Committed]
+    );
+
+    let size = PublicInputs::<E>::element_size();
+    let json = schema.json_schema();
+    assert!(json.contains("\"title\": \"OrderInputs\""));
+    assert!(json.contains(&format!("\"pattern\": \"^0x[0-9a-fA-F]{{{}}}$\"", 2 * size)));
+    assert!(json.contains("\"x-kind\": \"variable\",\n      \"x-index\": 1\n"));
+    assert!(json.contains("\"required\": [\"pool\", \"amount\", \"order_id\"]"));
+    let typescript = schema.typescript();
+    assert!(typescript.contains("export interface OrderInputs {"));
+    assert!(typescript.contains("  order_id: string;"));
+    assert!(typescript.contains(
+        "  static: [\"pool\"],\n  variable: [\"amount\"],\n  committed: [\"order_id\"],"
+    ));
+    assert!(typescript.contains(&format!("elementSize: {},", size)));
+    assert!(typescript.contains("endianness: \"big\","));
+
+    let default =
+        VerifyingKeyWithSchema::with_default_names(vk.clone(), "Inputs", Endianness::Little)
+            .unwrap();
+    assert_eq!(
+        default
+            .inputs()
+            .iter()
+            .map(|input| input.name.as_str())
+            .collect::<Vec<_>>(),
+        ["input_1", "input_2"]
+    );
+    assert!(default.typescript().contains("endianness: \"little\","));
+
+    assert_eq!(
+        VerifyingKeyWithSchema::new(vk.clone(), "Inputs", &["a"], Endianness::Big),
+        Err(SchemaError::NameCount {
+            expected: 2,
+            actual: 1
+        })
+    );
+    assert_eq!(
+        VerifyingKeyWithSchema::new(vk.clone(), "Inputs", &["a", "1c"], Endianness::Big),
+        Err(SchemaError::InvalidName("1c".into()))
+    );
+    assert_eq!(
+        VerifyingKeyWithSchema::new(vk.clone(), "Inputs", &["a", "b\""], Endianness::Big),
+        Err(SchemaError::InvalidName("b\"".into()))
+    );
+    assert_eq!(
+        VerifyingKeyWithSchema::new(vk.clone(), "Inputs", &["a", "a"], Endianness::Big),
+        Err(SchemaError::DuplicateName("a".into()))
+    );
+    assert_eq!(
+        VerifyingKeyWithSchema::new(vk, "My Inputs", &["a", "b"], Endianness::Big),
+        Err(SchemaError::InvalidName("My Inputs".into()))
+    );
+}
+
+fn test_versioned<E>()
+where
+    E: Pairing,
+{
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+    let (v1, v2) = (CircuitVersion(1), CircuitVersion(2));
+
+    let blank = || MySillyCircuit { a: None, b: None };
+    let pk1 = Groth16::<E>::setup_versioned(blank(), v1, 0, &mut rng).unwrap();
+    let pk2 = Groth16::<E>::setup_versioned(blank(), v2, 0, &mut rng).unwrap();
+    let pvk1 = prepare_verifying_key::<E>(&pk1.vk);
+    let pvk2 = prepare_verifying_key::<E>(&pk2.vk);
+    assert_eq!(pvk1.num_static_inputs(), 1);
+    assert_eq!(pvk1.num_variable_inputs(), 1);
+
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let circuit = || MySillyCircuit {
+        a: Some(a),
+        b: Some(b),
+    };
+    let proof1 = Groth16::<E>::prove_versioned(&pk1, v1, circuit(), &mut rng).unwrap();
+    let proof2 = Groth16::<E>::prove_versioned(&pk2, v2, circuit(), &mut rng).unwrap();
+
+    assert!(Groth16::<E>::verify_versioned(&pvk1, v1, &proof1, &[], &[a * b]).unwrap());
+    assert!(Groth16::<E>::verify_versioned(&pvk2, v2, &proof2, &[], &[a * b]).unwrap());
+    assert!(!Groth16::<E>::verify_versioned(&pvk1, v1, &proof1, &[], &[a]).unwrap());
+
+    // Proofs do not replay across versions, whichever version is expected.
+    assert!(!Groth16::<E>::verify_versioned(&pvk2, v2, &proof1, &[], &[a * b]).unwrap());
+    assert!(!Groth16::<E>::verify_versioned(&pvk2, v1, &proof1, &[], &[a * b]).unwrap());
+    assert!(!Groth16::<E>::verify_versioned(&pvk1, v2, &proof1, &[], &[a * b]).unwrap());
+
+    // A key without static inputs cannot hold a version.
+    let (_, vk) = Groth16::<E>::setup(blank(), &mut rng).unwrap();
+    let pvk = prepare_verifying_key::<E>(&vk);
+    assert!(Groth16::<E>::verify_versioned(&pvk, v1, &proof1, &[], &[a * b]).is_err());
+
+    let inputs = Groth16::<E>::public_inputs(VersionedCircuit::new(v2, circuit())).unwrap();
+    assert_eq!(input_version(&inputs), Some(v2));
+    assert_eq!(
+        input_version::<E::ScalarField>(&[-E::ScalarField::one()]),
+        None
+    );
+    assert_eq!(input_version::<E::ScalarField>(&[]), None);
+}
+
+fn test_verify_stream<E>()
+where
+    E: Pairing,
+{
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let pvk = prepare_verifying_key::<E>(&vk);
+
+    let mut bytes = Vec::new();
+    let mut expected = Vec::new();
+    for i in 0..100 {
+        let a = E::ScalarField::rand(&mut rng);
+        let b = E::ScalarField::rand(&mut rng);
+        let proof = Groth16::<E>::prove(
+            &pk,
+            MySillyCircuit {
+                a: Some(a),
+                b: Some(b),
+            },
+            &mut rng,
+        )
+        .unwrap();
+        let valid = i % 3 != 0;
+        let inputs = if valid { a * b } else { a };
+        write_stream_record(&mut bytes, &proof, &[inputs]).unwrap();
+        expected.push(valid);
+    }
+
+    let proof = Groth16::<E>::prove(
+        &pk,
+        MySillyCircuit {
+            a: Some(E::ScalarField::one()),
+            b: Some(E::ScalarField::one()),
+        },
+        &mut rng,
+    )
+    .unwrap();
+    // A record with the wrong number of inputs.
+    write_stream_record(&mut bytes, &proof, &[]).unwrap();
+    // A record that is not a proof.
+    bytes.extend_from_slice(&3u64.to_le_bytes());
+    bytes.extend_from_slice(&[1, 2, 3]);
+    // A truncated record.
+    bytes.extend_from_slice(&100u64.to_le_bytes());
+    bytes.extend_from_slice(&[0; 10]);
+
+    let mut results = Groth16::<E>::verify_stream(&pvk, std::io::Cursor::new(bytes));
+    for valid in expected {
+        assert_eq!(results.next().unwrap().unwrap(), valid);
+    }
+    assert!(matches!(
+        results.next(),
+        Some(Err(StreamError::Synthesis(_)))
+    ));
+    assert!(matches!(
+        results.next(),
+        Some(Err(StreamError::Serialization(_)))
+    ));
+    assert!(matches!(
+        results.next(),
+        Some(Err(StreamError::Serialization(_)))
+    ));
+    assert!(results.next().is_none());
+}
+
+fn test_legacy_verifying_key<E>()
+where
+    E: Pairing,
+{
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let legacy = LegacyVerifyingKey::<E> {
+        alpha_g1: vk.alpha_g1,
+        beta_g2: vk.beta_g2,
+        gamma_g2: vk.gamma_g2,
+        delta_g2: vk.delta_g2,
+        gamma_abc_g1: [
+            vk.gamma_abc_g1_static.clone(),
+            vk.gamma_abc_g1_variable.clone(),
+        ]
+        .concat(),
+    };
+
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let proof = Groth16::<E>::prove(
+        &pk,
+        MySillyCircuit {
+            a: Some(a),
+            b: Some(b),
+        },
+        &mut rng,
+    )
+    .unwrap();
+
+    assert_eq!(legacy.clone().into_split(1).unwrap(), vk);
+    assert!(legacy.clone().into_split(0).is_err());
+    assert!(legacy.clone().into_split(3).is_err());
+
+    // All inputs static, with an empty set of variable inputs.
+    let pvk = prepare_verifying_key(&legacy.clone().into_split(2).unwrap());
+    assert!(pvk.vk.gamma_abc_g1_variable.is_empty());
+    assert!(Groth16::<E>::verify_with_variables(&pvk, &proof, &[a * b], &[]).unwrap());
+    assert!(Groth16::<E>::verify_with_processed_vk(&pvk, &[a * b], &proof).unwrap());
+
+    let mut split_bytes = Vec::new();
+    vk.serialize_compressed(&mut split_bytes).unwrap();
+    let mut legacy_bytes = Vec::new();
+    legacy.serialize_uncompressed(&mut legacy_bytes).unwrap();
+    for (bytes, is_legacy) in [(split_bytes, false), (legacy_bytes, true)] {
+        let any = load_any_verifying_key::<E>(&bytes).unwrap();
+        assert_eq!(matches!(any, AnyVerifyingKey::Legacy(_)), is_legacy);
+        assert!(any.verify(&[a * b], &proof).unwrap());
+        assert!(!any.verify(&[a], &proof).unwrap());
+    }
+}
+
+fn test_cached_verifier<E>()
+where
+    E: Pairing,
+{
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let mut verifier = CachedVerifier::new(prepare_verifying_key::<E>(&vk), 2);
+
+    let mut statements = Vec::new();
+    for _ in 0..3 {
+        let a = E::ScalarField::rand(&mut rng);
+        let b = E::ScalarField::rand(&mut rng);
+        let proof = Groth16::<E>::prove(
+            &pk,
+            MySillyCircuit {
+                a: Some(a),
+                b: Some(b),
+            },
+            &mut rng,
+        )
+        .unwrap();
+        statements.push((proof, a * b));
+    }
+
+    let (proof_0, c_0) = &statements[0];
+    assert!(verifier
+        .verify_with_variables(proof_0, &[], &[*c_0])
+        .unwrap());
+    assert!(verifier
+        .verify_with_variables(proof_0, &[], &[*c_0])
+        .unwrap());
+    assert_eq!(verifier.cache().len(), 1);
+
+    // A wrong statement is cached too, and still rejected.
+    let (proof_1, _) = &statements[1];
+    assert!(!verifier
+        .verify_with_variables(proof_1, &[], &[*c_0])
+        .unwrap());
+
+    // Filling the cache evicts the least recently used inputs.
+    let (proof_1, c_1) = &statements[1];
+    let (proof_2, c_2) = &statements[2];
+    assert!(verifier
+        .verify_with_variables(proof_1, &[], &[*c_1])
+        .unwrap());
+    assert!(verifier
+        .verify_with_variables(proof_2, &[], &[*c_2])
+        .unwrap());
+    assert_eq!(verifier.cache().len(), 2);
+    let mut cache = verifier.cache().clone();
+    assert!(cache.get(&[], &[*c_0]).is_none());
+    assert!(cache.get(&[], &[*c_1]).is_some());
+
+    assert!(verifier
+        .verify_with_variables(proof_0, &[*c_0], &[])
+        .is_err());
+}
+
+fn test_shared_verifier<E>()
+where
+    E: Pairing,
+{
+    use crate::cache::SharedVerifier;
+    use std::{sync::Arc, thread};
+
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let window = Duration::from_secs(60);
+    let verifier = Arc::new(
+        SharedVerifier::new(prepare_verifying_key::<E>(&vk), 2)
+            .with_replay_guard(ReplayGuard::new(16, window)),
+    );
+
+    let mut statements = Vec::new();
+    for _ in 0..3 {
+        let a = E::ScalarField::rand(&mut rng);
+        let b = E::ScalarField::rand(&mut rng);
+        let proof = Groth16::<E>::prove(
+            &pk,
+            MySillyCircuit {
+                a: Some(a),
+                b: Some(b),
+            },
+            &mut rng,
+        )
+        .unwrap();
+        statements.push((proof, a * b));
+    }
+    let statements = Arc::new(statements);
+
+    // Threads share the verifier and its cache.
+    let handles: Vec<_> = (0..8)
+        .map(|i| {
+            let verifier = verifier.clone();
+            let statements = statements.clone();
+            thread::spawn(move || {
+                let (proof, c) = &statements[i % 2];
+                verifier.verify_with_variables(proof, &[], &[*c]).unwrap()
+            })
+        })
+        .collect();
+    assert!(handles.into_iter().all(|handle| handle.join().unwrap()));
+    assert_eq!(verifier.cache().len(), 2);
+
+    // Filling the cache evicts the least recently used inputs.
+    let (proof_2, c_2) = &statements[2];
+    assert!(verifier.cache().get(&[], &[statements[0].1]).is_some());
+    assert!(verifier
+        .verify_with_variables(proof_2, &[], &[*c_2])
+        .unwrap());
+    assert_eq!(verifier.cache().len(), 2);
+    assert!(verifier.cache().get(&[], &[statements[0].1]).is_some());
+    assert!(verifier.cache().get(&[], &[statements[1].1]).is_none());
+
+    // A proof submitted concurrently is accepted exactly once.
+    let t = Duration::from_secs(1_000);
+    let handles: Vec<_> = (0..8)
+        .map(|_| {
+            let verifier = verifier.clone();
+            let statements = statements.clone();
+            thread::spawn(move || {
+                let (proof, c) = &statements[0];
+                verifier.verify_once_at(proof, &[], &[*c], t)
+            })
+        })
+        .collect();
+    let results: Vec<_> = handles
+        .into_iter()
+        .map(|handle| handle.join().unwrap())
+        .collect();
+    assert_eq!(results.iter().filter(|r| **r == Ok(true)).count(), 1);
+    assert!(results
+        .iter()
+        .all(|r| *r == Ok(true) || *r == Err(ReplayError::Replayed)));
+    assert_eq!(verifier.replay_guard_len(), Some(1));
+
+    // An invalid proof is not recorded.
+    let (proof_1, _) = &statements[1];
+    assert!(!verifier.verify_once_at(proof_1, &[], &[*c_2], t).unwrap());
+    assert_eq!(verifier.replay_guard_len(), Some(1));
+    verifier.cache().clear();
+    assert!(verifier.cache().is_empty());
+
+    // Concurrent insertions into copies of the snapshot are all kept.
+    let cache = crate::cache::SharedInputCache::<E>::new(64);
+    let prepared = E::G1::rand(&mut rng);
+    thread::scope(|scope| {
+        for i in 0..8u64 {
+            let cache = &cache;
+            scope.spawn(move || {
+                for j in 0..4u64 {
+                    let input = E::ScalarField::from(4 * i + j);
+                    cache.insert(&[], &[input], prepared);
+                    assert_eq!(cache.get(&[], &[input]), Some(prepared));
+                }
+            });
+        }
+    });
+    assert_eq!(cache.len(), 32);
+}
+
+#[cfg(feature = "zkvm")]
+fn test_verify_with_hook<E>()
+where
+    E: Pairing,
+{
+    use crate::zkvm::NativePairing;
+    use ark_ec::pairing::PairingOutput;
+    use ark_std::cell::Cell;
+
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let inputs = vec![
+        E::ScalarField::rand(&mut rng),
+        E::ScalarField::rand(&mut rng),
+    ];
+    let circuit = InputsCircuit {
+        inputs: inputs.iter().copied().map(Some).collect(),
+    };
+    let pk =
+        Groth16::<E>::generate_random_parameters_with_reduction(circuit.clone(), &mut rng, 2, 0)
+            .unwrap();
+    let vk = pk.vk.clone();
+    let proof = Groth16::<E>::prove(&pk, circuit, &mut rng).unwrap();
+    let (static_inputs, variable_inputs) = inputs.split_at(1);
+
+    assert!(Groth16::<E>::verify_with_hook(
+        &vk,
+        &proof,
+        static_inputs,
+        variable_inputs,
+        &NativePairing
+    )
+    .unwrap());
+    assert!(!Groth16::<E>::verify_with_hook(
+        &vk,
+        &proof,
+        variable_inputs,
+        static_inputs,
+        &NativePairing
+    )
+    .unwrap());
+
+    // The pairings are computed by a single call to the hook.
+    let calls = Cell::new(0);
+    let hook = |g1: &[E::G1Affine], g2: &[E::G2Affine]| -> PairingOutput<E> {
+        calls.set(calls.get() + 1);
+        E::multi_pairing(g1.iter().copied(), g2.iter().copied())
+    };
+    assert!(
+        Groth16::<E>::verify_with_hook(&vk, &proof, static_inputs, variable_inputs, &hook).unwrap()
+    );
+    assert_eq!(calls.get(), 1);
+
+    assert!(Groth16::<E>::verify_with_hook(&vk, &proof, &inputs, &[], &NativePairing).is_err());
+    assert!(Groth16::<E>::verify_with_hook(
+        &VerifyingKey::default(),
+        &proof,
+        &[],
+        &[],
+        &NativePairing
+    )
+    .is_err());
+}
+
+fn test_statement_encoding<E>()
+where
+    E: Pairing,
+{
+    use crate::statement::{PublicInputValue, ToPublicInputs};
+
+    struct Order {
+        pool_id: u32,
+        amount_in: u64,
+        hash: [u8; 32],
+    }
+
+    impl<F: PrimeField> ToPublicInputs<F> for Order {
+        const NUM_STATIC_INPUTS: usize = 1;
+        const NUM_VARIABLE_INPUTS: usize = 1 + <[u8; 32] as PublicInputValue<F>>::NUM_ELEMENTS;
+
+        fn append_static_inputs(&self, inputs: &mut Vec<F>) {
+            self.pool_id.append_to(inputs);
+        }
+
+        fn append_variable_inputs(&self, inputs: &mut Vec<F>) {
+            self.amount_in.append_to(inputs);
+            self.hash.append_to(inputs);
+        }
+    }
+
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let order = Order {
+        pool_id: 3,
+        amount_in: u64::MAX,
+        hash: [0xab; 32],
+    };
+    let inputs: Vec<E::ScalarField> = order.public_inputs();
+    assert_eq!(inputs.len(), 4);
+    assert_eq!(inputs[..2], [3u64.into(), u64::MAX.into()]);
+    assert!(check_split_hash(&inputs[2..], &order.hash));
+
+    let circuit = InputsCircuit {
+        inputs: inputs.iter().copied().map(Some).collect(),
+    };
+    let pk = Groth16::<E>::generate_random_parameters_with_reduction(
+        InputsCircuit {
+            inputs: vec![None; 4],
+        },
+        &mut rng,
+        2,
+        3,
+    )
+    .unwrap();
+    let pvk = prepare_verifying_key::<E>(&pk.vk);
+    let proof = Groth16::<E>::prove(&pk, circuit, &mut rng).unwrap();
+    assert!(Groth16::<E>::verify_statement(&pvk, &proof, &order).unwrap());
+
+    let other = Order {
+        amount_in: 1,
+        ..order
+    };
+    assert!(!Groth16::<E>::verify_statement(&pvk, &proof, &other).unwrap());
+}
+
+fn test_prover_context<E>()
+where
+    E: Pairing,
+{
+    use crate::context::ProverContext;
+    use crate::msm::BigInt;
+
+    struct Accelerator;
+
+    impl<G: VariableBaseMSM> MsmBackend<G> for Accelerator {
+        fn msm_bigint(&self, bases: &[G::MulBase], scalars: &[BigInt<G>]) -> G {
+            G::msm_bigint(bases, scalars)
+        }
+
+        fn is_accelerated(&self) -> bool {
+            true
+        }
+    }
+
+    struct Faulty;
+
+    impl<G: VariableBaseMSM> MsmBackend<G> for Faulty {
+        fn msm_bigint(&self, _: &[G::MulBase], _: &[BigInt<G>]) -> G {
+            G::zero()
+        }
+    }
+
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let context = ProverContext::new();
+    assert!(!context.is_warm());
+    let report = context.warm_up(&pk).unwrap();
+    assert!(report.dry_proof_verified);
+    assert!(context.is_warm());
+    assert!(report.key_bytes >= pk.a_query.len() * ark_std::mem::size_of::<E::G1Affine>());
+
+    let capabilities = context.capabilities::<E>();
+    assert!(capabilities.threads >= 1);
+    assert!(!capabilities.accelerated);
+    assert_eq!(capabilities.glv, cfg!(feature = "glv"));
+
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let circuit = MySillyCircuit {
+        a: Some(a),
+        b: Some(b),
+    };
+    let proof = context.prove(&pk, circuit, &mut rng).unwrap();
+    assert!(Groth16::<E>::verify(&vk, &[a * b], &proof).unwrap());
+
+    let accelerated = ProverContext::with_backend(HybridMsm::new(CpuMsm, Accelerator))
+        .with_config(ProverConfig::low_memory());
+    assert!(accelerated.capabilities::<E>().accelerated);
+    assert!(accelerated.config().low_memory);
+
+    // A faulty backend fails the dry proof and leaves the context cold.
+    let faulty = ProverContext::with_backend(Faulty);
+    assert!(!faulty.warm_up(&pk).unwrap().dry_proof_verified);
+    assert!(!faulty.is_warm());
+}
+
+fn test_replay_guard<E>()
+where
+    E: Pairing,
+{
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let window = Duration::from_secs(60);
+    let mut verifier = CachedVerifier::new(prepare_verifying_key::<E>(&vk), 2)
+        .with_replay_guard(ReplayGuard::new(2, window));
+
+    let mut statements = Vec::new();
+    for _ in 0..3 {
+        let a = E::ScalarField::rand(&mut rng);
+        let b = E::ScalarField::rand(&mut rng);
+        let proof = Groth16::<E>::prove(
+            &pk,
+            MySillyCircuit {
+                a: Some(a),
+                b: Some(b),
+            },
+            &mut rng,
+        )
+        .unwrap();
+        statements.push((proof, a * b));
+    }
+    let (proof_0, c_0) = &statements[0];
+    let (proof_1, c_1) = &statements[1];
+    let (proof_2, c_2) = &statements[2];
+    let t = Duration::from_secs(1_000);
+
+    // An invalid proof is not recorded.
+    assert!(!verifier.verify_once_at(proof_0, &[], &[*c_1], t).unwrap());
+    assert!(verifier.replay_guard().unwrap().is_empty());
+
+    assert!(verifier.verify_once_at(proof_0, &[], &[*c_0], t).unwrap());
+    assert_eq!(
+        verifier.verify_once_at(proof_0, &[], &[*c_0], t + window / 2),
+        Err(ReplayError::Replayed)
+    );
+    // A rerandomized proof is a different proof.
+    let rerandomized = Groth16::<E>::rerandomize_proof(&vk, proof_0, &mut rng);
+    assert!(verifier
+        .verify_once_at(&rerandomized, &[], &[*c_0], t)
+        .unwrap());
+
+    // The proof is accepted again once the window has elapsed.
+    assert!(verifier
+        .verify_once_at(proof_0, &[], &[*c_0], t + window)
+        .unwrap());
+
+    // Once full, the guard forgets the oldest proofs.
+    let later = t + window * 3;
+    assert!(verifier
+        .verify_once_at(proof_1, &[], &[*c_1], later)
+        .unwrap());
+    assert!(verifier
+        .verify_once_at(proof_2, &[], &[*c_2], later + Duration::from_secs(1))
+        .unwrap());
+    assert_eq!(verifier.replay_guard().unwrap().len(), 2);
+    assert!(verifier
+        .verify_once_at(proof_1, &[], &[*c_1], later)
+        .is_err());
+    assert!(verifier
+        .verify_once_at(proof_0, &[], &[*c_0], later)
+        .unwrap());
+    assert!(verifier
+        .verify_once_at(proof_1, &[], &[*c_1], later)
+        .unwrap());
+
+    assert!(matches!(
+        verifier.verify_once_at(proof_0, &[*c_0], &[], later),
+        Err(ReplayError::Synthesis(_))
+    ));
+}
+
+fn test_aggregate_proof_points<E>()
+where
+    E: Pairing,
+{
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, _) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let proofs = (0..3)
+        .map(|_| {
+            Groth16::<E>::prove(
+                &pk,
+                MySillyCircuit {
+                    a: Some(E::ScalarField::rand(&mut rng)),
+                    b: Some(E::ScalarField::rand(&mut rng)),
+                },
+                &mut rng,
+            )
+            .unwrap()
+        })
+        .collect::<Vec<_>>();
+    let coeffs = (0..3)
+        .map(|_| E::ScalarField::rand(&mut rng))
+        .collect::<Vec<_>>();
+
+    let (a, b, c) = aggregate_proof_points(&proofs, &coeffs);
+    let expected = proofs.iter().zip(&coeffs).fold(
+        (E::G1::zero(), E::G2::zero(), E::G1::zero()),
+        |(a, b, c), (proof, coeff)| {
+            (
+                a + proof.a * coeff,
+                b + proof.b * coeff,
+                c + proof.c * coeff,
+            )
+        },
+    );
+    assert_eq!((a, b, c), expected);
+}
+
+fn test_circuit_templates<E>()
+where
+    E: Pairing,
+{
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+    let mimc = MiMC::<E::ScalarField>::random(10, &mut rng);
+    let is_satisfied = |circuit: RangeCircuit<E::ScalarField>| {
+        let cs = ConstraintSystem::new_ref();
+        circuit.generate_constraints(cs.clone()).unwrap();
+        cs.is_satisfied().unwrap()
+    };
+
+    // Preimage of a hash.
+    let (pk, pvk) =
+        circuits::setup::<E, _>(PreimageCircuit::blank(mimc.clone()), &mut rng).unwrap();
+    let preimage = (
+        E::ScalarField::rand(&mut rng),
+        E::ScalarField::rand(&mut rng),
+    );
+    let (proof, inputs) =
+        circuits::prove(&pk, PreimageCircuit::new(mimc.clone(), preimage), &mut rng).unwrap();
+    assert_eq!(inputs, vec![mimc.hash(preimage.0, preimage.1)]);
+    assert!(circuits::verify(&pvk, &inputs, &proof).unwrap());
+    assert!(!circuits::verify(&pvk, &[preimage.0], &proof).unwrap());
+
+    // Merkle membership.
+    let (pk, pvk) =
+        circuits::setup::<E, _>(MerkleMembershipCircuit::blank(mimc.clone(), 3), &mut rng).unwrap();
+    let leaf = E::ScalarField::rand(&mut rng);
+    let path = vec![
+        (E::ScalarField::rand(&mut rng), true),
+        (E::ScalarField::rand(&mut rng), false),
+        (E::ScalarField::rand(&mut rng), true),
+    ];
+    let root = MerkleMembershipCircuit::root(&mimc, leaf, &path);
+    let (proof, inputs) = circuits::prove(
+        &pk,
+        MerkleMembershipCircuit::new(mimc.clone(), leaf, path),
+        &mut rng,
+    )
+    .unwrap();
+    assert_eq!(inputs, vec![root]);
+    assert!(circuits::verify(&pvk, &inputs, &proof).unwrap());
+    assert!(!circuits::verify(&pvk, &[leaf], &proof).unwrap());
+
+    // Nullifier of a member, with the root as a static input.
+    let (pk, pvk) =
+        circuits::setup::<E, _>(NullifierCircuit::blank(mimc.clone(), 2), &mut rng).unwrap();
+    assert_eq!(pvk.vk.gamma_abc_g1_static.len(), 2);
+    assert_eq!(pvk.vk.gamma_abc_g1_variable.len(), 2);
+    let secret = E::ScalarField::rand(&mut rng);
+    let topic = E::ScalarField::rand(&mut rng);
+    let path = vec![
+        (E::ScalarField::rand(&mut rng), false),
+        (E::ScalarField::rand(&mut rng), true),
+    ];
+    let leaf = NullifierCircuit::leaf(&mimc, secret);
+    let root = MerkleMembershipCircuit::root(&mimc, leaf, &path);
+    let nullifier = NullifierCircuit::nullifier(&mimc, topic, secret);
+    let (proof, inputs) = circuits::prove(
+        &pk,
+        NullifierCircuit::new(mimc.clone(), topic, secret, path),
+        &mut rng,
+    )
+    .unwrap();
+    assert_eq!(inputs, vec![root, topic, nullifier]);
+    assert!(circuits::verify(&pvk, &inputs, &proof).unwrap());
+    assert!(
+        Groth16::<E>::verify_with_variables(&pvk, &proof, &[root], &[topic, nullifier]).unwrap()
+    );
+    assert!(!circuits::verify(&pvk, &[root, topic, leaf], &proof).unwrap());
+    assert!(!circuits::verify(&pvk, &[leaf, topic, nullifier], &proof).unwrap());
+
+    // Range proof.
+    let (pk, pvk) =
+        circuits::setup::<E, _>(RangeCircuit::blank(mimc.clone(), 16), &mut rng).unwrap();
+    let value = E::ScalarField::from(54321u64);
+    let blinding = E::ScalarField::rand(&mut rng);
+    let (proof, inputs) = circuits::prove(
+        &pk,
+        RangeCircuit::new(mimc.clone(), 16, value, blinding),
+        &mut rng,
+    )
+    .unwrap();
+    assert_eq!(inputs, vec![RangeCircuit::commit(&mimc, value, blinding)]);
+    assert!(circuits::verify(&pvk, &inputs, &proof).unwrap());
+    assert!(!is_satisfied(RangeCircuit::new(
+        mimc.clone(),
+        16,
+        E::ScalarField::from(1u64 << 16),
+        blinding
+    )));
+    assert!(!is_satisfied(RangeCircuit::new(mimc, 16, -value, blinding)));
+}
+
+fn test_constraint_export<E>()
+where
+    E: Pairing,
+{
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+    let circuit = InputsCircuit::<E::ScalarField> {
+        inputs: vec![None; 3],
+    };
+
+    let (pk, export) =
+        Groth16::<E>::generate_random_parameters_with_export(circuit.clone(), &mut rng, 2).unwrap();
+    assert_eq!(export.num_constraints(), 3);
+    assert_eq!(
+        export.symbols.variables,
+        [
+            "one",
+            "input_1",
+            "input_2",
+            "input_3",
+            "witness_0",
+            "witness_1",
+            "witness_2"
+        ]
+    );
+    assert_eq!(
+        export.symbols.first_constraints,
+        [
+            Some(0),
+            Some(0),
+            Some(1),
+            Some(2),
+            Some(0),
+            Some(1),
+            Some(2)
+        ]
+    );
+    assert!(export.symbols.constraints.is_empty());
+    assert!(export.matches_circuit(circuit.clone()).unwrap());
+    assert!(export.matches_verifying_key(&pk.vk, &mut rng));
+
+    // The export is serialized separately from the keys.
+    let mut bytes = Vec::new();
+    export.serialize_compressed(&mut bytes).unwrap();
+    let decoded = ConstraintExport::<E>::deserialize_compressed(&bytes[..]).unwrap();
+    assert_eq!(decoded, export);
+    assert!(decoded.matches_verifying_key(&pk.vk, &mut rng));
+
+    // Another circuit, another key or altered matrices do not match.
+    let other_circuit = InputsCircuit::<E::ScalarField> {
+        inputs: vec![None; 2],
+    };
+    assert!(!export.matches_circuit(other_circuit).unwrap());
+    let (other_pk, _) =
+        Groth16::<E>::generate_random_parameters_with_export(circuit, &mut rng, 2).unwrap();
+    assert!(!export.matches_verifying_key(&other_pk.vk, &mut rng));
+    let mut altered = export.clone();
+    altered.a[1][0].0 += E::ScalarField::one();
+    assert!(!altered.matches_verifying_key(&pk.vk, &mut rng));
+    let mut altered = export.clone();
+    altered.lagrange_g2.swap(0, 1);
+    assert!(!altered.matches_verifying_key(&pk.vk, &mut rng));
+    let mut truncated = pk.vk.clone();
+    truncated.gamma_abc_g1_variable.pop();
+    assert!(!export.matches_verifying_key(&truncated, &mut rng));
+}
+
+fn test_expand_ceremony_artifact<E>()
+where
+    E: Pairing,
+{
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    for h_basis in [KeyBasis::Monomial, KeyBasis::Lagrange] {
+        let artifact = Groth16::<E>::generate_ceremony_artifact(
+            MySillyCircuit { a: None, b: None },
+            &mut rng,
+            1,
+            h_basis,
+        )
+        .unwrap();
+        let mut bytes = Vec::new();
+        artifact.serialize_compressed(&mut bytes).unwrap();
+        let artifact = CeremonyArtifact::<E>::deserialize_compressed(&bytes[..]).unwrap();
+
+        let pk = expand_proving_key(&artifact).unwrap();
+        let pvk = prepare_verifying_key::<E>(&pk.vk);
+
+        let a = E::ScalarField::rand(&mut rng);
+        let b = E::ScalarField::rand(&mut rng);
+        let proof = Groth16::<E>::prove(
+            &pk,
+            MySillyCircuit {
+                a: Some(a),
+                b: Some(b),
+            },
+            &mut rng,
+        )
+        .unwrap();
+        assert!(Groth16::<E>::verify_with_processed_vk(&pvk, &[a * b], &proof).unwrap());
+        assert!(!Groth16::<E>::verify_with_processed_vk(&pvk, &[a], &proof).unwrap());
+
+        let mut truncated = artifact.clone();
+        truncated.lagrange_g2.pop();
+        assert!(expand_proving_key(&truncated).is_err());
+    }
+}
+
+#[cfg(feature = "arena")]
+fn test_prover_arena<E>()
+where
+    E: Pairing,
+{
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+    let mut arena = crate::arena::ProverArena::new();
+
+    for h_basis in [KeyBasis::Monomial, KeyBasis::Lagrange] {
+        let pk = Groth16::<E>::generate_random_parameters_with_basis(
+            MySillyCircuit { a: None, b: None },
+            &mut rng,
+            1,
+            0,
+            h_basis,
+        )
+        .unwrap();
+
+        for _ in 0..3 {
+            let a = E::ScalarField::rand(&mut rng);
+            let b = E::ScalarField::rand(&mut rng);
+            let r = E::ScalarField::rand(&mut rng);
+            let s = E::ScalarField::rand(&mut rng);
+            let circuit = || MySillyCircuit {
+                a: Some(a),
+                b: Some(b),
+            };
+
+            let proof = Groth16::<E>::create_proof_with_reduction(circuit(), &pk, r, s).unwrap();
+            let arena_proof =
+                Groth16::<E>::create_proof_with_arena(circuit(), &pk, r, s, &CpuMsm, &mut arena)
+                    .unwrap();
+            assert_eq!(proof, arena_proof);
+        }
+    }
+    assert!(arena.capacity() > 0);
+}
+
+/// The `G1` of BN254 with its GLV endomorphism, which arkworks does not
+/// provide for it.
+#[cfg(feature = "glv")]
+mod glv_bn254 {
+    use ark_bn254::{g1::Config as G1Config, Fq, Fr};
+    use ark_ec::{
+        scalar_mul::glv::GLVConfig,
+        short_weierstrass::{Affine, Projective, SWCurveConfig},
+        CurveConfig,
+    };
+    use ark_ff::{BigInteger, Field, MontFp, PrimeField};
+
+    #[derive(Clone, Copy, Default, PartialEq, Eq)]
+    pub struct Config;
+
+    impl CurveConfig for Config {
+        type BaseField = Fq;
+        type ScalarField = Fr;
+        const COFACTOR: &'static [u64] = G1Config::COFACTOR;
+        const COFACTOR_INV: Fr = G1Config::COFACTOR_INV;
+    }
+
+    impl SWCurveConfig for Config {
+        const COEFF_A: Fq = G1Config::COEFF_A;
+        const COEFF_B: Fq = G1Config::COEFF_B;
+        const GENERATOR: Affine<Self> =
+            Affine::new_unchecked(ark_bn254::g1::G1_GENERATOR_X, ark_bn254::g1::G1_GENERATOR_Y);
+    }
+
+    /// A cube root of unity of `Fq`, with `phi(x, y) = (BETA * x, y)`.
+    const BETA: Fq = MontFp!("2203960485148121921418603742825762020974279258880205651966");
+    /// A short basis of the lattice of the `(x, y)` with `x + lambda * y = 0`.
+    const BASIS: [[Fr; 2]; 2] = [
+        [
+            MontFp!("9931322734385697763"),
+            MontFp!(
+                "21888242871839275222246405745257275088400417643534245024697439155772005084889"
+            ),
+        ],
+        [
+            MontFp!("147946756881789319010696353538189108491"),
+            MontFp!("9931322734385697763"),
+        ],
+    ];
+    /// `|b2| * 2^256 / r` and `|b1| * 2^256 / r`, in little endian limbs.
+    const ROUNDING: [[u64; 3]; 2] = [
+        [0xd91d232ec7e0b3d7, 0x2, 0x0],
+        [0x7a7bd9d4391eb18d, 0x4ccef014a773d2cf, 0x2],
+    ];
+
+    /// Returns `(k * rounding) >> 256`.
+    fn mul_shift(k: &[u64], rounding: &[u64; 3]) -> Fr {
+        let mut product = [0u64; 7];
+        for (i, &k) in k.iter().enumerate() {
+            let mut carry = 0u128;
+            for (j, &g) in rounding.iter().enumerate() {
+                let limb = product[i + j] as u128 + k as u128 * g as u128 + carry;
+                product[i + j] = limb as u64;
+                carry = limb >> 64;
+            }
+            product[i + rounding.len()] = carry as u64;
+        }
+        Fr::from(product[4] as u128 | (product[5] as u128) << 64)
+    }
+
+    impl GLVConfig for Config {
+        type Curve = Projective<Self>;
+
+        // The endomorphism is computed directly, not from these.
+        const COEFF_A1: Fq = Fq::ONE;
+        const COEFF_A2: Fq = Fq::ONE;
+        const COEFF_A3: Fq = Fq::ONE;
+        const COEFF_B1: Fq = Fq::ONE;
+        const COEFF_B2: Fq = Fq::ONE;
+        const COEFF_B3: Fq = Fq::ONE;
+        const COEFF_C1: Fq = Fq::ONE;
+        const COEFF_C2: Fq = Fq::ONE;
+
+        const COEFF_N11: Fr = BASIS[0][0];
+        const COEFF_N12: Fr = BASIS[0][1];
+        const COEFF_N21: Fr = BASIS[1][0];
+        const COEFF_N22: Fr = BASIS[1][1];
+
+        fn endomorphism(base: &Affine<Self>) -> Affine<Self> {
+            let mut image = *base;
+            image.x *= BETA;
+            image
+        }
+
+        fn scalar_decomposition(k: &Fr) -> (Fr, Fr) {
+            let limbs = k.into_bigint();
+            let [c1, c2] = ROUNDING.map(|rounding| mul_shift(limbs.as_ref(), &rounding));
+            let [[a1, b1], [a2, b2]] = BASIS;
+            (*k - c1 * a1 - c2 * a2, -(c1 * b1 + c2 * b2))
+        }
+
+        fn glv_mul(base: &Affine<Self>, scalar: &Fr) -> Projective<Self> {
+            let (k1, k2) = Self::scalar_decomposition(scalar);
+            *base * k1 + Self::endomorphism(base) * k2
+        }
+    }
+
+    /// Returns the number of bits of `k` or `-k`, whichever is smaller.
+    pub fn signed_bits(k: Fr) -> u32 {
+        k.into_bigint()
+            .num_bits()
+            .min((-k).into_bigint().num_bits())
+    }
+}
+
+#[cfg(feature = "glv")]
+fn test_glv() {
+    use crate::glv::split_scalars;
+    use ark_bn254::{Bn254, Fr};
+    use ark_ec::{
+        scalar_mul::glv::GLVConfig,
+        short_weierstrass::{Affine, Projective},
+    };
+    use ark_ff::BigInteger;
+    use glv_bn254::{signed_bits, Config};
+
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    // The eigenvalue of the endomorphism is the root of the first basis vector
+    let lambda = -Config::COEFF_N11 / Config::COEFF_N12;
+    let base = Projective::<Config>::rand(&mut rng).into_affine();
+    assert_eq!(Config::endomorphism(&base), (base * lambda).into_affine());
+
+    let half_bits = Fr::MODULUS_BIT_SIZE / 2 + 2;
+    for k in [
+        Fr::zero(),
+        Fr::one(),
+        -Fr::one(),
+        Fr::rand(&mut rng),
+        Fr::rand(&mut rng),
+    ] {
+        let (k1, k2) = Config::scalar_decomposition(&k);
+        assert!(signed_bits(k1) <= half_bits && signed_bits(k2) <= half_bits);
+        assert_eq!(k1 + lambda * k2, k);
+    }
+
+    let bases = (0..4)
+        .map(|_| Projective::<Config>::rand(&mut rng).into_affine())
+        .collect::<Vec<_>>();
+    let scalars = (0..4).map(|_| Fr::rand(&mut rng)).collect::<Vec<_>>();
+    let (split_bases, split_inputs) = split_scalars::<Projective<Config>, Config>(&bases, &scalars);
+    assert_eq!(split_bases.len(), 8);
+    assert!(split_inputs
+        .iter()
+        .all(|scalar| scalar.num_bits() <= half_bits));
+    assert_eq!(
+        split_bases
+            .iter()
+            .zip(&split_inputs)
+            .map(|(base, scalar)| base.mul_bigint(scalar))
+            .sum::<Projective<Config>>(),
+        bases
+            .iter()
+            .zip(&scalars)
+            .map(|(base, scalar)| *base * scalar)
+            .sum::<Projective<Config>>(),
+    );
+    assert_eq!(
+        split_scalars::<Projective<Config>, Config>(&[Affine::<Config>::identity()], &scalars[..1])
+            .0,
+        vec![Affine::<Config>::identity(); 2]
+    );
+
+    // The key of a circuit over the same curve, whose `G1` has no GLV
+    // endomorphism in arkworks, prepares inputs like without it.
+    let vk = VerifyingKey::<Bn254> {
+        gamma_abc_g1_static: (0..3)
+            .map(|_| ark_bn254::G1Projective::rand(&mut rng).into_affine())
+            .collect(),
+        gamma_abc_g1_variable: vec![ark_bn254::G1Projective::rand(&mut rng).into_affine()],
+        ..Default::default()
+    };
+    let pvk = prepare_verifying_key(&vk);
+    let as_glv = |point: &ark_bn254::G1Affine| Affine::<Config>::new_unchecked(point.x, point.y);
+    let glv_bases = vk
+        .gamma_abc_g1_static
+        .iter()
+        .skip(1)
+        .chain(&vk.gamma_abc_g1_variable)
+        .map(as_glv)
+        .collect::<Vec<_>>();
+    let (split_bases, split_inputs) =
+        split_scalars::<Projective<Config>, Config>(&glv_bases, &scalars[..3]);
+    let split_sum = split_bases
+        .iter()
+        .zip(&split_inputs)
+        .map(|(base, scalar)| base.mul_bigint(scalar))
+        .sum::<Projective<Config>>()
+        + as_glv(&vk.gamma_abc_g1_static[0]);
+    let prepared =
+        Groth16::<Bn254>::prepare_inputs_with_variables(&pvk, &scalars[..2], &scalars[2..3])
+            .unwrap()
+            .into_affine();
+    assert_eq!(split_sum.into_affine(), as_glv(&prepared));
+}
+
+#[cfg(feature = "parallel")]
+fn test_prover_pipeline<E>()
+where
+    E: Pairing,
+{
+    use crate::pipeline::{ProverPipeline, StageThreads};
+
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+    let threads = StageThreads {
+        synthesis: Some(1),
+        qap: Some(2),
+        msm: Some(3),
+    };
+
+    for h_basis in [KeyBasis::Monomial, KeyBasis::Lagrange] {
+        let pk = Groth16::<E>::generate_random_parameters_with_basis(
+            MySillyCircuit { a: None, b: None },
+            &mut rng,
+            1,
+            0,
+            h_basis,
+        )
+        .unwrap();
+
+        for config in [ProverConfig::default(), ProverConfig::low_memory()] {
+            let pipeline = ProverPipeline::new(threads, config).unwrap();
+            let a = E::ScalarField::rand(&mut rng);
+            let b = E::ScalarField::rand(&mut rng);
+            let r = E::ScalarField::rand(&mut rng);
+            let s = E::ScalarField::rand(&mut rng);
+            let circuit = || MySillyCircuit {
+                a: Some(a),
+                b: Some(b),
+            };
+
+            let proof = Groth16::<E>::create_proof_with_reduction(circuit(), &pk, r, s).unwrap();
+            let pipeline_proof =
+                Groth16::<E>::create_proof_with_pipeline(circuit(), &pk, r, s, &CpuMsm, &pipeline)
+                    .unwrap();
+            assert_eq!(proof, pipeline_proof);
+        }
+    }
+
+    let pipeline = ProverPipeline::new(StageThreads::default(), ProverConfig::default()).unwrap();
+    let pk = Groth16::<E>::generate_random_parameters_with_reduction(
+        MySillyCircuit { a: None, b: None },
+        &mut rng,
+        1,
+        0,
+    )
+    .unwrap();
+    let a = E::ScalarField::rand(&mut rng);
+    let proof = Groth16::<E>::create_random_proof_with_pipeline(
+        MySillyCircuit {
+            a: Some(a),
+            b: Some(a),
+        },
+        &pk,
+        &CpuMsm,
+        &pipeline,
+        &mut rng,
+    )
+    .unwrap();
+    assert!(Groth16::<E>::verify(&pk.vk, &[a * a], &proof).unwrap());
+    assert!(Groth16::<E>::create_random_proof_with_pipeline(
+        MySillyCircuit {
+            a: Some(a),
+            b: None
+        },
+        &pk,
+        &CpuMsm,
+        &pipeline,
+        &mut rng,
+    )
+    .is_err());
+}
+
+fn test_verify_with_padding<E>()
+where
+    E: Pairing,
+{
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let pvk = prepare_verifying_key::<E>(&vk);
+
+    let a = E::ScalarField::rand(&mut rng);
+    let proof = Groth16::<E>::prove(
+        &pk,
+        MySillyCircuit {
+            a: Some(a),
+            b: Some(E::ScalarField::zero()),
+        },
+        &mut rng,
+    )
+    .unwrap();
+    let c = E::ScalarField::zero();
+
+    for policy in [
+        PaddingPolicy::Error,
+        PaddingPolicy::PadZeros,
+        PaddingPolicy::Truncate,
+    ] {
+        assert!(Groth16::<E>::verify_with_padding(&pvk, &[c], &proof, policy).unwrap());
+    }
+    assert_eq!(PaddingPolicy::default(), PaddingPolicy::Error);
+
+    // A trailing zero input may be omitted.
+    assert!(Groth16::<E>::verify_with_padding(&pvk, &[], &proof, PaddingPolicy::Error).is_err());
+    assert!(Groth16::<E>::verify_with_padding(&pvk, &[], &proof, PaddingPolicy::PadZeros).unwrap());
+    assert!(Groth16::<E>::verify_with_padding(&pvk, &[], &proof, PaddingPolicy::Truncate).is_err());
+
+    // Extra inputs may be dropped.
+    let extra = [c, a];
+    assert!(Groth16::<E>::verify_with_padding(&pvk, &extra, &proof, PaddingPolicy::Error).is_err());
+    assert!(
+        Groth16::<E>::verify_with_padding(&pvk, &extra, &proof, PaddingPolicy::PadZeros).is_err()
+    );
+    assert!(
+        Groth16::<E>::verify_with_padding(&pvk, &extra, &proof, PaddingPolicy::Truncate).unwrap()
+    );
+}
+
+fn test_matches_circuit<E>()
+where
+    E: Pairing,
+{
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    for h_basis in [KeyBasis::Monomial, KeyBasis::Lagrange] {
+        let pk = Groth16::<E>::generate_random_parameters_with_basis(
+            MySillyCircuit { a: None, b: None },
+            &mut rng,
+            1,
+            0,
+            h_basis,
+        )
+        .unwrap();
+        let a = E::ScalarField::rand(&mut rng);
+        assert_eq!(
+            pk.matches_circuit(&MySillyCircuit {
+                a: Some(a),
+                b: Some(a),
+            }),
+            Ok(())
+        );
+        assert_eq!(
+            pk.matches_circuit(&MySillyCircuit { a: None, b: None }),
+            Ok(())
+        );
+
+        let mismatch = pk
+            .matches_circuit(&InputsCircuit {
+                inputs: vec![None; 3],
+            })
+            .unwrap_err();
+        assert_eq!(
+            mismatch,
+            CircuitMismatch::Shape {
+                expected: CircuitShape {
+                    num_inputs: 1,
+                    num_witness_variables: 2,
+                    domain_size: 8,
+                },
+                actual: CircuitShape {
+                    num_inputs: 3,
+                    num_witness_variables: 3,
+                    domain_size: 8,
+                },
+            }
+        );
+        assert!(mismatch.to_string().contains("3 inputs"));
+        assert_eq!(
+            SynthesisError::from(mismatch),
+            SynthesisError::MalformedVerifyingKey
+        );
+
+        // A key with more witness than instance and witness variables.
+        let mut malformed = pk.clone();
+        malformed.l_query.extend_from_slice(&pk.a_query);
+        assert_eq!(
+            malformed.report(),
+            Err(SynthesisError::MalformedVerifyingKey)
+        );
+        assert_eq!(
+            malformed.matches_circuit(&MySillyCircuit { a: None, b: None }),
+            Err(CircuitMismatch::Synthesis(
+                SynthesisError::MalformedVerifyingKey
+            ))
+        );
+    }
+}
+
+fn test_proving_key_report<E>()
+where
+    E: Pairing,
+{
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    for h_basis in [KeyBasis::Monomial, KeyBasis::Lagrange] {
+        let pk = Groth16::<E>::generate_random_parameters_with_basis(
+            MySillyCircuit { a: None, b: None },
+            &mut rng,
+            1,
+            0,
+            h_basis,
+        )
+        .unwrap();
+        let report = pk.report().unwrap();
+
+        // One input and two witness variables in six constraints, plus the
+        // two instance variables enforced by the reduction.
+        assert_eq!(report.num_inputs, 1);
+        assert_eq!(report.num_witness_variables, 2);
+        assert_eq!(report.domain_size, 8);
+        assert_eq!(report.max_constraints, 6);
+        assert_eq!(report.a_query_len, 4);
+        // The witness `b` only appears in `B`, which only contains `b`.
+        assert_eq!(report.a_query_non_zero, 3);
+        assert_eq!(report.b_g2_query_non_zero, 1);
+        assert!(report.key_memory > 0 && report.prover_memory > 0);
+        assert!(report.to_string().contains("domain size: 8"));
+    }
+}
+
+fn test_estimate_sizes<E>()
+where
+    E: Pairing,
+{
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let check = |pk: ProvingKey<E>, cs: ConstraintSystemRef<E::ScalarField>| {
+        let estimate = estimate_sizes::<E>(
+            cs.num_constraints(),
+            cs.num_witness_variables(),
+            cs.num_instance_variables() - 1,
+        )
+        .unwrap();
+        let report = pk.report().unwrap();
+        assert_eq!(estimate.domain_size, report.domain_size);
+        assert_eq!(estimate.pk_bytes, pk.compressed_size());
+        assert_eq!(estimate.vk_bytes, pk.vk.compressed_size());
+        assert_eq!(
+            estimate.proof_bytes,
+            Proof::<E>::default().compressed_size()
+        );
+        assert_eq!(
+            estimate.proving_mem,
+            report.key_memory + report.prover_memory
+        );
+    };
+
+    let synthesize = |circuit: &dyn Fn(ConstraintSystemRef<E::ScalarField>)| {
+        let cs = ConstraintSystem::new_ref();
+        cs.set_mode(SynthesisMode::Setup);
+        circuit(cs.clone());
+        cs
+    };
+
+    let cs = synthesize(&|cs| {
+        MySillyCircuit { a: None, b: None }
+            .generate_constraints(cs)
+            .unwrap()
+    });
+    let (pk, _) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    check(pk, cs);
+
+    // The split between static and variable inputs does not matter.
+    let circuit = || InputsCircuit::<E::ScalarField> {
+        inputs: vec![None; 5],
+    };
+    let cs = synthesize(&|cs| circuit().generate_constraints(cs).unwrap());
+    let pk =
+        Groth16::<E>::generate_random_parameters_with_reduction(circuit(), &mut rng, 3, 0).unwrap();
+    check(pk, cs);
+
+    assert!(estimate_sizes::<E>(usize::MAX / 2, 0, 0).is_err());
+}
+
+fn test_check_witness<E>()
+where
+    E: Pairing,
+{
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, _) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let circuit = MySillyCircuit {
+        a: Some(a),
+        b: Some(b),
+    };
+    assert!(Groth16::<E>::check_witness(&pk, circuit, 10)
+        .unwrap()
+        .is_empty());
+
+    let cs = ConstraintSystem::new_ref();
+    MySillyCircuit {
+        a: Some(a),
+        b: Some(b),
+    }
+    .generate_constraints(cs.clone())
+    .unwrap();
+    cs.finalize();
+    let matrices = cs.to_matrices().unwrap();
+    // Claim the wrong product as the public input.
+    let full_assignment = [E::ScalarField::one(), a, a, b];
+
+    let failures = check_witness_with_matrices(&matrices, &full_assignment, 4).unwrap();
+    assert_eq!(failures.len(), 4);
+    for (i, failure) in failures.iter().enumerate() {
+        assert_eq!(failure.index, i);
+        assert_eq!(failure.a * failure.b, a * b);
+        assert_eq!(failure.c, a);
+    }
+}
+
+fn test_absorb_into_transcript<E>()
+where
+    E: Pairing,
+{
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let circuit = MySillyCircuit {
+        a: Some(a),
+        b: Some(b),
+    };
+    let proof = Groth16::<E>::prove(&pk, circuit, &mut rng).unwrap();
+    let other_proof = Groth16::<E>::rerandomize_proof(&vk, &proof, &mut rng);
+
+    let (ark, mds) = find_poseidon_ark_and_mds::<E::ScalarField>(
+        E::ScalarField::MODULUS_BIT_SIZE as u64,
+        2,
+        8,
+        31,
+        0,
+    );
+    let config = PoseidonConfig::new(8, 31, 17, mds, ark, 2, 1);
+    let challenge = |proof: &Proof<E>, inputs: &[E::ScalarField]| {
+        let mut sponge = PoseidonSponge::new(&config);
+        vk.absorb_into(&mut sponge);
+        absorb_public_inputs(&[], inputs, &mut sponge);
+        proof.absorb_into(&mut sponge);
+        sponge.squeeze_field_elements::<E::ScalarField>(1)[0]
+    };
+
+    assert_eq!(challenge(&proof, &[a * b]), challenge(&proof, &[a * b]));
+    assert_ne!(
+        challenge(&proof, &[a * b]),
+        challenge(&other_proof, &[a * b])
+    );
+    assert_ne!(challenge(&proof, &[a * b]), challenge(&proof, &[a]));
+    assert_ne!(
+        public_inputs_transcript_bytes(&[a], &[b]),
+        public_inputs_transcript_bytes(&[], &[a, b])
+    );
+}
+
+fn test_fixed_verifier<E>()
+where
+    E: Pairing,
+{
+    use crate::fixed::FixedVerifier;
+
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let pk = Groth16::<E>::generate_random_parameters_with_reduction(
+        InputsCircuit {
+            inputs: vec![None; 3],
+        },
+        &mut rng,
+        2,
+        2,
+    )
+    .unwrap();
+    let pvk = prepare_verifying_key::<E>(&pk.vk);
+    let inputs = [(); 3].map(|_| E::ScalarField::rand(&mut rng));
+    let circuit = InputsCircuit {
+        inputs: inputs.iter().copied().map(Some).collect(),
+    };
+    let proof = Groth16::<E>::prove(&pk, circuit, &mut rng).unwrap();
+
+    let verifier = FixedVerifier::<E, 1, 2>::new(pvk.clone()).unwrap();
+    let static_inputs = [inputs[0]];
+    let variable_inputs = [inputs[1], inputs[2]];
+    assert_eq!(
+        verifier.prepare_inputs(&static_inputs, &variable_inputs),
+        Groth16::<E>::prepare_inputs_with_variables(&pvk, &static_inputs, &variable_inputs)
+            .unwrap()
+    );
+    assert!(verifier
+        .verify(&proof, &static_inputs, &variable_inputs)
+        .unwrap());
+    assert!(!verifier
+        .verify(&proof, &static_inputs, &[inputs[2], inputs[1]])
+        .unwrap());
+
+    assert_eq!(
+        FixedVerifier::<E, 0, 3>::new(pvk).unwrap_err(),
+        InputCountMismatch {
+            expected_static: 1,
+            actual_static: 0,
+            expected_variable: 2,
+            actual_variable: 3,
+        }
+    );
+}
+
+fn test_verify_streaming<E>()
+where
+    E: Pairing,
+{
+    use crate::DEFAULT_INPUT_CHUNK_SIZE;
+
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let num_inputs = 40;
+    let pk = Groth16::<E>::generate_random_parameters_with_reduction(
+        InputsCircuit {
+            inputs: vec![None; num_inputs],
+        },
+        &mut rng,
+        5,
+        num_inputs - 4,
+    )
+    .unwrap();
+    let pvk = prepare_verifying_key::<E>(&pk.vk);
+    let inputs = (0..num_inputs)
+        .map(|_| E::ScalarField::rand(&mut rng))
+        .collect::<Vec<_>>();
+    let circuit = InputsCircuit {
+        inputs: inputs.iter().copied().map(Some).collect(),
+    };
+    let proof = Groth16::<E>::prove(&pk, circuit, &mut rng).unwrap();
+
+    let (static_inputs, variable_inputs) = inputs.split_at(4);
+    let expected =
+        Groth16::<E>::prepare_inputs_with_variables(&pvk, static_inputs, variable_inputs).unwrap();
+    for chunk_size in [0, 1, 5, 7, num_inputs, DEFAULT_INPUT_CHUNK_SIZE] {
+        assert_eq!(
+            Groth16::<E>::prepare_inputs_streaming(&pvk, &inputs, chunk_size).unwrap(),
+            expected
+        );
+        assert!(
+            Groth16::<E>::verify_streaming(&pvk, &proof, inputs.iter().copied(), chunk_size)
+                .unwrap()
+        );
+    }
+
+    let mut tampered = inputs.clone();
+    tampered[num_inputs - 1] += E::ScalarField::one();
+    assert!(!Groth16::<E>::verify_streaming(&pvk, &proof, &tampered, 7).unwrap());
+
+    // Missing and extra inputs are counted, static inputs first.
+    let mismatch = |inputs: &[E::ScalarField]| {
+        Groth16::<E>::prepare_inputs_streaming(&pvk, inputs, 7).unwrap_err()
+    };
+    assert_eq!(
+        mismatch(&inputs[..2]),
+        VerificationError::InputCount(InputCountMismatch {
+            expected_static: 4,
+            actual_static: 2,
+            expected_variable: num_inputs - 4,
+            actual_variable: 0,
+        })
+    );
+    let mut extra = inputs.clone();
+    extra.push(E::ScalarField::one());
+    assert_eq!(
+        mismatch(&extra),
+        VerificationError::InputCount(InputCountMismatch {
+            expected_static: 4,
+            actual_static: 4,
+            expected_variable: num_inputs - 4,
+            actual_variable: num_inputs - 3,
+        })
+    );
+
+    // Reading stops at the first excess input, even of an endless iterator.
+    assert_eq!(
+        Groth16::<E>::prepare_inputs_streaming(
+            &pvk,
+            ark_std::iter::repeat(E::ScalarField::one()),
+            7
+        )
+        .unwrap_err(),
+        VerificationError::InputCount(InputCountMismatch {
+            expected_static: 4,
+            actual_static: 4,
+            expected_variable: num_inputs - 4,
+            actual_variable: num_inputs - 3,
+        })
+    );
+}
+
+fn test_public_input_bytes<E>()
+where
+    E: Pairing,
+{
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let pvk = prepare_verifying_key::<E>(&vk);
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let circuit = MySillyCircuit {
+        a: Some(a),
+        b: Some(b),
+    };
+    let proof = Groth16::<E>::prove(&pk, circuit, &mut rng).unwrap();
+
+    let size = PublicInputs::<E>::element_size();
+    let inputs = PublicInputs::<E>(vec![a * b, -E::ScalarField::one()]);
+    let be = inputs.to_bytes_be();
+    let le = inputs.to_bytes_le();
+    assert_eq!(be.len(), 2 * size);
+    assert_eq!(PublicInputs::<E>::from_bytes_be(&be).unwrap(), inputs);
+    assert_eq!(PublicInputs::<E>::from_bytes_le(&le).unwrap(), inputs);
+    assert_eq!(
+        &be[..size].iter().rev().copied().collect::<Vec<_>>(),
+        &le[..size]
+    );
+
+    if size == 32 {
+        let words = inputs.to_words32(Endianness::Big).unwrap();
+        assert_eq!(
+            PublicInputs::<E>::from_words32(&words, Endianness::Big).unwrap(),
+            inputs
+        );
+        let words_of = |inputs: &[E::ScalarField]| {
+            PublicInputs::<E>(inputs.to_vec())
+                .to_words32(Endianness::Big)
+                .unwrap()
+        };
+        let words = words_of(&[a * b]);
+        assert!(Groth16::<E>::verify_be_words(&pvk, &proof, &words).unwrap());
+        assert!(!Groth16::<E>::verify_be_words(&pvk, &proof, &words_of(&[a])).unwrap());
+        assert!(Groth16::<E>::verify_be_words(&pvk, &proof, &[[0xff; 32]]).is_err());
+        assert!(Groth16::<E>::verify_be_words(&pvk, &proof, &[words[0]; 2]).is_err());
+    } else {
+        assert!(inputs.to_words32(Endianness::Big).is_err());
+    }
+    // Non-canonical encodings and truncated inputs are rejected.
+    assert!(PublicInputs::<E>::from_bytes_be(&vec![0xff; size]).is_err());
+    assert!(PublicInputs::<E>::from_bytes_be(&be[1..]).is_err());
+
+    let c = PublicInputs::<E>(vec![a * b]);
+    for endianness in [Endianness::Big, Endianness::Little] {
+        assert!(Groth16::<E>::verify_with_input_bytes(
+            &pvk,
+            &proof,
+            &[],
+            &c.to_bytes(endianness),
+            endianness
+        )
+        .unwrap());
+    }
+    assert!(Groth16::<E>::verify_with_input_bytes(
+        &pvk,
+        &proof,
+        &[],
+        &vec![0xff; size],
+        Endianness::Big
+    )
+    .is_err());
+}
+
+fn test_snarkjs_json<E>()
+where
+    E: Pairing,
+{
+    use crate::snarkjs::{
+        proof_from_json, proof_to_json, public_inputs_from_json, public_inputs_to_json,
+        verifying_key_from_json, verifying_key_to_json,
+    };
+
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let a = E::ScalarField::rand(&mut rng);
+    let circuit = MySillyCircuit {
+        a: Some(a),
+        b: Some(a),
+    };
+    let proof = Groth16::<E>::prove(&pk, circuit, &mut rng).unwrap();
+    let inputs = vec![a * a];
+
+    // snarkjs keys expect all their inputs as variable inputs.
+    let vk_json = verifying_key_to_json(&vk).unwrap();
+    assert!(vk_json.contains("\"nPublic\": 1"));
+    let imported = verifying_key_from_json::<E>(&vk_json).unwrap();
+    assert_eq!(imported.gamma_abc_g1_static, vk.gamma_abc_g1_static);
+    assert_eq!(imported.gamma_abc_g1_variable, vk.gamma_abc_g1_variable);
+    assert_eq!(verifying_key_to_json(&imported).unwrap(), vk_json);
+
+    let proof_json = proof_to_json(&proof).unwrap();
+    assert_eq!(proof_from_json::<E>(&proof_json).unwrap(), proof);
+    let inputs_json = public_inputs_to_json(&inputs);
+    assert_eq!(
+        public_inputs_from_json::<E::ScalarField>(&inputs_json).unwrap(),
+        inputs
+    );
+    assert!(Groth16::<E>::verify(
+        &imported,
+        &public_inputs_from_json::<E::ScalarField>(&inputs_json).unwrap(),
+        &proof_from_json(&proof_json).unwrap()
+    )
+    .unwrap());
+
+    // Files are parsed regardless of their layout, and the point at
+    // infinity is encoded with `z = 0`.
+    let compact = proof_json.replace(['\n', ' '], "");
+    assert_eq!(proof_from_json::<E>(&compact).unwrap(), proof);
+    let identity = Proof::<E> {
+        a: E::G1Affine::zero(),
+        ..proof.clone()
+    };
+    assert_eq!(
+        proof_from_json::<E>(&proof_to_json(&identity).unwrap()).unwrap(),
+        identity
+    );
+
+    // Files of other protocols and curves, and non-canonical or malformed
+    // files, are rejected.
+    assert!(proof_from_json::<E>(&proof_json.replace("groth16", "plonk")).is_err());
+    assert!(proof_from_json::<E>(&proof_json.replace("bn128", "bls12381")).is_err());
+    assert!(proof_from_json::<E>(&proof_json[..proof_json.len() - 1]).is_err());
+    assert!(proof_from_json::<E>(&format!("{}{{}}", proof_json)).is_err());
+    assert!(
+        verifying_key_from_json::<E>(&vk_json.replace("\"nPublic\": 1", "\"nPublic\": 2")).is_err()
+    );
+    let modulus = E::ScalarField::MODULUS.to_string();
+    assert!(public_inputs_from_json::<E::ScalarField>(&format!("[\"{}\"]", modulus)).is_err());
+    assert!(public_inputs_from_json::<E::ScalarField>("[\"01\"]").is_err());
+    assert!(public_inputs_from_json::<E::ScalarField>("[1]").is_err());
+}
+
+#[cfg(feature = "compat")]
+fn test_compat<E>()
+where
+    E: Pairing,
+{
+    use crate::{
+        compat::{self, prepare_verifying_key},
+        loader::load_any_verifying_key,
+    };
+    use ark_crypto_primitives::snark::CircuitSpecificSetupSNARK;
+
+    // Code generic over the traits of upstream Groth16.
+    fn setup_prove_verify<S, F>(
+        circuit: MySillyCircuit<F>,
+        c: F,
+        rng: &mut (impl RngCore + ark_std::rand::CryptoRng),
+    ) -> bool
+    where
+        F: PrimeField,
+        S: CircuitSpecificSetupSNARK<F>,
+    {
+        let (pk, vk) = S::setup(circuit.clone(), rng).unwrap();
+        let proof = S::prove(&pk, circuit, rng).unwrap();
+        S::verify(&vk, &[c], &proof).unwrap()
+    }
+
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+    let a = E::ScalarField::rand(&mut rng);
+    let circuit = MySillyCircuit {
+        a: Some(a),
+        b: Some(a),
+    };
+    let c = a * a;
+    assert!(setup_prove_verify::<compat::Groth16<E>, _>(
+        circuit.clone(),
+        c,
+        &mut rng
+    ));
+
+    let pk = compat::Groth16::<E>::generate_random_parameters_with_reduction(
+        MySillyCircuit { a: None, b: None },
+        &mut rng,
+    )
+    .unwrap();
+    assert_eq!(pk.vk.gamma_abc_g1.len(), 2);
+    let pvk = prepare_verifying_key(&pk.vk);
+    let proof =
+        compat::Groth16::<E>::create_random_proof_with_reduction(circuit.clone(), &pk, &mut rng)
+            .unwrap();
+    assert!(compat::Groth16::<E>::verify_proof(&pvk, &proof, &[c]).unwrap());
+    assert!(!compat::Groth16::<E>::verify_proof(&pvk, &proof, &[a]).unwrap());
+    assert!(compat::Groth16::<E>::verify_proof(&pvk, &proof, &[c, c]).is_err());
+    assert!(compat::Groth16::<E>::verify_proof(&pvk, &proof, &[]).is_err());
+    let rerandomized = compat::Groth16::<E>::rerandomize_proof(&pk.vk, &proof, &mut rng);
+    assert_ne!(rerandomized, proof);
+    assert!(compat::Groth16::<E>::verify_proof(&pvk, &rerandomized, &[c]).unwrap());
+    assert_eq!(
+        compat::Groth16::<E>::create_proof_with_reduction_no_zk(circuit.clone(), &pk).unwrap(),
+        compat::Groth16::<E>::create_proof_with_reduction_no_zk(circuit.clone(), &pk).unwrap()
+    );
+
+    // Keys convert to those of this crate, with all inputs variable, and
+    // verifying keys serialize in the legacy layout.
+    let native_pk = crate::ProvingKey::<E>::from(pk.clone());
+    assert_eq!(native_pk.vk.gamma_abc_g1_variable.len(), 1);
+    let native_proof = Groth16::<E>::prove(&native_pk, circuit, &mut rng).unwrap();
+    assert!(compat::Groth16::<E>::verify_proof(&pvk, &native_proof, &[c]).unwrap());
+    assert_eq!(compat::ProvingKey::try_from(native_pk).unwrap(), pk);
+    let mut bytes = Vec::new();
+    pk.vk.serialize_compressed(&mut bytes).unwrap();
+    assert_eq!(
+        load_any_verifying_key::<E>(&bytes).unwrap(),
+        AnyVerifyingKey::Legacy(pk.vk.clone())
+    );
+
+    let native_vk = pk.vk.clone().into_split(1).unwrap();
+    assert_eq!(compat::VerifyingKey::from(native_vk), pk.vk);
+}
+
+fn test_packed_inputs<E>()
+where
+    E: Pairing,
+{
+    use crate::inputs::{pack_flags, packing_capacity, unpack_flags, InputPacker, InputUnpacker};
+
+    struct PackedCircuit {
+        values: Vec<(Option<u64>, usize)>,
+    }
+
+    impl<F: PrimeField> ConstraintSynthesizer<F> for PackedCircuit {
+        fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+            let variables = circuits::enforce_packed_inputs(&cs, &self.values)?;
+            assert_eq!(variables.len(), self.values.len());
+            Ok(())
+        }
+    }
+
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+    let capacity = packing_capacity::<E::ScalarField>();
+
+    // Flags, then 3-bit enum discriminants, so that a discriminant straddles
+    // the end of the first input and starts the second one.
+    let num_flags = capacity - 1;
+    let mut values: Vec<(u64, usize)> = (0..num_flags).map(|_| (rng.next_u64() % 2, 1)).collect();
+    values.extend((0..10).map(|_| (rng.next_u64() % 8, 3)));
+    let mut packer = InputPacker::<E::ScalarField>::new();
+    for (value, num_bits) in &values {
+        packer.push(*value, *num_bits).unwrap();
+    }
+    let inputs = packer.finish();
+    assert_eq!(inputs.len(), 2);
+
+    let circuit = |values: &[(u64, usize)], known: bool| PackedCircuit {
+        values: values
+            .iter()
+            .map(|(value, num_bits)| (known.then_some(*value), *num_bits))
+            .collect(),
+    };
+    let (pk, vk) = Groth16::<E>::setup(circuit(&values, false), &mut rng).unwrap();
+    assert_eq!(vk.gamma_abc_g1_variable.len(), inputs.len());
+    let (proof, proved_inputs) =
+        Groth16::<E>::prove_and_output_inputs(&pk, circuit(&values, true), &mut rng).unwrap();
+    assert_eq!(proved_inputs, inputs);
+    assert!(Groth16::<E>::verify(&vk, &inputs, &proof).unwrap());
+
+    // The verifier unpacks the values with the same widths.
+    let mut unpacker = InputUnpacker::new(&inputs);
+    for (value, num_bits) in &values {
+        assert_eq!(unpacker.next(*num_bits).unwrap(), *value);
+    }
+    unpacker.finish().unwrap();
+
+    // Values that do not fit in their width are rejected by both sides.
+    assert!(InputPacker::<E::ScalarField>::new().push(8, 3).is_err());
+    assert!(InputPacker::<E::ScalarField>::new().push(0, 65).is_err());
+    assert!(Groth16::<E>::prove(&pk, circuit(&[(8, 3)], true), &mut rng).is_err());
+
+    let mut flags: Vec<bool> = (0..capacity + 5).map(|i| i % 3 == 0).collect();
+    flags.push(true);
+    let packed = pack_flags::<E::ScalarField>(&flags);
+    assert_eq!(packed.len(), 2);
+    assert_eq!(unpack_flags(&packed, flags.len()).unwrap(), flags);
+    assert!(pack_flags::<E::ScalarField>(&[]).is_empty());
+    assert!(unpack_flags::<E::ScalarField>(&[], 0).unwrap().is_empty());
+
+    // Unpacking rejects set bits outside the layout, unread inputs and
+    // missing inputs.
+    assert!(unpack_flags(&packed, flags.len() - 1).is_err());
+    assert!(unpack_flags(&packed, flags.len() + capacity).is_err());
+    assert!(unpack_flags(&packed[..1], flags.len()).is_err());
+    let mut extra = packed.clone();
+    extra.push(E::ScalarField::zero());
+    assert!(unpack_flags(&extra, flags.len()).is_err());
+    let mut high = packed.clone();
+    high[1] += E::ScalarField::from(2u64).pow([capacity as u64 - 1]);
+    assert!(unpack_flags(&high, flags.len()).is_err());
+    let wrapped = [-E::ScalarField::one()];
+    assert!(unpack_flags(&wrapped, capacity).is_err());
+}
+
+fn test_setup_attestation<E>()
+where
+    E: Pairing,
+{
+    use crate::attestation::{
+        AttestationError, Ceremony, Contribution, SetupAttestation, SigningKey,
+    };
+
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+    let (initial_pk, initial_vk) =
+        Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+
+    let alice = SigningKey::<E>::rand(&mut rng);
+    let bob = SigningKey::<E>::rand(&mut rng);
+    let coordinator = SigningKey::<E>::rand(&mut rng);
+
+    // Alice contributes through the coordinator, Bob on their own machine.
+    let mut ceremony = Ceremony::new(&initial_pk);
+    let pk = ceremony.contribute(&initial_pk, &alice, &mut rng).unwrap();
+    let (pk, contribution) = Contribution::create(&pk, &ceremony.hash(), &bob, &mut rng).unwrap();
+    let bob_hash = contribution.hash(&ceremony.hash());
+    ceremony.record(contribution.clone()).unwrap();
+    assert_eq!(ceremony.num_contributions(), 2);
+    assert_eq!(ceremony.hash(), bob_hash);
+    assert_eq!(
+        ceremony.record(contribution),
+        Err(AttestationError::BrokenChain { index: 2 })
+    );
+    assert!(ceremony
+        .clone()
+        .finalize(&initial_pk, None, &coordinator, &mut rng)
+        .is_err());
+
+    let (pk, attestation) = ceremony
+        .finalize(&pk, Some(b"block 123456"), &coordinator, &mut rng)
+        .unwrap();
+    assert_eq!(attestation.contribution_hashes().len(), 3);
+    assert_eq!(attestation.contribution_hashes()[1], bob_hash);
+    assert_eq!(attestation.coordinator, coordinator.public_key());
+    assert_eq!(attestation.contributions[0].participant, alice.public_key());
+
+    // The final keys prove and verify.
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let circuit = MySillyCircuit {
+        a: Some(a),
+        b: Some(b),
+    };
+    let proof = Groth16::<E>::prove(&pk, circuit, &mut rng).unwrap();
+    assert!(Groth16::<E>::verify(&pk.vk, &[a * b], &proof).unwrap());
+    assert!(!Groth16::<E>::verify(&initial_vk, &[a * b], &proof).unwrap());
+
+    // Third parties verify the deserialized attestation.
+    let mut bytes = Vec::new();
+    attestation.serialize_compressed(&mut bytes).unwrap();
+    let attestation = SetupAttestation::<E>::deserialize_compressed(&bytes[..]).unwrap();
+    attestation.verify(&pk.vk).unwrap();
+    attestation.verify_with_proving_key(&pk).unwrap();
+    assert_eq!(
+        attestation.verify(&initial_vk),
+        Err(AttestationError::KeyMismatch)
+    );
+    assert_eq!(
+        attestation.verify_with_proving_key(&initial_pk),
+        Err(AttestationError::KeyMismatch)
+    );
+
+    let mut tampered = attestation.clone();
+    tampered.contributions[1].delta_g1 = tampered.contributions[0].delta_g1;
+    assert_eq!(
+        tampered.verify(&pk.vk),
+        Err(AttestationError::BrokenChain { index: 1 })
+    );
+    let mut tampered = attestation.clone();
+    tampered.contributions[0].participant = bob.public_key();
+    assert_eq!(
+        tampered.verify(&pk.vk),
+        Err(AttestationError::InvalidContributionSignature { index: 0 })
+    );
+    let mut tampered = attestation.clone();
+    tampered.beacon.as_mut().unwrap().beacon = b"block 123457".to_vec();
+    assert_eq!(
+        tampered.verify(&pk.vk),
+        Err(AttestationError::InvalidBeacon)
+    );
+    let mut tampered = attestation.clone();
+    tampered.coordinator = alice.public_key();
+    assert_eq!(
+        tampered.verify(&pk.vk),
+        Err(AttestationError::InvalidSignature)
+    );
+
+    // A setup without contributions attests the initial keys.
+    let (same_pk, attestation) = Ceremony::new(&initial_pk)
+        .finalize(&initial_pk, None, &coordinator, &mut rng)
+        .unwrap();
+    assert_eq!(same_pk, initial_pk);
+    assert!(attestation.contribution_hashes().is_empty());
+    attestation.verify_with_proving_key(&initial_pk).unwrap();
+}
+
+fn test_preparation_profiles<E>()
+where
+    E: Pairing,
+{
+    use crate::profile::{prepare_verifying_key_with_profile, PreparationProfile};
+
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+    let inputs = (0..5)
+        .map(|_| E::ScalarField::rand(&mut rng))
+        .collect::<Vec<_>>();
+    let circuit = |inputs: &[E::ScalarField]| InputsCircuit {
+        inputs: inputs.iter().copied().map(Some).collect(),
+    };
+    let pk =
+        Groth16::<E>::generate_random_parameters_with_reduction(circuit(&inputs), &mut rng, 3, 0)
+            .unwrap();
+    let proof = Groth16::<E>::prove(&pk, circuit(&inputs), &mut rng).unwrap();
+    let (static_inputs, variable_inputs) = inputs.split_at(2);
+    let expected = Groth16::<E>::prepare_inputs_with_variables(
+        &prepare_verifying_key(&pk.vk),
+        static_inputs,
+        variable_inputs,
+    )
+    .unwrap();
+
+    let mut sizes = Vec::new();
+    for profile in [
+        PreparationProfile::Minimal,
+        PreparationProfile::Balanced,
+        PreparationProfile::MaxSpeed,
+    ] {
+        let mut key = prepare_verifying_key_with_profile(&pk.vk, profile);
+        assert_eq!(key.profile(), profile);
+        assert_eq!(
+            key.prepare_inputs(static_inputs, variable_inputs).unwrap(),
+            expected
+        );
+        assert!(key.verify(&proof, static_inputs, variable_inputs).unwrap());
+        assert!(!key
+            .verify(&proof, variable_inputs, static_inputs)
+            .unwrap_or(false));
+        let mut wrong = variable_inputs.to_vec();
+        wrong[0] += E::ScalarField::one();
+        assert!(!key.verify(&proof, static_inputs, &wrong).unwrap());
+        assert!(key.verify(&proof, &inputs, &[]).is_err());
+
+        // Only the fastest profile caches static inputs.
+        let cached = key.cache_static_inputs(static_inputs).unwrap();
+        assert_eq!(cached, profile == PreparationProfile::MaxSpeed);
+        assert_eq!(key.num_cached_static_inputs(), usize::from(cached));
+        assert!(key.verify(&proof, static_inputs, variable_inputs).unwrap());
+        let other_static = [static_inputs[1], static_inputs[0]];
+        assert!(!key.verify(&proof, &other_static, variable_inputs).unwrap());
+        assert!(key.cache_static_inputs(&inputs).is_err());
+        sizes.push(key.precomputed_size());
+
+        key.clear_static_cache();
+        assert_eq!(key.num_cached_static_inputs(), 0);
+        assert_eq!(key.into_pvk().vk, pk.vk);
+    }
+    assert_eq!(sizes[0], 0);
+    assert!(sizes[0] < sizes[1] && sizes[1] < sizes[2]);
+    assert_eq!(PreparationProfile::default(), PreparationProfile::Minimal);
+}
+
+#[cfg(feature = "fuzz")]
+fn test_fuzz_roundtrip<E>()
+where
+    E: Pairing,
+{
+    use crate::fuzz::{roundtrip_check_for, RoundtripError};
+
+    for seed in 0..8 {
+        roundtrip_check_for::<E>(seed).unwrap();
+    }
+    let error = RoundtripError::Mismatch("proof");
+    assert_eq!(
+        error.to_string(),
+        "deserialized proof differs from the original"
+    );
+}
+
+fn test_separated_gammas<E>()
+where
+    E: Pairing,
+{
+    use crate::separated::{prepare_separated_verifying_key, SeparatedVerifyingKey};
+
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+    let inputs = (0..5)
+        .map(|_| E::ScalarField::rand(&mut rng))
+        .collect::<Vec<_>>();
+    let circuit = |inputs: &[E::ScalarField]| InputsCircuit {
+        inputs: inputs.iter().copied().map(Some).collect(),
+    };
+    let (pk, svk) = Groth16::<E>::generate_random_parameters_with_separated_gammas(
+        circuit(&inputs),
+        &mut rng,
+        3,
+    )
+    .unwrap();
+    assert_eq!(pk.vk, svk.vk);
+    assert_ne!(svk.gamma_variable_g2, svk.vk.gamma_g2);
+    let proof = Groth16::<E>::prove(&pk, circuit(&inputs), &mut rng).unwrap();
+    let (static_inputs, variable_inputs) = inputs.split_at(2);
+
+    let psvk = prepare_separated_verifying_key(&svk);
+    assert!(Groth16::<E>::verify_separated(&psvk, &proof, static_inputs, variable_inputs).unwrap());
+    let mut wrong = variable_inputs.to_vec();
+    wrong[2] += E::ScalarField::one();
+    assert!(!Groth16::<E>::verify_separated(&psvk, &proof, static_inputs, &wrong).unwrap());
+    let wrong = [static_inputs[1], static_inputs[0]];
+    assert!(!Groth16::<E>::verify_separated(&psvk, &proof, &wrong, variable_inputs).unwrap());
+    assert!(Groth16::<E>::verify_separated(&psvk, &proof, &inputs, &[]).is_err());
+
+    // The key on its own only verifies with the variable inputs at zero.
+    let pvk = prepare_verifying_key(&svk.vk);
+    assert!(
+        !Groth16::<E>::verify_with_variables(&pvk, &proof, static_inputs, variable_inputs).unwrap()
+    );
+
+    // Rotating the variable gamma keeps the static bases and the proofs.
+    let rotated = svk.rotate_variable_gamma(&mut rng).unwrap();
+    assert_eq!(rotated.vk.gamma_g2, svk.vk.gamma_g2);
+    assert_eq!(rotated.vk.gamma_abc_g1_static, svk.vk.gamma_abc_g1_static);
+    assert_ne!(
+        rotated.vk.gamma_abc_g1_variable,
+        svk.vk.gamma_abc_g1_variable
+    );
+    assert_ne!(rotated.gamma_variable_g2, svk.gamma_variable_g2);
+    let rotated_psvk = prepare_separated_verifying_key(&rotated);
+    assert!(
+        Groth16::<E>::verify_separated(&rotated_psvk, &proof, static_inputs, variable_inputs)
+            .unwrap()
+    );
+
+    // A key with one gamma is separated by rotating its variable gamma.
+    let pk =
+        Groth16::<E>::generate_random_parameters_with_reduction(circuit(&inputs), &mut rng, 3, 0)
+            .unwrap();
+    let proof = Groth16::<E>::prove(&pk, circuit(&inputs), &mut rng).unwrap();
+    let svk = SeparatedVerifyingKey::from(pk.vk.clone());
+    let psvk = prepare_separated_verifying_key(&svk);
+    assert!(Groth16::<E>::verify_separated(&psvk, &proof, static_inputs, variable_inputs).unwrap());
+    let rotated = svk.rotate_variable_gamma(&mut rng).unwrap();
+    let mut bytes = Vec::new();
+    rotated.serialize_compressed(&mut bytes).unwrap();
+    let deserialized = SeparatedVerifyingKey::<E>::deserialize_compressed(&bytes[..]).unwrap();
+    assert_eq!(deserialized, rotated);
+    let psvk = prepare_separated_verifying_key(&deserialized);
+    assert!(Groth16::<E>::verify_separated(&psvk, &proof, static_inputs, variable_inputs).unwrap());
+}
+
+fn test_rewrite_key<E>()
+where
+    E: Pairing,
+{
+    use crate::loader::{rewrite_key, KeyKind, RewriteOptions};
+
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, vk) = Groth16::<E>::setup(
+        InputsCircuit {
+            inputs: vec![None; 3],
+        },
+        &mut rng,
+    )
+    .unwrap();
+    let legacy = LegacyVerifyingKey::<E> {
+        alpha_g1: vk.alpha_g1,
+        beta_g2: vk.beta_g2,
+        gamma_g2: vk.gamma_g2,
+        delta_g2: vk.delta_g2,
+        gamma_abc_g1: [
+            vk.gamma_abc_g1_static.clone(),
+            vk.gamma_abc_g1_variable.clone(),
+        ]
+        .concat(),
+    };
+
+    let encodings = |key: &dyn Fn(Compress) -> Vec<u8>| (key(Compress::Yes), key(Compress::No));
+    let keys = [
+        (
+            KeyKind::Proving,
+            encodings(&|compress| {
+                let mut bytes = Vec::new();
+                pk.serialize_with_mode(&mut bytes, compress).unwrap();
+                bytes
+            }),
+        ),
+        (
+            KeyKind::Verifying,
+            encodings(&|compress| {
+                let mut bytes = Vec::new();
+                vk.serialize_with_mode(&mut bytes, compress).unwrap();
+                bytes
+            }),
+        ),
+        (
+            KeyKind::LegacyVerifying,
+            encodings(&|compress| {
+                let mut bytes = Vec::new();
+                legacy.serialize_with_mode(&mut bytes, compress).unwrap();
+                bytes
+            }),
+        ),
+    ];
+
+    for (kind, (compressed, uncompressed)) in &keys {
+        for validate in [Validate::Yes, Validate::No] {
+            for (from, to, input, output) in [
+                (Compress::Yes, Compress::No, compressed, uncompressed),
+                (Compress::No, Compress::Yes, uncompressed, compressed),
+                (Compress::Yes, Compress::Yes, compressed, compressed),
+            ] {
+                let options = RewriteOptions {
+                    kind: *kind,
+                    from,
+                    to,
+                    validate,
+                };
+                let mut rewritten = Vec::new();
+                rewrite_key::<E>(&input[..], &mut rewritten, &options).unwrap();
+                assert_eq!(&rewritten, output);
+            }
+        }
+
+        let options = RewriteOptions {
+            kind: *kind,
+            from: Compress::Yes,
+            to: Compress::No,
+            validate: Validate::Yes,
+        };
+        // Truncated keys and trailing bytes are rejected. The last byte of a
+        // proving key is its optional basis tag.
+        assert!(matches!(
+            rewrite_key::<E>(&compressed[..compressed.len() - 2], Vec::new(), &options),
+            Err(KeyLoadError::Serialization(_))
+        ));
+        let padded = [&compressed[..], &[0]].concat();
+        assert!(matches!(
+            rewrite_key::<E>(&padded[..], Vec::new(), &options),
+            Err(KeyLoadError::Serialization(_))
+        ));
+    }
+}
+
+#[cfg(feature = "verifier-audit")]
+fn test_verifier_audit<E>()
+where
+    E: Pairing,
+{
+    use crate::verifier_audit::{capture, is_valid_point, is_valid_target};
+
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let pvk = prepare_verifying_key::<E>(&vk);
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let proof = Groth16::<E>::prove(
+        &pk,
+        MySillyCircuit {
+            a: Some(a),
+            b: Some(b),
+        },
+        &mut rng,
+    )
+    .unwrap();
+
+    let (accepted, records) =
+        capture(|| Groth16::<E>::verify_with_processed_vk(&pvk, &[a * b], &proof).unwrap());
+    assert!(accepted);
+    let steps = records
+        .iter()
+        .map(|record| record.step)
+        .filter(|step| !step.ends_with(".partial"))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        steps,
+        [
+            "prepared_inputs",
+            "proof.a",
+            "proof.b",
+            "proof.c",
+            "prepared_inputs",
+            "miller_loop",
+            "final_exponentiation",
+            "alpha_g1_beta_g2",
+            "accepted",
+        ]
+    );
+    let mut bytes = Vec::new();
+    proof.a.serialize_compressed(&mut bytes).unwrap();
+    let hex = bytes
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect::<String>();
+    let record = records.iter().find(|record| record.step == "proof.a");
+    assert_eq!(record.unwrap().to_string(), format!("proof.a: {}", hex));
+    assert_eq!(records.last().unwrap().value, "01");
+
+    // Nested captures also record into the enclosing one, and nothing is
+    // recorded outside of captures.
+    let ((rejected, inner), outer) =
+        capture(|| capture(|| Groth16::<E>::verify_with_processed_vk(&pvk, &[a], &proof).unwrap()));
+    assert!(!rejected);
+    assert_eq!(inner.last().unwrap().value, "00");
+    assert_eq!(inner, outer);
+    assert!(capture(|| ()).1.is_empty());
+
+    // Points off the prime-order subgroup and Miller loops before the final
+    // exponentiation are caught.
+    let point = loop {
+        let mut bytes = vec![0u8; 2 * E::G2Affine::zero().compressed_size()];
+        rng.fill_bytes(&mut bytes);
+        if let Some(point) = E::G2Affine::from_random_bytes(&bytes) {
+            break point;
+        }
+    };
+    assert!(!is_valid_point(&point));
+    assert!(is_valid_point(&point.clear_cofactor()));
+
+    // A proof element off the subgroup is rejected rather than asserted
+    // against, and recorded as the last point before the outcome.
+    let mut invalid = proof.clone();
+    invalid.b = point;
+    let (accepted, records) =
+        capture(|| Groth16::<E>::verify_with_processed_vk(&pvk, &[a * b], &invalid).unwrap());
+    assert!(!accepted);
+    let steps = records
+        .iter()
+        .map(|record| record.step)
+        .filter(|step| !step.ends_with(".partial"))
+        .collect::<Vec<_>>();
+    assert_eq!(steps, ["prepared_inputs", "proof.a", "proof.b", "accepted"]);
+    assert_eq!(records.last().unwrap().value, "00");
+    let (report, records) =
+        capture(|| Groth16::<E>::verify_with_report(&pvk, &invalid, &[], &[a * b]).unwrap());
+    assert!(!report.accepted);
+    assert_eq!(report.miller_time, Duration::ZERO);
+    let steps = records.iter().map(|record| record.step).collect::<Vec<_>>();
+    assert_eq!(steps, ["proof.a", "proof.b", "accepted"]);
+
+    let miller_loop = E::miller_loop(proof.a, proof.b);
+    assert!(!is_valid_target::<E>(&ark_ec::pairing::PairingOutput(
+        miller_loop.0
+    )));
+    assert!(is_valid_target(
+        &E::final_exponentiation(miller_loop).unwrap()
+    ));
+}
+
+fn test_derive_input<E>()
+where
+    E: Pairing,
+{
+    use crate::inputs::derive_input_from_bytes;
+    use ark_ff::field_hashers::{DefaultFieldHasher, HashToField};
+    use sha2::Sha256;
+
+    let derive = derive_input_from_bytes::<Sha256, E::ScalarField>;
+    let input = derive(b"darklake-v1-pool-id", b"ETH/USDC");
+    assert_eq!(input, derive(b"darklake-v1-pool-id", b"ETH/USDC"));
+    assert_ne!(input, derive(b"darklake-v1-pool-id", b"ETH/USDT"));
+    assert_ne!(input, derive(b"darklake-v1-asset-id", b"ETH/USDC"));
+    assert_ne!(input, derive(b"darklake-v1-pool-id", b""));
+    // Tags longer than 255 bytes are hashed first.
+    let long_domain = [7u8; 300];
+    assert_ne!(derive(&long_domain, b""), derive(&long_domain[..255], b""));
+
+    // The expansion of RFC 9380 with the test vector of its appendix K.1,
+    // reduced into the scalar field of BN254 from 48 bytes.
+    let domain = b"QUUX-V01-CS02-with-expander-SHA256-128";
+    assert_eq!(
+        derive_input_from_bytes::<Sha256, ark_bn254::Fr>(domain, b"abc"),
+        <ark_bn254::Fr as core::str::FromStr>::from_str(
+            "17965555785256401109651805815554049801035589719310328557913203511094791603862"
+        )
+        .unwrap()
+    );
+    // The field hasher of arkworks only matches RFC 9380 when elements are
+    // reduced from as many bytes as the block of the hash function, as for
+    // the base field of BLS12-381.
+    let hasher = <DefaultFieldHasher<Sha256> as HashToField<ark_bls12_381::Fq>>::new(domain);
+    for message in [&b""[..], b"abc", b"abcdef0123456789"] {
+        assert_eq!(
+            derive_input_from_bytes::<Sha256, ark_bls12_381::Fq>(domain, message),
+            hasher.hash_to_field(message, 1)[0]
+        );
+    }
+}
+
+fn test_text_encoding<E>(tag: &str)
+where
+    E: Pairing,
+{
+    use crate::text::{curve_tag, inputs_from_hex, inputs_to_hex, TextError};
+
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, _) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let a = E::ScalarField::rand(&mut rng);
+    let circuit = MySillyCircuit {
+        a: Some(a),
+        b: Some(a),
+    };
+    let proof = Groth16::<E>::prove(&pk, circuit, &mut rng).unwrap();
+    assert_eq!(curve_tag::<E>(), tag);
+
+    let text = proof.to_hex();
+    assert!(text.starts_with(&format!("groth16proof_{}_", tag)));
+    assert_eq!(Proof::<E>::from_hex(&text).unwrap(), proof);
+    assert_eq!(
+        Proof::<E>::from_hex(&format!("  {}\n", text)).unwrap(),
+        proof
+    );
+    assert_eq!(Proof::<E>::from_hex(&text).unwrap().to_hex(), text);
+
+    let inputs = vec![a * a, E::ScalarField::one()];
+    let inputs_text = inputs_to_hex::<E>(&inputs);
+    assert_eq!(inputs_from_hex::<E>(&inputs_text).unwrap(), inputs);
+    assert!(matches!(
+        Proof::<E>::from_hex(&inputs_text),
+        Err(TextError::WrongKind { .. })
+    ));
+
+    // Altering a digit of the payload or of the checksum is detected.
+    let payload_start = text.find(tag).unwrap() + tag.len() + 1;
+    for position in [payload_start, text.len() - 1] {
+        let mut altered = text.clone().into_bytes();
+        altered[position] = if altered[position] == b'0' {
+            b'1'
+        } else {
+            b'0'
+        };
+        assert!(matches!(
+            Proof::<E>::from_hex(core::str::from_utf8(&altered).unwrap()),
+            Err(TextError::ChecksumMismatch)
+        ));
+    }
+    assert!(matches!(
+        Proof::<E>::from_hex(&text[..text.len() - 2]),
+        Err(TextError::Malformed)
+    ));
+    assert!(matches!(
+        Proof::<E>::from_hex(&format!(
+            "{}{}",
+            &text[..payload_start],
+            text[payload_start..].to_uppercase()
+        )),
+        Err(TextError::Malformed)
+    ));
+    assert!(matches!(
+        Proof::<E>::from_hex(&text.replacen(tag, "bw6761", 1)),
+        Err(TextError::WrongCurve { .. })
+    ));
+}
+
+fn test_deserialize_limits<E>()
+where
+    E: Pairing,
+{
+    use crate::loader::{
+        load_verifying_key_with_limits, read_proving_key, read_verifying_key, DeserializeLimits,
+    };
+
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, vk) = Groth16::<E>::setup(
+        InputsCircuit {
+            inputs: vec![None; 3],
+        },
+        &mut rng,
+    )
+    .unwrap();
+    let limits = DeserializeLimits::default();
+    let tight = DeserializeLimits {
+        max_inputs: 3,
+        ..limits
+    };
+
+    for compress in [Compress::Yes, Compress::No] {
+        let mut bytes = Vec::new();
+        vk.serialize_with_mode(&mut bytes, compress).unwrap();
+        assert_eq!(
+            read_verifying_key::<E>(&bytes[..], compress, &limits).unwrap(),
+            vk
+        );
+        assert_eq!(
+            load_verifying_key_with_limits::<E>(&bytes, &limits).unwrap(),
+            vk
+        );
+        // The key has the constant term and three inputs.
+        assert!(matches!(
+            read_verifying_key::<E>(&bytes[..], compress, &tight),
+            Err(KeyLoadError::LimitExceeded)
+        ));
+        assert!(matches!(
+            load_verifying_key_with_limits::<E>(&bytes, &tight),
+            Err(KeyLoadError::LimitExceeded)
+        ));
+        let small = DeserializeLimits {
+            max_key_bytes: bytes.len() - 1,
+            ..limits
+        };
+        assert!(matches!(
+            read_verifying_key::<E>(&bytes[..], compress, &small),
+            Err(KeyLoadError::LimitExceeded)
+        ));
+
+        // A forged length prefix is rejected before anything is allocated.
+        let prefix = E::G1Affine::zero().serialized_size(compress)
+            + 3 * E::G2Affine::zero().serialized_size(compress);
+        bytes[prefix..prefix + 8].copy_from_slice(&(1u64 << 40).to_le_bytes());
+        assert!(matches!(
+            read_verifying_key::<E>(&bytes[..], compress, &limits),
+            Err(KeyLoadError::LimitExceeded)
+        ));
+
+        let mut bytes = Vec::new();
+        pk.serialize_with_mode(&mut bytes, compress).unwrap();
+        let exact = DeserializeLimits {
+            max_key_bytes: bytes.len(),
+            ..limits
+        };
+        assert_eq!(
+            read_proving_key::<E>(&bytes[..], compress, &exact).unwrap(),
+            pk
+        );
+        let small = DeserializeLimits {
+            max_key_bytes: bytes.len() - 1,
+            ..limits
+        };
+        assert!(matches!(
+            read_proving_key::<E>(&bytes[..], compress, &small),
+            Err(KeyLoadError::LimitExceeded)
+        ));
+    }
+}
+
+fn test_load_wrong_curve<E, Other>()
+where
+    E: Pairing,
+    Other: Pairing,
+{
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let (other_pk, other_vk) =
+        Groth16::<Other>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let a = E::ScalarField::rand(&mut rng);
+    let circuit = MySillyCircuit {
+        a: Some(a),
+        b: Some(a),
+    };
+    let proof = Groth16::<E>::prove(&pk, circuit, &mut rng).unwrap();
+
+    for compress in [Compress::Yes, Compress::No] {
+        let mut bytes = Vec::new();
+        vk.serialize_with_mode(&mut bytes, compress).unwrap();
+        assert_eq!(load_verifying_key::<E>(&bytes).unwrap(), vk);
+        assert!(matches!(
+            load_verifying_key::<Other>(&bytes),
+            Err(KeyLoadError::WrongCurve { expected, found })
+                if expected == CurveLayout::of::<Other>() && found == CurveLayout::of::<E>()
+        ));
+        assert!(matches!(
+            load_verifying_key::<E>(&bytes[1..]),
+            Err(KeyLoadError::UnknownLayout)
+        ));
+
+        let mut bytes = Vec::new();
+        pk.serialize_with_mode(&mut bytes, compress).unwrap();
+        assert_eq!(load_proving_key::<E>(&bytes).unwrap(), pk);
+
+        let mut bytes = Vec::new();
+        other_pk.serialize_with_mode(&mut bytes, compress).unwrap();
+        assert!(matches!(
+            load_proving_key::<E>(&bytes),
+            Err(KeyLoadError::WrongCurve { .. })
+        ));
+
+        let mut bytes = Vec::new();
+        proof.serialize_with_mode(&mut bytes, compress).unwrap();
+        assert_eq!(load_proof::<E>(&bytes).unwrap(), proof);
+    }
+
+    let mut bytes = Vec::new();
+    other_vk.serialize_compressed(&mut bytes).unwrap();
+    assert!(load_verifying_key::<E>(&bytes).is_err());
+}
+
+fn test_load_tagged<E, Other>()
+where
+    E: Pairing,
+    Other: Pairing,
+{
+    use crate::loader::{
+        read_verifying_key, rewrite_key, DeserializeLimits, KeyKind, RewriteOptions,
+    };
+
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let a = E::ScalarField::rand(&mut rng);
+    let circuit = MySillyCircuit {
+        a: Some(a),
+        b: Some(a),
+    };
+    let proof = Groth16::<E>::prove(&pk, circuit, &mut rng).unwrap();
+    assert_ne!(CurveTag::of::<E>(), CurveTag::of::<Other>());
+
+    for compress in [Compress::Yes, Compress::No] {
+        let mut bytes = Vec::new();
+        serialize_tagged(&vk, &mut bytes, compress).unwrap();
+        assert_eq!(load_verifying_key::<E>(&bytes).unwrap(), vk);
+        assert_eq!(
+            read_verifying_key::<E>(&bytes[..], compress, &DeserializeLimits::default()).unwrap(),
+            vk
+        );
+        assert!(matches!(
+            load_verifying_key::<Other>(&bytes),
+            Err(KeyLoadError::WrongCurveTag { expected, found })
+                if expected == CurveTag::of::<Other>() && found == CurveTag::of::<E>()
+        ));
+        assert!(matches!(
+            read_verifying_key::<Other>(&bytes[..], compress, &DeserializeLimits::default()),
+            Err(KeyLoadError::WrongCurveTag { .. })
+        ));
+
+        let mut rewritten = Vec::new();
+        let options = RewriteOptions {
+            kind: KeyKind::Verifying,
+            from: compress,
+            to: compress,
+            validate: Validate::Yes,
+        };
+        rewrite_key::<E>(&bytes[..], &mut rewritten, &options).unwrap();
+        assert_eq!(rewritten, bytes);
+
+        let mut bytes = Vec::new();
+        serialize_tagged(&pk, &mut bytes, compress).unwrap();
+        assert_eq!(load_proving_key::<E>(&bytes).unwrap(), pk);
+        assert!(matches!(
+            load_proving_key::<Other>(&bytes),
+            Err(KeyLoadError::WrongCurveTag { .. })
+        ));
+
+        let mut bytes = Vec::new();
+        serialize_tagged(&proof, &mut bytes, compress).unwrap();
+        assert_eq!(load_proof::<E>(&bytes).unwrap(), proof);
+        assert!(matches!(
+            load_proof::<Other>(&bytes),
+            Err(KeyLoadError::WrongCurveTag { .. })
+        ));
+    }
+}
+
+fn test_proof_preparer<E>()
+where
+    E: Pairing,
+{
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let pvk = prepare_verifying_key::<E>(&vk);
+    let mut preparer = ProofPreparer::<E>::new(16);
+
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let proof = Groth16::<E>::prove(
+        &pk,
+        MySillyCircuit {
+            a: Some(a),
+            b: Some(b),
+        },
+        &mut rng,
+    )
+    .unwrap();
+    // A related proof sharing `b`.
+    let related = Proof {
+        a: proof.a,
+        b: proof.b,
+        c: E::G1Affine::generator(),
+    };
+
+    assert!(!preparer.contains_g2(&proof.b));
+    assert!(Groth16::<E>::verify_with_variables_and_preparer(
+        &pvk,
+        &proof,
+        &[],
+        &[a * b],
+        &mut preparer
+    )
+    .unwrap());
+    assert!(preparer.contains_g2(&proof.b));
+    assert!(!Groth16::<E>::verify_with_variables_and_preparer(
+        &pvk,
+        &related,
+        &[],
+        &[a * b],
+        &mut preparer
+    )
+    .unwrap());
+    assert!(!Groth16::<E>::verify_with_variables_and_preparer(
+        &pvk,
+        &proof,
+        &[],
+        &[a],
+        &mut preparer
+    )
+    .unwrap());
+    assert_eq!(preparer.len(), (3, 1));
+
+    let mut bounded = ProofPreparer::<E>::new(1);
+    bounded.prepare_g1(&proof.a);
+    bounded.prepare_g1(&proof.c);
+    assert_eq!(bounded.len(), (1, 0));
+    bounded.clear();
+    assert!(bounded.is_empty());
+}
+
+mod bls12_377 {
+    use super::{
+        test_absorb_into_transcript, test_aggregate_proof_points, test_analyze_failure,
+        test_audit_public_inputs, test_cached_verifier, test_check_witness,
+        test_checkpointed_proof, test_circuit_templates, test_constraint_export,
+        test_cross_field_inputs, test_delegated_proof, test_derive_input, test_deserialize_limits,
+        test_diagnose_input_mismatch, test_estimate_sizes, test_expand_ceremony_artifact,
+        test_expected_inputs, test_fixed_verifier, test_hybrid_msm_backend, test_input_equality,
+        test_key_rotation, test_lagrange_basis_key, test_legacy_verifying_key, test_load_tagged,
+        test_load_wrong_curve, test_local_key_store, test_low_memory_prover, test_matches_circuit,
+        test_msm_bounded_bits, test_msm_constant_time, test_offloaded_proof, test_packed_inputs,
+        test_preparation_profiles, test_proof_bundle, test_proof_preparer,
+        test_proof_serialize_with, test_proof_spool, test_proof_wire_format, test_prove_and_verify,
+        test_prove_with_blinding, test_prove_with_fork_detecting_rng, test_prove_with_matrices,
+        test_prove_with_progress, test_prove_with_report, test_prover_context,
+        test_proving_key_report, test_public_input_bytes, test_public_inputs, test_replay_guard,
+        test_rerandomize, test_rewrite_key, test_rotate_delta, test_separated_gammas,
+        test_setup_attestation, test_setup_with_progress, test_shared_verifier,
+        test_snark_with_partitioned_inputs, test_statement_encoding, test_swap_circuit,
+        test_synthetic_circuit, test_text_encoding, test_threshold_prover, test_truncate_inputs,
+        test_tuned_msm, test_verifier_pool, test_verify_same_inputs_batch, test_verify_split,
+        test_verify_stream, test_verify_streaming, test_verify_strict, test_verify_with_commitment,
+        test_verify_with_padding, test_verify_with_report, test_verifying_key_commitments,
+        test_verifying_key_schema, test_versioned, test_vk_accumulator, test_zero_inputs,
+        test_zkey_export, test_zkey_verify,
+    };
+    use ark_bls12_377::Bls12_377;
+
+    #[test]
+    fn derive_input() {
+        test_derive_input::<Bls12_377>();
+    }
+
+    #[test]
+    fn rewrite_key() {
+        test_rewrite_key::<Bls12_377>();
+    }
+
+    #[test]
+    fn separated_gammas() {
+        test_separated_gammas::<Bls12_377>();
+    }
+
+    #[test]
+    fn preparation_profiles() {
+        test_preparation_profiles::<Bls12_377>();
+    }
+
+    #[test]
+    fn setup_attestation() {
+        test_setup_attestation::<Bls12_377>();
+    }
+
+    #[test]
+    fn packed_inputs() {
+        test_packed_inputs::<Bls12_377>();
+    }
+
+    #[test]
+    fn encode_as_text() {
+        test_text_encoding::<Bls12_377>("bls12377");
+    }
+
+    #[test]
+    fn prove_and_verify_with_accumulator() {
+        test_vk_accumulator::<Bls12_377>();
+    }
+
+    #[test]
+    fn deserialize_with_limits() {
+        test_deserialize_limits::<Bls12_377>();
+    }
+
+    #[test]
+    fn prove_and_verify_fixed() {
+        test_fixed_verifier::<Bls12_377>();
+    }
+
+    #[test]
+    fn prove_and_verify_delegated() {
+        test_delegated_proof::<Bls12_377>();
+    }
+
+    #[test]
+    fn proof_spool() {
+        test_proof_spool::<Bls12_377>();
+    }
+
+    #[test]
+    fn prove_and_analyze_failure() {
+        test_analyze_failure::<Bls12_377>();
+    }
+
+    #[test]
+    fn prove_and_verify_streaming() {
+        test_verify_streaming::<Bls12_377>();
+    }
+
+    #[test]
+    fn prove_and_verify_statement() {
+        test_statement_encoding::<Bls12_377>();
+    }
+
+    #[test]
+    fn prover_context() {
+        test_prover_context::<Bls12_377>();
+    }
+
+    #[test]
+    fn verifying_key_commitments() {
+        test_verifying_key_commitments::<Bls12_377>();
+    }
+
+    #[test]
+    fn shared_verifier() {
+        test_shared_verifier::<Bls12_377>();
+    }
+
+    #[cfg(feature = "zkvm")]
+    #[test]
+    fn verify_with_hook() {
+        super::test_verify_with_hook::<Bls12_377>();
+    }
+
+    #[test]
+    fn synthetic_circuit() {
+        test_synthetic_circuit::<Bls12_377>();
+    }
+
+    #[test]
+    fn cross_field_inputs() {
+        test_cross_field_inputs::<Bls12_377>();
+    }
+
+    #[test]
+    fn verifying_key_schema() {
+        test_verifying_key_schema::<Bls12_377>();
+    }
+
+    #[test]
+    fn constraint_export() {
+        test_constraint_export::<Bls12_377>();
+    }
+
+    #[test]
+    fn proof_wire_format() {
+        test_proof_wire_format::<Bls12_377>();
+    }
+
+    #[test]
+    fn zkey_verify() {
+        test_zkey_verify::<Bls12_377>();
+    }
+
+    #[test]
+    fn verify_split() {
+        test_verify_split::<Bls12_377>();
+    }
+
+    #[test]
+    fn tuned_msm() {
+        test_tuned_msm::<Bls12_377>();
+    }
+
+    #[test]
+    fn rotate_delta() {
+        test_rotate_delta::<Bls12_377>();
+    }
+
+    #[test]
+    fn truncate_inputs() {
+        test_truncate_inputs::<Bls12_377>();
+    }
+
+    #[test]
+    fn matches_circuit() {
+        test_matches_circuit::<Bls12_377>();
+    }
+
+    #[test]
+    fn prove_with_progress() {
+        test_prove_with_progress::<Bls12_377>();
+    }
+
+    #[test]
+    fn setup_with_progress() {
+        test_setup_with_progress::<Bls12_377>();
+    }
+
+    #[test]
+    fn replay_guard() {
+        test_replay_guard::<Bls12_377>();
+    }
+
+    #[test]
+    fn input_equality() {
+        test_input_equality::<Bls12_377>();
+    }
+
+    #[test]
+    fn snark_with_partitioned_inputs() {
+        test_snark_with_partitioned_inputs::<Bls12_377>();
+    }
+
+    #[test]
+    fn checkpointed_proof() {
+        test_checkpointed_proof::<Bls12_377>();
+    }
+
+    #[test]
+    fn msm_constant_time() {
+        test_msm_constant_time::<Bls12_377>();
+    }
+
+    #[test]
+    fn threshold_prover() {
+        test_threshold_prover::<Bls12_377>();
+    }
+
+    #[test]
+    fn verify_same_inputs_batch() {
+        test_verify_same_inputs_batch::<Bls12_377>();
+    }
+
+    #[test]
+    fn estimate_sizes() {
+        test_estimate_sizes::<Bls12_377>();
+    }
+
+    #[test]
+    fn versioned() {
+        test_versioned::<Bls12_377>();
+    }
+
+    #[test]
+    fn offloaded_proof() {
+        test_offloaded_proof::<Bls12_377>();
+    }
+
+    #[test]
+    fn prove_with_report() {
+        test_prove_with_report::<Bls12_377>();
+    }
+
+    #[test]
+    fn zero_inputs() {
+        test_zero_inputs::<Bls12_377>();
+    }
+
+    #[test]
+    fn audit_public_inputs() {
+        test_audit_public_inputs::<Bls12_377>();
+    }
+
+    #[test]
+    fn verifier_pool() {
+        test_verifier_pool::<Bls12_377>();
+    }
+
+    #[test]
+    fn zkey_export() {
+        test_zkey_export::<Bls12_377>();
+    }
+
+    #[test]
+    fn local_key_store() {
+        test_local_key_store::<Bls12_377>();
+    }
+
+    #[test]
+    fn swap_circuit() {
+        test_swap_circuit::<Bls12_377>();
+    }
+
+    #[test]
+    fn expected_inputs() {
+        test_expected_inputs::<Bls12_377>();
+    }
+
+    #[test]
+    fn msm_bounded_bits() {
+        test_msm_bounded_bits::<Bls12_377>();
+    }
+
+    #[test]
+    fn key_rotation() {
+        test_key_rotation::<Bls12_377>();
+    }
+
+    #[test]
+    fn verify_strict() {
+        test_verify_strict::<Bls12_377>();
+    }
+
+    #[test]
+    fn proof_serialize_with() {
+        test_proof_serialize_with::<Bls12_377>();
+    }
+
+    #[test]
+    fn prove_with_blinding() {
+        test_prove_with_blinding::<Bls12_377>();
+    }
+
+    #[test]
+    fn prove_with_fork_detecting_rng() {
+        test_prove_with_fork_detecting_rng::<Bls12_377>();
+    }
+
+    #[test]
+    fn diagnose_input_mismatch() {
+        test_diagnose_input_mismatch::<Bls12_377>();
+    }
+
+    #[test]
+    fn prove_with_matrices() {
+        test_prove_with_matrices::<Bls12_377>();
+    }
+
+    #[test]
+    fn proving_key_report() {
+        test_proving_key_report::<Bls12_377>();
+    }
+
+    #[test]
+    fn verify_with_padding() {
+        test_verify_with_padding::<Bls12_377>();
+    }
+
+    #[cfg(feature = "arena")]
+    #[test]
+    fn prove_with_arena() {
+        super::test_prover_arena::<Bls12_377>();
+    }
+
+    #[cfg(feature = "any-curve")]
+    #[test]
+    fn any_groth16() {
+        use crate::any_curve::Curve;
+        super::test_any_groth16::<Bls12_377>(Curve::Bls12_377);
+        // BLS12-381 shares the layout of BLS12-377
+        super::test_any_groth16::<ark_bls12_381::Bls12_381>(Curve::Bls12_381);
+    }
+
+    #[cfg(feature = "fuzz")]
+    #[test]
+    fn fuzz_input() {
+        super::test_fuzz_input::<Bls12_377>();
+    }
+
+    #[cfg(feature = "fuzz")]
+    #[test]
+    fn fuzz_roundtrip() {
+        super::test_fuzz_roundtrip::<Bls12_377>();
+    }
+
+    #[cfg(feature = "verifier-audit")]
+    #[test]
+    fn verifier_audit() {
+        super::test_verifier_audit::<Bls12_377>();
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn prover_pipeline() {
+        super::test_prover_pipeline::<Bls12_377>();
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn deserialize_parallel() {
+        super::test_deserialize_parallel::<Bls12_377>();
+    }
+
+    #[test]
+    fn expand_ceremony_artifact() {
+        test_expand_ceremony_artifact::<Bls12_377>();
+    }
+
+    #[test]
+    fn circuit_templates() {
+        test_circuit_templates::<Bls12_377>();
+    }
+
+    #[test]
+    fn aggregate_proof_points() {
+        test_aggregate_proof_points::<Bls12_377>();
+    }
+
+    #[test]
+    fn cached_verifier() {
+        test_cached_verifier::<Bls12_377>();
+    }
+
+    #[test]
+    fn legacy_verifying_key() {
+        test_legacy_verifying_key::<Bls12_377>();
+    }
+
+    #[test]
+    fn verify_stream() {
+        test_verify_stream::<Bls12_377>();
+    }
+
+    #[test]
+    fn verify_with_report() {
+        test_verify_with_report::<Bls12_377>();
+    }
+
+    #[test]
+    fn proof_bundle() {
+        test_proof_bundle::<Bls12_377>();
+    }
+
+    #[test]
+    fn public_inputs() {
+        test_public_inputs::<Bls12_377>();
+    }
+
+    #[test]
+    fn prove_and_verify_with_commitment() {
+        test_verify_with_commitment::<Bls12_377>();
+    }
+
+    #[test]
+    fn prove_and_verify_low_memory() {
+        test_low_memory_prover::<Bls12_377>();
+    }
+
+    #[test]
+    fn proof_preparer() {
+        test_proof_preparer::<Bls12_377>();
+    }
+
+    #[test]
+    fn load_wrong_curve() {
+        test_load_wrong_curve::<Bls12_377, ark_bn254::Bn254>();
+    }
+
+    #[test]
+    fn load_tagged() {
+        test_load_tagged::<Bls12_377, ark_bls12_381::Bls12_381>();
+    }
+
+    #[test]
+    fn public_input_bytes() {
+        test_public_input_bytes::<Bls12_377>();
+    }
+
+    #[test]
+    fn absorb_into_transcript() {
+        test_absorb_into_transcript::<Bls12_377>();
+    }
+
+    #[test]
+    fn check_witness() {
+        test_check_witness::<Bls12_377>();
+    }
+
+    #[test]
+    fn lagrange_basis_key() {
+        test_lagrange_basis_key::<Bls12_377>();
+    }
+
+    #[test]
+    fn hybrid_msm_backend() {
+        test_hybrid_msm_backend::<Bls12_377>();
+    }
+
+    #[test]
+    fn prove_and_verify() {
+        test_prove_and_verify::<Bls12_377>(100);
+    }
+
+    #[test]
+    fn rerandomize() {
+        test_rerandomize::<Bls12_377>();
+    }
+}
+
+mod bw6_761 {
+    use super::{
+        test_expand_ceremony_artifact, test_lagrange_basis_key, test_load_wrong_curve,
+        test_prove_and_verify, test_prove_with_matrices, test_public_input_bytes, test_rerandomize,
+        test_verify_with_commitment,
+    };
+
+    use ark_bls12_377::Bls12_377;
+    use ark_bw6_761::BW6_761;
+
+    #[test]
+    fn lagrange_basis_key() {
+        test_lagrange_basis_key::<BW6_761>();
+    }
+
+    #[test]
+    fn expand_ceremony_artifact() {
+        test_expand_ceremony_artifact::<BW6_761>();
+    }
+
+    #[test]
+    fn prove_with_matrices() {
+        test_prove_with_matrices::<BW6_761>();
+    }
+
+    #[test]
+    fn verify_with_commitment() {
+        test_verify_with_commitment::<BW6_761>();
+    }
+
+    #[test]
+    fn public_input_bytes() {
+        test_public_input_bytes::<BW6_761>();
+    }
+
+    // The outer curve of a one-layer recursion over BLS12-377.
+    #[test]
+    fn load_wrong_curve() {
+        test_load_wrong_curve::<BW6_761, Bls12_377>();
+        test_load_wrong_curve::<Bls12_377, BW6_761>();
+    }
+
+    #[test]
+    fn prove_and_verify() {
+        test_prove_and_verify::<BW6_761>(1);
+    }
+
+    #[test]
+    fn rerandomize() {
+        test_rerandomize::<BW6_761>();
+    }
+}
+
+mod bn_254 {
+    use super::{
+        test_analyze_failure, test_checkpointed_proof, test_circuit_templates,
+        test_constraint_export, test_cross_field_inputs, test_delegated_proof, test_derive_input,
+        test_deserialize_limits, test_diagnose_input_mismatch, test_estimate_sizes,
+        test_expand_ceremony_artifact, test_fixed_verifier, test_input_equality,
+        test_lagrange_basis_key, test_legacy_verifying_key, test_load_wrong_curve,
+        test_low_memory_prover, test_matches_circuit, test_msm_bounded_bits,
+        test_msm_constant_time, test_offloaded_proof, test_packed_inputs,
+        test_preparation_profiles, test_proof_bundle, test_proof_serialize_with, test_proof_spool,
+        test_proof_wire_format, test_prove_and_verify, test_prove_with_matrices,
+        test_prove_with_progress, test_prove_with_report, test_prover_context,
+        test_public_input_bytes, test_public_inputs, test_replay_guard, test_rewrite_key,
+        test_rotate_delta, test_separated_gammas, test_setup_attestation, test_setup_with_progress,
+        test_shared_verifier, test_snark_with_partitioned_inputs, test_snarkjs_json,
+        test_statement_encoding, test_swap_circuit, test_synthetic_circuit, test_text_encoding,
+        test_threshold_prover, test_truncate_inputs, test_tuned_msm, test_verify_same_inputs_batch,
+        test_verify_split, test_verify_streaming, test_verify_strict, test_verify_with_commitment,
+        test_verify_with_processed_vk, test_verifying_key_commitments, test_verifying_key_schema,
+        test_versioned, test_vk_accumulator, test_zero_inputs, test_zkey_export, test_zkey_verify,
+    };
+    use ark_bn254::Bn254;
+
+    #[test]
+    fn verify_with_processed_vk() {
+        test_verify_with_processed_vk::<Bn254>();
+    }
+
+    #[test]
+    fn derive_input() {
+        test_derive_input::<Bn254>();
+    }
+
+    #[test]
+    fn rewrite_key() {
+        test_rewrite_key::<Bn254>();
+    }
+
+    #[test]
+    fn separated_gammas() {
+        test_separated_gammas::<Bn254>();
+    }
+
+    #[test]
+    fn preparation_profiles() {
+        test_preparation_profiles::<Bn254>();
+    }
+
+    #[test]
+    fn setup_attestation() {
+        test_setup_attestation::<Bn254>();
+    }
+
+    #[test]
+    fn packed_inputs() {
+        test_packed_inputs::<Bn254>();
+    }
+
+    #[test]
+    fn snarkjs_json() {
+        test_snarkjs_json::<Bn254>();
+    }
+
+    #[test]
+    fn encode_as_text() {
+        test_text_encoding::<Bn254>("bn254");
+    }
+
+    #[test]
+    fn prove_and_verify_with_accumulator() {
+        test_vk_accumulator::<Bn254>();
+    }
+
+    #[test]
+    fn deserialize_with_limits() {
+        test_deserialize_limits::<Bn254>();
+    }
+
+    #[test]
+    fn prove_and_verify_fixed() {
+        test_fixed_verifier::<Bn254>();
+    }
+
+    #[test]
+    fn prove_and_verify_delegated() {
+        test_delegated_proof::<Bn254>();
+    }
+
+    #[test]
+    fn proof_spool() {
+        test_proof_spool::<Bn254>();
+    }
+
+    #[test]
+    fn prove_and_analyze_failure() {
+        test_analyze_failure::<Bn254>();
+    }
+
+    #[test]
+    fn prove_and_verify_streaming() {
+        test_verify_streaming::<Bn254>();
+    }
+
+    #[test]
+    fn prove_and_verify_statement() {
+        test_statement_encoding::<Bn254>();
+    }
+
+    #[test]
+    fn prover_context() {
+        test_prover_context::<Bn254>();
+    }
+
+    #[test]
+    fn verifying_key_commitments() {
+        test_verifying_key_commitments::<Bn254>();
+    }
+
+    #[test]
+    fn shared_verifier() {
+        test_shared_verifier::<Bn254>();
+    }
+
+    #[cfg(feature = "zkvm")]
+    #[test]
+    fn verify_with_hook() {
+        super::test_verify_with_hook::<Bn254>();
+    }
+
+    #[test]
+    fn synthetic_circuit() {
+        test_synthetic_circuit::<Bn254>();
+    }
+
+    #[test]
+    fn cross_field_inputs() {
+        test_cross_field_inputs::<Bn254>();
+    }
+
+    #[test]
+    fn verifying_key_schema() {
+        test_verifying_key_schema::<Bn254>();
+    }
+
+    #[test]
+    fn constraint_export() {
+        test_constraint_export::<Bn254>();
+    }
+
+    #[test]
+    fn proof_wire_format() {
+        test_proof_wire_format::<Bn254>();
+    }
+
+    #[test]
+    fn zkey_verify() {
+        test_zkey_verify::<Bn254>();
+    }
+
+    #[test]
+    fn verify_split() {
+        test_verify_split::<Bn254>();
+    }
+
+    #[test]
+    fn tuned_msm() {
+        test_tuned_msm::<Bn254>();
+    }
+
+    #[test]
+    fn rotate_delta() {
+        test_rotate_delta::<Bn254>();
+    }
+
+    #[test]
+    fn truncate_inputs() {
+        test_truncate_inputs::<Bn254>();
+    }
+
+    #[test]
+    fn matches_circuit() {
+        test_matches_circuit::<Bn254>();
+    }
+
+    #[test]
+    fn prove_with_progress() {
+        test_prove_with_progress::<Bn254>();
+    }
+
+    #[test]
+    fn setup_with_progress() {
+        test_setup_with_progress::<Bn254>();
+    }
+
+    #[test]
+    fn replay_guard() {
+        test_replay_guard::<Bn254>();
+    }
+
+    #[test]
+    fn input_equality() {
+        test_input_equality::<Bn254>();
+    }
+
+    #[test]
+    fn snark_with_partitioned_inputs() {
+        test_snark_with_partitioned_inputs::<Bn254>();
+    }
+
+    #[test]
+    fn checkpointed_proof() {
+        test_checkpointed_proof::<Bn254>();
+    }
+
+    #[test]
+    fn msm_constant_time() {
+        test_msm_constant_time::<Bn254>();
+    }
+
+    #[test]
+    fn threshold_prover() {
+        test_threshold_prover::<Bn254>();
+    }
+
+    #[test]
+    fn verify_same_inputs_batch() {
+        test_verify_same_inputs_batch::<Bn254>();
+    }
+
+    #[test]
+    fn estimate_sizes() {
+        test_estimate_sizes::<Bn254>();
+    }
+
+    #[test]
+    fn versioned() {
+        test_versioned::<Bn254>();
+    }
+
+    #[test]
+    fn offloaded_proof() {
+        test_offloaded_proof::<Bn254>();
+    }
+
+    #[test]
+    fn prove_with_report() {
+        test_prove_with_report::<Bn254>();
+    }
+
+    #[test]
+    fn zero_inputs() {
+        test_zero_inputs::<Bn254>();
+    }
+
+    #[cfg(feature = "any-curve")]
+    #[test]
+    fn any_groth16() {
+        super::test_any_groth16::<Bn254>(crate::any_curve::Curve::Bn254);
+    }
+
+    #[cfg(feature = "compat")]
+    #[test]
+    fn compat() {
+        super::test_compat::<Bn254>();
+    }
+
+    #[cfg(feature = "fuzz")]
+    #[test]
+    fn fuzz_roundtrip() {
+        super::test_fuzz_roundtrip::<Bn254>();
+    }
+
+    #[cfg(feature = "verifier-audit")]
+    #[test]
+    fn verifier_audit() {
+        super::test_verifier_audit::<Bn254>();
+    }
+
+    #[cfg(all(feature = "fuzz", feature = "any-curve"))]
+    #[test]
+    fn fuzz_roundtrip_any_curve() {
+        use crate::any_curve::Curve;
+        for curve in Curve::ALL {
+            crate::fuzz::roundtrip_check(7, curve).unwrap();
+        }
+    }
+
+    #[test]
+    fn zkey_export() {
+        test_zkey_export::<Bn254>();
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn deserialize_parallel() {
+        super::test_deserialize_parallel::<Bn254>();
+    }
+
+    #[test]
+    fn swap_circuit() {
+        test_swap_circuit::<Bn254>();
+    }
+
+    #[test]
+    fn msm_bounded_bits() {
+        test_msm_bounded_bits::<Bn254>();
+    }
+
+    #[test]
+    fn verify_strict() {
+        test_verify_strict::<Bn254>();
+    }
+
+    #[test]
+    fn proof_serialize_with() {
+        test_proof_serialize_with::<Bn254>();
+    }
+
+    #[test]
+    fn diagnose_input_mismatch() {
+        test_diagnose_input_mismatch::<Bn254>();
+    }
+
+    #[test]
+    fn prove_with_matrices() {
+        test_prove_with_matrices::<Bn254>();
+    }
+
+    #[test]
+    fn expand_ceremony_artifact() {
+        test_expand_ceremony_artifact::<Bn254>();
+    }
+
+    #[test]
+    fn circuit_templates() {
+        test_circuit_templates::<Bn254>();
+    }
+
+    #[test]
+    fn legacy_verifying_key() {
+        test_legacy_verifying_key::<Bn254>();
+    }
+
+    #[test]
+    fn proof_bundle() {
+        test_proof_bundle::<Bn254>();
+    }
+
+    #[test]
+    fn public_inputs() {
+        test_public_inputs::<Bn254>();
+    }
+
+    #[test]
+    fn prove_and_verify_with_commitment() {
+        test_verify_with_commitment::<Bn254>();
+    }
+
+    #[test]
+    fn prove_and_verify_low_memory() {
+        test_low_memory_prover::<Bn254>();
+    }
+
+    #[test]
+    fn load_wrong_curve() {
+        test_load_wrong_curve::<Bn254, ark_bls12_377::Bls12_377>();
+    }
+
+    #[test]
+    fn public_input_bytes() {
+        test_public_input_bytes::<Bn254>();
+    }
+
+    #[test]
+    fn lagrange_basis_key() {
+        test_lagrange_basis_key::<Bn254>();
+    }
+
+    #[test]
+    fn prove_and_verify() {
+        test_prove_and_verify::<Bn254>(100);
+    }
+
+    #[cfg(feature = "glv")]
+    #[test]
+    fn glv() {
+        super::test_glv();
+    }
+
+    #[test]
+    fn generated_verifier() {
+        super::test_generated_verifier();
     }
 }
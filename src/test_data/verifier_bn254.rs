@@ -0,0 +1,94 @@
+//! A Groth16 verifier for a fixed verifying key, generated by
+//! darklake-groth16. Do not edit.
+
+#![no_std]
+
+use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup};
+use ark_ff::Zero;
+use ark_serialize::{CanonicalDeserialize, Compress, Validate};
+
+type Curve = ark_bn254::Bn254;
+type G1 = <Curve as Pairing>::G1Affine;
+type G2 = <Curve as Pairing>::G2Affine;
+
+/// The scalar field of the public inputs.
+pub type Fr = <Curve as Pairing>::ScalarField;
+
+/// The number of public inputs.
+pub const NUM_INPUTS: usize = 1;
+
+static ALPHA_G1: [u8; 32] = [
+    0xe5, 0x73, 0x14, 0xeb, 0x0d, 0x2d, 0x4a, 0xce, 0xf7, 0xa0, 0xb5, 0x63,
+    0x06, 0xa4, 0xac, 0x1d, 0xc9, 0x9b, 0x9a, 0x1d, 0xc1, 0x5a, 0x34, 0xdc,
+    0x54, 0x9a, 0x05, 0x21, 0x71, 0xbd, 0x98, 0x1b,
+];
+static BETA_G2: [u8; 64] = [
+    0x1e, 0x65, 0x33, 0xaf, 0x92, 0xc3, 0x83, 0xbe, 0x56, 0xb3, 0x9f, 0x05,
+    0x20, 0xf0, 0xc3, 0xf5, 0x71, 0x3f, 0x40, 0x4c, 0xc5, 0x05, 0xf4, 0x88,
+    0x7a, 0x88, 0x22, 0x4f, 0xbf, 0x49, 0x56, 0x2d, 0xa2, 0xf9, 0x48, 0x12,
+    0x9e, 0x08, 0x0d, 0x36, 0x75, 0x95, 0xfc, 0xc2, 0xf8, 0xa6, 0xbe, 0xee,
+    0x2c, 0x08, 0x8f, 0x4e, 0x77, 0xfd, 0xfd, 0xb9, 0xed, 0xaa, 0xeb, 0x4b,
+    0x40, 0x7d, 0x38, 0x16,
+];
+static GAMMA_G2: [u8; 64] = [
+    0x17, 0xe4, 0x2d, 0xed, 0x92, 0x42, 0x36, 0xcb, 0xb7, 0xa8, 0x2b, 0xa7,
+    0x4b, 0x9a, 0xe3, 0x19, 0x8a, 0xee, 0xf6, 0x33, 0xe2, 0x90, 0xa9, 0x93,
+    0x1e, 0xd3, 0x96, 0xa6, 0xe6, 0x10, 0x9d, 0x1a, 0xbb, 0x86, 0x15, 0xf6,
+    0xe3, 0xdf, 0xff, 0x68, 0xed, 0x2b, 0xe3, 0x2c, 0xe4, 0x03, 0x5b, 0x12,
+    0xe8, 0x57, 0x92, 0x79, 0x5c, 0x2f, 0x87, 0xbc, 0x0d, 0x29, 0x99, 0xcf,
+    0x9d, 0x2a, 0x81, 0x22,
+];
+static DELTA_G2: [u8; 64] = [
+    0x47, 0x95, 0xe7, 0xb7, 0x2c, 0x7b, 0xe5, 0x95, 0x54, 0xce, 0x11, 0x57,
+    0xac, 0x2d, 0xb4, 0x9d, 0x27, 0x57, 0xe8, 0x1b, 0xae, 0x97, 0x50, 0x5a,
+    0x0d, 0x81, 0xcc, 0xbe, 0x88, 0xbe, 0xb1, 0x2f, 0x3e, 0x2e, 0x2c, 0x16,
+    0xd4, 0x2c, 0xc8, 0x21, 0xa6, 0x26, 0x63, 0xa6, 0xcd, 0x59, 0xdc, 0xfa,
+    0x62, 0x1b, 0x9a, 0xa7, 0x47, 0x2d, 0xf5, 0x4a, 0x6f, 0x13, 0x50, 0x0c,
+    0x1d, 0xea, 0x61, 0x1e,
+];
+static GAMMA_ABC_G1: [[u8; 32]; 2] = [
+    [
+        0x95, 0xe4, 0x9c, 0x37, 0x56, 0x50, 0xda, 0x0c, 0x7a, 0x6b, 0x47, 0xd9,
+        0x66, 0xf2, 0x8a, 0x6f, 0x55, 0x95, 0x86, 0x04, 0x81, 0xe9, 0x4c, 0xf8,
+        0xd3, 0x1c, 0x01, 0x3a, 0x10, 0x47, 0xf4, 0x93,
+    ],
+    [
+        0x2e, 0x0b, 0x4d, 0x59, 0x94, 0x8b, 0x10, 0xca, 0xb4, 0x25, 0x0b, 0x62,
+        0x2a, 0x1f, 0xfb, 0xf3, 0xbe, 0x92, 0x55, 0x39, 0x4e, 0x4c, 0xad, 0x9d,
+        0xd2, 0x4a, 0x5a, 0xaa, 0x44, 0x64, 0xc1, 0x24,
+    ],
+];
+
+/// Deserializes a point of the verifying key.
+fn point<P: CanonicalDeserialize>(bytes: &[u8]) -> P {
+    P::deserialize_with_mode(bytes, Compress::Yes, Validate::No).unwrap()
+}
+
+/// Verifies a proof, in the compressed serialization of arkworks, against
+/// the static inputs followed by the variable inputs. Returns `false` if the
+/// proof is malformed or if there are not [`NUM_INPUTS`] inputs.
+pub fn verify(proof_bytes: &[u8], inputs: &[Fr]) -> bool {
+    if inputs.len() != NUM_INPUTS {
+        return false;
+    }
+    let mut reader = proof_bytes;
+    let (a, b, c) = match (
+        G1::deserialize_compressed(&mut reader),
+        G2::deserialize_compressed(&mut reader),
+        G1::deserialize_compressed(&mut reader),
+    ) {
+        (Ok(a), Ok(b), Ok(c)) if reader.is_empty() => (a, b, c),
+        _ => return false,
+    };
+
+    let mut prepared_inputs = point::<G1>(&GAMMA_ABC_G1[0]).into_group();
+    for (base, input) in GAMMA_ABC_G1[1..].iter().zip(inputs) {
+        prepared_inputs += point::<G1>(base) * input;
+    }
+
+    Curve::multi_pairing(
+        [a, -point::<G1>(&ALPHA_G1), -prepared_inputs.into_affine(), -c],
+        [b, point::<G2>(&BETA_G2), point::<G2>(&GAMMA_G2), point::<G2>(&DELTA_G2)],
+    )
+    .is_zero()
+}
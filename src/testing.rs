@@ -0,0 +1,184 @@
+use ark_ff::PrimeField;
+use ark_relations::{
+    lc,
+    r1cs::{
+        ConstraintSynthesizer, ConstraintSystemRef, LinearCombination, Result as R1CSResult,
+        SynthesisError, Variable,
+    },
+};
+use ark_std::{
+    marker::PhantomData,
+    rand::{rngs::StdRng, Rng, SeedableRng},
+    vec::Vec,
+};
+
+/// A synthetic circuit of configurable size and shape, to generate
+/// load-test workloads without a real statement.
+///
+/// The circuit has [`Self::num_inputs`] public inputs and at least one
+/// witness variable per constraint. The variables that are not the output
+/// of a constraint, the leaves, take pseudo-random values. Each constraint
+/// multiplies two linear combinations of [`Self::density`] terms into a new
+/// witness variable, and the constraints form chains of
+/// [`Self::depth`] multiplications, the output of each constraint of a
+/// chain being a term of the next one. The other terms and all the
+/// coefficients are drawn from [`Self::seed`], so that circuits with the
+/// same parameters have the same matrices.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SyntheticCircuit<F: PrimeField> {
+    /// The number of constraints.
+    pub num_constraints: usize,
+    /// The number of witness variables, raised to the number of constraints
+    /// if lower.
+    pub num_variables: usize,
+    /// The number of public inputs, excluding the constant one.
+    pub num_inputs: usize,
+    /// The number of terms of the left and right linear combinations of each
+    /// constraint, at least one.
+    pub density: usize,
+    /// The number of multiplications of each chain of constraints, at least
+    /// one.
+    pub depth: usize,
+    /// The seed of the matrices.
+    pub seed: u64,
+    /// The seed of the values of the leaves, or `None` during setup.
+    pub assignment: Option<u64>,
+    _field: PhantomData<F>,
+}
+
+impl<F: PrimeField> SyntheticCircuit<F> {
+    /// Creates a circuit of `num_constraints` constraints without a witness,
+    /// for setup, with one public input, one witness variable per
+    /// constraint, one term per linear combination and independent
+    /// constraints.
+    pub fn blank(num_constraints: usize) -> Self {
+        Self {
+            num_constraints,
+            num_variables: num_constraints,
+            num_inputs: 1,
+            density: 1,
+            depth: 1,
+            seed: 0,
+            assignment: None,
+            _field: PhantomData,
+        }
+    }
+
+    /// Sets the number of witness variables.
+    pub fn with_variables(mut self, num_variables: usize) -> Self {
+        self.num_variables = num_variables;
+        self
+    }
+
+    /// Sets the number of public inputs.
+    pub fn with_inputs(mut self, num_inputs: usize) -> Self {
+        self.num_inputs = num_inputs;
+        self
+    }
+
+    /// Sets the number of terms of each linear combination.
+    pub fn with_density(mut self, density: usize) -> Self {
+        self.density = density.max(1);
+        self
+    }
+
+    /// Sets the number of multiplications of each chain of constraints.
+    pub fn with_depth(mut self, depth: usize) -> Self {
+        self.depth = depth.max(1);
+        self
+    }
+
+    /// Sets the seed of the matrices.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Sets the seed of the values of the leaves, for proving.
+    pub fn with_assignment(mut self, assignment: u64) -> Self {
+        self.assignment = Some(assignment);
+        self
+    }
+
+    /// Returns the number of witness variables that are not the output of a
+    /// constraint.
+    fn num_free_variables(&self) -> usize {
+        self.num_variables.saturating_sub(self.num_constraints)
+    }
+
+    /// Returns the values of the leaves: the public inputs followed by the
+    /// free witness variables.
+    fn leaf_values(&self) -> Option<Vec<F>> {
+        let mut rng = StdRng::seed_from_u64(self.assignment?);
+        Some(
+            (0..self.num_inputs + self.num_free_variables())
+                .map(|_| F::rand(&mut rng))
+                .collect(),
+        )
+    }
+
+    /// Returns the public inputs of the circuit, or `None` without an
+    /// assignment.
+    pub fn public_inputs(&self) -> Option<Vec<F>> {
+        let mut values = self.leaf_values()?;
+        values.truncate(self.num_inputs);
+        Some(values)
+    }
+}
+
+impl<F: PrimeField> ConstraintSynthesizer<F> for SyntheticCircuit<F> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> R1CSResult<()> {
+        let leaf_values = self.leaf_values();
+        let leaf_value = |i: usize| {
+            leaf_values
+                .as_ref()
+                .map(|values| values[i])
+                .ok_or(SynthesisError::AssignmentMissing)
+        };
+
+        // The leaves, with the constant one, and their values.
+        let mut leaves = Vec::with_capacity(1 + self.num_inputs + self.num_free_variables());
+        leaves.push((Variable::One, Some(F::one())));
+        for i in 0..self.num_inputs {
+            let variable = cs.new_input_variable(|| leaf_value(i))?;
+            leaves.push((variable, leaf_value(i).ok()));
+        }
+        for i in self.num_inputs..self.num_inputs + self.num_free_variables() {
+            let variable = cs.new_witness_variable(|| leaf_value(i))?;
+            leaves.push((variable, leaf_value(i).ok()));
+        }
+
+        let density = self.density.max(1);
+        let depth = self.depth.max(1);
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let combination = |rng: &mut StdRng, first: Option<(Variable, Option<F>)>| {
+            let mut lc = LinearCombination::zero();
+            let mut value = Some(F::zero());
+            let mut terms: Vec<_> = first.into_iter().collect();
+            while terms.len() < density {
+                terms.push(leaves[rng.gen_range(0..leaves.len())]);
+            }
+            for (variable, variable_value) in terms {
+                let coefficient = F::rand(rng);
+                lc += (coefficient, variable);
+                value = value.zip(variable_value).map(|(v, x)| v + coefficient * x);
+            }
+            (lc, value)
+        };
+
+        let mut previous = None;
+        for i in 0..self.num_constraints {
+            if i % depth == 0 {
+                previous = None;
+            }
+            let (a, a_value) = combination(&mut rng, previous);
+            let (b, b_value) = combination(&mut rng, None);
+            let value = a_value.zip(b_value).map(|(a, b)| a * b);
+            let output =
+                cs.new_witness_variable(|| value.ok_or(SynthesisError::AssignmentMissing))?;
+            cs.enforce_constraint(a, b, lc!() + output)?;
+            previous = Some((output, value));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,219 @@
+use crate::{hash::sha256, loader::CurveLayout, Proof};
+use ark_ec::pairing::Pairing;
+use ark_ff::PrimeField;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, SerializationError};
+use ark_std::{
+    fmt::{self, Write},
+    format,
+    string::String,
+    vec::Vec,
+};
+
+/// The kind tag of proofs in the text encoding.
+const PROOF_KIND: &str = "groth16proof";
+
+/// The kind tag of public inputs in the text encoding.
+const INPUTS_KIND: &str = "groth16inputs";
+
+/// The number of bytes of the checksum of the text encoding.
+const CHECKSUM_SIZE: usize = 4;
+
+/// The tags of the curves known to the text encoding, with the layout and
+/// the size of the scalar field of their group elements, which tell apart
+/// the curves sharing a layout.
+const CURVE_TAGS: &[(&str, CurveLayout, u32)] = &[
+    ("bn254", CurveLayout::new(32, 64), 254),
+    ("bls12381", CurveLayout::new(48, 96), 255),
+    ("bls12377", CurveLayout::new(48, 96), 253),
+    ("bw6761", CurveLayout::new(96, 96), 377),
+];
+
+/// An error when decoding a proof or inputs from their text encoding.
+#[derive(Debug)]
+pub enum TextError {
+    /// The text does not have the form `<kind>_<curve>_<hex>_<checksum>` of
+    /// the encoding, or its hex digits are not lowercase.
+    Malformed,
+    /// The text encodes another kind of object.
+    WrongKind {
+        /// The kind tag of the object being decoded.
+        expected: &'static str,
+        /// The kind tag of the text.
+        found: String,
+    },
+    /// The text encodes an object over another curve.
+    WrongCurve {
+        /// The tag of the curve of the object being decoded.
+        expected: String,
+        /// The curve tag of the text.
+        found: String,
+    },
+    /// The text does not match its checksum, e.g. because it was truncated
+    /// or altered when copied.
+    ChecksumMismatch,
+    /// The payload does not encode a valid object.
+    Serialization(SerializationError),
+}
+
+impl fmt::Display for TextError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TextError::Malformed => write!(f, "text is not a canonical encoding"),
+            TextError::WrongKind { expected, found } => {
+                write!(f, "expected a {} encoding, found {}", expected, found)
+            },
+            TextError::WrongCurve { expected, found } => {
+                write!(f, "expected an object over {}, found {}", expected, found)
+            },
+            TextError::ChecksumMismatch => write!(f, "text does not match its checksum"),
+            TextError::Serialization(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for TextError {}
+
+impl From<SerializationError> for TextError {
+    fn from(e: SerializationError) -> Self {
+        TextError::Serialization(e)
+    }
+}
+
+/// Returns the tag of the curves of `E` in the text encoding: its name if
+/// known, e.g. `bn254`, or the sizes of its elements, e.g. `g48r253`.
+pub fn curve_tag<E: Pairing>() -> String {
+    let layout = CurveLayout::of::<E>();
+    let bits = E::ScalarField::MODULUS_BIT_SIZE;
+    match CURVE_TAGS
+        .iter()
+        .find(|(_, known_layout, known_bits)| *known_layout == layout && *known_bits == bits)
+    {
+        Some((tag, ..)) => String::from(*tag),
+        None => format!("g{}r{}", layout.g1_size, bits),
+    }
+}
+
+/// Returns the checksum of the text encoding of `payload`, bound to its
+/// kind and curve tags.
+fn checksum(prefix: &str, payload: &[u8]) -> [u8; CHECKSUM_SIZE] {
+    let mut data = Vec::with_capacity(prefix.len() + payload.len());
+    data.extend_from_slice(prefix.as_bytes());
+    data.extend_from_slice(payload);
+    let mut checksum = [0u8; CHECKSUM_SIZE];
+    checksum.copy_from_slice(&sha256(&data)[..CHECKSUM_SIZE]);
+    checksum
+}
+
+pub(crate) fn push_hex(text: &mut String, bytes: &[u8]) {
+    for byte in bytes {
+        write!(text, "{:02x}", byte).expect("writing to a string cannot fail");
+    }
+}
+
+/// Decodes lowercase hex digits, rejecting uppercase ones so that each
+/// object has a single encoding.
+fn parse_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    let digit = |c: u8| match c {
+        b'0'..=b'9' => Some(c - b'0'),
+        b'a'..=b'f' => Some(c - b'a' + 10),
+        _ => None,
+    };
+    hex.as_bytes()
+        .chunks(2)
+        .map(|pair| Some((digit(pair[0])? << 4) | digit(pair[1])?))
+        .collect()
+}
+
+/// Encodes `value` as `<kind>_<curve>_<hex>_<checksum>`.
+fn encode<E: Pairing>(kind: &str, value: &impl CanonicalSerialize) -> String {
+    let mut payload = Vec::new();
+    value
+        .serialize_compressed(&mut payload)
+        .expect("serializing to a vector does not fail");
+    let mut text = format!("{}_{}_", kind, curve_tag::<E>());
+    let checksum = checksum(&text, &payload);
+    push_hex(&mut text, &payload);
+    text.push('_');
+    push_hex(&mut text, &checksum);
+    text
+}
+
+/// Decodes a value encoded by [`encode`], ignoring surrounding whitespace.
+fn decode<E: Pairing, T: CanonicalDeserialize>(
+    kind: &'static str,
+    text: &str,
+) -> Result<T, TextError> {
+    let mut parts = text.trim().split('_');
+    let (Some(found_kind), Some(curve), Some(payload), Some(checksum), None) = (
+        parts.next(),
+        parts.next(),
+        parts.next(),
+        parts.next(),
+        parts.next(),
+    ) else {
+        return Err(TextError::Malformed);
+    };
+    if found_kind != kind {
+        return Err(TextError::WrongKind {
+            expected: kind,
+            found: String::from(found_kind),
+        });
+    }
+    let expected_curve = curve_tag::<E>();
+    if curve != expected_curve {
+        return Err(TextError::WrongCurve {
+            expected: expected_curve,
+            found: String::from(curve),
+        });
+    }
+
+    let payload = parse_hex(payload).ok_or(TextError::Malformed)?;
+    let checksum = parse_hex(checksum)
+        .filter(|checksum| checksum.len() == CHECKSUM_SIZE)
+        .ok_or(TextError::Malformed)?;
+    let prefix = format!("{}_{}_", kind, curve);
+    if checksum != self::checksum(&prefix, &payload) {
+        return Err(TextError::ChecksumMismatch);
+    }
+
+    let mut reader = &payload[..];
+    let value = T::deserialize_compressed(&mut reader)?;
+    if !reader.is_empty() {
+        return Err(TextError::Malformed);
+    }
+    Ok(value)
+}
+
+impl<E: Pairing> Proof<E> {
+    /// Returns the canonical text encoding of the proof, for logs, support
+    /// tickets and command lines: `groth16proof_<curve>_<hex>_<checksum>`,
+    /// with the lowercase hex of the compressed proof, the
+    /// [tag](curve_tag) of its curve, and the first 4 bytes of the SHA-256
+    /// hash of the rest of the text as a checksum.
+    pub fn to_hex(&self) -> String {
+        encode::<E>(PROOF_KIND, self)
+    }
+
+    /// Decodes a proof from the encoding of [`Self::to_hex`], ignoring
+    /// surrounding whitespace. Points are checked to be on the curve and
+    /// in the right subgroup.
+    pub fn from_hex(text: &str) -> Result<Self, TextError> {
+        decode::<E, _>(PROOF_KIND, text)
+    }
+}
+
+/// Returns the canonical text encoding of public inputs over `E`, like
+/// [`Proof::to_hex`]: `groth16inputs_<curve>_<hex>_<checksum>`.
+pub fn inputs_to_hex<E: Pairing>(inputs: &[E::ScalarField]) -> String {
+    encode::<E>(INPUTS_KIND, &inputs)
+}
+
+/// Decodes public inputs over `E` from the encoding of [`inputs_to_hex`],
+/// ignoring surrounding whitespace.
+pub fn inputs_from_hex<E: Pairing>(text: &str) -> Result<Vec<E::ScalarField>, TextError> {
+    decode::<E, _>(INPUTS_KIND, text)
+}
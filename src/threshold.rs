@@ -0,0 +1,205 @@
+use crate::{
+    msm::{mul_secret, CpuMsm, MsmBackend},
+    prover::ProverConfig,
+    r1cs_to_qap::R1CSToQAP,
+    rng::BlindingFactors,
+    Groth16, Proof, ProvingKey,
+};
+use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup};
+use ark_ff::{PrimeField, Zero};
+use ark_relations::r1cs::{ConstraintSynthesizer, Result as R1CSResult, SynthesisError};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::vec::Vec;
+
+/// The commitment of a party to the `s` of its share of the blinding
+/// factors, sent to the [`ThresholdProver`] before the proof is computed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct ShareCommitment<E: Pairing> {
+    /// The share `s_i` times `δ` in `G1`.
+    pub s_delta_g1: E::G1Affine,
+}
+
+/// The unblinded elements of a proof, sent by the [`ThresholdProver`] to
+/// every party along with the sum of the commitments of the parties.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct ShareChallenge<E: Pairing> {
+    /// The `A` element of the proof without blinding.
+    pub a: E::G1Affine,
+    /// The `B` element of the proof in `G1` without blinding.
+    pub b_g1: E::G1Affine,
+    /// The sum of the [`ShareCommitment`]s, `s` times `δ` in `G1`.
+    pub s_delta_g1: E::G1Affine,
+}
+
+/// The contribution of the share of a party to the blinding of a proof.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct PartialProof<E: Pairing> {
+    /// The share `r_i` times `δ` in `G1`, added to `A`.
+    pub a: E::G1Affine,
+    /// The share `s_i` times `δ` in `G2`, added to `B`.
+    pub b: E::G2Affine,
+    /// The share of the blinding of `C`, `s_i A + r_i (B + s δ)` for the
+    /// unblinded `A` and `B`.
+    pub c: E::G1Affine,
+}
+
+/// The coordinator of a proof whose blinding factors `r` and `s` are the
+/// sums of the shares of several parties, so that none of them, including
+/// the coordinator, learns them, e.g. to split proving between a user device
+/// and a server. Unlike the blinding factors, the witness is not shared: the
+/// coordinator synthesizes the circuit.
+///
+/// Each party holds its share as [`BlindingFactors`], and the proof is
+/// computed in two rounds:
+///
+/// 1. every party sends the [`ShareCommitment`] from
+///    [`Groth16::commit_blinding_share`] to [`Self::add_commitment`];
+/// 2. every party receives the [`Self::challenge`] and sends the
+///    [`PartialProof`] from [`Groth16::prove_blinding_share`] to
+///    [`Self::add_partial_proof`].
+///
+/// The proof of [`Self::finish`] is the proof with the summed blinding
+/// factors. A party deviating from the protocol makes it invalid, so it must
+/// be verified before use.
+#[derive(Clone, Debug)]
+pub struct ThresholdProver<E: Pairing> {
+    a: E::G1,
+    b_g1: E::G1,
+    b_g2: E::G2,
+    c: E::G1,
+    s_delta_g1: E::G1,
+    num_commitments: usize,
+    num_partial_proofs: usize,
+}
+
+impl<E: Pairing> ThresholdProver<E> {
+    /// Adds the commitment of a party. Fails with
+    /// [`SynthesisError::Unsatisfiable`] once a partial proof was added, as
+    /// it was computed against the previous commitments.
+    pub fn add_commitment(&mut self, commitment: &ShareCommitment<E>) -> R1CSResult<()> {
+        if self.num_partial_proofs > 0 {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        self.s_delta_g1 += commitment.s_delta_g1;
+        self.num_commitments += 1;
+        Ok(())
+    }
+
+    /// Returns the challenge from which the parties compute their partial
+    /// proofs, once all commitments are added.
+    pub fn challenge(&self) -> ShareChallenge<E> {
+        ShareChallenge {
+            a: self.a.into_affine(),
+            b_g1: self.b_g1.into_affine(),
+            s_delta_g1: self.s_delta_g1.into_affine(),
+        }
+    }
+
+    /// Adds the partial proof of a party.
+    pub fn add_partial_proof(&mut self, partial: &PartialProof<E>) {
+        self.a += partial.a;
+        self.b_g2 += partial.b;
+        self.c += partial.c;
+        self.num_partial_proofs += 1;
+    }
+
+    /// Returns the proof. Fails with [`SynthesisError::AssignmentMissing`]
+    /// unless every committed party added its partial proof, and at least
+    /// one did, as the proof would not be zero-knowledge otherwise.
+    pub fn finish(self) -> R1CSResult<Proof<E>> {
+        if self.num_commitments == 0 || self.num_partial_proofs != self.num_commitments {
+            return Err(SynthesisError::AssignmentMissing);
+        }
+        Ok(Proof {
+            a: self.a.into_affine(),
+            b: self.b_g2.into_affine(),
+            c: self.c.into_affine(),
+        })
+    }
+}
+
+impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
+    /// Synthesizes `circuit` and computes the unblinded elements of its
+    /// proof, returning the coordinator of a proof with shared blinding
+    /// factors.
+    pub fn threshold_prover<C>(pk: &ProvingKey<E>, circuit: C) -> R1CSResult<ThresholdProver<E>>
+    where
+        C: ConstraintSynthesizer<E::ScalarField>,
+    {
+        let (cs, h) = Self::synthesize_and_witness_map(circuit, pk, &ProverConfig::default())?;
+        let prover = cs.borrow().unwrap();
+        let h_acc = Self::h_accumulator(pk, &h, &CpuMsm);
+        let aux_assignment = prover
+            .witness_assignment
+            .iter()
+            .map(|s| s.into_bigint())
+            .collect::<Vec<_>>();
+        let l_aux_acc: E::G1 = CpuMsm.msm_bigint(&pk.l_query, &aux_assignment);
+        let assignment = prover.instance_assignment[1..]
+            .iter()
+            .map(|s| s.into_bigint())
+            .chain(aux_assignment)
+            .collect::<Vec<_>>();
+
+        let a = Self::calculate_coeff(
+            E::G1::zero(),
+            &pk.a_query,
+            pk.vk.alpha_g1,
+            &assignment,
+            &CpuMsm,
+        );
+        let b_g1 = Self::calculate_coeff(
+            E::G1::zero(),
+            &pk.b_g1_query,
+            pk.beta_g1,
+            &assignment,
+            &CpuMsm,
+        );
+        let b_g2 = Self::calculate_coeff(
+            E::G2::zero(),
+            &pk.b_g2_query,
+            pk.vk.beta_g2,
+            &assignment,
+            &CpuMsm,
+        );
+
+        Ok(ThresholdProver {
+            a,
+            b_g1,
+            b_g2,
+            c: l_aux_acc + h_acc,
+            s_delta_g1: E::G1::zero(),
+            num_commitments: 0,
+            num_partial_proofs: 0,
+        })
+    }
+
+    /// Returns the commitment to the `s` of the blinding `share` of a party.
+    pub fn commit_blinding_share(
+        pk: &ProvingKey<E>,
+        share: &BlindingFactors<E::ScalarField>,
+    ) -> ShareCommitment<E> {
+        ShareCommitment {
+            s_delta_g1: mul_secret(pk.delta_g1.into_group(), share.s).into_affine(),
+        }
+    }
+
+    /// Returns the contribution of the blinding `share` of a party to the
+    /// proof of `challenge`. The share must be the one committed to with
+    /// [`Self::commit_blinding_share`].
+    pub fn prove_blinding_share(
+        pk: &ProvingKey<E>,
+        share: &BlindingFactors<E::ScalarField>,
+        challenge: &ShareChallenge<E>,
+    ) -> PartialProof<E> {
+        // The shares of C sum to s A + r B + r s δ, which is the blinding
+        // s (A + r δ) + r (B + s δ) - r s δ of the blinded A and B.
+        let c = mul_secret(challenge.a.into_group(), share.s)
+            + mul_secret(challenge.b_g1 + challenge.s_delta_g1, share.r);
+        PartialProof {
+            a: mul_secret(pk.delta_g1.into_group(), share.r).into_affine(),
+            b: mul_secret(pk.vk.delta_g2.into_group(), share.s).into_affine(),
+            c: c.into_affine(),
+        }
+    }
+}
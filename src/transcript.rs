@@ -0,0 +1,93 @@
+use crate::{PreparedVerifyingKey, Proof, VerifyingKey};
+use ark_crypto_primitives::sponge::CryptographicSponge;
+use ark_ec::pairing::Pairing;
+use ark_ff::PrimeField;
+use ark_serialize::CanonicalSerialize;
+use ark_std::vec::Vec;
+
+/// Domain separator prepended to the encoding of a [`Proof`].
+pub const PROOF_LABEL: &[u8] = b"darklake-groth16/proof";
+/// Domain separator prepended to the encoding of a [`VerifyingKey`].
+pub const VERIFYING_KEY_LABEL: &[u8] = b"darklake-groth16/vk";
+/// Domain separator prepended to the encoding of public inputs.
+pub const PUBLIC_INPUTS_LABEL: &[u8] = b"darklake-groth16/inputs";
+/// Domain separator prepended to the encoding of the prepared inputs of a
+/// batch verified with
+/// [`Groth16::verify_same_inputs_batch_with_sponge`](crate::Groth16::verify_same_inputs_batch_with_sponge).
+pub const BATCH_INPUTS_LABEL: &[u8] = b"darklake-groth16/batch-inputs";
+/// Domain separator of the coefficient combining a proof with the proof of
+/// knowledge of its input commitment in
+/// [`Groth16::verify_with_commitment`](crate::Groth16::verify_with_commitment).
+pub const COMMITMENT_LABEL: &[u8] = b"darklake-groth16/commitment";
+
+/// Returns `label` followed by the compressed canonical serialization of
+/// `value`.
+fn encode(label: &[u8], value: &impl CanonicalSerialize) -> Vec<u8> {
+    let mut bytes = label.to_vec();
+    value
+        .serialize_compressed(&mut bytes)
+        .expect("serializing into a vector cannot fail");
+    bytes
+}
+
+impl<E: Pairing> Proof<E> {
+    /// Returns the canonical transcript encoding of `self`: [`PROOF_LABEL`]
+    /// followed by the compressed serialization of the proof.
+    pub fn transcript_bytes(&self) -> Vec<u8> {
+        encode(PROOF_LABEL, self)
+    }
+
+    /// Absorbs the canonical transcript encoding of `self` into `sponge`.
+    pub fn absorb_into(&self, sponge: &mut impl CryptographicSponge) {
+        sponge.absorb(&self.transcript_bytes());
+    }
+}
+
+impl<E: Pairing> VerifyingKey<E> {
+    /// Returns the canonical transcript encoding of `self`:
+    /// [`VERIFYING_KEY_LABEL`] followed by the compressed serialization of
+    /// the key.
+    pub fn transcript_bytes(&self) -> Vec<u8> {
+        encode(VERIFYING_KEY_LABEL, self)
+    }
+
+    /// Absorbs the canonical transcript encoding of `self` into `sponge`.
+    pub fn absorb_into(&self, sponge: &mut impl CryptographicSponge) {
+        sponge.absorb(&self.transcript_bytes());
+    }
+}
+
+impl<E: Pairing> PreparedVerifyingKey<E> {
+    /// Absorbs the canonical transcript encoding of the underlying verifying
+    /// key into `sponge`.
+    pub fn absorb_into(&self, sponge: &mut impl CryptographicSponge) {
+        self.vk.absorb_into(sponge);
+    }
+}
+
+/// Returns the canonical transcript encoding of public inputs:
+/// [`PUBLIC_INPUTS_LABEL`] followed by the compressed serializations of the
+/// static and of the variable inputs, each prefixed by its length.
+pub fn public_inputs_transcript_bytes<F: PrimeField>(
+    static_inputs: &[F],
+    variable_inputs: &[F],
+) -> Vec<u8> {
+    let mut bytes = encode(PUBLIC_INPUTS_LABEL, &static_inputs);
+    variable_inputs
+        .serialize_compressed(&mut bytes)
+        .expect("serializing into a vector cannot fail");
+    bytes
+}
+
+/// Absorbs the canonical transcript encoding of the public inputs into
+/// `sponge`.
+pub fn absorb_public_inputs<F: PrimeField>(
+    static_inputs: &[F],
+    variable_inputs: &[F],
+    sponge: &mut impl CryptographicSponge,
+) {
+    sponge.absorb(&public_inputs_transcript_bytes(
+        static_inputs,
+        variable_inputs,
+    ));
+}
@@ -1,15 +1,249 @@
-use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup};
-use ark_ff::PrimeField;
+use ark_ec::{
+    pairing::{MillerLoopOutput, Pairing},
+    AffineRepr, CurveGroup, VariableBaseMSM,
+};
+use ark_ff::{Field, One, PrimeField, Zero};
 
-use crate::{r1cs_to_qap::R1CSToQAP, Groth16};
+#[cfg(feature = "glv")]
+use crate::glv;
+#[cfg(feature = "verifier-audit")]
+use crate::verifier_audit;
+use crate::{
+    inputs::{Endianness, PublicInputs},
+    msm::{msm_bounded_bits, BigInt},
+    preparer::ProofPreparer,
+    r1cs_to_qap::R1CSToQAP,
+    transcript::{BATCH_INPUTS_LABEL, COMMITMENT_LABEL},
+    CommitmentKey, CommitmentVerifyingKey, Groth16, InputCommitment,
+};
+use ark_crypto_primitives::sponge::{CryptographicSponge, FieldElementSize};
+#[cfg(feature = "verifier-audit")]
+use ark_ec::pairing::PairingOutput;
+#[cfg(feature = "glv")]
+use ark_ec::{scalar_mul::glv::GLVConfig, CurveConfig};
+use ark_serialize::CanonicalSerialize;
 
 use super::{PreparedVerifyingKey, Proof, VerifyingKey};
 
 use ark_relations::r1cs::{Result as R1CSResult, SynthesisError};
 
+use ark_std::{
+    borrow::{Borrow, Cow},
+    cmp::Ordering,
+    fmt,
+    rand::Rng,
+    vec::Vec,
+};
 use core::ops::{AddAssign, Neg};
+#[cfg(feature = "std")]
+use std::time::{Duration, Instant};
 
-/// Prepare the verifying key `vk` for use in proof verification.
+/// The number of public inputs from which they are aggregated with
+/// [`msm_bounded_bits`], whose windows are sized to the longest input.
+/// Below this, double-and-add, which already skips the leading zero bits of
+/// each input, is cheaper.
+const MIN_WINDOWED_INPUTS: usize = 6;
+
+/// The number of public inputs aggregated per MSM by
+/// [`Groth16::prepare_inputs_streaming`] when no chunk size is given.
+pub const DEFAULT_INPUT_CHUNK_SIZE: usize = 1 << 12;
+
+/// The outcome of a verification, with the time spent in each of its steps.
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VerificationReport {
+    /// Whether the proof was accepted.
+    pub accepted: bool,
+    /// The time spent aggregating the public inputs.
+    pub input_msm_time: Duration,
+    /// The time spent in the multi-Miller loop.
+    pub miller_time: Duration,
+    /// The time spent in the final exponentiation.
+    pub final_exp_time: Duration,
+    /// The number of public inputs aggregated.
+    pub input_msm_size: usize,
+}
+
+/// How the verifier handles a number of public inputs that differs from the
+/// number expected by the verifying key.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum PaddingPolicy {
+    /// Reject inputs of the wrong length with
+    /// [`SynthesisError::MalformedVerifyingKey`].
+    #[default]
+    Error,
+    /// Extend too few inputs with trailing zeros, for circuits with optional
+    /// trailing inputs. Too many inputs are rejected.
+    PadZeros,
+    /// Drop trailing inputs beyond the expected number. Too few inputs are
+    /// rejected.
+    Truncate,
+}
+
+impl PaddingPolicy {
+    /// Returns `inputs` adjusted to `expected` elements according to
+    /// `self`.
+    pub fn apply<'a, F: PrimeField>(
+        self,
+        inputs: &'a [F],
+        expected: usize,
+    ) -> R1CSResult<Cow<'a, [F]>> {
+        match (self, inputs.len().cmp(&expected)) {
+            (_, Ordering::Equal) => Ok(Cow::Borrowed(inputs)),
+            (PaddingPolicy::PadZeros, Ordering::Less) => {
+                let mut padded = inputs.to_vec();
+                padded.resize(expected, F::zero());
+                Ok(Cow::Owned(padded))
+            },
+            (PaddingPolicy::Truncate, Ordering::Greater) => Ok(Cow::Borrowed(&inputs[..expected])),
+            _ => Err(SynthesisError::MalformedVerifyingKey),
+        }
+    }
+}
+
+/// The numbers of static and variable public inputs expected by a
+/// verifying key and given to the verifier, when they differ.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct InputCountMismatch {
+    /// The number of static inputs expected by the key.
+    pub expected_static: usize,
+    /// The number of static inputs given.
+    pub actual_static: usize,
+    /// The number of variable inputs expected by the key.
+    pub expected_variable: usize,
+    /// The number of variable inputs given.
+    pub actual_variable: usize,
+}
+
+impl fmt::Display for InputCountMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "expected {} static and {} variable inputs, got {} static and {} variable inputs",
+            self.expected_static, self.expected_variable, self.actual_static, self.actual_variable
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for InputCountMismatch {}
+
+/// An error of [`Groth16::verify_with_variables`] and the other verifiers
+/// of static and variable inputs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum VerificationError {
+    /// The verifying key is malformed or the inputs could not be prepared.
+    Synthesis(SynthesisError),
+    /// The numbers of static or variable inputs differ from the ones
+    /// expected by the verifying key.
+    InputCount(InputCountMismatch),
+}
+
+impl fmt::Display for VerificationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerificationError::Synthesis(e) => write!(f, "{}", e),
+            VerificationError::InputCount(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for VerificationError {}
+
+impl From<SynthesisError> for VerificationError {
+    fn from(e: SynthesisError) -> Self {
+        VerificationError::Synthesis(e)
+    }
+}
+
+impl From<InputCountMismatch> for VerificationError {
+    fn from(e: InputCountMismatch) -> Self {
+        VerificationError::InputCount(e)
+    }
+}
+
+/// Reports input count mismatches as
+/// [`SynthesisError::MalformedVerifyingKey`], for the [`SNARK`] verifiers
+/// whose error type is fixed.
+///
+/// [`SNARK`]: ark_crypto_primitives::snark::SNARK
+impl From<VerificationError> for SynthesisError {
+    fn from(e: VerificationError) -> Self {
+        match e {
+            VerificationError::Synthesis(e) => e,
+            VerificationError::InputCount(_) => SynthesisError::MalformedVerifyingKey,
+        }
+    }
+}
+
+/// An error of [`Groth16::verify_strict`], which rejects degenerate keys,
+/// proofs and inputs before verifying.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StrictVerificationError {
+    /// The inputs do not match the verifying key.
+    Synthesis(SynthesisError),
+    /// The numbers of static or variable inputs differ from the ones
+    /// expected by the verifying key.
+    InputCount(InputCountMismatch),
+    /// `e(alpha * G, beta * H)` is one, or `gamma * H` or `delta * H` is
+    /// the identity, so the verifying key accepts any proof.
+    DegenerateVerifyingKey,
+    /// The public inputs aggregate to the identity.
+    IdentityPreparedInputs,
+    /// `proof.a` is the identity.
+    IdentityA,
+    /// `proof.b` is the identity.
+    IdentityB,
+    /// `proof.c` is the identity.
+    IdentityC,
+}
+
+impl fmt::Display for StrictVerificationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StrictVerificationError::Synthesis(e) => write!(f, "{}", e),
+            StrictVerificationError::InputCount(e) => write!(f, "{}", e),
+            StrictVerificationError::DegenerateVerifyingKey => {
+                write!(f, "degenerate verifying key")
+            },
+            StrictVerificationError::IdentityPreparedInputs => {
+                write!(f, "public inputs aggregate to the identity")
+            },
+            StrictVerificationError::IdentityA => write!(f, "proof.a is the identity"),
+            StrictVerificationError::IdentityB => write!(f, "proof.b is the identity"),
+            StrictVerificationError::IdentityC => write!(f, "proof.c is the identity"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for StrictVerificationError {}
+
+impl From<SynthesisError> for StrictVerificationError {
+    fn from(e: SynthesisError) -> Self {
+        StrictVerificationError::Synthesis(e)
+    }
+}
+
+impl From<InputCountMismatch> for StrictVerificationError {
+    fn from(e: InputCountMismatch) -> Self {
+        StrictVerificationError::InputCount(e)
+    }
+}
+
+impl From<VerificationError> for StrictVerificationError {
+    fn from(e: VerificationError) -> Self {
+        match e {
+            VerificationError::Synthesis(e) => StrictVerificationError::Synthesis(e),
+            VerificationError::InputCount(e) => StrictVerificationError::InputCount(e),
+        }
+    }
+}
+
+/// Prepare the verifying key `vk` for use in proof verification. See
+/// [`crate::profile::prepare_verifying_key_with_profile`] to precompute more
+/// for faster verifications.
 pub fn prepare_verifying_key<E: Pairing>(vk: &VerifyingKey<E>) -> PreparedVerifyingKey<E> {
     PreparedVerifyingKey {
         vk: vk.clone(),
@@ -19,51 +253,459 @@ pub fn prepare_verifying_key<E: Pairing>(vk: &VerifyingKey<E>) -> PreparedVerify
     }
 }
 
+/// Computes the linear combinations with coefficients `coeffs` of the `A`,
+/// `B` and `C` elements of `proofs`, as a building block for protocols
+/// which batch proofs together.
+///
+/// # Panics
+///
+/// Panics if `proofs` and `coeffs` have different lengths.
+pub fn aggregate_proof_points<E: Pairing>(
+    proofs: &[Proof<E>],
+    coeffs: &[E::ScalarField],
+) -> (E::G1, E::G2, E::G1) {
+    assert_eq!(proofs.len(), coeffs.len());
+
+    let a = proofs.iter().map(|proof| proof.a).collect::<Vec<_>>();
+    let b = proofs.iter().map(|proof| proof.b).collect::<Vec<_>>();
+    let c = proofs.iter().map(|proof| proof.c).collect::<Vec<_>>();
+
+    (
+        E::G1::msm_unchecked(&a, coeffs),
+        E::G2::msm_unchecked(&b, coeffs),
+        E::G1::msm_unchecked(&c, coeffs),
+    )
+}
+
+/// The Miller loops of the verification of a proof, from
+/// [`Groth16::verify_split`], before they are combined and the final
+/// exponentiation checks them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SplitMillerLoops<E: Pairing> {
+    /// The Miller loop of `e(A, B)`.
+    pub ab: MillerLoopOutput<E>,
+    /// The Miller loop of `e(inputs, -gamma) e(C, -delta)`.
+    pub rest: MillerLoopOutput<E>,
+}
+
+impl<E: Pairing> SplitMillerLoops<E> {
+    /// Returns the product of the Miller loops, whose final exponentiation
+    /// is `e(alpha, beta)` for a valid proof.
+    pub fn combine(&self) -> MillerLoopOutput<E> {
+        MillerLoopOutput(self.ab.0 * self.rest.0)
+    }
+}
+
 impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
     /// Prepare static and variable inputs for verification without modifying the VerifyingKey structure
     pub fn prepare_inputs_with_variables(
         pvk: &PreparedVerifyingKey<E>,
         static_inputs: &[E::ScalarField],
         variable_inputs: &[E::ScalarField],
-    ) -> R1CSResult<E::G1> {
+    ) -> Result<E::G1, VerificationError> {
         // Check that the number of inputs matches the expected sizes
-        if static_inputs.len() != pvk.vk.gamma_abc_g1_static.len() - 1 {
-            return Err(SynthesisError::MalformedVerifyingKey);
-        }
-        if variable_inputs.len() != pvk.vk.gamma_abc_g1_variable.len() {
-            return Err(SynthesisError::MalformedVerifyingKey);
+        if pvk.vk.gamma_abc_g1_static.is_empty() {
+            return Err(SynthesisError::MalformedVerifyingKey.into());
         }
-    
+        pvk.check_input_counts(static_inputs.len(), variable_inputs.len())?;
+
         // Start with the constant term
         let mut g_ic = pvk.vk.gamma_abc_g1_static[0].into_group();
-        
+
+        let bases = || {
+            [
+                &pvk.vk.gamma_abc_g1_static[1..],
+                &pvk.vk.gamma_abc_g1_variable[..],
+            ]
+            .concat()
+        };
+
+        let num_inputs = static_inputs.len() + variable_inputs.len();
+        if num_inputs >= MIN_WINDOWED_INPUTS {
+            let scalars = static_inputs
+                .iter()
+                .chain(variable_inputs)
+                .map(|input| input.into_bigint())
+                .collect::<Vec<_>>();
+            g_ic.add_assign(&msm_bounded_bits::<E::G1>(&bases(), &scalars));
+            #[cfg(feature = "verifier-audit")]
+            verifier_audit::projective("prepared_inputs", &g_ic);
+            return Ok(g_ic);
+        }
+
         // Add static inputs
         for (i, static_input) in static_inputs.iter().enumerate() {
-            g_ic.add_assign(&pvk.vk.gamma_abc_g1_static[i + 1].mul_bigint(static_input.into_bigint()));
+            g_ic.add_assign(
+                &pvk.vk.gamma_abc_g1_static[i + 1].mul_bigint(static_input.into_bigint()),
+            );
+            #[cfg(feature = "verifier-audit")]
+            verifier_audit::projective("prepared_inputs.partial", &g_ic);
         }
-        
+
         // Add variable inputs (no offset needed - separate vector)
         for (i, variable_input) in variable_inputs.iter().enumerate() {
-            g_ic.add_assign(&pvk.vk.gamma_abc_g1_variable[i].mul_bigint(variable_input.into_bigint()));
+            g_ic.add_assign(
+                &pvk.vk.gamma_abc_g1_variable[i].mul_bigint(variable_input.into_bigint()),
+            );
+            #[cfg(feature = "verifier-audit")]
+            verifier_audit::projective("prepared_inputs.partial", &g_ic);
+        }
+
+        #[cfg(feature = "verifier-audit")]
+        verifier_audit::projective("prepared_inputs", &g_ic);
+        Ok(g_ic)
+    }
+
+    /// Prepares static and variable inputs like
+    /// [`Self::prepare_inputs_with_variables`], splitting each input in two
+    /// halves with the GLV endomorphism of `P`, the configuration of `G1`.
+    /// This halves the length of the scalar multiplications but branches on
+    /// the inputs, so it must not be used where inputs are secret.
+    #[cfg(feature = "glv")]
+    pub fn prepare_inputs_with_glv<P>(
+        pvk: &PreparedVerifyingKey<E>,
+        static_inputs: &[E::ScalarField],
+        variable_inputs: &[E::ScalarField],
+    ) -> Result<E::G1, VerificationError>
+    where
+        P: GLVConfig<Curve = E::G1> + CurveConfig<ScalarField = E::ScalarField>,
+    {
+        if pvk.vk.gamma_abc_g1_static.is_empty() {
+            return Err(SynthesisError::MalformedVerifyingKey.into());
         }
-        
+        pvk.check_input_counts(static_inputs.len(), variable_inputs.len())?;
+
+        let bases = [
+            &pvk.vk.gamma_abc_g1_static[1..],
+            &pvk.vk.gamma_abc_g1_variable[..],
+        ]
+        .concat();
+        let inputs = [static_inputs, variable_inputs].concat();
+        let (bases, scalars) = glv::split_scalars::<E::G1, P>(&bases, &inputs);
+        let g_ic =
+            pvk.vk.gamma_abc_g1_static[0].into_group() + Self::aggregate_inputs(&bases, &scalars);
+        #[cfg(feature = "verifier-audit")]
+        verifier_audit::projective("prepared_inputs", &g_ic);
         Ok(g_ic)
     }
 
-    /// Verify a proof with separate static and variable inputs
+    /// Returns the sum of the `scalars[i] * bases[i]`, with a windowed MSM
+    /// from [`MIN_WINDOWED_INPUTS`] products.
+    fn aggregate_inputs(bases: &[E::G1Affine], scalars: &[BigInt<E::G1>]) -> E::G1 {
+        if bases.len() >= MIN_WINDOWED_INPUTS {
+            msm_bounded_bits::<E::G1>(bases, scalars)
+        } else {
+            bases
+                .iter()
+                .zip(scalars)
+                .map(|(base, scalar)| base.mul_bigint(scalar))
+                .sum()
+        }
+    }
+
+    /// Prepare the public inputs read from `inputs`, static inputs first,
+    /// like [`Self::prepare_inputs_with_variables`], aggregating them in
+    /// MSMs of `chunk_size` inputs (at least one).
+    ///
+    /// Only a chunk of inputs and bases is held in memory at a time, so
+    /// circuits with tens of thousands of public inputs are verified
+    /// without materializing the full scalar vector, at the cost of one MSM
+    /// per chunk rather than one for all inputs. Inputs are not split with
+    /// the endomorphism of `G1`.
+    ///
+    /// At most one input past those expected by `pvk` is read, so that an
+    /// endless iterator is rejected: the [`InputCountMismatch`] then reports
+    /// a single excess variable input, whatever the length of `inputs`.
+    pub fn prepare_inputs_streaming<I>(
+        pvk: &PreparedVerifyingKey<E>,
+        inputs: I,
+        chunk_size: usize,
+    ) -> Result<E::G1, VerificationError>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<E::ScalarField>,
+    {
+        let Some((constant, static_bases)) = pvk.vk.gamma_abc_g1_static.split_first() else {
+            return Err(SynthesisError::MalformedVerifyingKey.into());
+        };
+        let mut bases = static_bases.iter().chain(&pvk.vk.gamma_abc_g1_variable);
+        let num_bases = static_bases.len() + pvk.vk.gamma_abc_g1_variable.len();
+
+        let chunk_size = chunk_size.clamp(1, num_bases.max(1));
+        let mut chunk_bases = Vec::with_capacity(chunk_size);
+        let mut chunk_scalars = Vec::with_capacity(chunk_size);
+        let mut g_ic = constant.into_group();
+        let mut num_inputs = 0;
+        for input in inputs {
+            num_inputs += 1;
+            let Some(base) = bases.next() else {
+                break;
+            };
+            chunk_bases.push(*base);
+            chunk_scalars.push(input.borrow().into_bigint());
+            if chunk_bases.len() == chunk_size {
+                g_ic += Self::aggregate_inputs(&chunk_bases, &chunk_scalars);
+                #[cfg(feature = "verifier-audit")]
+                verifier_audit::projective("prepared_inputs.partial", &g_ic);
+                chunk_bases.clear();
+                chunk_scalars.clear();
+            }
+        }
+
+        let num_static = num_inputs.min(static_bases.len());
+        pvk.check_input_counts(num_static, num_inputs - num_static)?;
+        g_ic += Self::aggregate_inputs(&chunk_bases, &chunk_scalars);
+        #[cfg(feature = "verifier-audit")]
+        verifier_audit::projective("prepared_inputs", &g_ic);
+        Ok(g_ic)
+    }
+
+    /// Verify a proof of the public inputs read from `inputs`, static
+    /// inputs first, see [`Self::prepare_inputs_streaming`].
+    pub fn verify_streaming<I>(
+        pvk: &PreparedVerifyingKey<E>,
+        proof: &Proof<E>,
+        inputs: I,
+        chunk_size: usize,
+    ) -> Result<bool, VerificationError>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<E::ScalarField>,
+    {
+        let prepared_inputs = Self::prepare_inputs_streaming(pvk, inputs, chunk_size)?;
+        Ok(Self::verify_proof_with_prepared_inputs(
+            pvk,
+            proof,
+            &prepared_inputs,
+        )?)
+    }
+
+    /// Verify a proof with separate static and variable inputs. Inputs of
+    /// the wrong length are rejected with
+    /// [`VerificationError::InputCount`], which reports which group
+    /// differs.
     pub fn verify_with_variables(
         pvk: &PreparedVerifyingKey<E>,
         proof: &Proof<E>,
         static_inputs: &[E::ScalarField],
         variable_inputs: &[E::ScalarField],
-    ) -> R1CSResult<bool> {
+    ) -> Result<bool, VerificationError> {
         // Prepare the input aggregation
-        let prepared_inputs = Self::prepare_inputs_with_variables(pvk, static_inputs, variable_inputs)?;
-        
+        let prepared_inputs =
+            Self::prepare_inputs_with_variables(pvk, static_inputs, variable_inputs)?;
+
         // Use standard verification with prepared inputs
-        Self::verify_proof_with_prepared_inputs(pvk, proof, &prepared_inputs)
+        Ok(Self::verify_proof_with_prepared_inputs(
+            pvk,
+            proof,
+            &prepared_inputs,
+        )?)
     }
-        
+
+    /// Verify a proof with separate static and variable inputs like
+    /// [`Self::verify_with_variables`], after rejecting inputs of the wrong
+    /// length with the expected and actual counts, degenerate verifying
+    /// keys, identity proof elements and public inputs aggregating to the
+    /// identity.
+    ///
+    /// None of these occur with honestly generated keys and proofs except
+    /// with negligible probability, but a degenerate verifying key accepts
+    /// arbitrary proofs.
+    ///
+    /// The pairing equation is computed from the points of `pvk.vk` which
+    /// were checked, ignoring the precomputed `alpha_g1_beta_g2`,
+    /// `gamma_g2_neg_pc` and `delta_g2_neg_pc` of `pvk`, which could
+    /// otherwise disagree with them.
+    pub fn verify_strict(
+        pvk: &PreparedVerifyingKey<E>,
+        proof: &Proof<E>,
+        static_inputs: &[E::ScalarField],
+        variable_inputs: &[E::ScalarField],
+    ) -> Result<bool, StrictVerificationError> {
+        pvk.check_input_counts(static_inputs.len(), variable_inputs.len())?;
+        let vk = &pvk.vk;
+        if vk.alpha_g1.is_zero()
+            || vk.beta_g2.is_zero()
+            || vk.gamma_g2.is_zero()
+            || vk.delta_g2.is_zero()
+        {
+            return Err(StrictVerificationError::DegenerateVerifyingKey);
+        }
+        if proof.a.is_zero() {
+            return Err(StrictVerificationError::IdentityA);
+        }
+        if proof.b.is_zero() {
+            return Err(StrictVerificationError::IdentityB);
+        }
+        if proof.c.is_zero() {
+            return Err(StrictVerificationError::IdentityC);
+        }
+
+        let prepared_inputs =
+            Self::prepare_inputs_with_variables(pvk, static_inputs, variable_inputs)?;
+        if prepared_inputs.is_zero() {
+            return Err(StrictVerificationError::IdentityPreparedInputs);
+        }
+
+        #[cfg(feature = "verifier-audit")]
+        if !audit_proof(proof) {
+            return Ok(false);
+        }
+        // e(A, B) = e(alpha, beta) * e(inputs, gamma) * e(C, delta)
+        let g1 = [
+            proof.a.into_group(),
+            -vk.alpha_g1.into_group(),
+            -prepared_inputs,
+            -proof.c.into_group(),
+        ];
+        let g2 = [proof.b, vk.beta_g2, vk.gamma_g2, vk.delta_g2];
+        Ok(E::multi_pairing(E::G1::normalize_batch(&g1), g2).is_zero())
+    }
+
+    /// Computes the Pedersen commitment to `committed_inputs` over the
+    /// [`CommitmentKey::bases`] of `ck`, with the proof of knowledge of its
+    /// opening expected by [`Self::verify_with_commitment`].
+    pub fn commit_to_inputs(
+        ck: &CommitmentKey<E>,
+        committed_inputs: &[E::ScalarField],
+    ) -> R1CSResult<InputCommitment<E>> {
+        if committed_inputs.len() != ck.bases.len() || ck.sigma_bases.len() != ck.bases.len() {
+            return Err(SynthesisError::MalformedVerifyingKey);
+        }
+
+        let commitment = E::G1::msm_unchecked(&ck.bases, committed_inputs);
+        let pok = E::G1::msm_unchecked(&ck.sigma_bases, committed_inputs);
+        Ok(InputCommitment {
+            commitment: commitment.into_affine(),
+            pok: pok.into_affine(),
+        })
+    }
+
+    /// Verify a proof whose public inputs are `inputs`, static inputs first,
+    /// followed by the inputs committed to in `commitment`, a Pedersen
+    /// commitment over the [`CommitmentKey::bases`] whose verifying part is
+    /// `ck`. The commitment is folded into the input aggregation, so the
+    /// committed inputs are never revealed to the verifier.
+    ///
+    /// The commitment is only accepted with a valid proof of knowledge of
+    /// its opening, without which any statement could be satisfied by a
+    /// commitment cancelling the other inputs. The pairing check of that
+    /// proof is combined with the one of `proof` with a coefficient hashed
+    /// from both, so that they are checked together in a single
+    /// multi-Miller loop.
+    pub fn verify_with_commitment(
+        pvk: &PreparedVerifyingKey<E>,
+        ck: &CommitmentVerifyingKey<E>,
+        proof: &Proof<E>,
+        inputs: &[E::ScalarField],
+        commitment: &InputCommitment<E>,
+    ) -> R1CSResult<bool> {
+        let num_static_inputs = pvk.num_static_inputs();
+        if inputs.len() < num_static_inputs {
+            return Err(SynthesisError::MalformedVerifyingKey);
+        }
+        let (static_inputs, variable_inputs) = inputs.split_at(num_static_inputs);
+
+        let mut prepared_inputs =
+            Self::prepare_inputs_with_variables(pvk, static_inputs, variable_inputs)?;
+        prepared_inputs.add_assign(&commitment.commitment);
+
+        #[cfg(feature = "verifier-audit")]
+        if !audit_proof(proof) {
+            return Ok(false);
+        }
+        let challenge = commitment_challenge(pvk, ck, proof, inputs, commitment);
+        let qap = E::multi_miller_loop(
+            [
+                proof.a,
+                prepared_inputs.into_affine(),
+                proof.c,
+                (commitment.commitment * challenge).into_affine(),
+                (commitment.pok * challenge).into_affine(),
+            ],
+            [
+                proof.b.into(),
+                pvk.gamma_g2_neg_pc.clone(),
+                pvk.delta_g2_neg_pc.clone(),
+                ck.sigma_neg_g2.into(),
+                ck.g2.into(),
+            ],
+        );
+
+        let test = E::final_exponentiation(qap).ok_or(SynthesisError::UnexpectedIdentity)?;
+        Ok(test.0 == pvk.alpha_g1_beta_g2)
+    }
+
+    /// Verify a proof against all its public `inputs`, static inputs first,
+    /// after adjusting their number to the one expected by `pvk` according
+    /// to `policy`.
+    pub fn verify_with_padding(
+        pvk: &PreparedVerifyingKey<E>,
+        inputs: &[E::ScalarField],
+        proof: &Proof<E>,
+        policy: PaddingPolicy,
+    ) -> Result<bool, VerificationError> {
+        let num_static_inputs = pvk.num_static_inputs();
+        let inputs = policy.apply(inputs, pvk.expected_total_inputs())?;
+        let (static_inputs, variable_inputs) = inputs.split_at(num_static_inputs);
+
+        Self::verify_with_variables(pvk, proof, static_inputs, variable_inputs)
+    }
+
+    /// Verify a proof with static and variable inputs given as fixed-width
+    /// byte encodings, see [`PublicInputs`]. Byte strings which are not
+    /// canonical encodings of inputs are rejected with
+    /// [`SynthesisError::AssignmentMissing`].
+    pub fn verify_with_input_bytes(
+        pvk: &PreparedVerifyingKey<E>,
+        proof: &Proof<E>,
+        static_inputs: &[u8],
+        variable_inputs: &[u8],
+        endianness: Endianness,
+    ) -> Result<bool, VerificationError> {
+        let static_inputs = PublicInputs::<E>::from_bytes(static_inputs, endianness)
+            .map_err(|_| SynthesisError::AssignmentMissing)?;
+        let variable_inputs = PublicInputs::<E>::from_bytes(variable_inputs, endianness)
+            .map_err(|_| SynthesisError::AssignmentMissing)?;
+
+        Self::verify_with_variables(pvk, proof, &static_inputs.0, &variable_inputs.0)
+    }
+
+    /// Verify a proof against all its public `inputs`, static inputs first,
+    /// each given as a big-endian 32-byte word as in EVM calldata or Solana
+    /// instruction data, see [`PublicInputs::from_words32`]. Words which are
+    /// not canonical encodings of inputs, or scalar fields whose elements do
+    /// not fit in 32 bytes, are rejected with
+    /// [`SynthesisError::AssignmentMissing`].
+    pub fn verify_be_words(
+        pvk: &PreparedVerifyingKey<E>,
+        proof: &Proof<E>,
+        inputs: &[[u8; 32]],
+    ) -> Result<bool, VerificationError> {
+        let inputs = PublicInputs::<E>::from_words32(inputs, Endianness::Big)
+            .map_err(|_| SynthesisError::AssignmentMissing)?;
+        let num_static_inputs = pvk.num_static_inputs().min(inputs.0.len());
+        let (static_inputs, variable_inputs) = inputs.0.split_at(num_static_inputs);
+
+        Self::verify_with_variables(pvk, proof, static_inputs, variable_inputs)
+    }
+
+    /// Verify a proof with separate static and variable inputs, preparing
+    /// the proof elements through `preparer` so that components shared with
+    /// previously verified proofs are not prepared again.
+    pub fn verify_with_variables_and_preparer(
+        pvk: &PreparedVerifyingKey<E>,
+        proof: &Proof<E>,
+        static_inputs: &[E::ScalarField],
+        variable_inputs: &[E::ScalarField],
+        preparer: &mut ProofPreparer<E>,
+    ) -> R1CSResult<bool> {
+        let prepared_inputs =
+            Self::prepare_inputs_with_variables(pvk, static_inputs, variable_inputs)?;
+
+        Self::verify_proof_with_prepared_inputs_and_preparer(pvk, proof, &prepared_inputs, preparer)
+    }
+
     /// Verify a Groth16 proof `proof` against the prepared verification key `pvk` and prepared public
     /// inputs. This should be preferred over [`verify_proof`] if the instance's public inputs are
     /// known in advance.
@@ -72,21 +714,323 @@ impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
         proof: &Proof<E>,
         prepared_inputs: &E::G1,
     ) -> R1CSResult<bool> {
+        #[cfg(feature = "verifier-audit")]
+        if !audit_proof(proof) {
+            return Ok(false);
+        }
+        Self::verify_prepared_proof(
+            pvk,
+            proof.a.into(),
+            proof.b.into(),
+            proof.c.into(),
+            prepared_inputs,
+        )
+    }
+
+    /// Computes the Miller loops of the verification of `proof` against
+    /// `pvk` and `prepared_inputs`, without combining them nor computing the
+    /// final exponentiation, so that a pipelined verifier can overlap the
+    /// Miller loops of consecutive proofs and check them later with
+    /// [`Self::check_split`] or [`Self::check_split_batch`].
+    ///
+    /// `e(A, B)` is computed apart from the terms of the key, which depend
+    /// on the proof only through `C`.
+    pub fn verify_split(
+        pvk: &PreparedVerifyingKey<E>,
+        proof: &Proof<E>,
+        prepared_inputs: &E::G1,
+    ) -> SplitMillerLoops<E> {
+        SplitMillerLoops {
+            ab: E::miller_loop(proof.a, proof.b),
+            rest: E::multi_miller_loop(
+                [prepared_inputs.into_affine(), proof.c],
+                [pvk.gamma_g2_neg_pc.clone(), pvk.delta_g2_neg_pc.clone()],
+            ),
+        }
+    }
+
+    /// Checks the Miller loops of a proof from [`Self::verify_split`] with a
+    /// final exponentiation.
+    pub fn check_split(
+        pvk: &PreparedVerifyingKey<E>,
+        split: &SplitMillerLoops<E>,
+    ) -> R1CSResult<bool> {
+        let test =
+            E::final_exponentiation(split.combine()).ok_or(SynthesisError::UnexpectedIdentity)?;
+        #[cfg(feature = "verifier-audit")]
+        audit_final_exponentiation(&split.combine(), &test);
+
+        Ok(test.0 == pvk.alpha_g1_beta_g2)
+    }
+
+    /// Checks the Miller loops of several proofs from [`Self::verify_split`]
+    /// with a single final exponentiation. Returns whether all proofs
+    /// verify; an empty batch does.
+    ///
+    /// The Miller loops are raised to random 128-bit powers sampled from
+    /// `rng` before they are multiplied, so that a batch with an invalid
+    /// proof is accepted with probability at most `2^-128`.
+    pub fn check_split_batch(
+        pvk: &PreparedVerifyingKey<E>,
+        splits: &[SplitMillerLoops<E>],
+        rng: &mut impl Rng,
+    ) -> R1CSResult<bool> {
+        if splits.is_empty() {
+            return Ok(true);
+        }
+        let mut product = E::TargetField::one();
+        let mut coeffs_sum = E::ScalarField::zero();
+        for split in splits {
+            let coeff = E::ScalarField::from(rng.gen::<u128>());
+            product *= (split.combine() * coeff).0;
+            coeffs_sum += coeff;
+        }
+        let test = E::final_exponentiation(MillerLoopOutput(product))
+            .ok_or(SynthesisError::UnexpectedIdentity)?;
+        #[cfg(feature = "verifier-audit")]
+        audit_final_exponentiation(&MillerLoopOutput(product), &test);
+
+        Ok(test.0 == pvk.alpha_g1_beta_g2.pow(coeffs_sum.into_bigint()))
+    }
+
+    /// Verify a Groth16 proof `proof` against the prepared verification key
+    /// `pvk` and prepared public inputs, preparing the proof elements through
+    /// `preparer`.
+    pub fn verify_proof_with_prepared_inputs_and_preparer(
+        pvk: &PreparedVerifyingKey<E>,
+        proof: &Proof<E>,
+        prepared_inputs: &E::G1,
+        preparer: &mut ProofPreparer<E>,
+    ) -> R1CSResult<bool> {
+        #[cfg(feature = "verifier-audit")]
+        if !audit_proof(proof) {
+            return Ok(false);
+        }
+        Self::verify_prepared_proof(
+            pvk,
+            preparer.prepare_g1(&proof.a),
+            preparer.prepare_g2(&proof.b),
+            preparer.prepare_g1(&proof.c),
+            prepared_inputs,
+        )
+    }
+
+    /// Verify a batch of proofs of the same statement, e.g. from redundant
+    /// provers, against its `prepared_inputs`, as from
+    /// [`Self::prepare_inputs_with_variables`]. Returns whether all proofs
+    /// verify; an empty batch does.
+    ///
+    /// The pairing checks of the proofs are combined with random 128-bit
+    /// coefficients sampled from `rng`, so that the batch costs a single
+    /// multi-Miller loop and final exponentiation, and shares the terms of
+    /// the prepared inputs and of `C`. A batch with an invalid proof is
+    /// accepted with probability at most `2^-128`.
+    pub fn verify_same_inputs_batch(
+        pvk: &PreparedVerifyingKey<E>,
+        prepared_inputs: &E::G1,
+        proofs: &[Proof<E>],
+        rng: &mut impl Rng,
+    ) -> R1CSResult<bool> {
+        let coeffs = proofs
+            .iter()
+            .map(|_| E::ScalarField::from(rng.gen::<u128>()))
+            .collect::<Vec<_>>();
+        Self::verify_same_inputs_batch_with_coeffs(pvk, prepared_inputs, proofs, &coeffs)
+    }
+
+    /// Verify a batch of proofs of the same statement like
+    /// [`Self::verify_same_inputs_batch`], with the coefficients squeezed
+    /// from `sponge` after absorbing the key, the prepared inputs and the
+    /// proofs, so that anyone can reproduce and audit the verification.
+    ///
+    /// The coefficients are derived with the Fiat-Shamir transform: a
+    /// batch with an invalid proof is accepted with probability at most
+    /// `2^-128` per attempt when `sponge` behaves as a random oracle, e.g.
+    /// a freshly initialized Poseidon sponge. Anything already absorbed
+    /// into `sponge` is bound to the coefficients too.
+    pub fn verify_same_inputs_batch_with_sponge(
+        pvk: &PreparedVerifyingKey<E>,
+        prepared_inputs: &E::G1,
+        proofs: &[Proof<E>],
+        sponge: &mut impl CryptographicSponge,
+    ) -> R1CSResult<bool> {
+        pvk.absorb_into(sponge);
+        let mut bytes = BATCH_INPUTS_LABEL.to_vec();
+        prepared_inputs
+            .into_affine()
+            .serialize_compressed(&mut bytes)
+            .expect("serializing into a vector cannot fail");
+        sponge.absorb(&bytes);
+        for proof in proofs {
+            proof.absorb_into(sponge);
+        }
+        let coeffs = sponge.squeeze_field_elements_with_sizes::<E::ScalarField>(&vec![
+            FieldElementSize::Truncated(128);
+            proofs.len()
+        ]);
+        Self::verify_same_inputs_batch_with_coeffs(pvk, prepared_inputs, proofs, &coeffs)
+    }
+
+    /// Verify a batch of proofs of the same statement, combining their
+    /// pairing checks with `coeffs`.
+    fn verify_same_inputs_batch_with_coeffs(
+        pvk: &PreparedVerifyingKey<E>,
+        prepared_inputs: &E::G1,
+        proofs: &[Proof<E>],
+        coeffs: &[E::ScalarField],
+    ) -> R1CSResult<bool> {
+        if proofs.is_empty() {
+            return Ok(true);
+        }
+        let coeffs_sum: E::ScalarField = coeffs.iter().sum();
+
+        // sum r_i * (e(A_i, B_i) - e(inputs, gamma) - e(C_i, delta))
+        //     == sum r_i * e(alpha, beta)
+        let scaled_a = proofs
+            .iter()
+            .zip(coeffs)
+            .map(|(proof, coeff)| proof.a * coeff)
+            .collect::<Vec<_>>();
+        let (_, _, c_acc) = aggregate_proof_points(proofs, coeffs);
+        let g1 = E::G1::normalize_batch(&scaled_a)
+            .into_iter()
+            .map(E::G1Prepared::from)
+            .chain([
+                (*prepared_inputs * coeffs_sum).into_affine().into(),
+                c_acc.into_affine().into(),
+            ]);
+        let g2 = proofs
+            .iter()
+            .map(|proof| E::G2Prepared::from(proof.b))
+            .chain([pvk.gamma_g2_neg_pc.clone(), pvk.delta_g2_neg_pc.clone()]);
+
+        let qap = E::multi_miller_loop(g1, g2);
+        let test = E::final_exponentiation(qap).ok_or(SynthesisError::UnexpectedIdentity)?;
+        #[cfg(feature = "verifier-audit")]
+        audit_final_exponentiation(&qap, &test);
+
+        Ok(test.0 == pvk.alpha_g1_beta_g2.pow(coeffs_sum.into_bigint()))
+    }
+
+    /// Verify a proof with separate static and variable inputs like
+    /// [`Self::verify_with_variables`], and report the time spent in each
+    /// step of the verification.
+    #[cfg(feature = "std")]
+    pub fn verify_with_report(
+        pvk: &PreparedVerifyingKey<E>,
+        proof: &Proof<E>,
+        static_inputs: &[E::ScalarField],
+        variable_inputs: &[E::ScalarField],
+    ) -> R1CSResult<VerificationReport> {
+        #[cfg(feature = "verifier-audit")]
+        if !audit_proof(proof) {
+            return Ok(VerificationReport {
+                accepted: false,
+                input_msm_time: Duration::ZERO,
+                miller_time: Duration::ZERO,
+                final_exp_time: Duration::ZERO,
+                input_msm_size: static_inputs.len() + variable_inputs.len(),
+            });
+        }
+        let start = Instant::now();
+        let prepared_inputs =
+            Self::prepare_inputs_with_variables(pvk, static_inputs, variable_inputs)?;
+        let input_msm_time = start.elapsed();
+
+        let start = Instant::now();
         let qap = E::multi_miller_loop(
-            [
-                <E::G1Affine as Into<E::G1Prepared>>::into(proof.a),
-                prepared_inputs.into_affine().into(),
-                proof.c.into(),
-            ],
+            [proof.a, prepared_inputs.into_affine(), proof.c],
             [
                 proof.b.into(),
                 pvk.gamma_g2_neg_pc.clone(),
                 pvk.delta_g2_neg_pc.clone(),
             ],
         );
+        let miller_time = start.elapsed();
 
+        let start = Instant::now();
         let test = E::final_exponentiation(qap).ok_or(SynthesisError::UnexpectedIdentity)?;
+        let final_exp_time = start.elapsed();
+        #[cfg(feature = "verifier-audit")]
+        audit_final_exponentiation(&qap, &test);
+
+        Ok(VerificationReport {
+            accepted: test.0 == pvk.alpha_g1_beta_g2,
+            input_msm_time,
+            miller_time,
+            final_exp_time,
+            input_msm_size: static_inputs.len() + variable_inputs.len(),
+        })
+    }
+
+    pub(crate) fn verify_prepared_proof(
+        pvk: &PreparedVerifyingKey<E>,
+        a: E::G1Prepared,
+        b: E::G2Prepared,
+        c: E::G1Prepared,
+        prepared_inputs: &E::G1,
+    ) -> R1CSResult<bool> {
+        #[cfg(feature = "verifier-audit")]
+        verifier_audit::projective("prepared_inputs", prepared_inputs);
+        let qap = E::multi_miller_loop(
+            [a, prepared_inputs.into_affine().into(), c],
+            [b, pvk.gamma_g2_neg_pc.clone(), pvk.delta_g2_neg_pc.clone()],
+        );
+
+        let test = E::final_exponentiation(qap).ok_or(SynthesisError::UnexpectedIdentity)?;
+        #[cfg(feature = "verifier-audit")]
+        {
+            audit_final_exponentiation(&qap, &test);
+            verifier_audit::target::<E>("alpha_g1_beta_g2", &PairingOutput(pvk.alpha_g1_beta_g2));
+            verifier_audit::outcome(test.0 == pvk.alpha_g1_beta_g2);
+        }
 
         Ok(test.0 == pvk.alpha_g1_beta_g2)
     }
 }
+
+/// Returns the coefficient with which [`Groth16::verify_with_commitment`]
+/// combines the proof of knowledge of the opening of `commitment` with the
+/// check of `proof`, hashed from the key, the statement and both proofs.
+fn commitment_challenge<E: Pairing>(
+    pvk: &PreparedVerifyingKey<E>,
+    ck: &CommitmentVerifyingKey<E>,
+    proof: &Proof<E>,
+    inputs: &[E::ScalarField],
+    commitment: &InputCommitment<E>,
+) -> E::ScalarField {
+    let mut bytes = COMMITMENT_LABEL.to_vec();
+    pvk.vk
+        .serialize_compressed(&mut bytes)
+        .and_then(|()| ck.serialize_compressed(&mut bytes))
+        .and_then(|()| inputs.serialize_compressed(&mut bytes))
+        .and_then(|()| proof.serialize_compressed(&mut bytes))
+        .and_then(|()| commitment.serialize_compressed(&mut bytes))
+        .expect("serializing into a vector cannot fail");
+    E::ScalarField::from_le_bytes_mod_order(&crate::hash::sha256(&bytes))
+}
+
+/// Records the elements of `proof` up to the first invalid one, returning
+/// whether they are all valid points. A proof with an invalid element is
+/// recorded as rejected, and must be rejected by the caller.
+#[cfg(feature = "verifier-audit")]
+pub(crate) fn audit_proof<E: Pairing>(proof: &Proof<E>) -> bool {
+    let valid = verifier_audit::proof_point("proof.a", &proof.a)
+        && verifier_audit::proof_point("proof.b", &proof.b)
+        && verifier_audit::proof_point("proof.c", &proof.c);
+    if !valid {
+        verifier_audit::outcome(false);
+    }
+    valid
+}
+
+/// Records the Miller loop `qap`, and asserts that its final exponentiation
+/// `test` is in the target group and records it.
+#[cfg(feature = "verifier-audit")]
+pub(crate) fn audit_final_exponentiation<E: Pairing>(
+    qap: &MillerLoopOutput<E>,
+    test: &PairingOutput<E>,
+) {
+    verifier_audit::miller_loop("miller_loop", qap);
+    verifier_audit::target("final_exponentiation", test);
+}
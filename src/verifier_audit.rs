@@ -0,0 +1,117 @@
+use crate::text::push_hex;
+use ark_ec::{
+    pairing::{MillerLoopOutput, Pairing, PairingOutput},
+    AffineRepr, CurveGroup,
+};
+use ark_ff::{Field, One, PrimeField, Zero};
+use ark_serialize::CanonicalSerialize;
+use std::{cell::RefCell, fmt, string::String, vec::Vec};
+
+thread_local! {
+    static RECORDS: RefCell<Option<Vec<AuditRecord>>> = const { RefCell::new(None) };
+}
+
+/// An intermediate value of a verification, recorded by the audit build.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AuditRecord {
+    /// The step of the verification which computed the value, e.g.
+    /// `prepared_inputs` or `final_exponentiation`.
+    pub step: &'static str,
+    /// The lowercase hex of the compressed canonical serialization of the
+    /// value.
+    pub value: String,
+}
+
+impl fmt::Display for AuditRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.step, self.value)
+    }
+}
+
+/// Runs `f`, returning its result with the records of the verifications it
+/// ran on this thread, in order. Records of nested captures are also
+/// returned by the enclosing ones.
+pub fn capture<R>(f: impl FnOnce() -> R) -> (R, Vec<AuditRecord>) {
+    let outer = RECORDS.with(|records| records.replace(Some(Vec::new())));
+    let result = f();
+    let inner = RECORDS
+        .with(|records| records.replace(outer))
+        .unwrap_or_default();
+    RECORDS.with(|records| {
+        if let Some(outer) = records.borrow_mut().as_mut() {
+            outer.extend_from_slice(&inner);
+        }
+    });
+    (result, inner)
+}
+
+/// Returns whether `point` is on the curve and in its prime-order subgroup.
+pub fn is_valid_point<G: AffineRepr>(point: &G) -> bool {
+    point.check().is_ok()
+}
+
+/// Returns whether `value` is in the subgroup of order `r` of the target
+/// field, where the results of pairings lie.
+pub fn is_valid_target<E: Pairing>(value: &PairingOutput<E>) -> bool {
+    !value.0.is_zero() && value.0.pow(E::ScalarField::MODULUS).is_one()
+}
+
+fn record(step: &'static str, value: &impl CanonicalSerialize) {
+    RECORDS.with(|records| {
+        if let Some(records) = records.borrow_mut().as_mut() {
+            let mut bytes = Vec::new();
+            value
+                .serialize_compressed(&mut bytes)
+                .expect("serializing into a vector cannot fail");
+            let mut hex = String::with_capacity(2 * bytes.len());
+            push_hex(&mut hex, &bytes);
+            records.push(AuditRecord { step, value: hex });
+        }
+    });
+}
+
+/// Records `point`, an element of an untrusted proof, returning whether
+/// it is a valid point. Unlike the values computed by the verifier, an
+/// invalid element is not a bug of the verifier but a malformed proof, to
+/// be rejected.
+pub(crate) fn proof_point<G: AffineRepr>(step: &'static str, point: &G) -> bool {
+    record(step, point);
+    is_valid_point(point)
+}
+
+/// Asserts that `point` is a valid point, and records it.
+pub(crate) fn point<G: AffineRepr>(step: &'static str, point: &G) {
+    assert!(
+        is_valid_point(point),
+        "verifier audit: {} is not in the prime-order subgroup",
+        step
+    );
+    record(step, point);
+}
+
+/// Asserts that `point` is a valid point, and records it.
+pub(crate) fn projective<G: CurveGroup>(step: &'static str, point: &G) {
+    self::point(step, &point.into_affine());
+}
+
+/// Records the output of a Miller loop, which is only in the subgroup of
+/// order `r` after the final exponentiation.
+pub(crate) fn miller_loop<E: Pairing>(step: &'static str, value: &MillerLoopOutput<E>) {
+    record(step, &value.0);
+}
+
+/// Asserts that `value` is in the subgroup of order `r` of the target field,
+/// and records it.
+pub(crate) fn target<E: Pairing>(step: &'static str, value: &PairingOutput<E>) {
+    assert!(
+        is_valid_target(value),
+        "verifier audit: {} is not in the target group",
+        step
+    );
+    record(step, value);
+}
+
+/// Records the outcome of a verification.
+pub(crate) fn outcome(accepted: bool) {
+    record("accepted", &accepted);
+}
@@ -0,0 +1,219 @@
+use crate::{prepare_verifying_key, Groth16, PreparedVerifyingKey, Proof, VerifyingKey};
+use ark_crypto_primitives::snark::SNARK;
+use ark_ec::pairing::Pairing;
+use ark_relations::r1cs::Result as R1CSResult;
+use ark_std::{fmt, string::String, vec::Vec};
+use std::{
+    collections::HashMap,
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc::{channel, sync_channel, Receiver, Sender, SyncSender, TrySendError},
+        Arc, Mutex, PoisonError, RwLock,
+    },
+    thread::{self, JoinHandle},
+};
+
+/// The receiving end of the result of a verification submitted to a
+/// [`VerifierPool`]. It receives exactly one result, or disconnects if the
+/// pool is dropped before the verification ran or if the verification
+/// panicked.
+pub type VerificationReceiver = Receiver<R1CSResult<bool>>;
+
+/// The sizes of a [`VerifierPool`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VerifierPoolConfig {
+    /// The number of worker threads verifying proofs.
+    pub num_workers: usize,
+    /// The number of verifications queued for the workers, beyond which
+    /// submissions are rejected.
+    pub queue_capacity: usize,
+    /// The number of verifications of a single tenant that may be queued or
+    /// running at once, so that a busy tenant cannot fill the queue.
+    pub max_pending_per_tenant: usize,
+}
+
+impl Default for VerifierPoolConfig {
+    fn default() -> Self {
+        Self {
+            num_workers: thread::available_parallelism().map_or(1, |n| n.get()),
+            queue_capacity: 1024,
+            max_pending_per_tenant: 256,
+        }
+    }
+}
+
+/// An error when submitting a verification to a [`VerifierPool`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SubmitError {
+    /// No verifying key is registered for the tenant.
+    UnknownTenant,
+    /// The tenant has as many verifications pending as allowed.
+    TenantBusy,
+    /// The queue of the pool is full.
+    QueueFull,
+}
+
+impl fmt::Display for SubmitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SubmitError::UnknownTenant => write!(f, "no verifying key registered for the tenant"),
+            SubmitError::TenantBusy => write!(f, "too many pending verifications for the tenant"),
+            SubmitError::QueueFull => write!(f, "verification queue is full"),
+        }
+    }
+}
+
+impl std::error::Error for SubmitError {}
+
+/// The prepared verifying key of a tenant, and its number of pending
+/// verifications.
+struct Tenant<E: Pairing> {
+    pvk: PreparedVerifyingKey<E>,
+    pending: AtomicUsize,
+}
+
+struct Job<E: Pairing> {
+    tenant: Arc<Tenant<E>>,
+    proof: Proof<E>,
+    inputs: Vec<E::ScalarField>,
+    result: Sender<R1CSResult<bool>>,
+}
+
+/// A pool of worker threads verifying proofs for multiple tenants, each
+/// with its own verifying key, through a bounded queue.
+///
+/// Submissions never block: when the queue or the quota of a tenant is
+/// full, they are rejected, so that callers can apply backpressure, e.g. by
+/// answering with a retry later. A panicking verification only disconnects
+/// its receiver: the worker keeps running and the quota of the tenant is
+/// released. Dropping the pool waits for the queued verifications to
+/// complete.
+pub struct VerifierPool<E: Pairing> {
+    tenants: RwLock<HashMap<String, Arc<Tenant<E>>>>,
+    queue: Option<SyncSender<Job<E>>>,
+    workers: Vec<JoinHandle<()>>,
+    max_pending_per_tenant: usize,
+}
+
+impl<E: Pairing> VerifierPool<E> {
+    /// Spawns the workers of a pool of the sizes in `config`, with at least
+    /// one worker.
+    pub fn new(config: VerifierPoolConfig) -> Self {
+        let (queue, jobs) = sync_channel::<Job<E>>(config.queue_capacity);
+        let jobs = Arc::new(Mutex::new(jobs));
+        let workers = (0..config.num_workers.max(1))
+            .map(|_| {
+                let jobs = jobs.clone();
+                thread::spawn(move || loop {
+                    // The lock is released before verifying, and the
+                    // receiver cannot be left inconsistent by a panic.
+                    let job = jobs.lock().unwrap_or_else(PoisonError::into_inner).recv();
+                    let Ok(job) = job else { break };
+                    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                        Groth16::<E>::verify_with_processed_vk(
+                            &job.tenant.pvk,
+                            &job.inputs,
+                            &job.proof,
+                        )
+                    }));
+                    job.tenant.pending.fetch_sub(1, Ordering::AcqRel);
+                    if let Ok(result) = result {
+                        let _ = job.result.send(result);
+                    }
+                })
+            })
+            .collect();
+
+        Self {
+            tenants: RwLock::new(HashMap::new()),
+            queue: Some(queue),
+            workers,
+            max_pending_per_tenant: config.max_pending_per_tenant,
+        }
+    }
+
+    /// Registers the verifying key of `tenant`, replacing its previous key.
+    /// Verifications already submitted use the previous key.
+    pub fn register_tenant(&self, tenant: impl Into<String>, vk: &VerifyingKey<E>) {
+        let tenant_state = Arc::new(Tenant {
+            pvk: prepare_verifying_key(vk),
+            pending: AtomicUsize::new(0),
+        });
+        self.tenants
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(tenant.into(), tenant_state);
+    }
+
+    /// Removes the verifying key of `tenant`, returning whether it was
+    /// registered. Verifications already submitted still run.
+    pub fn remove_tenant(&self, tenant: &str) -> bool {
+        self.tenants
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(tenant)
+            .is_some()
+    }
+
+    /// Returns the number of verifications of `tenant` queued or running,
+    /// or `None` if it is not registered.
+    pub fn pending(&self, tenant: &str) -> Option<usize> {
+        let tenants = self.tenants.read().unwrap_or_else(PoisonError::into_inner);
+        Some(tenants.get(tenant)?.pending.load(Ordering::Acquire))
+    }
+
+    /// Queues the verification of `proof` against the public `inputs`,
+    /// static inputs first, under the key of `tenant`, returning the
+    /// receiver of its result.
+    pub fn submit(
+        &self,
+        tenant: &str,
+        proof: Proof<E>,
+        inputs: Vec<E::ScalarField>,
+    ) -> Result<VerificationReceiver, SubmitError> {
+        let tenant = self
+            .tenants
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(tenant)
+            .cloned()
+            .ok_or(SubmitError::UnknownTenant)?;
+        let max_pending = self.max_pending_per_tenant;
+        tenant
+            .pending
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |pending| {
+                (pending < max_pending).then_some(pending + 1)
+            })
+            .map_err(|_| SubmitError::TenantBusy)?;
+
+        let (result, receiver) = channel();
+        let job = Job {
+            tenant,
+            proof,
+            inputs,
+            result,
+        };
+        let queue = self
+            .queue
+            .as_ref()
+            .expect("the queue lives as long as the pool");
+        match queue.try_send(job) {
+            Ok(()) => Ok(receiver),
+            Err(TrySendError::Full(job) | TrySendError::Disconnected(job)) => {
+                job.tenant.pending.fetch_sub(1, Ordering::AcqRel);
+                Err(SubmitError::QueueFull)
+            },
+        }
+    }
+}
+
+impl<E: Pairing> Drop for VerifierPool<E> {
+    fn drop(&mut self) {
+        // Closing the queue stops the workers once it is drained.
+        self.queue = None;
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
@@ -0,0 +1,142 @@
+use crate::{
+    r1cs_to_qap::R1CSToQAP, Groth16, PreparedVerifyingKey, Proof, ProvingKey, VerificationError,
+};
+use ark_ec::pairing::Pairing;
+use ark_ff::PrimeField;
+use ark_relations::{
+    lc,
+    r1cs::{
+        ConstraintSynthesizer, ConstraintSystemRef, Result as R1CSResult, SynthesisError, Variable,
+    },
+};
+use ark_std::{fmt, rand::Rng, vec::Vec};
+
+/// The version of a release of a circuit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CircuitVersion(pub u64);
+
+impl CircuitVersion {
+    /// Returns the public input encoding the version.
+    pub fn to_input<F: PrimeField>(self) -> F {
+        F::from(self.0)
+    }
+}
+
+impl fmt::Display for CircuitVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "v{}", self.0)
+    }
+}
+
+/// A circuit whose first static input is its version.
+///
+/// The input is constrained to the version, a constant of the constraint
+/// system, so keys generated for different versions differ even if the
+/// wrapped circuit does not, and a proof of one version never verifies
+/// under the key of another.
+#[derive(Clone, Debug)]
+pub struct VersionedCircuit<C> {
+    /// The version of the circuit.
+    pub version: CircuitVersion,
+    /// The wrapped circuit, whose inputs follow the version.
+    pub circuit: C,
+}
+
+impl<C> VersionedCircuit<C> {
+    /// Wraps `circuit` with its `version`.
+    pub fn new(version: CircuitVersion, circuit: C) -> Self {
+        Self { version, circuit }
+    }
+}
+
+impl<F, C> ConstraintSynthesizer<F> for VersionedCircuit<C>
+where
+    F: PrimeField,
+    C: ConstraintSynthesizer<F>,
+{
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> R1CSResult<()> {
+        let version = self.version.to_input::<F>();
+        let input = cs.new_input_variable(|| Ok(version))?;
+        cs.enforce_constraint(
+            lc!() + input - (version, Variable::One),
+            lc!() + Variable::One,
+            lc!(),
+        )?;
+        self.circuit.generate_constraints(cs)
+    }
+}
+
+impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
+    /// Generates a random common reference string for `circuit` at
+    /// `version`, wrapped in a [`VersionedCircuit`]. The first
+    /// `num_static_inputs` inputs of `circuit` are static inputs, after the
+    /// version.
+    pub fn setup_versioned<C>(
+        circuit: C,
+        version: CircuitVersion,
+        num_static_inputs: usize,
+        rng: &mut impl Rng,
+    ) -> R1CSResult<ProvingKey<E>>
+    where
+        C: ConstraintSynthesizer<E::ScalarField>,
+    {
+        // The constant one and the version precede the static inputs.
+        Self::generate_random_parameters_with_reduction(
+            VersionedCircuit::new(version, circuit),
+            rng,
+            num_static_inputs + 2,
+            0,
+        )
+    }
+
+    /// Create a Groth16 proof that is zero-knowledge of `circuit` at
+    /// `version`, with a key from [`Self::setup_versioned`] for the same
+    /// version. This method samples randomness for zero knowledge via `rng`.
+    pub fn prove_versioned<C>(
+        pk: &ProvingKey<E>,
+        version: CircuitVersion,
+        circuit: C,
+        rng: &mut impl Rng,
+    ) -> R1CSResult<Proof<E>>
+    where
+        C: ConstraintSynthesizer<E::ScalarField>,
+    {
+        Self::create_random_proof_with_reduction(VersionedCircuit::new(version, circuit), pk, rng)
+    }
+
+    /// Verify a proof from [`Self::prove_versioned`] against
+    /// `expected_version`, passed as the first static input, followed by
+    /// `static_inputs` and `variable_inputs`.
+    ///
+    /// Proofs of other versions are rejected, and so are all proofs if `pvk`
+    /// was not generated for `expected_version`. Fails with
+    /// [`SynthesisError::MalformedVerifyingKey`] if `pvk` has no static
+    /// input for the version.
+    pub fn verify_versioned(
+        pvk: &PreparedVerifyingKey<E>,
+        expected_version: CircuitVersion,
+        proof: &Proof<E>,
+        static_inputs: &[E::ScalarField],
+        variable_inputs: &[E::ScalarField],
+    ) -> Result<bool, VerificationError> {
+        if pvk.num_static_inputs() == 0 {
+            return Err(SynthesisError::MalformedVerifyingKey.into());
+        }
+        let mut inputs = Vec::with_capacity(static_inputs.len() + 1);
+        inputs.push(expected_version.to_input::<E::ScalarField>());
+        inputs.extend_from_slice(static_inputs);
+
+        Self::verify_with_variables(pvk, proof, &inputs, variable_inputs)
+    }
+}
+
+/// Returns the version in the public `inputs` of a [`VersionedCircuit`],
+/// static inputs first, if it fits in a `u64`.
+pub fn input_version<F: PrimeField>(inputs: &[F]) -> Option<CircuitVersion> {
+    let version = inputs.first()?.into_bigint();
+    let limbs = version.as_ref();
+    if limbs.iter().skip(1).any(|limb| *limb != 0) {
+        return None;
+    }
+    Some(CircuitVersion(limbs.first().copied().unwrap_or(0)))
+}
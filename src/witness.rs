@@ -0,0 +1,109 @@
+use crate::{r1cs_to_qap::evaluate_constraint, r1cs_to_qap::R1CSToQAP, Groth16, ProvingKey};
+use ark_ec::pairing::Pairing;
+use ark_ff::Field;
+use ark_relations::r1cs::{
+    ConstraintMatrices, ConstraintSynthesizer, ConstraintSystem, OptimizationGoal,
+    Result as R1CSResult, SynthesisError,
+};
+use ark_std::{string::String, vec::Vec};
+
+/// A constraint `a * b = c` which does not hold for a given assignment.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnsatisfiedConstraint<F: Field> {
+    /// The index of the constraint in the constraint system.
+    pub index: usize,
+    /// The namespace path of the constraint, if constraint traces were
+    /// recorded during synthesis.
+    pub label: Option<String>,
+    /// The evaluation of the `A` linear combination.
+    pub a: F,
+    /// The evaluation of the `B` linear combination.
+    pub b: F,
+    /// The evaluation of the `C` linear combination.
+    pub c: F,
+}
+
+/// Evaluates every constraint in `matrices` on `full_assignment`, which
+/// contains the instance assignment (starting with the constant `1`)
+/// followed by the witness assignment, and returns the first `max_failures`
+/// constraints that do not hold.
+pub fn check_witness_with_matrices<F: Field>(
+    matrices: &ConstraintMatrices<F>,
+    full_assignment: &[F],
+    max_failures: usize,
+) -> R1CSResult<Vec<UnsatisfiedConstraint<F>>> {
+    let num_variables = matrices.num_instance_variables + matrices.num_witness_variables;
+    if full_assignment.len() != num_variables {
+        return Err(SynthesisError::AssignmentMissing);
+    }
+
+    let mut failures = Vec::new();
+    for index in 0..matrices.num_constraints {
+        if failures.len() == max_failures {
+            break;
+        }
+        let a: F = evaluate_constraint(&matrices.a[index], full_assignment);
+        let b: F = evaluate_constraint(&matrices.b[index], full_assignment);
+        let c: F = evaluate_constraint(&matrices.c[index], full_assignment);
+        if a * b != c {
+            failures.push(UnsatisfiedConstraint {
+                index,
+                label: None,
+                a,
+                b,
+                c,
+            });
+        }
+    }
+
+    Ok(failures)
+}
+
+impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
+    /// Synthesizes `circuit` with its assignment and returns the first
+    /// `max_failures` constraints it does not satisfy. An empty result means
+    /// the witness is valid. This is much cheaper than attempting a proof,
+    /// and pinpoints the failing constraints instead of producing a proof
+    /// which does not verify.
+    ///
+    /// Returns [`SynthesisError::MalformedVerifyingKey`] if the circuit does
+    /// not have the shape `pk` was generated for.
+    pub fn check_witness<C>(
+        pk: &ProvingKey<E>,
+        circuit: C,
+        max_failures: usize,
+    ) -> R1CSResult<Vec<UnsatisfiedConstraint<E::ScalarField>>>
+    where
+        C: ConstraintSynthesizer<E::ScalarField>,
+    {
+        let cs = ConstraintSystem::new_ref();
+        cs.set_optimization_goal(OptimizationGoal::Constraints);
+        circuit.generate_constraints(cs.clone())?;
+        cs.finalize();
+
+        if pk.a_query.len() != cs.num_instance_variables() + cs.num_witness_variables()
+            || pk.l_query.len() != cs.num_witness_variables()
+        {
+            return Err(SynthesisError::MalformedVerifyingKey);
+        }
+
+        let matrices = cs.to_matrices().ok_or(SynthesisError::AssignmentMissing)?;
+        let full_assignment = {
+            let cs = cs.borrow().ok_or(SynthesisError::AssignmentMissing)?;
+            [
+                cs.instance_assignment.as_slice(),
+                cs.witness_assignment.as_slice(),
+            ]
+            .concat()
+        };
+
+        let mut failures = check_witness_with_matrices(&matrices, &full_assignment, max_failures)?;
+        if let Some(names) = cs.constraint_names() {
+            for failure in &mut failures {
+                failure.label = names.get(failure.index).cloned();
+            }
+        }
+
+        Ok(failures)
+    }
+}
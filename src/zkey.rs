@@ -0,0 +1,762 @@
+use crate::{KeyBasis, ProvingKey};
+use ark_ec::{pairing::Pairing, short_weierstrass::SWFlags, AffineRepr, CurveGroup};
+use ark_ff::{BigInteger, Field, LegendreSymbol, One, PrimeField, UniformRand, Zero};
+use ark_poly::{EvaluationDomain, Radix2EvaluationDomain};
+use ark_relations::r1cs::ConstraintMatrices;
+use ark_serialize::{CanonicalSerializeWithFlags, SerializationError, Write};
+use ark_std::{cfg_iter, collections::BTreeMap, fmt, rand::Rng, vec::Vec};
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// The magic bytes starting a zkey file.
+const MAGIC: &[u8; 4] = b"zkey";
+
+/// The version of the zkey format.
+const VERSION: u32 = 1;
+
+/// The number of sections of a Groth16 zkey file.
+const NUM_SECTIONS: u32 = 10;
+
+/// The protocol identifier of Groth16 in the header section.
+const GROTH16_PROTOCOL: u32 = 1;
+
+/// The identifiers of the `A` and `B` matrices in the coefficients section.
+const MATRIX_A: u32 = 0;
+const MATRIX_B: u32 = 1;
+
+/// The size of the hash of the constraint system in the contributions
+/// section.
+const CS_HASH_SIZE: usize = 64;
+
+/// Returns the Montgomery factor `R = 2^(64 * limbs)` of `F`.
+pub(crate) fn montgomery_factor<F: PrimeField>() -> F {
+    F::from(2u64).pow([64 * <F::BigInt as BigInteger>::NUM_LIMBS as u64])
+}
+
+/// Returns the number of bytes of the elements of `F` in zkey files.
+fn element_size<F: PrimeField>() -> usize {
+    8 * <F::BigInt as BigInteger>::NUM_LIMBS
+}
+
+/// Writes `x` in little endian Montgomery form, the encoding of field
+/// elements in zkey files.
+fn write_montgomery<F: PrimeField>(buffer: &mut Vec<u8>, x: F) {
+    buffer.extend_from_slice(&(x * montgomery_factor::<F>()).into_bigint().to_bytes_le());
+}
+
+/// Returns the number of bytes of the points of `G` in zkey files.
+fn point_size<G: AffineRepr>() -> usize {
+    type BasePrimeField<G> = <<G as AffineRepr>::BaseField as Field>::BasePrimeField;
+    2 * G::BaseField::extension_degree() as usize * element_size::<BasePrimeField<G>>()
+}
+
+/// Writes the coordinates of `points` in Montgomery form, with zeros for the
+/// point at infinity.
+pub(crate) fn write_points<'a, G: AffineRepr>(
+    buffer: &mut Vec<u8>,
+    points: impl IntoIterator<Item = &'a G>,
+) {
+    let point_size = point_size::<G>();
+    for point in points {
+        match point.xy() {
+            Some((x, y)) => {
+                for element in x
+                    .to_base_prime_field_elements()
+                    .chain(y.to_base_prime_field_elements())
+                {
+                    write_montgomery(buffer, element);
+                }
+            },
+            None => buffer.resize(buffer.len() + point_size, 0),
+        }
+    }
+}
+
+pub(crate) fn write_section(
+    writer: &mut impl Write,
+    id: u32,
+    section: &[u8],
+) -> Result<(), SerializationError> {
+    writer.write_all(&id.to_le_bytes())?;
+    writer.write_all(&(section.len() as u64).to_le_bytes())?;
+    writer.write_all(section)?;
+    Ok(())
+}
+
+fn write_points_section<'a, G: AffineRepr>(
+    writer: &mut impl Write,
+    id: u32,
+    points: impl IntoIterator<Item = &'a G>,
+) -> Result<(), SerializationError> {
+    let mut section = Vec::new();
+    write_points(&mut section, points);
+    write_section(writer, id, &section)
+}
+
+/// Returns the primitive `size`-th root of unity of snarkjs, a power of the
+/// smallest quadratic non-residue, which differs from the root of unity of
+/// arkworks.
+pub(crate) fn root_of_unity<F: PrimeField>(size: u64) -> Option<F> {
+    if !size.is_power_of_two() || size.trailing_zeros() > F::TWO_ADICITY {
+        return None;
+    }
+    let mut non_residue = F::from(2u64);
+    while non_residue.legendre() != LegendreSymbol::QuadraticNonResidue {
+        non_residue += F::one();
+    }
+    let mut root = non_residue.pow(F::TRACE);
+    for _ in size.trailing_zeros()..F::TWO_ADICITY {
+        root.square_in_place();
+    }
+    Some(root)
+}
+
+/// Returns the evaluation domain of `size`, a power of two, over the roots
+/// of unity of snarkjs.
+pub(crate) fn domain<F: PrimeField>(size: usize) -> Option<Radix2EvaluationDomain<F>> {
+    let mut domain = Radix2EvaluationDomain::<F>::new(size)?;
+    if domain.size() != size {
+        return None;
+    }
+    domain.group_gen = root_of_unity(size as u64)?;
+    domain.group_gen_inv = domain.group_gen.inverse()?;
+    Some(domain)
+}
+
+/// Converts the `h_query` of a key in the monomial basis into the `H`
+/// section of a zkey.
+///
+/// The prover of snarkjs evaluates `a(x) * b(x) - c(x)` over the coset
+/// `g * <w>` of `domain`, where `g` is the root of unity of twice its size,
+/// and multiplies these evaluations with the points of the `H` section. As
+/// `Z(g * w^i) = g^n - 1 = -2`, the coefficients of `h(x)` are `-1/2` times
+/// the inverse FFT of the evaluations scaled by the powers of `g^-1`, so
+/// the `H` section is `-1/2` times the inverse FFT of the `h_query` scaled
+/// by the powers of `g^-1`.
+pub(crate) fn h_points<E: Pairing>(
+    h_query: &[E::G1Affine],
+    domain: &Radix2EvaluationDomain<E::ScalarField>,
+) -> Option<Vec<E::G1Affine>> {
+    let shift_inv = root_of_unity::<E::ScalarField>(2 * domain.size() as u64)?.inverse()?;
+    let mut powers = Vec::with_capacity(h_query.len());
+    let mut power = E::ScalarField::one();
+    for _ in 0..h_query.len() {
+        powers.push(power);
+        power *= shift_inv;
+    }
+
+    let mut points: Vec<E::G1> = cfg_iter!(h_query)
+        .zip(cfg_iter!(powers))
+        .map(|(point, power)| *point * power)
+        .collect();
+    points.resize(domain.size(), E::G1::zero());
+    domain.ifft_in_place(&mut points);
+
+    let minus_half = -E::ScalarField::from(2u64).inverse()?;
+    let points: Vec<E::G1> = cfg_iter!(points).map(|point| *point * minus_half).collect();
+    Some(E::G1::normalize_batch(&points))
+}
+
+/// Writes `pk`, generated for the constraint system `matrices`, as a zkey
+/// file of snarkjs, from which snarkjs and the provers built on it create
+/// proofs verified by the verifying key of `pk`.
+///
+/// Only keys from a single-party setup can be exported, so the file records
+/// no contributions. Fails with [`SerializationError::InvalidData`] if `pk`
+/// does not match `matrices` or has an `h_query` in the Lagrange basis,
+/// which snarkjs does not support.
+pub fn write_zkey<E: Pairing>(
+    pk: &ProvingKey<E>,
+    matrices: &ConstraintMatrices<E::ScalarField>,
+    mut writer: impl Write,
+) -> Result<(), SerializationError> {
+    let vk = &pk.vk;
+    let num_instance = matrices.num_instance_variables;
+    let num_variables = num_instance + matrices.num_witness_variables;
+    let domain_size = (matrices.num_constraints + num_instance).next_power_of_two();
+    let ic = || {
+        vk.gamma_abc_g1_static
+            .iter()
+            .chain(&vk.gamma_abc_g1_variable)
+    };
+    if pk.h_basis != KeyBasis::Monomial
+        || ic().count() != num_instance
+        || pk.a_query.len() != num_variables
+        || pk.b_g1_query.len() != num_variables
+        || pk.b_g2_query.len() != num_variables
+        || pk.l_query.len() != matrices.num_witness_variables
+        || pk.h_query.len() + 1 != domain_size
+        || u32::try_from(num_variables).is_err()
+    {
+        return Err(SerializationError::InvalidData);
+    }
+    let domain = domain::<E::ScalarField>(domain_size).ok_or(SerializationError::InvalidData)?;
+    let h_points = h_points::<E>(&pk.h_query, &domain).ok_or(SerializationError::InvalidData)?;
+
+    writer.write_all(MAGIC)?;
+    writer.write_all(&VERSION.to_le_bytes())?;
+    writer.write_all(&NUM_SECTIONS.to_le_bytes())?;
+
+    write_section(&mut writer, 1, &GROTH16_PROTOCOL.to_le_bytes())?;
+
+    let mut header = Vec::new();
+    for modulus in [
+        <<E::G1Affine as AffineRepr>::BaseField as Field>::BasePrimeField::MODULUS.to_bytes_le(),
+        E::ScalarField::MODULUS.to_bytes_le(),
+    ] {
+        header.extend_from_slice(&(modulus.len() as u32).to_le_bytes());
+        header.extend_from_slice(&modulus);
+    }
+    for count in [num_variables, num_instance - 1, domain_size] {
+        header.extend_from_slice(&(count as u32).to_le_bytes());
+    }
+    write_points(&mut header, [&vk.alpha_g1, &pk.beta_g1]);
+    write_points(&mut header, [&vk.beta_g2, &vk.gamma_g2]);
+    write_points(&mut header, [&pk.delta_g1]);
+    write_points(&mut header, [&vk.delta_g2]);
+    write_section(&mut writer, 2, &header)?;
+
+    write_points_section(&mut writer, 3, ic())?;
+
+    // The coefficients of A and B, with the constraints `x_i * 0 = 0` of the
+    // R1CS-to-QAP reduction binding the public inputs. C is implied, as
+    // `c = a * b` over the domain.
+    let r_squared = montgomery_factor::<E::ScalarField>().square();
+    let input_constraints = (0..num_instance).map(|i| {
+        (
+            MATRIX_A,
+            matrices.num_constraints + i,
+            E::ScalarField::one(),
+            i,
+        )
+    });
+    let coefficients = (matrices.a.iter().enumerate().map(|row| (MATRIX_A, row)))
+        .chain(matrices.b.iter().enumerate().map(|row| (MATRIX_B, row)))
+        .flat_map(|(matrix, (constraint, row))| {
+            row.iter()
+                .map(move |(coefficient, variable)| (matrix, constraint, *coefficient, *variable))
+        })
+        .chain(input_constraints);
+    let mut section = 0u32.to_le_bytes().to_vec();
+    let mut num_coefficients = 0u32;
+    for (matrix, constraint, coefficient, variable) in coefficients {
+        section.extend_from_slice(&matrix.to_le_bytes());
+        section.extend_from_slice(&(constraint as u32).to_le_bytes());
+        section.extend_from_slice(&(variable as u32).to_le_bytes());
+        section.extend_from_slice(&(coefficient * r_squared).into_bigint().to_bytes_le());
+        num_coefficients += 1;
+    }
+    section[..4].copy_from_slice(&num_coefficients.to_le_bytes());
+    write_section(&mut writer, 4, &section)?;
+
+    write_points_section(&mut writer, 5, &pk.a_query)?;
+    write_points_section(&mut writer, 6, &pk.b_g1_query)?;
+    write_points_section(&mut writer, 7, &pk.b_g2_query)?;
+    write_points_section(&mut writer, 8, &pk.l_query)?;
+    write_points_section(&mut writer, 9, &h_points)?;
+
+    let mut contributions = ark_std::vec![0u8; CS_HASH_SIZE];
+    contributions.extend_from_slice(&0u32.to_le_bytes());
+    write_section(&mut writer, 10, &contributions)?;
+
+    Ok(())
+}
+
+/// The magic bytes starting an r1cs file of circom.
+const R1CS_MAGIC: &[u8; 4] = b"r1cs";
+
+/// The magic bytes starting a ptau file of snarkjs.
+const PTAU_MAGIC: &[u8; 4] = b"ptau";
+
+/// The size of the transcript hash of a contribution.
+const TRANSCRIPT_SIZE: usize = 64;
+
+/// A cursor over the bytes of a section.
+struct Cursor<'a>(&'a [u8]);
+
+impl<'a> Cursor<'a> {
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8], SerializationError> {
+        if self.0.len() < len {
+            return Err(SerializationError::InvalidData);
+        }
+        let (bytes, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Ok(bytes)
+    }
+
+    fn u32(&mut self) -> Result<u32, SerializationError> {
+        Ok(u32::from_le_bytes(self.bytes(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64, SerializationError> {
+        Ok(u64::from_le_bytes(self.bytes(8)?.try_into().unwrap()))
+    }
+
+    fn usize(&mut self) -> Result<usize, SerializationError> {
+        usize::try_from(self.u32()?).map_err(|_| SerializationError::InvalidData)
+    }
+}
+
+/// Splits a binary file of snarkjs starting with `magic` into its sections,
+/// by identifier.
+fn read_sections<'a>(
+    bytes: &'a [u8],
+    magic: &[u8; 4],
+) -> Result<BTreeMap<u32, &'a [u8]>, SerializationError> {
+    let mut cursor = Cursor(bytes);
+    if cursor.bytes(4)? != magic || cursor.u32()? != VERSION {
+        return Err(SerializationError::InvalidData);
+    }
+    let num_sections = cursor.u32()?;
+    let mut sections = BTreeMap::new();
+    for _ in 0..num_sections {
+        let id = cursor.u32()?;
+        let size = usize::try_from(cursor.u64()?).map_err(|_| SerializationError::InvalidData)?;
+        if sections.insert(id, cursor.bytes(size)?).is_some() {
+            return Err(SerializationError::InvalidData);
+        }
+    }
+    if !cursor.0.is_empty() {
+        return Err(SerializationError::InvalidData);
+    }
+    Ok(sections)
+}
+
+fn section<'a>(
+    sections: &BTreeMap<u32, &'a [u8]>,
+    id: u32,
+) -> Result<Cursor<'a>, SerializationError> {
+    sections
+        .get(&id)
+        .map(|section| Cursor(section))
+        .ok_or(SerializationError::InvalidData)
+}
+
+/// Reads a modulus, prefixed by its size, and returns whether it is the
+/// modulus of `F`.
+fn read_modulus<F: PrimeField>(cursor: &mut Cursor<'_>) -> Result<bool, SerializationError> {
+    let size = cursor.usize()?;
+    Ok(cursor.bytes(size)? == F::MODULUS.to_bytes_le().as_slice())
+}
+
+/// Reads an element of `F` in little endian standard form, rejecting
+/// non-canonical encodings.
+fn read_standard<F: PrimeField>(cursor: &mut Cursor<'_>) -> Result<F, SerializationError> {
+    let bytes = cursor.bytes(element_size::<F>())?;
+    let x = F::from_le_bytes_mod_order(bytes);
+    if x.into_bigint().to_bytes_le() != bytes {
+        return Err(SerializationError::InvalidData);
+    }
+    Ok(x)
+}
+
+/// Reads an element of `F` in little endian Montgomery form, rejecting
+/// non-canonical encodings.
+fn read_montgomery<F: PrimeField>(cursor: &mut Cursor<'_>) -> Result<F, SerializationError> {
+    Ok(read_standard::<F>(cursor)? / montgomery_factor::<F>())
+}
+
+/// Returns the point of affine coordinates `x` and `y` of a short
+/// Weierstrass curve, checking that it is on the curve and in the prime
+/// order subgroup.
+pub(crate) fn point_from_coordinates<G: AffineRepr>(
+    x: G::BaseField,
+    y: G::BaseField,
+) -> Result<G, SerializationError> {
+    // Points are recovered from `x` and the sign of `y`, as `AffineRepr`
+    // has no constructor from coordinates.
+    let flags = if y > -y {
+        SWFlags::YIsPositive
+    } else {
+        SWFlags::YIsNegative
+    };
+    let mut bytes = Vec::new();
+    x.serialize_with_flags(&mut bytes, flags)?;
+    let point = G::from_random_bytes(&bytes).ok_or(SerializationError::InvalidData)?;
+    if point.xy() != Some((&x, &y)) {
+        return Err(SerializationError::InvalidData);
+    }
+    point.check()?;
+    Ok(point)
+}
+
+/// Reads a point written by [`write_points`], checking that it is on the
+/// curve and in the prime order subgroup.
+fn read_point<G: AffineRepr>(cursor: &mut Cursor<'_>) -> Result<G, SerializationError> {
+    type BasePrimeField<G> = <<G as AffineRepr>::BaseField as Field>::BasePrimeField;
+    let degree = G::BaseField::extension_degree() as usize;
+    let point_size = point_size::<G>();
+    if cursor.0.len() >= point_size && cursor.0[..point_size].iter().all(|byte| *byte == 0) {
+        cursor.bytes(point_size)?;
+        return Ok(G::zero());
+    }
+    let mut coordinate = || {
+        let elements = (0..degree)
+            .map(|_| read_montgomery::<BasePrimeField<G>>(cursor))
+            .collect::<Result<Vec<_>, _>>()?;
+        G::BaseField::from_base_prime_field_elems(&elements).ok_or(SerializationError::InvalidData)
+    };
+    let (x, y) = (coordinate()?, coordinate()?);
+    point_from_coordinates(x, y)
+}
+
+fn read_points<G: AffineRepr>(
+    cursor: &mut Cursor<'_>,
+    count: usize,
+) -> Result<Vec<G>, SerializationError> {
+    (0..count).map(|_| read_point(cursor)).collect()
+}
+
+/// Reads all the points of a section, which must hold exactly `count`.
+fn read_points_section<G: AffineRepr>(
+    sections: &BTreeMap<u32, &[u8]>,
+    id: u32,
+    count: usize,
+) -> Result<Vec<G>, SerializationError> {
+    let mut cursor = section(sections, id)?;
+    let points = read_points(&mut cursor, count)?;
+    if !cursor.0.is_empty() {
+        return Err(SerializationError::InvalidData);
+    }
+    Ok(points)
+}
+
+/// A term of a constraint of an r1cs file: a signal and its coefficient.
+type Term<F> = (usize, F);
+
+/// The constraint system of an r1cs file of circom.
+struct R1cs<F> {
+    num_wires: usize,
+    num_public: usize,
+    /// The `A`, `B` and `C` linear combinations of each constraint.
+    constraints: Vec<[Vec<Term<F>>; 3]>,
+}
+
+/// Reads an r1cs file, returning `None` if its prime is not the modulus of
+/// `F`.
+fn read_r1cs<F: PrimeField>(bytes: &[u8]) -> Result<Option<R1cs<F>>, SerializationError> {
+    let sections = read_sections(bytes, R1CS_MAGIC)?;
+    let mut header = section(&sections, 1)?;
+    if !read_modulus::<F>(&mut header)? {
+        return Ok(None);
+    }
+    let num_wires = header.usize()?;
+    let num_public = header.usize()? + header.usize()?;
+    let _num_private = header.u32()?;
+    let _num_labels = header.u64()?;
+    let num_constraints = header.usize()?;
+
+    let mut cursor = section(&sections, 2)?;
+    let mut constraints = Vec::new();
+    for _ in 0..num_constraints {
+        let mut read_combination = || {
+            let num_terms = cursor.usize()?;
+            (0..num_terms)
+                .map(|_| {
+                    let signal = cursor.usize()?;
+                    if signal >= num_wires {
+                        return Err(SerializationError::InvalidData);
+                    }
+                    Ok((signal, read_standard::<F>(&mut cursor)?))
+                })
+                .collect::<Result<Vec<_>, _>>()
+        };
+        constraints.push([
+            read_combination()?,
+            read_combination()?,
+            read_combination()?,
+        ]);
+    }
+    if !cursor.0.is_empty() || num_public >= num_wires {
+        return Err(SerializationError::InvalidData);
+    }
+    Ok(Some(R1cs {
+        num_wires,
+        num_public,
+        constraints,
+    }))
+}
+
+/// The result of checking a zkey file of snarkjs against the r1cs file of
+/// its circuit and the prepared ptau file of its phase 1 ceremony, as the
+/// `zkey verify` command of snarkjs does, see [`verify_zkey`].
+///
+/// Each field is `true` if the check passed. When the curve or the shape
+/// of the files do not match, the checks of the points cannot run and are
+/// `false`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ZkeyReport {
+    /// The zkey, the r1cs and the ptau are all over the curve.
+    pub curve: bool,
+    /// The numbers of variables and public inputs and the domain size of
+    /// the zkey match the r1cs.
+    pub shape: bool,
+    /// The ceremony of the ptau has enough powers of tau for the domain of
+    /// twice the domain size, from which the `H` query is derived.
+    pub ptau_power: bool,
+    /// The coefficients of `A` and `B` of the zkey match the r1cs.
+    pub coefficients: bool,
+    /// `alpha` and `beta` are those of the ptau.
+    pub alpha_beta: bool,
+    /// `gamma` in `G2` is the generator, as set by snarkjs.
+    pub gamma: bool,
+    /// `delta` in `G1` and in `G2` have the same discrete logarithm.
+    pub delta: bool,
+    /// `delta` in `G1` is the one after the last recorded contribution, or
+    /// the generator if there is no contribution. The proofs of knowledge
+    /// of the contributions are not checked.
+    pub contributions: bool,
+    /// The number of recorded contributions.
+    pub num_contributions: usize,
+    /// The `IC` section matches the circuit and the ptau.
+    pub ic: bool,
+    /// The `A` section matches the circuit and the ptau.
+    pub a: bool,
+    /// The `B` section in `G1` matches the circuit and the ptau.
+    pub b_g1: bool,
+    /// The `B` section in `G2` matches the circuit and the ptau.
+    pub b_g2: bool,
+    /// The `C` section is the one of the circuit and the ptau divided by
+    /// `delta`.
+    pub c: bool,
+    /// The `H` section is the one of the ptau divided by `delta`.
+    pub h: bool,
+}
+
+impl ZkeyReport {
+    fn checks(&self) -> [(&'static str, bool); 14] {
+        [
+            ("curve", self.curve),
+            ("shape", self.shape),
+            ("ptau power", self.ptau_power),
+            ("coefficients", self.coefficients),
+            ("alpha and beta", self.alpha_beta),
+            ("gamma", self.gamma),
+            ("delta", self.delta),
+            ("contributions", self.contributions),
+            ("IC", self.ic),
+            ("A", self.a),
+            ("B in G1", self.b_g1),
+            ("B in G2", self.b_g2),
+            ("C", self.c),
+            ("H", self.h),
+        ]
+    }
+
+    /// Returns whether all the checks passed.
+    pub fn is_valid(&self) -> bool {
+        self.checks().iter().all(|(_, passed)| *passed)
+    }
+}
+
+impl fmt::Display for ZkeyReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (check, passed) in self.checks() {
+            writeln!(f, "{}: {}", check, if passed { "ok" } else { "failed" })?;
+        }
+        write!(f, "{} contributions", self.num_contributions)
+    }
+}
+
+/// Returns whether `e(a, b) = e(c, d)`.
+fn same_ratio<E: Pairing>(a: E::G1, b: E::G2Affine, c: E::G1, d: E::G2Affine) -> bool {
+    E::multi_pairing([a.into_affine(), (-c).into_affine()], [b, d]).is_zero()
+}
+
+/// Returns `sum r_i * x_i` and `sum r_i * y_i` for random `r_i`, which
+/// have the same ratio as all pairs `(x_i, y_i)` with high probability.
+fn random_combinations<G: CurveGroup>(
+    x: &[G::Affine],
+    y: &[G::Affine],
+    rng: &mut impl Rng,
+) -> (G, G) {
+    let r: Vec<G::ScalarField> = (0..x.len()).map(|_| G::ScalarField::rand(rng)).collect();
+    (G::msm_unchecked(x, &r), G::msm_unchecked(y, &r))
+}
+
+/// Verifies that `zkey`, a Groth16 zkey file of snarkjs, is the key of the
+/// circuit of the r1cs file `r1cs` set up with the phase 1 ceremony of the
+/// prepared ptau file `ptau`, followed by the phase 2 contributions it
+/// records, performing the checks of the `zkey verify` command of snarkjs.
+///
+/// Recomputes the initial key from the Lagrange bases of the ptau: `IC`,
+/// `A` and `B` must be equal, and `C` and `H` must be the initial ones
+/// divided by `delta`, which is checked on random linear combinations
+/// drawn from `rng`. The hashes and proofs of knowledge of the
+/// contributions are not checked.
+///
+/// Returns the outcome of each check, or fails with
+/// [`SerializationError::InvalidData`] if a file is malformed.
+pub fn verify_zkey<E: Pairing>(
+    zkey: &[u8],
+    r1cs: &[u8],
+    ptau: &[u8],
+    rng: &mut impl Rng,
+) -> Result<ZkeyReport, SerializationError> {
+    type Fq<E> = <<<E as Pairing>::G1Affine as AffineRepr>::BaseField as Field>::BasePrimeField;
+    let mut report = ZkeyReport::default();
+
+    let zkey = read_sections(zkey, MAGIC)?;
+    if section(&zkey, 1)?.u32()? != GROTH16_PROTOCOL {
+        return Err(SerializationError::InvalidData);
+    }
+    let mut header = section(&zkey, 2)?;
+    let zkey_curve =
+        read_modulus::<Fq<E>>(&mut header)? && read_modulus::<E::ScalarField>(&mut header)?;
+    let ptau = read_sections(ptau, PTAU_MAGIC)?;
+    let mut ptau_header = section(&ptau, 1)?;
+    let ptau_curve = read_modulus::<Fq<E>>(&mut ptau_header)?;
+    let r1cs = read_r1cs::<E::ScalarField>(r1cs)?;
+    let r1cs = match r1cs {
+        Some(r1cs) if zkey_curve && ptau_curve => r1cs,
+        _ => return Ok(report),
+    };
+    report.curve = true;
+
+    let num_variables = header.usize()?;
+    let num_public = header.usize()?;
+    let domain_size = header.usize()?;
+    let alpha_g1: E::G1Affine = read_point(&mut header)?;
+    let beta_g1: E::G1Affine = read_point(&mut header)?;
+    let beta_g2: E::G2Affine = read_point(&mut header)?;
+    let gamma_g2: E::G2Affine = read_point(&mut header)?;
+    let delta_g1: E::G1Affine = read_point(&mut header)?;
+    let delta_g2: E::G2Affine = read_point(&mut header)?;
+    let num_constraints = r1cs.constraints.len();
+    report.shape = num_variables == r1cs.num_wires
+        && num_public == r1cs.num_public
+        && domain_size == (num_constraints + num_public + 1).next_power_of_two();
+    let power = ptau_header.u32()?;
+    if power >= usize::BITS {
+        return Err(SerializationError::InvalidData);
+    }
+    // The H query is read from the bases of the domain of twice the size.
+    report.ptau_power = domain_size.trailing_zeros() < power;
+    if !report.shape || !report.ptau_power {
+        return Ok(report);
+    }
+
+    let (g1, g2) = (E::G1Affine::generator(), E::G2Affine::generator());
+    let first_point =
+        |id| -> Result<E::G1Affine, SerializationError> { read_point(&mut section(&ptau, id)?) };
+    let mut beta_g2_section = section(&ptau, 6)?;
+    report.alpha_beta = first_point(4)? == alpha_g1
+        && first_point(5)? == beta_g1
+        && read_point::<E::G2Affine>(&mut beta_g2_section)? == beta_g2;
+    report.gamma = gamma_g2 == g2;
+    report.delta = !delta_g1.is_zero()
+        && same_ratio::<E>(delta_g1.into_group(), g2, g1.into_group(), delta_g2);
+
+    let mut contributions = section(&zkey, 10)?;
+    contributions.bytes(CS_HASH_SIZE)?;
+    report.num_contributions = contributions.usize()?;
+    let mut last_delta = g1;
+    for _ in 0..report.num_contributions {
+        last_delta = read_point(&mut contributions)?;
+        read_points::<E::G1Affine>(&mut contributions, 2)?;
+        read_point::<E::G2Affine>(&mut contributions)?;
+        contributions.bytes(TRANSCRIPT_SIZE)?;
+        let _contribution_type = contributions.u32()?;
+        let params_size = contributions.usize()?;
+        contributions.bytes(params_size)?;
+    }
+    report.contributions = last_delta == delta_g1;
+
+    // The coefficients of A and B, summed by matrix, constraint and signal,
+    // with the constraints `x_i * 0 = 0` binding the public inputs.
+    let mut expected = BTreeMap::new();
+    for (constraint, combinations) in r1cs.constraints.iter().enumerate() {
+        for (matrix, combination) in [MATRIX_A, MATRIX_B].into_iter().zip(combinations) {
+            for (signal, coefficient) in combination {
+                *expected
+                    .entry((matrix, constraint, *signal))
+                    .or_insert_with(E::ScalarField::zero) += coefficient;
+            }
+        }
+    }
+    for signal in 0..=num_public {
+        *expected
+            .entry((MATRIX_A, num_constraints + signal, signal))
+            .or_insert_with(E::ScalarField::zero) += E::ScalarField::one();
+    }
+    let r_squared = montgomery_factor::<E::ScalarField>().square();
+    let mut coefficients = section(&zkey, 4)?;
+    let mut actual = BTreeMap::new();
+    for _ in 0..coefficients.u32()? {
+        let key = (
+            coefficients.u32()?,
+            coefficients.usize()?,
+            coefficients.usize()?,
+        );
+        let coefficient = read_standard::<E::ScalarField>(&mut coefficients)? / r_squared;
+        *actual.entry(key).or_insert_with(E::ScalarField::zero) += coefficient;
+    }
+    expected.retain(|_, coefficient| !coefficient.is_zero());
+    actual.retain(|_, coefficient| !coefficient.is_zero());
+    report.coefficients = coefficients.0.is_empty() && actual == expected;
+
+    // The Lagrange bases of the ptau hold the bases of all the domains up to
+    // its size, the domain of `domain_size` starting at `domain_size - 1`.
+    let lagrange = |id, size: usize| -> Result<Vec<E::G1Affine>, SerializationError> {
+        let mut cursor = section(&ptau, id)?;
+        cursor.bytes((size - 1) * point_size::<E::G1Affine>())?;
+        read_points(&mut cursor, size)
+    };
+    let tau_g1 = lagrange(12, domain_size)?;
+    let alpha_tau_g1 = lagrange(14, domain_size)?;
+    let beta_tau_g1 = lagrange(15, domain_size)?;
+    let mut tau_g2_section = section(&ptau, 13)?;
+    tau_g2_section.bytes((domain_size - 1) * point_size::<E::G2Affine>())?;
+    let tau_g2: Vec<E::G2Affine> = read_points(&mut tau_g2_section, domain_size)?;
+
+    let mut a = ark_std::vec![E::G1::zero(); num_variables];
+    let mut b_g1 = ark_std::vec![E::G1::zero(); num_variables];
+    let mut b_g2 = ark_std::vec![E::G2::zero(); num_variables];
+    let mut c = ark_std::vec![E::G1::zero(); num_variables];
+    for (constraint, [a_terms, b_terms, c_terms]) in r1cs.constraints.iter().enumerate() {
+        for (signal, coefficient) in a_terms {
+            a[*signal] += tau_g1[constraint] * coefficient;
+            c[*signal] += beta_tau_g1[constraint] * coefficient;
+        }
+        for (signal, coefficient) in b_terms {
+            b_g1[*signal] += tau_g1[constraint] * coefficient;
+            b_g2[*signal] += tau_g2[constraint] * coefficient;
+            c[*signal] += alpha_tau_g1[constraint] * coefficient;
+        }
+        for (signal, coefficient) in c_terms {
+            c[*signal] += tau_g1[constraint] * coefficient;
+        }
+    }
+    for signal in 0..=num_public {
+        a[signal] += tau_g1[num_constraints + signal];
+        c[signal] += beta_tau_g1[num_constraints + signal];
+    }
+    let c = E::G1::normalize_batch(&c);
+    let (ic, c) = c.split_at(num_public + 1);
+
+    report.ic = read_points_section::<E::G1Affine>(&zkey, 3, num_public + 1)? == ic;
+    report.a =
+        read_points_section::<E::G1Affine>(&zkey, 5, num_variables)? == E::G1::normalize_batch(&a);
+    report.b_g1 = read_points_section::<E::G1Affine>(&zkey, 6, num_variables)?
+        == E::G1::normalize_batch(&b_g1);
+    report.b_g2 = read_points_section::<E::G2Affine>(&zkey, 7, num_variables)?
+        == E::G2::normalize_batch(&b_g2);
+
+    let zkey_c = read_points_section::<E::G1Affine>(&zkey, 8, c.len())?;
+    let (c, zkey_c) = random_combinations::<E::G1>(c, &zkey_c, rng);
+    report.c = report.delta && same_ratio::<E>(c, g2, zkey_c, delta_g2);
+
+    // The H section is the Lagrange basis of the domain of twice the size
+    // at the odd indices, the points of the coset of the prover.
+    let h: Vec<_> = lagrange(12, 2 * domain_size)?
+        .into_iter()
+        .skip(1)
+        .step_by(2)
+        .collect();
+    let zkey_h = read_points_section::<E::G1Affine>(&zkey, 9, domain_size)?;
+    let (h, zkey_h) = random_combinations::<E::G1>(&h, &zkey_h, rng);
+    report.h = report.delta && same_ratio::<E>(h, g2, zkey_h, delta_g2);
+
+    Ok(report)
+}
@@ -0,0 +1,94 @@
+use crate::{
+    r1cs_to_qap::R1CSToQAP, Groth16, InputCountMismatch, Proof, VerificationError, VerifyingKey,
+};
+use ark_ec::{
+    pairing::{Pairing, PairingOutput},
+    AffineRepr, CurveGroup,
+};
+use ark_ff::{PrimeField, Zero};
+use ark_relations::r1cs::SynthesisError;
+
+/// Computes the products of pairings checked by [`Groth16::verify_with_hook`].
+///
+/// zkVM guests such as SP1 or RISC Zero accelerate pairings with syscalls
+/// or precompiles: a hook calling them replaces the whole pairing check,
+/// the only expensive step of the verification, while the rest of the path
+/// is plain field and curve arithmetic.
+pub trait PairingHook<E: Pairing> {
+    /// Computes `prod_i e(g1[i], g2[i])`, for slices of the same length.
+    fn multi_pairing(&self, g1: &[E::G1Affine], g2: &[E::G2Affine]) -> PairingOutput<E>;
+}
+
+/// The default hook, computing pairings with the arkworks implementation.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NativePairing;
+
+impl<E: Pairing> PairingHook<E> for NativePairing {
+    #[inline]
+    fn multi_pairing(&self, g1: &[E::G1Affine], g2: &[E::G2Affine]) -> PairingOutput<E> {
+        E::multi_pairing(g1.iter().copied(), g2.iter().copied())
+    }
+}
+
+impl<E: Pairing, F> PairingHook<E> for F
+where
+    F: Fn(&[E::G1Affine], &[E::G2Affine]) -> PairingOutput<E>,
+{
+    #[inline]
+    fn multi_pairing(&self, g1: &[E::G1Affine], g2: &[E::G2Affine]) -> PairingOutput<E> {
+        self(g1, g2)
+    }
+}
+
+impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
+    /// Verify a proof with separate static and variable inputs against an
+    /// unprepared `vk`, like [`Self::verify_with_variables`], with a single
+    /// call to `hook` for the pairings.
+    ///
+    /// The path avoids threads, the GLV endomorphism, MSM windows and
+    /// prepared `G2` elements, none of which benefit a zkVM guest: inputs
+    /// are aggregated by double-and-add, and the four pairings are checked
+    /// as one product `e(A, B) * e(-alpha, beta) * e(-IC, gamma) *
+    /// e(-C, delta) = 1`, with `IC` the aggregated inputs.
+    pub fn verify_with_hook(
+        vk: &VerifyingKey<E>,
+        proof: &Proof<E>,
+        static_inputs: &[E::ScalarField],
+        variable_inputs: &[E::ScalarField],
+        hook: &impl PairingHook<E>,
+    ) -> Result<bool, VerificationError> {
+        let Some((constant, static_bases)) = vk.gamma_abc_g1_static.split_first() else {
+            return Err(SynthesisError::MalformedVerifyingKey.into());
+        };
+        if static_inputs.len() != static_bases.len()
+            || variable_inputs.len() != vk.gamma_abc_g1_variable.len()
+        {
+            return Err(InputCountMismatch {
+                expected_static: static_bases.len(),
+                actual_static: static_inputs.len(),
+                expected_variable: vk.gamma_abc_g1_variable.len(),
+                actual_variable: variable_inputs.len(),
+            }
+            .into());
+        }
+
+        let mut prepared_inputs = constant.into_group();
+        for (base, input) in static_bases
+            .iter()
+            .chain(&vk.gamma_abc_g1_variable)
+            .zip(static_inputs.iter().chain(variable_inputs))
+        {
+            prepared_inputs += base.mul_bigint(input.into_bigint());
+        }
+
+        let neg = |p: E::G1Affine| (-p.into_group()).into_affine();
+        let g1 = [
+            proof.a,
+            neg(vk.alpha_g1),
+            (-prepared_inputs).into_affine(),
+            neg(proof.c),
+        ];
+        let g2 = [proof.b, vk.beta_g2, vk.gamma_g2, vk.delta_g2];
+        Ok(hook.multi_pairing(&g1, &g2).is_zero())
+    }
+}